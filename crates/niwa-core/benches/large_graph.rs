@@ -0,0 +1,127 @@
+//! Benchmarks for large knowledge bases
+//!
+//! Seeds a database with 10k expertises and 30k relations, then measures the
+//! bulk read paths used by `niwa graph`, `niwa list` and `niwa search`.
+//!
+//! Run with: `cargo bench -p niwa-core --bench large_graph`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use niwa_core::{Database, Expertise, Scope, SearchOptions, StorageOperations};
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+const EXPERTISE_COUNT: usize = 10_000;
+const EDGES_PER_EXPERTISE: usize = 3;
+const TAGS: &[&str] = &["rust", "async", "testing", "database", "cli", "llm"];
+const RELATION_TYPES: &[&str] = &["uses", "extends", "requires"];
+
+/// Seed the database with raw inserts inside a single transaction
+///
+/// Relations are inserted directly (bypassing cycle detection) so seeding
+/// stays fast; the benchmarks only exercise read paths.
+async fn seed(db: &Database) {
+    let mut tx = db.pool().begin().await.unwrap();
+
+    for i in 0..EXPERTISE_COUNT {
+        let id = format!("exp-{:05}", i);
+        let mut exp = Expertise::new(&id, "1.0.0");
+        exp.inner.description = Some(format!("Expertise number {} about rust patterns", i));
+        exp.inner.tags = vec![
+            TAGS[i % TAGS.len()].to_string(),
+            TAGS[(i / TAGS.len()) % TAGS.len()].to_string(),
+        ];
+        exp.inner.tags.dedup();
+        exp.metadata.scope = Scope::Personal;
+
+        sqlx::query(
+            r#"
+            INSERT INTO expertises (id, version, scope, created_at, updated_at, data_json, description)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(exp.version())
+        .bind(exp.metadata.scope.as_str())
+        .bind(exp.metadata.created_at)
+        .bind(exp.metadata.updated_at)
+        .bind(exp.to_json().unwrap())
+        .bind(exp.description())
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        for tag in exp.tags() {
+            sqlx::query("INSERT INTO tags (expertise_id, tag) VALUES (?, ?)")
+                .bind(&id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+        }
+    }
+
+    for i in 0..EXPERTISE_COUNT {
+        for (k, offset) in [1, 7, 131].iter().take(EDGES_PER_EXPERTISE).enumerate() {
+            let to = (i + offset) % EXPERTISE_COUNT;
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO relations (from_id, to_id, relation_type, metadata, created_at)
+                VALUES (?, ?, ?, NULL, ?)
+                "#,
+            )
+            .bind(format!("exp-{:05}", i))
+            .bind(format!("exp-{:05}", to))
+            .bind(RELATION_TYPES[k % RELATION_TYPES.len()])
+            .bind(i as i64)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+    }
+
+    tx.commit().await.unwrap();
+}
+
+fn large_graph(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let temp_dir = TempDir::new().unwrap();
+    let db = rt.block_on(async {
+        let db = Database::open(temp_dir.path().join("bench.db"))
+            .await
+            .unwrap();
+        seed(&db).await;
+        db
+    });
+
+    let mut group = c.benchmark_group("large_graph");
+    group.sample_size(10);
+
+    group.bench_function("all_relations", |b| {
+        b.to_async(&rt)
+            .iter(|| async { db.graph().all_relations().await.unwrap() })
+    });
+
+    group.bench_function("build_graph", |b| {
+        b.to_async(&rt)
+            .iter(|| async { db.graph().build_graph().await.unwrap() })
+    });
+
+    group.bench_function("list_all", |b| {
+        b.to_async(&rt)
+            .iter(|| async { db.storage().list_all().await.unwrap() })
+    });
+
+    group.bench_function("search", |b| {
+        b.to_async(&rt).iter(|| async {
+            db.query()
+                .search("rust", SearchOptions::new().limit(50))
+                .await
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, large_graph);
+criterion_main!(benches);
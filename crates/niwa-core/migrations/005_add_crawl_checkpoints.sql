@@ -0,0 +1,13 @@
+-- Add crawl_checkpoints table for resumable crawler runs
+
+CREATE TABLE IF NOT EXISTS crawl_checkpoints (
+    directory TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    file_hash TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    done INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (directory, file_path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_crawl_checkpoints_directory ON crawl_checkpoints(directory, done);
@@ -0,0 +1,5 @@
+-- Add lock state so reviewed expertises are protected from modification
+
+ALTER TABLE expertises ADD COLUMN locked INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE expertises ADD COLUMN locked_at INTEGER;
+ALTER TABLE expertises ADD COLUMN locked_by_reason TEXT;
@@ -0,0 +1,3 @@
+-- Flag rows whose data_json could not be parsed or repaired, so queries skip them
+
+ALTER TABLE expertises ADD COLUMN malformed INTEGER NOT NULL DEFAULT 0;
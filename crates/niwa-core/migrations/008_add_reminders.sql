@@ -0,0 +1,14 @@
+-- Add reminders for time-based expertise reviews
+
+CREATE TABLE IF NOT EXISTS reminders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    expertise_id TEXT NOT NULL,
+    due_at INTEGER NOT NULL,
+    message TEXT,
+    repeat_interval TEXT,
+    completed INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (expertise_id) REFERENCES expertises(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminders_pending ON reminders(completed, due_at);
@@ -0,0 +1,10 @@
+-- Cache computed scores so reports don't recompute them (`niwa expertise score-all`)
+
+CREATE TABLE IF NOT EXISTS expertise_scores (
+    expertise_id TEXT PRIMARY KEY,
+    health_score REAL NOT NULL,
+    pagerank_score REAL NOT NULL,
+    quality_score REAL,
+    computed_at INTEGER NOT NULL,
+    FOREIGN KEY (expertise_id) REFERENCES expertises(id) ON DELETE CASCADE
+);
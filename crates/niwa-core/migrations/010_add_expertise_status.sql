@@ -0,0 +1,12 @@
+-- Add review status (draft, reviewed, deprecated) for filtering and prompt assembly
+
+ALTER TABLE expertises ADD COLUMN status TEXT NOT NULL DEFAULT 'draft';
+
+-- The default applies to expertises created from now on. Existing ones were in
+-- use before statuses existed, so treat them as reviewed rather than hiding
+-- them from --exclude-drafts.
+UPDATE expertises SET status = 'reviewed';
+UPDATE expertises SET data_json = json_set(data_json, '$.status', 'reviewed')
+WHERE json_valid(data_json);
+
+CREATE INDEX IF NOT EXISTS idx_expertises_status ON expertises(status);
@@ -0,0 +1,3 @@
+-- Add transcript fingerprints to processed sessions for near-duplicate detection
+
+ALTER TABLE processed_sessions ADD COLUMN fingerprint INTEGER;
@@ -0,0 +1,618 @@
+//! Portable bundles of expertises and their relations
+//!
+//! A bundle is a self-contained JSON document used to move knowledge between
+//! databases. Every bundle carries a schema `version`; older bundles are
+//! migrated to the current shape before deserialization so that restores keep
+//! working as the format evolves.
+//!
+//! Very large bases can use the JSON Lines form instead: one [`BundleRecord`]
+//! per line, written by [`export_jsonl`] and restored line by line by
+//! [`restore_jsonl`] without holding the whole bundle in memory.
+//!
+//! The graph structure can also be moved alone, as a JSON array of relations
+//! written by [`relations_to_json`] and restored by [`restore_relations`],
+//! e.g. to reapply hand-curated links after regenerating the expertises.
+
+use crate::graph::{BulkRelationInput, BulkRelationReport, Relation};
+use crate::{Database, Error, Expertise, Result, Scope, StorageOperations};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, Write};
+use tracing::{debug, info};
+
+/// Bundle schema version written by this build
+///
+/// History:
+/// - v1: `{ version, expertises }`
+/// - v2: adds `exported_at` and `relations`
+pub const BUNDLE_VERSION: u32 = 2;
+
+/// A set of expertises and the relations among them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    /// Schema version of this bundle
+    pub version: u32,
+    /// Unix timestamp of the export
+    pub exported_at: i64,
+    /// Exported expertises
+    pub expertises: Vec<Expertise>,
+    /// Relations whose endpoints are both in `expertises`
+    pub relations: Vec<Relation>,
+}
+
+impl Bundle {
+    /// Create a bundle at the current schema version
+    pub fn new(expertises: Vec<Expertise>, relations: Vec<Relation>) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().timestamp(),
+            expertises,
+            relations,
+        }
+    }
+
+    /// Serialize to pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a bundle of any supported version, migrating it if needed
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+
+        // Bundles predating the version field are v1
+        let from_version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .map(|v| v as u32)
+            .unwrap_or(1);
+
+        Ok(serde_json::from_value(migrate(value, from_version)?)?)
+    }
+}
+
+/// Upgrade a raw bundle from `from_version` to [`BUNDLE_VERSION`]
+///
+/// Bundles written by a newer NIWA are rejected rather than guessed at.
+pub fn migrate(mut value: Value, from_version: u32) -> Result<Value> {
+    if from_version == 0 || from_version > BUNDLE_VERSION {
+        return Err(Error::UnsupportedBundleVersion {
+            found: from_version,
+            supported: BUNDLE_VERSION,
+        });
+    }
+
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| Error::Other("Bundle must be a JSON object".to_string()))?;
+
+    let mut version = from_version;
+    while version < BUNDLE_VERSION {
+        debug!("Migrating bundle from v{} to v{}", version, version + 1);
+        match version {
+            1 => {
+                object
+                    .entry("exported_at")
+                    .or_insert_with(|| Value::from(0));
+                object
+                    .entry("relations")
+                    .or_insert_with(|| Value::Array(vec![]));
+            }
+            _ => unreachable!("no migration defined for bundle v{}", version),
+        }
+        version += 1;
+    }
+
+    object.insert("version".to_string(), Value::from(BUNDLE_VERSION));
+    Ok(value)
+}
+
+/// One line of a JSON Lines bundle, tagged by `type`
+///
+/// A JSONL bundle starts with a header, followed by every expertise and then
+/// every relation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BundleRecord {
+    /// Schema version and export time
+    Header {
+        version: u32,
+        exported_at: i64,
+    },
+    Expertise(Expertise),
+    Relation(Relation),
+}
+
+impl BundleRecord {
+    /// Parse one line, naming its 1-based line number on failure
+    pub fn from_line(line: &str, line_number: usize) -> Result<Self> {
+        serde_json::from_str(line).map_err(|e| {
+            Error::Other(format!(
+                "Invalid bundle record on line {}: {}",
+                line_number, e
+            ))
+        })
+    }
+}
+
+/// Whether `first_line` starts a JSON Lines bundle rather than a JSON one
+///
+/// JSON bundles are pretty-printed (a lone `{`) or, for old exports, a single
+/// object without a `type` field.
+pub fn is_jsonl(first_line: &str) -> bool {
+    serde_json::from_str::<Value>(first_line)
+        .ok()
+        .is_some_and(|value| value.get("type").is_some())
+}
+
+/// Counts reported by [`restore`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreSummary {
+    /// Expertises that did not exist before
+    pub created: usize,
+    /// Existing expertises overwritten (a version is saved first)
+    pub updated: usize,
+    /// Relations restored
+    pub relations: usize,
+}
+
+/// Export expertises (optionally limited to one scope) with their relations
+pub async fn export(db: &Database, scope: Option<Scope>) -> Result<Bundle> {
+    let storage = db.storage();
+    let expertises = match scope {
+        Some(scope) => storage.list(scope).await?,
+        None => storage.list_all().await?,
+    };
+
+    let ids: Vec<String> = expertises.iter().map(|e| e.id().to_string()).collect();
+    let relations = db.graph().relations_among(&ids).await?;
+
+    info!(
+        "Exported bundle with {} expertises and {} relations",
+        expertises.len(),
+        relations.len()
+    );
+    Ok(Bundle::new(expertises, relations))
+}
+
+/// Export expertises and their relations as JSON Lines to `writer`
+///
+/// Returns the number of expertises and relations written.
+pub async fn export_jsonl<W: Write>(
+    db: &Database,
+    scope: Option<Scope>,
+    mut writer: W,
+) -> Result<(usize, usize)> {
+    let storage = db.storage();
+    let expertises = match scope {
+        Some(scope) => storage.list(scope).await?,
+        None => storage.list_all().await?,
+    };
+    let ids: Vec<String> = expertises.iter().map(|e| e.id().to_string()).collect();
+    let expertise_count = expertises.len();
+
+    write_record(
+        &mut writer,
+        &BundleRecord::Header {
+            version: BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().timestamp(),
+        },
+    )?;
+    for expertise in expertises {
+        write_record(&mut writer, &BundleRecord::Expertise(expertise))?;
+    }
+
+    let relations = db.graph().relations_among(&ids).await?;
+    let relation_count = relations.len();
+    for relation in relations {
+        write_record(&mut writer, &BundleRecord::Relation(relation))?;
+    }
+    writer.flush()?;
+
+    info!(
+        "Exported JSONL bundle with {} expertises and {} relations",
+        expertise_count, relation_count
+    );
+    Ok((expertise_count, relation_count))
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &BundleRecord) -> Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Restore a JSON Lines bundle, one line at a time
+///
+/// Expertises are written as they are read, so memory use does not grow with
+/// the number of expertises. Relation lines are buffered and recreated once
+/// every expertise is in place. Blank lines are ignored.
+pub async fn restore_jsonl<R: BufRead>(db: &Database, reader: R) -> Result<RestoreSummary> {
+    let storage = db.storage();
+    let mut summary = RestoreSummary::default();
+    let mut relations = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match BundleRecord::from_line(&line, index + 1)? {
+            BundleRecord::Header { version, .. } => {
+                if version == 0 || version > BUNDLE_VERSION {
+                    return Err(Error::UnsupportedBundleVersion {
+                        found: version,
+                        supported: BUNDLE_VERSION,
+                    });
+                }
+            }
+            BundleRecord::Expertise(expertise) => {
+                if storage
+                    .exists(expertise.id(), expertise.metadata.scope)
+                    .await?
+                {
+                    storage.update(expertise).await?;
+                    summary.updated += 1;
+                } else {
+                    storage.create(expertise).await?;
+                    summary.created += 1;
+                }
+            }
+            BundleRecord::Relation(relation) => relations.push(relation),
+        }
+    }
+
+    let graph = db.graph();
+    for relation in relations {
+        graph
+            .create_relation(
+                &relation.from_id,
+                &relation.to_id,
+                relation.relation_type,
+                relation.metadata,
+            )
+            .await?;
+        summary.relations += 1;
+    }
+
+    info!(
+        "Restored JSONL bundle: {} created, {} updated, {} relations",
+        summary.created, summary.updated, summary.relations
+    );
+    Ok(summary)
+}
+
+/// Restore a bundle into the database
+///
+/// Existing expertises with the same ID and scope are updated; relations are
+/// recreated after all expertises are in place.
+pub async fn restore(db: &Database, bundle: Bundle) -> Result<RestoreSummary> {
+    let storage = db.storage();
+    let mut summary = RestoreSummary::default();
+
+    for expertise in bundle.expertises {
+        if storage
+            .exists(expertise.id(), expertise.metadata.scope)
+            .await?
+        {
+            storage.update(expertise).await?;
+            summary.updated += 1;
+        } else {
+            storage.create(expertise).await?;
+            summary.created += 1;
+        }
+    }
+
+    let graph = db.graph();
+    for relation in bundle.relations {
+        graph
+            .create_relation(
+                &relation.from_id,
+                &relation.to_id,
+                relation.relation_type,
+                relation.metadata,
+            )
+            .await?;
+        summary.relations += 1;
+    }
+
+    info!(
+        "Restored bundle: {} created, {} updated, {} relations",
+        summary.created, summary.updated, summary.relations
+    );
+    Ok(summary)
+}
+
+/// Serialize relations alone as a pretty-printed JSON array
+pub fn relations_to_json(relations: &[Relation]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(relations)?)
+}
+
+/// Recreate relations from a JSON array written by [`relations_to_json`]
+///
+/// Unlike [`restore`], invalid edges don't fail the restore: edges whose
+/// expertises don't exist, that would close a cycle, that already exist or
+/// that have an unknown type are skipped and counted in the report. With
+/// `dry_run`, nothing is created.
+pub async fn restore_relations(
+    db: &Database,
+    json: &str,
+    dry_run: bool,
+) -> Result<BulkRelationReport> {
+    let inputs: Vec<BulkRelationInput> = serde_json::from_str(json)?;
+    let graph = db.graph();
+    let report = if dry_run {
+        graph.check_bulk_relations(inputs, true).await?
+    } else {
+        graph.bulk_create_relations(inputs, true).await?
+    };
+
+    info!(
+        "Restored {} relations ({} skipped as dangling, {} as cycles)",
+        report.created, report.skipped_missing_id, report.skipped_cycle
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RelationType;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::open(&db_path).await.unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_restore_v1_bundle() {
+        let (db, _temp) = setup_db().await;
+
+        let expertise = Expertise::new("rust-expert", "1.0.0");
+        let v1 = serde_json::json!({
+            "version": 1,
+            "expertises": [expertise],
+        });
+
+        let bundle = Bundle::from_json(&v1.to_string()).unwrap();
+        assert_eq!(bundle.version, BUNDLE_VERSION);
+        assert!(bundle.relations.is_empty());
+
+        let summary = restore(&db, bundle).await.unwrap();
+        assert_eq!(summary.created, 1);
+        assert!(db
+            .storage()
+            .exists("rust-expert", Scope::Personal)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_export_restore_roundtrip() {
+        let (source, _temp1) = setup_db().await;
+        let (target, _temp2) = setup_db().await;
+
+        source
+            .storage()
+            .create(Expertise::new("a", "1.0.0"))
+            .await
+            .unwrap();
+        source
+            .storage()
+            .create(Expertise::new("b", "1.0.0"))
+            .await
+            .unwrap();
+        source
+            .graph()
+            .create_relation("a", "b", RelationType::Uses, None)
+            .await
+            .unwrap();
+
+        let json = export(&source, None).await.unwrap().to_json().unwrap();
+        let summary = restore(&target, Bundle::from_json(&json).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            RestoreSummary {
+                created: 2,
+                updated: 0,
+                relations: 1,
+            }
+        );
+        assert_eq!(
+            target.graph().get_dependencies("a").await.unwrap(),
+            vec!["b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_roundtrip() {
+        let (source, _temp1) = setup_db().await;
+        let (target, _temp2) = setup_db().await;
+
+        const COUNT: usize = 2000;
+        for i in 0..COUNT {
+            source
+                .storage()
+                .create(Expertise::new(format!("exp-{:04}", i), "1.0.0"))
+                .await
+                .unwrap();
+        }
+        for i in 1..COUNT {
+            source
+                .graph()
+                .create_relation(
+                    &format!("exp-{:04}", i),
+                    &format!("exp-{:04}", i - 1),
+                    RelationType::Extends,
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut jsonl = Vec::new();
+        let written = export_jsonl(&source, None, &mut jsonl).await.unwrap();
+        assert_eq!(written, (COUNT, COUNT - 1));
+
+        let text = String::from_utf8(jsonl).unwrap();
+        assert_eq!(text.lines().count(), 1 + COUNT + COUNT - 1);
+        assert!(is_jsonl(text.lines().next().unwrap()));
+
+        let summary = restore_jsonl(&target, text.as_bytes()).await.unwrap();
+        assert_eq!(
+            summary,
+            RestoreSummary {
+                created: COUNT,
+                updated: 0,
+                relations: COUNT - 1,
+            }
+        );
+        assert_eq!(target.storage().list_all().await.unwrap().len(), COUNT);
+        assert_eq!(
+            target.graph().get_dependencies("exp-1999").await.unwrap(),
+            vec!["exp-1998"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_jsonl_errors() {
+        let (db, _temp) = setup_db().await;
+
+        let newer = format!(
+            "{{\"type\":\"header\",\"version\":{},\"exported_at\":0}}\n",
+            BUNDLE_VERSION + 1
+        );
+        assert!(matches!(
+            restore_jsonl(&db, newer.as_bytes()).await,
+            Err(Error::UnsupportedBundleVersion { .. })
+        ));
+
+        let garbage = "\n{\"type\":\"unknown\"}\n";
+        let err = restore_jsonl(&db, garbage.as_bytes()).await.unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[tokio::test]
+    async fn test_relations_roundtrip() {
+        let (source, _temp1) = setup_db().await;
+        let (target, _temp2) = setup_db().await;
+
+        for db in [&source, &target] {
+            for id in ["a", "b", "c"] {
+                db.storage()
+                    .create(Expertise::new(id, "1.0.0"))
+                    .await
+                    .unwrap();
+            }
+        }
+        let graph = source.graph();
+        graph
+            .create_relation("a", "b", RelationType::Uses, Some("curated".to_string()))
+            .await
+            .unwrap();
+        graph
+            .create_relation("b", "c", RelationType::Requires, None)
+            .await
+            .unwrap();
+
+        let json = relations_to_json(&graph.all_relations().await.unwrap()).unwrap();
+        let report = restore_relations(&target, &json, false).await.unwrap();
+        assert_eq!(report.created, 2);
+
+        let edges = |relations: Vec<Relation>| {
+            let mut edges: Vec<_> = relations
+                .into_iter()
+                .map(|r| (r.from_id, r.to_id, r.relation_type.as_str(), r.metadata))
+                .collect();
+            edges.sort();
+            edges
+        };
+        assert_eq!(
+            edges(target.graph().all_relations().await.unwrap()),
+            edges(graph.all_relations().await.unwrap())
+        );
+
+        // Restoring again only finds duplicates
+        let report = restore_relations(&target, &json, false).await.unwrap();
+        assert_eq!(report.created, 0);
+        assert_eq!(report.skipped_duplicate, 2);
+    }
+
+    #[tokio::test]
+    async fn test_restore_relations_skips_invalid_edges() {
+        let (db, _temp) = setup_db().await;
+        for id in ["a", "b", "c"] {
+            db.storage()
+                .create(Expertise::new(id, "1.0.0"))
+                .await
+                .unwrap();
+        }
+        db.graph()
+            .create_relation("b", "a", RelationType::Uses, None)
+            .await
+            .unwrap();
+
+        let json = serde_json::json!([
+            { "from_id": "a", "to_id": "b", "relation_type": "uses" },
+            { "from_id": "b", "to_id": "gone", "relation_type": "uses" },
+            { "from_id": "c", "to_id": "a", "relation_type": "extends", "metadata": null },
+        ])
+        .to_string();
+
+        let check = restore_relations(&db, &json, true).await.unwrap();
+        assert_eq!(db.graph().all_relations().await.unwrap().len(), 1);
+
+        let report = restore_relations(&db, &json, false).await.unwrap();
+        assert_eq!(report, check);
+        assert_eq!(
+            report,
+            BulkRelationReport {
+                created: 1,
+                skipped_cycle: 1,
+                skipped_missing_id: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(db.graph().all_relations().await.unwrap().len(), 2);
+
+        assert!(restore_relations(&db, "not json", false).await.is_err());
+    }
+
+    #[test]
+    fn test_is_jsonl() {
+        let bundle = Bundle::new(vec![], vec![]);
+        let pretty = bundle.to_json().unwrap();
+        assert!(!is_jsonl(pretty.lines().next().unwrap()));
+        assert!(!is_jsonl(&serde_json::to_string(&bundle).unwrap()));
+        assert!(is_jsonl(r#"{"type":"header","version":2,"exported_at":0}"#));
+    }
+
+    #[test]
+    fn test_rejects_newer_bundle() {
+        let json = serde_json::json!({
+            "version": BUNDLE_VERSION + 1,
+            "expertises": [],
+        });
+
+        let result = Bundle::from_json(&json.to_string());
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedBundleVersion { found, .. }) if found == BUNDLE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_unversioned_bundle_is_v1() {
+        let json = serde_json::json!({ "expertises": [] });
+
+        let bundle = Bundle::from_json(&json.to_string()).unwrap();
+        assert_eq!(bundle.version, BUNDLE_VERSION);
+        assert_eq!(bundle.exported_at, 0);
+    }
+}
@@ -14,6 +14,7 @@ use tracing::{debug, info};
 #[derive(Clone)]
 pub struct Database {
     pool: SqlitePool,
+    path: PathBuf,
 }
 
 impl Database {
@@ -43,19 +44,8 @@ impl Database {
             std::fs::create_dir_all(parent)?;
         }
 
-        // Configure SQLite connection
-        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path.display()))?
-            .create_if_missing(true)
-            .foreign_keys(true) // Enable foreign key constraints
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal); // Use WAL mode for better concurrency
-
-        // Create connection pool
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(options)
-            .await?;
-
-        let db = Self { pool };
+        let pool = Self::connect(&path, true).await?;
+        let db = Self { pool, path };
 
         // Run migrations
         db.migrate().await?;
@@ -76,6 +66,25 @@ impl Database {
         Ok(PathBuf::from(home).join(".niwa").join("graph.db"))
     }
 
+    /// Create a connection pool for the file at `path`
+    ///
+    /// `foreign_keys` is off only for pools whose rows may point at
+    /// expertises stored in another file (see [`crate::ScopedDatabase`]).
+    pub(crate) async fn connect(path: &Path, foreign_keys: bool) -> Result<SqlitePool> {
+        // Configure SQLite connection
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path.display()))?
+            .create_if_missing(true)
+            .foreign_keys(foreign_keys)
+            .with_regexp() // REGEXP operator for QueryBuilder::search_regex
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal); // Use WAL mode for better concurrency
+
+        // Create connection pool
+        Ok(SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?)
+    }
+
     /// Run database migrations
     async fn migrate(&self) -> Result<()> {
         info!("Running database migrations");
@@ -111,6 +120,20 @@ impl Database {
         GraphOperations::new(self.pool.clone())
     }
 
+    /// Get the resolved path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the latest applied migration version
+    pub async fn schema_version(&self) -> Result<i64> {
+        let (version,): (Option<i64>,) =
+            sqlx::query_as("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(version.unwrap_or(0))
+    }
+
     /// Get the underlying pool (for advanced usage)
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
@@ -170,6 +193,8 @@ mod tests {
         .unwrap();
 
         assert_eq!(result.0, 1, "expertises table should exist");
+        assert!(db.schema_version().await.unwrap() >= 5);
+        assert_eq!(db.path(), db_path);
 
         db.close().await;
     }
@@ -16,18 +16,38 @@ pub enum Error {
     #[error("Expertise not found: {id} (scope: {scope})")]
     NotFound { id: String, scope: String },
 
+    /// Version not in the history or the current expertise
+    #[error("Version not found: {id} v{version}")]
+    VersionNotFound { id: String, version: String },
+
+    /// Expertise is locked against modification
+    #[error("Expertise is locked: {id} (unlock it or use --force)")]
+    ExpertiseLocked { id: String },
+
+    /// Fragment index past the end of an expertise's content
+    #[error("Fragment index {index} is out of range ({count} fragments, indices start at 0)")]
+    FragmentIndexOutOfRange { index: usize, count: usize },
+
+    /// No pending reminder with this id
+    #[error("Reminder not found: {0}")]
+    ReminderNotFound(i64),
+
     /// Expertise already exists
     #[error("Expertise already exists: {id} (scope: {scope})")]
     AlreadyExists { id: String, scope: String },
 
     /// Invalid scope
-    #[error("Invalid scope: {0}")]
+    #[error("Invalid scope: {0} (expected personal, company or project; aliases: me, org, proj)")]
     InvalidScope(String),
 
     /// Invalid relation type
     #[error("Invalid relation type: {0}")]
     InvalidRelationType(String),
 
+    /// Invalid regular expression
+    #[error("Invalid regular expression: {0}")]
+    InvalidPattern(String),
+
     /// Circular dependency detected
     #[error("Circular dependency detected: {from} -> {to}")]
     CircularDependency { from: String, to: String },
@@ -44,6 +64,10 @@ pub enum Error {
     #[error("Migration error: {0}")]
     Migration(String),
 
+    /// Bundle written by an unknown (usually newer) schema version
+    #[error("Unsupported bundle version {found} (this build supports up to {supported})")]
+    UnsupportedBundleVersion { found: u32, supported: u32 },
+
     /// Generic error
     #[error("{0}")]
     Other(String),
@@ -0,0 +1,98 @@
+//! Content fingerprints for near-duplicate session transcripts
+//!
+//! A 64-bit SimHash over three-word shingles. Transcripts that differ only
+//! slightly (a resumed session, a few extra turns) get fingerprints a few bits
+//! apart, while unrelated ones differ in about half of the bits. The hash is
+//! FNV-1a so fingerprints stored in the database stay comparable across builds.
+
+/// Words per shingle
+const SHINGLE_WORDS: usize = 3;
+
+/// Default maximum Hamming distance for two transcripts to be near-duplicates
+pub const DEFAULT_NEAR_DUPLICATE_DISTANCE: u32 = 3;
+
+/// SimHash fingerprint of `text` (case-insensitive, whitespace-normalized)
+pub fn simhash(text: &str) -> u64 {
+    let words: Vec<String> = text.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return 0;
+    }
+
+    let shingles: Vec<String> = if words.len() < SHINGLE_WORDS {
+        vec![words.join(" ")]
+    } else {
+        words.windows(SHINGLE_WORDS).map(|w| w.join(" ")).collect()
+    };
+
+    let mut weights = [0i64; 64];
+    for shingle in &shingles {
+        let hash = fnv1a(shingle.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if (hash >> bit) & 1 == 1 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |fingerprint, (bit, _)| fingerprint | (1 << bit))
+}
+
+/// Number of differing bits between two fingerprints
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 64-bit FNV-1a hash
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simhash() {
+        let transcript = "How do I handle errors in Rust without unwrap everywhere? \
+            Use the question mark operator and return Result from your functions. \
+            Define an error enum with thiserror for library code and use anyhow in binaries. \
+            What about converting between error types? \
+            Implement From for your error type, or use the from attribute of thiserror, \
+            so the question mark operator converts automatically. \
+            Should I ever panic? \
+            Panic only on bugs and broken invariants, never on recoverable conditions \
+            such as missing files or bad user input.";
+        // The same session resumed for one more turn
+        let resumed = format!("{} Thanks, that helps.", transcript);
+        let unrelated = "Set up a Python virtual environment with venv and pin your \
+            dependencies in requirements.txt. Use pytest fixtures for shared setup and \
+            parametrize to cover many inputs. Run black and ruff in CI so formatting \
+            never blocks review.";
+
+        assert_eq!(simhash(transcript), simhash(&transcript.to_uppercase()));
+        assert!(
+            hamming_distance(simhash(transcript), simhash(&resumed))
+                <= DEFAULT_NEAR_DUPLICATE_DISTANCE
+        );
+        assert!(
+            hamming_distance(simhash(transcript), simhash(unrelated))
+                > DEFAULT_NEAR_DUPLICATE_DISTANCE
+        );
+        assert_eq!(simhash("   "), 0);
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        // Reference values of 64-bit FNV-1a
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -8,7 +8,7 @@ use std::str::FromStr;
 use tracing::debug;
 
 /// Relation type between expertises
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RelationType {
     /// One expertise uses another
@@ -69,10 +69,109 @@ pub struct Relation {
     pub from_id: String,
     pub to_id: String,
     pub relation_type: RelationType,
+    /// Raw metadata column; see [`RelationMetadata`] for the structured form
     pub metadata: Option<String>,
     pub created_at: i64,
 }
 
+impl Relation {
+    /// Structured view of the metadata column
+    pub fn parsed_metadata(&self) -> RelationMetadata {
+        self.metadata
+            .as_deref()
+            .map(RelationMetadata::parse)
+            .unwrap_or_default()
+    }
+
+    /// Why the relation exists, if recorded
+    pub fn reason(&self) -> Option<String> {
+        self.parsed_metadata().reason
+    }
+
+    /// Confidence (0.0-1.0) of an inferred relation, if recorded
+    pub fn confidence(&self) -> Option<f64> {
+        self.parsed_metadata().confidence
+    }
+
+    /// What created the relation (e.g. "auto-link"), if recorded
+    pub fn source(&self) -> Option<String> {
+        self.parsed_metadata().source
+    }
+}
+
+/// Structured metadata stored as JSON in a relation's `metadata` column
+///
+/// Unknown keys are kept in `extra` so round-trips don't lose data.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelationMetadata {
+    /// Why the relation exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Confidence (0.0-1.0) of an inferred relation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// What created the relation (e.g. "auto-link", "extract-relations")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Any other keys
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RelationMetadata {
+    /// Parse a metadata column value
+    ///
+    /// Older relations store a plain-text reason; anything that isn't a JSON
+    /// object is read as such.
+    pub fn parse(raw: &str) -> Self {
+        serde_json::from_str(raw).unwrap_or_else(|_| Self {
+            reason: Some(raw.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Serialize for the metadata column
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Whether no field is set
+    pub fn is_empty(&self) -> bool {
+        self.reason.is_none()
+            && self.confidence.is_none()
+            && self.source.is_none()
+            && self.extra.is_empty()
+    }
+}
+
+/// One relation to create with [`GraphOperations::bulk_create_relations`]
+///
+/// Fields are unvalidated, as read from a spreadsheet row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkRelationInput {
+    pub from_id: String,
+    pub to_id: String,
+    /// Relation type name ("uses", "extends", "conflicts", "requires")
+    pub relation_type: String,
+    #[serde(default)]
+    pub metadata: Option<String>,
+}
+
+/// Outcome of [`GraphOperations::bulk_create_relations`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkRelationReport {
+    /// Relations created (or, for a check, that would be created)
+    pub created: usize,
+    /// Relations that already exist or repeat an earlier input
+    pub skipped_duplicate: usize,
+    /// Relations skipped because they would close a cycle
+    pub skipped_cycle: usize,
+    /// Relations whose source or target expertise does not exist
+    pub skipped_missing_id: usize,
+    /// Inputs rejected for other reasons, e.g. an unknown relation type
+    pub errors: Vec<String>,
+}
+
 /// Graph operations for managing relations
 #[derive(Clone)]
 pub struct GraphOperations {
@@ -87,6 +186,12 @@ impl GraphOperations {
 
     /// Create a relation between two expertises
     ///
+    /// Relations are keyed by `(from_id, to_id, relation_type)`. Creating an
+    /// edge that already exists replaces its metadata, while a different type
+    /// between the same pair is stored as a second edge next to the first.
+    /// Use [`relation_types_between`](Self::relation_types_between) to check
+    /// for existing edges beforehand.
+    ///
     /// # Arguments
     ///
     /// * `from_id` - Source expertise ID
@@ -153,6 +258,150 @@ impl GraphOperations {
         Ok(())
     }
 
+    /// Create many relations at once, all or nothing
+    ///
+    /// Each input is validated in order: its relation type must parse, both
+    /// expertises must exist, and it must not repeat an existing relation or
+    /// an earlier input. Invalid inputs are counted in the report and skipped.
+    /// Cycles are checked against the graph including the inputs accepted so
+    /// far; with `skip_cycles` a cycle-forming input is skipped, otherwise
+    /// the whole import fails with [`Error::CircularDependency`] and nothing
+    /// is created.
+    pub async fn bulk_create_relations(
+        &self,
+        relations: Vec<BulkRelationInput>,
+        skip_cycles: bool,
+    ) -> Result<BulkRelationReport> {
+        let (accepted, report) = self.plan_bulk_relations(relations, skip_cycles).await?;
+
+        let created_at = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+        for (input, relation_type) in &accepted {
+            sqlx::query(
+                r#"
+                INSERT INTO relations (from_id, to_id, relation_type, metadata, created_at)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&input.from_id)
+            .bind(&input.to_id)
+            .bind(relation_type.as_str())
+            .bind(&input.metadata)
+            .bind(created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        debug!(
+            "Bulk-created {} relations ({} duplicate, {} cycle, {} missing, {} errors)",
+            report.created,
+            report.skipped_duplicate,
+            report.skipped_cycle,
+            report.skipped_missing_id,
+            report.errors.len()
+        );
+        Ok(report)
+    }
+
+    /// Report what [`bulk_create_relations`](Self::bulk_create_relations)
+    /// would do, without creating anything
+    pub async fn check_bulk_relations(
+        &self,
+        relations: Vec<BulkRelationInput>,
+        skip_cycles: bool,
+    ) -> Result<BulkRelationReport> {
+        Ok(self.plan_bulk_relations(relations, skip_cycles).await?.1)
+    }
+
+    /// Validate bulk inputs, returning the ones to create and the report
+    async fn plan_bulk_relations(
+        &self,
+        relations: Vec<BulkRelationInput>,
+        skip_cycles: bool,
+    ) -> Result<(Vec<(BulkRelationInput, RelationType)>, BulkRelationReport)> {
+        let ids: HashSet<String> = sqlx::query_as::<_, (String,)>("SELECT id FROM expertises")
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(id,)| id)
+            .collect();
+        let mut edges: HashSet<(String, String, RelationType)> = self
+            .all_relations()
+            .await?
+            .into_iter()
+            .map(|r| (r.from_id, r.to_id, r.relation_type))
+            .collect();
+        let mut graph = self.build_graph().await?;
+
+        let mut report = BulkRelationReport::default();
+        let mut accepted = Vec::new();
+        for (index, input) in relations.into_iter().enumerate() {
+            let relation_type = match input.relation_type.trim().parse::<RelationType>() {
+                Ok(relation_type) => relation_type,
+                Err(e) => {
+                    report.errors.push(format!("Relation {}: {}", index + 1, e));
+                    continue;
+                }
+            };
+            if !ids.contains(&input.from_id) || !ids.contains(&input.to_id) {
+                report.skipped_missing_id += 1;
+                continue;
+            }
+            let edge = (input.from_id.clone(), input.to_id.clone(), relation_type);
+            if edges.contains(&edge) {
+                report.skipped_duplicate += 1;
+                continue;
+            }
+            if reachable_from(&graph, &input.to_id).contains(&input.from_id) {
+                if !skip_cycles {
+                    return Err(Error::CircularDependency {
+                        from: input.from_id,
+                        to: input.to_id,
+                    });
+                }
+                report.skipped_cycle += 1;
+                continue;
+            }
+
+            if relation_type != RelationType::Conflicts {
+                graph
+                    .entry(input.from_id.clone())
+                    .or_default()
+                    .push(input.to_id.clone());
+            }
+            edges.insert(edge);
+            report.created += 1;
+            accepted.push((input, relation_type));
+        }
+
+        Ok((accepted, report))
+    }
+
+    /// Types of the relations from `from_id` to `to_id`, in name order
+    ///
+    /// Empty when the pair is not linked in that direction.
+    pub async fn relation_types_between(
+        &self,
+        from_id: &str,
+        to_id: &str,
+    ) -> Result<Vec<RelationType>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT relation_type
+            FROM relations
+            WHERE from_id = ? AND to_id = ?
+            ORDER BY relation_type
+            "#,
+        )
+        .bind(from_id)
+        .bind(to_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(|(t,)| t.parse()).collect()
+    }
+
     /// Delete a relation
     pub async fn delete_relation(
         &self,
@@ -180,6 +429,40 @@ impl GraphOperations {
         Ok(())
     }
 
+    /// Replace the metadata of an existing relation
+    ///
+    /// Unlike [`create_relation`](Self::create_relation) this keeps
+    /// `created_at` and skips the cycle check. Returns whether the relation
+    /// exists.
+    pub async fn update_metadata(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        relation_type: RelationType,
+        metadata: Option<String>,
+    ) -> Result<bool> {
+        debug!(
+            "Updating relation metadata: {} -[{}]-> {}",
+            from_id, relation_type, to_id
+        );
+
+        let result = sqlx::query(
+            r#"
+            UPDATE relations
+            SET metadata = ?
+            WHERE from_id = ? AND to_id = ? AND relation_type = ?
+            "#,
+        )
+        .bind(&metadata)
+        .bind(from_id)
+        .bind(to_id)
+        .bind(relation_type.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Get outgoing relations from an expertise
     pub async fn get_outgoing(&self, from_id: &str) -> Result<Vec<Relation>> {
         debug!("Getting outgoing relations for: {}", from_id);
@@ -271,6 +554,65 @@ impl GraphOperations {
         Ok(relations)
     }
 
+    /// Get every relation in the graph with a single query
+    ///
+    /// Prefer this over calling [`get_outgoing`](Self::get_outgoing) per
+    /// expertise when the whole graph is needed.
+    pub async fn all_relations(&self) -> Result<Vec<Relation>> {
+        debug!("Getting all relations");
+
+        let rows: Vec<(String, String, String, Option<String>, i64)> = sqlx::query_as(
+            r#"
+            SELECT from_id, to_id, relation_type, metadata, created_at
+            FROM relations
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut relations = Vec::with_capacity(rows.len());
+        for (from_id, to_id, relation_type, metadata, created_at) in rows {
+            relations.push(Relation {
+                from_id,
+                to_id,
+                relation_type: RelationType::from_str(&relation_type)?,
+                metadata,
+                created_at,
+            });
+        }
+
+        Ok(relations)
+    }
+
+    /// Count all relations
+    pub async fn count_relations(&self) -> Result<usize> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM relations")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    /// Get relations whose endpoints are both in `ids`
+    ///
+    /// Useful for exporting a closed subset of the graph (e.g. a bundle).
+    pub async fn relations_among(&self, ids: &[String]) -> Result<Vec<Relation>> {
+        debug!("Getting relations among {} expertises", ids.len());
+
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let id_set: HashSet<&str> = ids.iter().map(String::as_str).collect();
+
+        Ok(self
+            .all_relations()
+            .await?
+            .into_iter()
+            .filter(|r| id_set.contains(r.from_id.as_str()) && id_set.contains(r.to_id.as_str()))
+            .collect())
+    }
+
     /// Get dependencies (expertises that this expertise depends on)
     pub async fn get_dependencies(&self, id: &str) -> Result<Vec<String>> {
         debug!("Getting dependencies for: {}", id);
@@ -317,26 +659,12 @@ impl GraphOperations {
     }
 
     /// Get all nodes reachable from a given node (DFS)
+    ///
+    /// The dependency graph is loaded once and traversed in memory rather
+    /// than issuing one query per visited node.
     async fn get_reachable_nodes(&self, start_id: &str) -> Result<HashSet<String>> {
-        let mut reachable = HashSet::new();
-        let mut to_visit = vec![start_id.to_string()];
-
-        while let Some(current) = to_visit.pop() {
-            if reachable.contains(&current) {
-                continue;
-            }
-
-            reachable.insert(current.clone());
-
-            let deps = self.get_dependencies(&current).await?;
-            for dep in deps {
-                if !reachable.contains(&dep) {
-                    to_visit.push(dep);
-                }
-            }
-        }
-
-        Ok(reachable)
+        let graph = self.build_graph().await?;
+        Ok(reachable_from(&graph, start_id))
     }
 
     /// Build a full dependency graph
@@ -361,6 +689,294 @@ impl GraphOperations {
 
         Ok(graph)
     }
+
+    /// Rank expertises by PageRank over all relations, highest first
+    ///
+    /// A relation `from -> to` passes importance to `to`, so expertises that
+    /// many others use, extend or require rank highest. Only expertises with
+    /// at least one relation are ranked.
+    pub async fn pagerank(&self, iterations: usize, damping: f64) -> Result<Vec<(String, f64)>> {
+        debug!(
+            "Computing PageRank ({} iterations, damping {})",
+            iterations, damping
+        );
+        Ok(pagerank(&self.all_relations().await?, iterations, damping))
+    }
+
+    /// Dependencies and dependents of an expertise, `depth` levels deep
+    ///
+    /// Only `uses`, `requires` and `extends` relations are followed, as in
+    /// [`get_dependencies`](Self::get_dependencies).
+    pub async fn build_link_map(&self, id: &str, depth: usize) -> Result<LinkMap> {
+        debug!("Building link map for {} (depth {})", id, depth);
+        Ok(LinkMap::from_relations(
+            &self.all_relations().await?,
+            id,
+            depth,
+        ))
+    }
+}
+
+/// Relations around one expertise, as a tree in each direction
+///
+/// Children are sorted by ID so rendered maps are deterministic.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkMap {
+    pub id: String,
+    /// Expertises this one uses, requires or extends
+    pub dependencies: Vec<LinkNode>,
+    /// Expertises that use, require or extend this one
+    pub dependents: Vec<LinkNode>,
+}
+
+/// An expertise in a [`LinkMap`], with the relation that led to it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkNode {
+    pub id: String,
+    pub relation_type: RelationType,
+    /// Next level in the same direction (empty at the depth limit)
+    pub children: Vec<LinkNode>,
+}
+
+impl LinkMap {
+    /// Build the map of `id` from a set of relations
+    pub fn from_relations(relations: &[Relation], id: &str, depth: usize) -> Self {
+        let dependency_types = [
+            RelationType::Uses,
+            RelationType::Requires,
+            RelationType::Extends,
+        ];
+        let mut outgoing: HashMap<&str, Vec<(&str, RelationType)>> = HashMap::new();
+        let mut incoming: HashMap<&str, Vec<(&str, RelationType)>> = HashMap::new();
+        for relation in relations
+            .iter()
+            .filter(|r| dependency_types.contains(&r.relation_type))
+        {
+            outgoing
+                .entry(relation.from_id.as_str())
+                .or_default()
+                .push((relation.to_id.as_str(), relation.relation_type));
+            incoming
+                .entry(relation.to_id.as_str())
+                .or_default()
+                .push((relation.from_id.as_str(), relation.relation_type));
+        }
+
+        let mut path = vec![id];
+        Self {
+            id: id.to_string(),
+            dependencies: link_tree(&outgoing, &mut path, depth),
+            dependents: link_tree(&incoming, &mut path, depth),
+        }
+    }
+
+    /// Drop every node (and its subtree) whose ID fails `keep`
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        fn prune(nodes: &mut Vec<LinkNode>, keep: &dyn Fn(&str) -> bool) {
+            nodes.retain(|n| keep(&n.id));
+            for node in nodes {
+                prune(&mut node.children, keep);
+            }
+        }
+        prune(&mut self.dependencies, &keep);
+        prune(&mut self.dependents, &keep);
+    }
+}
+
+/// Children of the last ID in `path`, skipping IDs already on the path
+/// so cycles end instead of repeating
+fn link_tree<'a>(
+    edges: &HashMap<&'a str, Vec<(&'a str, RelationType)>>,
+    path: &mut Vec<&'a str>,
+    depth: usize,
+) -> Vec<LinkNode> {
+    if depth == 0 {
+        return vec![];
+    }
+    let Some(targets) = path.last().and_then(|id| edges.get(id)) else {
+        return vec![];
+    };
+
+    let mut targets = targets.clone();
+    targets.sort_by(|a, b| a.0.cmp(b.0).then_with(|| a.1.as_str().cmp(b.1.as_str())));
+    targets.dedup();
+
+    let mut nodes = Vec::new();
+    for (id, relation_type) in targets {
+        if path.contains(&id) {
+            continue;
+        }
+        path.push(id);
+        let children = link_tree(edges, path, depth - 1);
+        path.pop();
+        nodes.push(LinkNode {
+            id: id.to_string(),
+            relation_type,
+            children,
+        });
+    }
+    nodes
+}
+
+/// Nodes reachable from `start` in a dependency graph, including `start` (DFS)
+fn reachable_from(graph: &HashMap<String, Vec<String>>, start: &str) -> HashSet<String> {
+    let mut reachable = HashSet::new();
+    let mut to_visit = vec![start.to_string()];
+
+    while let Some(current) = to_visit.pop() {
+        if reachable.contains(&current) {
+            continue;
+        }
+
+        if let Some(deps) = graph.get(&current) {
+            for dep in deps {
+                if !reachable.contains(dep) {
+                    to_visit.push(dep.clone());
+                }
+            }
+        }
+
+        reachable.insert(current);
+    }
+
+    reachable
+}
+
+/// PageRank scores (summing to 1) of the expertises in `relations`,
+/// highest first and ties by ID
+///
+/// Expertises without outgoing relations spread their score evenly over
+/// every node so no importance is lost.
+pub fn pagerank(relations: &[Relation], iterations: usize, damping: f64) -> Vec<(String, f64)> {
+    let mut ids: Vec<&str> = relations
+        .iter()
+        .flat_map(|r| [r.from_id.as_str(), r.to_id.as_str()])
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return vec![];
+    }
+    let index: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    // Parallel relations of different types count as one link
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+    for relation in relations {
+        let (from, to) = (
+            index[relation.from_id.as_str()],
+            index[relation.to_id.as_str()],
+        );
+        if from != to && !outgoing[from].contains(&to) {
+            outgoing[from].push(to);
+        }
+    }
+
+    let n = ids.len() as f64;
+    let mut scores = vec![1.0 / n; ids.len()];
+    for _ in 0..iterations {
+        let dangling: f64 = outgoing
+            .iter()
+            .zip(&scores)
+            .filter(|(targets, _)| targets.is_empty())
+            .map(|(_, score)| score)
+            .sum();
+        let base = (1.0 - damping) / n + damping * dangling / n;
+        let mut next = vec![base; ids.len()];
+        for (from, targets) in outgoing.iter().enumerate() {
+            for &to in targets {
+                next[to] += damping * scores[from] / targets.len() as f64;
+            }
+        }
+        scores = next;
+    }
+
+    let mut ranked: Vec<(String, f64)> = ids.into_iter().map(str::to_string).zip(scores).collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+/// A connected group of expertises in the relation graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Community {
+    /// Member IDs with their coreness, most central first
+    pub members: Vec<(String, usize)>,
+}
+
+impl Community {
+    /// Number of expertises in the community
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether the community has no members
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+/// Group expertises into communities (connected components, ignoring
+/// relation direction), largest first
+///
+/// Each member is scored by its coreness: the largest `k` such that it
+/// belongs to a subgraph where every expertise has at least `k` neighbors.
+/// Expertises without relations are not part of any community.
+pub fn detect_communities(relations: &[Relation]) -> Vec<Community> {
+    let mut neighbors: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for relation in relations {
+        let (from, to) = (relation.from_id.as_str(), relation.to_id.as_str());
+        if from == to {
+            continue;
+        }
+        neighbors.entry(from).or_default().insert(to);
+        neighbors.entry(to).or_default().insert(from);
+    }
+
+    // Peel off the lowest-degree node until none are left
+    let mut degrees: HashMap<&str, usize> = neighbors
+        .iter()
+        .map(|(id, adjacent)| (*id, adjacent.len()))
+        .collect();
+    let mut coreness: HashMap<&str, usize> = HashMap::new();
+    let mut k = 0;
+    while let Some((&id, &degree)) = degrees.iter().min_by_key(|(id, degree)| (**degree, **id)) {
+        k = k.max(degree);
+        coreness.insert(id, k);
+        degrees.remove(id);
+        for adjacent in &neighbors[id] {
+            if let Some(d) = degrees.get_mut(adjacent) {
+                *d -= 1;
+            }
+        }
+    }
+
+    let mut ids: Vec<&str> = neighbors.keys().copied().collect();
+    ids.sort_unstable();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut communities = Vec::new();
+    for start in ids {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut members = Vec::new();
+        let mut to_visit = vec![start];
+        while let Some(id) = to_visit.pop() {
+            members.push((id.to_string(), coreness[id]));
+            for adjacent in &neighbors[id] {
+                if seen.insert(adjacent) {
+                    to_visit.push(adjacent);
+                }
+            }
+        }
+        members.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        communities.push(Community { members });
+    }
+
+    communities.sort_by(|a, b| {
+        b.len()
+            .cmp(&a.len())
+            .then_with(|| a.members[0].0.cmp(&b.members[0].0))
+    });
+    communities
 }
 
 #[cfg(test)]
@@ -382,6 +998,28 @@ mod tests {
         db.storage().create(exp).await.unwrap();
     }
 
+    #[test]
+    fn test_relation_metadata_roundtrip() {
+        let metadata = RelationMetadata {
+            reason: Some("shares async patterns".to_string()),
+            confidence: Some(0.85),
+            source: Some("auto-link".to_string()),
+            extra: serde_json::Map::from_iter([("reviewed".to_string(), true.into())]),
+        };
+
+        let json = metadata.to_json();
+        assert_eq!(RelationMetadata::parse(&json), metadata);
+        assert!(json.contains(r#""reviewed":true"#));
+
+        // Legacy plain-text metadata is a reason
+        let legacy = RelationMetadata::parse("both cover tokio");
+        assert_eq!(legacy.reason.as_deref(), Some("both cover tokio"));
+        assert_eq!(legacy.confidence, None);
+
+        assert_eq!(RelationMetadata::default().to_json(), "{}");
+        assert!(RelationMetadata::parse("{}").is_empty());
+    }
+
     #[tokio::test]
     async fn test_create_relation() {
         let (db, _temp) = setup_db().await;
@@ -400,6 +1038,148 @@ mod tests {
         assert_eq!(outgoing[0].relation_type, RelationType::Uses);
     }
 
+    #[tokio::test]
+    async fn test_relation_types_between() {
+        let (db, _temp) = setup_db().await;
+
+        create_test_expertise(&db, "exp-1").await;
+        create_test_expertise(&db, "exp-2").await;
+
+        let graph = db.graph();
+        graph
+            .create_relation("exp-1", "exp-2", RelationType::Uses, None)
+            .await
+            .unwrap();
+        graph
+            .create_relation(
+                "exp-1",
+                "exp-2",
+                RelationType::Uses,
+                Some(r#"{"confidence":0.9}"#.to_string()),
+            )
+            .await
+            .unwrap();
+
+        // The same type again replaces the edge
+        let outgoing = graph.get_outgoing("exp-1").await.unwrap();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].confidence(), Some(0.9));
+
+        // A different type between the same pair coexists with it
+        graph
+            .create_relation("exp-1", "exp-2", RelationType::Requires, None)
+            .await
+            .unwrap();
+        assert_eq!(graph.get_outgoing("exp-1").await.unwrap().len(), 2);
+        assert_eq!(
+            graph
+                .relation_types_between("exp-1", "exp-2")
+                .await
+                .unwrap(),
+            vec![RelationType::Requires, RelationType::Uses]
+        );
+        assert!(graph
+            .relation_types_between("exp-2", "exp-1")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_relations() {
+        let (db, _temp) = setup_db().await;
+
+        for id in ["exp-1", "exp-2", "exp-3"] {
+            create_test_expertise(&db, id).await;
+        }
+        let graph = db.graph();
+        graph
+            .create_relation("exp-1", "exp-2", RelationType::Uses, None)
+            .await
+            .unwrap();
+
+        let input = |from: &str, to: &str, relation_type: &str| BulkRelationInput {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type: relation_type.to_string(),
+            metadata: None,
+        };
+        let inputs = vec![
+            input("exp-2", "exp-3", "requires"),
+            input("exp-1", "exp-2", "uses"),     // exists
+            input("exp-2", "exp-3", "requires"), // repeats the first input
+            input("exp-3", "exp-1", "extends"),  // closes exp-1 -> exp-2 -> exp-3
+            input("exp-1", "missing", "uses"),
+            input("exp-1", "exp-3", "depends"),
+            input("exp-1", "exp-3", "conflicts"),
+        ];
+
+        // Without skip_cycles, the cycle fails the whole import
+        assert!(matches!(
+            graph.bulk_create_relations(inputs.clone(), false).await,
+            Err(Error::CircularDependency { .. })
+        ));
+        assert_eq!(graph.all_relations().await.unwrap().len(), 1);
+
+        let expected = BulkRelationReport {
+            created: 2,
+            skipped_duplicate: 2,
+            skipped_cycle: 1,
+            skipped_missing_id: 1,
+            errors: vec!["Relation 6: Invalid relation type: depends".to_string()],
+        };
+        let checked = graph
+            .check_bulk_relations(inputs.clone(), true)
+            .await
+            .unwrap();
+        assert_eq!(checked, expected);
+        assert_eq!(graph.all_relations().await.unwrap().len(), 1);
+
+        let report = graph.bulk_create_relations(inputs, true).await.unwrap();
+        assert_eq!(report, expected);
+        assert_eq!(
+            graph
+                .relation_types_between("exp-1", "exp-3")
+                .await
+                .unwrap(),
+            vec![RelationType::Conflicts]
+        );
+        assert_eq!(
+            graph.get_dependencies("exp-2").await.unwrap(),
+            vec!["exp-3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_metadata() {
+        let (db, _temp) = setup_db().await;
+
+        create_test_expertise(&db, "exp-1").await;
+        create_test_expertise(&db, "exp-2").await;
+
+        let graph = db.graph();
+        graph
+            .create_relation("exp-1", "exp-2", RelationType::Uses, None)
+            .await
+            .unwrap();
+        let created_at = graph.get_outgoing("exp-1").await.unwrap()[0].created_at;
+
+        let metadata = r#"{"confidence":0.5}"#.to_string();
+        assert!(graph
+            .update_metadata("exp-1", "exp-2", RelationType::Uses, Some(metadata))
+            .await
+            .unwrap());
+
+        let outgoing = graph.get_outgoing("exp-1").await.unwrap();
+        assert_eq!(outgoing[0].confidence(), Some(0.5));
+        assert_eq!(outgoing[0].created_at, created_at);
+
+        assert!(!graph
+            .update_metadata("exp-2", "exp-1", RelationType::Uses, None)
+            .await
+            .unwrap());
+    }
+
     #[tokio::test]
     async fn test_circular_dependency_detection() {
         let (db, _temp) = setup_db().await;
@@ -493,4 +1273,161 @@ mod tests {
         let outgoing = db.graph().get_outgoing("exp-1").await.unwrap();
         assert_eq!(outgoing.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_all_relations() {
+        let (db, _temp) = setup_db().await;
+
+        create_test_expertise(&db, "exp-1").await;
+        create_test_expertise(&db, "exp-2").await;
+        create_test_expertise(&db, "exp-3").await;
+
+        db.graph()
+            .create_relation("exp-1", "exp-2", RelationType::Uses, None)
+            .await
+            .unwrap();
+        db.graph()
+            .create_relation("exp-2", "exp-3", RelationType::Extends, None)
+            .await
+            .unwrap();
+
+        let relations = db.graph().all_relations().await.unwrap();
+        assert_eq!(relations.len(), 2);
+        assert!(relations.iter().any(|r| r.from_id == "exp-1"
+            && r.to_id == "exp-2"
+            && r.relation_type == RelationType::Uses));
+        assert!(relations.iter().any(|r| r.from_id == "exp-2"
+            && r.to_id == "exp-3"
+            && r.relation_type == RelationType::Extends));
+    }
+
+    #[tokio::test]
+    async fn test_relations_among() {
+        let (db, _temp) = setup_db().await;
+
+        create_test_expertise(&db, "exp-1").await;
+        create_test_expertise(&db, "exp-2").await;
+        create_test_expertise(&db, "exp-3").await;
+
+        db.graph()
+            .create_relation("exp-1", "exp-2", RelationType::Uses, None)
+            .await
+            .unwrap();
+        db.graph()
+            .create_relation("exp-2", "exp-3", RelationType::Uses, None)
+            .await
+            .unwrap();
+
+        let ids = vec!["exp-1".to_string(), "exp-2".to_string()];
+        let relations = db.graph().relations_among(&ids).await.unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].from_id, "exp-1");
+        assert_eq!(relations[0].to_id, "exp-2");
+
+        let relations = db.graph().relations_among(&[]).await.unwrap();
+        assert!(relations.is_empty());
+    }
+
+    #[test]
+    fn test_detect_communities() {
+        let relation = |from: &str, to: &str| Relation {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type: RelationType::Uses,
+            metadata: None,
+            created_at: 0,
+        };
+        // Triangle a-b-c with a tail c-d, plus a separate pair x-y
+        let relations = vec![
+            relation("a", "b"),
+            relation("b", "c"),
+            relation("c", "a"),
+            relation("d", "c"),
+            relation("x", "y"),
+            relation("y", "x"),
+            relation("z", "z"),
+        ];
+
+        let communities = detect_communities(&relations);
+        assert_eq!(communities.len(), 2);
+        assert_eq!(
+            communities[0].members,
+            vec![
+                ("a".to_string(), 2),
+                ("b".to_string(), 2),
+                ("c".to_string(), 2),
+                ("d".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            communities[1].members,
+            vec![("x".to_string(), 1), ("y".to_string(), 1)]
+        );
+        assert!(detect_communities(&[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_build_link_map() {
+        let (db, _temp) = setup_db().await;
+        for id in ["api", "web", "http", "tls", "cli", "legacy"] {
+            create_test_expertise(&db, id).await;
+        }
+        let graph = db.graph();
+        for (from, to, relation_type) in [
+            ("web", "http", RelationType::Uses),
+            ("web", "api", RelationType::Extends),
+            ("http", "tls", RelationType::Requires),
+            ("cli", "web", RelationType::Uses),
+            ("legacy", "web", RelationType::Conflicts),
+        ] {
+            graph
+                .create_relation(from, to, relation_type, None)
+                .await
+                .unwrap();
+        }
+
+        let map = graph.build_link_map("web", 2).await.unwrap();
+        let ids = |nodes: &[LinkNode]| nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&map.dependencies), vec!["api", "http"]);
+        assert_eq!(map.dependencies[0].relation_type, RelationType::Extends);
+        assert_eq!(ids(&map.dependencies[1].children), vec!["tls"]);
+        // Conflicts are not dependencies
+        assert_eq!(ids(&map.dependents), vec!["cli"]);
+
+        let map = graph.build_link_map("web", 1).await.unwrap();
+        assert!(map.dependencies[1].children.is_empty());
+
+        let mut map = graph.build_link_map("web", 2).await.unwrap();
+        map.retain(|id| id != "http");
+        assert_eq!(ids(&map.dependencies), vec!["api"]);
+    }
+
+    #[tokio::test]
+    async fn test_pagerank() {
+        let (db, _temp) = setup_db().await;
+        for id in ["core", "a", "b", "c"] {
+            create_test_expertise(&db, id).await;
+        }
+        let graph = db.graph();
+        assert!(graph.pagerank(20, 0.85).await.unwrap().is_empty());
+
+        // Everything points at core; a also points at b
+        for (from, to) in [("a", "core"), ("b", "core"), ("c", "core"), ("a", "b")] {
+            graph
+                .create_relation(from, to, RelationType::Uses, None)
+                .await
+                .unwrap();
+        }
+        graph
+            .create_relation("a", "core", RelationType::Requires, None)
+            .await
+            .unwrap();
+
+        let ranked = graph.pagerank(50, 0.85).await.unwrap();
+        let ids: Vec<&str> = ranked.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["core", "b", "a", "c"]);
+        assert_eq!(ranked[2].1, ranked[3].1);
+        let total: f64 = ranked.iter().map(|(_, score)| score).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,119 @@
+//! Structural health of an expertise
+//!
+//! Health measures how usable an expertise is as stored, without asking an
+//! LLM: whether it is described, tagged, substantial, recently maintained and
+//! connected to the rest of the graph. Each of the five checks contributes up
+//! to 0.2 to a score between 0.0 and 1.0.
+
+use crate::Expertise;
+
+/// Fragments needed for full marks on substance
+const FULL_FRAGMENT_COUNT: usize = 3;
+
+/// Days since the last update with full marks on freshness
+const FRESH_DAYS: i64 = 90;
+
+/// Days since the last update after which freshness scores nothing
+const STALE_DAYS: i64 = 365;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Health inputs for one expertise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpertiseHealth {
+    pub has_description: bool,
+    pub tag_count: usize,
+    pub fragment_count: usize,
+    /// Whole days since the last update
+    pub age_days: i64,
+    /// Relations in either direction
+    pub relation_count: usize,
+}
+
+impl ExpertiseHealth {
+    /// Assess `expertise`, which has `relation_count` relations, as of `now`
+    pub fn assess(expertise: &Expertise, relation_count: usize, now: i64) -> Self {
+        Self {
+            has_description: !expertise.description().trim().is_empty(),
+            tag_count: expertise.tags().len(),
+            fragment_count: expertise.inner.content.len(),
+            age_days: (now - expertise.metadata.updated_at).max(0) / SECONDS_PER_DAY,
+            relation_count,
+        }
+    }
+
+    /// Score from 0.0 (unusable) to 1.0 (healthy)
+    pub fn score(&self) -> f64 {
+        let description = if self.has_description { 1.0 } else { 0.0 };
+        let tags = if self.tag_count > 0 { 1.0 } else { 0.0 };
+        let substance =
+            self.fragment_count.min(FULL_FRAGMENT_COUNT) as f64 / FULL_FRAGMENT_COUNT as f64;
+        let freshness = if self.age_days <= FRESH_DAYS {
+            1.0
+        } else {
+            (STALE_DAYS - self.age_days).max(0) as f64 / (STALE_DAYS - FRESH_DAYS) as f64
+        };
+        let connected = if self.relation_count > 0 { 1.0 } else { 0.0 };
+
+        (description + tags + substance + freshness + connected) / 5.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnowledgeFragment, WeightedFragment};
+
+    #[test]
+    fn test_assess() {
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.description = Some("Idiomatic Rust".to_string());
+        expertise.inner.tags = vec!["rust".to_string()];
+        expertise.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Prefer ? over unwrap".to_string(),
+        ))];
+        let now = expertise.metadata.updated_at + 10 * SECONDS_PER_DAY;
+
+        let health = ExpertiseHealth::assess(&expertise, 2, now);
+        assert_eq!(
+            health,
+            ExpertiseHealth {
+                has_description: true,
+                tag_count: 1,
+                fragment_count: 1,
+                age_days: 10,
+                relation_count: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_score() {
+        let healthy = ExpertiseHealth {
+            has_description: true,
+            tag_count: 3,
+            fragment_count: 5,
+            age_days: 30,
+            relation_count: 1,
+        };
+        assert_eq!(healthy.score(), 1.0);
+
+        let empty = ExpertiseHealth {
+            has_description: false,
+            tag_count: 0,
+            fragment_count: 0,
+            age_days: 400,
+            relation_count: 0,
+        };
+        assert_eq!(empty.score(), 0.0);
+
+        // Half-way between fresh and stale, one of three fragments
+        let partial = ExpertiseHealth {
+            fragment_count: 1,
+            age_days: (FRESH_DAYS + STALE_DAYS) / 2,
+            ..healthy
+        };
+        let expected = (1.0 + 1.0 + 1.0 / 3.0 + 0.5 + 1.0) / 5.0;
+        assert!((partial.score() - expected).abs() < 1e-9);
+    }
+}
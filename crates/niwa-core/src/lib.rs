@@ -7,6 +7,7 @@
 //! - SQLite-based Expertise storage with versioning
 //! - Full-text search with FTS5
 //! - Dependency graph (Relations)
+//! - Similarity between expertises ([`SimilarityConfig`])
 //! - Type-safe operations with llm-toolkit Expertise types
 //!
 //! ## Example
@@ -33,20 +34,38 @@
 //! }
 //! ```
 
+pub mod bundle;
 pub mod db;
 pub mod error;
+pub mod fingerprint;
 pub mod graph;
+pub mod health;
 pub mod query;
+pub mod scoped;
+pub mod similarity;
 pub mod storage;
 pub mod types;
 
 // Re-exports for convenience
+pub use bundle::Bundle;
 pub use db::Database;
 pub use error::{Error, Result};
-pub use graph::{GraphOperations, RelationType};
-pub use query::{QueryBuilder, SearchOptions};
-pub use storage::{Storage, StorageOperations};
-pub use types::{Expertise, ExpertiseMetadata, KnowledgeFragment, Scope, WeightedFragment};
+pub use graph::{
+    detect_communities, pagerank, BulkRelationInput, BulkRelationReport, Community,
+    GraphOperations, LinkMap, LinkNode, RelationMetadata, RelationType,
+};
+pub use health::ExpertiseHealth;
+pub use query::{CrossReference, QueryBuilder, SearchDiagnostics, SearchOptions, WordCountStats};
+pub use scoped::{ScopedDatabase, ScopedStorage};
+pub use similarity::{SimilarityConfig, SimilarityMethod};
+pub use storage::{
+    ConflictPolicy, ExpertiseScore, ExportBundle, ProcessedSession, Reminder, RepeatInterval,
+    ResolveResult, Storage, StorageOperations, VersionComparison,
+};
+pub use types::{
+    Expertise, ExpertiseDiff, ExpertiseMetadata, ExpertiseStatus, KnowledgeFragment, Priority,
+    Scope, WeightedFragment,
+};
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,6 +1,7 @@
 //! Query and search operations
 
-use crate::{Expertise, Result, Scope};
+use crate::{Error, Expertise, ExpertiseStatus, Result, Scope};
+use serde::Serialize;
 use sqlx::SqlitePool;
 use tracing::debug;
 
@@ -15,6 +16,18 @@ pub struct SearchOptions {
     pub scope: Option<Scope>,
     /// Filter by tags (AND condition)
     pub tags: Vec<String>,
+    /// Only include expertises updated at or after this Unix timestamp
+    pub modified_since: Option<i64>,
+    /// Only include expertises updated at or before this Unix timestamp
+    pub modified_before: Option<i64>,
+    /// Only include expertises created at or after this Unix timestamp
+    pub created_since: Option<i64>,
+    /// Only include expertises created at or before this Unix timestamp
+    pub created_before: Option<i64>,
+    /// Filter by review status
+    pub status: Option<ExpertiseStatus>,
+    /// Leave out expertises with these review statuses
+    pub excluded_statuses: Vec<ExpertiseStatus>,
 }
 
 impl SearchOptions {
@@ -52,6 +65,138 @@ impl SearchOptions {
         self.tags = tags;
         self
     }
+
+    /// Set lower bound on `updated_at`
+    pub fn modified_since(mut self, timestamp: i64) -> Self {
+        self.modified_since = Some(timestamp);
+        self
+    }
+
+    /// Set upper bound on `updated_at`
+    pub fn modified_before(mut self, timestamp: i64) -> Self {
+        self.modified_before = Some(timestamp);
+        self
+    }
+
+    /// Set lower bound on `created_at`
+    pub fn created_since(mut self, timestamp: i64) -> Self {
+        self.created_since = Some(timestamp);
+        self
+    }
+
+    /// Set upper bound on `created_at`
+    pub fn created_before(mut self, timestamp: i64) -> Self {
+        self.created_before = Some(timestamp);
+        self
+    }
+
+    /// Set status filter
+    pub fn status(mut self, status: ExpertiseStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Leave out expertises with `status` (repeatable)
+    pub fn exclude_status(mut self, status: ExpertiseStatus) -> Self {
+        self.excluded_statuses.push(status);
+        self
+    }
+
+    /// Timestamp filters as (column condition, value) pairs
+    fn time_filters(&self) -> Vec<(&'static str, i64)> {
+        [
+            (" AND e.updated_at >= ?", self.modified_since),
+            (" AND e.updated_at <= ?", self.modified_before),
+            (" AND e.created_at >= ?", self.created_since),
+            (" AND e.created_at <= ?", self.created_before),
+        ]
+        .into_iter()
+        .filter_map(|(clause, value)| value.map(|v| (clause, v)))
+        .collect()
+    }
+}
+
+/// Diagnostic report explaining why a search returned (or did not return) results
+#[derive(Debug, Clone, Default)]
+pub struct SearchDiagnostics {
+    /// The original query
+    pub query: String,
+    /// Number of matches for the full query in the FTS5 index
+    pub fts_matches: usize,
+    /// FTS5 error for the full query (e.g. syntax errors), if any
+    pub fts_error: Option<String>,
+    /// FTS5 match count for each individual term
+    pub term_matches: Vec<(String, usize)>,
+    /// Expertises whose raw `description` column contains every term
+    pub description_matches: usize,
+    /// Expertises whose fragment text contains every term
+    pub fragment_matches: usize,
+}
+
+impl SearchDiagnostics {
+    /// True when raw descriptions match but the FTS5 index does not
+    pub fn index_out_of_sync(&self) -> bool {
+        self.fts_matches == 0 && self.fts_error.is_none() && self.description_matches > 0
+    }
+}
+
+/// An expertise that mentions another expertise's ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossReference {
+    /// ID of the referencing expertise
+    pub id: String,
+    /// Scope of the referencing expertise
+    pub scope: Scope,
+    /// The ID is one of its tags
+    pub in_tags: bool,
+    /// The ID appears in its description
+    pub in_description: bool,
+    /// Number of fragments mentioning the ID
+    pub fragment_mentions: usize,
+}
+
+impl CrossReference {
+    /// Heuristic confidence (0.0-1.0) that the reference is a real dependency
+    ///
+    /// Tags are deliberate references and score highest; fragment mentions
+    /// score higher the more often they repeat.
+    pub fn confidence(&self) -> f64 {
+        let mut confidence: f64 = 0.0;
+        if self.in_tags {
+            confidence = confidence.max(0.9);
+        }
+        if self.in_description {
+            confidence = confidence.max(0.8);
+        }
+        if self.fragment_mentions > 0 {
+            let repeated = 0.1 * (self.fragment_mentions - 1) as f64;
+            confidence = confidence.max((0.5 + repeated).min(0.8));
+        }
+        confidence
+    }
+}
+
+/// Corpus size statistics from [`QueryBuilder::word_count_stats`]
+///
+/// Words are approximated by counting spaces, and only text fragments are
+/// counted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WordCountStats {
+    /// Expertises matching the filters
+    pub expertise_count: usize,
+    /// Text fragments in those expertises
+    pub fragment_count: usize,
+    pub total_words: usize,
+    pub avg_words_per_expertise: f32,
+    pub avg_words_per_fragment: f32,
+    /// Median fragment length in characters
+    pub median_fragment_length: usize,
+    /// Expertise with the most words (empty if none matched)
+    pub longest_expertise_id: String,
+    /// Expertise with the fewest words (empty if none matched)
+    pub shortest_expertise_id: String,
+    /// Length in characters of every fragment, shortest first
+    pub fragment_lengths: Vec<usize>,
 }
 
 /// Query builder for searching expertises
@@ -96,24 +241,32 @@ impl QueryBuilder {
             SELECT e.data_json
             FROM expertises e
             WHERE e.id IN (SELECT id FROM expertises_fts WHERE expertises_fts MATCH ?)
+              AND e.malformed = 0
             "#,
         );
 
-        let mut params: Vec<Box<dyn sqlx::Encode<'_, sqlx::Sqlite> + Send>> = vec![];
-        params.push(Box::new(query.to_string()));
-
         // Add scope filter
-        if let Some(scope) = options.scope {
+        if options.scope.is_some() {
             sql.push_str(" AND e.scope = ?");
-            params.push(Box::new(scope.as_str().to_string()));
+        }
+
+        // Add status filters
+        if options.status.is_some() {
+            sql.push_str(" AND e.status = ?");
+        }
+        for _ in &options.excluded_statuses {
+            sql.push_str(" AND e.status != ?");
         }
 
         // Add tag filters
-        if !options.tags.is_empty() {
-            for tag in &options.tags {
-                sql.push_str(" AND e.id IN (SELECT expertise_id FROM tags WHERE tag = ?)");
-                params.push(Box::new(tag.clone()));
-            }
+        for _ in &options.tags {
+            sql.push_str(" AND e.id IN (SELECT expertise_id FROM tags WHERE tag = ?)");
+        }
+
+        // Add time filters
+        let time_filters = options.time_filters();
+        for (clause, _) in &time_filters {
+            sql.push_str(clause);
         }
 
         sql.push_str(" ORDER BY e.updated_at DESC");
@@ -126,7 +279,6 @@ impl QueryBuilder {
             sql.push_str(" OFFSET ?");
         }
 
-        // Execute query (note: this is simplified, real implementation would use proper binding)
         let mut query_builder = sqlx::query_as::<_, (String,)>(&sql);
 
         // Bind parameters
@@ -134,9 +286,18 @@ impl QueryBuilder {
         if let Some(scope) = &options.scope {
             query_builder = query_builder.bind(scope.as_str());
         }
+        if let Some(status) = &options.status {
+            query_builder = query_builder.bind(status.as_str());
+        }
+        for status in &options.excluded_statuses {
+            query_builder = query_builder.bind(status.as_str());
+        }
         for tag in &options.tags {
             query_builder = query_builder.bind(tag);
         }
+        for (_, value) in &time_filters {
+            query_builder = query_builder.bind(*value);
+        }
         if let Some(limit) = options.limit {
             query_builder = query_builder.bind(limit as i64);
         }
@@ -155,6 +316,268 @@ impl QueryBuilder {
         Ok(expertises)
     }
 
+    /// Regular-expression search over ids, descriptions, tags and text fragments
+    ///
+    /// Uses the `REGEXP` operator registered on every connection, so the
+    /// pattern follows the syntax of the `regex` crate. Unlike [`search`],
+    /// no index is used: every expertise in `scope` is scanned.
+    ///
+    /// Fails with [`Error::InvalidPattern`] before querying if the pattern
+    /// doesn't compile.
+    ///
+    /// [`search`]: Self::search
+    pub async fn search_regex(
+        &self,
+        pattern: &str,
+        scope: Option<Scope>,
+    ) -> Result<Vec<Expertise>> {
+        debug!("Regex search for: {}", pattern);
+
+        regex::Regex::new(pattern).map_err(|e| Error::InvalidPattern(e.to_string()))?;
+
+        let mut sql = String::from(
+            r#"
+            SELECT e.data_json
+            FROM expertises e
+            WHERE e.malformed = 0 AND (
+                e.id REGEXP ?
+                OR e.description REGEXP ?
+                OR EXISTS (SELECT 1 FROM tags t WHERE t.expertise_id = e.id AND t.tag REGEXP ?)
+                OR EXISTS (
+                    SELECT 1 FROM json_each(e.data_json, '$.content') f
+                    WHERE json_extract(f.value, '$.fragment.type') = 'Text'
+                      AND json_extract(f.value, '$.fragment.content') REGEXP ?
+                )
+            )
+            "#,
+        );
+        if scope.is_some() {
+            sql.push_str(" AND e.scope = ?");
+        }
+        sql.push_str(" ORDER BY e.updated_at DESC");
+
+        let mut query_builder = sqlx::query_as::<_, (String,)>(&sql)
+            .bind(pattern)
+            .bind(pattern)
+            .bind(pattern)
+            .bind(pattern);
+        if let Some(scope) = scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+
+        let rows = query_builder.fetch_all(&self.pool).await?;
+
+        let mut expertises = Vec::with_capacity(rows.len());
+        for (data_json,) in rows {
+            expertises.push(Expertise::from_json(&data_json)?);
+        }
+
+        debug!("Found {} results", expertises.len());
+        Ok(expertises)
+    }
+
+    /// List expertises matching the given filters (no full-text query)
+    ///
+    /// Honors scope, tags (AND), time filters, limit and offset.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use niwa_core::{Database, SearchOptions};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let db = Database::open_default().await?;
+    ///
+    ///     let since = chrono::Utc::now().timestamp() - 7 * 24 * 60 * 60;
+    ///     let options = SearchOptions::new().modified_since(since);
+    ///     let recent = db.query().list(options).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn list(&self, options: SearchOptions) -> Result<Vec<Expertise>> {
+        debug!("Listing with options: {:?}", options);
+
+        let mut sql = String::from(
+            r#"
+            SELECT e.data_json
+            FROM expertises e
+            WHERE e.malformed = 0
+            "#,
+        );
+
+        if options.scope.is_some() {
+            sql.push_str(" AND e.scope = ?");
+        }
+        if options.status.is_some() {
+            sql.push_str(" AND e.status = ?");
+        }
+        for _ in &options.excluded_statuses {
+            sql.push_str(" AND e.status != ?");
+        }
+        for _ in &options.tags {
+            sql.push_str(" AND e.id IN (SELECT expertise_id FROM tags WHERE tag = ?)");
+        }
+        let time_filters = options.time_filters();
+        for (clause, _) in &time_filters {
+            sql.push_str(clause);
+        }
+
+        sql.push_str(" ORDER BY e.scope, e.updated_at DESC");
+
+        if options.limit.is_some() || options.offset.is_some() {
+            sql.push_str(" LIMIT ?");
+        }
+        if options.offset.is_some() {
+            sql.push_str(" OFFSET ?");
+        }
+
+        let mut query_builder = sqlx::query_as::<_, (String,)>(&sql);
+
+        if let Some(scope) = &options.scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+        if let Some(status) = &options.status {
+            query_builder = query_builder.bind(status.as_str());
+        }
+        for status in &options.excluded_statuses {
+            query_builder = query_builder.bind(status.as_str());
+        }
+        for tag in &options.tags {
+            query_builder = query_builder.bind(tag);
+        }
+        for (_, value) in &time_filters {
+            query_builder = query_builder.bind(*value);
+        }
+        if options.limit.is_some() || options.offset.is_some() {
+            // SQLite requires LIMIT before OFFSET; -1 means no limit
+            query_builder = query_builder.bind(options.limit.map(|l| l as i64).unwrap_or(-1));
+        }
+        if let Some(offset) = options.offset {
+            query_builder = query_builder.bind(offset as i64);
+        }
+
+        let rows = query_builder.fetch_all(&self.pool).await?;
+
+        let mut expertises = Vec::with_capacity(rows.len());
+        for (data_json,) in rows {
+            expertises.push(Expertise::from_json(&data_json)?);
+        }
+
+        debug!("Listed {} expertises", expertises.len());
+        Ok(expertises)
+    }
+
+    /// Diagnose a search query
+    ///
+    /// Checks the query at several levels: the FTS5 index as a whole, each
+    /// term individually in FTS5, the raw `description` column via `LIKE`,
+    /// and each text fragment via `json_each`. Helps distinguish "knowledge
+    /// doesn't exist" from "index out of sync".
+    pub async fn explain(&self, query: &str) -> Result<SearchDiagnostics> {
+        debug!("Explaining search for: {}", query);
+
+        let mut diagnostics = SearchDiagnostics {
+            query: query.to_string(),
+            ..Default::default()
+        };
+
+        // Full query against FTS5 (may fail on FTS5 syntax)
+        let full: std::result::Result<(i64,), sqlx::Error> =
+            sqlx::query_as("SELECT COUNT(*) FROM expertises_fts WHERE expertises_fts MATCH ?")
+                .bind(query)
+                .fetch_one(&self.pool)
+                .await;
+        match full {
+            Ok((count,)) => diagnostics.fts_matches = count as usize,
+            Err(e) => diagnostics.fts_error = Some(e.to_string()),
+        }
+
+        let terms = split_terms(query);
+
+        // Each term individually, quoted so FTS5 treats it as a plain phrase
+        for term in &terms {
+            let (count,): (i64,) =
+                sqlx::query_as("SELECT COUNT(*) FROM expertises_fts WHERE expertises_fts MATCH ?")
+                    .bind(format!("\"{}\"", term))
+                    .fetch_one(&self.pool)
+                    .await?;
+            diagnostics
+                .term_matches
+                .push((term.clone(), count as usize));
+        }
+
+        if terms.is_empty() {
+            return Ok(diagnostics);
+        }
+
+        // Raw description and fragment text (all terms must appear)
+        let description_clause = vec!["e.description LIKE ?"; terms.len()].join(" AND ");
+        // Match fragment text only, not the JSON keys and type tags around it
+        let fragment_clause = vec![
+            r#"EXISTS (
+                SELECT 1 FROM json_each(e.data_json, '$.content') f
+                WHERE json_extract(f.value, '$.fragment.type') = 'Text'
+                  AND json_extract(f.value, '$.fragment.content') LIKE ?
+            )"#;
+            terms.len()
+        ]
+        .join(" AND ");
+
+        let sql = format!(
+            "SELECT COUNT(*) FROM expertises e WHERE {}",
+            description_clause
+        );
+        let mut query_builder = sqlx::query_as::<_, (i64,)>(&sql);
+        for term in &terms {
+            query_builder = query_builder.bind(format!("%{}%", term));
+        }
+        let (count,) = query_builder.fetch_one(&self.pool).await?;
+        diagnostics.description_matches = count as usize;
+
+        let sql = format!(
+            "SELECT COUNT(*) FROM expertises e WHERE {}",
+            fragment_clause
+        );
+        let mut query_builder = sqlx::query_as::<_, (i64,)>(&sql);
+        for term in &terms {
+            query_builder = query_builder.bind(format!("%{}%", term));
+        }
+        let (count,) = query_builder.fetch_one(&self.pool).await?;
+        diagnostics.fragment_matches = count as usize;
+
+        Ok(diagnostics)
+    }
+
+    /// Rebuild the FTS5 index from the expertises and tags tables
+    ///
+    /// Returns the number of indexed expertises.
+    pub async fn rebuild_index(&self) -> Result<usize> {
+        debug!("Rebuilding FTS5 index");
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM expertises_fts")
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO expertises_fts(id, description, tags)
+            SELECT e.id, e.description,
+                   (SELECT group_concat(tag, ' ') FROM tags WHERE expertise_id = e.id)
+            FROM expertises e
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
     /// Filter expertises by tags
     pub async fn filter_by_tags(
         &self,
@@ -172,7 +595,7 @@ impl QueryBuilder {
             SELECT DISTINCT e.data_json
             FROM expertises e
             INNER JOIN tags t ON e.id = t.expertise_id
-            WHERE t.tag IN (
+            WHERE e.malformed = 0 AND t.tag IN (
             "#,
         );
 
@@ -228,6 +651,48 @@ impl QueryBuilder {
         Ok(expertises)
     }
 
+    /// Find expertises sharing at least `min_shared` tags with `id`
+    ///
+    /// Returns each related expertise with the number of shared tags, most
+    /// shared first. With `scope`, only expertises in that scope are returned.
+    pub async fn related_by_tags(
+        &self,
+        id: &str,
+        min_shared: usize,
+        scope: Option<Scope>,
+    ) -> Result<Vec<(Expertise, usize)>> {
+        debug!("Finding expertises related to {} by tags", id);
+
+        let mut sql = String::from(
+            r#"
+            SELECT e.data_json, COUNT(*) AS shared
+            FROM tags s
+            INNER JOIN tags t ON t.tag = s.tag AND t.expertise_id != s.expertise_id
+            INNER JOIN expertises e ON e.id = t.expertise_id
+            WHERE s.expertise_id = ? AND e.malformed = 0
+            "#,
+        );
+        if scope.is_some() {
+            sql.push_str(" AND e.scope = ?");
+        }
+        sql.push_str(" GROUP BY e.id HAVING shared >= ? ORDER BY shared DESC, e.id");
+
+        let mut query_builder = sqlx::query_as::<_, (String, i64)>(&sql).bind(id);
+        if let Some(scope) = scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+        let rows = query_builder
+            .bind(min_shared.max(1) as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut related = Vec::with_capacity(rows.len());
+        for (data_json, shared) in rows {
+            related.push((Expertise::from_json(&data_json)?, shared as usize));
+        }
+        Ok(related)
+    }
+
     /// List all tags with counts
     pub async fn list_tags(&self, scope: Option<Scope>) -> Result<Vec<(String, usize)>> {
         debug!("Listing tags");
@@ -264,12 +729,104 @@ impl QueryBuilder {
             .collect())
     }
 
+    /// Find expertises whose complete tag set no other expertise has
+    ///
+    /// Only expertises with at least `min_tags` tags are considered, and with
+    /// `scope` only expertises in that scope are compared. A lone tag
+    /// combination often marks an emerging topic or niche knowledge.
+    pub async fn find_unique_tag_combos(
+        &self,
+        min_tags: usize,
+        scope: Option<Scope>,
+    ) -> Result<Vec<Expertise>> {
+        let counts = self.tag_combo_counts(min_tags, scope).await?;
+        Ok(counts
+            .into_iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|(expertise, _)| expertise)
+            .collect())
+    }
+
+    /// Find expertises whose complete tag set is shared by other expertises
+    ///
+    /// Returns each expertise with the number of expertises (itself included)
+    /// having the identical tag set, most shared first. These are candidates
+    /// for merging, or signs of over-tagging.
+    pub async fn find_shared_tag_combos(
+        &self,
+        min_tags: usize,
+        scope: Option<Scope>,
+    ) -> Result<Vec<(Expertise, usize)>> {
+        let mut counts = self.tag_combo_counts(min_tags, scope).await?;
+        counts.retain(|(_, count)| *count > 1);
+        counts.sort_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.id().cmp(b.id()))
+        });
+        Ok(counts)
+    }
+
+    /// Each expertise with at least `min_tags` tags, paired with the number
+    /// of expertises (itself included) having the identical tag set
+    async fn tag_combo_counts(
+        &self,
+        min_tags: usize,
+        scope: Option<Scope>,
+    ) -> Result<Vec<(Expertise, usize)>> {
+        debug!("Counting tag combinations (min_tags: {})", min_tags);
+
+        let scope_filter = if scope.is_some() {
+            "WHERE e.malformed = 0 AND e.scope = ?"
+        } else {
+            "WHERE e.malformed = 0"
+        };
+
+        // Two tag sets of equal size are identical when one contains the other
+        let sql = format!(
+            r#"
+            WITH sets AS (
+                SELECT e.id, e.data_json, COUNT(t.tag) AS size
+                FROM expertises e
+                INNER JOIN tags t ON e.id = t.expertise_id
+                {}
+                GROUP BY e.id
+                HAVING size >= ?
+            )
+            SELECT a.data_json, COUNT(*) AS shared
+            FROM sets a
+            INNER JOIN sets b ON a.size = b.size
+            WHERE NOT EXISTS (
+                SELECT 1 FROM tags ta
+                WHERE ta.expertise_id = a.id
+                  AND ta.tag NOT IN (SELECT tb.tag FROM tags tb WHERE tb.expertise_id = b.id)
+            )
+            GROUP BY a.id
+            ORDER BY a.id
+            "#,
+            scope_filter
+        );
+
+        let mut query_builder = sqlx::query_as::<_, (String, i64)>(&sql);
+        if let Some(scope) = scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+        let rows = query_builder
+            .bind(min_tags as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut counts = Vec::with_capacity(rows.len());
+        for (data_json, shared) in rows {
+            counts.push((Expertise::from_json(&data_json)?, shared as usize));
+        }
+        Ok(counts)
+    }
+
     /// Count total expertises
     pub async fn count(&self, scope: Option<Scope>) -> Result<usize> {
         let sql = if scope.is_some() {
-            "SELECT COUNT(*) FROM expertises WHERE scope = ?"
+            "SELECT COUNT(*) FROM expertises WHERE scope = ? AND malformed = 0"
         } else {
-            "SELECT COUNT(*) FROM expertises"
+            "SELECT COUNT(*) FROM expertises WHERE malformed = 0"
         };
 
         let mut query_builder = sqlx::query_as::<_, (i64,)>(sql);
@@ -281,12 +838,237 @@ impl QueryBuilder {
         let (count,) = query_builder.fetch_one(&self.pool).await?;
         Ok(count as usize)
     }
+
+    /// Word count statistics over text fragments
+    ///
+    /// With `tags`, only expertises having every tag are counted.
+    pub async fn word_count_stats(
+        &self,
+        scope: Option<Scope>,
+        tags: &[String],
+    ) -> Result<WordCountStats> {
+        debug!(
+            "Computing word counts (scope: {:?}, tags: {:?})",
+            scope, tags
+        );
+
+        // Newlines and tabs count as word separators too
+        let mut sql = String::from(
+            r#"
+            WITH fragments AS (
+                SELECT e.id,
+                       trim(replace(replace(json_extract(f.value, '$.fragment.content'),
+                            char(10), ' '), char(9), ' ')) AS text
+                FROM expertises e
+                LEFT JOIN json_each(e.data_json, '$.content') f
+                    ON json_extract(f.value, '$.fragment.type') = 'Text'
+                WHERE e.malformed = 0
+            "#,
+        );
+        if scope.is_some() {
+            sql.push_str(" AND e.scope = ?");
+        }
+        if !tags.is_empty() {
+            sql.push_str(&format!(
+                " AND e.id IN (SELECT expertise_id FROM tags WHERE tag IN ({}) \
+                 GROUP BY expertise_id HAVING COUNT(DISTINCT tag) = {})",
+                vec!["?"; tags.len()].join(", "),
+                tags.len()
+            ));
+        }
+        sql.push_str(
+            r#"
+            )
+            SELECT id,
+                   CASE WHEN text = '' THEN 0
+                        ELSE length(text) - length(replace(text, ' ', '')) + 1 END,
+                   length(text)
+            FROM fragments
+            ORDER BY id
+            "#,
+        );
+
+        let mut query_builder = sqlx::query_as::<_, (String, Option<i64>, Option<i64>)>(&sql);
+        if let Some(scope) = scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+        for tag in tags {
+            query_builder = query_builder.bind(tag);
+        }
+        let rows = query_builder.fetch_all(&self.pool).await?;
+
+        // Words per expertise, in ID order; expertises without text fragments
+        // come back as a single row of NULLs
+        let mut per_expertise: Vec<(String, usize)> = Vec::new();
+        let mut stats = WordCountStats::default();
+        for (id, words, length) in rows {
+            if per_expertise.last().map(|(last, _)| last) != Some(&id) {
+                per_expertise.push((id, 0));
+            }
+            if let (Some(words), Some(length)) = (words, length) {
+                per_expertise.last_mut().unwrap().1 += words as usize;
+                stats.fragment_lengths.push(length as usize);
+            }
+        }
+
+        stats.fragment_lengths.sort_unstable();
+        stats.expertise_count = per_expertise.len();
+        stats.fragment_count = stats.fragment_lengths.len();
+        stats.total_words = per_expertise.iter().map(|(_, words)| words).sum();
+        if stats.expertise_count > 0 {
+            stats.avg_words_per_expertise = stats.total_words as f32 / stats.expertise_count as f32;
+        }
+        if stats.fragment_count > 0 {
+            stats.avg_words_per_fragment = stats.total_words as f32 / stats.fragment_count as f32;
+            stats.median_fragment_length = stats.fragment_lengths[stats.fragment_count / 2];
+        }
+        // Ties go to the first ID alphabetically
+        if let Some((id, _)) = per_expertise.iter().rev().max_by_key(|(_, words)| *words) {
+            stats.longest_expertise_id = id.clone();
+        }
+        if let Some((id, _)) = per_expertise.iter().min_by_key(|(_, words)| *words) {
+            stats.shortest_expertise_id = id.clone();
+        }
+
+        Ok(stats)
+    }
+
+    /// Find expertises that mention `id` in their tags, description or fragments
+    ///
+    /// Matches are whole-token and case-insensitive. The expertise itself is
+    /// excluded. Results are ordered by descending confidence.
+    pub async fn find_cross_references(&self, id: &str) -> Result<Vec<CrossReference>> {
+        debug!("Finding cross references to: {}", id);
+
+        // Cheap substring prefilter; token boundaries are checked below
+        let pattern = format!(
+            "%{}%",
+            id.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT data_json
+            FROM expertises
+            WHERE id != ? AND malformed = 0 AND data_json LIKE ? ESCAPE '\'
+            "#,
+        )
+        .bind(id)
+        .bind(&pattern)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut references = Vec::new();
+        for (data_json,) in rows {
+            let expertise = Expertise::from_json(&data_json)?;
+
+            let reference = CrossReference {
+                id: expertise.id().to_string(),
+                scope: expertise.metadata.scope,
+                in_tags: expertise.tags().iter().any(|t| t.eq_ignore_ascii_case(id)),
+                in_description: mentions_id(&expertise.description(), id),
+                fragment_mentions: expertise
+                    .inner
+                    .content
+                    .iter()
+                    .filter(|w| mentions_id(&w.fragment.to_prompt(), id))
+                    .count(),
+            };
+
+            if reference.in_tags || reference.in_description || reference.fragment_mentions > 0 {
+                references.push(reference);
+            }
+        }
+
+        references.sort_by(|a, b| {
+            b.confidence()
+                .total_cmp(&a.confidence())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        debug!("Found {} cross references", references.len());
+        Ok(references)
+    }
+
+    /// List expertises not updated in the last `older_than_days` days
+    ///
+    /// Results are ordered oldest first, so the expertises most in need of
+    /// review come at the top.
+    pub async fn stale(
+        &self,
+        older_than_days: u32,
+        scope: Option<Scope>,
+    ) -> Result<Vec<Expertise>> {
+        let cutoff = chrono::Utc::now().timestamp() - i64::from(older_than_days) * 24 * 60 * 60;
+        self.updated_before(cutoff, scope).await
+    }
+
+    /// List expertises whose `updated_at` is strictly before `cutoff`, oldest first
+    async fn updated_before(&self, cutoff: i64, scope: Option<Scope>) -> Result<Vec<Expertise>> {
+        debug!("Listing expertises updated before {}", cutoff);
+
+        let mut sql =
+            String::from("SELECT data_json FROM expertises WHERE updated_at < ? AND malformed = 0");
+        if scope.is_some() {
+            sql.push_str(" AND scope = ?");
+        }
+        sql.push_str(" ORDER BY updated_at ASC, id");
+
+        let mut query_builder = sqlx::query_as::<_, (String,)>(&sql).bind(cutoff);
+        if let Some(scope) = scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+
+        let rows = query_builder.fetch_all(&self.pool).await?;
+
+        let mut expertises = Vec::with_capacity(rows.len());
+        for (data_json,) in rows {
+            expertises.push(Expertise::from_json(&data_json)?);
+        }
+
+        debug!("Found {} stale expertises", expertises.len());
+        Ok(expertises)
+    }
+}
+
+/// Whether `text` mentions `id` as a whole token (case-insensitive)
+///
+/// Hyphens and underscores count as part of a token, so `rust` is not
+/// found inside `rust-async`.
+fn mentions_id(text: &str, id: &str) -> bool {
+    if id.is_empty() {
+        return false;
+    }
+
+    let text = text.to_ascii_lowercase();
+    let id = id.to_ascii_lowercase();
+    let is_token_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+
+    text.match_indices(&id).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + id.len()..].chars().next();
+        !before.is_some_and(is_token_char) && !after.is_some_and(is_token_char)
+    })
+}
+
+/// Split a search query into plain terms, dropping FTS5 operators and punctuation
+fn split_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|t| {
+            t.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                .collect::<String>()
+        })
+        .filter(|t| !t.is_empty() && !matches!(t.as_str(), "AND" | "OR" | "NOT" | "NEAR"))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Database, StorageOperations};
+    use crate::{Database, KnowledgeFragment, StorageOperations, WeightedFragment};
     use tempfile::TempDir;
 
     async fn setup_db() -> (Database, TempDir) {
@@ -315,6 +1097,53 @@ mod tests {
         assert_eq!(results[0].id(), "rust-expert");
     }
 
+    #[tokio::test]
+    async fn test_search_regex() {
+        let (db, _temp) = setup_db().await;
+
+        let mut tokio = Expertise::new("tokio-runtime", "1.0.0");
+        tokio.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Use spawn_blocking for CPU-bound work".to_string(),
+        ))];
+        let mut serde = Expertise::new("serde-expert", "1.0.0");
+        serde.inner.description = Some("Derive Serialize v2".to_string());
+        serde.metadata.scope = Scope::Company;
+        db.storage().create(tokio).await.unwrap();
+        db.storage().create(serde).await.unwrap();
+
+        let ids = |results: Vec<Expertise>| -> Vec<String> {
+            results.iter().map(|e| e.id().to_string()).collect()
+        };
+        let query = db.query();
+        assert_eq!(
+            ids(query.search_regex(r"spawn_\w+", None).await.unwrap()),
+            vec!["tokio-runtime"]
+        );
+        assert_eq!(
+            ids(query.search_regex(r"v\d$", None).await.unwrap()),
+            vec!["serde-expert"]
+        );
+        assert_eq!(
+            ids(query.search_regex("^serde", None).await.unwrap()).len(),
+            1
+        );
+        assert!(query
+            .search_regex("^serde", Some(Scope::Personal))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(query.search_regex("SPAWN", None).await.unwrap().is_empty());
+        assert_eq!(
+            query.search_regex("(?i)SPAWN", None).await.unwrap().len(),
+            1
+        );
+
+        assert!(matches!(
+            query.search_regex("(unclosed", None).await,
+            Err(Error::InvalidPattern(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_filter_by_tags() {
         let (db, _temp) = setup_db().await;
@@ -394,4 +1223,390 @@ mod tests {
         let personal = db.query().count(Some(Scope::Personal)).await.unwrap();
         assert_eq!(personal, 1);
     }
+
+    #[tokio::test]
+    async fn test_explain_detects_out_of_sync_index() {
+        let (db, _temp) = setup_db().await;
+
+        let mut exp = Expertise::new("tokio-expert", "1.0.0");
+        exp.inner.description = Some("Tuning the tokio runtime".to_string());
+        exp.metadata.scope = Scope::Personal;
+        db.storage().create(exp).await.unwrap();
+
+        let report = db.query().explain("tokio runtime").await.unwrap();
+        assert_eq!(report.fts_matches, 1);
+        assert!(!report.index_out_of_sync());
+
+        // Simulate a stale index
+        sqlx::query("DELETE FROM expertises_fts")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let report = db.query().explain("tokio runtime").await.unwrap();
+        assert_eq!(report.fts_matches, 0);
+        assert_eq!(report.description_matches, 1);
+        assert_eq!(report.term_matches.len(), 2);
+        assert!(report.index_out_of_sync());
+
+        // Rebuilding restores search
+        assert_eq!(db.query().rebuild_index().await.unwrap(), 1);
+        let results = db
+            .query()
+            .search("tokio runtime", SearchOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_explain_fragment_matches() {
+        let (db, _temp) = setup_db().await;
+
+        let mut exp = Expertise::new("tokio-expert", "1.0.0");
+        exp.inner.content = vec![
+            WeightedFragment::new(KnowledgeFragment::Text(
+                "Use spawn_blocking for CPU-bound work".to_string(),
+            )),
+            WeightedFragment::new(KnowledgeFragment::Text(
+                "Keep the runtime responsive".to_string(),
+            )),
+        ];
+        db.storage().create(exp).await.unwrap();
+
+        // Terms may come from different fragments
+        let report = db.query().explain("spawn_blocking runtime").await.unwrap();
+        assert_eq!(report.fragment_matches, 1);
+
+        // The serialized structure around the text ("type": "Text") is not matched
+        let report = db.query().explain("type").await.unwrap();
+        assert_eq!(report.fragment_matches, 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_time_filters() {
+        let (db, _temp) = setup_db().await;
+
+        let mut old = Expertise::new("old", "1.0.0");
+        old.metadata.created_at = 1_000;
+        old.metadata.updated_at = 2_000;
+
+        let mut new = Expertise::new("new", "1.0.0");
+        new.metadata.created_at = 5_000;
+        new.metadata.updated_at = 6_000;
+
+        db.storage().create(old).await.unwrap();
+        db.storage().create(new).await.unwrap();
+
+        let results = db
+            .query()
+            .list(SearchOptions::new().modified_since(3_000))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "new");
+
+        let results = db
+            .query()
+            .list(SearchOptions::new().modified_before(3_000))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "old");
+
+        let results = db
+            .query()
+            .list(
+                SearchOptions::new()
+                    .created_since(500)
+                    .created_before(4_000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "old");
+
+        let results = db.query().list(SearchOptions::new()).await.unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_status_filter() {
+        let (db, _temp) = setup_db().await;
+
+        db.storage()
+            .create(Expertise::new("draft", "1.0.0"))
+            .await
+            .unwrap();
+        db.storage()
+            .create(Expertise::new("vetted", "1.0.0"))
+            .await
+            .unwrap();
+        db.storage()
+            .set_status("vetted", Scope::Personal, ExpertiseStatus::Reviewed)
+            .await
+            .unwrap();
+
+        let results = db
+            .query()
+            .list(SearchOptions::new().status(ExpertiseStatus::Reviewed))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "vetted");
+
+        let results = db
+            .query()
+            .list(SearchOptions::new().status(ExpertiseStatus::Draft))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "draft");
+
+        // Deprecated expertises can be left out
+        db.storage()
+            .set_status("draft", Scope::Personal, ExpertiseStatus::Deprecated)
+            .await
+            .unwrap();
+        let results = db
+            .query()
+            .list(
+                SearchOptions::new()
+                    .scope(Scope::Personal)
+                    .exclude_status(ExpertiseStatus::Deprecated),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "vetted");
+    }
+
+    #[test]
+    fn test_mentions_id() {
+        assert!(mentions_id("See rust-expert for details", "rust-expert"));
+        assert!(mentions_id("(Rust-Expert)", "rust-expert"));
+        assert!(!mentions_id(
+            "rust-expert-advanced covers it",
+            "rust-expert"
+        ));
+        assert!(!mentions_id("trust-expert", "rust-expert"));
+        assert!(!mentions_id("anything", ""));
+    }
+
+    #[tokio::test]
+    async fn test_find_cross_references() {
+        use crate::{KnowledgeFragment, WeightedFragment};
+
+        let (db, _temp) = setup_db().await;
+
+        let mut tagged = Expertise::new("tagged", "1.0.0");
+        tagged.inner.tags = vec!["rust-expert".to_string()];
+
+        let mut described = Expertise::new("described", "1.0.0");
+        described.inner.description = Some("Builds on rust-expert.".to_string());
+
+        let mut fragment = Expertise::new("fragment", "1.0.0");
+        fragment.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Ask rust-expert first".to_string(),
+        ))];
+
+        let mut unrelated = Expertise::new("unrelated", "1.0.0");
+        unrelated.inner.description = Some("Mentions rust-expert-advanced only".to_string());
+
+        for exp in [
+            Expertise::new("rust-expert", "1.0.0"),
+            tagged,
+            described,
+            fragment,
+            unrelated,
+        ] {
+            db.storage().create(exp).await.unwrap();
+        }
+
+        let references = db
+            .query()
+            .find_cross_references("rust-expert")
+            .await
+            .unwrap();
+        let ids: Vec<_> = references.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["tagged", "described", "fragment"]);
+        assert_eq!(references[2].fragment_mentions, 1);
+        assert!(references[0].confidence() > references[2].confidence());
+    }
+
+    #[tokio::test]
+    async fn test_updated_before_cutoff_boundary() {
+        let (db, _temp) = setup_db().await;
+
+        for (id, updated_at, scope) in [
+            ("older", 999, Scope::Personal),
+            ("oldest", 100, Scope::Company),
+            ("at-cutoff", 1_000, Scope::Personal),
+            ("newer", 1_001, Scope::Personal),
+        ] {
+            let mut exp = Expertise::new(id, "1.0.0");
+            exp.metadata.updated_at = updated_at;
+            exp.metadata.scope = scope;
+            db.storage().create(exp).await.unwrap();
+        }
+
+        let results = db.query().updated_before(1_000, None).await.unwrap();
+        let ids: Vec<_> = results.iter().map(|e| e.id()).collect();
+        assert_eq!(ids, vec!["oldest", "older"]);
+
+        let results = db
+            .query()
+            .updated_before(1_000, Some(Scope::Personal))
+            .await
+            .unwrap();
+        let ids: Vec<_> = results.iter().map(|e| e.id()).collect();
+        assert_eq!(ids, vec!["older"]);
+
+        let results = db.query().updated_before(100, None).await.unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stale() {
+        let (db, _temp) = setup_db().await;
+        let day = 24 * 60 * 60;
+        let now = chrono::Utc::now().timestamp();
+
+        let mut old = Expertise::new("old", "1.0.0");
+        old.metadata.updated_at = now - 30 * day - 60;
+        let mut fresh = Expertise::new("fresh", "1.0.0");
+        fresh.metadata.updated_at = now - 30 * day + 60;
+
+        db.storage().create(old).await.unwrap();
+        db.storage().create(fresh).await.unwrap();
+
+        let results = db.query().stale(30, None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), "old");
+
+        let results = db.query().stale(0, None).await.unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_find_unique_tag_combos() {
+        let (db, _temp) = setup_db().await;
+
+        let tagged = |id: &str, tags: &[&str]| {
+            let mut exp = Expertise::new(id, "1.0.0");
+            exp.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+            exp
+        };
+        for exp in [
+            tagged("tokio", &["rust", "async"]),
+            tagged("async-std", &["async", "rust"]),
+            tagged("rust-web", &["rust", "async", "web"]),
+            tagged("wasm", &["rust", "wasm"]),
+            tagged("rust", &["rust"]),
+        ] {
+            db.storage().create(exp).await.unwrap();
+        }
+
+        let unique = db.query().find_unique_tag_combos(2, None).await.unwrap();
+        let ids: Vec<&str> = unique.iter().map(|e| e.id()).collect();
+        assert_eq!(ids, vec!["rust-web", "wasm"]);
+
+        let unique = db.query().find_unique_tag_combos(1, None).await.unwrap();
+        assert_eq!(unique.len(), 3);
+
+        let shared = db.query().find_shared_tag_combos(2, None).await.unwrap();
+        let shared: Vec<(&str, usize)> = shared.iter().map(|(e, n)| (e.id(), *n)).collect();
+        assert_eq!(shared, vec![("async-std", 2), ("tokio", 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_related_by_tags() {
+        let (db, _temp) = setup_db().await;
+
+        let tagged = |id: &str, tags: &[&str], scope: Scope| {
+            let mut exp = Expertise::new(id, "1.0.0");
+            exp.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+            exp.metadata.scope = scope;
+            exp
+        };
+        for exp in [
+            tagged("tokio", &["rust", "async", "runtime"], Scope::Personal),
+            tagged("async-std", &["rust", "async", "runtime"], Scope::Personal),
+            tagged("axum", &["rust", "async", "web"], Scope::Personal),
+            tagged("serde", &["rust", "serialization"], Scope::Personal),
+            tagged("work-async", &["rust", "async"], Scope::Company),
+        ] {
+            db.storage().create(exp).await.unwrap();
+        }
+
+        let related = db.query().related_by_tags("tokio", 2, None).await.unwrap();
+        let related: Vec<(&str, usize)> = related.iter().map(|(e, n)| (e.id(), *n)).collect();
+        assert_eq!(
+            related,
+            vec![("async-std", 3), ("axum", 2), ("work-async", 2)]
+        );
+
+        let related = db
+            .query()
+            .related_by_tags("tokio", 2, Some(Scope::Personal))
+            .await
+            .unwrap();
+        assert_eq!(related.len(), 2);
+
+        let related = db.query().related_by_tags("tokio", 1, None).await.unwrap();
+        assert_eq!(related.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_word_count_stats() {
+        let (db, _temp) = setup_db().await;
+
+        let expertise = |id: &str, tags: &[&str], texts: &[&str]| {
+            let mut exp = Expertise::new(id, "1.0.0");
+            exp.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+            exp.inner.content = texts
+                .iter()
+                .map(|t| WeightedFragment::new(KnowledgeFragment::Text(t.to_string())))
+                .collect();
+            exp
+        };
+        let mut with_logic = expertise("tokio", &["rust", "async"], &["spawn blocking work"]);
+        with_logic
+            .inner
+            .content
+            .push(WeightedFragment::new(KnowledgeFragment::Logic {
+                instruction: "not counted at all".to_string(),
+                steps: vec![],
+            }));
+        for exp in [
+            with_logic,
+            expertise("serde", &["rust"], &["derive it", "use\nrename_all"]),
+            expertise("empty", &["rust"], &[]),
+        ] {
+            db.storage().create(exp).await.unwrap();
+        }
+
+        let stats = db.query().word_count_stats(None, &[]).await.unwrap();
+        assert_eq!(stats.expertise_count, 3);
+        assert_eq!(stats.fragment_count, 3);
+        assert_eq!(stats.total_words, 7);
+        assert!((stats.avg_words_per_expertise - 7.0 / 3.0).abs() < 1e-6);
+        assert!((stats.avg_words_per_fragment - 7.0 / 3.0).abs() < 1e-6);
+        assert_eq!(stats.fragment_lengths, vec![9, 14, 19]);
+        assert_eq!(stats.median_fragment_length, 14);
+        assert_eq!(stats.longest_expertise_id, "serde");
+        assert_eq!(stats.shortest_expertise_id, "empty");
+
+        let tags = vec!["rust".to_string(), "async".to_string()];
+        let stats = db.query().word_count_stats(None, &tags).await.unwrap();
+        assert_eq!(stats.expertise_count, 1);
+        assert_eq!(stats.total_words, 3);
+
+        let stats = db
+            .query()
+            .word_count_stats(Some(Scope::Company), &[])
+            .await
+            .unwrap();
+        assert_eq!(stats, WordCountStats::default());
+    }
 }
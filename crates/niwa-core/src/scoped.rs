@@ -0,0 +1,474 @@
+//! Scope-routed storage across several SQLite files
+//!
+//! A [`ScopedDatabase`] keeps some scopes in files of their own, e.g. personal
+//! expertise in `~/.niwa/graph.db` and company expertise in a shared file.
+//! Scopes without a route stay in the main database.
+//!
+//! Relations may connect expertises stored in different files, so they are
+//! always kept in the main database. Its graph connections run without
+//! foreign-key enforcement, and deleting a routed expertise removes its edges
+//! explicitly instead of relying on `ON DELETE CASCADE`.
+//!
+//! ## Migrating from a single file
+//!
+//! Open the main database with the new routes, then move each routed scope
+//! out of it with [`ScopedDatabase::migrate_from_main`]. The move is
+//! idempotent, so an interrupted migration can simply be run again.
+
+use crate::storage::{insert_expertise, insert_relation};
+use crate::{
+    ConflictPolicy, Database, Error, Expertise, ExportBundle, GraphOperations, Result, Scope,
+    Storage, StorageOperations,
+};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Database handle that stores each routed scope in its own file
+#[derive(Clone)]
+pub struct ScopedDatabase {
+    main: Database,
+    /// Pool on the main file without foreign keys, for cross-file relations
+    graph_pool: SqlitePool,
+    routes: HashMap<Scope, Database>,
+}
+
+impl ScopedDatabase {
+    /// Open the main database and one database per routed scope
+    ///
+    /// A route pointing at the main file is ignored.
+    pub async fn open<P: AsRef<Path>>(
+        main_path: P,
+        routes: impl IntoIterator<Item = (Scope, PathBuf)>,
+    ) -> Result<Self> {
+        let main = Database::open(main_path).await?;
+        let graph_pool = Database::connect(main.path(), false).await?;
+
+        let mut routed = HashMap::new();
+        for (scope, path) in routes {
+            let db = Database::open(path).await?;
+            if db.path() == main.path() {
+                continue;
+            }
+            info!("Routing scope {} to {}", scope, db.path().display());
+            routed.insert(scope, db);
+        }
+
+        Ok(Self {
+            main,
+            graph_pool,
+            routes: routed,
+        })
+    }
+
+    /// The main database (unrouted scopes, relations, crawler state)
+    pub fn main(&self) -> &Database {
+        &self.main
+    }
+
+    /// The database holding `scope`
+    pub fn database(&self, scope: Scope) -> &Database {
+        self.routes.get(&scope).unwrap_or(&self.main)
+    }
+
+    /// Whether `scope` is stored outside the main database
+    pub fn is_routed(&self, scope: Scope) -> bool {
+        self.routes.contains_key(&scope)
+    }
+
+    /// Storage operations routed by scope
+    pub fn storage(&self) -> ScopedStorage {
+        ScopedStorage {
+            main: self.main.storage(),
+            graph_pool: self.graph_pool.clone(),
+            routes: self
+                .routes
+                .iter()
+                .map(|(scope, db)| (*scope, db.storage()))
+                .collect(),
+        }
+    }
+
+    /// Graph operations on the main database, across all files
+    pub fn graph(&self) -> GraphOperations {
+        GraphOperations::new(self.graph_pool.clone())
+    }
+
+    /// Move the expertises of a routed `scope` out of the main database
+    ///
+    /// Copies each expertise with its tags and version history into the
+    /// scope's file, then removes it from the main file. Relations stay in
+    /// the main file. Returns the number of expertises moved.
+    pub async fn migrate_from_main(&self, scope: Scope) -> Result<usize> {
+        let target = self.routes.get(&scope).ok_or_else(|| {
+            Error::Other(format!("Scope {} is not routed to its own file", scope))
+        })?;
+
+        info!("Moving {} expertises to {}", scope, target.path().display());
+
+        let rows: Vec<(String,)> =
+            sqlx::query_as("SELECT data_json FROM expertises WHERE scope = ?")
+                .bind(scope.as_str())
+                .fetch_all(&self.graph_pool)
+                .await?;
+
+        for (data_json,) in &rows {
+            let expertise = Expertise::from_json(data_json)?;
+            let id = expertise.id();
+
+            let versions: Vec<(String, i64, String)> = sqlx::query_as(
+                "SELECT version, created_at, data_json FROM versions WHERE expertise_id = ?",
+            )
+            .bind(id)
+            .fetch_all(&self.graph_pool)
+            .await?;
+
+            // Copy first; a rerun after an interruption finds it already there
+            let mut tx = target.pool().begin().await?;
+            match insert_expertise(&mut tx, &expertise).await {
+                Ok(()) | Err(Error::AlreadyExists { .. }) => {}
+                Err(e) => return Err(e),
+            }
+            for (version, created_at, data_json) in &versions {
+                sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO versions (expertise_id, version, created_at, data_json)
+                    VALUES (?, ?, ?, ?)
+                    "#,
+                )
+                .bind(id)
+                .bind(version)
+                .bind(created_at)
+                .bind(data_json)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+
+            // Remove without cascading, so relations to it survive
+            let mut tx = self.graph_pool.begin().await?;
+            for sql in [
+                "DELETE FROM tags WHERE expertise_id = ?",
+                "DELETE FROM versions WHERE expertise_id = ?",
+                "DELETE FROM expertises WHERE id = ?",
+            ] {
+                sqlx::query(sql).bind(id).execute(&mut *tx).await?;
+            }
+            tx.commit().await?;
+
+            debug!("Moved expertise: {}", id);
+        }
+
+        Ok(rows.len())
+    }
+}
+
+/// [`StorageOperations`] that dispatch to the file holding each scope
+#[derive(Clone)]
+pub struct ScopedStorage {
+    main: Storage,
+    graph_pool: SqlitePool,
+    routes: HashMap<Scope, Storage>,
+}
+
+impl ScopedStorage {
+    /// Storage holding `scope`
+    pub fn for_scope(&self, scope: Scope) -> &Storage {
+        self.routes.get(&scope).unwrap_or(&self.main)
+    }
+
+    /// Whether any file holds an expertise with this id
+    async fn is_stored(&self, id: &str) -> Result<bool> {
+        for scope in Scope::all() {
+            if self.for_scope(*scope).exists(id, *scope).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn graph(&self) -> GraphOperations {
+        GraphOperations::new(self.graph_pool.clone())
+    }
+
+    /// Remove the relations of a deleted routed expertise from the main file
+    ///
+    /// Edges are kept if the main file still has an expertise with that id.
+    async fn delete_relations(&self, id: &str) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM relations
+            WHERE (from_id = ? OR to_id = ?)
+              AND NOT EXISTS (SELECT 1 FROM expertises WHERE id = ?)
+            "#,
+        )
+        .bind(id)
+        .bind(id)
+        .bind(id)
+        .execute(&self.graph_pool)
+        .await?;
+
+        debug!("Deleted {} relations of {}", result.rows_affected(), id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageOperations for ScopedStorage {
+    async fn create(&self, expertise: Expertise) -> Result<()> {
+        self.for_scope(expertise.metadata.scope)
+            .create(expertise)
+            .await
+    }
+
+    async fn get(&self, id: &str, scope: Scope) -> Result<Option<Expertise>> {
+        self.for_scope(scope).get(id, scope).await
+    }
+
+    async fn update(&self, expertise: Expertise) -> Result<()> {
+        self.for_scope(expertise.metadata.scope)
+            .update(expertise)
+            .await
+    }
+
+    async fn delete(&self, id: &str, scope: Scope) -> Result<()> {
+        self.for_scope(scope).delete(id, scope).await?;
+        if self.routes.contains_key(&scope) {
+            self.delete_relations(id).await?;
+        }
+        Ok(())
+    }
+
+    async fn list(&self, scope: Scope) -> Result<Vec<Expertise>> {
+        self.for_scope(scope).list(scope).await
+    }
+
+    async fn list_all(&self) -> Result<Vec<Expertise>> {
+        let mut expertises = Vec::new();
+        for scope in Scope::all() {
+            expertises.extend(self.list(*scope).await?);
+        }
+
+        // Same order as a single file: scope, then most recently updated
+        expertises.sort_by(|a, b| {
+            a.metadata
+                .scope
+                .as_str()
+                .cmp(b.metadata.scope.as_str())
+                .then(b.metadata.updated_at.cmp(&a.metadata.updated_at))
+        });
+        Ok(expertises)
+    }
+
+    async fn exists(&self, id: &str, scope: Scope) -> Result<bool> {
+        self.for_scope(scope).exists(id, scope).await
+    }
+
+    async fn touch_only(&self, id: &str, scope: Scope) -> Result<i64> {
+        self.for_scope(scope).touch_only(id, scope).await
+    }
+
+    async fn rename_tag_globally(
+        &self,
+        old_tag: &str,
+        new_tag: &str,
+        scope: Option<Scope>,
+    ) -> Result<usize> {
+        let scopes = match scope {
+            Some(scope) => vec![scope],
+            None => Scope::all().to_vec(),
+        };
+
+        let mut renamed = 0;
+        for scope in scopes {
+            renamed += self
+                .for_scope(scope)
+                .rename_tag_globally(old_tag, new_tag, Some(scope))
+                .await?;
+        }
+        Ok(renamed)
+    }
+
+    async fn update_description(&self, id: &str, scope: Scope, description: &str) -> Result<()> {
+        self.for_scope(scope)
+            .update_description(id, scope, description)
+            .await
+    }
+
+    async fn add_tags(&self, id: &str, scope: Scope, tags: &[String]) -> Result<Vec<String>> {
+        self.for_scope(scope).add_tags(id, scope, tags).await
+    }
+
+    async fn export_expertise(&self, id: &str, scope: Scope) -> Result<ExportBundle> {
+        let mut bundle = self.for_scope(scope).export_expertise(id, scope).await?;
+        if self.routes.contains_key(&scope) {
+            bundle.relations = self.graph().get_all_relations(id).await?;
+        }
+        Ok(bundle)
+    }
+
+    async fn import_expertise(
+        &self,
+        mut bundle: ExportBundle,
+        on_conflict: ConflictPolicy,
+    ) -> Result<()> {
+        let relations = std::mem::take(&mut bundle.relations);
+        let scope = bundle.expertise.metadata.scope;
+        let id = bundle.expertise.id().to_string();
+
+        // A skipped or failed import writes no relations either
+        if on_conflict != ConflictPolicy::Overwrite && self.is_stored(&id).await? {
+            return self
+                .for_scope(scope)
+                .import_expertise(bundle, on_conflict)
+                .await;
+        }
+        self.for_scope(scope)
+            .import_expertise(bundle, on_conflict)
+            .await?;
+
+        // Relations live in the main file and may point into any file
+        let mut conn = self.graph_pool.acquire().await?;
+        for relation in &relations {
+            let other = if relation.from_id == id {
+                &relation.to_id
+            } else {
+                &relation.from_id
+            };
+            if !self.is_stored(other).await? {
+                warn!(
+                    "Dropping relation {} -[{}]-> {}: {} is not stored",
+                    relation.from_id, relation.relation_type, relation.to_id, other
+                );
+                continue;
+            }
+            insert_relation(&mut conn, relation).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RelationType;
+    use tempfile::TempDir;
+
+    fn expertise(id: &str, scope: Scope) -> Expertise {
+        let mut expertise = Expertise::new(id, "1.0.0");
+        expertise.metadata.scope = scope;
+        expertise.inner.tags = vec!["rust".to_string()];
+        expertise
+    }
+
+    async fn count(db: &Database, scope: Scope) -> i64 {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM expertises WHERE scope = ?")
+            .bind(scope.as_str())
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        count
+    }
+
+    #[tokio::test]
+    async fn test_routing() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = ScopedDatabase::open(
+            temp_dir.path().join("graph.db"),
+            [(Scope::Company, temp_dir.path().join("company.db"))],
+        )
+        .await
+        .unwrap();
+        let storage = db.storage();
+
+        storage
+            .create(expertise("rust-expert", Scope::Personal))
+            .await
+            .unwrap();
+        storage
+            .create(expertise("team-style", Scope::Company))
+            .await
+            .unwrap();
+
+        assert_eq!(count(db.main(), Scope::Company).await, 0);
+        assert_eq!(count(db.database(Scope::Company), Scope::Company).await, 1);
+        assert!(storage
+            .get("team-style", Scope::Company)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(storage.list_all().await.unwrap().len(), 2);
+        assert_eq!(
+            storage
+                .rename_tag_globally("rust", "rustlang", None)
+                .await
+                .unwrap(),
+            2
+        );
+
+        // Relations across files live in the main database
+        db.graph()
+            .create_relation("rust-expert", "team-style", RelationType::Uses, None)
+            .await
+            .unwrap();
+        assert_eq!(db.graph().count_relations().await.unwrap(), 1);
+
+        storage.delete("team-style", Scope::Company).await.unwrap();
+        assert_eq!(db.graph().count_relations().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_from_main() {
+        let temp_dir = TempDir::new().unwrap();
+        let main_path = temp_dir.path().join("graph.db");
+
+        // Single-file layout
+        let single = Database::open(&main_path).await.unwrap();
+        single
+            .storage()
+            .create(expertise("rust-expert", Scope::Personal))
+            .await
+            .unwrap();
+        let mut team = expertise("team-style", Scope::Company);
+        single.storage().create(team.clone()).await.unwrap();
+        team.inner.description = Some("Updated".to_string());
+        team.inner.version = "1.1.0".to_string();
+        single.storage().update(team).await.unwrap();
+        single
+            .graph()
+            .create_relation("rust-expert", "team-style", RelationType::Uses, None)
+            .await
+            .unwrap();
+        single.close().await;
+
+        let db = ScopedDatabase::open(
+            &main_path,
+            [(Scope::Company, temp_dir.path().join("company.db"))],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(db.migrate_from_main(Scope::Company).await.unwrap(), 1);
+        assert_eq!(db.migrate_from_main(Scope::Company).await.unwrap(), 0);
+        assert!(db.migrate_from_main(Scope::Project).await.is_err());
+
+        assert_eq!(count(db.main(), Scope::Company).await, 0);
+        let moved = db
+            .storage()
+            .get("team-style", Scope::Company)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(moved.description(), "Updated");
+        assert!(db
+            .database(Scope::Company)
+            .storage()
+            .get_version("team-style", "1.0.0")
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(db.graph().count_relations().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,256 @@
+//! Similarity between expertises and their texts
+//!
+//! Related-expertise lookups, duplicate checks and version comparisons share
+//! these measures, so they agree on what "similar" means. [`SimilarityConfig`]
+//! picks a measure and the score from which two expertises count as similar.
+
+use crate::types::Expertise;
+use std::collections::HashSet;
+
+/// Fragments at least this similar to an existing one are treated as duplicates
+pub const DUPLICATE_FRAGMENT_SIMILARITY: f64 = 0.6;
+
+/// Word-level Jaccard similarity between two texts (0.0 - 1.0)
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect()
+    };
+
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let intersection = a.intersection(&b).count() as f64;
+    let union = a.union(&b).count() as f64;
+    intersection / union
+}
+
+/// Jaccard overlap of two tag sets, ignoring case (0.0 - 1.0)
+///
+/// Two untagged expertises score 0.0: missing tags say nothing about relatedness.
+pub fn jaccard_tags(a: &[String], b: &[String]) -> f64 {
+    let a: HashSet<String> = a.iter().map(|t| t.to_lowercase()).collect();
+    let b: HashSet<String> = b.iter().map(|t| t.to_lowercase()).collect();
+
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Edit-distance similarity of two descriptions, ignoring case (0.0 - 1.0)
+///
+/// One minus the Levenshtein distance over the length of the longer text, in
+/// characters. Two missing descriptions score 0.0.
+pub fn levenshtein_description(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.trim().to_lowercase().chars().collect();
+    let b: Vec<char> = b.trim().to_lowercase().chars().collect();
+
+    let longer = a.len().max(b.len());
+    if longer == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longer as f64
+}
+
+/// Insertions, deletions and substitutions turning `a` into `b`
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How two expertises are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarityMethod {
+    /// Jaccard overlap of the tags ([`jaccard_tags`])
+    #[default]
+    Tags,
+    /// Edit distance of the descriptions ([`levenshtein_description`])
+    Description,
+}
+
+impl SimilarityMethod {
+    /// Score from which expertises count as similar unless configured otherwise
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            Self::Tags => 0.3,
+            Self::Description => 0.6,
+        }
+    }
+
+    /// Similarity of `a` and `b` (0.0 - 1.0)
+    pub fn score(self, a: &Expertise, b: &Expertise) -> f64 {
+        match self {
+            Self::Tags => jaccard_tags(a.tags(), b.tags()),
+            Self::Description => levenshtein_description(&a.description(), &b.description()),
+        }
+    }
+}
+
+/// A similarity measure and the score from which expertises count as similar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarityConfig {
+    pub method: SimilarityMethod,
+    /// Minimum score (0.0 - 1.0)
+    pub threshold: f64,
+}
+
+impl Default for SimilarityConfig {
+    fn default() -> Self {
+        Self::new(SimilarityMethod::default())
+    }
+}
+
+impl SimilarityConfig {
+    /// `method` with its default threshold
+    pub fn new(method: SimilarityMethod) -> Self {
+        Self {
+            method,
+            threshold: method.default_threshold(),
+        }
+    }
+
+    /// Use `threshold` instead of the method's default
+    pub fn with_threshold(self, threshold: f64) -> Self {
+        Self { threshold, ..self }
+    }
+
+    /// Similarity of `a` and `b` (0.0 - 1.0)
+    pub fn score(&self, a: &Expertise, b: &Expertise) -> f64 {
+        self.method.score(a, b)
+    }
+
+    /// Whether `a` and `b` score at least the threshold
+    pub fn is_similar(&self, a: &Expertise, b: &Expertise) -> bool {
+        self.score(a, b) >= self.threshold
+    }
+
+    /// The `candidates` similar to `target` with their scores, most similar first
+    ///
+    /// `target` itself (same ID and scope) is never included.
+    pub fn rank<'a>(
+        &self,
+        target: &Expertise,
+        candidates: &'a [Expertise],
+    ) -> Vec<(&'a Expertise, f64)> {
+        let mut ranked: Vec<(&Expertise, f64)> = candidates
+            .iter()
+            .filter(|c| c.id() != target.id() || c.metadata.scope != target.metadata.scope)
+            .map(|c| (c, self.score(target, c)))
+            .filter(|(_, score)| *score >= self.threshold)
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.id().cmp(b.0.id())));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_text_similarity() {
+        assert_eq!(text_similarity("Use cargo fmt", "use Cargo FMT!"), 1.0);
+        assert!(text_similarity("Run clippy before pushing", "Run clippy before each push") >= 0.5);
+        assert!(text_similarity("Prefer borrowing", "Index tables by date") < 0.2);
+    }
+
+    #[test]
+    fn test_jaccard_tags() {
+        assert_eq!(
+            jaccard_tags(&tags(&["rust", "async"]), &tags(&["Async", "rust"])),
+            1.0
+        );
+        assert_eq!(jaccard_tags(&tags(&["rust"]), &tags(&["python"])), 0.0);
+        assert_eq!(jaccard_tags(&[], &[]), 0.0);
+        // 2 shared of 4 distinct
+        assert_eq!(
+            jaccard_tags(
+                &tags(&["rust", "async", "web"]),
+                &tags(&["rust", "async", "cli"])
+            ),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_levenshtein_description() {
+        assert_eq!(levenshtein(&['k', 'i', 't'], &['s', 'i', 't']), 1);
+        assert_eq!(
+            levenshtein(
+                &"kitten".chars().collect::<Vec<_>>(),
+                &"sitting".chars().collect::<Vec<_>>()
+            ),
+            3
+        );
+
+        assert_eq!(levenshtein_description("Async Rust", " async rust "), 1.0);
+        // kitten -> sitting: 3 edits over 7 characters
+        assert!((levenshtein_description("kitten", "sitting") - 4.0 / 7.0).abs() < 1e-9);
+        assert_eq!(levenshtein_description("abc", ""), 0.0);
+        assert_eq!(levenshtein_description("", ""), 0.0);
+        // Counted in characters, not bytes
+        assert_eq!(
+            levenshtein_description("設計レビュー", "設計レビュ"),
+            1.0 - 1.0 / 6.0
+        );
+    }
+
+    #[test]
+    fn test_similarity_config() {
+        let expertise = |id: &str, description: &str, tag_list: &[&str]| {
+            let mut e = Expertise::new(id, "1.0.0");
+            e.inner.description = Some(description.to_string());
+            e.inner.tags = tags(tag_list);
+            e
+        };
+        let target = expertise("tokio", "Async runtime patterns", &["rust", "async"]);
+        let candidates = vec![
+            target.clone(),
+            expertise("axum", "Async web services", &["rust", "async", "web"]),
+            expertise("smol", "Async runtime pattern", &["rust"]),
+            expertise("django", "Python web apps", &["python"]),
+        ];
+
+        let config = SimilarityConfig::default();
+        assert_eq!(config.method, SimilarityMethod::Tags);
+        assert_eq!(config.threshold, 0.3);
+        let ranked: Vec<(&str, f64)> = config
+            .rank(&target, &candidates)
+            .into_iter()
+            .map(|(e, score)| (e.id(), score))
+            .collect();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, "axum");
+        assert!((ranked[0].1 - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(ranked[1], ("smol", 0.5));
+
+        let config = SimilarityConfig::new(SimilarityMethod::Description);
+        assert!(config.is_similar(&target, &candidates[2]));
+        assert!(!config.is_similar(&target, &candidates[1]));
+
+        let strict = SimilarityConfig::default().with_threshold(0.6);
+        let ranked = strict.rank(&target, &candidates);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].0.id(), "axum");
+    }
+}
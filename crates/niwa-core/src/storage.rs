@@ -1,9 +1,15 @@
 //! Storage operations for Expertise CRUD
 
-use crate::{Error, Expertise, Result, Scope};
+use crate::graph::Relation;
+use crate::similarity::text_similarity;
+use crate::types::LlmExpertise;
+use crate::{Error, Expertise, ExpertiseMetadata, ExpertiseStatus, GraphOperations, Result, Scope};
 use async_trait::async_trait;
-use sqlx::SqlitePool;
-use tracing::{debug, info};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::{debug, info, warn};
 
 /// Storage operations interface
 #[async_trait]
@@ -15,9 +21,14 @@ pub trait StorageOperations {
     async fn get(&self, id: &str, scope: Scope) -> Result<Option<Expertise>>;
 
     /// Update an existing expertise
+    ///
+    /// Fails with [`Error::ExpertiseLocked`] if the expertise is locked. The
+    /// stored lock state is kept regardless of the passed metadata.
     async fn update(&self, expertise: Expertise) -> Result<()>;
 
     /// Delete an expertise
+    ///
+    /// Fails with [`Error::ExpertiseLocked`] if the expertise is locked.
     async fn delete(&self, id: &str, scope: Scope) -> Result<()>;
 
     /// List all expertises in a scope
@@ -28,6 +39,286 @@ pub trait StorageOperations {
 
     /// Check if an expertise exists
     async fn exists(&self, id: &str, scope: Scope) -> Result<bool>;
+
+    /// Mark an expertise as reviewed by bumping `updated_at` only
+    ///
+    /// Unlike [`update`](Self::update), this does not save a version or
+    /// change any content. Returns the new timestamp.
+    async fn touch_only(&self, id: &str, scope: Scope) -> Result<i64>;
+
+    /// Rename a tag on every expertise that carries it
+    ///
+    /// Rewrites both the `tags` table and the serialized expertise, saving a
+    /// version and bumping the patch version and `updated_at` on each one, in
+    /// a single transaction.
+    /// Locked expertises are left untouched. Returns the number of expertises
+    /// changed.
+    async fn rename_tag_globally(
+        &self,
+        old_tag: &str,
+        new_tag: &str,
+        scope: Option<Scope>,
+    ) -> Result<usize>;
+
+    /// Replace only the description of an expertise
+    ///
+    /// Saves a version and bumps the patch version, then updates the
+    /// serialized expertise and the `description` column together in one
+    /// transaction. Fails with
+    /// [`Error::ExpertiseLocked`] if the expertise is locked.
+    async fn update_description(&self, id: &str, scope: Scope, description: &str) -> Result<()>;
+
+    /// Add tags to an expertise, keeping its existing ones
+    ///
+    /// Tags it already carries are ignored. When anything is added, saves a
+    /// version, bumps the patch version and updates the serialized expertise
+    /// and the `tags` table in one transaction. Fails with [`Error::ExpertiseLocked`] if the
+    /// expertise is locked. Returns the tags that were added.
+    async fn add_tags(&self, id: &str, scope: Scope, tags: &[String]) -> Result<Vec<String>>;
+
+    /// Collect an expertise with its tags, relations and version history
+    ///
+    /// Fails with [`Error::NotFound`] if the expertise doesn't exist.
+    async fn export_expertise(&self, id: &str, scope: Scope) -> Result<ExportBundle>;
+
+    /// Write an exported expertise with its tags and version history
+    ///
+    /// Relations are only written when the expertise at the other end is
+    /// already stored here; the rest are dropped with a warning. An existing
+    /// expertise with the same id is handled according to `on_conflict`.
+    async fn import_expertise(
+        &self,
+        bundle: ExportBundle,
+        on_conflict: ConflictPolicy,
+    ) -> Result<()>;
+}
+
+/// Result of [`Storage::resolve_prefix`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveResult {
+    /// The ID exactly, or the only ID starting with the prefix
+    Unique(String),
+    /// All IDs starting with the prefix, sorted
+    Ambiguous(Vec<String>),
+    /// No ID starts with the prefix
+    None,
+}
+
+/// Word overlap above which a removed and an added fragment count as one modified fragment
+const MODIFIED_FRAGMENT_SIMILARITY: f64 = 0.5;
+
+/// Result of [`Storage::compare_versions`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VersionComparison {
+    pub id: String,
+    pub from_version: String,
+    pub to_version: String,
+    /// Fragments only in the newer version
+    pub new_fragments: Vec<String>,
+    /// Fragments only in the older version
+    pub removed_fragments: Vec<String>,
+    /// (old, new) pairs of fragments that were reworded
+    pub modified_fragments: Vec<(String, String)>,
+    pub description_changed: bool,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+}
+
+impl VersionComparison {
+    /// Compare two versions of an expertise
+    ///
+    /// Fragments are compared by their rendered text. A removed and an added
+    /// fragment sharing most of their words ([`text_similarity`]) are reported
+    /// as one modified fragment.
+    pub fn new(from: &Expertise, to: &Expertise) -> Self {
+        let diff = from.diff(to);
+        let trimmed = |fragments: Vec<String>| -> Vec<String> {
+            fragments
+                .into_iter()
+                .map(|f| f.trim_end().to_string())
+                .collect()
+        };
+
+        let mut removed = trimmed(diff.fragments_removed);
+        let mut added = trimmed(diff.fragments_added);
+        let mut modified = Vec::new();
+        let mut i = 0;
+        while i < removed.len() {
+            let best = added
+                .iter()
+                .enumerate()
+                .map(|(j, new)| (j, text_similarity(&removed[i], new)))
+                .filter(|(_, similarity)| *similarity >= MODIFIED_FRAGMENT_SIMILARITY)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            match best {
+                Some((j, _)) => modified.push((removed.remove(i), added.remove(j))),
+                None => i += 1,
+            }
+        }
+
+        Self {
+            id: to.id().to_string(),
+            from_version: from.version().to_string(),
+            to_version: to.version().to_string(),
+            new_fragments: added,
+            removed_fragments: removed,
+            modified_fragments: modified,
+            description_changed: diff.description_changed,
+            tags_added: diff.tags_added,
+            tags_removed: diff.tags_removed,
+        }
+    }
+
+    /// Whether the versions have the same fragments, description and tags
+    pub fn is_empty(&self) -> bool {
+        self.new_fragments.is_empty()
+            && self.removed_fragments.is_empty()
+            && self.modified_fragments.is_empty()
+            && !self.description_changed
+            && self.tags_added.is_empty()
+            && self.tags_removed.is_empty()
+    }
+}
+
+/// A session log already processed by the crawler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessedSession {
+    pub file_path: String,
+    pub file_hash: String,
+    pub expertise_id: String,
+    pub processed_at: i64,
+}
+
+/// A time-based review reminder for an expertise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub id: i64,
+    pub expertise_id: String,
+    pub due_at: i64,
+    pub message: Option<String>,
+    /// Set for recurring reminders, which come due again when completed
+    pub repeat: Option<RepeatInterval>,
+    pub completed: bool,
+    pub created_at: i64,
+}
+
+/// Cached scores of an expertise, as computed by `niwa expertise score-all`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpertiseScore {
+    pub expertise_id: String,
+    /// See [`ExpertiseHealth::score`](crate::ExpertiseHealth::score)
+    pub health_score: f64,
+    /// PageRank in the relation graph (0 without relations)
+    pub pagerank_score: f64,
+    /// LLM quality rating from 0.0 to 1.0, when one was requested
+    pub quality_score: Option<f64>,
+    pub computed_at: i64,
+}
+
+/// How often a recurring [`Reminder`] comes due
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatInterval {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl RepeatInterval {
+    /// Name stored in the `repeat_interval` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepeatInterval::Daily => "daily",
+            RepeatInterval::Weekly => "weekly",
+            RepeatInterval::Monthly => "monthly",
+        }
+    }
+
+    /// Due time one interval after `due_at`
+    ///
+    /// Monthly reminders keep their day of month, clamped to the end of
+    /// shorter months.
+    pub fn next_due(&self, due_at: i64) -> i64 {
+        match self {
+            RepeatInterval::Daily => due_at + 24 * 60 * 60,
+            RepeatInterval::Weekly => due_at + 7 * 24 * 60 * 60,
+            RepeatInterval::Monthly => chrono::DateTime::from_timestamp(due_at, 0)
+                .and_then(|dt| dt.checked_add_months(chrono::Months::new(1)))
+                .map(|dt| dt.timestamp())
+                .unwrap_or(due_at + 30 * 24 * 60 * 60),
+        }
+    }
+}
+
+impl std::fmt::Display for RepeatInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RepeatInterval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "daily" => Ok(RepeatInterval::Daily),
+            "weekly" => Ok(RepeatInterval::Weekly),
+            "monthly" => Ok(RepeatInterval::Monthly),
+            _ => Err(Error::Other(format!(
+                "Invalid repeat interval: {} (expected daily, weekly or monthly)",
+                s
+            ))),
+        }
+    }
+}
+
+/// One expertise with everything attached to it, as moved between databases
+#[derive(Debug, Clone)]
+pub struct ExportBundle {
+    pub expertise: Expertise,
+    /// Relations in either direction
+    pub relations: Vec<Relation>,
+    pub tags: Vec<String>,
+    /// Saved versions as `(version, snapshot)`, oldest first
+    pub versions: Vec<(String, Expertise)>,
+}
+
+impl ExportBundle {
+    /// Id of the expertise at the other end of `relation`
+    pub fn other_end<'a>(&self, relation: &'a Relation) -> &'a str {
+        if relation.from_id == self.expertise.id() {
+            &relation.to_id
+        } else {
+            &relation.from_id
+        }
+    }
+}
+
+/// What [`StorageOperations::import_expertise`] does when the id is taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Fail with [`Error::AlreadyExists`]
+    #[default]
+    Fail,
+    /// Keep the existing expertise and import nothing
+    Skip,
+    /// Replace the existing expertise, keeping relations already stored
+    Overwrite,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(ConflictPolicy::Fail),
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            _ => Err(Error::Other(format!(
+                "Invalid conflict policy: {} (expected fail, skip or overwrite)",
+                s
+            ))),
+        }
+    }
 }
 
 /// Storage implementation
@@ -59,40 +350,11 @@ impl StorageOperations for Storage {
             });
         }
 
-        // Serialize expertise
-        let data_json = expertise.to_json()?;
-        let description = expertise.description();
-
-        // Insert into expertises table
-        sqlx::query(
-            r#"
-            INSERT INTO expertises (id, version, scope, created_at, updated_at, data_json, description)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(id)
-        .bind(expertise.version())
-        .bind(scope.as_str())
-        .bind(expertise.metadata.created_at)
-        .bind(expertise.metadata.updated_at)
-        .bind(&data_json)
-        .bind(&description)
-        .execute(&self.pool)
-        .await?;
-
-        // Insert tags
-        for tag in expertise.tags() {
-            sqlx::query(
-                r#"
-                INSERT INTO tags (expertise_id, tag)
-                VALUES (?, ?)
-                "#,
-            )
-            .bind(id)
-            .bind(tag)
-            .execute(&self.pool)
-            .await?;
-        }
+        // The insert itself is the authoritative check: a concurrent writer
+        // may create the same id between `exists` and here.
+        let mut tx = self.pool.begin().await?;
+        insert_expertise(&mut tx, &expertise).await?;
+        tx.commit().await?;
 
         debug!("Created expertise: {}", id);
         Ok(())
@@ -105,7 +367,7 @@ impl StorageOperations for Storage {
             r#"
             SELECT data_json
             FROM expertises
-            WHERE id = ? AND scope = ?
+            WHERE id = ? AND scope = ? AND malformed = 0
             "#,
         )
         .bind(id)
@@ -122,86 +384,12 @@ impl StorageOperations for Storage {
         }
     }
 
-    async fn update(&self, mut expertise: Expertise) -> Result<()> {
-        let id = expertise.id().to_string();
-        let scope = expertise.metadata.scope;
-
-        info!("Updating expertise: {} (scope: {})", id, scope);
-
-        // Check if exists
-        if !self.exists(&id, scope).await? {
-            return Err(Error::NotFound {
-                id: id.clone(),
-                scope: scope.to_string(),
-            });
-        }
-
-        // Get existing expertise for versioning
-        if let Some(existing) = self.get(&id, scope).await? {
-            // Save old version
-            self.save_version(&existing).await?;
-        }
-
-        // Serialize expertise
-        expertise.metadata.touch(); // Update timestamp
-        let data_json = expertise.to_json()?;
-        let description = expertise.description();
-        let version = expertise.version().to_string();
-
-        // Update expertises table
-        sqlx::query(
-            r#"
-            UPDATE expertises
-            SET version = ?, updated_at = ?, data_json = ?, description = ?
-            WHERE id = ? AND scope = ?
-            "#,
-        )
-        .bind(&version)
-        .bind(expertise.metadata.updated_at)
-        .bind(&data_json)
-        .bind(&description)
-        .bind(&id)
-        .bind(scope.as_str())
-        .execute(&self.pool)
-        .await?;
-
-        // Update tags (delete old, insert new)
-        sqlx::query("DELETE FROM tags WHERE expertise_id = ?")
-            .bind(&id)
-            .execute(&self.pool)
-            .await?;
-
-        for tag in expertise.tags() {
-            sqlx::query("INSERT INTO tags (expertise_id, tag) VALUES (?, ?)")
-                .bind(&id)
-                .bind(tag)
-                .execute(&self.pool)
-                .await?;
-        }
-
-        debug!("Updated expertise: {}", id);
-        Ok(())
+    async fn update(&self, expertise: Expertise) -> Result<()> {
+        self.update_checked(expertise, false).await
     }
 
     async fn delete(&self, id: &str, scope: Scope) -> Result<()> {
-        info!("Deleting expertise: {} (scope: {})", id, scope);
-
-        let result = sqlx::query("DELETE FROM expertises WHERE id = ? AND scope = ?")
-            .bind(id)
-            .bind(scope.as_str())
-            .execute(&self.pool)
-            .await?;
-
-        if result.rows_affected() == 0 {
-            return Err(Error::NotFound {
-                id: id.to_string(),
-                scope: scope.to_string(),
-            });
-        }
-
-        // Tags are automatically deleted by CASCADE
-        debug!("Deleted expertise: {}", id);
-        Ok(())
+        self.delete_checked(id, scope, false).await
     }
 
     async fn list(&self, scope: Scope) -> Result<Vec<Expertise>> {
@@ -211,7 +399,7 @@ impl StorageOperations for Storage {
             r#"
             SELECT data_json
             FROM expertises
-            WHERE scope = ?
+            WHERE scope = ? AND malformed = 0
             ORDER BY updated_at DESC
             "#,
         )
@@ -234,6 +422,7 @@ impl StorageOperations for Storage {
             r#"
             SELECT data_json
             FROM expertises
+            WHERE malformed = 0
             ORDER BY scope, updated_at DESC
             "#,
         )
@@ -263,100 +452,1526 @@ impl StorageOperations for Storage {
 
         Ok(row.0 > 0)
     }
-}
 
-impl Storage {
-    /// Save a version to the versions table
-    async fn save_version(&self, expertise: &Expertise) -> Result<()> {
-        let id = expertise.id();
-        let version = expertise.version();
-        let data_json = expertise.to_json()?;
-        let created_at = chrono::Utc::now().timestamp();
+    async fn touch_only(&self, id: &str, scope: Scope) -> Result<i64> {
+        info!("Touching expertise: {} (scope: {})", id, scope);
 
-        sqlx::query(
+        let updated_at = chrono::Utc::now().timestamp();
+
+        // Keep the serialized metadata in sync with the column
+        let result = sqlx::query(
             r#"
-            INSERT OR REPLACE INTO versions (expertise_id, version, created_at, data_json)
-            VALUES (?, ?, ?, ?)
+            UPDATE expertises
+            SET updated_at = ?, data_json = json_set(data_json, '$.updated_at', ?)
+            WHERE id = ? AND scope = ?
             "#,
         )
+        .bind(updated_at)
+        .bind(updated_at)
         .bind(id)
-        .bind(version)
-        .bind(created_at)
-        .bind(&data_json)
+        .bind(scope.as_str())
         .execute(&self.pool)
         .await?;
 
-        debug!("Saved version: {} v{}", id, version);
-        Ok(())
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        }
+
+        debug!("Touched expertise: {}", id);
+        Ok(updated_at)
     }
 
-    /// Get a specific version
-    pub async fn get_version(&self, id: &str, version: &str) -> Result<Option<Expertise>> {
-        debug!("Getting expertise version: {} v{}", id, version);
+    async fn rename_tag_globally(
+        &self,
+        old_tag: &str,
+        new_tag: &str,
+        scope: Option<Scope>,
+    ) -> Result<usize> {
+        info!("Renaming tag '{}' to '{}'", old_tag, new_tag);
 
-        let row: Option<(String,)> = sqlx::query_as(
+        if old_tag == new_tag {
+            return Ok(0);
+        }
+
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let mut sql = String::from(
             r#"
-            SELECT data_json
-            FROM versions
-            WHERE expertise_id = ? AND version = ?
+            SELECT e.data_json
+            FROM expertises e
+            INNER JOIN tags t ON t.expertise_id = e.id
+            WHERE t.tag = ? AND e.locked = 0 AND e.malformed = 0
             "#,
-        )
-        .bind(id)
-        .bind(version)
-        .fetch_optional(&self.pool)
-        .await?;
+        );
+        if scope.is_some() {
+            sql.push_str(" AND e.scope = ?");
+        }
 
-        match row {
-            Some((data_json,)) => Ok(Some(Expertise::from_json(&data_json)?)),
-            None => Ok(None),
+        let mut query_builder = sqlx::query_as::<_, (String,)>(&sql).bind(old_tag);
+        if let Some(scope) = scope {
+            query_builder = query_builder.bind(scope.as_str());
+        }
+        let rows = query_builder.fetch_all(&mut *tx).await?;
+
+        for (data_json,) in &rows {
+            let mut expertise = Expertise::from_json(data_json)?;
+            snapshot_and_bump(&mut tx, &mut expertise).await?;
+
+            let mut tags: Vec<String> = Vec::with_capacity(expertise.inner.tags.len());
+            for tag in expertise.inner.tags.drain(..) {
+                let tag = if tag == old_tag {
+                    new_tag.to_string()
+                } else {
+                    tag
+                };
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            expertise.inner.tags = tags;
+            expertise.metadata.touch();
+
+            let id = expertise.id();
+
+            // Tags first, so the FTS update trigger on expertises sees the new set
+            sqlx::query("DELETE FROM tags WHERE expertise_id = ? AND tag = ?")
+                .bind(id)
+                .bind(old_tag)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("INSERT OR IGNORE INTO tags (expertise_id, tag) VALUES (?, ?)")
+                .bind(id)
+                .bind(new_tag)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE expertises
+                SET version = ?, updated_at = ?, data_json = ?
+                WHERE id = ? AND scope = ?
+                "#,
+            )
+            .bind(expertise.version())
+            .bind(expertise.metadata.updated_at)
+            .bind(expertise.to_json()?)
+            .bind(id)
+            .bind(expertise.metadata.scope.as_str())
+            .execute(&mut *tx)
+            .await?;
         }
+
+        tx.commit().await?;
+
+        debug!("Renamed tag on {} expertises", rows.len());
+        Ok(rows.len())
     }
 
-    /// List all versions of an expertise
-    pub async fn list_versions(&self, id: &str) -> Result<Vec<String>> {
-        debug!("Listing versions for expertise: {}", id);
+    async fn update_description(&self, id: &str, scope: Scope, description: &str) -> Result<()> {
+        info!("Updating description: {} (scope: {})", id, scope);
 
-        let rows: Vec<(String,)> = sqlx::query_as(
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT data_json FROM expertises WHERE id = ? AND scope = ?")
+                .bind(id)
+                .bind(scope.as_str())
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((data_json,)) = row else {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        };
+
+        let mut expertise = Expertise::from_json(&data_json)?;
+        if expertise.metadata.locked {
+            return Err(Error::ExpertiseLocked { id: id.to_string() });
+        }
+        snapshot_and_bump(&mut tx, &mut expertise).await?;
+
+        expertise.inner.description = Some(description.to_string());
+        expertise.metadata.touch();
+
+        sqlx::query(
             r#"
-            SELECT version
-            FROM versions
-            WHERE expertise_id = ?
-            ORDER BY created_at DESC
+            UPDATE expertises
+            SET version = ?, updated_at = ?, data_json = ?, description = ?
+            WHERE id = ? AND scope = ?
             "#,
         )
+        .bind(expertise.version())
+        .bind(expertise.metadata.updated_at)
+        .bind(expertise.to_json()?)
+        .bind(description)
         .bind(id)
-        .fetch_all(&self.pool)
+        .bind(scope.as_str())
+        .execute(&mut *tx)
         .await?;
 
-        Ok(rows.into_iter().map(|(v,)| v).collect())
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::Database;
-    use tempfile::TempDir;
+        tx.commit().await?;
 
-    async fn setup_db() -> (Database, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("test.db");
-        let db = Database::open(&db_path).await.unwrap();
-        (db, temp_dir)
+        debug!("Updated description: {}", id);
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_create_and_get() {
-        let (db, _temp) = setup_db().await;
-        let storage = db.storage();
+    async fn add_tags(&self, id: &str, scope: Scope, tags: &[String]) -> Result<Vec<String>> {
+        info!("Adding tags to {} (scope: {}): {:?}", id, scope, tags);
 
-        let mut expertise = Expertise::new("test-id", "1.0.0");
-        expertise.metadata.scope = Scope::Personal;
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
 
-        storage.create(expertise.clone()).await.unwrap();
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT data_json FROM expertises WHERE id = ? AND scope = ?")
+                .bind(id)
+                .bind(scope.as_str())
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((data_json,)) = row else {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        };
 
-        let retrieved = storage.get("test-id", Scope::Personal).await.unwrap();
-        assert!(retrieved.is_some());
+        let mut expertise = Expertise::from_json(&data_json)?;
+        if expertise.metadata.locked {
+            return Err(Error::ExpertiseLocked { id: id.to_string() });
+        }
+
+        let mut added: Vec<String> = Vec::new();
+        for tag in tags {
+            if !expertise.inner.tags.contains(tag) && !added.contains(tag) {
+                added.push(tag.clone());
+            }
+        }
+        if added.is_empty() {
+            return Ok(added);
+        }
+
+        snapshot_and_bump(&mut tx, &mut expertise).await?;
+        expertise.inner.tags.extend(added.iter().cloned());
+        expertise.metadata.touch();
+
+        // Tags first, so the FTS update trigger on expertises sees the new set
+        for tag in &added {
+            sqlx::query("INSERT OR IGNORE INTO tags (expertise_id, tag) VALUES (?, ?)")
+                .bind(id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query(
+            r#"
+            UPDATE expertises
+            SET version = ?, updated_at = ?, data_json = ?
+            WHERE id = ? AND scope = ?
+            "#,
+        )
+        .bind(expertise.version())
+        .bind(expertise.metadata.updated_at)
+        .bind(expertise.to_json()?)
+        .bind(id)
+        .bind(scope.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        debug!("Added {} tags to {}", added.len(), id);
+        Ok(added)
+    }
+
+    async fn export_expertise(&self, id: &str, scope: Scope) -> Result<ExportBundle> {
+        debug!("Exporting expertise: {} (scope: {})", id, scope);
+
+        let expertise = self.get(id, scope).await?.ok_or_else(|| Error::NotFound {
+            id: id.to_string(),
+            scope: scope.to_string(),
+        })?;
+
+        let tags: Vec<(String,)> =
+            sqlx::query_as("SELECT tag FROM tags WHERE expertise_id = ? ORDER BY tag")
+                .bind(id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT version, data_json
+            FROM versions
+            WHERE expertise_id = ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut versions = Vec::with_capacity(rows.len());
+        for (version, data_json) in rows {
+            versions.push((version, Expertise::from_json(&data_json)?));
+        }
+
+        let relations = GraphOperations::new(self.pool.clone())
+            .get_all_relations(id)
+            .await?;
+
+        Ok(ExportBundle {
+            expertise,
+            relations,
+            tags: tags.into_iter().map(|(tag,)| tag).collect(),
+            versions,
+        })
+    }
+
+    async fn import_expertise(
+        &self,
+        bundle: ExportBundle,
+        on_conflict: ConflictPolicy,
+    ) -> Result<()> {
+        let id = bundle.expertise.id().to_string();
+        info!(
+            "Importing expertise: {} ({:?} on conflict)",
+            id, on_conflict
+        );
+
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let existing: Option<(String,)> =
+            sqlx::query_as("SELECT scope FROM expertises WHERE id = ?")
+                .bind(&id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        match (existing, on_conflict) {
+            (None, _) => insert_expertise(&mut tx, &bundle.expertise).await?,
+            (Some((scope,)), ConflictPolicy::Fail) => {
+                return Err(Error::AlreadyExists { id, scope });
+            }
+            (Some(_), ConflictPolicy::Skip) => {
+                debug!("Skipped existing expertise: {}", id);
+                return Ok(());
+            }
+            (Some(_), ConflictPolicy::Overwrite) => {
+                replace_expertise(&mut tx, &bundle.expertise).await?;
+            }
+        }
+
+        // The tags table mirrors the expertise, but keep any extra exported tags
+        for tag in &bundle.tags {
+            sqlx::query("INSERT OR IGNORE INTO tags (expertise_id, tag) VALUES (?, ?)")
+                .bind(&id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for (version, snapshot) in &bundle.versions {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO versions (expertise_id, version, created_at, data_json)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )
+            .bind(&id)
+            .bind(version)
+            .bind(snapshot.metadata.updated_at)
+            .bind(snapshot.to_json()?)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for relation in &bundle.relations {
+            let other = bundle.other_end(relation);
+            let known: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM expertises WHERE id = ?")
+                .bind(other)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if known.is_none() {
+                warn!(
+                    "Dropping relation {} -[{}]-> {}: {} is not in this database",
+                    relation.from_id, relation.relation_type, relation.to_id, other
+                );
+                continue;
+            }
+            insert_relation(&mut tx, relation).await?;
+        }
+
+        tx.commit().await?;
+
+        debug!("Imported expertise: {}", id);
+        Ok(())
+    }
+}
+
+impl Storage {
+    /// Update an expertise even if it is locked (its lock is kept)
+    pub async fn update_forced(&self, expertise: Expertise) -> Result<()> {
+        self.update_checked(expertise, true).await
+    }
+
+    /// Delete an expertise even if it is locked
+    pub async fn delete_forced(&self, id: &str, scope: Scope) -> Result<()> {
+        self.delete_checked(id, scope, true).await
+    }
+
+    async fn update_checked(&self, mut expertise: Expertise, force: bool) -> Result<()> {
+        let id = expertise.id().to_string();
+        let scope = expertise.metadata.scope;
+
+        info!("Updating expertise: {} (scope: {})", id, scope);
+
+        // The snapshot, the row and its tags change together or not at all
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT data_json FROM expertises WHERE id = ? AND scope = ? AND malformed = 0",
+        )
+        .bind(&id)
+        .bind(scope.as_str())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((existing_json,)) = row else {
+            return Err(Error::NotFound {
+                id: id.clone(),
+                scope: scope.to_string(),
+            });
+        };
+        let existing = Expertise::from_json(&existing_json)?;
+        if existing.metadata.locked && !force {
+            return Err(Error::ExpertiseLocked { id });
+        }
+
+        // Save old version
+        insert_version(&mut tx, &existing).await?;
+
+        // Lock state is only changed by lock()/unlock()
+        expertise.metadata.locked = existing.metadata.locked;
+        expertise.metadata.locked_at = existing.metadata.locked_at;
+        expertise.metadata.locked_by_reason = existing.metadata.locked_by_reason;
+
+        // So is the review status, by set_status()
+        expertise.metadata.status = existing.metadata.status;
+
+        // Serialize expertise
+        expertise.metadata.touch(); // Update timestamp
+        let data_json = expertise.to_json()?;
+        let description = expertise.description();
+        let version = expertise.version().to_string();
+
+        // Update expertises table
+        sqlx::query(
+            r#"
+            UPDATE expertises
+            SET version = ?, updated_at = ?, data_json = ?, description = ?
+            WHERE id = ? AND scope = ?
+            "#,
+        )
+        .bind(&version)
+        .bind(expertise.metadata.updated_at)
+        .bind(&data_json)
+        .bind(&description)
+        .bind(&id)
+        .bind(scope.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        // Update tags (delete old, insert new)
+        sqlx::query("DELETE FROM tags WHERE expertise_id = ?")
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+
+        for tag in expertise.tags() {
+            sqlx::query("INSERT INTO tags (expertise_id, tag) VALUES (?, ?)")
+                .bind(&id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        debug!("Updated expertise: {}", id);
+        Ok(())
+    }
+
+    async fn delete_checked(&self, id: &str, scope: Scope, force: bool) -> Result<()> {
+        info!("Deleting expertise: {} (scope: {})", id, scope);
+
+        if !force && self.is_locked(id, scope).await? {
+            return Err(Error::ExpertiseLocked { id: id.to_string() });
+        }
+
+        let result = sqlx::query("DELETE FROM expertises WHERE id = ? AND scope = ?")
+            .bind(id)
+            .bind(scope.as_str())
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        }
+
+        // Tags are automatically deleted by CASCADE
+        debug!("Deleted expertise: {}", id);
+        Ok(())
+    }
+
+    /// Resolve a unique ID prefix (e.g. `rust-err` → `rust-error-handling`)
+    ///
+    /// An exact ID match always wins, even if longer IDs share the prefix.
+    /// With `scope`, only IDs in that scope are considered.
+    pub async fn resolve_prefix(
+        &self,
+        prefix: &str,
+        scope: Option<Scope>,
+    ) -> Result<ResolveResult> {
+        let pattern = format!(
+            "{}%",
+            prefix
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+
+        let mut sql = String::from("SELECT id FROM expertises WHERE id LIKE ? ESCAPE '\\'");
+        if scope.is_some() {
+            sql.push_str(" AND scope = ?");
+        }
+        sql.push_str(" ORDER BY id");
+
+        let mut query = sqlx::query_as::<_, (String,)>(&sql).bind(&pattern);
+        if let Some(scope) = scope {
+            query = query.bind(scope.as_str());
+        }
+        // LIKE is case-insensitive for ASCII, so keep only true prefixes
+        let ids: Vec<String> = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(id,)| id)
+            .filter(|id| id.starts_with(prefix))
+            .collect();
+
+        Ok(if ids.iter().any(|id| id == prefix) {
+            ResolveResult::Unique(prefix.to_string())
+        } else {
+            match ids.len() {
+                0 => ResolveResult::None,
+                1 => ResolveResult::Unique(ids.into_iter().next().unwrap_or_default()),
+                _ => ResolveResult::Ambiguous(ids),
+            }
+        })
+    }
+
+    /// Find rows whose `data_json` doesn't parse as an [`Expertise`]
+    ///
+    /// Every row is checked, including those already flagged as malformed.
+    /// Returns `(id, scope, error_message)` for each failure, ordered by id.
+    pub async fn scan_for_malformed(&self) -> Result<Vec<(String, String, String)>> {
+        debug!("Scanning for malformed data_json");
+
+        let rows: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT id, scope, data_json FROM expertises ORDER BY id")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, scope, data_json)| {
+                Expertise::from_json(&data_json)
+                    .err()
+                    .map(|e| (id, scope, e.to_string()))
+            })
+            .collect())
+    }
+
+    /// Rebuild a malformed row from its bare expertise content
+    ///
+    /// Parses `data_json` without the NIWA metadata and takes scope,
+    /// timestamps, lock state and status from the row's columns. On success the
+    /// repaired JSON is written back and the row is visible again. Otherwise
+    /// the row is flagged `malformed` so queries skip it. Returns whether the
+    /// repair succeeded.
+    pub async fn repair_malformed(&self, id: &str) -> Result<bool> {
+        info!("Repairing data_json: {}", id);
+
+        type Row = (
+            String,
+            String,
+            i64,
+            i64,
+            bool,
+            Option<i64>,
+            Option<String>,
+            String,
+        );
+        let row: Option<Row> = sqlx::query_as(
+            r#"
+            SELECT data_json, scope, created_at, updated_at, locked, locked_at, locked_by_reason,
+                   status
+            FROM expertises
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((
+            data_json,
+            scope,
+            created_at,
+            updated_at,
+            locked,
+            locked_at,
+            locked_by_reason,
+            status,
+        )) = row
+        else {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: "any".to_string(),
+            });
+        };
+
+        let inner = match serde_json::from_str::<LlmExpertise>(&data_json) {
+            Ok(inner) if inner.id == id => inner,
+            parsed => {
+                if let Err(e) = parsed {
+                    debug!("Could not repair {}: {}", id, e);
+                }
+                sqlx::query("UPDATE expertises SET malformed = 1 WHERE id = ?")
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await?;
+                return Ok(false);
+            }
+        };
+        let expertise = Expertise {
+            inner,
+            metadata: ExpertiseMetadata {
+                scope: scope.parse()?,
+                created_at,
+                updated_at,
+                locked,
+                locked_at,
+                locked_by_reason,
+                status: status.parse()?,
+                ..Default::default()
+            },
+        };
+
+        sqlx::query(
+            "UPDATE expertises SET data_json = ?, description = ?, malformed = 0 WHERE id = ?",
+        )
+        .bind(expertise.to_json()?)
+        .bind(expertise.description())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        debug!("Repaired data_json: {}", id);
+        Ok(true)
+    }
+
+    /// Whether an expertise is locked (false if it doesn't exist)
+    pub async fn is_locked(&self, id: &str, scope: Scope) -> Result<bool> {
+        let row: Option<(bool,)> =
+            sqlx::query_as("SELECT locked FROM expertises WHERE id = ? AND scope = ?")
+                .bind(id)
+                .bind(scope.as_str())
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.is_some_and(|(locked,)| locked))
+    }
+
+    /// Lock an expertise against updates and deletion
+    ///
+    /// Locking an already locked expertise replaces its reason and timestamp.
+    pub async fn lock(&self, id: &str, scope: Scope, reason: Option<&str>) -> Result<()> {
+        info!("Locking expertise: {} (scope: {})", id, scope);
+
+        let locked_at = chrono::Utc::now().timestamp();
+        let result = sqlx::query(
+            r#"
+            UPDATE expertises
+            SET locked = 1, locked_at = ?, locked_by_reason = ?,
+                data_json = json_set(
+                    json_remove(data_json, '$.locked_by_reason'),
+                    '$.locked', json('true'), '$.locked_at', ?
+                )
+            WHERE id = ? AND scope = ?
+            "#,
+        )
+        .bind(locked_at)
+        .bind(reason)
+        .bind(locked_at)
+        .bind(id)
+        .bind(scope.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        }
+
+        if let Some(reason) = reason {
+            sqlx::query(
+                r#"
+                UPDATE expertises
+                SET data_json = json_set(data_json, '$.locked_by_reason', ?)
+                WHERE id = ? AND scope = ?
+                "#,
+            )
+            .bind(reason)
+            .bind(id)
+            .bind(scope.as_str())
+            .execute(&self.pool)
+            .await?;
+        }
+
+        debug!("Locked expertise: {}", id);
+        Ok(())
+    }
+
+    /// Remove the lock from an expertise
+    pub async fn unlock(&self, id: &str, scope: Scope) -> Result<()> {
+        info!("Unlocking expertise: {} (scope: {})", id, scope);
+
+        let result = sqlx::query(
+            r#"
+            UPDATE expertises
+            SET locked = 0, locked_at = NULL, locked_by_reason = NULL,
+                data_json = json_remove(data_json, '$.locked', '$.locked_at', '$.locked_by_reason')
+            WHERE id = ? AND scope = ?
+            "#,
+        )
+        .bind(id)
+        .bind(scope.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        }
+
+        debug!("Unlocked expertise: {}", id);
+        Ok(())
+    }
+
+    /// Set the review status of an expertise
+    ///
+    /// Like locking, this is allowed on locked expertises and does not save
+    /// a version or change `updated_at`.
+    pub async fn set_status(&self, id: &str, scope: Scope, status: ExpertiseStatus) -> Result<()> {
+        info!(
+            "Setting status of expertise: {} (scope: {}) to {}",
+            id, scope, status
+        );
+
+        let result = sqlx::query(
+            r#"
+            UPDATE expertises
+            SET status = ?, data_json = json_set(data_json, '$.status', ?)
+            WHERE id = ? AND scope = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(status.as_str())
+        .bind(id)
+        .bind(scope.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        }
+
+        debug!("Set status of expertise: {}", id);
+        Ok(())
+    }
+
+    /// Get a specific version
+    pub async fn get_version(&self, id: &str, version: &str) -> Result<Option<Expertise>> {
+        debug!("Getting expertise version: {} v{}", id, version);
+
+        let row: Option<(String,)> = sqlx::query_as(
+            r#"
+            SELECT data_json
+            FROM versions
+            WHERE expertise_id = ? AND version = ?
+            "#,
+        )
+        .bind(id)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some((data_json,)) => Ok(Some(Expertise::from_json(&data_json)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Compare two versions of an expertise
+    ///
+    /// Versions are looked up in the history, or in the current expertise
+    /// when it has that version.
+    pub async fn compare_versions(
+        &self,
+        id: &str,
+        v1: &str,
+        v2: &str,
+    ) -> Result<VersionComparison> {
+        let from = self.version_or_current(id, v1).await?;
+        let to = self.version_or_current(id, v2).await?;
+        Ok(VersionComparison::new(&from, &to))
+    }
+
+    /// The current expertise if it has `version`, else that version from the history
+    async fn version_or_current(&self, id: &str, version: &str) -> Result<Expertise> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT data_json FROM expertises WHERE id = ? AND version = ?")
+                .bind(id)
+                .bind(version)
+                .fetch_optional(&self.pool)
+                .await?;
+        if let Some((data_json,)) = row {
+            return Expertise::from_json(&data_json);
+        }
+
+        self.get_version(id, version)
+            .await?
+            .ok_or_else(|| Error::VersionNotFound {
+                id: id.to_string(),
+                version: version.to_string(),
+            })
+    }
+
+    /// Store expertises generated from a session log and record the log as processed
+    ///
+    /// Runs in a single write transaction so concurrent crawler workers are
+    /// serialized:
+    /// - If another log with the same content hash was already recorded, no
+    ///   expertise is created and the session points at the existing one.
+    /// - An id that is already taken gets a numeric suffix (`-2`, `-3`, ...)
+    ///   instead of failing.
+    ///
+    /// Returns the ids the session is linked to (the first one is recorded in
+    /// `processed_sessions`).
+    pub async fn record_processed_session(
+        &self,
+        file_path: &str,
+        file_hash: &str,
+        expertises: Vec<Expertise>,
+    ) -> Result<Vec<String>> {
+        if expertises.is_empty() {
+            return Err(Error::Other(format!(
+                "No expertises to record for {}",
+                file_path
+            )));
+        }
+
+        // Take the write lock up front so the checks below cannot race
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let duplicate: Option<(String,)> = sqlx::query_as(
+            r#"
+            SELECT expertise_id
+            FROM processed_sessions
+            WHERE file_hash = ? AND file_path != ?
+            LIMIT 1
+            "#,
+        )
+        .bind(file_hash)
+        .bind(file_path)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let ids = match duplicate {
+            Some((existing_id,)) => {
+                debug!(
+                    "Session {} has the same content as an earlier one; linking to {}",
+                    file_path, existing_id
+                );
+                vec![existing_id]
+            }
+            None => {
+                let mut ids = Vec::with_capacity(expertises.len());
+                for mut expertise in expertises {
+                    let id = available_id(&mut tx, expertise.id()).await?;
+                    if id != expertise.id() {
+                        info!("Expertise id {} is taken, using {}", expertise.id(), id);
+                        expertise.inner.id = id.clone();
+                    }
+                    insert_expertise(&mut tx, &expertise).await?;
+                    ids.push(id);
+                }
+                ids
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO processed_sessions (file_path, file_hash, expertise_id, processed_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(file_path)
+        .bind(file_hash)
+        .bind(&ids[0])
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        debug!("Recorded processed session: {} -> {:?}", file_path, ids);
+        Ok(ids)
+    }
+
+    /// Store the transcript fingerprint of a processed session
+    ///
+    /// See [`crate::fingerprint`]. Sessions without one are never reported
+    /// as near-duplicates.
+    pub async fn set_session_fingerprint(&self, file_path: &str, fingerprint: u64) -> Result<()> {
+        sqlx::query("UPDATE processed_sessions SET fingerprint = ? WHERE file_path = ?")
+            // SQLite integers are signed; the bits round-trip unchanged
+            .bind(fingerprint as i64)
+            .bind(file_path)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Find the processed session closest to `fingerprint`, if it is within
+    /// `max_distance` differing bits
+    ///
+    /// The session recorded for `file_path` itself is ignored. Returns the
+    /// session and its distance.
+    pub async fn find_near_duplicate_session(
+        &self,
+        file_path: &str,
+        fingerprint: u64,
+        max_distance: u32,
+    ) -> Result<Option<(ProcessedSession, u32)>> {
+        let rows: Vec<(String, String, String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT file_path, file_hash, expertise_id, processed_at, fingerprint
+            FROM processed_sessions
+            WHERE fingerprint IS NOT NULL AND file_path != ?
+            "#,
+        )
+        .bind(file_path)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(file_path, file_hash, expertise_id, processed_at, other)| {
+                    let session = ProcessedSession {
+                        file_path,
+                        file_hash,
+                        expertise_id,
+                        processed_at,
+                    };
+                    (
+                        session,
+                        crate::fingerprint::hamming_distance(fingerprint, other as u64),
+                    )
+                },
+            )
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by_key(|(_, distance)| *distance))
+    }
+
+    /// Record a session skipped as a near-duplicate of `original`
+    ///
+    /// The session points at the original's expertise, like an exact
+    /// duplicate, so later crawls skip it by hash.
+    pub async fn record_near_duplicate_session(
+        &self,
+        file_path: &str,
+        file_hash: &str,
+        fingerprint: u64,
+        original: &ProcessedSession,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO processed_sessions (file_path, file_hash, expertise_id, processed_at, fingerprint)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(file_path)
+        .bind(file_hash)
+        .bind(&original.expertise_id)
+        .bind(chrono::Utc::now().timestamp())
+        .bind(fingerprint as i64)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "Recorded near-duplicate session: {} -> {}",
+            file_path, original.file_path
+        );
+        Ok(())
+    }
+
+    /// List all processed session records
+    pub async fn list_processed_sessions(&self) -> Result<Vec<ProcessedSession>> {
+        debug!("Listing processed sessions");
+
+        let rows: Vec<(String, String, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT file_path, file_hash, expertise_id, processed_at
+            FROM processed_sessions
+            ORDER BY processed_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(file_path, file_hash, expertise_id, processed_at)| ProcessedSession {
+                    file_path,
+                    file_hash,
+                    expertise_id,
+                    processed_at,
+                },
+            )
+            .collect())
+    }
+
+    /// Delete processed session records processed before `before`
+    ///
+    /// Checks against the session files themselves (missing or changed) are
+    /// left to the caller, which can pass the matching paths to
+    /// [`delete_processed_sessions`](Self::delete_processed_sessions).
+    /// Returns the number of deleted records.
+    pub async fn clean_processed_sessions(&self, before: i64) -> Result<usize> {
+        info!("Cleaning processed sessions before {}", before);
+
+        let result = sqlx::query("DELETE FROM processed_sessions WHERE processed_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        debug!(
+            "Deleted {} processed session records",
+            result.rows_affected()
+        );
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Delete processed session records by file path
+    ///
+    /// Deleted files will be picked up again by the next crawl. Returns the
+    /// number of deleted records.
+    pub async fn delete_processed_sessions(&self, file_paths: &[String]) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut deleted = 0;
+
+        for file_path in file_paths {
+            let result = sqlx::query("DELETE FROM processed_sessions WHERE file_path = ?")
+                .bind(file_path)
+                .execute(&mut *tx)
+                .await?;
+            deleted += result.rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+
+        debug!("Deleted {} processed session records", deleted);
+        Ok(deleted)
+    }
+
+    /// Add a review reminder for an expertise, returning its id
+    pub async fn add_reminder(
+        &self,
+        expertise_id: &str,
+        due_at: i64,
+        message: Option<&str>,
+        repeat: Option<RepeatInterval>,
+    ) -> Result<i64> {
+        info!("Adding reminder for {} (due: {})", expertise_id, due_at);
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reminders (expertise_id, due_at, message, repeat_interval, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(expertise_id)
+        .bind(due_at)
+        .bind(message)
+        .bind(repeat.map(|r| r.as_str()))
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// List pending reminders, soonest first
+    ///
+    /// With `due_before`, only reminders due at or before that time are
+    /// returned.
+    pub async fn list_reminders(&self, due_before: Option<i64>) -> Result<Vec<Reminder>> {
+        debug!("Listing reminders (due before: {:?})", due_before);
+
+        let rows: Vec<(i64, String, i64, Option<String>, Option<String>, bool, i64)> =
+            sqlx::query_as(
+                r#"
+                SELECT id, expertise_id, due_at, message, repeat_interval, completed, created_at
+                FROM reminders
+                WHERE completed = 0 AND (? IS NULL OR due_at <= ?)
+                ORDER BY due_at, id
+                "#,
+            )
+            .bind(due_before)
+            .bind(due_before)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, expertise_id, due_at, message, repeat, completed, created_at)| Reminder {
+                    id,
+                    expertise_id,
+                    due_at,
+                    message,
+                    repeat: repeat.and_then(|r| r.parse().ok()),
+                    completed,
+                    created_at,
+                },
+            )
+            .collect())
+    }
+
+    /// Complete a pending reminder
+    ///
+    /// A recurring reminder stays pending and moves to its next due time,
+    /// which is returned. Fails with [`Error::ReminderNotFound`] if there is
+    /// no pending reminder with that id.
+    pub async fn complete_reminder(&self, id: i64) -> Result<Option<i64>> {
+        info!("Completing reminder: {}", id);
+
+        let row: Option<(i64, Option<String>)> = sqlx::query_as(
+            "SELECT due_at, repeat_interval FROM reminders WHERE id = ? AND completed = 0",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let (due_at, repeat) = row.ok_or(Error::ReminderNotFound(id))?;
+
+        match repeat.and_then(|r| r.parse::<RepeatInterval>().ok()) {
+            Some(repeat) => {
+                let next_due = repeat.next_due(due_at);
+                sqlx::query("UPDATE reminders SET due_at = ? WHERE id = ?")
+                    .bind(next_due)
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await?;
+                debug!("Rescheduled reminder {} to {}", id, next_due);
+                Ok(Some(next_due))
+            }
+            None => {
+                sqlx::query("UPDATE reminders SET completed = 1 WHERE id = ?")
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await?;
+                debug!("Completed reminder: {}", id);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record where an expertise was published, or clear it with `None`
+    ///
+    /// Only the publication fields in `data_json` change; no version is saved
+    /// and `updated_at` is left alone.
+    pub async fn set_published(
+        &self,
+        id: &str,
+        scope: Scope,
+        published: Option<(&str, i64)>,
+    ) -> Result<()> {
+        let result = match published {
+            Some((url, published_at)) => {
+                sqlx::query(
+                    r#"
+                    UPDATE expertises
+                    SET data_json = json_set(data_json, '$.published_url', ?, '$.published_at', ?)
+                    WHERE id = ? AND scope = ?
+                    "#,
+                )
+                .bind(url)
+                .bind(published_at)
+                .bind(id)
+                .bind(scope.as_str())
+                .execute(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    UPDATE expertises
+                    SET data_json = json_remove(data_json, '$.published_url', '$.published_at')
+                    WHERE id = ? AND scope = ?
+                    "#,
+                )
+                .bind(id)
+                .bind(scope.as_str())
+                .execute(&self.pool)
+                .await?
+            }
+        };
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                id: id.to_string(),
+                scope: scope.to_string(),
+            });
+        }
+
+        debug!("Updated publication info for expertise: {}", id);
+        Ok(())
+    }
+
+    /// List all versions of an expertise
+    pub async fn list_versions(&self, id: &str) -> Result<Vec<String>> {
+        debug!("Listing versions for expertise: {}", id);
+
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT version
+            FROM versions
+            WHERE expertise_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(v,)| v).collect())
+    }
+
+    /// Insert or replace cached scores in one transaction
+    pub async fn save_scores(&self, scores: &[ExpertiseScore]) -> Result<()> {
+        info!("Caching scores for {} expertises", scores.len());
+
+        let mut tx = self.pool.begin().await?;
+        for score in scores {
+            sqlx::query(
+                r#"
+                INSERT INTO expertise_scores
+                    (expertise_id, health_score, pagerank_score, quality_score, computed_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(expertise_id) DO UPDATE SET
+                    health_score = excluded.health_score,
+                    pagerank_score = excluded.pagerank_score,
+                    quality_score = excluded.quality_score,
+                    computed_at = excluded.computed_at
+                "#,
+            )
+            .bind(&score.expertise_id)
+            .bind(score.health_score)
+            .bind(score.pagerank_score)
+            .bind(score.quality_score)
+            .bind(score.computed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// All cached scores, keyed by expertise ID
+    pub async fn list_scores(&self) -> Result<HashMap<String, ExpertiseScore>> {
+        debug!("Listing cached scores");
+
+        let rows: Vec<(String, f64, f64, Option<f64>, i64)> = sqlx::query_as(
+            r#"
+            SELECT expertise_id, health_score, pagerank_score, quality_score, computed_at
+            FROM expertise_scores
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(expertise_id, health_score, pagerank_score, quality_score, computed_at)| {
+                    (
+                        expertise_id.clone(),
+                        ExpertiseScore {
+                            expertise_id,
+                            health_score,
+                            pagerank_score,
+                            quality_score,
+                            computed_at,
+                        },
+                    )
+                },
+            )
+            .collect())
+    }
+}
+
+/// Insert an expertise row and its tags
+///
+/// A primary-key conflict is reported as [`Error::AlreadyExists`].
+pub(crate) async fn insert_expertise(
+    conn: &mut SqliteConnection,
+    expertise: &Expertise,
+) -> Result<()> {
+    let id = expertise.id();
+    let scope = expertise.metadata.scope;
+
+    let data_json = expertise.to_json()?;
+    let description = expertise.description();
+
+    sqlx::query(
+        r#"
+        INSERT INTO expertises (
+            id, version, scope, created_at, updated_at, data_json, description,
+            locked, locked_at, locked_by_reason, status
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(expertise.version())
+    .bind(scope.as_str())
+    .bind(expertise.metadata.created_at)
+    .bind(expertise.metadata.updated_at)
+    .bind(&data_json)
+    .bind(&description)
+    .bind(expertise.metadata.locked)
+    .bind(expertise.metadata.locked_at)
+    .bind(&expertise.metadata.locked_by_reason)
+    .bind(expertise.metadata.status.as_str())
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => Error::AlreadyExists {
+            id: id.to_string(),
+            scope: scope.to_string(),
+        },
+        e => Error::Database(e),
+    })?;
+
+    for tag in expertise.tags() {
+        sqlx::query(
+            r#"
+            INSERT INTO tags (expertise_id, tag)
+            VALUES (?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(tag)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Rewrite an existing expertise row and its tags in place
+///
+/// Unlike deleting and inserting, this keeps the relations pointing at it.
+async fn replace_expertise(conn: &mut SqliteConnection, expertise: &Expertise) -> Result<()> {
+    let id = expertise.id();
+
+    sqlx::query(
+        r#"
+        UPDATE expertises
+        SET version = ?, scope = ?, created_at = ?, updated_at = ?, data_json = ?,
+            description = ?, locked = ?, locked_at = ?, locked_by_reason = ?, status = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(expertise.version())
+    .bind(expertise.metadata.scope.as_str())
+    .bind(expertise.metadata.created_at)
+    .bind(expertise.metadata.updated_at)
+    .bind(expertise.to_json()?)
+    .bind(expertise.description())
+    .bind(expertise.metadata.locked)
+    .bind(expertise.metadata.locked_at)
+    .bind(&expertise.metadata.locked_by_reason)
+    .bind(expertise.metadata.status.as_str())
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM tags WHERE expertise_id = ?")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    for tag in expertise.tags() {
+        sqlx::query("INSERT INTO tags (expertise_id, tag) VALUES (?, ?)")
+            .bind(id)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Copy a relation as is, keeping its metadata and creation time
+///
+/// An edge that already exists is left untouched.
+pub(crate) async fn insert_relation(
+    conn: &mut SqliteConnection,
+    relation: &Relation,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO relations (from_id, to_id, relation_type, metadata, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&relation.from_id)
+    .bind(&relation.to_id)
+    .bind(relation.relation_type.as_str())
+    .bind(&relation.metadata)
+    .bind(relation.created_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Save a snapshot of an expertise to the versions table
+async fn insert_version(conn: &mut SqliteConnection, expertise: &Expertise) -> Result<()> {
+    let id = expertise.id();
+    let version = expertise.version();
+    let data_json = expertise.to_json()?;
+    let created_at = chrono::Utc::now().timestamp();
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO versions (expertise_id, version, created_at, data_json)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(version)
+    .bind(created_at)
+    .bind(&data_json)
+    .execute(&mut *conn)
+    .await?;
+
+    debug!("Saved version: {} v{}", id, version);
+    Ok(())
+}
+
+/// Save `expertise` as a version and move it to the next unused patch version
+///
+/// For edits made outside the generator, which don't choose a new version
+/// themselves. Saving under an unchanged version would replace the snapshot
+/// an earlier edit of that version saved.
+async fn snapshot_and_bump(conn: &mut SqliteConnection, expertise: &mut Expertise) -> Result<()> {
+    insert_version(conn, expertise).await?;
+
+    let mut version = next_patch_version(expertise.version());
+    loop {
+        let taken: Option<(i64,)> =
+            sqlx::query_as("SELECT 1 FROM versions WHERE expertise_id = ? AND version = ?")
+                .bind(expertise.id())
+                .bind(&version)
+                .fetch_optional(&mut *conn)
+                .await?;
+        if taken.is_none() {
+            break;
+        }
+        version = next_patch_version(&version);
+    }
+    expertise.inner.version = version;
+
+    Ok(())
+}
+
+/// `version` with its patch component incremented ("1.2.3" -> "1.2.4")
+///
+/// Missing components count as 0 ("1.2" -> "1.2.1"); a version that isn't
+/// numeric gets `.1` appended.
+fn next_patch_version(version: &str) -> String {
+    let parts: Option<Vec<u64>> = version.split('.').map(|p| p.parse().ok()).collect();
+    match parts.as_deref() {
+        Some([major]) => format!("{}.0.1", major),
+        Some([major, minor]) => format!("{}.{}.1", major, minor),
+        Some([major, minor, patch]) => format!("{}.{}.{}", major, minor, patch + 1),
+        _ => format!("{}.1", version),
+    }
+}
+
+/// Find the first unused id among `base`, `base-2`, `base-3`, ...
+async fn available_id(conn: &mut SqliteConnection, base: &str) -> Result<String> {
+    let mut candidate = base.to_string();
+    let mut suffix = 2;
+
+    loop {
+        let taken: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM expertises WHERE id = ?")
+            .bind(&candidate)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if taken.is_none() {
+            return Ok(candidate);
+        }
+        candidate = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use tempfile::TempDir;
+
+    async fn setup_db() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::open(&db_path).await.unwrap();
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("test-id", "1.0.0");
+        expertise.metadata.scope = Scope::Personal;
+
+        storage.create(expertise.clone()).await.unwrap();
+
+        let retrieved = storage.get("test-id", Scope::Personal).await.unwrap();
+        assert!(retrieved.is_some());
 
         let retrieved = retrieved.unwrap();
         assert_eq!(retrieved.id(), "test-id");
@@ -364,71 +1979,1054 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_create_duplicate_fails() {
+    async fn test_create_duplicate_fails() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("test-id", "1.0.0");
+        expertise.metadata.scope = Scope::Personal;
+
+        storage.create(expertise.clone()).await.unwrap();
+
+        let result = storage.create(expertise).await;
+        assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("test-id", "1.0.0");
+        expertise.metadata.scope = Scope::Personal;
+
+        storage.create(expertise.clone()).await.unwrap();
+
+        // Update version
+        expertise.inner.version = "2.0.0".to_string();
+        storage.update(expertise).await.unwrap();
+
+        let retrieved = storage
+            .get("test-id", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retrieved.version(), "2.0.0");
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("test-id", "1.0.0");
+        expertise.metadata.scope = Scope::Personal;
+
+        storage.create(expertise).await.unwrap();
+        storage.delete("test-id", Scope::Personal).await.unwrap();
+
+        let retrieved = storage.get("test-id", Scope::Personal).await.unwrap();
+        assert!(retrieved.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut exp1 = Expertise::new("test-1", "1.0.0");
+        exp1.metadata.scope = Scope::Personal;
+
+        let mut exp2 = Expertise::new("test-2", "1.0.0");
+        exp2.metadata.scope = Scope::Personal;
+
+        storage.create(exp1).await.unwrap();
+        storage.create(exp2).await.unwrap();
+
+        let list = storage.list(Scope::Personal).await.unwrap();
+        assert_eq!(list.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_touch_only() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("test-id", "1.0.0");
+        expertise.metadata.scope = Scope::Personal;
+        expertise.metadata.updated_at = 1_000;
+
+        storage.create(expertise).await.unwrap();
+
+        let touched_at = storage
+            .touch_only("test-id", Scope::Personal)
+            .await
+            .unwrap();
+        assert!(touched_at > 1_000);
+
+        let retrieved = storage
+            .get("test-id", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retrieved.metadata.updated_at, touched_at);
+        assert_eq!(retrieved.version(), "1.0.0");
+
+        // No version snapshot is recorded
+        assert!(storage.list_versions("test-id").await.unwrap().is_empty());
+
+        let result = storage.touch_only("missing", Scope::Personal).await;
+        assert!(matches!(result, Err(Error::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag_globally() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        for (id, tags, scope) in [
+            ("a", vec!["k8s", "rust"], Scope::Personal),
+            ("b", vec!["k8s", "kubernetes"], Scope::Personal),
+            ("c", vec!["k8s"], Scope::Company),
+            ("d", vec!["rust"], Scope::Personal),
+        ] {
+            let mut expertise = Expertise::new(id, "1.0.0");
+            expertise.inner.tags = tags.into_iter().map(String::from).collect();
+            expertise.metadata.scope = scope;
+            expertise.metadata.updated_at = 1_000;
+            storage.create(expertise).await.unwrap();
+        }
+
+        let renamed = storage
+            .rename_tag_globally("k8s", "kubernetes", Some(Scope::Personal))
+            .await
+            .unwrap();
+        assert_eq!(renamed, 2);
+
+        let a = storage.get("a", Scope::Personal).await.unwrap().unwrap();
+        assert_eq!(a.tags(), &["kubernetes", "rust"]);
+        assert!(a.metadata.updated_at > 1_000);
+        assert_eq!(a.version(), "1.0.1");
+        assert_eq!(storage.list_versions("a").await.unwrap(), vec!["1.0.0"]);
+
+        // Already-present target tag is not duplicated
+        let b = storage.get("b", Scope::Personal).await.unwrap().unwrap();
+        assert_eq!(b.tags(), &["kubernetes"]);
+
+        // Other scopes and untagged expertises are left alone
+        let c = storage.get("c", Scope::Company).await.unwrap().unwrap();
+        assert_eq!(c.tags(), &["k8s"]);
+        let d = storage.get("d", Scope::Personal).await.unwrap().unwrap();
+        assert_eq!(d.metadata.updated_at, 1_000);
+
+        let tags = db.query().list_tags(Some(Scope::Personal)).await.unwrap();
+        assert!(tags.contains(&("kubernetes".to_string(), 2)));
+        assert!(!tags.iter().any(|(tag, _)| tag == "k8s"));
+
+        let results = db
+            .query()
+            .search("kubernetes", crate::SearchOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+
+        let renamed = storage
+            .rename_tag_globally("k8s", "kubernetes", None)
+            .await
+            .unwrap();
+        assert_eq!(renamed, 1);
+
+        // A second rename keeps the snapshot the first one saved
+        storage
+            .rename_tag_globally("rust", "rustlang", None)
+            .await
+            .unwrap();
+        let first = storage.get_version("a", "1.0.0").await.unwrap().unwrap();
+        assert_eq!(first.tags(), &["k8s", "rust"]);
+        let second = storage.get_version("a", "1.0.1").await.unwrap().unwrap();
+        assert_eq!(second.tags(), &["kubernetes", "rust"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_description() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.description = Some("Old description".to_string());
+        storage.create(expertise).await.unwrap();
+
+        storage
+            .update_description("rust-expert", Scope::Personal, "New description")
+            .await
+            .unwrap();
+
+        let updated = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.description(), "New description");
+        assert_eq!(updated.version(), "1.0.1");
+
+        let (column, version): (String, String) =
+            sqlx::query_as("SELECT description, version FROM expertises WHERE id = ?")
+                .bind("rust-expert")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(column, "New description");
+        assert_eq!(version, "1.0.1");
+
+        // A second edit saves a new snapshot instead of replacing the first
+        storage
+            .update_description("rust-expert", Scope::Personal, "Newer description")
+            .await
+            .unwrap();
+        for (version, description) in [("1.0.0", "Old description"), ("1.0.1", "New description")] {
+            let previous = storage
+                .get_version("rust-expert", version)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(previous.description(), description);
+        }
+
+        assert!(matches!(
+            storage
+                .update_description("missing", Scope::Personal, "x")
+                .await,
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_next_patch_version() {
+        assert_eq!(next_patch_version("1.2.3"), "1.2.4");
+        assert_eq!(next_patch_version("1.2"), "1.2.1");
+        assert_eq!(next_patch_version("2"), "2.0.1");
+        assert_eq!(next_patch_version("1.0.0-beta"), "1.0.0-beta.1");
+    }
+
+    #[tokio::test]
+    async fn test_add_tags() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.tags = vec!["rust".to_string()];
+        storage.create(expertise).await.unwrap();
+
+        let added = storage
+            .add_tags(
+                "rust-expert",
+                Scope::Personal,
+                &["rust".to_string(), "async".to_string(), "async".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(added, vec!["async"]);
+
+        let updated = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.tags(), ["rust", "async"]);
+        assert_eq!(updated.version(), "1.0.1");
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tags WHERE expertise_id = ?")
+            .bind("rust-expert")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let previous = storage
+            .get_version("rust-expert", "1.0.0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(previous.tags(), ["rust"]);
+
+        assert!(storage
+            .add_tags("rust-expert", Scope::Personal, &["rust".to_string()])
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            storage
+                .add_tags("missing", Scope::Personal, &["x".to_string()])
+                .await,
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_tags_keeps_each_snapshot() {
         let (db, _temp) = setup_db().await;
         let storage = db.storage();
 
-        let mut expertise = Expertise::new("test-id", "1.0.0");
-        expertise.metadata.scope = Scope::Personal;
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.tags = vec!["rust".to_string()];
+        storage.create(expertise).await.unwrap();
+
+        for tag in ["async", "tokio"] {
+            storage
+                .add_tags("rust-expert", Scope::Personal, &[tag.to_string()])
+                .await
+                .unwrap();
+        }
+
+        let current = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(current.version(), "1.0.2");
+        assert_eq!(current.tags(), ["rust", "async", "tokio"]);
+
+        let first = storage
+            .get_version("rust-expert", "1.0.0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.tags(), ["rust"]);
+        let second = storage
+            .get_version("rust-expert", "1.0.1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.tags(), ["rust", "async"]);
+    }
+
+    #[tokio::test]
+    async fn test_compare_versions() {
+        use crate::{KnowledgeFragment, WeightedFragment};
 
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+        let text = |t: &str| WeightedFragment::new(KnowledgeFragment::Text(t.to_string()));
+
+        let mut expertise = Expertise::new("rust-async", "1.0.0");
+        expertise.inner.tags = vec!["rust".to_string(), "sync".to_string()];
+        expertise.inner.content = vec![
+            text("Use tokio::spawn for concurrent tasks"),
+            text("Avoid blocking calls inside async functions"),
+            text("Prefer channels over shared state"),
+        ];
         storage.create(expertise.clone()).await.unwrap();
 
-        let result = storage.create(expertise).await;
-        assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+        expertise.inner.version = "2.0.0".to_string();
+        expertise.inner.description = Some("Async Rust".to_string());
+        expertise.inner.tags = vec!["rust".to_string(), "async".to_string()];
+        expertise.inner.content = vec![
+            text("Use tokio::spawn for concurrent tasks"),
+            text("Avoid blocking calls inside async fns, use spawn_blocking"),
+            text("Pin futures before polling them"),
+        ];
+        storage.update(expertise).await.unwrap();
+
+        let comparison = storage
+            .compare_versions("rust-async", "1.0.0", "2.0.0")
+            .await
+            .unwrap();
+        assert_eq!(
+            comparison.new_fragments,
+            vec!["Pin futures before polling them"]
+        );
+        assert_eq!(
+            comparison.removed_fragments,
+            vec!["Prefer channels over shared state"]
+        );
+        assert_eq!(
+            comparison.modified_fragments,
+            vec![(
+                "Avoid blocking calls inside async functions".to_string(),
+                "Avoid blocking calls inside async fns, use spawn_blocking".to_string()
+            )]
+        );
+        assert!(comparison.description_changed);
+        assert_eq!(comparison.tags_added, vec!["async"]);
+        assert_eq!(comparison.tags_removed, vec!["sync"]);
+
+        assert!(storage
+            .compare_versions("rust-async", "2.0.0", "2.0.0")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            storage
+                .compare_versions("rust-async", "1.0.0", "3.0.0")
+                .await,
+            Err(Error::VersionNotFound { .. })
+        ));
     }
 
     #[tokio::test]
-    async fn test_update() {
+    async fn test_resolve_prefix() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        for (id, scope) in [
+            ("rust", Scope::Personal),
+            ("rust-error-handling", Scope::Personal),
+            ("rust-async", Scope::Company),
+            ("python_tips", Scope::Personal),
+        ] {
+            let mut expertise = Expertise::new(id, "1.0.0");
+            expertise.metadata.scope = scope;
+            storage.create(expertise).await.unwrap();
+        }
+
+        // Unique
+        assert_eq!(
+            storage.resolve_prefix("rust-err", None).await.unwrap(),
+            ResolveResult::Unique("rust-error-handling".to_string())
+        );
+        assert_eq!(
+            storage
+                .resolve_prefix("rust-", Some(Scope::Company))
+                .await
+                .unwrap(),
+            ResolveResult::Unique("rust-async".to_string())
+        );
+        // An exact match beats longer IDs
+        assert_eq!(
+            storage.resolve_prefix("rust", None).await.unwrap(),
+            ResolveResult::Unique("rust".to_string())
+        );
+
+        // Ambiguous
+        assert_eq!(
+            storage.resolve_prefix("rust-", None).await.unwrap(),
+            ResolveResult::Ambiguous(vec![
+                "rust-async".to_string(),
+                "rust-error-handling".to_string()
+            ])
+        );
+
+        // No match; `_` and case are matched literally
+        assert_eq!(
+            storage.resolve_prefix("go", None).await.unwrap(),
+            ResolveResult::None
+        );
+        assert_eq!(
+            storage.resolve_prefix("pythonx", None).await.unwrap(),
+            ResolveResult::None
+        );
+        assert_eq!(
+            storage.resolve_prefix("python-", None).await.unwrap(),
+            ResolveResult::None
+        );
+        assert_eq!(
+            storage.resolve_prefix("RUST-ASYNC", None).await.unwrap(),
+            ResolveResult::None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_status() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        storage
+            .create(Expertise::new("rust-expert", "1.0.0"))
+            .await
+            .unwrap();
+        storage
+            .set_status("rust-expert", Scope::Personal, ExpertiseStatus::Reviewed)
+            .await
+            .unwrap();
+
+        let mut expertise = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(expertise.metadata.status, ExpertiseStatus::Reviewed);
+
+        // Regular updates keep the status
+        expertise.metadata.status = ExpertiseStatus::Draft;
+        expertise.inner.description = Some("Idiomatic Rust".to_string());
+        storage.update(expertise).await.unwrap();
+        let updated = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.metadata.status, ExpertiseStatus::Reviewed);
+
+        assert!(matches!(
+            storage
+                .set_status("missing", Scope::Personal, ExpertiseStatus::Deprecated)
+                .await,
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_lock() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.tags = vec!["rust".to_string()];
+        storage.create(expertise.clone()).await.unwrap();
+
+        storage
+            .lock("rust-expert", Scope::Personal, Some("reviewed"))
+            .await
+            .unwrap();
+        assert!(storage
+            .is_locked("rust-expert", Scope::Personal)
+            .await
+            .unwrap());
+
+        let locked = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(locked.metadata.locked);
+        assert!(locked.metadata.locked_at.is_some());
+        assert_eq!(
+            locked.metadata.locked_by_reason.as_deref(),
+            Some("reviewed")
+        );
+
+        // Regular writes are rejected, bulk renames skip it
+        assert!(matches!(
+            storage.update(expertise.clone()).await,
+            Err(Error::ExpertiseLocked { .. })
+        ));
+        assert!(matches!(
+            storage
+                .update_description("rust-expert", Scope::Personal, "x")
+                .await,
+            Err(Error::ExpertiseLocked { .. })
+        ));
+        assert!(matches!(
+            storage.delete("rust-expert", Scope::Personal).await,
+            Err(Error::ExpertiseLocked { .. })
+        ));
+        assert_eq!(
+            storage
+                .rename_tag_globally("rust", "rustlang", None)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // Forced updates keep the lock
+        expertise.inner.description = Some("Forced".to_string());
+        storage.update_forced(expertise).await.unwrap();
+        let updated = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.description(), "Forced");
+        assert!(updated.metadata.locked);
+
+        storage
+            .unlock("rust-expert", Scope::Personal)
+            .await
+            .unwrap();
+        let unlocked = storage
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!unlocked.metadata.locked);
+        assert!(unlocked.metadata.locked_by_reason.is_none());
+        storage
+            .delete("rust-expert", Scope::Personal)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            storage.lock("missing", Scope::Personal, None).await,
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_save_scores() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+        storage
+            .create(Expertise::new("rust-expert", "1.0.0"))
+            .await
+            .unwrap();
+
+        let mut score = ExpertiseScore {
+            expertise_id: "rust-expert".to_string(),
+            health_score: 0.6,
+            pagerank_score: 0.0,
+            quality_score: None,
+            computed_at: 1_700_000_000,
+        };
+        storage.save_scores(&[score.clone()]).await.unwrap();
+
+        score.quality_score = Some(0.8);
+        score.computed_at += 60;
+        storage.save_scores(&[score.clone()]).await.unwrap();
+
+        let scores = storage.list_scores().await.unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores["rust-expert"], score);
+
+        // Deleting the expertise drops its cached scores
+        storage
+            .delete("rust-expert", Scope::Personal)
+            .await
+            .unwrap();
+        assert!(storage.list_scores().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_published() {
         let (db, _temp) = setup_db().await;
         let storage = db.storage();
 
         let mut expertise = Expertise::new("test-id", "1.0.0");
         expertise.metadata.scope = Scope::Personal;
+        storage.create(expertise).await.unwrap();
 
-        storage.create(expertise.clone()).await.unwrap();
+        storage
+            .set_published(
+                "test-id",
+                Scope::Personal,
+                Some(("https://gist.github.com/abc", 1_700_000_000)),
+            )
+            .await
+            .unwrap();
 
-        // Update version
-        expertise.inner.version = "2.0.0".to_string();
-        storage.update(expertise).await.unwrap();
+        let retrieved = storage
+            .get("test-id", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            retrieved.metadata.published_url.as_deref(),
+            Some("https://gist.github.com/abc")
+        );
+        assert_eq!(retrieved.metadata.published_at, Some(1_700_000_000));
+
+        storage
+            .set_published("test-id", Scope::Personal, None)
+            .await
+            .unwrap();
 
         let retrieved = storage
             .get("test-id", Scope::Personal)
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(retrieved.version(), "2.0.0");
+        assert!(retrieved.metadata.published_url.is_none());
+        assert!(retrieved.metadata.published_at.is_none());
+
+        let result = storage
+            .set_published("missing", Scope::Personal, None)
+            .await;
+        assert!(matches!(result, Err(Error::NotFound { .. })));
     }
 
     #[tokio::test]
-    async fn test_delete() {
+    async fn test_clean_processed_sessions() {
         let (db, _temp) = setup_db().await;
         let storage = db.storage();
 
         let mut expertise = Expertise::new("test-id", "1.0.0");
         expertise.metadata.scope = Scope::Personal;
-
         storage.create(expertise).await.unwrap();
-        storage.delete("test-id", Scope::Personal).await.unwrap();
 
-        let retrieved = storage.get("test-id", Scope::Personal).await.unwrap();
-        assert!(retrieved.is_none());
+        for (path, processed_at) in [("old.log", 1_000), ("new.log", 5_000)] {
+            sqlx::query(
+                "INSERT INTO processed_sessions (file_path, file_hash, expertise_id, processed_at) VALUES (?, 'hash', 'test-id', ?)",
+            )
+            .bind(path)
+            .bind(processed_at)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let deleted = storage.clean_processed_sessions(2_000).await.unwrap();
+        assert_eq!(deleted, 1);
+
+        let remaining = storage.list_processed_sessions().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].file_path, "new.log");
+
+        let deleted = storage
+            .delete_processed_sessions(&["new.log".to_string(), "gone.log".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+        assert!(storage.list_processed_sessions().await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_record_processed_session_concurrent() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        // Distinct logs whose expertises all derive the same id
+        let handles: Vec<_> = (0..16)
+            .map(|i| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    let mut expertise = Expertise::new("session-notes", "1.0.0");
+                    expertise.inner.tags = vec!["rust".to_string()];
+                    storage
+                        .record_processed_session(
+                            &format!("/logs/session-{}.log", i),
+                            &format!("hash-{}", i),
+                            vec![expertise],
+                        )
+                        .await
+                })
+            })
+            .collect();
+
+        let mut ids = Vec::new();
+        for handle in handles {
+            ids.extend(handle.await.unwrap().unwrap());
+        }
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 16);
+        assert!(ids.contains(&"session-notes".to_string()));
+        assert_eq!(storage.list_all().await.unwrap().len(), 16);
+        assert_eq!(storage.list_processed_sessions().await.unwrap().len(), 16);
+
+        // Logs with identical content (same hash) share a single expertise
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    storage
+                        .record_processed_session(
+                            &format!("/logs/copy-{}.log", i),
+                            "shared-hash",
+                            vec![Expertise::new("copied-notes", "1.0.0")],
+                        )
+                        .await
+                })
+            })
+            .collect();
+
+        let mut ids = Vec::new();
+        for handle in handles {
+            ids.extend(handle.await.unwrap().unwrap());
+        }
+        assert!(ids.iter().all(|id| id == "copied-notes"));
+        assert_eq!(storage.list_all().await.unwrap().len(), 17);
+        assert_eq!(storage.list_processed_sessions().await.unwrap().len(), 24);
     }
 
     #[tokio::test]
-    async fn test_list() {
+    async fn test_near_duplicate_session() {
         let (db, _temp) = setup_db().await;
         let storage = db.storage();
 
-        let mut exp1 = Expertise::new("test-1", "1.0.0");
-        exp1.metadata.scope = Scope::Personal;
+        storage
+            .record_processed_session(
+                "/logs/a.jsonl",
+                "hash-a",
+                vec![Expertise::new("rust-errors", "1.0.0")],
+            )
+            .await
+            .unwrap();
+        let fingerprint = 0b1011_0110;
+        storage
+            .set_session_fingerprint("/logs/a.jsonl", fingerprint)
+            .await
+            .unwrap();
 
-        let mut exp2 = Expertise::new("test-2", "1.0.0");
-        exp2.metadata.scope = Scope::Personal;
+        // Two bits apart
+        let (original, distance) = storage
+            .find_near_duplicate_session("/logs/b.jsonl", fingerprint ^ 0b11, 3)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(original.file_path, "/logs/a.jsonl");
+        assert_eq!(distance, 2);
+        assert!(storage
+            .find_near_duplicate_session("/logs/b.jsonl", !fingerprint, 3)
+            .await
+            .unwrap()
+            .is_none());
+        // A session is not a duplicate of itself
+        assert!(storage
+            .find_near_duplicate_session("/logs/a.jsonl", fingerprint, 3)
+            .await
+            .unwrap()
+            .is_none());
 
-        storage.create(exp1).await.unwrap();
-        storage.create(exp2).await.unwrap();
+        // High-bit fingerprints survive the signed column
+        storage
+            .record_near_duplicate_session("/logs/b.jsonl", "hash-b", u64::MAX, &original)
+            .await
+            .unwrap();
+        let (found, _) = storage
+            .find_near_duplicate_session("/logs/c.jsonl", u64::MAX, 0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.file_path, "/logs/b.jsonl");
+        assert_eq!(found.expertise_id, "rust-errors");
+    }
 
-        let list = storage.list(Scope::Personal).await.unwrap();
-        assert_eq!(list.len(), 2);
+    #[tokio::test]
+    async fn test_create_conflicting_id_across_scopes() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        storage
+            .create(Expertise::new("shared-id", "1.0.0"))
+            .await
+            .unwrap();
+
+        let mut other = Expertise::new("shared-id", "1.0.0");
+        other.metadata.scope = Scope::Company;
+        let result = storage.create(other).await;
+        assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_export_import_expertise() {
+        let (source, _source_temp) = setup_db().await;
+        let (target, _target_temp) = setup_db().await;
+
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.tags = vec!["rust".to_string()];
+        source.storage().create(expertise.clone()).await.unwrap();
+        expertise.inner.version = "1.1.0".to_string();
+        source.storage().update(expertise).await.unwrap();
+        for id in ["tokio-expert", "serde-expert"] {
+            source
+                .storage()
+                .create(Expertise::new(id, "1.0.0"))
+                .await
+                .unwrap();
+        }
+        let graph = source.graph();
+        graph
+            .create_relation(
+                "rust-expert",
+                "tokio-expert",
+                crate::RelationType::Uses,
+                None,
+            )
+            .await
+            .unwrap();
+        graph
+            .create_relation(
+                "serde-expert",
+                "rust-expert",
+                crate::RelationType::Uses,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let bundle = source
+            .storage()
+            .export_expertise("rust-expert", Scope::Personal)
+            .await
+            .unwrap();
+        assert_eq!(bundle.tags, vec!["rust"]);
+        assert_eq!(bundle.relations.len(), 2);
+        assert_eq!(bundle.versions.len(), 1);
+        assert_eq!(bundle.versions[0].0, "1.0.0");
+
+        // Only the edge to an expertise the target has survives
+        target
+            .storage()
+            .create(Expertise::new("tokio-expert", "1.0.0"))
+            .await
+            .unwrap();
+        target
+            .storage()
+            .import_expertise(bundle.clone(), ConflictPolicy::Fail)
+            .await
+            .unwrap();
+
+        let imported = target
+            .storage()
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.version(), "1.1.0");
+        assert_eq!(
+            target.storage().list_versions("rust-expert").await.unwrap(),
+            vec!["1.0.0"]
+        );
+        let relations = target
+            .graph()
+            .get_all_relations("rust-expert")
+            .await
+            .unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].to_id, "tokio-expert");
+
+        // Conflicts
+        let result = target
+            .storage()
+            .import_expertise(bundle.clone(), ConflictPolicy::Fail)
+            .await;
+        assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+        target
+            .storage()
+            .import_expertise(bundle.clone(), ConflictPolicy::Skip)
+            .await
+            .unwrap();
+
+        let mut changed = bundle;
+        changed.expertise.inner.version = "2.0.0".to_string();
+        target
+            .storage()
+            .import_expertise(changed, ConflictPolicy::Overwrite)
+            .await
+            .unwrap();
+        let overwritten = target
+            .storage()
+            .get("rust-expert", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(overwritten.version(), "2.0.0");
+        assert_eq!(
+            target
+                .graph()
+                .get_all_relations("rust-expert")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(matches!(
+            source
+                .storage()
+                .export_expertise("missing", Scope::Personal)
+                .await,
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_scan_and_repair_malformed() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+
+        let mut bare = Expertise::new("bare", "1.0.0");
+        bare.inner.description = Some("Lost its metadata".to_string());
+        for id in ["ok", "bare", "broken"] {
+            storage.create(Expertise::new(id, "1.0.0")).await.unwrap();
+        }
+        for (id, data_json) in [
+            ("bare", serde_json::to_string(&bare.inner).unwrap()),
+            ("broken", "{\"id\": \"broken\", ".to_string()),
+        ] {
+            sqlx::query("UPDATE expertises SET data_json = ? WHERE id = ?")
+                .bind(data_json)
+                .bind(id)
+                .execute(db.pool())
+                .await
+                .unwrap();
+        }
+
+        let malformed = storage.scan_for_malformed().await.unwrap();
+        let ids: Vec<&str> = malformed.iter().map(|(id, _, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["bare", "broken"]);
+        assert_eq!(malformed[0].1, "personal");
+
+        assert!(storage.repair_malformed("bare").await.unwrap());
+        let repaired = storage.get("bare", Scope::Personal).await.unwrap().unwrap();
+        assert_eq!(repaired.description(), "Lost its metadata");
+
+        // An unrepairable row is flagged and hidden from queries
+        assert!(!storage.repair_malformed("broken").await.unwrap());
+        assert!(storage
+            .get("broken", Scope::Personal)
+            .await
+            .unwrap()
+            .is_none());
+        let listed: Vec<String> = storage
+            .list_all()
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.id().to_string())
+            .collect();
+        assert_eq!(listed.len(), 2);
+        assert!(!listed.contains(&"broken".to_string()));
+        assert_eq!(db.query().count(None).await.unwrap(), 2);
+
+        assert_eq!(storage.scan_for_malformed().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reminders() {
+        let (db, _temp) = setup_db().await;
+        let storage = db.storage();
+        storage
+            .create(Expertise::new("test-id", "1.0.0"))
+            .await
+            .unwrap();
+
+        let once = storage
+            .add_reminder("test-id", 1_000, Some("Review"), None)
+            .await
+            .unwrap();
+        let weekly = storage
+            .add_reminder("test-id", 5_000, None, Some(RepeatInterval::Weekly))
+            .await
+            .unwrap();
+
+        let pending = storage.list_reminders(None).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].id, once);
+        assert_eq!(pending[0].message.as_deref(), Some("Review"));
+        assert_eq!(pending[1].repeat, Some(RepeatInterval::Weekly));
+
+        let due = storage.list_reminders(Some(2_000)).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, once);
+
+        assert_eq!(storage.complete_reminder(once).await.unwrap(), None);
+        assert!(matches!(
+            storage.complete_reminder(once).await,
+            Err(Error::ReminderNotFound(_))
+        ));
+
+        // A recurring reminder moves to its next due time instead
+        let next_due = 5_000 + 7 * 24 * 60 * 60;
+        assert_eq!(
+            storage.complete_reminder(weekly).await.unwrap(),
+            Some(next_due)
+        );
+        let pending = storage.list_reminders(None).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].due_at, next_due);
+    }
+
+    #[test]
+    fn test_repeat_interval() {
+        assert_eq!(
+            "Weekly".parse::<RepeatInterval>().unwrap(),
+            RepeatInterval::Weekly
+        );
+        assert!("yearly".parse::<RepeatInterval>().is_err());
+
+        // 2025-01-31 -> 2025-02-28
+        let jan_31 = 1_738_281_600;
+        assert_eq!(RepeatInterval::Monthly.next_due(jan_31), 1_740_700_800);
+        assert_eq!(RepeatInterval::Daily.next_due(0), 86_400);
     }
 }
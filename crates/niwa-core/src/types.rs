@@ -6,7 +6,9 @@ use std::str::FromStr;
 
 // Re-export from llm-toolkit-expertise
 // Note: llm-toolkit-expertise v0.2.1 is a separate crate (deprecated but functional)
-pub use llm_toolkit_expertise::{Expertise as LlmExpertise, KnowledgeFragment, WeightedFragment};
+pub use llm_toolkit_expertise::{
+    Expertise as LlmExpertise, KnowledgeFragment, Priority, WeightedFragment,
+};
 
 /// Scope for expertise organization
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -22,14 +24,16 @@ pub enum Scope {
     Project,
 }
 
+/// Parses the scope names case-insensitively, plus the short aliases
+/// `me`, `org` and `proj`
 impl FromStr for Scope {
     type Err = crate::Error;
 
     fn from_str(s: &str) -> Result<Self, crate::Error> {
         match s.to_lowercase().as_str() {
-            "personal" => Ok(Scope::Personal),
-            "company" => Ok(Scope::Company),
-            "project" => Ok(Scope::Project),
+            "personal" | "me" => Ok(Scope::Personal),
+            "company" | "org" => Ok(Scope::Company),
+            "project" | "proj" => Ok(Scope::Project),
             _ => Err(crate::Error::InvalidScope(s.to_string())),
         }
     }
@@ -57,6 +61,56 @@ impl fmt::Display for Scope {
     }
 }
 
+/// Review status of an expertise
+///
+/// Generated expertises start as drafts; promoting one to `Reviewed` records
+/// that a human vetted it. Deprecated expertises are left out of prompt and
+/// context assembly unless explicitly included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpertiseStatus {
+    /// Not yet vetted
+    #[default]
+    Draft,
+    /// Vetted by a human
+    Reviewed,
+    /// Superseded or no longer accurate
+    Deprecated,
+}
+
+impl FromStr for ExpertiseStatus {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, crate::Error> {
+        match s.to_lowercase().as_str() {
+            "draft" => Ok(ExpertiseStatus::Draft),
+            "reviewed" => Ok(ExpertiseStatus::Reviewed),
+            "deprecated" => Ok(ExpertiseStatus::Deprecated),
+            _ => Err(crate::Error::Other(format!(
+                "Invalid status: {} (expected draft, reviewed or deprecated)",
+                s
+            ))),
+        }
+    }
+}
+
+impl ExpertiseStatus {
+    /// Convert to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpertiseStatus::Draft => "draft",
+            ExpertiseStatus::Reviewed => "reviewed",
+            ExpertiseStatus::Deprecated => "deprecated",
+        }
+    }
+}
+
+impl fmt::Display for ExpertiseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Expertise with NIWA-specific metadata
 ///
 /// This wraps llm-toolkit's Expertise with additional metadata
@@ -110,6 +164,216 @@ impl Expertise {
     pub fn from_json(json: &str) -> Result<Self, crate::Error> {
         Ok(serde_json::from_str(json)?)
     }
+
+    /// Collapse byte-identical `Text` fragments, keeping the first occurrence
+    ///
+    /// Repeated `improve` runs used to append the same text more than once.
+    /// [`Self::from_json`] never normalizes on its own, so stored data only
+    /// changes when this is called explicitly (e.g. `niwa lint --fix`).
+    /// Fragment sources stay aligned with the content. Returns the number of
+    /// fragments removed.
+    pub fn normalize(&mut self) -> usize {
+        let mut seen = std::collections::HashSet::new();
+        let keep: Vec<bool> = self
+            .inner
+            .content
+            .iter()
+            .map(|weighted| match &weighted.fragment {
+                KnowledgeFragment::Text(text) => seen.insert(text.clone()),
+                _ => true,
+            })
+            .collect();
+
+        let removed = keep.iter().filter(|kept| !**kept).count();
+        if removed == 0 {
+            return 0;
+        }
+
+        let mut flags = keep.iter();
+        self.inner
+            .content
+            .retain(|_| *flags.next().unwrap_or(&true));
+        if self.metadata.fragment_sources.len() == keep.len() {
+            let mut flags = keep.iter();
+            self.metadata
+                .fragment_sources
+                .retain(|_| *flags.next().unwrap_or(&true));
+        }
+        removed
+    }
+
+    /// Move the fragment at `from` so it ends up at index `to`
+    ///
+    /// Fragments in between shift by one; fragment sources move along with
+    /// their fragments.
+    pub fn move_fragment(&mut self, from: usize, to: usize) -> crate::Result<()> {
+        let count = self.inner.content.len();
+        for index in [from, to] {
+            if index >= count {
+                return Err(crate::Error::FragmentIndexOutOfRange { index, count });
+            }
+        }
+
+        let fragment = self.inner.content.remove(from);
+        self.inner.content.insert(to, fragment);
+        if self.metadata.fragment_sources.len() == count {
+            let source = self.metadata.fragment_sources.remove(from);
+            self.metadata.fragment_sources.insert(to, source);
+        }
+        Ok(())
+    }
+
+    /// Source the fragment at `index` was extracted from, if recorded
+    pub fn fragment_source(&self, index: usize) -> Option<&str> {
+        self.metadata.fragment_sources.get(index)?.as_deref()
+    }
+
+    /// Record `source` as the origin of every fragment
+    pub fn set_source(&mut self, source: impl Into<String>) {
+        let source = source.into();
+        self.metadata.fragment_sources = vec![Some(source); self.inner.content.len()];
+    }
+
+    /// Render the fragments as LLM context, ordered by priority
+    pub fn to_prompt_string(&self) -> String {
+        self.inner.to_prompt()
+    }
+
+    /// Render as a standalone Markdown document
+    ///
+    /// Used when sharing an expertise outside NIWA (e.g. publishing a Gist).
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {}\n\n", self.id());
+
+        let description = self.description();
+        if !description.is_empty() {
+            md.push_str(&format!("{}\n\n", description));
+        }
+
+        md.push_str(&format!(
+            "- **Version:** {}\n- **Scope:** {}\n",
+            self.version(),
+            self.metadata.scope
+        ));
+        if !self.tags().is_empty() {
+            let tags: Vec<String> = self.tags().iter().map(|t| format!("`{}`", t)).collect();
+            md.push_str(&format!("- **Tags:** {}\n", tags.join(", ")));
+        }
+
+        if !self.inner.content.is_empty() {
+            md.push_str("\n## Knowledge\n");
+        }
+
+        for (i, weighted) in self.inner.content.iter().enumerate() {
+            md.push_str(&format!(
+                "\n### {}. {}\n\n",
+                i + 1,
+                weighted.fragment.type_label()
+            ));
+
+            match &weighted.fragment {
+                KnowledgeFragment::Text(text) => {
+                    md.push_str(text.trim_end());
+                    md.push('\n');
+                }
+                KnowledgeFragment::Logic { instruction, steps } => {
+                    md.push_str(&format!("{}\n", instruction));
+                    if !steps.is_empty() {
+                        md.push('\n');
+                        for (n, step) in steps.iter().enumerate() {
+                            md.push_str(&format!("{}. {}\n", n + 1, step));
+                        }
+                    }
+                }
+                KnowledgeFragment::Guideline { rule, anchors } => {
+                    md.push_str(&format!("{}\n", rule));
+                    for anchor in anchors {
+                        md.push_str(&format!("\n**{}**\n\n", anchor.context));
+                        md.push_str(&format!("- Do: {}\n", anchor.positive));
+                        md.push_str(&format!("- Don't: {}\n", anchor.negative));
+                        md.push_str(&format!("- Why: {}\n", anchor.reason));
+                    }
+                }
+                KnowledgeFragment::QualityStandard {
+                    criteria,
+                    passing_grade,
+                } => {
+                    for criterion in criteria {
+                        md.push_str(&format!("- {}\n", criterion));
+                    }
+                    md.push_str(&format!("\nPassing grade: {}\n", passing_grade));
+                }
+                KnowledgeFragment::ToolDefinition(value) => {
+                    let json =
+                        serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+                    md.push_str(&format!("```json\n{}\n```\n", json));
+                }
+            }
+        }
+
+        md
+    }
+
+    /// Compare with `other`; "added" means present only in `other`
+    ///
+    /// Fragments are compared by their rendered text, so a fragment whose
+    /// priority changed is not reported.
+    pub fn diff(&self, other: &Expertise) -> ExpertiseDiff {
+        let fragments = |e: &Expertise| -> Vec<String> {
+            e.inner
+                .content
+                .iter()
+                .map(|w| w.fragment.to_prompt())
+                .collect()
+        };
+        let (ours, theirs) = (fragments(self), fragments(other));
+
+        let only_in = |a: &[String], b: &[String]| -> Vec<String> {
+            a.iter().filter(|x| !b.contains(x)).cloned().collect()
+        };
+
+        ExpertiseDiff {
+            description_changed: self.description() != other.description(),
+            shared_tags: self
+                .tags()
+                .iter()
+                .filter(|t| other.tags().contains(t))
+                .cloned()
+                .collect(),
+            tags_added: only_in(other.tags(), self.tags()),
+            tags_removed: only_in(self.tags(), other.tags()),
+            fragments_added: only_in(&theirs, &ours),
+            fragments_removed: only_in(&ours, &theirs),
+        }
+    }
+}
+
+/// Result of [`Expertise::diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExpertiseDiff {
+    /// Whether the descriptions differ
+    pub description_changed: bool,
+    /// Tags on both
+    pub shared_tags: Vec<String>,
+    /// Tags only on the other expertise
+    pub tags_added: Vec<String>,
+    /// Tags only on this expertise
+    pub tags_removed: Vec<String>,
+    /// Rendered fragments only in the other expertise
+    pub fragments_added: Vec<String>,
+    /// Rendered fragments only in this expertise
+    pub fragments_removed: Vec<String>,
+}
+
+impl ExpertiseDiff {
+    /// Whether description, tags and fragments are all the same
+    pub fn is_empty(&self) -> bool {
+        !self.description_changed
+            && self.tags_added.is_empty()
+            && self.tags_removed.is_empty()
+            && self.fragments_added.is_empty()
+            && self.fragments_removed.is_empty()
+    }
 }
 
 /// NIWA-specific metadata for Expertise
@@ -123,6 +387,62 @@ pub struct ExpertiseMetadata {
 
     /// Last updated timestamp (Unix timestamp in seconds)
     pub updated_at: i64,
+
+    /// URL of the published copy (e.g. a GitHub Gist), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_url: Option<String>,
+
+    /// When the expertise was published (Unix timestamp in seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<i64>,
+
+    /// Source (e.g. session log path) of each fragment, aligned with `content`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fragment_sources: Vec<Option<String>>,
+
+    /// Locked expertises reject updates and deletion unless forced
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+
+    /// When the expertise was locked (Unix timestamp in seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_at: Option<i64>,
+
+    /// Why the expertise was locked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_by_reason: Option<String>,
+
+    /// LLM provider and model that generated the expertise (e.g. "claude/claude-sonnet-4-5")
+    ///
+    /// Unset for hand-authored expertises.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_by: Option<String>,
+
+    /// When the expertise was generated (Unix timestamp in seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_at: Option<i64>,
+
+    /// Kind of input it was generated from (log, interactive, merge, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<String>,
+
+    /// File it was generated from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
+
+    /// ID of the expertise this one is a translation of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translated_from: Option<String>,
+
+    /// Review status (draft, reviewed, deprecated)
+    #[serde(default)]
+    pub status: ExpertiseStatus,
+
+    /// Why the version was bumped to its current value, when an LLM chose the bump
+    ///
+    /// Kept in each version history snapshot alongside the content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_reason: Option<String>,
 }
 
 impl Default for ExpertiseMetadata {
@@ -132,6 +452,19 @@ impl Default for ExpertiseMetadata {
             scope: Scope::default(),
             created_at: now,
             updated_at: now,
+            published_url: None,
+            published_at: None,
+            fragment_sources: Vec::new(),
+            locked: false,
+            locked_at: None,
+            locked_by_reason: None,
+            generated_by: None,
+            generated_at: None,
+            source_type: None,
+            source_file: None,
+            translated_from: None,
+            status: ExpertiseStatus::default(),
+            version_reason: None,
         }
     }
 }
@@ -164,6 +497,36 @@ mod tests {
         assert!(Scope::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_scope_aliases() {
+        assert_eq!(Scope::from_str("me").unwrap(), Scope::Personal);
+        assert_eq!(Scope::from_str("org").unwrap(), Scope::Company);
+        assert_eq!(Scope::from_str("PROJ").unwrap(), Scope::Project);
+
+        let err = Scope::from_str("team").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Invalid scope: team (expected personal, company or project; \
+             aliases: me, org, proj)"
+        );
+    }
+
+    #[test]
+    fn test_status_conversion() {
+        assert_eq!(
+            ExpertiseStatus::from_str("Reviewed").unwrap(),
+            ExpertiseStatus::Reviewed
+        );
+        assert_eq!(ExpertiseStatus::Deprecated.to_string(), "deprecated");
+        assert!(ExpertiseStatus::from_str("archived").is_err());
+
+        // Expertises stored before statuses existed read back as drafts
+        let mut value = serde_json::to_value(Expertise::new("rust-expert", "1.0.0")).unwrap();
+        value.as_object_mut().unwrap().remove("status");
+        let expertise: Expertise = serde_json::from_value(value).unwrap();
+        assert_eq!(expertise.metadata.status, ExpertiseStatus::Draft);
+    }
+
     #[test]
     fn test_scope_display() {
         assert_eq!(Scope::Personal.to_string(), "personal");
@@ -171,6 +534,69 @@ mod tests {
         assert_eq!(Scope::Project.to_string(), "project");
     }
 
+    #[test]
+    fn test_normalize_duplicate_fragments() {
+        let json = r#"{
+            "id": "rust-async",
+            "version": "1.2.0",
+            "tags": ["rust"],
+            "content": [
+                {"fragment": {"type": "Text", "content": "Use tokio::spawn"}},
+                {"fragment": {"type": "Text", "content": "Avoid blocking calls"}},
+                {"fragment": {"type": "Text", "content": "Use tokio::spawn"}},
+                {"fragment": {"type": "Text", "content": "Use tokio::spawn"}}
+            ],
+            "scope": "personal",
+            "created_at": 1700000000,
+            "updated_at": 1700000000,
+            "fragment_sources": ["a.log", "b.log", "c.log", null]
+        }"#;
+
+        let mut expertise = Expertise::from_json(json).unwrap();
+        assert_eq!(expertise.inner.content.len(), 4);
+
+        assert_eq!(expertise.normalize(), 2);
+        assert_eq!(expertise.inner.content.len(), 2);
+        assert_eq!(expertise.fragment_source(0), Some("a.log"));
+        assert_eq!(expertise.fragment_source(1), Some("b.log"));
+        assert_eq!(expertise.metadata.fragment_sources.len(), 2);
+
+        assert_eq!(expertise.normalize(), 0);
+    }
+
+    #[test]
+    fn test_move_fragment() {
+        let text = |t: &str| WeightedFragment::new(KnowledgeFragment::Text(t.to_string()));
+        let texts = |e: &Expertise| -> Vec<String> {
+            e.inner
+                .content
+                .iter()
+                .map(|w| match &w.fragment {
+                    KnowledgeFragment::Text(t) => t.clone(),
+                    _ => String::new(),
+                })
+                .collect()
+        };
+        let mut expertise = Expertise::new("rust-async", "1.0.0");
+        expertise.inner.content = vec![text("a"), text("b"), text("c")];
+        expertise.metadata.fragment_sources =
+            vec![Some("a.log".to_string()), None, Some("c.log".to_string())];
+
+        expertise.move_fragment(2, 0).unwrap();
+        assert_eq!(texts(&expertise), vec!["c", "a", "b"]);
+        assert_eq!(expertise.fragment_source(0), Some("c.log"));
+        assert_eq!(expertise.fragment_source(2), None);
+
+        expertise.move_fragment(0, 2).unwrap();
+        assert_eq!(expertise.fragment_source(2), Some("c.log"));
+
+        assert!(matches!(
+            expertise.move_fragment(3, 0),
+            Err(crate::Error::FragmentIndexOutOfRange { index: 3, count: 3 })
+        ));
+        assert!(expertise.move_fragment(0, 5).is_err());
+    }
+
     #[test]
     fn test_expertise_creation() {
         let expertise = Expertise::new("test-id", "1.0.0");
@@ -188,4 +614,79 @@ mod tests {
         assert_eq!(parsed.id(), expertise.id());
         assert_eq!(parsed.version(), expertise.version());
     }
+
+    #[test]
+    fn test_fragment_sources_roundtrip() {
+        let mut expertise = Expertise::new("test-id", "1.0.0");
+        expertise.inner.content = vec![
+            WeightedFragment::new(KnowledgeFragment::Text("a".to_string())),
+            WeightedFragment::new(KnowledgeFragment::Text("b".to_string())),
+        ];
+        expertise.set_source("/logs/session.jsonl");
+
+        let parsed = Expertise::from_json(&expertise.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.fragment_source(0), Some("/logs/session.jsonl"));
+        assert_eq!(parsed.fragment_source(1), Some("/logs/session.jsonl"));
+        assert_eq!(parsed.fragment_source(2), None);
+
+        // Expertises without provenance omit the field entirely
+        let plain = Expertise::new("plain", "1.0.0").to_json().unwrap();
+        assert!(!plain.contains("fragment_sources"));
+        let parsed = Expertise::from_json(&plain).unwrap();
+        assert!(parsed.metadata.fragment_sources.is_empty());
+    }
+
+    #[test]
+    fn test_expertise_to_markdown() {
+        let mut expertise = Expertise::new("rust-release", "1.2.0");
+        expertise.inner.description = Some("Releasing Rust crates".to_string());
+        expertise.inner.tags = vec!["rust".to_string()];
+        expertise.inner.content = vec![
+            WeightedFragment::new(KnowledgeFragment::Text("Check the changelog".to_string())),
+            WeightedFragment::new(KnowledgeFragment::Logic {
+                instruction: "Publish".to_string(),
+                steps: vec!["Bump version".to_string(), "cargo publish".to_string()],
+            }),
+        ];
+
+        let md = expertise.to_markdown();
+        assert!(md.starts_with("# rust-release\n\nReleasing Rust crates\n"));
+        assert!(md.contains("- **Tags:** `rust`"));
+        assert!(md.contains("### 1. Text\n\nCheck the changelog\n"));
+        assert!(md.contains("1. Bump version\n2. cargo publish\n"));
+    }
+
+    #[test]
+    fn test_expertise_diff() {
+        let text = |s: &str| WeightedFragment::new(KnowledgeFragment::Text(s.to_string()));
+
+        let mut a = Expertise::new("a", "1.0.0");
+        a.inner.tags = vec!["rust".to_string(), "async".to_string()];
+        a.inner.content = vec![text("Use tokio"), text("Avoid blocking")];
+
+        let mut b = Expertise::new("b", "1.0.0");
+        b.inner.description = Some("Async Rust".to_string());
+        b.inner.tags = vec!["rust".to_string(), "tokio".to_string()];
+        b.inner.content = vec![text("Use tokio"), text("Prefer channels")];
+
+        let diff = a.diff(&b);
+        assert!(diff.description_changed);
+        assert_eq!(diff.shared_tags, vec!["rust"]);
+        assert_eq!(diff.tags_added, vec!["tokio"]);
+        assert_eq!(diff.tags_removed, vec!["async"]);
+        assert_eq!(diff.fragments_added.len(), 1);
+        assert!(diff.fragments_added[0].contains("Prefer channels"));
+        assert_eq!(diff.fragments_removed.len(), 1);
+        assert!(diff.fragments_removed[0].contains("Avoid blocking"));
+
+        assert!(!diff.is_empty());
+        assert!(a.diff(&a).is_empty());
+        assert_eq!(
+            a.diff(&a),
+            ExpertiseDiff {
+                shared_tags: a.tags().to_vec(),
+                ..Default::default()
+            }
+        );
+    }
 }
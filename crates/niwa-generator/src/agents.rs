@@ -3,8 +3,11 @@
 //! This module contains Agent trait implementations using llm-toolkit's Agent derive macro.
 //! Agents are kept in a separate module to avoid conflicts with the Result<T> type alias.
 
-use llm_toolkit::{agent, type_marker, ToPrompt};
-use serde::{Deserialize, Serialize};
+use async_trait::async_trait;
+use llm_toolkit::agent::{error::ParseErrorReason, Payload};
+use llm_toolkit::{agent, extract_json, type_marker, Agent, AgentError, PromptPart, ToPrompt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
 
 /// Structured response for Expertise generation from LLM
 ///
@@ -28,6 +31,16 @@ pub struct ExpertiseResponse {
     /// List of key knowledge fragments extracted from the content.
     /// Each fragment should be a self-contained insight, best practice, or important concept.
     pub fragments: Vec<String>,
+
+    /// How confident you are that the extraction holds valuable knowledge (0.0-1.0).
+    /// Use about 0.9 for rich, clear knowledge and about 0.3 for mostly generic or unrelated content.
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+/// Confidence of a response that doesn't report one (e.g. from a custom prompt)
+fn default_confidence() -> f32 {
+    1.0
 }
 
 /// Response for extracting multiple expertises from large session logs
@@ -77,6 +90,8 @@ Your task is to identify and extract knowledge that would be valuable for future
    - Represent decisions/learnings from actual implementation work
    - Help understand "WHY" not just "WHAT"
 
+5. Set confidence to 0.9 if the log contains rich, clear knowledge; 0.3 if the content is mostly generic or unrelated
+
 If the conversation contains only generic tool usage or system prompts without domain knowledge, return minimal fragments focusing on any project context mentioned.
 
 Output a single, valid JSON object with the structure defined by the `ExpertiseResponse` type."#,
@@ -137,6 +152,134 @@ Output a JSON object with an 'expertises' array containing 1-5 expertise objects
 )]
 pub struct FileBasedExpertiseExtractorAgent;
 
+// ============================================================================
+// Source Code Analysis
+// ============================================================================
+
+/// Agent for extracting design knowledge from source code
+#[agent(
+    expertise = r#"You are an expert at reading SOURCE CODE and extracting the design knowledge embedded in it.
+
+Analyze this source code and extract architectural decisions, design patterns, non-obvious implementation choices, and domain-specific knowledge. Focus on WHY decisions were made.
+
+## EXTRACT (High Priority)
+- **Architectural decisions** (module boundaries, layering, ownership of state)
+- **Design patterns** in use and why they fit here (builder, state machine, newtype, etc.)
+- **Non-obvious implementation choices** (performance trade-offs, workarounds, invariants)
+- **Domain concepts** encoded in types, names and constraints
+- **Error handling and failure modes** the code is designed around
+
+## DO NOT EXTRACT
+- Line-by-line descriptions of what the code does
+- Generic language features or standard library usage
+- Formatting or naming trivia
+
+## Output Requirements
+1. Generate a meaningful suggested_id (lowercase, hyphenated, 3-5 words) describing the component
+2. Write a description focusing on the component's role and key design decisions
+3. Tags must include the language as `language:<name>` (e.g., "language:rust") and each
+   detected pattern as `pattern:<name>` (e.g., "pattern:builder"), plus 2-4 domain tags
+4. Extract 5-10 knowledge fragments, each explaining a decision and its rationale
+
+Output a single, valid JSON object with the structure defined by the `ExpertiseResponse` type."#,
+    output = "ExpertiseResponse",
+    backend = "claude"
+)]
+pub struct CodeAnalysisAgent;
+
+// ============================================================================
+// Changelog Analysis
+// ============================================================================
+
+/// Agent for extracting release knowledge from changelogs
+#[agent(
+    expertise = r#"You are an expert at reading CHANGELOGS and extracting the project knowledge recorded in them.
+
+Extract knowledge about breaking changes, significant new patterns, deprecation reasons, and architectural evolution from this changelog.
+
+## EXTRACT (High Priority)
+- **Breaking changes** and how to migrate existing code
+- **Deprecations** and the reason behind them, with the recommended replacement
+- **Significant new patterns or APIs** and when to use them
+- **Architectural evolution** (what was restructured, replaced or removed, and why)
+
+## DO NOT EXTRACT
+- Routine bug fixes without lasting lessons
+- Dependency bumps, typo fixes and other housekeeping
+- Contributor credits and release dates on their own
+
+## Output Requirements
+1. Generate a meaningful suggested_id (lowercase, hyphenated, 3-5 words)
+2. Write a description summarizing the releases covered and their main themes
+3. Tags must describe the kind of knowledge, e.g. "breaking-change", "migration-guide",
+   "deprecation", "new-feature", plus 2-4 domain tags
+4. Extract 5-10 knowledge fragments; name the version each one applies to
+
+Output a single, valid JSON object with the structure defined by the `ExpertiseResponse` type."#,
+    output = "ExpertiseResponse",
+    backend = "claude"
+)]
+pub struct ChangelogExtractorAgent;
+
+// ============================================================================
+// Meeting Notes Analysis
+// ============================================================================
+
+/// Agent for extracting decisions and action items from meeting notes
+#[agent(
+    expertise = r#"You are an expert at reading MEETING NOTES and extracting the decisions and technical knowledge recorded in them.
+
+Meeting notes often contain decisions in phrases like 'we decided to', 'the team agreed', 'action item:' — prioritize these.
+
+## EXTRACT (High Priority)
+- **Decisions made** and the reasoning or alternatives discussed
+- **Technical constraints** raised (performance budgets, compatibility, deadlines, dependencies)
+- **Action items** with their owners and, if stated, due dates
+- **Architecture changes agreed upon** (components added, replaced or restructured, and why)
+
+## DO NOT EXTRACT
+- Attendance, agenda logistics and scheduling chatter
+- Status updates without a decision or lasting lesson
+- Open questions that were not resolved
+
+## Output Requirements
+1. Generate a meaningful suggested_id (lowercase, hyphenated, 3-5 words) describing the meeting topic
+2. Write a description summarizing the meeting's main outcomes
+3. Tags must include "meeting-decision" for decisions, "architecture-decision" for agreed
+   architecture changes and "action-item" for action items, as they occur, plus 2-4 domain tags
+4. Extract 5-10 knowledge fragments; state each decision with its rationale and each
+   action item with its owner
+
+Output a single, valid JSON object with the structure defined by the `ExpertiseResponse` type."#,
+    output = "ExpertiseResponse",
+    backend = "claude"
+)]
+pub struct MeetingNotesAgent;
+
+// ============================================================================
+// Translation
+// ============================================================================
+
+/// Agent for translating an expertise into another language
+#[agent(
+    expertise = r#"You are an expert technical translator.
+
+Translate all text content (description, fragment texts) to the target language named in the request. Preserve technical terms in English. Keep the same structure.
+
+## Rules
+- Translate the description and every fragment; do not add, drop, merge or reorder fragments
+- Keep code, identifiers, commands, file paths and product names exactly as they are
+- Keep technical terms (e.g. "borrow checker", "dependency injection") in English, optionally
+  followed by a translation in parentheses on first use
+- Return the original tags unchanged
+- Set suggested_id to the original ID
+
+Output a single, valid JSON object with the structure defined by the `ExpertiseResponse` type."#,
+    output = "ExpertiseResponse",
+    backend = "claude"
+)]
+pub struct TranslatorAgent;
+
 // ============================================================================
 // Expertise Improvement
 // ============================================================================
@@ -318,6 +461,70 @@ Focus on creating a comprehensive, unified knowledge base that synthesizes all i
 )]
 pub struct ExpertiseMergerAgent;
 
+// ============================================================================
+// Fragment Upgrading
+// ============================================================================
+
+/// A text fragment rewritten as a richer fragment type
+///
+/// `content` is free-form JSON, so `ToPrompt` is implemented by hand below
+/// (`serde_json::Value` has no `ToPrompt` impl for the derive to use).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpgradedFragment {
+    /// Fragment type: "Logic", "Guideline" or "QualityStandard"
+    pub fragment_type: String,
+
+    /// Fragment content matching the type
+    pub content: serde_json::Value,
+}
+
+impl ToPrompt for UpgradedFragment {
+    fn to_prompt_parts(&self) -> Vec<PromptPart> {
+        vec![PromptPart::Text(self.to_prompt())]
+    }
+
+    fn to_prompt(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    fn prompt_schema() -> String {
+        r#"type UpgradedFragment = {
+  fragment_type: string;  // "Logic", "Guideline" or "QualityStandard"
+  content: object;  // Content matching fragment_type:
+    // Logic: { instruction: string; steps: string[] }
+    // Guideline: { rule: string; anchors: { context: string; positive: string; negative: string; reason: string }[] }
+    // QualityStandard: { criteria: string[]; passing_grade: string }
+}"#
+        .to_string()
+    }
+}
+
+/// Agent for upgrading plain text fragments to richer fragment types
+#[agent(
+    expertise = r#"You are an expert at restructuring knowledge for LLM consumption.
+
+You receive a plain text knowledge fragment and a requested target type. Rewrite the
+fragment as that richer type WITHOUT adding facts that are not in the original.
+
+Target types:
+- "Logic": a procedure. `instruction` states the goal; `steps` lists the ordered steps.
+- "Guideline": a behavioral rule. `rule` states it; `anchors` give positive/negative
+  example pairs with the context and the reason (include at least one anchor when the
+  text allows it).
+- "QualityStandard": evaluation criteria. `criteria` lists checkable items;
+  `passing_grade` describes what passes.
+
+Guidelines:
+- Preserve the original meaning and any project-specific details
+- Keep wording concise and actionable
+- If the text does not fit the requested type well, still produce the closest faithful form
+
+Output a single JSON object with `fragment_type` set to the requested type and `content`
+shaped as described by the `UpgradedFragment` type."#,
+    output = "UpgradedFragment"
+)]
+pub struct FragmentUpgraderAgent;
+
 // ============================================================================
 // Expertise Linking
 // ============================================================================
@@ -391,3 +598,370 @@ Output a JSON object with suggested_links array. If no strong links exist, retur
     backend = "claude"
 )]
 pub struct ExpertiseLinkerAgent;
+
+// ============================================================================
+// Implicit Relation Extraction
+// ============================================================================
+
+/// A relation mentioned in fragment text without a formal graph edge
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct ImplicitRelation {
+    /// Name or ID of the referenced expertise as written in the text
+    pub target_id_hint: String,
+    /// Relation type: "uses", "extends", "requires", or "conflicts"
+    pub relation_type: String,
+    /// Confidence score (0.0 to 1.0)
+    pub confidence: f32,
+    /// The sentence that mentions the relation
+    pub source_fragment: String,
+}
+
+/// Response for implicit relation extraction
+#[type_marker]
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct RelationExtractionResponse {
+    /// Relations found in the text
+    pub relations: Vec<ImplicitRelation>,
+}
+
+/// Agent for mining relations that fragment text mentions implicitly
+#[agent(
+    expertise = r#"You are an expert at finding dependencies between knowledge areas in prose.
+
+You receive the fragments of one expertise and a list of KNOWN expertise IDs. Find every
+place where the text refers to another expertise, for example:
+- "This builds on X" / "an extension of X" -> "extends"
+- "Requires understanding of Y" / "assumes familiarity with Y" -> "requires"
+- "Uses Z" / "applies Z" / "see Z" -> "uses"
+- "Contradicts W" / "unlike W, avoid ..." -> "conflicts"
+
+Guidelines:
+- Prefer a KNOWN ID as `target_id_hint` when the text clearly refers to it; otherwise
+  copy the name as written
+- Quote the sentence containing the mention in `source_fragment`
+- Do not report the expertise itself, generic technologies without a matching ID, or
+  relations you have to guess at
+- Use confidence >= 0.8 only for explicit statements
+
+Output a JSON object with a `relations` array. If nothing is mentioned, return an empty array."#,
+    output = "RelationExtractionResponse"
+)]
+pub struct RelationExtractionAgent;
+
+// ============================================================================
+// Topic Splitting
+// ============================================================================
+
+/// One topic of a multi-topic expertise
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct ExpertisePart {
+    /// Short topic name appended to the original ID (lowercase, hyphenated, 1-3 words)
+    pub suffix: String,
+    /// Description of this part (1-2 sentences)
+    pub description: String,
+    /// Tags for this part: the original tags that apply plus tags specific to the topic
+    pub tags: Vec<String>,
+    /// Indices of the original fragments that belong to this part
+    pub fragment_indices: Vec<usize>,
+}
+
+/// Response for splitting an expertise by topic
+#[type_marker]
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct TopicSplitResponse {
+    /// The proposed parts
+    pub parts: Vec<ExpertisePart>,
+}
+
+/// Agent for decomposing a multi-topic expertise into focused parts
+#[agent(
+    expertise = r#"You are an expert at organizing knowledge into focused, single-topic units.
+
+You receive one expertise whose fragments cover several topics, with its tags and
+numbered fragments. Group the fragments by topic:
+- Infer the topics from both the tags and the fragment content
+- Assign every fragment index to exactly one part
+- Stay within the requested minimum and maximum number of parts
+- Give each part a short, distinct suffix, a description, and tags; keep the original
+  tags that apply and add tags unique to the part
+
+Do not rewrite fragments; refer to them only by index.
+
+Output a JSON object with a `parts` array."#,
+    output = "TopicSplitResponse"
+)]
+pub struct TagBasedSplitterAgent;
+
+// ============================================================================
+// Description Generation
+// ============================================================================
+
+/// Response for generating an expertise description
+#[type_marker]
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct DescriptionResponse {
+    /// Description of the expertise (1-2 sentences)
+    pub description: String,
+}
+
+/// Agent for writing the description of an existing expertise
+#[agent(
+    expertise = r#"You write short descriptions for knowledge bases.
+
+Write a 1-2 sentence description for expertise with these fragments.
+
+Guidelines:
+- Summarize what the fragments cover as a whole, not each fragment
+- Name the main technologies or practices
+- Do not start with "This expertise"
+
+Output a JSON object with a `description` field."#,
+    output = "DescriptionResponse"
+)]
+pub struct DescriptionGeneratorAgent;
+
+// ============================================================================
+// Tag Suggestion
+// ============================================================================
+
+/// Response for suggesting tags for an expertise
+#[type_marker]
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct TagSuggestionResponse {
+    /// Suggested tags (lowercase, hyphenated, 3-5 tags)
+    pub tags: Vec<String>,
+}
+
+/// Agent for proposing tags for an expertise that has none
+#[agent(
+    expertise = r#"You tag entries in a knowledge base so they can be searched and filtered.
+
+You receive one expertise (description and fragments) and the tags already in use in the
+knowledge base. Propose 3-5 tags for it:
+- Prefer EXISTING TAGS when they fit, so related expertise share tags
+- Otherwise use short, lowercase, hyphenated terms (e.g. "error-handling", "postgres")
+- Name technologies and topics, not generic words like "tips" or "knowledge"
+
+Output a JSON object with a `tags` array."#,
+    output = "TagSuggestionResponse"
+)]
+pub struct TagSuggesterAgent;
+
+// ============================================================================
+// Quality Check
+// ============================================================================
+
+/// Response for rating the quality of an expertise
+#[type_marker]
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct QualityCheckResponse {
+    /// Overall quality from 0.0 (unusable) to 1.0 (excellent)
+    pub score: f32,
+    /// Concrete problems that lowered the score (empty if none)
+    pub issues: Vec<String>,
+}
+
+/// Agent for rating how useful an expertise is as LLM context
+#[agent(
+    expertise = r#"You review entries in a knowledge base that is injected into LLM prompts.
+
+Rate the expertise you receive from 0.0 to 1.0:
+- Accuracy: statements are correct and current
+- Actionability: fragments give concrete guidance rather than generalities
+- Clarity: each fragment is unambiguous and self-contained
+- Focus: fragments belong together and do not repeat each other
+
+Use 0.8 or higher only for entries you would trust without edits. List each concrete
+problem that lowered the score in `issues`.
+
+Output a JSON object with `score` and `issues`."#,
+    output = "QualityCheckResponse"
+)]
+pub struct QualityCheckerAgent;
+
+// ============================================================================
+// Version Analysis
+// ============================================================================
+
+/// Size of a version bump
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToPrompt)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverBump {
+    /// Breaking structural changes: many fragments removed, description rewritten
+    Major,
+    /// Substantial additions
+    Minor,
+    /// Typo and formatting fixes
+    Patch,
+}
+
+impl SemverBump {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+        }
+    }
+}
+
+/// Response for choosing the version bump between two revisions of an expertise
+#[type_marker]
+#[derive(Serialize, Deserialize, Debug, Clone, ToPrompt)]
+#[prompt(mode = "full")]
+pub struct VersionBumpRecommendation {
+    /// "major", "minor" or "patch"
+    pub bump: SemverBump,
+    /// Why this bump fits the change (1-2 sentences)
+    pub reasoning: String,
+}
+
+/// Agent for choosing a semantic version bump from the change between revisions
+#[agent(
+    expertise = r#"You version entries in a knowledge base with semantic versioning.
+
+You receive the OLD and NEW revision of an expertise. Choose the bump that matches the change:
+- major: breaking structural changes, e.g. many fragments removed or the description
+  completely changed, so prompts relying on the old revision may behave differently
+- minor: substantial additions (new fragments, new topics) that keep existing content
+- patch: typo, wording and formatting fixes that do not change the knowledge
+
+Output a JSON object with `bump` and a short `reasoning`."#,
+    output = "VersionBumpRecommendation"
+)]
+pub struct VersionAnalysisAgent;
+
+// ============================================================================
+// Runtime-configured Agents
+// ============================================================================
+
+/// Agent whose system prompt is supplied at runtime
+///
+/// `#[agent]` fixes the system prompt at compile time. This agent mirrors the
+/// code the macro generates (prepend prompt + schema, extract JSON, deserialize)
+/// but takes the prompt as a value, so users can replace the extractor prompt
+/// via `GenerationOptions::extractor_system_prompt`.
+pub struct CustomPromptAgent<A, T> {
+    inner: A,
+    expertise: String,
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<A, T: ToPrompt> CustomPromptAgent<A, T> {
+    /// Wrap a backend with a system prompt; the `T` schema is appended to it
+    pub fn new(inner: A, system_prompt: &str) -> Self {
+        let expertise = format!(
+            "{}\n\nIMPORTANT: Respond with valid JSON matching this schema:\n\n{}",
+            system_prompt.trim_end(),
+            T::prompt_schema()
+        );
+        Self {
+            inner,
+            expertise,
+            _output: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<A, T> Agent for CustomPromptAgent<A, T>
+where
+    A: Agent<Output = String>,
+    T: ToPrompt + Serialize + DeserializeOwned + Send + Sync,
+{
+    type Output = T;
+    type Expertise = String;
+
+    fn expertise(&self) -> &String {
+        &self.expertise
+    }
+
+    fn description(&self) -> &str {
+        "Agent with a runtime-provided system prompt"
+    }
+
+    async fn execute(&self, intent: Payload) -> Result<T, AgentError> {
+        let payload = intent.prepend_text(self.expertise.as_str());
+        let response = self.inner.execute(payload).await?;
+
+        let json_str = extract_json(&response).map_err(|e| AgentError::ParseError {
+            message: e.to_string(),
+            reason: ParseErrorReason::MarkdownExtractionFailed,
+        })?;
+
+        serde_json::from_str(&json_str).map_err(|e| {
+            let reason = if e.is_eof() {
+                ParseErrorReason::UnexpectedEof
+            } else if e.is_syntax() {
+                ParseErrorReason::InvalidJson
+            } else {
+                ParseErrorReason::SchemaMismatch
+            };
+            AgentError::ParseError {
+                message: e.to_string(),
+                reason,
+            }
+        })
+    }
+
+    async fn is_available(&self) -> Result<(), AgentError> {
+        self.inner.is_available().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Backend that records the prompt it receives and returns a canned response
+    struct RecordingBackend {
+        prompts: Mutex<Vec<String>>,
+        response: String,
+    }
+
+    #[async_trait]
+    impl Agent for RecordingBackend {
+        type Output = String;
+        type Expertise = String;
+
+        fn expertise(&self) -> &String {
+            &self.response
+        }
+
+        async fn execute(&self, intent: Payload) -> Result<String, AgentError> {
+            self.prompts.lock().unwrap().push(intent.to_text());
+            Ok(self.response.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_prompt_reaches_executed_prompt() {
+        let backend = RecordingBackend {
+            prompts: Mutex::new(Vec::new()),
+            response: r#"```json
+{"suggested_id": "sql-tuning", "description": "Tuning SQL", "tags": ["sql"], "fragments": ["Add indexes"]}
+```"#
+                .to_string(),
+        };
+
+        let agent: CustomPromptAgent<_, ExpertiseResponse> =
+            CustomPromptAgent::new(backend, "You extract database tuning knowledge only.");
+        let response = agent.execute("Session log".into()).await.unwrap();
+        assert_eq!(response.suggested_id, "sql-tuning");
+        assert_eq!(response.fragments, vec!["Add indexes"]);
+
+        let prompts = agent.inner.prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].contains("You extract database tuning knowledge only."));
+        assert!(prompts[0].contains("Session log"));
+        assert!(prompts[0].contains("suggested_id"));
+    }
+}
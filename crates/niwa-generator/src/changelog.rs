@@ -0,0 +1,116 @@
+//! Changelog parsing utilities
+
+/// One release section of a changelog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangelogSection {
+    /// Version from the heading (e.g. "1.2.0", "v2.0.0", "Unreleased")
+    pub version: String,
+    /// The section including its heading
+    pub content: String,
+}
+
+impl ChangelogSection {
+    /// Version as an id-safe slug (e.g. "1.2.0" → "1-2-0")
+    pub fn slug(&self) -> String {
+        let slug: String = self
+            .version
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        slug.split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+}
+
+/// Split a changelog into its version sections
+///
+/// Every `## ` heading starts a section, as in Keep a Changelog
+/// (`## [1.2.0] - 2024-01-15`) or plain `## v1.2.0` headings. Text before the
+/// first section and sections without any body are dropped.
+///
+/// # Example
+///
+/// ```
+/// use niwa_generator::split_changelog;
+///
+/// let sections = split_changelog("# Changelog\n\n## [1.1.0]\n- Added X\n\n## [1.0.0]\n- Initial\n");
+/// assert_eq!(sections.len(), 2);
+/// assert_eq!(sections[0].version, "1.1.0");
+/// ```
+pub fn split_changelog(content: &str) -> Vec<ChangelogSection> {
+    let mut sections = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in content.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            sections.extend(current.take().and_then(finish_section));
+            current = Some((heading_version(heading), vec![line]));
+        } else if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        }
+    }
+    sections.extend(current.and_then(finish_section));
+
+    sections
+}
+
+fn finish_section((version, lines): (String, Vec<&str>)) -> Option<ChangelogSection> {
+    if lines[1..].iter().all(|line| line.trim().is_empty()) {
+        return None;
+    }
+    Some(ChangelogSection {
+        version,
+        content: lines.join("\n").trim_end().to_string(),
+    })
+}
+
+/// Version from a heading: the bracketed part, or else the first word
+fn heading_version(heading: &str) -> String {
+    let heading = heading.trim();
+    if let Some(rest) = heading.strip_prefix('[') {
+        if let Some(end) = rest.find(']') {
+            return rest[..end].trim().to_string();
+        }
+    }
+    heading
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_changelog() {
+        let changelog = "# Changelog\n\
+                         \n\
+                         All notable changes.\n\
+                         \n\
+                         ## [Unreleased]\n\
+                         \n\
+                         ## [2.0.0] - 2024-03-01\n\
+                         ### Removed\n\
+                         - Old sync API\n\
+                         \n\
+                         ## v1.4.2 (2024-01-10)\n\
+                         - Deprecated `connect_sync`\n";
+
+        let sections = split_changelog(changelog);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].version, "2.0.0");
+        assert_eq!(
+            sections[0].content,
+            "## [2.0.0] - 2024-03-01\n### Removed\n- Old sync API"
+        );
+        assert_eq!(sections[1].version, "v1.4.2");
+        assert_eq!(sections[1].slug(), "v1-4-2");
+
+        assert!(split_changelog("# Changelog\nNo releases yet\n").is_empty());
+    }
+}
@@ -0,0 +1,1932 @@
+//! Crawling session logs into expertises
+//!
+//! [`Crawler`] runs the pipeline behind `niwa crawler run`: scan a directory
+//! for session logs, leave out trivial, oversized and already processed
+//! files, extract expertises from the rest with the LLM, store them and
+//! optionally link them to existing ones. Progress is reported through
+//! [`ScanOptions::on_event`]; the outcome is returned as a [`ScanReport`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use niwa_core::Database;
+//! use niwa_generator::{Crawler, ExpertiseGenerator, ScanOptions};
+//! use std::path::Path;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let db = Arc::new(Database::open_default().await?);
+//!     let generator = Arc::new(ExpertiseGenerator::new().await?);
+//!
+//!     let options = ScanOptions {
+//!         modified_within: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+//!         auto_link: true,
+//!         ..Default::default()
+//!     };
+//!     let report = Crawler::new(db, generator)
+//!         .scan(Path::new("sessions"), &options)
+//!         .await?;
+//!
+//!     println!("Created {} expertise(s)", report.created().count());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Error, ExpertiseGenerator, Result, SuggestedLink};
+use niwa_core::{
+    fingerprint, Database, Expertise, ExpertiseStatus, RelationMetadata, RelationType, Scope,
+    SearchOptions, StorageOperations,
+};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+/// Sessions with fewer user/assistant messages are left out as trivial
+const MIN_MESSAGES: usize = 3;
+
+/// Sessions with fewer message characters are left out as trivial
+const MIN_CHARS: usize = 200;
+
+/// Maximum file size for in-memory processing (500KB)
+/// Files larger than this will be processed using file attachment to avoid ARG_MAX limits
+const MAX_IN_MEMORY_SIZE: u64 = 500 * 1024;
+
+/// Bytes sniffed for NUL bytes in files too large to validate in full
+const SNIFF_LEN: u64 = 8 * 1024;
+
+/// File extensions scanned unless [`ScanOptions::extensions`] says otherwise
+pub const DEFAULT_EXTENSIONS: &[&str] = &["log", "md", "txt", "jsonl", "toml"];
+
+/// Progress of a crawl, reported as it happens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanEvent {
+    /// Processing of a session file began
+    FileStarted { path: PathBuf },
+    /// An expertise was stored from a session file
+    ExpertiseCreated {
+        id: String,
+        path: PathBuf,
+        fragment_count: usize,
+    },
+    /// A session file was skipped without storing anything
+    FileSkipped { path: PathBuf, reason: String },
+    /// Processing of a session file failed
+    FileFailed { path: PathBuf, error: String },
+}
+
+/// Receives [`ScanEvent`]s; called from the crawl's worker tasks
+pub type EventHandler = Arc<dyn Fn(ScanEvent) + Send + Sync>;
+
+/// How auto-link finds links between expertises
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkStrategy {
+    /// Ask the LLM (LinkerAgent) to suggest links
+    #[default]
+    Llm,
+    /// Link expertises sharing tags, without calling the LLM
+    Tags,
+}
+
+/// Options for [`Crawler::scan`]
+#[derive(Clone)]
+pub struct ScanOptions {
+    /// Scope for generated expertises (unless overridden by auto-scope)
+    pub scope: Scope,
+    /// Only report what would be processed
+    pub dry_run: bool,
+    /// Maximum number of files to process
+    pub limit: Option<usize>,
+    /// Only process files modified within this long before the scan
+    pub modified_within: Option<Duration>,
+    /// Link new expertises to existing ones
+    pub auto_link: bool,
+    /// How auto-link finds links
+    pub link_strategy: LinkStrategy,
+    /// Minimum shared tags for tag-based auto-link
+    pub min_shared_tags: usize,
+    /// Statuses of expertises auto-link never links to
+    pub link_excluded: Vec<ExpertiseStatus>,
+    /// Most candidates per new expertise for LLM auto-link (None = all)
+    pub link_candidates: Option<usize>,
+    /// Resolve scope from file path using scope mappings
+    pub auto_scope: bool,
+    /// Prefix for the IDs of new expertises (`<prefix>-<id>`)
+    pub id_prefix: Option<String>,
+    /// Continue from the last saved checkpoint instead of rescanning
+    pub resume: bool,
+    /// Skip near-duplicates of processed transcripts within this many bits
+    pub dedup_near: Option<u32>,
+    /// Skip files larger than this many bytes
+    pub max_size: Option<u64>,
+    /// Skip files smaller than this many bytes
+    pub min_size: Option<u64>,
+    /// Filename globs to include (empty means all files)
+    pub include: Vec<String>,
+    /// File extensions to scan (lowercase, without the dot)
+    pub extensions: Vec<String>,
+    /// Files processed at the same time
+    ///
+    /// Near-duplicates of a file still being processed are not detected.
+    pub concurrency: usize,
+    /// Set to stop the crawl after the files in progress
+    pub cancelled: Arc<AtomicBool>,
+    /// Called with every [`ScanEvent`]
+    pub on_event: Option<EventHandler>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            scope: Scope::default(),
+            dry_run: false,
+            limit: None,
+            modified_within: None,
+            auto_link: false,
+            link_strategy: LinkStrategy::default(),
+            min_shared_tags: 2,
+            link_excluded: vec![ExpertiseStatus::Deprecated],
+            link_candidates: None,
+            auto_scope: false,
+            id_prefix: None,
+            resume: false,
+            dedup_near: None,
+            max_size: None,
+            min_size: None,
+            include: Vec::new(),
+            extensions: DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+            concurrency: 1,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            on_event: None,
+        }
+    }
+}
+
+impl fmt::Debug for ScanOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScanOptions")
+            .field("scope", &self.scope)
+            .field("dry_run", &self.dry_run)
+            .field("limit", &self.limit)
+            .field("modified_within", &self.modified_within)
+            .field("auto_link", &self.auto_link)
+            .field("link_strategy", &self.link_strategy)
+            .field("auto_scope", &self.auto_scope)
+            .field("id_prefix", &self.id_prefix)
+            .field("resume", &self.resume)
+            .field("dedup_near", &self.dedup_near)
+            .field("concurrency", &self.concurrency)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ScanOptions {
+    fn emit(&self, event: ScanEvent) {
+        if let Some(on_event) = &self.on_event {
+            on_event(event);
+        }
+    }
+
+    /// Report `path` as skipped
+    fn skip(&self, path: &Path, reason: &str) -> FileOutcome {
+        info!("Skipping {}: {}", path.display(), reason);
+        self.emit(ScanEvent::FileSkipped {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        });
+        FileOutcome::Skipped(reason.to_string())
+    }
+
+    /// Report `path` as failed
+    fn fail(&self, path: &Path, error: String) -> FileOutcome {
+        warn!("Failed to process {}: {}", path.display(), error);
+        self.emit(ScanEvent::FileFailed {
+            path: path.to_path_buf(),
+            error: error.clone(),
+        });
+        FileOutcome::Failed(error)
+    }
+}
+
+/// What happened to a processed session file
+#[derive(Debug, Clone)]
+pub enum FileOutcome {
+    /// Expertises were stored from the file (with their stored ids)
+    Created {
+        scope: Scope,
+        expertises: Vec<Expertise>,
+    },
+    /// Nothing was stored, for the given reason (e.g. "empty extraction")
+    Skipped(String),
+    /// Processing failed with the given error
+    Failed(String),
+}
+
+/// A processed session file
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub outcome: FileOutcome,
+}
+
+/// Auto-link result for the expertises created in one scope
+#[derive(Debug, Clone)]
+pub struct LinkReport {
+    pub scope: Scope,
+    /// Relations created, or why linking failed
+    pub result: std::result::Result<usize, String>,
+}
+
+/// Session files left out before processing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SkipCounts {
+    /// Too few messages or characters to be worth extracting
+    pub trivial: usize,
+    /// Larger than [`ScanOptions::max_size`]
+    pub too_large: usize,
+    /// Smaller than [`ScanOptions::min_size`]
+    pub too_small: usize,
+}
+
+/// Outcome of [`Crawler::scan`]
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    /// Session files found in the directory (the checkpoint's total when resumed)
+    pub found: usize,
+    /// Session files left out before processing
+    pub skipped_before: SkipCounts,
+    /// Files due for processing after the filters (and limit)
+    pub total_files: usize,
+    /// Files that would be processed (dry runs only)
+    pub pending: Vec<PathBuf>,
+    /// Processed files, in scan order
+    pub files: Vec<FileReport>,
+    /// Auto-link results, per scope
+    pub links: Vec<LinkReport>,
+    /// Files left and total files of the checkpoint the crawl resumed from
+    pub resumed: Option<(usize, usize)>,
+    /// Whether the crawl was stopped through [`ScanOptions::cancelled`]
+    ///
+    /// The checkpoint is kept so the crawl can be resumed.
+    pub interrupted: bool,
+}
+
+impl ScanReport {
+    /// Files that produced expertises
+    pub fn processed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, FileOutcome::Created { .. }))
+    }
+
+    /// Files skipped while processing
+    pub fn skipped(&self) -> usize {
+        self.count(|outcome| matches!(outcome, FileOutcome::Skipped(_)))
+    }
+
+    /// Files that failed
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, FileOutcome::Failed(_)))
+    }
+
+    /// Relations created by auto-link
+    pub fn link_count(&self) -> usize {
+        self.links
+            .iter()
+            .filter_map(|l| l.result.as_ref().ok())
+            .sum()
+    }
+
+    /// Expertises created, in scan order
+    pub fn created(&self) -> impl Iterator<Item = &Expertise> {
+        self.files.iter().flat_map(|file| match &file.outcome {
+            FileOutcome::Created { expertises, .. } => expertises.as_slice(),
+            _ => &[][..],
+        })
+    }
+
+    fn count(&self, predicate: impl Fn(&FileOutcome) -> bool) -> usize {
+        self.files.iter().filter(|f| predicate(&f.outcome)).count()
+    }
+}
+
+/// Crawls session logs into the database with an [`ExpertiseGenerator`]
+#[derive(Clone)]
+pub struct Crawler {
+    db: Arc<Database>,
+    generator: Arc<ExpertiseGenerator>,
+}
+
+impl Crawler {
+    pub fn new(db: Arc<Database>, generator: Arc<ExpertiseGenerator>) -> Self {
+        Self { db, generator }
+    }
+
+    /// Crawl `directory` for session logs
+    ///
+    /// Unless it is a dry run, the files to process are saved as a checkpoint
+    /// first, so an interrupted crawl can continue with
+    /// [`ScanOptions::resume`]. A failing file is reported in the
+    /// [`ScanReport`]; only database and I/O errors abort the crawl.
+    pub async fn scan(&self, directory: &Path, options: &ScanOptions) -> Result<ScanReport> {
+        if !directory.is_dir() {
+            return Err(Error::DirectoryNotFound(directory.to_path_buf()));
+        }
+
+        let pool = self.db.pool();
+        if options.auto_scope {
+            let (mappings,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scope_mappings")
+                .fetch_one(pool)
+                .await
+                .map_err(niwa_core::Error::from)?;
+            if mappings > 0 {
+                info!("Auto-scope enabled: {} mappings active", mappings);
+            }
+        }
+
+        // Resume from a saved checkpoint, or scan and filter from scratch
+        let checkpoint_key = checkpoint_key(directory);
+        let checkpoint = if options.resume {
+            load_checkpoint(pool, &checkpoint_key).await?
+        } else {
+            None
+        };
+
+        let mut report = ScanReport::default();
+        let pending = match checkpoint {
+            Some(checkpoint) => {
+                info!(
+                    "Resuming from checkpoint: {} of {} files done",
+                    checkpoint.done, checkpoint.total
+                );
+                report.found = checkpoint.total;
+                report.resumed = Some((checkpoint.pending.len(), checkpoint.total));
+                checkpoint.pending
+            }
+            None => {
+                let pending = self.pending_files(directory, options, &mut report).await?;
+                if report.found == 0 {
+                    return Ok(report);
+                }
+                if !options.dry_run {
+                    save_checkpoint(pool, &checkpoint_key, &pending).await?;
+                }
+                pending
+            }
+        };
+
+        info!("Found {} unprocessed files (after filters)", pending.len());
+        report.total_files = pending.len();
+
+        if options.dry_run {
+            report.pending = pending.into_iter().map(|(path, _)| path).collect();
+            return Ok(report);
+        }
+        if pending.is_empty() {
+            return Ok(report);
+        }
+
+        // At most `concurrency` files at a time; a slot is taken before the
+        // stop flag is checked, so a stop request waits for the files in progress
+        let limit = Arc::new(Semaphore::new(options.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, (file_path, file_hash)) in pending.into_iter().enumerate() {
+            let permit = limit
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|e| Error::Other(format!("Crawl worker pool closed: {}", e)))?;
+            if options.cancelled.load(Ordering::SeqCst) {
+                report.interrupted = true;
+                break;
+            }
+
+            let crawler = self.clone();
+            let options = options.clone();
+            let checkpoint_key = checkpoint_key.clone();
+            tasks.spawn(async move {
+                let _permit = permit;
+                let outcome = crawler.process(&file_path, &file_hash, &options).await;
+                let marked =
+                    mark_checkpoint_done(crawler.db.pool(), &checkpoint_key, &file_path).await;
+                (
+                    index,
+                    FileReport {
+                        path: file_path,
+                        outcome,
+                    },
+                    marked,
+                )
+            });
+        }
+
+        let mut files = Vec::with_capacity(tasks.len());
+        while let Some(joined) = tasks.join_next().await {
+            let (index, file, marked) =
+                joined.map_err(|e| Error::Other(format!("Crawl task failed: {}", e)))?;
+            marked?;
+            files.push((index, file));
+        }
+        files.sort_by_key(|(index, _)| *index);
+        report.files = files.into_iter().map(|(_, file)| file).collect();
+
+        if options.auto_link {
+            report.links = self.link_created(&report.files, options).await;
+        }
+
+        if !report.interrupted {
+            clear_checkpoint(pool, &checkpoint_key).await?;
+        }
+        Ok(report)
+    }
+
+    /// Session files in `directory` still to process, with their hashes
+    ///
+    /// Records how many files were found and left out in `report`.
+    async fn pending_files(
+        &self,
+        directory: &Path,
+        options: &ScanOptions,
+        report: &mut ScanReport,
+    ) -> Result<Vec<(PathBuf, String)>> {
+        info!("Scanning directory: {}", directory.display());
+
+        // Scan for session log files
+        let session_files = filter_included(
+            scan_session_files(directory, &options.extensions)?,
+            &options.include,
+        );
+        info!("Found {} potential session files", session_files.len());
+        report.found = session_files.len();
+
+        // Filter by modification time if specified
+        let filtered_files: Vec<PathBuf> = match options.modified_within {
+            Some(window) => {
+                let cutoff = SystemTime::now()
+                    .checked_sub(window)
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                modified_since(session_files, cutoff)
+            }
+            None => session_files,
+        };
+
+        info!(
+            "After modification time filter: {} files",
+            filtered_files.len()
+        );
+
+        // Filter out already processed files and files without meaningful content
+        let skipped = &mut report.skipped_before;
+        let mut pending = Vec::new();
+        for file_path in filtered_files {
+            // Size limits only need metadata, so they run before reading content
+            let len = std::fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+            match check_size(len, options.min_size, options.max_size) {
+                SizeCheck::TooLarge => {
+                    skipped.too_large += 1;
+                    continue;
+                }
+                SizeCheck::TooSmall => {
+                    skipped.too_small += 1;
+                    continue;
+                }
+                SizeCheck::Within => {}
+            }
+
+            // Then check if the file has meaningful content (fast filter)
+            if !has_meaningful_content(&file_path, MIN_MESSAGES, MIN_CHARS) {
+                skipped.trivial += 1;
+                continue;
+            }
+
+            let hash = calculate_file_hash(&file_path)?;
+            if !is_file_processed(self.db.pool(), &file_path, &hash).await? {
+                pending.push((file_path, hash));
+            }
+        }
+
+        if skipped.trivial > 0 {
+            info!(
+                "Skipped {} trivial sessions (< {} messages or < {} chars)",
+                skipped.trivial, MIN_MESSAGES, MIN_CHARS
+            );
+        }
+
+        // Apply limit if specified
+        if let Some(max_count) = options.limit {
+            pending.truncate(max_count);
+        }
+
+        Ok(pending)
+    }
+
+    /// Process one pending session file
+    async fn process(
+        &self,
+        file_path: &Path,
+        file_hash: &str,
+        options: &ScanOptions,
+    ) -> FileOutcome {
+        info!("Processing: {}", file_path.display());
+
+        // Determine scope for this file
+        let scope = if options.auto_scope {
+            resolve_scope_from_path(self.db.pool(), file_path)
+                .await
+                .unwrap_or(options.scope)
+        } else {
+            options.scope
+        };
+
+        // Binary or non-UTF8 files can match by extension; skip them up front
+        if let Some(reason) = non_text_reason(file_path) {
+            return options.skip(file_path, reason);
+        }
+
+        // Fingerprinted even without dedup_near, so later crawls can compare
+        let fingerprint = session_transcript(file_path).map(|t| fingerprint::simhash(&t));
+        if let (Some(max_distance), Some(fingerprint)) = (options.dedup_near, fingerprint) {
+            match skip_near_duplicate(&self.db, file_path, file_hash, fingerprint, max_distance)
+                .await
+            {
+                Ok(Some(reason)) => return options.skip(file_path, &reason),
+                Ok(None) => {}
+                Err(e) => return options.fail(file_path, e.to_string()),
+            }
+        }
+
+        options.emit(ScanEvent::FileStarted {
+            path: file_path.to_path_buf(),
+        });
+        match self
+            .process_session_file(file_path, file_hash, scope, options.id_prefix.as_deref())
+            .await
+        {
+            Ok(expertises) if expertises.is_empty() => options.skip(file_path, "empty extraction"),
+            Ok(expertises) => {
+                for expertise in &expertises {
+                    options.emit(ScanEvent::ExpertiseCreated {
+                        id: expertise.id().to_string(),
+                        path: file_path.to_path_buf(),
+                        fragment_count: expertise.inner.content.len(),
+                    });
+                }
+                if let Some(fingerprint) = fingerprint {
+                    let path = file_path.to_string_lossy();
+                    if let Err(e) = self
+                        .db
+                        .storage()
+                        .set_session_fingerprint(&path, fingerprint)
+                        .await
+                    {
+                        warn!("Failed to store fingerprint of {}: {}", path, e);
+                    }
+                }
+                FileOutcome::Created { scope, expertises }
+            }
+            Err(e) => options.fail(file_path, e),
+        }
+    }
+
+    /// Auto-link the expertises created from `files` to existing ones, scope by scope
+    async fn link_created(&self, files: &[FileReport], options: &ScanOptions) -> Vec<LinkReport> {
+        let mut new_ids: Vec<(Scope, Vec<String>)> = Vec::new();
+        for file in files {
+            if let FileOutcome::Created { scope, expertises } = &file.outcome {
+                let ids = expertises.iter().map(|e| e.id().to_string());
+                match new_ids.iter_mut().find(|(s, _)| s == scope) {
+                    Some((_, scope_ids)) => scope_ids.extend(ids),
+                    None => new_ids.push((*scope, ids.collect())),
+                }
+            }
+        }
+
+        let mut links = Vec::new();
+        for (scope, scope_ids) in new_ids {
+            info!(
+                "Auto-linking {} new expertises (scope: {})",
+                scope_ids.len(),
+                scope
+            );
+            let result = match options.link_strategy {
+                LinkStrategy::Llm => {
+                    self.auto_link_expertises(
+                        &scope_ids,
+                        scope,
+                        &options.link_excluded,
+                        options.link_candidates,
+                    )
+                    .await
+                }
+                LinkStrategy::Tags => {
+                    tag_link_expertises(
+                        &self.db,
+                        &scope_ids,
+                        scope,
+                        options.min_shared_tags,
+                        &options.link_excluded,
+                    )
+                    .await
+                }
+            };
+            if let Err(e) = &result {
+                warn!("Auto-link failed for scope {}: {}", scope, e);
+            }
+            links.push(LinkReport { scope, result });
+        }
+        links
+    }
+
+    /// Process a session file and generate expertise
+    ///
+    /// For small files (<500KB), the content is passed directly to the LLM.
+    /// For large files (>=500KB), the file is passed as an attachment to avoid command-line
+    /// argument length limitations. Large files may generate multiple expertises.
+    ///
+    /// Returns the stored expertises, with their stored ids; none, and records
+    /// nothing, when the extraction came back empty.
+    async fn process_session_file(
+        &self,
+        file_path: &Path,
+        file_hash: &str,
+        scope: Scope,
+        id_prefix: Option<&str>,
+    ) -> std::result::Result<Vec<Expertise>, String> {
+        // Check file size to determine processing method
+        let metadata = std::fs::metadata(file_path)
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+        let file_size = metadata.len();
+
+        // Generate fallback expertise ID from file name (used if LLM doesn't provide a good one)
+        let fallback_id = generate_expertise_id(file_path);
+
+        debug!("Fallback expertise ID: {}", fallback_id);
+        debug!("File size: {} bytes", file_size);
+
+        let mut expertises = if file_size < MAX_IN_MEMORY_SIZE {
+            // Small file: use in-memory processing
+            debug!(
+                "Using in-memory processing (file size < {}KB)",
+                MAX_IN_MEMORY_SIZE / 1024
+            );
+
+            // Read file content
+            let content = std::fs::read_to_string(file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+
+            // Generate expertise using LLM
+            match self
+                .generator
+                .generate_from_log(&content, &fallback_id, scope)
+                .await
+            {
+                Ok(expertise) => vec![expertise],
+                Err(niwa_generator::Error::EmptyExtraction) => return Ok(Vec::new()),
+                Err(e) => return Err(format!("Failed to generate expertise: {}", e)),
+            }
+        } else {
+            // Large file: use file attachment processing
+            info!(
+                "Using file-based processing (file size: {}KB)",
+                file_size / 1024
+            );
+
+            // Generate expertise(s) using file attachment (may return multiple)
+            match self
+                .generator
+                .generate_from_file(file_path, &fallback_id, scope)
+                .await
+            {
+                Ok(expertises) => expertises,
+                Err(niwa_generator::Error::EmptyExtraction) => return Ok(Vec::new()),
+                Err(e) => return Err(format!("Failed to generate expertise from file: {}", e)),
+            }
+        };
+
+        // Store the expertises and record the session in one transaction, so
+        // concurrent workers cannot race on ids or duplicate content
+        let path_str = file_path.to_string_lossy();
+        for expertise in &mut expertises {
+            if let Some(prefix) = id_prefix {
+                expertise.inner.id = prefixed_id(prefix, expertise.id());
+            }
+            expertise.set_source(path_str.as_ref());
+            expertise.metadata.source_file = Some(path_str.to_string());
+        }
+        let expertise_ids = self
+            .db
+            .storage()
+            .record_processed_session(&path_str, file_hash, expertises.clone())
+            .await
+            .map_err(|e| format!("Failed to store expertise: {}", e))?;
+
+        info!("Stored expertise(s): {}", expertise_ids.join(", "));
+
+        // Stored ids may differ from the generated ones when they collide
+        for (expertise, id) in expertises.iter_mut().zip(expertise_ids) {
+            expertise.inner.id = id;
+        }
+        Ok(expertises)
+    }
+
+    /// Auto-link new expertises to existing ones using LLM-powered LinkerAgent
+    ///
+    /// Expertises with an `excluded` status are not offered as link targets.
+    /// With `max_candidates`, each new expertise is only compared with that many
+    /// candidates (see [`link_candidates`]), keeping token use flat on large bases.
+    async fn auto_link_expertises(
+        &self,
+        new_ids: &[String],
+        scope: Scope,
+        excluded: &[ExpertiseStatus],
+        max_candidates: Option<usize>,
+    ) -> std::result::Result<usize, String> {
+        let storage = self.db.storage();
+        let mut link_count = 0;
+        let mut skipped = 0;
+
+        // Get all existing expertises for comparison
+        let options = excluded
+            .iter()
+            .fold(SearchOptions::new().scope(scope), |options, status| {
+                options.exclude_status(*status)
+            });
+        let all_expertises = self
+            .db
+            .query()
+            .list(options)
+            .await
+            .map_err(|e| format!("Failed to list expertises: {}", e))?;
+
+        if all_expertises.is_empty() {
+            return Ok(0); // Nothing to link to
+        }
+
+        // For each new expertise, use LinkerAgent to suggest links
+        for new_id in new_ids {
+            // Get the new expertise
+            let new_expertise = match storage.get(new_id, scope).await {
+                Ok(Some(e)) => e,
+                _ => continue,
+            };
+
+            let candidates =
+                link_candidates(&self.db, &new_expertise, &all_expertises, max_candidates).await?;
+            if candidates.is_empty() {
+                continue;
+            }
+
+            // Use LinkerAgent to analyze and suggest links
+            let suggested_links = self
+                .generator
+                .suggest_links(&new_expertise, &candidates)
+                .await
+                .unwrap_or_default();
+
+            let (created, invalid) = create_suggested_links(&self.db, &suggested_links).await;
+            link_count += created;
+            skipped += invalid;
+        }
+
+        if skipped > 0 {
+            warn!(
+                "Skipped {} suggested link(s) with an unknown relation type",
+                skipped
+            );
+        }
+        Ok(link_count)
+    }
+}
+
+/// Create the relations the linker suggested, except ones already linked
+///
+/// A link whose relation type doesn't parse is skipped rather than stored
+/// under a guessed type. Returns the numbers of created and skipped links.
+async fn create_suggested_links(db: &Database, links: &[SuggestedLink]) -> (usize, usize) {
+    let graph = db.graph();
+    let mut created = 0;
+    let mut skipped = 0;
+
+    for link in links {
+        let relation_type: RelationType = match link.relation_type.trim().parse() {
+            Ok(relation_type) => relation_type,
+            Err(_) => {
+                warn!(
+                    "Skipping suggested link {} -> {}: unknown relation type '{}'",
+                    link.from_id, link.to_id, link.relation_type
+                );
+                skipped += 1;
+                continue;
+            }
+        };
+
+        // Check if relation already exists
+        let existing_relations = graph
+            .get_all_relations(&link.from_id)
+            .await
+            .unwrap_or_default();
+
+        let already_linked = existing_relations
+            .iter()
+            .any(|r| r.to_id == link.to_id || r.from_id == link.to_id);
+
+        if !already_linked {
+            let metadata = RelationMetadata {
+                reason: Some(link.reason.clone()),
+                confidence: Some(link.confidence),
+                source: Some("auto-link".to_string()),
+                ..Default::default()
+            };
+            if let Ok(()) = graph
+                .create_relation(
+                    &link.from_id,
+                    &link.to_id,
+                    relation_type,
+                    Some(metadata.to_json()),
+                )
+                .await
+            {
+                info!(
+                    "Auto-linked {} -[{}]-> {} (confidence: {:.2}, reason: {})",
+                    link.from_id, relation_type, link.to_id, link.confidence, link.reason
+                );
+                created += 1;
+            }
+        }
+    }
+
+    (created, skipped)
+}
+
+/// Progress of an interrupted scan
+struct Checkpoint {
+    /// Files not yet processed (path, hash)
+    pending: Vec<(PathBuf, String)>,
+    /// Files already processed in the interrupted run
+    done: usize,
+    /// Total files in the interrupted run
+    total: usize,
+}
+
+/// Key identifying a scan's checkpoint (the canonical directory path)
+fn checkpoint_key(directory: &Path) -> String {
+    directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Load the checkpoint for a directory, if it still has pending files
+async fn load_checkpoint(pool: &sqlx::SqlitePool, directory: &str) -> Result<Option<Checkpoint>> {
+    let rows: Vec<(String, String, bool)> = sqlx::query_as(
+        r#"
+        SELECT file_path, file_hash, done
+        FROM crawl_checkpoints
+        WHERE directory = ?
+        ORDER BY position
+        "#,
+    )
+    .bind(directory)
+    .fetch_all(pool)
+    .await
+    .map_err(niwa_core::Error::from)?;
+
+    let total = rows.len();
+    let pending: Vec<(PathBuf, String)> = rows
+        .into_iter()
+        .filter(|(_, _, done)| !done)
+        .map(|(path, hash, _)| (PathBuf::from(path), hash))
+        .collect();
+
+    if pending.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(Checkpoint {
+        done: total - pending.len(),
+        total,
+        pending,
+    }))
+}
+
+/// Replace the checkpoint for a directory with a fresh list of pending files
+async fn save_checkpoint(
+    pool: &sqlx::SqlitePool,
+    directory: &str,
+    files: &[(PathBuf, String)],
+) -> Result<()> {
+    let db_err = |e: sqlx::Error| Error::Core(e.into());
+    let created_at = chrono::Utc::now().timestamp();
+
+    let mut tx = pool.begin().await.map_err(db_err)?;
+
+    sqlx::query("DELETE FROM crawl_checkpoints WHERE directory = ?")
+        .bind(directory)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+    for (position, (path, hash)) in files.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO crawl_checkpoints (directory, file_path, file_hash, position, done, created_at)
+            VALUES (?, ?, ?, ?, 0, ?)
+            "#,
+        )
+        .bind(directory)
+        .bind(path.to_string_lossy().to_string())
+        .bind(hash)
+        .bind(position as i64)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    }
+
+    tx.commit().await.map_err(db_err)?;
+    Ok(())
+}
+
+/// Mark a file in the checkpoint as done
+async fn mark_checkpoint_done(
+    pool: &sqlx::SqlitePool,
+    directory: &str,
+    file_path: &Path,
+) -> Result<()> {
+    sqlx::query("UPDATE crawl_checkpoints SET done = 1 WHERE directory = ? AND file_path = ?")
+        .bind(directory)
+        .bind(file_path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .map_err(niwa_core::Error::from)?;
+    Ok(())
+}
+
+/// Remove the checkpoint for a directory once a run completes
+async fn clear_checkpoint(pool: &sqlx::SqlitePool, directory: &str) -> Result<()> {
+    sqlx::query("DELETE FROM crawl_checkpoints WHERE directory = ?")
+        .bind(directory)
+        .execute(pool)
+        .await
+        .map_err(niwa_core::Error::from)?;
+    Ok(())
+}
+
+/// Scan directory recursively for session log files
+fn scan_session_files(dir: &Path, extensions: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in walkdir::WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_file() {
+            let path = entry.path();
+
+            // Filter by extension
+            if let Some(ext) = path.extension() {
+                let ext_str = ext.to_string_lossy().to_lowercase();
+                if extensions.contains(&ext_str) {
+                    files.push(path.to_path_buf());
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Keep only files whose name matches one of the include globs
+///
+/// An empty pattern list keeps every file.
+fn filter_included(files: Vec<PathBuf>, include: &[String]) -> Vec<PathBuf> {
+    if include.is_empty() {
+        return files;
+    }
+
+    files
+        .into_iter()
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            include
+                .iter()
+                .any(|pattern| matches_pattern(&name, pattern))
+        })
+        .collect()
+}
+
+/// Calculate SHA256 hash of file content
+pub fn calculate_file_hash(path: &Path) -> Result<String> {
+    let content = std::fs::read(path)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    let hash = hasher.finalize();
+
+    Ok(format!("{:x}", hash))
+}
+
+/// Check if file has already been processed
+async fn is_file_processed(
+    pool: &sqlx::SqlitePool,
+    file_path: &Path,
+    file_hash: &str,
+) -> Result<bool> {
+    let path_str = file_path.to_string_lossy();
+
+    let row: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT file_hash
+        FROM processed_sessions
+        WHERE file_path = ?
+        "#,
+    )
+    .bind(&*path_str)
+    .fetch_optional(pool)
+    .await
+    .map_err(niwa_core::Error::from)?;
+
+    match row {
+        Some((existing_hash,)) => {
+            // Check if hash matches (file not modified)
+            Ok(existing_hash == file_hash)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Result of checking a file size against --min-size/--max-size
+#[derive(Debug, PartialEq, Eq)]
+enum SizeCheck {
+    Within,
+    TooSmall,
+    TooLarge,
+}
+
+fn check_size(len: u64, min_size: Option<u64>, max_size: Option<u64>) -> SizeCheck {
+    if max_size.is_some_and(|max| len > max) {
+        SizeCheck::TooLarge
+    } else if min_size.is_some_and(|min| len < min) {
+        SizeCheck::TooSmall
+    } else {
+        SizeCheck::Within
+    }
+}
+
+/// Why a session file can't be processed as text, if it can't
+///
+/// Small files are validated as UTF-8 in full since their content is passed to
+/// the LLM directly; large files are only sniffed for NUL bytes. Unreadable
+/// files return `None` so the read error surfaces as a failure later.
+fn non_text_reason(path: &Path) -> Option<&'static str> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+    let limit = if size < MAX_IN_MEMORY_SIZE {
+        size
+    } else {
+        SNIFF_LEN
+    };
+
+    let mut bytes = Vec::new();
+    file.take(limit).read_to_end(&mut bytes).ok()?;
+
+    if bytes.contains(&0) {
+        Some("binary content")
+    } else if size < MAX_IN_MEMORY_SIZE && std::str::from_utf8(&bytes).is_err() {
+        Some("not valid UTF-8")
+    } else {
+        None
+    }
+}
+
+/// Skip `file_path` if its transcript is a near-duplicate of an already
+/// processed session, recording it as processed
+///
+/// Returns the reason it was skipped.
+async fn skip_near_duplicate(
+    db: &Database,
+    file_path: &Path,
+    file_hash: &str,
+    fingerprint: u64,
+    max_distance: u32,
+) -> Result<Option<String>> {
+    let storage = db.storage();
+    let path = file_path.to_string_lossy();
+
+    let Some((original, distance)) = storage
+        .find_near_duplicate_session(&path, fingerprint, max_distance)
+        .await?
+    else {
+        return Ok(None);
+    };
+    storage
+        .record_near_duplicate_session(&path, file_hash, fingerprint, &original)
+        .await?;
+
+    Ok(Some(format!(
+        "near-duplicate of {} (distance {})",
+        original.file_path, distance
+    )))
+}
+
+/// The `files` modified at or after `cutoff`
+fn modified_since(files: Vec<PathBuf>, cutoff: SystemTime) -> Vec<PathBuf> {
+    files
+        .into_iter()
+        .filter(|path| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified >= cutoff)
+        })
+        .collect()
+}
+
+/// Generate expertise ID from file path
+fn generate_expertise_id(path: &Path) -> String {
+    // Use file stem (name without extension) as base
+    let file_stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("session");
+
+    // Sanitize: replace spaces and special chars with hyphens
+    let sanitized = file_stem
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+
+    // Remove consecutive hyphens
+    let cleaned = sanitized
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    // Limit length (by characters: file names may be multibyte)
+    cleaned.chars().take(50).collect()
+}
+
+/// `id` under the project `prefix`, unless it already starts with it
+fn prefixed_id(prefix: &str, id: &str) -> String {
+    let prefix = prefix.trim_end_matches('-');
+    match id.strip_prefix(prefix) {
+        Some(rest) if rest.starts_with('-') => id.to_string(),
+        _ => format!("{}-{}", prefix, id),
+    }
+}
+
+/// Auto-link new expertises to existing ones sharing at least `min_shared` tags
+///
+/// Each new expertise `uses` every related one, with a confidence equal to
+/// the fraction of its tags that are shared. Expertises with an `excluded`
+/// status are never linked to.
+async fn tag_link_expertises(
+    db: &Database,
+    new_ids: &[String],
+    scope: Scope,
+    min_shared: usize,
+    excluded: &[ExpertiseStatus],
+) -> std::result::Result<usize, String> {
+    let storage = db.storage();
+    let graph = db.graph();
+    let mut link_count = 0;
+
+    for new_id in new_ids {
+        let new_expertise = match storage.get(new_id, scope).await {
+            Ok(Some(e)) => e,
+            _ => continue,
+        };
+        let tag_count = new_expertise.tags().len();
+        if tag_count == 0 {
+            continue;
+        }
+
+        let related = db
+            .query()
+            .related_by_tags(new_id, min_shared, Some(scope))
+            .await
+            .map_err(|e| format!("Failed to find related expertises: {}", e))?;
+
+        let existing_relations = graph.get_all_relations(new_id).await.unwrap_or_default();
+
+        for (other, shared) in related {
+            if excluded.contains(&other.metadata.status) {
+                continue;
+            }
+            let already_linked = existing_relations
+                .iter()
+                .any(|r| r.to_id == other.id() || r.from_id == other.id());
+            if already_linked {
+                continue;
+            }
+
+            let confidence = (shared as f64 / tag_count as f64).min(1.0);
+            let metadata = RelationMetadata {
+                reason: Some(format!("Shares {} tag(s)", shared)),
+                confidence: Some(confidence),
+                source: Some("auto-link:tags".to_string()),
+                ..Default::default()
+            };
+            if let Ok(()) = graph
+                .create_relation(
+                    new_id,
+                    other.id(),
+                    RelationType::Uses,
+                    Some(metadata.to_json()),
+                )
+                .await
+            {
+                info!(
+                    "Auto-linked {} -[uses]-> {} ({} shared tags, confidence: {:.2})",
+                    new_id,
+                    other.id(),
+                    shared,
+                    confidence
+                );
+                link_count += 1;
+            }
+        }
+    }
+
+    Ok(link_count)
+}
+
+/// Expertises from `all_expertises` to offer the linker for `new_expertise`
+///
+/// Without `max_candidates`, all of them but the new expertise itself.
+/// Otherwise the `max_candidates` sharing the most tags with it; expertises
+/// sharing no tag are never offered then.
+async fn link_candidates(
+    db: &Database,
+    new_expertise: &Expertise,
+    all_expertises: &[Expertise],
+    max_candidates: Option<usize>,
+) -> std::result::Result<Vec<Expertise>, String> {
+    let new_id = new_expertise.id();
+    let Some(max_candidates) = max_candidates else {
+        return Ok(all_expertises
+            .iter()
+            .filter(|e| e.id() != new_id)
+            .cloned()
+            .collect());
+    };
+
+    // Ranked by shared tags; keep only listed (not excluded) expertises
+    let allowed: std::collections::HashSet<&str> = all_expertises.iter().map(|e| e.id()).collect();
+    let related = db
+        .query()
+        .related_by_tags(new_id, 1, Some(new_expertise.metadata.scope))
+        .await
+        .map_err(|e| format!("Failed to find related expertises: {}", e))?;
+    Ok(related
+        .into_iter()
+        .map(|(expertise, _)| expertise)
+        .filter(|e| allowed.contains(e.id()))
+        .take(max_candidates)
+        .collect())
+}
+
+/// Resolve scope from a file path using scope mappings
+async fn resolve_scope_from_path(pool: &sqlx::SqlitePool, path: &Path) -> Option<Scope> {
+    let path_str = path.to_string_lossy();
+
+    // Get all mappings ordered by priority (highest first)
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT pattern, scope
+        FROM scope_mappings
+        ORDER BY priority DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .ok()?;
+
+    for (pattern, scope_str) in rows {
+        if matches_pattern(&path_str, &pattern) {
+            return scope_str.parse().ok();
+        }
+    }
+
+    None // No match found
+}
+
+/// Check if a session file has meaningful content
+///
+/// Returns true if the session has:
+/// - For JSONL (Claude): At least `min_messages` user/assistant messages combined and `min_chars` total characters
+/// - For TOML (Orcs): File size >= 5KB (heuristic for sessions with actual conversation)
+/// - For other formats: Default to true (process all files)
+///
+/// This filters out empty agent initialization logs and trivial sessions.
+fn has_meaningful_content(path: &Path, min_messages: usize, min_chars: usize) -> bool {
+    // For TOML files (Orcs sessions), use file size heuristic
+    if let Some(ext) = path.extension() {
+        if ext.to_string_lossy().to_lowercase() == "toml" {
+            // TOML sessions: check if file is >= 5KB (typical for sessions with actual content)
+            if let Ok(metadata) = std::fs::metadata(path) {
+                return metadata.len() >= 5 * 1024; // 5KB threshold
+            }
+            return false;
+        }
+    }
+
+    // For JSONL files (Claude sessions), parse JSON content
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+
+    let reader = std::io::BufReader::new(file);
+    let mut message_count = 0;
+    let mut total_chars = 0;
+
+    for line in std::io::BufRead::lines(reader) {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+
+        // Parse JSON line
+        let json: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+
+        // Check message type (user or assistant)
+        let msg_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if msg_type == "user" || msg_type == "assistant" {
+            message_count += 1;
+
+            // Extract content from message
+            if let Some(message) = json.get("message") {
+                total_chars += message_texts(message)
+                    .iter()
+                    .map(|t| t.len())
+                    .sum::<usize>();
+            }
+        }
+
+        // Early exit if we've already met the criteria
+        if message_count >= min_messages && total_chars >= min_chars {
+            return true;
+        }
+    }
+
+    message_count >= min_messages && total_chars >= min_chars
+}
+
+/// Text parts of the `message` of a JSONL session line
+fn message_texts(message: &serde_json::Value) -> Vec<&str> {
+    // Handle Claude API format: message.content array
+    if let Some(content_array) = message.get("content").and_then(|c| c.as_array()) {
+        content_array
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect()
+    }
+    // Handle simple string content
+    else if let Some(content_str) = message.get("content").and_then(|c| c.as_str()) {
+        vec![content_str]
+    }
+    // Handle direct message as string (user messages)
+    else {
+        message.as_str().into_iter().collect()
+    }
+}
+
+/// Conversation text of a session file, for near-duplicate fingerprints
+///
+/// JSONL sessions keep only user and assistant message text, so ids and
+/// timestamps don't make copies of one session look different. Other formats
+/// are used as is.
+fn session_transcript(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let is_jsonl = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"));
+    if !is_jsonl {
+        return Some(content);
+    }
+
+    let mut transcript = String::new();
+    for line in content.lines() {
+        let json: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let msg_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if msg_type != "user" && msg_type != "assistant" {
+            continue;
+        }
+        if let Some(message) = json.get("message") {
+            for text in message_texts(message) {
+                transcript.push_str(text);
+                transcript.push('\n');
+            }
+        }
+    }
+    Some(transcript)
+}
+
+/// Match a path against a glob-like pattern
+/// Supports:
+/// - `*` matches any sequence of characters (except /)
+/// - `**` matches any sequence including /
+/// - `[...]` character classes (e.g., `[0-9]`, `[a-z]`)
+/// - Literal text matches exactly
+fn matches_pattern(path: &str, pattern: &str) -> bool {
+    // Extract and preserve character classes [...] before escaping
+    let mut result = String::new();
+    let mut chars = pattern.chars().peekable();
+    let mut char_classes: Vec<String> = Vec::new();
+
+    while let Some(c) = chars.next() {
+        if c == '[' {
+            // Collect the entire character class
+            let mut class = String::from("[");
+            while let Some(&next) = chars.peek() {
+                chars.next();
+                class.push(next);
+                if next == ']' {
+                    break;
+                }
+            }
+            // Replace with placeholder (use unique marker)
+            result.push_str(&format!("__CHARCLASS{}__", char_classes.len()));
+            char_classes.push(class);
+        } else {
+            result.push(c);
+        }
+    }
+
+    // Simple glob matching
+    let pattern = result.replace("**", "__DOUBLESTAR__");
+    let pattern = pattern.replace('*', "[^/]*");
+    let pattern = pattern.replace("__DOUBLESTAR__", ".*");
+
+    // Escape other regex chars
+    let mut pattern = regex::escape(&pattern)
+        .replace(r"\[\^/\]\*", "[^/]*")
+        .replace(r"\.\*", ".*");
+
+    // Restore character classes (after escaping, the placeholder becomes escaped)
+    for (i, class) in char_classes.iter().enumerate() {
+        pattern = pattern.replace(&format!("__CHARCLASS{}__", i), class);
+    }
+
+    // Match anywhere in the path
+    let regex_pattern = format!("(?i){}", pattern); // Case-insensitive
+
+    regex::Regex::new(&regex_pattern)
+        .map(|re| re.is_match(path))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        // Simple wildcard
+        assert!(matches_pattern(
+            "/Users/test/projects/company-foo/file",
+            "company-*"
+        ));
+        assert!(matches_pattern(
+            "/Users/test/projects/niwa-cli/src",
+            "niwa-*"
+        ));
+
+        // Double wildcard
+        assert!(matches_pattern(
+            "/Users/test/work/client/project/file",
+            "work/**"
+        ));
+
+        // Exact match
+        assert!(matches_pattern("/Users/test/projects/niwa", "niwa"));
+
+        // Character classes
+        assert!(matches_pattern("/Users/test/projects/y1/file", "y[0-9]*"));
+        assert!(matches_pattern("/Users/test/projects/y23/file", "y[0-9]*"));
+        assert!(matches_pattern("/Users/test/projects/y100/file", "y[0-9]*"));
+        assert!(!matches_pattern("/Users/test/projects/yui/file", "y[0-9]*"));
+        assert!(!matches_pattern("/Users/test/projects/ya/file", "y[0-9]*"));
+
+        // No match
+        assert!(!matches_pattern("/Users/test/personal/stuff", "company-*"));
+    }
+
+    #[test]
+    fn test_non_text_reason() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let text = temp_dir.path().join("session.log");
+        std::fs::write(&text, "{\"type\":\"user\"}\n").unwrap();
+        assert_eq!(non_text_reason(&text), None);
+
+        let invalid_utf8 = temp_dir.path().join("latin1.log");
+        std::fs::write(&invalid_utf8, b"caf\xe9 au lait\n").unwrap();
+        assert_eq!(non_text_reason(&invalid_utf8), Some("not valid UTF-8"));
+
+        let binary = temp_dir.path().join("dump.log");
+        std::fs::write(&binary, b"\x7fELF\x00\x01\x02").unwrap();
+        assert_eq!(non_text_reason(&binary), Some("binary content"));
+
+        assert_eq!(non_text_reason(&temp_dir.path().join("missing.log")), None);
+    }
+
+    #[test]
+    fn test_modified_since() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let hours = |n: u64| Duration::from_secs(n * 60 * 60);
+        let touch = |name: &str, age: Duration| {
+            let path = temp_dir.path().join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            file.set_modified(now - age).unwrap();
+            path
+        };
+        let files = vec![
+            touch("fresh.jsonl", hours(1)),
+            touch("morning.jsonl", hours(5)),
+            touch("yesterday.jsonl", hours(30)),
+        ];
+
+        // Last 6 hours
+        let recent = modified_since(files.clone(), now - hours(6));
+        assert_eq!(recent, files[..2]);
+
+        assert_eq!(modified_since(files.clone(), now - hours(2)), files[..1]);
+        assert!(
+            modified_since(vec![temp_dir.path().join("missing.jsonl")], now - hours(6)).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_generate_expertise_id() {
+        assert_eq!(
+            generate_expertise_id(Path::new("session-2024-01-15.log")),
+            "session-2024-01-15"
+        );
+        assert_eq!(
+            generate_expertise_id(Path::new("My Session Log.txt")),
+            "my-session-log"
+        );
+        assert_eq!(
+            generate_expertise_id(Path::new("rust_async_patterns.md")),
+            "rust-async-patterns"
+        );
+
+        let long_japanese = format!("{}.md", "設計レビュー".repeat(10));
+        assert_eq!(
+            generate_expertise_id(Path::new(&long_japanese))
+                .chars()
+                .count(),
+            50
+        );
+    }
+
+    #[test]
+    fn test_prefixed_id() {
+        assert_eq!(prefixed_id("billing", "rust-errors"), "billing-rust-errors");
+        assert_eq!(
+            prefixed_id("billing-", "rust-errors"),
+            "billing-rust-errors"
+        );
+        // Already prefixed, e.g. by the LLM following the project name
+        assert_eq!(
+            prefixed_id("billing", "billing-invoices"),
+            "billing-invoices"
+        );
+        assert_eq!(
+            prefixed_id("bill", "billing-invoices"),
+            "bill-billing-invoices"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = niwa_core::Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+        let pool = db.pool();
+
+        let files = vec![
+            (PathBuf::from("/logs/a.log"), "hash-a".to_string()),
+            (PathBuf::from("/logs/b.log"), "hash-b".to_string()),
+            (PathBuf::from("/logs/c.log"), "hash-c".to_string()),
+        ];
+        save_checkpoint(pool, "/logs", &files).await.unwrap();
+        mark_checkpoint_done(pool, "/logs", Path::new("/logs/a.log"))
+            .await
+            .unwrap();
+
+        let checkpoint = load_checkpoint(pool, "/logs").await.unwrap().unwrap();
+        assert_eq!(checkpoint.total, 3);
+        assert_eq!(checkpoint.done, 1);
+        assert_eq!(checkpoint.pending, files[1..].to_vec());
+
+        clear_checkpoint(pool, "/logs").await.unwrap();
+        assert!(load_checkpoint(pool, "/logs").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_check_size() {
+        assert_eq!(check_size(500, None, None), SizeCheck::Within);
+        assert_eq!(check_size(500, Some(100), Some(500)), SizeCheck::Within);
+        assert_eq!(check_size(501, Some(100), Some(500)), SizeCheck::TooLarge);
+        assert_eq!(check_size(99, Some(100), Some(500)), SizeCheck::TooSmall);
+
+        assert_eq!(skipped_breakdown(&[(0, "trivial".to_string())]), None);
+        assert_eq!(
+            skipped_breakdown(&[(0, "trivial".to_string()), (2, "too large".to_string())]),
+            Some("Skipped before processing: 2 too large".to_string())
+        );
+    }
+
+    #[test]
+    fn test_filter_included() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for name in [
+            "rust-notes.log",
+            "rust-notes.rs",
+            "python.log",
+            "Rust-async.md",
+        ] {
+            std::fs::write(temp_dir.path().join(name), "content").unwrap();
+        }
+
+        let defaults = scan_extensions(Vec::new(), None);
+        let files = scan_session_files(temp_dir.path(), &defaults).unwrap();
+
+        // Extension filter runs first, so rust-notes.rs is never a candidate
+        let mut names: Vec<String> = filter_included(files.clone(), &["*rust*".to_string()])
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["Rust-async.md", "rust-notes.log"]);
+
+        // Multiple patterns are OR-ed
+        let included = filter_included(files.clone(), &["python*".to_string(), "*.md".to_string()]);
+        assert_eq!(included.len(), 2);
+
+        // No patterns keeps everything
+        assert_eq!(filter_included(files, &[]).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_skip_near_duplicate() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let write_session = |name: &str, messages: &[&str]| {
+            let lines: Vec<String> = messages
+                .iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    let role = if i % 2 == 0 { "user" } else { "assistant" };
+                    serde_json::json!({
+                        "type": role,
+                        "uuid": format!("{}-{}", name, i),
+                        "message": { "role": role, "content": text },
+                    })
+                    .to_string()
+                })
+                .collect();
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, lines.join("\n")).unwrap();
+            path
+        };
+        let messages = [
+            "How do I handle errors in Rust without unwrap everywhere?",
+            "Use the question mark operator and return Result from your functions. \
+             Define an error enum with thiserror for library code and use anyhow in binaries.",
+            "What about converting between error types?",
+            "Implement From for your error type, or use the from attribute of thiserror, \
+             so the question mark operator converts automatically.",
+            "Should I ever panic?",
+            "Panic only on bugs and broken invariants, never on recoverable conditions \
+             such as missing files or bad user input.",
+        ];
+        let original = write_session("original.jsonl", &messages);
+        // The same session resumed for one more turn, with new line ids
+        let resumed = write_session(
+            "resumed.jsonl",
+            &[messages.as_slice(), &["Thanks, that helps."]].concat(),
+        );
+        let unrelated = write_session(
+            "unrelated.jsonl",
+            &[
+                "How should I structure a Python project?",
+                "Set up a virtual environment with venv and pin your dependencies in \
+                 requirements.txt. Use pytest fixtures for shared setup and parametrize \
+                 to cover many inputs.",
+                "And formatting?",
+                "Run black and ruff in CI so formatting never blocks review.",
+            ],
+        );
+        let fingerprint_of = |path: &Path| fingerprint::simhash(&session_transcript(path).unwrap());
+
+        let storage = db.storage();
+        let original_path = original.to_string_lossy();
+        storage
+            .record_processed_session(
+                &original_path,
+                "hash-original",
+                vec![Expertise::new("rust-errors", "1.0.0")],
+            )
+            .await
+            .unwrap();
+        storage
+            .set_session_fingerprint(&original_path, fingerprint_of(&original))
+            .await
+            .unwrap();
+
+        let max_distance = fingerprint::DEFAULT_NEAR_DUPLICATE_DISTANCE;
+        let reason = skip_near_duplicate(
+            &db,
+            &resumed,
+            "hash-resumed",
+            fingerprint_of(&resumed),
+            max_distance,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(reason.starts_with(&format!("near-duplicate of {}", original_path)));
+
+        let skipped = skip_near_duplicate(
+            &db,
+            &unrelated,
+            "hash-unrelated",
+            fingerprint_of(&unrelated),
+            max_distance,
+        )
+        .await
+        .unwrap();
+        assert!(skipped.is_none());
+
+        // The skipped session is recorded against the original's expertise
+        let sessions = storage.list_processed_sessions().await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        let resumed_session = sessions
+            .iter()
+            .find(|s| s.file_path == resumed.to_string_lossy())
+            .unwrap();
+        assert_eq!(resumed_session.expertise_id, "rust-errors");
+    }
+
+    #[tokio::test]
+    async fn test_create_suggested_links() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+        for id in ["axum", "tokio", "actix"] {
+            db.storage()
+                .create(niwa_core::Expertise::new(id, "1.0.0"))
+                .await
+                .unwrap();
+        }
+
+        let link = |to_id: &str, relation_type: &str| SuggestedLink {
+            from_id: "axum".to_string(),
+            to_id: to_id.to_string(),
+            relation_type: relation_type.to_string(),
+            reason: "Built on it".to_string(),
+            confidence: 0.9,
+        };
+        let links = [link("tokio", "Requires"), link("actix", "supersedes")];
+
+        // A hallucinated type is skipped, not coerced to uses
+        assert_eq!(create_suggested_links(&db, &links).await, (1, 1));
+        let relations = db.graph().get_outgoing("axum").await.unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].to_id, "tokio");
+        assert_eq!(relations[0].relation_type, RelationType::Requires);
+    }
+
+    #[tokio::test]
+    async fn test_tag_link_expertises() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let tagged = |id: &str, tags: &[&str]| {
+            let mut exp = niwa_core::Expertise::new(id, "1.0.0");
+            exp.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+            exp
+        };
+        for exp in [
+            tagged("tokio", &["rust", "async", "runtime"]),
+            tagged("serde", &["rust", "serialization"]),
+            tagged("axum", &["rust", "async", "web", "http"]),
+        ] {
+            db.storage().create(exp).await.unwrap();
+        }
+
+        let new_ids = vec!["axum".to_string()];
+        let count = tag_link_expertises(&db, &new_ids, Scope::Personal, 2, &[])
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let relations = db.graph().get_outgoing("axum").await.unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].to_id, "tokio");
+        assert_eq!(relations[0].relation_type, RelationType::Uses);
+        let metadata = relations[0].parsed_metadata();
+        assert_eq!(metadata.confidence, Some(0.5));
+
+        // Existing links are not duplicated
+        let count = tag_link_expertises(&db, &new_ids, Scope::Personal, 2, &[])
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        // Deprecated expertises are not linked to by default
+        db.storage()
+            .create(tagged("hyper", &["rust", "async", "http"]))
+            .await
+            .unwrap();
+        db.storage()
+            .set_status("hyper", Scope::Personal, ExpertiseStatus::Deprecated)
+            .await
+            .unwrap();
+        let excluded = [ExpertiseStatus::Deprecated];
+        let count = tag_link_expertises(&db, &new_ids, Scope::Personal, 2, &excluded)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+        let count = tag_link_expertises(&db, &new_ids, Scope::Personal, 2, &[])
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_link_candidates() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let tagged = |id: &str, tags: &[&str]| {
+            let mut exp = Expertise::new(id, "1.0.0");
+            exp.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+            exp
+        };
+        for exp in [
+            tagged("axum", &["rust", "async", "web", "http"]),
+            tagged("hyper", &["rust", "async", "http"]),
+            tagged("tokio", &["rust", "async"]),
+            tagged("serde", &["rust"]),
+            tagged("django", &["python", "web"]),
+            tagged("pandas", &["python"]),
+        ] {
+            db.storage().create(exp).await.unwrap();
+        }
+        let all = db.storage().list(Scope::Personal).await.unwrap();
+        let axum = all.iter().find(|e| e.id() == "axum").unwrap().clone();
+        let ids = |candidates: Vec<Expertise>| -> Vec<String> {
+            candidates.iter().map(|e| e.id().to_string()).collect()
+        };
+
+        // Without a limit, everything but the new expertise goes to the linker
+        let candidates = link_candidates(&db, &axum, &all, None).await.unwrap();
+        assert_eq!(candidates.len(), 5);
+        assert!(!ids(candidates).contains(&"axum".to_string()));
+
+        // With one, only the top K by shared tags; pandas shares none
+        let candidates = link_candidates(&db, &axum, &all, Some(3)).await.unwrap();
+        assert_eq!(ids(candidates), vec!["hyper", "tokio", "django"]);
+        let candidates = link_candidates(&db, &axum, &all, Some(10)).await.unwrap();
+        assert!(!ids(candidates).contains(&"pandas".to_string()));
+
+        // Expertises left out of the listing (e.g. excluded statuses) stay out
+        let listed: Vec<Expertise> = all.into_iter().filter(|e| e.id() != "hyper").collect();
+        let candidates = link_candidates(&db, &axum, &listed, Some(2)).await.unwrap();
+        assert_eq!(ids(candidates), vec!["tokio", "django"]);
+    }
+}
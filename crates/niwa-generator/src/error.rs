@@ -1,5 +1,6 @@
 //! Error types for niwa-generator
 
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Result type for niwa-generator operations
@@ -36,6 +37,28 @@ pub enum Error {
     #[error("Agent error: {0}")]
     Agent(#[from] llm_toolkit::agent::AgentError),
 
+    /// The extractor reported less confidence than `min_confidence`
+    #[error("Extraction confidence {confidence:.2} is below the minimum")]
+    LowConfidence { confidence: f32 },
+
+    /// The extractor returned neither a description nor any fragment
+    #[error("Extraction is empty (no description and no fragments)")]
+    EmptyExtraction,
+
+    /// The extractor returned fewer fragments than `min_fragments`
+    #[error("Extraction has {count} fragment(s), fewer than the minimum of {min}")]
+    TooFewFragments { count: usize, min: usize },
+
+    /// Generated content contains possible secrets under [`SecretPolicy::Reject`]
+    ///
+    /// [`SecretPolicy::Reject`]: crate::SecretPolicy::Reject
+    #[error("Generated content contains possible secrets ({})", .kinds.join(", "))]
+    SecretDetected { kinds: Vec<String> },
+
+    /// A crawl was pointed at a path that is not a directory
+    #[error("Directory not found: {}", .0.display())]
+    DirectoryNotFound(PathBuf),
+
     /// Generic error
     #[error("{0}")]
     Other(String),
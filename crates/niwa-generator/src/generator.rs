@@ -1,10 +1,17 @@
 //! Expertise generator using LLM
 
 use crate::agents::{
+    ChangelogExtractorAgent, CodeAnalysisAgent, CustomPromptAgent, DescriptionGeneratorAgent,
     ExpertiseExtractorAgent, ExpertiseImproverAgent, ExpertiseLinkerAgent, ExpertiseMergerAgent,
-    ExpertiseSummary, FileBasedExpertiseExtractorAgent, InteractiveExpertiseAgent, SuggestedLink,
+    ExpertisePart, ExpertiseResponse, ExpertiseSummary, FileBasedExpertiseExtractorAgent,
+    FragmentUpgraderAgent, ImplicitRelation, InteractiveExpertiseAgent, MeetingNotesAgent,
+    MultiExpertiseResponse, QualityCheckResponse, QualityCheckerAgent, RelationExtractionAgent,
+    SemverBump, SuggestedLink, TagBasedSplitterAgent, TagSuggesterAgent, TranslatorAgent,
+    UpgradedFragment, VersionAnalysisAgent, VersionBumpRecommendation,
 };
-use crate::Result;
+use crate::secrets::{self, SecretPolicy};
+use crate::{Error, Result};
+use async_trait::async_trait;
 use llm_toolkit::{
     agent::{
         impls::{ClaudeCodeAgent, CodexAgent, GeminiAgent},
@@ -13,14 +20,17 @@ use llm_toolkit::{
     attachment::Attachment,
     Agent, AgentError,
 };
-use niwa_core::{Expertise, Scope};
+use llm_toolkit_expertise::{KnowledgeFragment, Priority};
+use niwa_core::similarity::{text_similarity, DUPLICATE_FRAGMENT_SIMILARITY};
+use niwa_core::{Expertise, ExpertiseStatus, Scope};
 use std::path::Path;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// LLM Provider options
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum LlmProvider {
     /// Claude (Anthropic)
+    #[default]
     Claude,
     /// Gemini (Google)
     Gemini,
@@ -28,10 +38,55 @@ pub enum LlmProvider {
     Codex,
 }
 
-impl Default for LlmProvider {
-    fn default() -> Self {
-        Self::Claude
+/// Raw LLM backend for the configured provider and model
+///
+/// The typed agents wrap it, so generation methods pick the provider once
+/// through [`ExpertiseGenerator::run_agent`] instead of matching on it.
+enum Backend {
+    Claude(ClaudeCodeAgent),
+    Gemini(GeminiAgent),
+    Codex(CodexAgent),
+}
+
+#[async_trait]
+impl Agent for Backend {
+    type Output = String;
+    type Expertise = String;
+
+    fn expertise(&self) -> &String {
+        // A raw backend has no system prompt of its own
+        static NONE: String = String::new();
+        &NONE
+    }
+
+    async fn execute(&self, intent: Payload) -> std::result::Result<String, AgentError> {
+        match self {
+            Self::Claude(backend) => backend.execute(intent).await,
+            Self::Gemini(backend) => backend.execute(intent).await,
+            Self::Codex(backend) => backend.execute(intent).await,
+        }
     }
+
+    async fn is_available(&self) -> std::result::Result<(), AgentError> {
+        match self {
+            Self::Claude(backend) => backend.is_available().await,
+            Self::Gemini(backend) => backend.is_available().await,
+            Self::Codex(backend) => backend.is_available().await,
+        }
+    }
+}
+
+/// How [`ExpertiseGenerator::improve`] bumps the version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionBumpStrategy {
+    /// Always bump the minor version
+    #[default]
+    Minor,
+    /// Always apply this bump
+    Semantic(SemverBump),
+    /// Let the LLM choose from the size of the change
+    /// (see [`ExpertiseGenerator::suggest_version_bump`])
+    Auto,
 }
 
 /// Generation options
@@ -43,8 +98,31 @@ pub struct GenerationOptions {
     pub model: String,
     /// Temperature (0.0-1.0)
     pub temperature: f32,
-    /// Additional context to include
+    /// Domain context (glossary, project conventions) appended to generation prompts
     pub additional_context: Option<String>,
+    /// System prompt replacing the built-in extractor prompt (log and file extraction)
+    pub extractor_system_prompt: Option<String>,
+    /// Tags the LLM is asked to include alongside its own suggestions
+    pub seed_tags: Vec<String>,
+    /// Tags added to every generated expertise, whatever the LLM returns
+    pub required_tags: Vec<String>,
+    /// Reject log extractions whose reported confidence is lower (0.0-1.0)
+    pub min_confidence: f32,
+    /// Keep extractions with neither a description nor any fragment
+    pub keep_empty: bool,
+    /// Fewest fragments a log extraction may have
+    ///
+    /// Together with `max_fragments` this sets the acceptable range
+    /// `[min, max]`, and the prompt asks the LLM to stay inside it. Fewer
+    /// fragments fail with [`Error::TooFewFragments`]; more are cut to the
+    /// first `max_fragments`, which the LLM was asked to put first.
+    pub min_fragments: Option<usize>,
+    /// Most fragments a log extraction may have (see `min_fragments`)
+    pub max_fragments: Option<usize>,
+    /// How `improve` bumps the version
+    pub version_strategy: VersionBumpStrategy,
+    /// What to do with secrets found in generated logs, improvements and merges
+    pub on_secret: SecretPolicy,
 }
 
 impl Default for GenerationOptions {
@@ -54,6 +132,15 @@ impl Default for GenerationOptions {
             model: "claude-sonnet-4-5".to_string(),
             temperature: 0.7,
             additional_context: None,
+            extractor_system_prompt: None,
+            seed_tags: Vec::new(),
+            required_tags: Vec::new(),
+            min_confidence: 0.0,
+            keep_empty: false,
+            min_fragments: None,
+            max_fragments: None,
+            version_strategy: VersionBumpStrategy::default(),
+            on_secret: SecretPolicy::default(),
         }
     }
 }
@@ -93,6 +180,259 @@ impl ExpertiseGenerator {
         Ok(Self { options })
     }
 
+    /// Get the generation options in use
+    pub fn options(&self) -> &GenerationOptions {
+        &self.options
+    }
+
+    /// Append the seed-tag instruction to an extraction prompt, if seed tags are set
+    fn with_seed_tags(&self, mut prompt: String) -> String {
+        if !self.options.seed_tags.is_empty() {
+            prompt.push_str(&format!(
+                "\n\nIn addition to any relevant tags you identify, please include these \
+                 user-specified tags: {}\n",
+                self.options.seed_tags.join(", ")
+            ));
+        }
+        prompt
+    }
+
+    /// Append the fragment count limits to an extraction prompt, if set
+    fn with_fragment_limits(&self, mut prompt: String) -> String {
+        if let Some(min) = self.options.min_fragments {
+            prompt.push_str(&format!("\n\nExtract at least {} fragments.", min));
+        }
+        if let Some(max) = self.options.max_fragments {
+            prompt.push_str(&format!(
+                "\n\nExtract at most {} of the most important fragments. Prioritize unique \
+                 domain-specific knowledge over general best practices.",
+                max
+            ));
+        }
+        prompt
+    }
+
+    /// Provider and model recorded as `generated_by`, e.g. "claude/claude-sonnet-4-5"
+    fn generated_by(&self) -> String {
+        match self.options.provider {
+            LlmProvider::Claude if self.options.model.is_empty() => "claude".to_string(),
+            LlmProvider::Claude => format!("claude/{}", self.options.model),
+            LlmProvider::Gemini => "gemini".to_string(),
+            LlmProvider::Codex => "codex".to_string(),
+        }
+    }
+
+    /// Backend for the configured provider and model
+    fn backend(&self) -> Backend {
+        match self.options.provider {
+            LlmProvider::Claude => {
+                if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
+                    Backend::Claude(ClaudeCodeAgent::new())
+                } else {
+                    Backend::Claude(ClaudeCodeAgent::new().with_model_str(&self.options.model))
+                }
+            }
+            LlmProvider::Gemini => Backend::Gemini(GeminiAgent::new()),
+            LlmProvider::Codex => Backend::Codex(CodexAgent::new()),
+        }
+    }
+
+    /// Execute `payload` with the agent `make` builds around the configured backend
+    async fn run_agent<A>(
+        &self,
+        make: impl FnOnce(Backend) -> A,
+        payload: Payload,
+    ) -> std::result::Result<A::Output, AgentError>
+    where
+        A: Agent,
+    {
+        make(self.backend()).execute(payload).await
+    }
+
+    /// Record which model generated `expertise`, when, and from what kind of input
+    fn stamp_provenance(&self, expertise: &mut Expertise, source_type: &str) {
+        expertise.metadata.generated_by = Some(self.generated_by());
+        expertise.metadata.generated_at = Some(chrono::Utc::now().timestamp());
+        expertise.metadata.source_type = Some(source_type.to_string());
+    }
+
+    /// Append `additional_context` to a prompt, if set
+    fn with_additional_context(&self, mut prompt: String) -> String {
+        if let Some(context) = self.options.additional_context.as_deref() {
+            prompt.push_str(&format!("\n\nAdditional Context:\n{}", context));
+        }
+        prompt
+    }
+
+    /// Prompt for [`generate_from_log`](Self::generate_from_log)
+    fn log_prompt(&self, log_content: &str) -> String {
+        let prompt = format!(
+            "Analyze the following conversation log and extract structured expertise.\n\n\
+             =====================================================================\n
+             Log Content Start\n
+             =====================================================================\n
+             {}
+             =====================================================================\n
+             Log Content End\n
+             =====================================================================\n
+             ",
+            log_content
+        );
+        self.with_fragment_limits(self.with_seed_tags(self.with_additional_context(prompt)))
+    }
+
+    /// Prompt for [`improve`](Self::improve)
+    fn improve_prompt(&self, current_json: &str, instruction: &str) -> String {
+        let prompt = format!(
+            "Current Expertise:\n{}\n\nImprovement Instruction:\n{}\n\n\
+             Please analyze the current expertise and apply the improvement instruction. \
+             Identify what to add, update, or remove to make this expertise more valuable.",
+            current_json, instruction
+        );
+        self.with_additional_context(prompt)
+    }
+
+    /// Prompt for [`merge`](Self::merge)
+    fn merge_prompt(
+        &self,
+        expertises_json: &[String],
+        output_id: &str,
+        description: &str,
+    ) -> String {
+        let prompt = format!(
+            "Target Output ID: {}\nTarget Description: {}\n\n\
+             Expertises to Merge:\n{}\n\n\
+             Please synthesize these expertises into a unified, coherent expertise. \
+             Identify common themes, preserve unique insights, and resolve any conflicts.",
+            output_id,
+            description,
+            expertises_json.join("\n\n---\n\n")
+        );
+        self.with_additional_context(prompt)
+    }
+
+    /// Tags of an extraction response with the required tags added
+    fn with_required_tags(&self, tags: Vec<String>) -> Vec<String> {
+        add_required_tags(tags, &self.options.required_tags)
+    }
+
+    /// Fail with [`Error::LowConfidence`] below `min_confidence`
+    fn check_confidence(&self, confidence: f32) -> Result<()> {
+        if confidence < self.options.min_confidence {
+            return Err(Error::LowConfidence { confidence });
+        }
+        Ok(())
+    }
+
+    /// Fail with [`Error::EmptyExtraction`] for an empty response unless `keep_empty`
+    ///
+    /// Trivial logs often yield a blank description and no fragments; such
+    /// an expertise would only add noise to the graph.
+    fn check_not_empty(&self, response: &ExpertiseResponse) -> Result<()> {
+        if !self.options.keep_empty && is_empty_extraction(response) {
+            return Err(Error::EmptyExtraction);
+        }
+        Ok(())
+    }
+
+    /// Apply `on_secret` to secrets and personal data in `expertise`
+    ///
+    /// The LLM can echo them from its input even when asked not to, so this
+    /// runs on everything it wrote before the expertise is returned.
+    fn check_secrets(&self, expertise: &mut Expertise) -> Result<()> {
+        let kinds = secrets::expertise_secrets(expertise);
+        if kinds.is_empty() {
+            return Ok(());
+        }
+
+        match self.options.on_secret {
+            SecretPolicy::Redact => {
+                warn!(
+                    "Redacted possible secrets in {}: {}",
+                    expertise.id(),
+                    kinds.join(", ")
+                );
+                secrets::redact_expertise(expertise);
+            }
+            SecretPolicy::Warn => {
+                warn!(
+                    "{} may contain secrets ({}); marked as draft for review",
+                    expertise.id(),
+                    kinds.join(", ")
+                );
+                expertise.metadata.status = ExpertiseStatus::Draft;
+            }
+            SecretPolicy::Reject => {
+                return Err(Error::SecretDetected {
+                    kinds: kinds.into_iter().map(str::to_string).collect(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Cut `fragments` to `max_fragments`, failing with
+    /// [`Error::TooFewFragments`] below `min_fragments`
+    fn apply_fragment_limits(&self, mut fragments: Vec<String>) -> Result<Vec<String>> {
+        if let Some(max) = self.options.max_fragments {
+            if fragments.len() > max {
+                debug!("Keeping the first {} of {} fragments", max, fragments.len());
+                fragments.truncate(max);
+            }
+        }
+        if let Some(min) = self.options.min_fragments {
+            if fragments.len() < min {
+                return Err(Error::TooFewFragments {
+                    count: fragments.len(),
+                    min,
+                });
+            }
+        }
+        Ok(fragments)
+    }
+
+    /// Run log extraction, honoring `extractor_system_prompt` if set
+    async fn execute_extractor<B>(
+        &self,
+        backend: B,
+        payload: Payload,
+    ) -> std::result::Result<ExpertiseResponse, AgentError>
+    where
+        B: Agent<Output = String>,
+    {
+        match &self.options.extractor_system_prompt {
+            Some(system_prompt) => {
+                let agent: CustomPromptAgent<_, ExpertiseResponse> =
+                    CustomPromptAgent::new(backend, system_prompt);
+                agent.execute(payload).await
+            }
+            None => ExpertiseExtractorAgent::new(backend).execute(payload).await,
+        }
+    }
+
+    /// Run file-based extraction, honoring `extractor_system_prompt` if set
+    async fn execute_file_extractor<B>(
+        &self,
+        backend: B,
+        payload: Payload,
+    ) -> std::result::Result<MultiExpertiseResponse, AgentError>
+    where
+        B: Agent<Output = String>,
+    {
+        match &self.options.extractor_system_prompt {
+            Some(system_prompt) => {
+                let agent: CustomPromptAgent<_, MultiExpertiseResponse> =
+                    CustomPromptAgent::new(backend, system_prompt);
+                agent.execute(payload).await
+            }
+            None => {
+                FileBasedExpertiseExtractorAgent::new(backend)
+                    .execute(payload)
+                    .await
+            }
+        }
+    }
+
     /// Generate Expertise from conversation log
     ///
     /// # Arguments
@@ -129,18 +469,7 @@ impl ExpertiseGenerator {
         info!("Generating expertise from log: fallback_id={}", fallback_id);
 
         // Build prompt for the agent
-        let prompt = format!(
-            "Analyze the following conversation log and extract structured expertise.\n\n\
-             =====================================================================\n
-             Log Content Start\n
-             =====================================================================\n
-             {}
-             =====================================================================\n
-             Log Content End\n
-             =====================================================================\n
-             ",
-            log_content
-        );
+        let prompt = self.log_prompt(log_content);
 
         // Use the Agent macro-powered agent
         // Agent derive automatically handles:
@@ -150,71 +479,10 @@ impl ExpertiseGenerator {
         // - Error handling with proper error messages
 
         // Create agent based on configured provider
-        let response = match self.options.provider {
-            LlmProvider::Claude => {
-                let backend = if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
-                    ClaudeCodeAgent::new()
-                } else {
-                    ClaudeCodeAgent::new().with_model_str(&self.options.model)
-                };
-                let agent = ExpertiseExtractorAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Gemini => {
-                let backend = GeminiAgent::new();
-                let agent = ExpertiseExtractorAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Codex => {
-                let backend = CodexAgent::new();
-                let agent = ExpertiseExtractorAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-        };
+        let response = self.execute_extractor(self.backend(), prompt.into()).await;
 
         match response {
-            Ok(response) => {
-                // Use LLM-suggested ID if valid, otherwise use fallback
-                let expertise_id = if is_valid_id(&response.suggested_id) {
-                    info!(
-                        "Using LLM-suggested ID: {} (fallback was: {})",
-                        response.suggested_id, fallback_id
-                    );
-                    response.suggested_id.clone()
-                } else {
-                    info!(
-                        "LLM suggested invalid ID '{}', using fallback: {}",
-                        response.suggested_id, fallback_id
-                    );
-                    fallback_id.to_string()
-                };
-
-                info!(
-                    "Successfully extracted expertise: id={}, {} tags, {} fragments",
-                    expertise_id,
-                    response.tags.len(),
-                    response.fragments.len()
-                );
-
-                // Convert ExpertiseResponse to Expertise
-                let mut expertise = Expertise::new(&expertise_id, "1.0.0");
-                expertise.inner.description = Some(response.description);
-                expertise.inner.tags = response.tags;
-                expertise.metadata.scope = scope;
-
-                // Add text fragments
-                use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
-                for fragment_text in response.fragments {
-                    expertise
-                        .inner
-                        .content
-                        .push(WeightedFragment::new(KnowledgeFragment::Text(
-                            fragment_text,
-                        )));
-                }
-
-                Ok(expertise)
-            }
+            Ok(response) => self.log_expertise(response, fallback_id, scope),
             Err(e) => {
                 // Agent error - return error
                 error!("LLM generation failed: {:?}", e);
@@ -223,6 +491,65 @@ impl ExpertiseGenerator {
         }
     }
 
+    /// Convert a log extraction response into an Expertise
+    ///
+    /// Uses the LLM-suggested ID when valid, applies the confidence and
+    /// emptiness checks, and stamps `source_type = "log"` provenance.
+    fn log_expertise(
+        &self,
+        response: ExpertiseResponse,
+        fallback_id: &str,
+        scope: Scope,
+    ) -> Result<Expertise> {
+        // Use LLM-suggested ID if valid, otherwise use fallback
+        let expertise_id = if is_valid_id(&response.suggested_id) {
+            info!(
+                "Using LLM-suggested ID: {} (fallback was: {})",
+                response.suggested_id, fallback_id
+            );
+            response.suggested_id.clone()
+        } else {
+            info!(
+                "LLM suggested invalid ID '{}', using fallback: {}",
+                response.suggested_id, fallback_id
+            );
+            fallback_id.to_string()
+        };
+
+        info!(
+            "Successfully extracted expertise: id={}, {} tags, {} fragments, confidence {:.2}",
+            expertise_id,
+            response.tags.len(),
+            response.fragments.len(),
+            response.confidence
+        );
+        self.check_confidence(response.confidence)?;
+        self.check_not_empty(&response)?;
+        let fragments = self.apply_fragment_limits(response.fragments)?;
+
+        // Convert ExpertiseResponse to Expertise
+        let mut expertise = Expertise::new(&expertise_id, "1.0.0");
+        expertise.inner.description = Some(response.description);
+        expertise.inner.tags = self.with_required_tags(response.tags);
+        expertise.metadata.scope = scope;
+
+        // Add text fragments
+        use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
+        for fragment_text in fragments {
+            expertise
+                .inner
+                .content
+                .push(WeightedFragment::new(KnowledgeFragment::Text(
+                    fragment_text,
+                )));
+        }
+
+        self.stamp_provenance(&mut expertise, "log");
+        self.check_secrets(&mut expertise)?;
+
+        Ok(expertise)
+    }
+
     /// Generate one or more Expertises from a session log file
     ///
     /// This method is designed to handle large session files by using file attachments
@@ -275,46 +602,31 @@ impl ExpertiseGenerator {
         let attachment = Attachment::local(file_path.to_path_buf());
 
         // Build prompt with file reference
-        let prompt = format!(
-            "Analyze the attached session log file and extract structured expertise.\n\n\
+        let prompt = "Analyze the attached session log file and extract structured expertise.\n\n\
              The file contains a conversation log. Please read it entirely and extract domain-specific knowledge.\n\
              If the session covers multiple distinct domains, extract each as a separate expertise."
-        );
+            .to_string();
+        let prompt = self.with_seed_tags(self.with_additional_context(prompt));
 
         // Create payload with both text and file attachment
-        let payload = Payload::new()
-            .with_text(prompt)
-            .with_attachment(attachment);
+        let payload = Payload::new().with_text(prompt).with_attachment(attachment);
 
         // Use the file-based agent with configured provider
-        let response = match self.options.provider {
-            LlmProvider::Claude => {
-                let backend = if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
-                    ClaudeCodeAgent::new()
-                } else {
-                    ClaudeCodeAgent::new().with_model_str(&self.options.model)
-                };
-                let agent = FileBasedExpertiseExtractorAgent::new(backend);
-                agent.execute(payload).await
-            }
-            LlmProvider::Gemini => {
-                let backend = GeminiAgent::new();
-                let agent = FileBasedExpertiseExtractorAgent::new(backend);
-                agent.execute(payload).await
-            }
-            LlmProvider::Codex => {
-                let backend = CodexAgent::new();
-                let agent = FileBasedExpertiseExtractorAgent::new(backend);
-                agent.execute(payload).await
-            }
-        };
+        let response = self.execute_file_extractor(self.backend(), payload).await;
 
         match response {
             Ok(response) => {
                 let mut expertises = Vec::new();
+                let mut empty = 0;
 
                 // Process each expertise in the response
                 for (idx, expertise_resp) in response.expertises.into_iter().enumerate() {
+                    if self.check_not_empty(&expertise_resp).is_err() {
+                        info!("Dropping empty extraction #{}", idx);
+                        empty += 1;
+                        continue;
+                    }
+
                     // Use LLM-suggested ID if valid, otherwise use fallback with index
                     let expertise_id = if is_valid_id(&expertise_resp.suggested_id) {
                         info!(
@@ -345,23 +657,27 @@ impl ExpertiseGenerator {
                     // Convert ExpertiseResponse to Expertise
                     let mut expertise = Expertise::new(&expertise_id, "1.0.0");
                     expertise.inner.description = Some(expertise_resp.description);
-                    expertise.inner.tags = expertise_resp.tags;
+                    expertise.inner.tags = self.with_required_tags(expertise_resp.tags);
                     expertise.metadata.scope = scope;
 
                     // Add text fragments
                     use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
                     for fragment_text in expertise_resp.fragments {
-                        expertise
-                            .inner
-                            .content
-                            .push(WeightedFragment::new(KnowledgeFragment::Text(
-                                fragment_text,
-                            )));
+                        expertise.inner.content.push(WeightedFragment::new(
+                            KnowledgeFragment::Text(fragment_text),
+                        ));
                     }
 
+                    self.stamp_provenance(&mut expertise, "log");
+                    expertise.metadata.source_file = Some(file_path.display().to_string());
+                    self.check_secrets(&mut expertise)?;
+
                     expertises.push(expertise);
                 }
 
+                if expertises.is_empty() && empty > 0 {
+                    return Err(Error::EmptyExtraction);
+                }
                 if expertises.is_empty() {
                     error!("No expertises extracted from file");
                     return Err(crate::error::Error::Agent(AgentError::ProcessError {
@@ -383,102 +699,82 @@ impl ExpertiseGenerator {
         }
     }
 
-    /// Improve existing Expertise
+    /// Generate Expertise from a source code file
+    ///
+    /// The code is wrapped in a fenced block tagged with `language`. The
+    /// resulting expertise always carries a `language:<language>` tag.
     ///
     /// # Arguments
     ///
-    /// * `expertise` - The Expertise to improve
-    /// * `instruction` - How to improve it
+    /// * `code` - The source code
+    /// * `language` - Language name used for the code fence and tag (e.g., "rust")
+    /// * `id` - ID for the new Expertise
+    /// * `scope` - Scope for the new Expertise
     ///
     /// # Example
     ///
     /// ```no_run
     /// use niwa_generator::ExpertiseGenerator;
-    /// use niwa_core::{Database, Scope, StorageOperations};
+    /// use niwa_core::Scope;
     ///
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
-    ///     let db = Database::open_default().await?;
     ///     let generator = ExpertiseGenerator::new().await?;
+    ///     let code = std::fs::read_to_string("src/storage.rs")?;
     ///
-    ///     let expertise = db.storage()
-    ///         .get("rust-expert", Scope::Personal)
-    ///         .await?
-    ///         .unwrap();
-    ///
-    ///     let improved = generator
-    ///         .improve(expertise, "Add more error handling examples")
+    ///     let expertise = generator
+    ///         .generate_from_code(&code, "rust", "niwa-storage-design", Scope::Project)
     ///         .await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn improve(&self, expertise: Expertise, instruction: &str) -> Result<Expertise> {
-        info!("Improving expertise: id={}", expertise.id());
-
-        let current_json = expertise.to_json()?;
+    pub async fn generate_from_code(
+        &self,
+        code: &str,
+        language: &str,
+        id: &str,
+        scope: Scope,
+    ) -> Result<Expertise> {
+        info!(
+            "Generating expertise from code: id={}, language={}",
+            id, language
+        );
 
-        // Build prompt for the agent
         let prompt = format!(
-            "Current Expertise:\n{}\n\nImprovement Instruction:\n{}\n\n\
-             Please analyze the current expertise and apply the improvement instruction. \
-             Identify what to add, update, or remove to make this expertise more valuable.",
-            current_json, instruction
+            "Analyze the following {} source code and extract structured expertise.\n\n\
+             ```{}\n{}\n```\n",
+            language, language, code
         );
+        let prompt = self.with_seed_tags(prompt);
 
-        // Use the Agent macro-powered agent with configured provider
-        let response = match self.options.provider {
-            LlmProvider::Claude => {
-                let backend = if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
-                    ClaudeCodeAgent::new()
-                } else {
-                    ClaudeCodeAgent::new().with_model_str(&self.options.model)
-                };
-                let agent = ExpertiseImproverAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Gemini => {
-                let backend = GeminiAgent::new();
-                let agent = ExpertiseImproverAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Codex => {
-                let backend = CodexAgent::new();
-                let agent = ExpertiseImproverAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-        };
+        // Create agent based on configured provider
+        let response = self.run_agent(CodeAnalysisAgent::new, prompt.into()).await;
 
         match response {
             Ok(response) => {
                 info!(
-                    "Successfully improved expertise: {} new fragments, {} to remove",
-                    response.new_fragments.len(),
-                    response.fragments_to_remove.len()
+                    "Successfully analyzed code: id={}, {} tags, {} fragments",
+                    id,
+                    response.tags.len(),
+                    response.fragments.len()
                 );
-                debug!("Improvement summary: {}", response.improvement_summary);
 
-                // Apply improvements to expertise
-                let mut improved = expertise.clone();
-                improved.inner.description = Some(response.description);
-                improved.inner.tags = response.tags;
+                let mut expertise = Expertise::new(id, "1.0.0");
+                expertise.inner.description = Some(response.description);
+                expertise.inner.tags = self.with_required_tags(response.tags);
+                expertise.metadata.scope = scope;
 
-                // Remove fragments marked for removal
-                use llm_toolkit_expertise::KnowledgeFragment;
-                if !response.fragments_to_remove.is_empty() {
-                    improved.inner.content.retain(|weighted_fragment| {
-                        if let KnowledgeFragment::Text(text) = &weighted_fragment.fragment {
-                            !response.fragments_to_remove.contains(text)
-                        } else {
-                            true // Keep non-text fragments
-                        }
-                    });
+                // Guarantee the language tag regardless of what the LLM returned
+                let language_tag = format!("language:{}", language.to_lowercase());
+                if !expertise.inner.tags.contains(&language_tag) {
+                    expertise.inner.tags.insert(0, language_tag);
                 }
 
-                // Add new fragments
-                use llm_toolkit_expertise::WeightedFragment;
-                for fragment_text in response.new_fragments {
-                    improved
+                // Add text fragments
+                use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
+                for fragment_text in response.fragments {
+                    expertise
                         .inner
                         .content
                         .push(WeightedFragment::new(KnowledgeFragment::Text(
@@ -486,39 +782,27 @@ impl ExpertiseGenerator {
                         )));
                 }
 
-                // Increment version
-                let version_parts: Vec<&str> = improved.version().split('.').collect();
-                if version_parts.len() >= 2 {
-                    let minor: u32 = version_parts[1].parse().unwrap_or(0);
-                    improved.inner.version = format!("{}.{}.0", version_parts[0], minor + 1);
-                }
+                self.stamp_provenance(&mut expertise, "code");
 
-                Ok(improved)
+                Ok(expertise)
             }
             Err(e) => {
-                // Agent error - return original expertise with version bump
-                debug!(
-                    "LLM improvement failed: {:?}, returning original with version bump",
-                    e
-                );
-                let mut improved = expertise;
-                let version_parts: Vec<&str> = improved.version().split('.').collect();
-                if version_parts.len() >= 2 {
-                    let minor: u32 = version_parts[1].parse().unwrap_or(0);
-                    improved.inner.version = format!("{}.{}.0", version_parts[0], minor + 1);
-                }
-                Ok(improved)
+                error!("LLM code analysis failed: {:?}", e);
+                Err(e.into())
             }
         }
     }
 
-    /// Interactive Expertise generation
+    /// Generate Expertise from a changelog
+    ///
+    /// Focuses on breaking changes, deprecations, new patterns and
+    /// architectural evolution. Use [`split_changelog`](crate::split_changelog)
+    /// to process one release section at a time.
     ///
     /// # Arguments
     ///
+    /// * `content` - The changelog (or one section of it)
     /// * `id` - ID for the new Expertise
-    /// * `description` - Brief description
-    /// * `domain` - Domain/category
     /// * `scope` - Scope for the new Expertise
     ///
     /// # Example
@@ -530,85 +814,113 @@ impl ExpertiseGenerator {
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let generator = ExpertiseGenerator::new().await?;
+    ///     let changelog = std::fs::read_to_string("CHANGELOG.md")?;
     ///
     ///     let expertise = generator
-    ///         .generate_interactive(
-    ///             "rust-expert",
-    ///             "Expert in Rust programming",
-    ///             "programming",
-    ///             Scope::Personal
-    ///         )
+    ///         .generate_from_changelog(&changelog, "project-history", Scope::Project)
     ///         .await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn generate_interactive(
+    pub async fn generate_from_changelog(
         &self,
+        content: &str,
         id: &str,
-        description: &str,
-        domain: &str,
         scope: Scope,
     ) -> Result<Expertise> {
-        info!(
-            "Generating expertise interactively: id={}, domain={}",
-            id, domain
-        );
+        info!("Generating expertise from changelog: id={}", id);
 
-        // Build prompt for the agent
-        let mut prompt = format!(
-            "Domain: {}\nDescription: {}\n\n\
-             Please generate comprehensive expertise for this domain.",
-            domain, description
+        let prompt = format!(
+            "Analyze the following changelog and extract structured expertise.\n\n{}\n",
+            content
         );
+        let prompt = self.with_seed_tags(prompt);
 
-        // Add optional context if provided
-        if let Some(context) = self.options.additional_context.as_deref() {
-            prompt.push_str(&format!("\n\nAdditional Context:\n{}", context));
-        }
+        // Create agent based on configured provider
+        let response = self
+            .run_agent(ChangelogExtractorAgent::new, prompt.into())
+            .await;
 
-        // Use the Agent macro-powered agent with configured provider
-        let response = match self.options.provider {
-            LlmProvider::Claude => {
-                let backend = if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
-                    ClaudeCodeAgent::new()
-                } else {
-                    ClaudeCodeAgent::new().with_model_str(&self.options.model)
-                };
-                let agent = InteractiveExpertiseAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Gemini => {
-                let backend = GeminiAgent::new();
-                let agent = InteractiveExpertiseAgent::new(backend);
-                agent.execute(prompt.into()).await
+        match response {
+            Ok(response) => {
+                info!(
+                    "Successfully analyzed changelog: id={}, {} tags, {} fragments",
+                    id,
+                    response.tags.len(),
+                    response.fragments.len()
+                );
+
+                let mut expertise = Expertise::new(id, "1.0.0");
+                expertise.inner.description = Some(response.description);
+                expertise.inner.tags = self.with_required_tags(response.tags);
+                expertise.metadata.scope = scope;
+
+                use llm_toolkit_expertise::WeightedFragment;
+                for fragment_text in response.fragments {
+                    expertise
+                        .inner
+                        .content
+                        .push(WeightedFragment::new(KnowledgeFragment::Text(
+                            fragment_text,
+                        )));
+                }
+
+                self.stamp_provenance(&mut expertise, "changelog");
+
+                Ok(expertise)
             }
-            LlmProvider::Codex => {
-                let backend = CodexAgent::new();
-                let agent = InteractiveExpertiseAgent::new(backend);
-                agent.execute(prompt.into()).await
+            Err(e) => {
+                error!("LLM changelog analysis failed: {:?}", e);
+                Err(e.into())
             }
-        };
+        }
+    }
+
+    /// Generate Expertise from meeting notes
+    ///
+    /// Focuses on decisions, technical constraints, action items and agreed
+    /// architecture changes. The result is tagged `meeting-decision`,
+    /// `architecture-decision` and/or `action-item`; if the LLM returns none
+    /// of these, `meeting-decision` is added.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The meeting notes
+    /// * `id` - ID for the new Expertise
+    /// * `scope` - Scope for the new Expertise
+    pub async fn generate_from_meeting_notes(
+        &self,
+        content: &str,
+        id: &str,
+        scope: Scope,
+    ) -> Result<Expertise> {
+        info!("Generating expertise from meeting notes: id={}", id);
+
+        let prompt = format!(
+            "Analyze the following meeting notes and extract structured expertise.\n\n{}\n",
+            content
+        );
+        let prompt = self.with_seed_tags(self.with_additional_context(prompt));
+
+        // Create agent based on configured provider
+        let response = self.run_agent(MeetingNotesAgent::new, prompt.into()).await;
 
         match response {
             Ok(response) => {
                 info!(
-                    "Successfully generated interactive expertise: {} tags, {} fragments",
+                    "Successfully analyzed meeting notes: id={}, {} tags, {} fragments",
+                    id,
                     response.tags.len(),
                     response.fragments.len()
                 );
-                if !response.related_areas.is_empty() {
-                    debug!("Suggested related areas: {:?}", response.related_areas);
-                }
 
-                // Convert response to Expertise
                 let mut expertise = Expertise::new(id, "1.0.0");
                 expertise.inner.description = Some(response.description);
-                expertise.inner.tags = response.tags;
+                expertise.inner.tags = self.with_required_tags(add_meeting_tag(response.tags));
                 expertise.metadata.scope = scope;
 
-                // Add fragments
-                use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
+                use llm_toolkit_expertise::WeightedFragment;
                 for fragment_text in response.fragments {
                     expertise
                         .inner
@@ -618,107 +930,174 @@ impl ExpertiseGenerator {
                         )));
                 }
 
-                // Optionally store related_areas as metadata (if needed)
-                // For now, we log them but don't persist them in the Expertise structure
+                self.stamp_provenance(&mut expertise, "meeting-notes");
 
                 Ok(expertise)
             }
             Err(e) => {
-                // Agent error - return error
-                debug!("LLM generation failed: {:?}", e);
+                error!("LLM meeting notes analysis failed: {:?}", e);
                 Err(e.into())
             }
         }
     }
 
-    /// Merge multiple Expertises
+    /// Translate an expertise into another language
+    ///
+    /// The copy keeps the original version and tags and records the
+    /// original ID in `translated_from`. Technical terms stay in English.
     ///
     /// # Arguments
     ///
-    /// * `expertises` - The Expertises to merge
-    /// * `output_id` - ID for the merged Expertise
-    /// * `description` - Description for the merged Expertise
-    /// * `scope` - Scope for the merged Expertise
-    pub async fn merge(
+    /// * `expertise` - The Expertise to translate
+    /// * `language` - Target language (e.g. "ja" or "Japanese")
+    /// * `new_id` - ID for the translated copy (e.g. "rust-error-handling-ja")
+    /// * `scope` - Scope for the translated copy
+    pub async fn translate(
         &self,
-        expertises: &[Expertise],
-        output_id: &str,
-        description: &str,
+        expertise: &Expertise,
+        language: &str,
+        new_id: &str,
         scope: Scope,
     ) -> Result<Expertise> {
-        info!("Merging {} expertises into {}", expertises.len(), output_id);
+        info!(
+            "Translating expertise: id={}, language={}",
+            expertise.id(),
+            language
+        );
 
-        if expertises.is_empty() {
-            return Err(crate::Error::Other(
-                "Cannot merge empty expertise list".to_string(),
-            ));
-        }
+        let prompt = self.translate_prompt(&expertise.to_json()?, language);
 
-        // Convert expertises to JSON for the prompt
-        let expertises_json: Vec<String> = expertises
-            .iter()
-            .map(|e| e.to_json())
-            .collect::<std::result::Result<_, _>>()?;
+        // Create agent based on configured provider
+        let response = self.run_agent(TranslatorAgent::new, prompt.into()).await;
 
-        // Build prompt for the agent
+        match response {
+            Ok(response) => {
+                info!(
+                    "Successfully translated expertise: id={}, {} fragments",
+                    new_id,
+                    response.fragments.len()
+                );
+                Ok(self.translated_expertise(expertise, response, new_id, scope))
+            }
+            Err(e) => {
+                error!("LLM translation failed: {:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Prompt for [`translate`](Self::translate)
+    fn translate_prompt(&self, expertise_json: &str, language: &str) -> String {
         let prompt = format!(
-            "Target Output ID: {}\nTarget Description: {}\n\n\
-             Expertises to Merge:\n{}\n\n\
-             Please synthesize these expertises into a unified, coherent expertise. \
-             Identify common themes, preserve unique insights, and resolve any conflicts.",
-            output_id,
-            description,
-            expertises_json.join("\n\n---\n\n")
+            "Target Language: {language}\n\n\
+             Translate all text content (description, fragment texts) to {language}. \
+             Preserve technical terms in English. Keep the same structure.\n\n\
+             Expertise:\n{expertise_json}"
         );
+        self.with_additional_context(prompt)
+    }
+
+    /// Build the translated copy of `original` from a translation response
+    fn translated_expertise(
+        &self,
+        original: &Expertise,
+        response: ExpertiseResponse,
+        new_id: &str,
+        scope: Scope,
+    ) -> Expertise {
+        let mut translated = Expertise::new(new_id, original.version());
+        translated.inner.description = Some(response.description);
+        translated.inner.tags = original.tags().to_vec();
+        translated.metadata.scope = scope;
+        translated.metadata.translated_from = Some(original.id().to_string());
+
+        use llm_toolkit_expertise::WeightedFragment;
+        for fragment_text in response.fragments {
+            translated
+                .inner
+                .content
+                .push(WeightedFragment::new(KnowledgeFragment::Text(
+                    fragment_text,
+                )));
+        }
+
+        self.stamp_provenance(&mut translated, "translation");
+        translated
+    }
+
+    /// Improve existing Expertise
+    ///
+    /// # Arguments
+    ///
+    /// * `expertise` - The Expertise to improve
+    /// * `instruction` - How to improve it
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use niwa_generator::ExpertiseGenerator;
+    /// use niwa_core::{Database, Scope, StorageOperations};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let db = Database::open_default().await?;
+    ///     let generator = ExpertiseGenerator::new().await?;
+    ///
+    ///     let expertise = db.storage()
+    ///         .get("rust-expert", Scope::Personal)
+    ///         .await?
+    ///         .unwrap();
+    ///
+    ///     let improved = generator
+    ///         .improve(expertise, "Add more error handling examples")
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn improve(&self, expertise: Expertise, instruction: &str) -> Result<Expertise> {
+        info!("Improving expertise: id={}", expertise.id());
+
+        let current_json = expertise.to_json()?;
+
+        // Build prompt for the agent
+        let prompt = self.improve_prompt(&current_json, instruction);
 
         // Use the Agent macro-powered agent with configured provider
-        let response = match self.options.provider {
-            LlmProvider::Claude => {
-                let backend = if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
-                    ClaudeCodeAgent::new()
-                } else {
-                    ClaudeCodeAgent::new().with_model_str(&self.options.model)
-                };
-                let agent = ExpertiseMergerAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Gemini => {
-                let backend = GeminiAgent::new();
-                let agent = ExpertiseMergerAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Codex => {
-                let backend = CodexAgent::new();
-                let agent = ExpertiseMergerAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-        };
+        let response = self
+            .run_agent(ExpertiseImproverAgent::new, prompt.into())
+            .await;
 
         match response {
             Ok(response) => {
                 info!(
-                    "Successfully merged expertises: {} tags, {} fragments",
-                    response.tags.len(),
-                    response.fragments.len()
+                    "Successfully improved expertise: {} new fragments, {} to remove",
+                    response.new_fragments.len(),
+                    response.fragments_to_remove.len()
                 );
-                debug!("Merge summary: {}", response.merge_summary);
-                if !response.conflicts_found.is_empty() {
-                    info!(
-                        "Conflicts found during merge: {:?}",
-                        response.conflicts_found
-                    );
-                }
+                debug!("Improvement summary: {}", response.improvement_summary);
 
-                // Convert response to Expertise
-                let mut merged = Expertise::new(output_id, "1.0.0");
-                merged.inner.description = Some(response.description);
-                merged.inner.tags = response.tags;
-                merged.metadata.scope = scope;
+                // Apply improvements to expertise
+                let mut improved = expertise.clone();
+                improved.inner.description = Some(response.description);
+                improved.inner.tags = response.tags;
 
-                // Add fragments
-                use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
-                for fragment_text in response.fragments {
-                    merged
+                // Remove fragments marked for removal
+                use llm_toolkit_expertise::KnowledgeFragment;
+                if !response.fragments_to_remove.is_empty() {
+                    improved.inner.content.retain(|weighted_fragment| {
+                        if let KnowledgeFragment::Text(text) = &weighted_fragment.fragment {
+                            !response.fragments_to_remove.contains(text)
+                        } else {
+                            true // Keep non-text fragments
+                        }
+                    });
+                }
+
+                // Add new fragments
+                use llm_toolkit_expertise::WeightedFragment;
+                for fragment_text in response.new_fragments {
+                    improved
                         .inner
                         .content
                         .push(WeightedFragment::new(KnowledgeFragment::Text(
@@ -726,135 +1105,1208 @@ impl ExpertiseGenerator {
                         )));
                 }
 
-                Ok(merged)
-            }
-            Err(e) => {
-                // Agent error - return error
-                debug!("LLM merge failed: {:?}", e);
-                Err(e.into())
-            }
-        }
+                self.check_secrets(&mut improved)?;
+
+                // Increment version
+                let (bump, reasoning) = self.version_bump(&expertise, &improved).await;
+                improved.inner.version = bump_version(improved.version(), bump);
+                improved.metadata.version_reason = reasoning;
+
+                Ok(improved)
+            }
+            Err(e) => {
+                // Agent error - return original expertise with version bump
+                debug!(
+                    "LLM improvement failed: {:?}, returning original with version bump",
+                    e
+                );
+                let mut improved = expertise;
+                improved.inner.version = bump_version(improved.version(), SemverBump::Minor);
+                improved.metadata.version_reason = None;
+                Ok(improved)
+            }
+        }
+    }
+
+    /// Bump from `old` to `new` under `version_strategy`, with the LLM's
+    /// reasoning when it chose the bump
+    ///
+    /// If the LLM fails, `Auto` falls back to a minor bump.
+    async fn version_bump(&self, old: &Expertise, new: &Expertise) -> (SemverBump, Option<String>) {
+        match self.options.version_strategy {
+            VersionBumpStrategy::Minor => (SemverBump::Minor, None),
+            VersionBumpStrategy::Semantic(bump) => (bump, None),
+            VersionBumpStrategy::Auto => match self.suggest_version_bump(old, new).await {
+                Ok(recommendation) => (recommendation.bump, Some(recommendation.reasoning)),
+                Err(e) => {
+                    warn!("Version analysis failed, bumping minor version: {}", e);
+                    (SemverBump::Minor, None)
+                }
+            },
+        }
+    }
+
+    /// Ask the LLM how much the version should change from `old` to `new`
+    ///
+    /// Removed fragments and a rewritten description suggest a major bump,
+    /// added knowledge a minor one, wording fixes a patch.
+    pub async fn suggest_version_bump(
+        &self,
+        old: &Expertise,
+        new: &Expertise,
+    ) -> Result<VersionBumpRecommendation> {
+        info!("Analyzing version bump for {}", new.id());
+        let prompt = self.with_additional_context(version_bump_prompt(old, new));
+
+        let response = self
+            .run_agent(VersionAnalysisAgent::new, prompt.into())
+            .await?;
+
+        debug!(
+            "Version bump for {}: {:?} ({})",
+            new.id(),
+            response.bump,
+            response.reasoning
+        );
+        Ok(response)
+    }
+
+    /// Enrich an existing Expertise with knowledge from a new source
+    ///
+    /// Unlike [`improve`](Self::improve), the existing content is left as is:
+    /// fragments are extracted from `new_content` and only those that are not
+    /// similar to an existing fragment are appended. The version is bumped
+    /// under the configured strategy when at least one fragment is added.
+    ///
+    /// # Returns
+    ///
+    /// The enriched expertise and the number of fragments added
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use niwa_generator::ExpertiseGenerator;
+    /// use niwa_core::Expertise;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let generator = ExpertiseGenerator::new().await?;
+    ///     let expertise = Expertise::new("rust-expert", "1.0.0");
+    ///     let log = std::fs::read_to_string("session.log")?;
+    ///
+    ///     let (enriched, added) = generator.enrich(expertise, &log).await?;
+    ///     println!("Added {} new fragments", added);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn enrich(
+        &self,
+        existing: Expertise,
+        new_content: &str,
+    ) -> Result<(Expertise, usize)> {
+        info!("Enriching expertise: id={}", existing.id());
+
+        let candidate = self
+            .generate_from_log(new_content, existing.id(), existing.metadata.scope)
+            .await?;
+
+        let mut known: Vec<String> = existing
+            .inner
+            .content
+            .iter()
+            .map(|w| w.fragment.to_prompt())
+            .collect();
+
+        let mut enriched = existing.clone();
+        let mut added = 0;
+
+        for weighted in candidate.inner.content {
+            let text = weighted.fragment.to_prompt();
+            if known
+                .iter()
+                .any(|k| text_similarity(k, &text) >= DUPLICATE_FRAGMENT_SIMILARITY)
+            {
+                debug!("Skipping known fragment: {}", weighted.fragment.summary());
+                continue;
+            }
+
+            known.push(text);
+            enriched.inner.content.push(weighted);
+            added += 1;
+        }
+
+        info!("Enrichment added {} new fragments", added);
+
+        if added > 0 {
+            let (bump, reasoning) = self.version_bump(&existing, &enriched).await;
+            enriched.inner.version = bump_version(existing.version(), bump);
+            enriched.metadata.version_reason = reasoning;
+        }
+
+        Ok((enriched, added))
+    }
+
+    /// Refresh the fragments of an Expertise from new log content
+    ///
+    /// Unlike [`improve`](Self::improve), no instruction is involved: the log
+    /// is extracted as for a new expertise and its fragments replace the
+    /// existing ones. With `replace_description`, so does its description.
+    /// The ID, scope, tags and metadata are kept, so relations stay valid;
+    /// provenance is that of the new extraction, and the version is bumped
+    /// under the configured strategy. Callers reading from a file record it
+    /// with [`Expertise::set_source`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use niwa_generator::ExpertiseGenerator;
+    /// use niwa_core::Expertise;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let generator = ExpertiseGenerator::new().await?;
+    ///     let expertise = Expertise::new("rust-expert", "1.0.0");
+    ///     let log = std::fs::read_to_string("session.log")?;
+    ///
+    ///     let refreshed = generator.regenerate(expertise, &log, false).await?;
+    ///     println!("Now {} fragments", refreshed.inner.content.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn regenerate(
+        &self,
+        existing: Expertise,
+        new_content: &str,
+        replace_description: bool,
+    ) -> Result<Expertise> {
+        info!("Regenerating expertise: id={}", existing.id());
+
+        let extracted = self
+            .generate_from_log(new_content, existing.id(), existing.metadata.scope)
+            .await?;
+        let mut regenerated = refresh_fragments(&existing, extracted, replace_description);
+
+        let (bump, reasoning) = self.version_bump(&existing, &regenerated).await;
+        regenerated.inner.version = bump_version(existing.version(), bump);
+        regenerated.metadata.version_reason = reasoning;
+
+        Ok(regenerated)
+    }
+
+    /// Interactive Expertise generation
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID for the new Expertise
+    /// * `description` - Brief description
+    /// * `domain` - Domain/category
+    /// * `scope` - Scope for the new Expertise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use niwa_generator::ExpertiseGenerator;
+    /// use niwa_core::Scope;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let generator = ExpertiseGenerator::new().await?;
+    ///
+    ///     let expertise = generator
+    ///         .generate_interactive(
+    ///             "rust-expert",
+    ///             "Expert in Rust programming",
+    ///             "programming",
+    ///             Scope::Personal
+    ///         )
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn generate_interactive(
+        &self,
+        id: &str,
+        description: &str,
+        domain: &str,
+        scope: Scope,
+    ) -> Result<Expertise> {
+        info!(
+            "Generating expertise interactively: id={}, domain={}",
+            id, domain
+        );
+
+        // Build prompt for the agent
+        let prompt = format!(
+            "Domain: {}\nDescription: {}\n\n\
+             Please generate comprehensive expertise for this domain.",
+            domain, description
+        );
+        let prompt = self.with_seed_tags(self.with_additional_context(prompt));
+
+        // Use the Agent macro-powered agent with configured provider
+        let response = self
+            .run_agent(InteractiveExpertiseAgent::new, prompt.into())
+            .await;
+
+        match response {
+            Ok(response) => {
+                info!(
+                    "Successfully generated interactive expertise: {} tags, {} fragments",
+                    response.tags.len(),
+                    response.fragments.len()
+                );
+                if !response.related_areas.is_empty() {
+                    debug!("Suggested related areas: {:?}", response.related_areas);
+                }
+
+                // Convert response to Expertise
+                let mut expertise = Expertise::new(id, "1.0.0");
+                expertise.inner.description = Some(response.description);
+                expertise.inner.tags = self.with_required_tags(response.tags);
+                expertise.metadata.scope = scope;
+
+                // Add fragments
+                use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
+                for fragment_text in response.fragments {
+                    expertise
+                        .inner
+                        .content
+                        .push(WeightedFragment::new(KnowledgeFragment::Text(
+                            fragment_text,
+                        )));
+                }
+
+                // Optionally store related_areas as metadata (if needed)
+                // For now, we log them but don't persist them in the Expertise structure
+
+                self.stamp_provenance(&mut expertise, "interactive");
+
+                Ok(expertise)
+            }
+            Err(e) => {
+                // Agent error - return error
+                debug!("LLM generation failed: {:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Merge multiple Expertises
+    ///
+    /// # Arguments
+    ///
+    /// * `expertises` - The Expertises to merge
+    /// * `output_id` - ID for the merged Expertise
+    /// * `description` - Description for the merged Expertise
+    /// * `scope` - Scope for the merged Expertise
+    pub async fn merge(
+        &self,
+        expertises: &[Expertise],
+        output_id: &str,
+        description: &str,
+        scope: Scope,
+    ) -> Result<Expertise> {
+        self.merge_with_conflicts(expertises, output_id, description, scope)
+            .await
+            .map(|(merged, _)| merged)
+    }
+
+    /// Merge multiple Expertises, also returning the conflicts the LLM found
+    ///
+    /// Same as [`merge`](Self::merge), plus the cases where the sources
+    /// contradicted each other (empty if none).
+    pub async fn merge_with_conflicts(
+        &self,
+        expertises: &[Expertise],
+        output_id: &str,
+        description: &str,
+        scope: Scope,
+    ) -> Result<(Expertise, Vec<String>)> {
+        info!("Merging {} expertises into {}", expertises.len(), output_id);
+
+        if expertises.is_empty() {
+            return Err(crate::Error::Other(
+                "Cannot merge empty expertise list".to_string(),
+            ));
+        }
+
+        // Convert expertises to JSON for the prompt
+        let expertises_json: Vec<String> = expertises
+            .iter()
+            .map(|e| e.to_json())
+            .collect::<std::result::Result<_, _>>()?;
+
+        // Build prompt for the agent
+        let prompt = self.merge_prompt(&expertises_json, output_id, description);
+
+        // Use the Agent macro-powered agent with configured provider
+        let response = self
+            .run_agent(ExpertiseMergerAgent::new, prompt.into())
+            .await;
+
+        match response {
+            Ok(response) => {
+                info!(
+                    "Successfully merged expertises: {} tags, {} fragments",
+                    response.tags.len(),
+                    response.fragments.len()
+                );
+                debug!("Merge summary: {}", response.merge_summary);
+                if !response.conflicts_found.is_empty() {
+                    info!(
+                        "Conflicts found during merge: {:?}",
+                        response.conflicts_found
+                    );
+                }
+
+                // Convert response to Expertise
+                let mut merged = Expertise::new(output_id, "1.0.0");
+                merged.inner.description = Some(response.description);
+                merged.inner.tags = response.tags;
+                merged.metadata.scope = scope;
+
+                // Add fragments
+                use llm_toolkit_expertise::{KnowledgeFragment, WeightedFragment};
+                for fragment_text in response.fragments {
+                    merged
+                        .inner
+                        .content
+                        .push(WeightedFragment::new(KnowledgeFragment::Text(
+                            fragment_text,
+                        )));
+                }
+
+                self.stamp_provenance(&mut merged, "merge");
+                self.check_secrets(&mut merged)?;
+
+                Ok((merged, response.conflicts_found))
+            }
+            Err(e) => {
+                // Agent error - return error
+                debug!("LLM merge failed: {:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Suggest links between a new expertise and existing ones
+    ///
+    /// Uses LLM to analyze semantic relationships based on descriptions and tags.
+    /// Every candidate is considered, so callers leave out the ones that
+    /// should not be linked to (e.g. deprecated expertises).
+    ///
+    /// # Arguments
+    ///
+    /// * `new_expertise` - The newly created expertise to link
+    /// * `existing_expertises` - List of existing expertises to compare against
+    ///
+    /// # Returns
+    ///
+    /// A list of suggested links with confidence scores and reasons
+    pub async fn suggest_links(
+        &self,
+        new_expertise: &Expertise,
+        existing_expertises: &[Expertise],
+    ) -> Result<Vec<SuggestedLink>> {
+        if existing_expertises.is_empty() {
+            return Ok(vec![]);
+        }
+
+        info!(
+            "Analyzing links for expertise: {} against {} existing",
+            new_expertise.id(),
+            existing_expertises.len()
+        );
+
+        // Build summaries for the prompt
+        let new_summary = ExpertiseSummary {
+            id: new_expertise.id().to_string(),
+            description: new_expertise.description(),
+            tags: new_expertise.tags().to_vec(),
+        };
+
+        let existing_summaries: Vec<ExpertiseSummary> = existing_expertises
+            .iter()
+            .filter(|e| e.id() != new_expertise.id()) // Exclude self
+            .map(|e| ExpertiseSummary {
+                id: e.id().to_string(),
+                description: e.description(),
+                tags: e.tags().to_vec(),
+            })
+            .collect();
+
+        if existing_summaries.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Build prompt
+        let prompt = format!(
+            "Analyze potential links for the following NEW expertise:\n\n\
+             NEW EXPERTISE:\n\
+             ID: {}\n\
+             Description: {}\n\
+             Tags: {}\n\n\
+             EXISTING EXPERTISES:\n{}\n\n\
+             Suggest meaningful links between the NEW expertise and existing ones.",
+            new_summary.id,
+            new_summary.description,
+            new_summary.tags.join(", "),
+            existing_summaries
+                .iter()
+                .map(|s| format!(
+                    "- ID: {}\n  Description: {}\n  Tags: {}",
+                    s.id,
+                    s.description,
+                    s.tags.join(", ")
+                ))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        );
+
+        // Use the Agent macro-powered agent with configured provider
+        let response = self
+            .run_agent(ExpertiseLinkerAgent::new, prompt.into())
+            .await;
+
+        match response {
+            Ok(response) => {
+                let valid_links: Vec<SuggestedLink> = response
+                    .suggested_links
+                    .into_iter()
+                    .filter(|link| link.confidence >= 0.7)
+                    .collect();
+
+                info!(
+                    "LinkerAgent suggested {} links (filtered from response)",
+                    valid_links.len()
+                );
+
+                for link in &valid_links {
+                    debug!(
+                        "Suggested: {} -[{}]-> {} (confidence: {:.2}, reason: {})",
+                        link.from_id, link.relation_type, link.to_id, link.confidence, link.reason
+                    );
+                }
+
+                Ok(valid_links)
+            }
+            Err(e) => {
+                debug!("LinkerAgent failed: {:?}", e);
+                // Return empty list on failure (non-critical)
+                Ok(vec![])
+            }
+        }
+    }
+
+    /// Find relations to other expertises mentioned in fragment text
+    ///
+    /// The agent reports references such as "builds on X" or "requires Y";
+    /// each hint is then resolved against `known_ids` and only matches are
+    /// returned, with `target_id_hint` replaced by the matched ID.
+    pub async fn extract_implicit_relations(
+        &self,
+        expertise: &Expertise,
+        known_ids: &[String],
+    ) -> Result<Vec<ImplicitRelation>> {
+        let candidates: Vec<&String> = known_ids
+            .iter()
+            .filter(|id| id.as_str() != expertise.id())
+            .collect();
+        if candidates.is_empty() || expertise.inner.content.is_empty() {
+            return Ok(vec![]);
+        }
+
+        info!(
+            "Extracting implicit relations from {} ({} fragments)",
+            expertise.id(),
+            expertise.inner.content.len()
+        );
+
+        let fragments = expertise
+            .inner
+            .content
+            .iter()
+            .enumerate()
+            .map(|(i, w)| format!("[{}] {}", i, w.fragment.to_prompt()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let prompt = format!(
+            "EXPERTISE: {}\n\n\
+             FRAGMENTS:\n{}\n\n\
+             KNOWN EXPERTISE IDS:\n{}\n\n\
+             Find the relations to other expertises mentioned in the fragments.",
+            expertise.id(),
+            fragments,
+            candidates
+                .iter()
+                .map(|id| format!("- {}", id))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        let response = self
+            .run_agent(RelationExtractionAgent::new, prompt.into())
+            .await?;
+
+        let mut relations = Vec::new();
+        for mut relation in response.relations {
+            match resolve_target_id(&relation.target_id_hint, &candidates) {
+                Some(id) => {
+                    debug!(
+                        "Resolved hint '{}' to {} ({}, confidence: {:.2})",
+                        relation.target_id_hint, id, relation.relation_type, relation.confidence
+                    );
+                    relation.target_id_hint = id.to_string();
+                    relations.push(relation);
+                }
+                None => debug!("No known ID matches hint '{}'", relation.target_id_hint),
+            }
+        }
+
+        info!("Found {} implicit relations", relations.len());
+        Ok(relations)
+    }
+
+    /// Split a multi-topic expertise into focused parts
+    ///
+    /// The LLM groups the fragments by topic; fragments are copied unchanged
+    /// into parts named `<id>-<topic>`, which keep the original scope. Nothing
+    /// is stored.
+    ///
+    /// Fails if the expertise has fewer than `min_parts` fragments or the LLM
+    /// proposes fewer than `min_parts` non-empty parts. Extra parts beyond
+    /// `max_parts` are dropped, with their fragments kept in the first part.
+    pub async fn split_by_topic(
+        &self,
+        expertise: &Expertise,
+        min_parts: usize,
+        max_parts: usize,
+    ) -> Result<Vec<Expertise>> {
+        if min_parts < 2 || min_parts > max_parts {
+            return Err(Error::Other(format!(
+                "Invalid part range {}..={} (need 2 <= min <= max)",
+                min_parts, max_parts
+            )));
+        }
+        let fragment_count = expertise.inner.content.len();
+        if fragment_count < min_parts {
+            return Err(Error::Other(format!(
+                "{} has {} fragments, too few to split into {} parts",
+                expertise.id(),
+                fragment_count,
+                min_parts
+            )));
+        }
+
+        info!(
+            "Splitting {} ({} fragments) into {}-{} parts",
+            expertise.id(),
+            fragment_count,
+            min_parts,
+            max_parts
+        );
+
+        let fragments = expertise
+            .inner
+            .content
+            .iter()
+            .enumerate()
+            .map(|(i, w)| format!("[{}] {}", i, w.fragment.to_prompt()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let prompt = format!(
+            "EXPERTISE: {}\n\
+             DESCRIPTION: {}\n\
+             TAGS: {}\n\n\
+             FRAGMENTS:\n{}\n\n\
+             Split the fragments into at least {} and at most {} parts by topic.",
+            expertise.id(),
+            expertise.description(),
+            expertise.tags().join(", "),
+            fragments,
+            min_parts,
+            max_parts
+        );
+
+        let response = self
+            .run_agent(TagBasedSplitterAgent::new, prompt.into())
+            .await?;
+
+        let mut parts = response.parts;
+        parts.truncate(max_parts);
+        let parts = build_parts(expertise, parts);
+        if parts.len() < min_parts {
+            return Err(Error::Other(format!(
+                "Only {} topic(s) found in {}, fewer than the minimum {}",
+                parts.len(),
+                expertise.id(),
+                min_parts
+            )));
+        }
+
+        info!("Split {} into {} parts", expertise.id(), parts.len());
+        Ok(parts)
+    }
+
+    /// Generate a 1-2 sentence description from fragment texts
+    ///
+    /// Used to fill in descriptions of expertise that lack one. Fails if
+    /// `fragments` is empty or the LLM returns a blank description.
+    pub async fn generate_description(&self, fragments: &[String]) -> Result<String> {
+        if fragments.is_empty() {
+            return Err(Error::Other("No fragments to describe".to_string()));
+        }
+        let prompt = self.with_additional_context(description_prompt(fragments));
+
+        let response = self
+            .run_agent(DescriptionGeneratorAgent::new, prompt.into())
+            .await?;
+
+        let description = response.description.trim();
+        if description.is_empty() {
+            return Err(Error::Llm("LLM returned an empty description".to_string()));
+        }
+        Ok(description.to_string())
+    }
+
+    /// Propose tags for an expertise, typically one that has none
+    ///
+    /// `known_tags` is the tag vocabulary already in use; the LLM prefers
+    /// those tags so related expertises end up sharing them. Suggestions are
+    /// normalized to lowercase, hyphenated form, and tags the expertise
+    /// already carries are dropped.
+    pub async fn suggest_tags(
+        &self,
+        expertise: &Expertise,
+        known_tags: &[String],
+    ) -> Result<Vec<String>> {
+        if expertise.inner.content.is_empty() && expertise.description().trim().is_empty() {
+            return Err(Error::Other(format!(
+                "{} has neither a description nor fragments to tag",
+                expertise.id()
+            )));
+        }
+        let prompt = self.with_additional_context(tag_suggestion_prompt(expertise, known_tags));
+
+        let response = self
+            .run_agent(TagSuggesterAgent::new, prompt.into())
+            .await?;
+
+        let tags = normalize_suggested_tags(response.tags, expertise.tags());
+        debug!("Suggested tags for {}: {:?}", expertise.id(), tags);
+        Ok(tags)
+    }
+
+    /// Rate the quality of an expertise with the LLM
+    ///
+    /// The score is clamped to 0.0..=1.0.
+    pub async fn quality_check(&self, expertise: &Expertise) -> Result<QualityCheckResponse> {
+        info!("Checking quality of {}", expertise.id());
+        let prompt = self.with_additional_context(format!(
+            "Rate the quality of this expertise.\n\n{}",
+            expertise.to_prompt_string()
+        ));
+
+        let mut response = self
+            .run_agent(QualityCheckerAgent::new, prompt.into())
+            .await?;
+
+        response.score = response.score.clamp(0.0, 1.0);
+        Ok(response)
+    }
+
+    /// Send a free-form prompt to the LLM and return its plain-text reply
+    ///
+    /// When `system_context` is given it is placed before the prompt as
+    /// standing instructions, e.g. an expertise rendered with
+    /// [`Expertise::to_prompt_string`].
+    pub async fn respond(&self, system_context: Option<&str>, prompt: &str) -> Result<String> {
+        let text = match system_context {
+            Some(context) => format!(
+                "{}\n\n---\n\nUsing the knowledge above where relevant, respond to:\n\n{}",
+                context, prompt
+            ),
+            None => prompt.to_string(),
+        };
+        let payload = Payload::from(text);
+
+        let response = self.backend().execute(payload).await;
+
+        response.map_err(|e| e.into())
+    }
+
+    /// Upgrade all text fragments of an expertise to a richer fragment type
+    ///
+    /// Each `KnowledgeFragment::Text` fragment is rewritten by the LLM as the
+    /// target type; other fragments are left untouched. Fragments the LLM
+    /// fails to convert keep their original text. The version is bumped under
+    /// the configured strategy when at least one fragment changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `expertise` - Expertise to upgrade
+    /// * `target_type` - Target fragment type: "logic", "guideline" or "quality"
+    pub async fn upgrade_fragments(
+        &self,
+        expertise: Expertise,
+        target_type: &str,
+    ) -> Result<Expertise> {
+        let indices: Vec<usize> = (0..expertise.inner.content.len()).collect();
+        self.upgrade_fragments_at(expertise, target_type, &indices)
+            .await
+    }
+
+    /// Upgrade the text fragment at a specific index to a richer fragment type
+    ///
+    /// Returns an error if the index is out of range or the fragment is not text.
+    pub async fn upgrade_fragment(
+        &self,
+        expertise: Expertise,
+        index: usize,
+        target_type: &str,
+    ) -> Result<Expertise> {
+        match expertise.inner.content.get(index) {
+            Some(weighted) if matches!(weighted.fragment, KnowledgeFragment::Text(_)) => {}
+            Some(weighted) => {
+                return Err(Error::Other(format!(
+                    "Fragment {} is {}, only Text fragments can be upgraded",
+                    index,
+                    weighted.fragment.type_label()
+                )))
+            }
+            None => {
+                return Err(Error::Other(format!(
+                    "Fragment index {} out of range ({} fragments)",
+                    index,
+                    expertise.inner.content.len()
+                )))
+            }
+        }
+
+        self.upgrade_fragments_at(expertise, target_type, &[index])
+            .await
+    }
+
+    async fn upgrade_fragments_at(
+        &self,
+        expertise: Expertise,
+        target_type: &str,
+        indices: &[usize],
+    ) -> Result<Expertise> {
+        let fragment_type = fragment_type_name(target_type).ok_or_else(|| {
+            Error::Other(format!(
+                "Unknown fragment type '{}' (expected logic, guideline or quality)",
+                target_type
+            ))
+        })?;
+
+        info!(
+            "Upgrading text fragments of {} to {}",
+            expertise.id(),
+            fragment_type
+        );
+
+        let mut upgraded = expertise.clone();
+        let mut changed = 0;
+
+        for &index in indices {
+            let text = match upgraded.inner.content.get(index).map(|w| &w.fragment) {
+                Some(KnowledgeFragment::Text(text)) => text.clone(),
+                _ => continue,
+            };
+
+            let prompt = format!(
+                "Target Type: {}\n\nText Fragment:\n{}\n\n\
+                 Rewrite this text fragment as a {} fragment.",
+                fragment_type, text, fragment_type
+            );
+
+            let response = self
+                .run_agent(FragmentUpgraderAgent::new, prompt.into())
+                .await;
+
+            let fragment = response
+                .map_err(Error::from)
+                .and_then(|response| to_knowledge_fragment(response, fragment_type));
+
+            match fragment {
+                Ok(fragment) => {
+                    upgraded.inner.content[index].fragment = fragment;
+                    changed += 1;
+                }
+                Err(e) => {
+                    // Keep the original text on failure
+                    debug!("Fragment {} upgrade failed: {:?}", index, e);
+                }
+            }
+        }
+
+        info!("Upgraded {} of {} fragments", changed, indices.len());
+
+        if changed > 0 {
+            let (bump, reasoning) = self.version_bump(&expertise, &upgraded).await;
+            upgraded.inner.version = bump_version(expertise.version(), bump);
+            upgraded.metadata.version_reason = reasoning;
+        }
+
+        Ok(upgraded)
+    }
+
+    /// Rough token count of `text` (about four characters per token)
+    ///
+    /// Good enough for budgeting prompts; it does not match any specific
+    /// tokenizer exactly.
+    pub fn estimate_tokens(text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+
+    /// Relative value of a fragment priority, from 4 (critical) down to 1 (low)
+    ///
+    /// Divided by a fragment's token count, this ranks fragments by how
+    /// much they are worth per token spent.
+    pub fn priority_weight(priority: &Priority) -> u32 {
+        4 - u32::from(priority_rank(priority))
+    }
+
+    /// Render an expertise as a compact context block for an LLM prompt
+    ///
+    /// Unlike [`Expertise::to_prompt_string`] there are no markdown headers:
+    /// the description and tags come first, followed by numbered fragments
+    /// separated by `|`, highest priority first. Fragments below
+    /// `min_priority` are left out. Fragments that would push the estimate
+    /// past `max_tokens` are dropped, lowest priority first; critical
+    /// fragments are always kept, even if they alone exceed the budget.
+    pub fn export_as_context(
+        expertise: &Expertise,
+        max_tokens: usize,
+        min_priority: &Priority,
+    ) -> String {
+        let mut output = format!("{}: {}", expertise.id(), compact(expertise.description()));
+        if !expertise.tags().is_empty() {
+            output.push_str(&format!(" [tags: {}]", expertise.tags().join(", ")));
+        }
+
+        let mut fragments: Vec<_> = expertise
+            .inner
+            .content
+            .iter()
+            .filter(|w| priority_rank(&w.priority) <= priority_rank(min_priority))
+            .collect();
+        fragments.sort_by_key(|w| priority_rank(&w.priority));
+
+        let mut tokens = Self::estimate_tokens(&output);
+        let mut included = 0;
+        for weighted in fragments {
+            let entry = format!(
+                " | {}. {}",
+                included + 1,
+                compact(&weighted.fragment.to_prompt())
+            );
+            let entry_tokens = Self::estimate_tokens(&entry);
+            let critical = matches!(weighted.priority, Priority::Critical);
+            if !critical && tokens + entry_tokens > max_tokens {
+                // Sorted by priority, so everything after this is lower or equal
+                break;
+            }
+            tokens += entry_tokens;
+            included += 1;
+            output.push_str(&entry);
+        }
+
+        if included < expertise.inner.content.len() {
+            debug!(
+                "Context for {} truncated to {} of {} fragments ({} tokens)",
+                expertise.id(),
+                included,
+                expertise.inner.content.len(),
+                tokens
+            );
+        }
+        output
+    }
+}
+
+/// Sort key for fragment priorities, most important first
+fn priority_rank(priority: &Priority) -> u8 {
+    match priority {
+        Priority::Critical => 0,
+        Priority::High => 1,
+        Priority::Normal => 2,
+        Priority::Low => 3,
+    }
+}
+
+/// Collapse all whitespace (including newlines) into single spaces
+fn compact(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Map a CLI fragment type name to the `KnowledgeFragment` variant tag
+fn fragment_type_name(target_type: &str) -> Option<&'static str> {
+    match target_type.to_lowercase().replace(['-', '_'], "").as_str() {
+        "logic" => Some("Logic"),
+        "guideline" => Some("Guideline"),
+        "quality" | "qualitystandard" => Some("QualityStandard"),
+        _ => None,
+    }
+}
+
+/// Convert an agent response into a `KnowledgeFragment` of the expected type
+fn to_knowledge_fragment(
+    upgraded: UpgradedFragment,
+    expected_type: &str,
+) -> Result<KnowledgeFragment> {
+    if upgraded.fragment_type != expected_type {
+        return Err(Error::SchemaValidation(format!(
+            "Expected {} fragment, got {}",
+            expected_type, upgraded.fragment_type
+        )));
+    }
+
+    let fragment = serde_json::from_value(serde_json::json!({
+        "type": upgraded.fragment_type,
+        "content": upgraded.content,
+    }))?;
+    Ok(fragment)
+}
+
+/// Resolve a free-form expertise name to one of `known_ids`
+///
+/// Hints are normalized to ID form ("Rust Async" -> "rust-async") and matched
+/// exactly first; otherwise the longest known ID contained in the hint wins.
+fn resolve_target_id<'a>(hint: &str, known_ids: &[&'a String]) -> Option<&'a str> {
+    let normalized = to_id_form(hint);
+    if normalized.is_empty() {
+        return None;
+    }
+
+    if let Some(id) = known_ids.iter().find(|id| id.as_str() == normalized) {
+        return Some(id.as_str());
     }
 
-    /// Suggest links between a new expertise and existing ones
-    ///
-    /// Uses LLM to analyze semantic relationships based on descriptions and tags.
-    ///
-    /// # Arguments
-    ///
-    /// * `new_expertise` - The newly created expertise to link
-    /// * `existing_expertises` - List of existing expertises to compare against
-    ///
-    /// # Returns
-    ///
-    /// A list of suggested links with confidence scores and reasons
-    pub async fn suggest_links(
-        &self,
-        new_expertise: &Expertise,
-        existing_expertises: &[Expertise],
-    ) -> Result<Vec<SuggestedLink>> {
-        if existing_expertises.is_empty() {
-            return Ok(vec![]);
+    let padded = format!("-{}-", normalized);
+    known_ids
+        .iter()
+        .filter(|id| padded.contains(&format!("-{}-", id)))
+        .max_by_key(|id| id.len())
+        .map(|id| id.as_str())
+}
+
+/// `tags` with every tag of `required` that isn't already present (case-insensitive)
+fn add_required_tags(mut tags: Vec<String>, required: &[String]) -> Vec<String> {
+    for tag in required {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.clone());
         }
+    }
+    tags
+}
 
-        info!(
-            "Analyzing links for expertise: {} against {} existing",
-            new_expertise.id(),
-            existing_expertises.len()
-        );
+/// Tags marking what kind of meeting outcome an expertise records
+const MEETING_TAGS: [&str; 3] = ["meeting-decision", "architecture-decision", "action-item"];
 
-        // Build summaries for the prompt
-        let new_summary = ExpertiseSummary {
-            id: new_expertise.id().to_string(),
-            description: new_expertise.description(),
-            tags: new_expertise.tags().to_vec(),
-        };
+/// Add `meeting-decision` unless the tags already name a meeting outcome
+fn add_meeting_tag(mut tags: Vec<String>) -> Vec<String> {
+    if !tags
+        .iter()
+        .any(|t| MEETING_TAGS.contains(&t.to_lowercase().as_str()))
+    {
+        tags.insert(0, MEETING_TAGS[0].to_string());
+    }
+    tags
+}
 
-        let existing_summaries: Vec<ExpertiseSummary> = existing_expertises
-            .iter()
-            .filter(|e| e.id() != new_expertise.id()) // Exclude self
-            .map(|e| ExpertiseSummary {
-                id: e.id().to_string(),
-                description: e.description(),
-                tags: e.tags().to_vec(),
-            })
-            .collect();
+/// Most tags a suggestion keeps
+const MAX_SUGGESTED_TAGS: usize = 5;
+
+/// Prompt for [`ExpertiseGenerator::suggest_tags`]
+fn tag_suggestion_prompt(expertise: &Expertise, known_tags: &[String]) -> String {
+    let fragments = expertise
+        .inner
+        .content
+        .iter()
+        .map(|w| format!("- {}", compact(&w.fragment.to_prompt())))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let known_tags = if known_tags.is_empty() {
+        "(none)".to_string()
+    } else {
+        known_tags.join(", ")
+    };
+    format!(
+        "EXPERTISE: {}\n\
+         DESCRIPTION: {}\n\n\
+         FRAGMENTS:\n{}\n\n\
+         EXISTING TAGS: {}\n\n\
+         Propose 3-5 tags for this expertise.",
+        expertise.id(),
+        expertise.description(),
+        fragments,
+        known_tags
+    )
+}
 
-        if existing_summaries.is_empty() {
-            return Ok(vec![]);
+/// Lowercase and hyphenate suggested tags, dropping duplicates and `existing` ones
+fn normalize_suggested_tags(suggested: Vec<String>, existing: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in suggested {
+        let tag = tag
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) && !existing.contains(&tag) {
+            tags.push(tag);
         }
+    }
+    tags.truncate(MAX_SUGGESTED_TAGS);
+    tags
+}
 
-        // Build prompt
-        let prompt = format!(
-            "Analyze potential links for the following NEW expertise:\n\n\
-             NEW EXPERTISE:\n\
-             ID: {}\n\
-             Description: {}\n\
-             Tags: {}\n\n\
-             EXISTING EXPERTISES:\n{}\n\n\
-             Suggest meaningful links between the NEW expertise and existing ones.",
-            new_summary.id,
-            new_summary.description,
-            new_summary.tags.join(", "),
-            existing_summaries
-                .iter()
-                .map(|s| format!("- ID: {}\n  Description: {}\n  Tags: {}", s.id, s.description, s.tags.join(", ")))
-                .collect::<Vec<_>>()
-                .join("\n\n")
-        );
+/// Prompt for [`ExpertiseGenerator::generate_description`]
+fn description_prompt(fragments: &[String]) -> String {
+    let fragments = fragments
+        .iter()
+        .enumerate()
+        .map(|(i, f)| format!("[{}] {}", i + 1, f.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!(
+        "FRAGMENTS:\n{}\n\n\
+         Write a 1-2 sentence description for expertise with these fragments.",
+        fragments
+    )
+}
 
-        // Use the Agent macro-powered agent with configured provider
-        let response = match self.options.provider {
-            LlmProvider::Claude => {
-                let backend = if self.options.model.is_empty() || self.options.model == "claude-sonnet-4-5" {
-                    ClaudeCodeAgent::new()
-                } else {
-                    ClaudeCodeAgent::new().with_model_str(&self.options.model)
-                };
-                let agent = ExpertiseLinkerAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Gemini => {
-                let backend = GeminiAgent::new();
-                let agent = ExpertiseLinkerAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-            LlmProvider::Codex => {
-                let backend = CodexAgent::new();
-                let agent = ExpertiseLinkerAgent::new(backend);
-                agent.execute(prompt.into()).await
-            }
-        };
+/// Prompt for [`ExpertiseGenerator::suggest_version_bump`]
+fn version_bump_prompt(old: &Expertise, new: &Expertise) -> String {
+    let diff = old.diff(new);
+    format!(
+        "OLD REVISION (v{}):\n{}\n\nNEW REVISION:\n{}\n\n\
+         Summary: description {}, {} fragment(s) added, {} fragment(s) removed, \
+         {} tag(s) added, {} tag(s) removed.\n\n\
+         Choose the version bump for this change.",
+        old.version(),
+        old.to_prompt_string(),
+        new.to_prompt_string(),
+        if diff.description_changed {
+            "changed"
+        } else {
+            "unchanged"
+        },
+        diff.fragments_added.len(),
+        diff.fragments_removed.len(),
+        diff.tags_added.len(),
+        diff.tags_removed.len()
+    )
+}
 
-        match response {
-            Ok(response) => {
-                let valid_links: Vec<SuggestedLink> = response
-                    .suggested_links
-                    .into_iter()
-                    .filter(|link| link.confidence >= 0.7)
-                    .collect();
+/// `version` with `bump` applied ("1.2.3" + minor = "1.3.0")
+///
+/// Missing components count as 0; a version without a minor component is
+/// returned unchanged.
+pub fn bump_version(version: &str, bump: SemverBump) -> String {
+    let parts: Vec<&str> = version.split('.').collect();
+    if parts.len() < 2 {
+        return version.to_string();
+    }
+    let part = |i: usize| -> u32 { parts.get(i).and_then(|p| p.parse().ok()).unwrap_or(0) };
+    let (major, minor, patch) = (part(0), part(1), part(2));
+    match bump {
+        SemverBump::Major => format!("{}.0.0", major + 1),
+        SemverBump::Minor => format!("{}.{}.0", major, minor + 1),
+        SemverBump::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+    }
+}
 
-                info!(
-                    "LinkerAgent suggested {} links (filtered from response)",
-                    valid_links.len()
-                );
+/// Whether a response has a blank description and no non-blank fragment
+fn is_empty_extraction(response: &ExpertiseResponse) -> bool {
+    response.description.trim().is_empty() && response.fragments.iter().all(|f| f.trim().is_empty())
+}
 
-                for link in &valid_links {
-                    debug!(
-                        "Suggested: {} -[{}]-> {} (confidence: {:.2}, reason: {})",
-                        link.from_id, link.relation_type, link.to_id, link.confidence, link.reason
-                    );
-                }
+/// Lowercase, hyphen-separated form of a name ("Rust Async" -> "rust-async")
+fn to_id_form(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
 
-                Ok(valid_links)
+/// Turn the splitter's parts into expertises derived from `original`
+///
+/// Each fragment goes to the first part that claims it; fragments no part
+/// claims are added to the first part so nothing is lost. Parts left without
+/// fragments are dropped, and duplicate suffixes get a numeric suffix.
+fn build_parts(original: &Expertise, parts: Vec<ExpertisePart>) -> Vec<Expertise> {
+    let fragments = &original.inner.content;
+    let mut assigned = vec![false; fragments.len()];
+    let mut built: Vec<Expertise> = Vec::new();
+
+    for part in parts {
+        let suffix = to_id_form(&part.suffix);
+        let base = if suffix.is_empty() {
+            format!("{}-part", original.id())
+        } else {
+            format!("{}-{}", original.id(), suffix)
+        };
+        let mut id = base.clone();
+        let mut n = 2;
+        while built.iter().any(|e| e.id() == id) {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+
+        let mut expertise = Expertise::new(&id, "1.0.0");
+        expertise.inner.description = Some(part.description);
+        expertise.inner.tags = part.tags;
+        expertise.metadata.scope = original.metadata.scope;
+        for index in part.fragment_indices {
+            if index < fragments.len() && !assigned[index] {
+                assigned[index] = true;
+                expertise.inner.content.push(fragments[index].clone());
             }
-            Err(e) => {
-                debug!("LinkerAgent failed: {:?}", e);
-                // Return empty list on failure (non-critical)
-                Ok(vec![])
+        }
+
+        if !expertise.inner.content.is_empty() {
+            built.push(expertise);
+        }
+    }
+
+    if let Some(first) = built.first_mut() {
+        for (index, fragment) in fragments.iter().enumerate() {
+            if !assigned[index] {
+                debug!("Fragment {} unassigned, adding it to {}", index, first.id());
+                first.inner.content.push(fragment.clone());
             }
         }
     }
+
+    built
+}
+
+/// `existing` with the fragments (and optionally the description) of `extracted`
+///
+/// Provenance comes from `extracted` as well, so sources recorded for the old
+/// fragments don't outlive them.
+fn refresh_fragments(
+    existing: &Expertise,
+    extracted: Expertise,
+    replace_description: bool,
+) -> Expertise {
+    let mut refreshed = existing.clone();
+    refreshed.inner.content = extracted.inner.content;
+    if replace_description {
+        refreshed.inner.description = extracted.inner.description;
+    }
+    refreshed.metadata.fragment_sources = extracted.metadata.fragment_sources;
+    refreshed.metadata.generated_by = extracted.metadata.generated_by;
+    refreshed.metadata.generated_at = extracted.metadata.generated_at;
+    refreshed.metadata.source_type = extracted.metadata.source_type;
+    refreshed.metadata.source_file = extracted.metadata.source_file;
+    refreshed
 }
 
 /// Validate an expertise ID
@@ -866,7 +2318,10 @@ fn is_valid_id(id: &str) -> bool {
     }
 
     // Must be lowercase and only contain alphanumeric chars and hyphens
-    if !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
         return false;
     }
 
@@ -887,7 +2342,10 @@ fn is_valid_id(id: &str) -> bool {
 
     // Reject IDs that look like UUIDs or session hashes
     let parts: Vec<&str> = id.split('-').collect();
-    if parts.iter().any(|p| p.len() == 8 && p.chars().all(|c| c.is_ascii_hexdigit())) {
+    if parts
+        .iter()
+        .any(|p| p.len() == 8 && p.chars().all(|c| c.is_ascii_hexdigit()))
+    {
         return false;
     }
 
@@ -897,6 +2355,7 @@ fn is_valid_id(id: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use llm_toolkit_expertise::WeightedFragment;
 
     #[tokio::test]
     async fn test_create_generator() {
@@ -951,6 +2410,475 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_required_tags_always_appear() {
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions {
+                seed_tags: vec!["async".to_string()],
+                required_tags: vec!["rust".to_string(), "team-a".to_string()],
+                ..Default::default()
+            },
+        };
+
+        let tags = generator.with_required_tags(vec!["Rust".to_string(), "tokio".to_string()]);
+        assert_eq!(tags, vec!["Rust", "tokio", "team-a"]);
+        assert_eq!(
+            generator.with_required_tags(Vec::new()),
+            vec!["rust", "team-a"]
+        );
+
+        let prompt = generator.with_seed_tags("Analyze this.".to_string());
+        assert!(prompt.contains("please include these user-specified tags: async"));
+        assert_eq!(
+            ExpertiseGenerator {
+                options: GenerationOptions::default()
+            }
+            .with_seed_tags("Analyze this.".to_string()),
+            "Analyze this."
+        );
+    }
+
+    #[test]
+    fn test_additional_context_in_prompts() {
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions {
+                additional_context: Some("Glossary: PR = pull request".to_string()),
+                ..Default::default()
+            },
+        };
+        let context = "Additional Context:\nGlossary: PR = pull request";
+
+        assert!(generator.log_prompt("user: hi").contains(context));
+        assert!(generator
+            .improve_prompt("{}", "Add examples")
+            .contains(context));
+        assert!(generator
+            .merge_prompt(&["{}".to_string()], "merged", "Merged")
+            .contains(context));
+
+        let plain = ExpertiseGenerator {
+            options: GenerationOptions::default(),
+        };
+        assert!(!plain.log_prompt("user: hi").contains("Additional Context"));
+    }
+
+    #[test]
+    fn test_add_meeting_tag() {
+        assert_eq!(
+            add_meeting_tag(vec!["postgres".to_string()]),
+            vec!["meeting-decision", "postgres"]
+        );
+        assert_eq!(
+            add_meeting_tag(vec!["postgres".to_string(), "Action-Item".to_string()]),
+            vec!["postgres", "Action-Item"]
+        );
+    }
+
+    #[test]
+    fn test_normalize_suggested_tags() {
+        let tags = normalize_suggested_tags(
+            vec![
+                "Error Handling".to_string(),
+                " rust ".to_string(),
+                "error-handling".to_string(),
+                "".to_string(),
+                "async".to_string(),
+            ],
+            &["async".to_string()],
+        );
+        assert_eq!(tags, vec!["error-handling", "rust"]);
+
+        let many = (0..8).map(|i| format!("tag-{}", i)).collect();
+        assert_eq!(
+            normalize_suggested_tags(many, &[]).len(),
+            MAX_SUGGESTED_TAGS
+        );
+    }
+
+    #[test]
+    fn test_tag_suggestion_prompt() {
+        let mut expertise = Expertise::new("sqlx-tips", "1.0.0");
+        expertise.inner.description = Some("Working with sqlx".to_string());
+        expertise.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Use query_as\nfor typed rows".to_string(),
+        ))];
+
+        let prompt = tag_suggestion_prompt(&expertise, &["rust".to_string(), "sql".to_string()]);
+        assert!(prompt.contains("- Use query_as for typed rows"));
+        assert!(prompt.contains("EXISTING TAGS: rust, sql"));
+        assert!(tag_suggestion_prompt(&expertise, &[]).contains("EXISTING TAGS: (none)"));
+    }
+
+    #[test]
+    fn test_description_prompt() {
+        let prompt = description_prompt(&[
+            "Use sqlx for queries ".to_string(),
+            "Enable WAL mode".to_string(),
+        ]);
+        assert!(prompt.contains("[1] Use sqlx for queries\n\n[2] Enable WAL mode"));
+        assert!(prompt.ends_with("description for expertise with these fragments."));
+    }
+
+    #[tokio::test]
+    async fn test_generate_description_requires_fragments() {
+        let generator = ExpertiseGenerator::new().await.unwrap();
+        assert!(generator.generate_description(&[]).await.is_err());
+    }
+
+    #[test]
+    fn test_priority_weight() {
+        assert_eq!(ExpertiseGenerator::priority_weight(&Priority::Critical), 4);
+        assert_eq!(ExpertiseGenerator::priority_weight(&Priority::Normal), 2);
+        assert_eq!(ExpertiseGenerator::priority_weight(&Priority::Low), 1);
+    }
+
+    #[test]
+    fn test_export_as_context() {
+        let mut expertise = Expertise::new("rust-error-handling", "1.0.0");
+        expertise.inner.description = Some("Error handling\nin Rust".to_string());
+        expertise.inner.tags = vec!["rust".to_string(), "errors".to_string()];
+        let mut low = WeightedFragment::new(KnowledgeFragment::Text("x".repeat(40)));
+        low.priority = Priority::Low;
+        let mut critical = WeightedFragment::new(KnowledgeFragment::Text(
+            "Never unwrap in libraries".to_string(),
+        ));
+        critical.priority = Priority::Critical;
+        expertise.inner.content = vec![low, critical];
+
+        let context = ExpertiseGenerator::export_as_context(&expertise, 4000, &Priority::Low);
+        assert!(context
+            .starts_with("rust-error-handling: Error handling in Rust [tags: rust, errors] | 1. "));
+        assert!(context.contains("1. Never unwrap in libraries"));
+        assert!(context.contains(" | 2. "));
+        assert!(!context.contains('\n'));
+
+        // The low-priority fragment is dropped first
+        let budget = ExpertiseGenerator::estimate_tokens(&context) - 5;
+        let truncated = ExpertiseGenerator::export_as_context(&expertise, budget, &Priority::Low);
+        assert!(truncated.contains("Never unwrap"));
+        assert!(!truncated.contains("xxxx"));
+        assert!(ExpertiseGenerator::estimate_tokens(&truncated) <= budget);
+
+        // Fragments below the minimum priority are left out regardless of budget
+        let filtered = ExpertiseGenerator::export_as_context(&expertise, 4000, &Priority::High);
+        assert!(filtered.contains("Never unwrap"));
+        assert!(!filtered.contains("xxxx"));
+    }
+
+    #[test]
+    fn test_export_as_context_keeps_critical() {
+        let fragment = |text: &str, priority: Priority| {
+            let mut weighted = WeightedFragment::new(KnowledgeFragment::Text(text.to_string()));
+            weighted.priority = priority;
+            weighted
+        };
+        let mut expertise = Expertise::new("rust-unsafe", "1.0.0");
+        expertise.inner.content = vec![
+            fragment("Low: prefer iterators over indexing", Priority::Low),
+            fragment("Normal: document every unsafe block", Priority::Normal),
+            fragment("Critical: never alias a &mut", Priority::Critical),
+            fragment(
+                "Critical: uphold Send and Sync contracts",
+                Priority::Critical,
+            ),
+        ];
+
+        // Room for the critical fragments and one more: the low one goes first
+        let full = ExpertiseGenerator::export_as_context(&expertise, 4000, &Priority::Low);
+        let budget = ExpertiseGenerator::estimate_tokens(&full) - 5;
+        let truncated = ExpertiseGenerator::export_as_context(&expertise, budget, &Priority::Low);
+        assert!(truncated.contains("1. Critical: never alias"));
+        assert!(truncated.contains("2. Critical: uphold"));
+        assert!(truncated.contains("3. Normal"));
+        assert!(!truncated.contains("Low:"));
+
+        // Critical fragments survive even a budget too small for any fragment
+        let starved = ExpertiseGenerator::export_as_context(&expertise, 1, &Priority::Low);
+        assert!(starved.contains("never alias"));
+        assert!(starved.contains("uphold Send and Sync"));
+        assert!(!starved.contains("Normal:"));
+        assert!(!starved.contains("Low:"));
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(ExpertiseGenerator::estimate_tokens(""), 0);
+        assert_eq!(ExpertiseGenerator::estimate_tokens("abcd"), 1);
+        assert_eq!(ExpertiseGenerator::estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_translated_expertise() {
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions::default(),
+        };
+        let mut original = Expertise::new("rust-error-handling", "1.2.0");
+        original.inner.tags = vec!["rust".to_string()];
+        let response: ExpertiseResponse = serde_json::from_value(serde_json::json!({
+            "suggested_id": "rust-error-handling",
+            "description": "Rust のエラー処理",
+            "tags": ["ラスト"],
+            "fragments": ["ライブラリには thiserror を使う"],
+        }))
+        .unwrap();
+
+        let translated = generator.translated_expertise(
+            &original,
+            response,
+            "rust-error-handling-ja",
+            Scope::Personal,
+        );
+        assert_eq!(translated.id(), "rust-error-handling-ja");
+        assert_eq!(translated.version(), "1.2.0");
+        assert_eq!(translated.tags(), ["rust"]);
+        assert_eq!(
+            translated.metadata.translated_from.as_deref(),
+            Some("rust-error-handling")
+        );
+        assert_eq!(translated.inner.content.len(), 1);
+
+        let prompt = generator.translate_prompt("{}", "ja");
+        assert!(prompt.contains("to ja. Preserve technical terms in English."));
+    }
+
+    #[test]
+    fn test_log_expertise_provenance() {
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions::default(),
+        };
+        let response: ExpertiseResponse = serde_json::from_value(serde_json::json!({
+            "suggested_id": "rust-async-patterns",
+            "description": "Async Rust",
+            "tags": ["rust"],
+            "fragments": ["Use tokio::spawn for concurrent tasks"],
+        }))
+        .unwrap();
+
+        let expertise = generator
+            .log_expertise(response, "fallback", Scope::Personal)
+            .unwrap();
+        assert_eq!(expertise.id(), "rust-async-patterns");
+        assert_eq!(
+            expertise.metadata.generated_by.as_deref(),
+            Some("claude/claude-sonnet-4-5")
+        );
+        assert_eq!(expertise.metadata.source_type.as_deref(), Some("log"));
+        assert!(expertise.metadata.generated_at.is_some());
+
+        // Provenance survives storage
+        let parsed = Expertise::from_json(&expertise.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.metadata.source_type.as_deref(), Some("log"));
+    }
+
+    #[tokio::test]
+    async fn test_log_expertise_secrets() {
+        use niwa_core::StorageOperations;
+
+        let response = || -> ExpertiseResponse {
+            serde_json::from_value(serde_json::json!({
+                "suggested_id": "openai-client",
+                "description": "Calling the OpenAI API",
+                "tags": ["openai"],
+                "fragments": [
+                    "Authenticate with OPENAI_API_KEY=sk-proj-abcdefghijklmnopqrstuvwx",
+                    "Retry on 429 with exponential backoff",
+                ],
+            }))
+            .unwrap()
+        };
+        let generator = |on_secret| ExpertiseGenerator {
+            options: GenerationOptions {
+                on_secret,
+                ..Default::default()
+            },
+        };
+
+        // Redacted before it ever reaches storage
+        let expertise = generator(SecretPolicy::Redact)
+            .log_expertise(response(), "fallback", Scope::Personal)
+            .unwrap();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = niwa_core::Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.storage().create(expertise).await.unwrap();
+        let stored = db
+            .storage()
+            .get("openai-client", Scope::Personal)
+            .await
+            .unwrap()
+            .unwrap();
+        let json = stored.to_json().unwrap();
+        assert!(!json.contains("sk-proj-"));
+        assert!(json.contains("OPENAI_API_KEY=[REDACTED:api-key]"));
+        assert!(json.contains("Retry on 429"));
+
+        let mut reviewed = generator(SecretPolicy::Warn)
+            .log_expertise(response(), "fallback", Scope::Personal)
+            .unwrap();
+        assert!(reviewed.to_json().unwrap().contains("sk-proj-"));
+        assert_eq!(reviewed.metadata.status, ExpertiseStatus::Draft);
+        reviewed.metadata.status = ExpertiseStatus::Reviewed;
+        assert!(generator(SecretPolicy::Warn)
+            .check_secrets(&mut reviewed)
+            .is_ok());
+        assert_eq!(reviewed.metadata.status, ExpertiseStatus::Draft);
+
+        assert!(matches!(
+            generator(SecretPolicy::Reject).log_expertise(response(), "fallback", Scope::Personal),
+            Err(Error::SecretDetected { kinds }) if kinds == ["api-key"]
+        ));
+    }
+
+    #[test]
+    fn test_empty_extraction() {
+        let response = |description: &str, fragments: &[&str]| -> ExpertiseResponse {
+            serde_json::from_value(serde_json::json!({
+                "suggested_id": "trivial-log",
+                "description": description,
+                "tags": [],
+                "fragments": fragments,
+            }))
+            .unwrap()
+        };
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions::default(),
+        };
+
+        assert!(matches!(
+            generator.check_not_empty(&response("", &[])),
+            Err(Error::EmptyExtraction)
+        ));
+        assert!(matches!(
+            generator.check_not_empty(&response("  \n", &[" "])),
+            Err(Error::EmptyExtraction)
+        ));
+        assert!(generator
+            .check_not_empty(&response("", &["A fact"]))
+            .is_ok());
+        assert!(generator.check_not_empty(&response("Notes", &[])).is_ok());
+
+        let keep = ExpertiseGenerator {
+            options: GenerationOptions {
+                keep_empty: true,
+                ..Default::default()
+            },
+        };
+        assert!(keep.check_not_empty(&response("", &[])).is_ok());
+    }
+
+    #[test]
+    fn test_confidence_threshold() {
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions {
+                min_confidence: 0.6,
+                ..Default::default()
+            },
+        };
+        assert!(generator.check_confidence(0.9).is_ok());
+        assert!(generator.check_confidence(0.6).is_ok());
+        assert!(matches!(
+            generator.check_confidence(0.3),
+            Err(Error::LowConfidence { confidence }) if confidence == 0.3
+        ));
+
+        // Responses without a confidence (e.g. from custom prompts) pass
+        let response: ExpertiseResponse = serde_json::from_str(
+            r#"{"suggested_id": "x", "description": "d", "tags": [], "fragments": []}"#,
+        )
+        .unwrap();
+        assert_eq!(response.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_fragment_limits() {
+        let fragments =
+            |n: usize| -> Vec<String> { (1..=n).map(|i| format!("Fact {}", i)).collect() };
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions {
+                min_fragments: Some(2),
+                max_fragments: Some(3),
+                ..Default::default()
+            },
+        };
+
+        let prompt = generator.log_prompt("log");
+        assert!(prompt.contains("Extract at least 2 fragments."));
+        assert!(prompt.contains("Extract at most 3 of the most important fragments."));
+
+        assert_eq!(
+            generator.apply_fragment_limits(fragments(5)).unwrap(),
+            fragments(3)
+        );
+        assert_eq!(
+            generator.apply_fragment_limits(fragments(2)).unwrap().len(),
+            2
+        );
+        assert!(matches!(
+            generator.apply_fragment_limits(fragments(1)),
+            Err(Error::TooFewFragments { count: 1, min: 2 })
+        ));
+
+        // Unlimited by default
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions::default(),
+        };
+        assert!(!generator.log_prompt("log").contains("Extract at"));
+        assert_eq!(
+            generator
+                .apply_fragment_limits(fragments(30))
+                .unwrap()
+                .len(),
+            30
+        );
+    }
+
+    #[test]
+    fn test_bump_version() {
+        assert_eq!(bump_version("1.2.3", SemverBump::Major), "2.0.0");
+        assert_eq!(bump_version("1.2.3", SemverBump::Minor), "1.3.0");
+        assert_eq!(bump_version("1.2.3", SemverBump::Patch), "1.2.4");
+        assert_eq!(bump_version("1.0", SemverBump::Patch), "1.0.1");
+        assert_eq!(bump_version("draft", SemverBump::Minor), "draft");
+    }
+
+    #[test]
+    fn test_version_bump_prompt() {
+        let mut old = Expertise::new("rust-expert", "1.2.0");
+        old.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Prefer ? over unwrap".to_string(),
+        ))];
+        let mut new = old.clone();
+        new.inner.content.clear();
+
+        let prompt = version_bump_prompt(&old, &new);
+        assert!(prompt.contains("OLD REVISION (v1.2.0)"));
+        assert!(prompt.contains("0 fragment(s) added, 1 fragment(s) removed"));
+    }
+
+    #[tokio::test]
+    async fn test_version_bump_strategies() {
+        let old = Expertise::new("rust-expert", "1.0.0");
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions::default(),
+        };
+        assert_eq!(
+            generator.version_bump(&old, &old).await,
+            (SemverBump::Minor, None)
+        );
+
+        let generator = ExpertiseGenerator {
+            options: GenerationOptions {
+                version_strategy: VersionBumpStrategy::Semantic(SemverBump::Patch),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            generator.version_bump(&old, &old).await,
+            (SemverBump::Patch, None)
+        );
+    }
+
     #[test]
     fn test_is_valid_id() {
         // Valid IDs
@@ -980,4 +2908,165 @@ mod tests {
         assert!(!is_valid_id("agent-8862213c"));
         assert!(!is_valid_id("session-abcd1234"));
     }
+
+    #[test]
+    fn test_to_knowledge_fragment() {
+        assert_eq!(fragment_type_name("logic"), Some("Logic"));
+        assert_eq!(
+            fragment_type_name("quality-standard"),
+            Some("QualityStandard")
+        );
+        assert_eq!(fragment_type_name("text"), None);
+
+        let upgraded = UpgradedFragment {
+            fragment_type: "Logic".to_string(),
+            content: serde_json::json!({
+                "instruction": "Release a crate",
+                "steps": ["Bump version", "cargo publish"]
+            }),
+        };
+        match to_knowledge_fragment(upgraded.clone(), "Logic").unwrap() {
+            KnowledgeFragment::Logic { instruction, steps } => {
+                assert_eq!(instruction, "Release a crate");
+                assert_eq!(steps.len(), 2);
+            }
+            other => panic!("unexpected fragment: {:?}", other),
+        }
+
+        // Type mismatch is rejected
+        assert!(to_knowledge_fragment(upgraded, "Guideline").is_err());
+    }
+
+    #[test]
+    fn test_resolve_target_id() {
+        let ids: Vec<String> = ["rust-async", "rust", "error-handling"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let known: Vec<&String> = ids.iter().collect();
+
+        assert_eq!(resolve_target_id("rust-async", &known), Some("rust-async"));
+        assert_eq!(
+            resolve_target_id("Error Handling", &known),
+            Some("error-handling")
+        );
+        assert_eq!(
+            resolve_target_id("the rust async expertise", &known),
+            Some("rust-async")
+        );
+        assert_eq!(resolve_target_id("trusted inputs", &known), None);
+        assert_eq!(resolve_target_id("  ", &known), None);
+    }
+
+    #[test]
+    fn test_build_parts() {
+        let mut original = Expertise::new("web-dev", "1.0.0");
+        original.metadata.scope = Scope::Project;
+        for text in ["css grid", "flexbox", "sql joins", "indexes"] {
+            original
+                .inner
+                .content
+                .push(WeightedFragment::new(KnowledgeFragment::Text(
+                    text.to_string(),
+                )));
+        }
+
+        let part = |suffix: &str, indices: Vec<usize>| ExpertisePart {
+            suffix: suffix.to_string(),
+            description: String::new(),
+            tags: vec![],
+            fragment_indices: indices,
+        };
+        let parts = build_parts(
+            &original,
+            vec![
+                part("CSS Layout", vec![0, 1]),
+                part("css layout", vec![1, 2, 9]),
+                part("empty", vec![]),
+            ],
+        );
+
+        let ids: Vec<&str> = parts.iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec!["web-dev-css-layout", "web-dev-css-layout-2"]);
+        assert!(parts.iter().all(|p| p.metadata.scope == Scope::Project));
+        // Fragment 1 stays with the first claim; unclaimed 3 joins the first part
+        assert_eq!(parts[0].inner.content.len(), 3);
+        assert_eq!(parts[1].inner.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_fragments() {
+        use niwa_core::{RelationType, StorageOperations};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = niwa_core::Database::open(temp_dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let mut existing = Expertise::new("rust-errors", "1.2.0");
+        existing.inner.description = Some("Curated error handling".to_string());
+        existing.inner.tags = vec!["rust".to_string(), "errors".to_string()];
+        existing.metadata.scope = Scope::Company;
+        existing.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Use failure crate".to_string(),
+        ))];
+        existing.set_source("old-session.log");
+        existing.metadata.source_file = Some("old-session.log".to_string());
+        existing.metadata.generated_at = Some(1_700_000_000);
+        let mut tokio = Expertise::new("tokio", "1.0.0");
+        tokio.metadata.scope = Scope::Company;
+        db.storage().create(existing.clone()).await.unwrap();
+        db.storage().create(tokio).await.unwrap();
+        db.graph()
+            .create_relation("rust-errors", "tokio", RelationType::Uses, None)
+            .await
+            .unwrap();
+
+        // Extracted as a new expertise: another id, tags and description
+        let mut extracted = Expertise::new("error-handling-notes", "1.0.0");
+        extracted.inner.description = Some("Errors in Rust".to_string());
+        extracted.inner.tags = vec!["anyhow".to_string()];
+        extracted.inner.content = vec![
+            WeightedFragment::new(KnowledgeFragment::Text(
+                "Use thiserror in libraries".to_string(),
+            )),
+            WeightedFragment::new(KnowledgeFragment::Text(
+                "Use anyhow in binaries".to_string(),
+            )),
+        ];
+        extracted.metadata.generated_at = Some(1_800_000_000);
+        extracted.metadata.source_type = Some("log".to_string());
+
+        let mut refreshed = refresh_fragments(&existing, extracted.clone(), false);
+        assert_eq!(refreshed.id(), "rust-errors");
+        assert_eq!(refreshed.metadata.scope, Scope::Company);
+        assert_eq!(refreshed.tags(), ["rust", "errors"]);
+        assert_eq!(refreshed.description(), "Curated error handling");
+        assert_eq!(refreshed.inner.content.len(), 2);
+        assert_eq!(
+            refresh_fragments(&existing, extracted, true).description(),
+            "Errors in Rust"
+        );
+
+        // Provenance of the old fragments is gone, the new extraction's is kept
+        assert!(refreshed.metadata.fragment_sources.is_empty());
+        assert_eq!(refreshed.metadata.source_file, None);
+        assert_eq!(refreshed.metadata.generated_at, Some(1_800_000_000));
+        assert_eq!(refreshed.metadata.source_type.as_deref(), Some("log"));
+        refreshed.set_source("new-session.log");
+        assert_eq!(refreshed.fragment_source(1), Some("new-session.log"));
+
+        db.storage().update(refreshed).await.unwrap();
+        let stored = db
+            .storage()
+            .get("rust-errors", Scope::Company)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tags(), ["rust", "errors"]);
+        assert_eq!(stored.inner.content.len(), 2);
+        let relations = db.graph().get_outgoing("rust-errors").await.unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].to_id, "tokio");
+    }
 }
@@ -5,7 +5,10 @@
 //! ## Features
 //!
 //! - Generate Expertise from conversation logs using LLM
+//! - Extract decisions and action items from meeting notes
 //! - Improve existing Expertise with LLM assistance
+//! - Crawl directories of session logs into Expertise ([`Crawler`])
+//! - Redact secrets and personal data the LLM echoes into fragments
 //! - Interactive Expertise creation
 //! - Schema-based structured data generation
 //!
@@ -32,19 +35,35 @@
 //! ```
 
 pub mod agents;
+pub mod changelog;
+pub mod crawler;
 pub mod error;
 pub mod generator;
+pub mod secrets;
 pub mod session_log;
 
 // Re-exports
 pub use agents::{
-    ExpertiseExtractorAgent, ExpertiseImprovementResponse, ExpertiseImproverAgent,
-    ExpertiseLinkerAgent, ExpertiseMergerAgent, ExpertiseResponse, ExpertiseSummary,
-    InteractiveExpertiseAgent, InteractiveExpertiseResponse, LinkerResponse,
-    MergedExpertiseResponse, SuggestedLink,
+    ChangelogExtractorAgent, CodeAnalysisAgent, CustomPromptAgent, DescriptionGeneratorAgent,
+    DescriptionResponse, ExpertiseExtractorAgent, ExpertiseImprovementResponse,
+    ExpertiseImproverAgent, ExpertiseLinkerAgent, ExpertiseMergerAgent, ExpertisePart,
+    ExpertiseResponse, ExpertiseSummary, FragmentUpgraderAgent, ImplicitRelation,
+    InteractiveExpertiseAgent, InteractiveExpertiseResponse, LinkerResponse, MeetingNotesAgent,
+    MergedExpertiseResponse, QualityCheckResponse, QualityCheckerAgent, RelationExtractionAgent,
+    RelationExtractionResponse, SemverBump, SuggestedLink, TagBasedSplitterAgent,
+    TagSuggesterAgent, TagSuggestionResponse, TopicSplitResponse, TranslatorAgent,
+    UpgradedFragment, VersionAnalysisAgent, VersionBumpRecommendation,
+};
+pub use changelog::{split_changelog, ChangelogSection};
+pub use crawler::{
+    Crawler, EventHandler, FileOutcome, FileReport, LinkReport, LinkStrategy, ScanEvent,
+    ScanOptions, ScanReport, SkipCounts,
 };
 pub use error::{Error, Result};
-pub use generator::{ExpertiseGenerator, GenerationOptions, LlmProvider};
+pub use generator::{
+    bump_version, ExpertiseGenerator, GenerationOptions, LlmProvider, VersionBumpStrategy,
+};
+pub use secrets::SecretPolicy;
 pub use session_log::SessionLogParser;
 
 /// Library version
@@ -0,0 +1,353 @@
+//! User configuration from `~/.niwa/config.toml`
+//!
+//! Every setting is optional; a missing file is the same as an empty one.
+//!
+//! ```toml
+//! default_scope = "project"  # scope of new expertises when --scope is omitted
+//!
+//! [list]
+//! stale_after_days = 90  # show the age of older expertises in red
+//!
+//! [crawler]
+//! auto_scope = true   # resolve scope from scope mappings on every crawl
+//! max_size = 1048576  # skip session files larger than 1 MB
+//! min_size = 512      # skip session files smaller than 512 bytes
+//!
+//! [smtp]                 # for `niwa crawler run --email-report`
+//! host = "smtp.example.com"
+//! port = 587
+//! username = "niwa"
+//! password = "secret"
+//! from_address = "niwa@example.com"
+//!
+//! [sendgrid]             # for `--email-provider sendgrid`
+//! api_key = "SG.xxx"     # or set SENDGRID_API_KEY
+//! from_address = "niwa@example.com"
+//! ```
+//!
+//! Inside a project, a `.niwarc` in the working directory or any parent
+//! overrides the defaults for that project, like `.editorconfig`:
+//!
+//! ```toml
+//! scope = "project"     # scope of new expertises when --scope is omitted
+//! id_prefix = "billing" # crawled expertises are stored as billing-<id>
+//! ```
+
+use anyhow::Context;
+use niwa_core::{Database, Scope};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding `default_scope` from config.toml
+pub const DEFAULT_SCOPE_ENV: &str = "NIWA_DEFAULT_SCOPE";
+
+/// Per-directory project file, looked up from the working directory upwards
+pub const PROJECT_FILE: &str = ".niwarc";
+
+/// Top-level configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Scope of new expertises when `--scope` is omitted (default: personal)
+    pub default_scope: Option<Scope>,
+
+    /// `[list]` section
+    pub list: ListConfig,
+
+    /// `[crawler]` section
+    pub crawler: CrawlerConfig,
+
+    /// `[smtp]` section
+    pub smtp: Option<SmtpConfig>,
+
+    /// `[sendgrid]` section
+    pub sendgrid: Option<SendGridConfig>,
+
+    /// Settings from the nearest `.niwarc`, if any
+    #[serde(skip)]
+    pub project: ProjectConfig,
+}
+
+/// Settings from a `.niwarc` project file
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Scope of new expertises when `--scope` is omitted (overrides `default_scope`)
+    pub scope: Option<Scope>,
+
+    /// Prefix for the IDs of expertises the crawler generates in this project
+    pub id_prefix: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Path of the `.niwarc` in `dir` or the nearest of its parents
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(PROJECT_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the project file at `path`
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid project file {}", path.display()))
+    }
+}
+
+/// Settings for `niwa list`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListConfig {
+    /// Age in days after which an expertise is highlighted as stale (default: 180)
+    pub stale_after_days: Option<u32>,
+}
+
+/// Settings for `niwa crawler`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlerConfig {
+    /// Imply `--auto-scope` for every crawl run (disable with `--no-auto-scope`)
+    pub auto_scope: bool,
+
+    /// Default for `--max-size` (bytes)
+    pub max_size: Option<u64>,
+
+    /// Default for `--min-size` (bytes)
+    pub min_size: Option<u64>,
+
+    /// Default for `--ext`: file extensions the crawler scans
+    pub extensions: Option<Vec<String>>,
+}
+
+/// SMTP server used to email crawl reports
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+
+    /// STARTTLS port (default: 587)
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+
+    pub username: Option<String>,
+
+    pub password: Option<String>,
+
+    /// Sender of report emails
+    pub from_address: String,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+/// SendGrid account used to email crawl reports without an SMTP server
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SendGridConfig {
+    /// API key (overridden by `SENDGRID_API_KEY`)
+    pub api_key: Option<String>,
+
+    /// Verified sender of report emails
+    pub from_address: String,
+}
+
+impl Config {
+    /// Path of the config file, next to the default database
+    pub fn default_path() -> anyhow::Result<PathBuf> {
+        let db_path = Database::default_path()?;
+        Ok(db_path.with_file_name("config.toml"))
+    }
+
+    /// Load the config from the default location, with the `.niwarc` of the
+    /// working directory
+    pub fn load_default() -> anyhow::Result<Self> {
+        let cwd = std::env::current_dir().context("Failed to get current directory")?;
+        Self::load_with_project(&Self::default_path()?, &cwd)
+    }
+
+    /// Load the config from `path` and the project file nearest to `dir`
+    pub fn load_with_project(path: &Path, dir: &Path) -> anyhow::Result<Self> {
+        let mut config = Self::load(path)?;
+        if let Some(project_path) = ProjectConfig::discover(dir) {
+            config.project = ProjectConfig::load(&project_path)?;
+        }
+        Ok(config)
+    }
+
+    /// Scope for a new expertise: the flag if given, else `NIWA_DEFAULT_SCOPE`,
+    /// else the `.niwarc` scope, else `default_scope`, else personal
+    pub fn scope_or_default(&self, flag: Option<Scope>) -> Result<Scope, String> {
+        match flag {
+            Some(scope) => Ok(scope),
+            None => self.default_scope_from(std::env::var(DEFAULT_SCOPE_ENV).ok().as_deref()),
+        }
+    }
+
+    /// Default scope given the value of `NIWA_DEFAULT_SCOPE`, if set
+    fn default_scope_from(&self, env: Option<&str>) -> Result<Scope, String> {
+        match env.map(str::trim) {
+            None | Some("") => Ok(self
+                .project
+                .scope
+                .or(self.default_scope)
+                .unwrap_or_default()),
+            Some(value) => value
+                .parse()
+                .map_err(|e| format!("Invalid {}: {}", DEFAULT_SCOPE_ENV, e)),
+        }
+    }
+
+    /// Load the config from `path`, or the defaults if it doesn't exist
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        std::fs::write(&path, "[crawler]\nauto_scope = true\n").unwrap();
+        assert!(Config::load(&path).unwrap().crawler.auto_scope);
+
+        std::fs::write(&path, "[crawler]\nmax_size = 1048576\n").unwrap();
+        assert_eq!(Config::load(&path).unwrap().crawler.max_size, Some(1048576));
+
+        std::fs::write(&path, "[crawler]\nextensions = [\"chat\", \"org\"]\n").unwrap();
+        assert_eq!(
+            Config::load(&path).unwrap().crawler.extensions,
+            Some(vec!["chat".to_string(), "org".to_string()])
+        );
+
+        std::fs::write(&path, "[list]\nstale_after_days = 90\n").unwrap();
+        assert_eq!(Config::load(&path).unwrap().list.stale_after_days, Some(90));
+
+        std::fs::write(
+            &path,
+            "[smtp]\nhost = \"smtp.example.com\"\nfrom_address = \"niwa@example.com\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Config::load(&path).unwrap().smtp,
+            Some(SmtpConfig {
+                host: "smtp.example.com".to_string(),
+                port: 587,
+                username: None,
+                password: None,
+                from_address: "niwa@example.com".to_string(),
+            })
+        );
+
+        std::fs::write(&path, "[smtp]\nhost = \"smtp.example.com\"\n").unwrap();
+        assert!(Config::load(&path).is_err());
+
+        std::fs::write(&path, "[crawler]\nauto_scop = true\n").unwrap();
+        assert!(Config::load(&path).is_err());
+
+        std::fs::write(&path, "default_scope = \"project\"\n").unwrap();
+        assert_eq!(
+            Config::load(&path).unwrap().default_scope,
+            Some(Scope::Project)
+        );
+    }
+
+    #[test]
+    fn test_default_scope() {
+        let config = Config {
+            default_scope: Some(Scope::Project),
+            ..Default::default()
+        };
+
+        // The configured default applies when the flag is absent
+        assert_eq!(config.default_scope_from(None), Ok(Scope::Project));
+        assert_eq!(config.default_scope_from(Some("")), Ok(Scope::Project));
+        // The environment variable overrides the config file
+        assert_eq!(
+            config.default_scope_from(Some("company")),
+            Ok(Scope::Company)
+        );
+        assert!(config.default_scope_from(Some("team")).is_err());
+        // Without either, new expertises stay personal
+        assert_eq!(
+            Config::default().default_scope_from(None),
+            Ok(Scope::Personal)
+        );
+        assert_eq!(
+            config.scope_or_default(Some(Scope::Personal)),
+            Ok(Scope::Personal)
+        );
+    }
+
+    #[test]
+    fn test_discover_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let nested = root.join("repo/crates/core");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(ProjectConfig::discover(&nested), None);
+
+        std::fs::write(root.join(PROJECT_FILE), "scope = \"company\"\n").unwrap();
+        assert_eq!(
+            ProjectConfig::discover(&nested),
+            Some(root.join(PROJECT_FILE))
+        );
+
+        // The nearest file wins
+        let repo_file = root.join("repo").join(PROJECT_FILE);
+        std::fs::write(&repo_file, "scope = \"project\"\nid_prefix = \"billing\"\n").unwrap();
+        assert_eq!(ProjectConfig::discover(&nested), Some(repo_file.clone()));
+        assert_eq!(
+            ProjectConfig::load(&repo_file).unwrap(),
+            ProjectConfig {
+                scope: Some(Scope::Project),
+                id_prefix: Some("billing".to_string()),
+            }
+        );
+
+        std::fs::write(&repo_file, "scop = \"project\"\n").unwrap();
+        assert!(ProjectConfig::load(&repo_file).is_err());
+    }
+
+    #[test]
+    fn test_project_scope_precedence() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let project_dir = temp_dir.path().join("repo");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        std::fs::write(&config_path, "default_scope = \"company\"\n").unwrap();
+
+        // Outside a project the global config applies
+        let config = Config::load_with_project(&config_path, temp_dir.path()).unwrap();
+        assert_eq!(config.default_scope_from(None), Ok(Scope::Company));
+
+        std::fs::write(project_dir.join(PROJECT_FILE), "scope = \"project\"\n").unwrap();
+        let config = Config::load_with_project(&config_path, &project_dir).unwrap();
+        // .niwarc overrides config.toml ...
+        assert_eq!(config.default_scope_from(None), Ok(Scope::Project));
+        // ... but not NIWA_DEFAULT_SCOPE or the flag
+        assert_eq!(
+            config.default_scope_from(Some("personal")),
+            Ok(Scope::Personal)
+        );
+        assert_eq!(
+            config.scope_or_default(Some(Scope::Company)),
+            Ok(Scope::Company)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Crawl report delivery by email, over SMTP or the SendGrid HTTP API
+//!
+//! Used by `niwa crawler run --email-report`. Server settings come from the
+//! `[smtp]` and `[sendgrid]` sections of config.toml.
+
+use crate::config::{Config, SendGridConfig, SmtpConfig};
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+
+const SENDGRID_API_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+/// Environment variable overriding `api_key` in `[sendgrid]`
+pub const SENDGRID_API_KEY_ENV: &str = "SENDGRID_API_KEY";
+
+/// How report emails are delivered
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmailProvider {
+    /// The SMTP server in `[smtp]`
+    #[default]
+    Smtp,
+    /// The SendGrid HTTP API (`[sendgrid]`)
+    Sendgrid,
+}
+
+/// A plain-text email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Email {
+    /// Report email for a finished crawl
+    ///
+    /// `source` names what was crawled (a directory, a target or
+    /// "registered paths") and `report` is the crawl summary.
+    pub fn crawl_report(to: &str, new_count: usize, source: &str, report: &str) -> Self {
+        Self {
+            to: to.to_string(),
+            subject: format!(
+                "NIWA Crawl Report: {} new expertise from {}",
+                new_count, source
+            ),
+            body: format!(
+                "NIWA crawl of {} finished at {}.\n\n{}\n",
+                source,
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                report.trim()
+            ),
+        }
+    }
+}
+
+/// Sends emails with the configured provider
+pub enum Mailer {
+    Smtp(SmtpConfig),
+    SendGrid {
+        api_key: String,
+        from_address: String,
+    },
+}
+
+impl Mailer {
+    /// Build a mailer from config.toml, failing early if it isn't configured
+    pub fn from_config(config: &Config, provider: EmailProvider) -> anyhow::Result<Self> {
+        match provider {
+            EmailProvider::Smtp => config
+                .smtp
+                .clone()
+                .map(Self::Smtp)
+                .context("No [smtp] section in config.toml (or use --email-provider sendgrid)"),
+            EmailProvider::Sendgrid => {
+                let SendGridConfig {
+                    api_key,
+                    from_address,
+                } = config
+                    .sendgrid
+                    .clone()
+                    .context("No [sendgrid] section in config.toml")?;
+                let api_key = std::env::var(SENDGRID_API_KEY_ENV)
+                    .ok()
+                    .or(api_key)
+                    .filter(|k| !k.trim().is_empty())
+                    .with_context(|| {
+                        format!(
+                            "No SendGrid API key (set {} or api_key in [sendgrid])",
+                            SENDGRID_API_KEY_ENV
+                        )
+                    })?;
+                Ok(Self::SendGrid {
+                    api_key,
+                    from_address,
+                })
+            }
+        }
+    }
+
+    /// Send a plain-text email
+    pub async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        match self {
+            Self::Smtp(smtp) => send_smtp(smtp, email).await,
+            Self::SendGrid {
+                api_key,
+                from_address,
+            } => send_sendgrid(api_key, from_address, email).await,
+        }
+    }
+}
+
+async fn send_smtp(smtp: &SmtpConfig, email: &Email) -> anyhow::Result<()> {
+    let message = Message::builder()
+        .from(smtp.from_address.parse().context("Invalid from_address")?)
+        .to(email.to.parse().context("Invalid recipient address")?)
+        .subject(&email.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())
+        .context("Failed to build email")?;
+
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)
+        .with_context(|| format!("Invalid SMTP host {}", smtp.host))?
+        .port(smtp.port);
+    if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("Failed to send email via {}", smtp.host))?;
+    Ok(())
+}
+
+async fn send_sendgrid(api_key: &str, from_address: &str, email: &Email) -> anyhow::Result<()> {
+    let response = reqwest::Client::new()
+        .post(SENDGRID_API_URL)
+        .bearer_auth(api_key)
+        .json(&sendgrid_payload(from_address, email))
+        .send()
+        .await
+        .context("Failed to reach the SendGrid API")?;
+
+    if !response.status().is_success() {
+        bail!(
+            "SendGrid API returned {}: {}",
+            response.status(),
+            response.text().await.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// Request body for the SendGrid v3 mail/send endpoint
+fn sendgrid_payload(from_address: &str, email: &Email) -> serde_json::Value {
+    json!({
+        "personalizations": [{ "to": [{ "email": email.to }] }],
+        "from": { "email": from_address },
+        "subject": email.subject,
+        "content": [{ "type": "text/plain", "value": email.body }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crawl_report() {
+        let email = Email::crawl_report(
+            "me@example.com",
+            3,
+            "/home/me/.claude/projects",
+            "\nSummary: 3 processed\n",
+        );
+        assert_eq!(
+            email.subject,
+            "NIWA Crawl Report: 3 new expertise from /home/me/.claude/projects"
+        );
+        assert!(email.body.ends_with("\n\nSummary: 3 processed\n"));
+
+        let payload = sendgrid_payload("niwa@example.com", &email);
+        assert_eq!(
+            payload["personalizations"][0]["to"][0]["email"],
+            "me@example.com"
+        );
+        assert_eq!(payload["from"]["email"], "niwa@example.com");
+        assert_eq!(payload["subject"], email.subject);
+    }
+
+    #[test]
+    fn test_mailer_requires_config() {
+        let config = Config::default();
+        assert!(Mailer::from_config(&config, EmailProvider::Smtp).is_err());
+        assert!(Mailer::from_config(&config, EmailProvider::Sendgrid).is_err());
+
+        let config = Config {
+            smtp: Some(SmtpConfig {
+                host: "smtp.example.com".to_string(),
+                port: 587,
+                username: None,
+                password: None,
+                from_address: "niwa@example.com".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Mailer::from_config(&config, EmailProvider::Smtp),
+            Ok(Mailer::Smtp(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+//! Machine-readable crawl events for `niwa crawler run --emit-events`
+//!
+//! Events are written as JSON Lines so they can be piped into `jq`, tailed
+//! from a file, or consumed from a named pipe or Unix domain socket.
+
+use anyhow::Context;
+use serde::Serialize;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A single crawl progress event
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CrawlEvent {
+    /// Processing of a session file began
+    FileStarted { path: String, timestamp: i64 },
+    /// An expertise was stored from a session file
+    ExpertiseCreated {
+        id: String,
+        source: String,
+        fragment_count: usize,
+        timestamp: i64,
+    },
+    /// A session file was skipped without processing (e.g. binary content)
+    FileSkipped {
+        path: String,
+        reason: String,
+        timestamp: i64,
+    },
+    /// Processing of a session file failed
+    FileFailed {
+        path: String,
+        error: String,
+        timestamp: i64,
+    },
+}
+
+impl CrawlEvent {
+    pub fn file_started(path: &Path) -> Self {
+        Self::FileStarted {
+            path: path.display().to_string(),
+            timestamp: now(),
+        }
+    }
+
+    pub fn expertise_created(id: &str, source: &Path, fragment_count: usize) -> Self {
+        Self::ExpertiseCreated {
+            id: id.to_string(),
+            source: source.display().to_string(),
+            fragment_count,
+            timestamp: now(),
+        }
+    }
+
+    pub fn file_skipped(path: &Path, reason: &str) -> Self {
+        Self::FileSkipped {
+            path: path.display().to_string(),
+            reason: reason.to_string(),
+            timestamp: now(),
+        }
+    }
+
+    pub fn file_failed(path: &Path, error: &str) -> Self {
+        Self::FileFailed {
+            path: path.display().to_string(),
+            error: error.to_string(),
+            timestamp: now(),
+        }
+    }
+}
+
+impl From<niwa_generator::ScanEvent> for CrawlEvent {
+    fn from(event: niwa_generator::ScanEvent) -> Self {
+        use niwa_generator::ScanEvent;
+        match event {
+            ScanEvent::FileStarted { path } => Self::file_started(&path),
+            ScanEvent::ExpertiseCreated {
+                id,
+                path,
+                fragment_count,
+            } => Self::expertise_created(&id, &path, fragment_count),
+            ScanEvent::FileSkipped { path, reason } => Self::file_skipped(&path, &reason),
+            ScanEvent::FileFailed { path, error } => Self::file_failed(&path, &error),
+        }
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// Where events are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventsOutput {
+    Stdout,
+    /// A regular file, named pipe or (on Unix) a listening domain socket
+    Path(PathBuf),
+}
+
+impl std::str::FromStr for EventsOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err("events output must not be empty".to_string()),
+            "stdout" | "-" => Ok(Self::Stdout),
+            path => Ok(Self::Path(PathBuf::from(path))),
+        }
+    }
+}
+
+impl fmt::Display for EventsOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdout => write!(f, "stdout"),
+            Self::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Writes crawl events as JSON Lines to a configured output
+pub struct EventEmitter {
+    output: EventsOutput,
+    writer: Box<dyn Write + Send>,
+}
+
+impl fmt::Debug for EventEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventEmitter")
+            .field("output", &self.output)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventEmitter {
+    /// Open the output; files are appended to, sockets are connected to
+    pub fn open(output: EventsOutput) -> anyhow::Result<Self> {
+        let writer: Box<dyn Write + Send> = match &output {
+            EventsOutput::Stdout => Box::new(std::io::stdout()),
+            EventsOutput::Path(path) => open_path(path)?,
+        };
+        Ok(Self { output, writer })
+    }
+
+    /// Whether events share stdout with the command output
+    pub fn is_stdout(&self) -> bool {
+        self.output == EventsOutput::Stdout
+    }
+
+    /// Write one event and flush it immediately
+    ///
+    /// Failures are logged rather than returned so a broken consumer never
+    /// aborts the crawl.
+    pub fn emit(&mut self, event: CrawlEvent) {
+        let result = serde_json::to_string(&event)
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(self.writer, "{}", line))
+            .and_then(|_| self.writer.flush());
+
+        if let Err(e) = result {
+            warn!("Failed to emit crawl event to {}: {}", self.output, e);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn open_path(path: &Path) -> anyhow::Result<Box<dyn Write + Send>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let is_socket = std::fs::metadata(path)
+        .map(|m| m.file_type().is_socket())
+        .unwrap_or(false);
+    if is_socket {
+        let stream = std::os::unix::net::UnixStream::connect(path)
+            .with_context(|| format!("Failed to connect to socket {}", path.display()))?;
+        return Ok(Box::new(stream));
+    }
+
+    open_file(path)
+}
+
+#[cfg(not(unix))]
+fn open_path(path: &Path) -> anyhow::Result<Box<dyn Write + Send>> {
+    open_file(path)
+}
+
+fn open_file(path: &Path) -> anyhow::Result<Box<dyn Write + Send>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open events output {}", path.display()))?;
+    Ok(Box::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json_shape() {
+        let event = CrawlEvent::ExpertiseCreated {
+            id: "rust-expert".to_string(),
+            source: "/logs/a.jsonl".to_string(),
+            fragment_count: 5,
+            timestamp: 123,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"expertise_created","id":"rust-expert","source":"/logs/a.jsonl","fragment_count":5,"timestamp":123}"#
+        );
+    }
+
+    #[test]
+    fn test_emit_to_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.jsonl");
+
+        let mut emitter = EventEmitter::open(EventsOutput::Path(path.clone())).unwrap();
+        emitter.emit(CrawlEvent::file_started(Path::new("a.jsonl")));
+        emitter.emit(CrawlEvent::file_failed(Path::new("a.jsonl"), "boom"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let types: Vec<String> = content
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["type"].to_string()
+            })
+            .collect();
+        assert_eq!(types, vec![r#""file_started""#, r#""file_failed""#]);
+    }
+
+    #[test]
+    fn test_events_output_from_str() {
+        assert_eq!("stdout".parse(), Ok(EventsOutput::Stdout));
+        assert_eq!("-".parse(), Ok(EventsOutput::Stdout));
+        assert_eq!(
+            "/tmp/pipe".parse(),
+            Ok(EventsOutput::Path(PathBuf::from("/tmp/pipe")))
+        );
+    }
+}
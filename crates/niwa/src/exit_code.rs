@@ -0,0 +1,191 @@
+//! Process exit codes
+//!
+//! Scripts can branch on the kind of failure, e.g. retry only on LLM errors:
+//!
+//! | Code | Meaning                                                    |
+//! |------|------------------------------------------------------------|
+//! | 0    | Success                                                    |
+//! | 1    | User error (invalid arguments or input)                    |
+//! | 2    | System error (database, I/O, anything unexpected)          |
+//! | 3    | Not found (expertise, version, reminder, ...)              |
+//! | 4    | LLM error (the generation backend failed; often transient) |
+//!
+//! The router only knows user and system errors, so handlers report the
+//! finer kinds by building their errors with [`not_found`], [`llm_error`] or
+//! [`generator_error`], which record the kind for `main`. Only build them for
+//! errors the handler returns: the last one built decides the exit code.
+//! `main` calls [`reset`] before running a command.
+
+use sen::CliError;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Exit code of the `niwa` process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success = 0,
+    User = 1,
+    System = 2,
+    NotFound = 3,
+    Llm = 4,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Kind of failure a core error represents
+    pub fn from_core(e: &niwa_core::Error) -> Self {
+        use niwa_core::Error;
+        match e {
+            Error::NotFound { .. } | Error::VersionNotFound { .. } | Error::ReminderNotFound(_) => {
+                Self::NotFound
+            }
+            Error::Database(_) | Error::Io(_) | Error::Migration(_) | Error::Serialization(_) => {
+                Self::System
+            }
+            _ => Self::User,
+        }
+    }
+
+    /// Kind of failure a generator error represents
+    pub fn from_generator(e: &niwa_generator::Error) -> Self {
+        use niwa_generator::Error;
+        match e {
+            Error::Core(e) => Self::from_core(e),
+            Error::Io(_) => Self::System,
+            Error::EmptyExtraction
+            | Error::LowConfidence { .. }
+            | Error::TooFewFragments { .. }
+            | Error::SecretDetected { .. }
+            | Error::DirectoryNotFound(_) => Self::User,
+            _ => Self::Llm,
+        }
+    }
+}
+
+/// Failure kind recorded by the error constructors below (0 = none)
+static RECORDED: AtomicI32 = AtomicI32::new(0);
+
+fn record(code: ExitCode) {
+    RECORDED.store(code.code(), Ordering::SeqCst);
+}
+
+/// Forget the failure kind recorded for a previous command
+pub fn reset() {
+    RECORDED.store(0, Ordering::SeqCst);
+}
+
+fn recorded() -> Option<ExitCode> {
+    match RECORDED.load(Ordering::SeqCst) {
+        3 => Some(ExitCode::NotFound),
+        4 => Some(ExitCode::Llm),
+        _ => None,
+    }
+}
+
+/// A "not found" error (exit code 3)
+pub fn not_found(message: impl Into<String>) -> CliError {
+    record(ExitCode::NotFound);
+    CliError::user(message.into())
+}
+
+/// An LLM backend error (exit code 4)
+pub fn llm_error(message: impl Into<String>) -> CliError {
+    record(ExitCode::Llm);
+    CliError::system(message.into())
+}
+
+/// Wrap a generator error with `context`, keeping its kind
+pub fn generator_error(context: &str, e: niwa_generator::Error) -> CliError {
+    let message = format!("{}: {}", context, e);
+    match ExitCode::from_generator(&e) {
+        ExitCode::NotFound => not_found(message),
+        ExitCode::Llm => llm_error(message),
+        ExitCode::User => CliError::user(message),
+        _ => CliError::system(message),
+    }
+}
+
+/// Exit code for a finished command, given the router's exit code
+pub fn resolve(router_code: i32) -> i32 {
+    classify(router_code, recorded()).code()
+}
+
+/// The router exits 1 for user errors and with another non-zero code for
+/// system errors; a recorded kind refines either.
+fn classify(router_code: i32, recorded: Option<ExitCode>) -> ExitCode {
+    match (router_code, recorded) {
+        (0, _) => ExitCode::Success,
+        (_, Some(kind)) => kind,
+        (1, None) => ExitCode::User,
+        (_, None) => ExitCode::System,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(0, None), ExitCode::Success);
+        assert_eq!(classify(0, Some(ExitCode::Llm)), ExitCode::Success);
+        assert_eq!(classify(1, None), ExitCode::User);
+        assert_eq!(classify(101, None), ExitCode::System);
+        assert_eq!(classify(1, Some(ExitCode::NotFound)), ExitCode::NotFound);
+        assert_eq!(classify(101, Some(ExitCode::Llm)), ExitCode::Llm);
+    }
+
+    #[test]
+    fn test_reset() {
+        let _ = not_found("Expertise not found: rust-expert");
+        assert_eq!(recorded(), Some(ExitCode::NotFound));
+        reset();
+        assert_eq!(recorded(), None);
+        assert_eq!(resolve(1), ExitCode::User.code());
+    }
+
+    #[test]
+    fn test_from_core() {
+        let not_found = niwa_core::Error::NotFound {
+            id: "rust-expert".to_string(),
+            scope: "personal".to_string(),
+        };
+        assert_eq!(ExitCode::from_core(&not_found), ExitCode::NotFound);
+        assert_eq!(
+            ExitCode::from_core(&niwa_core::Error::ReminderNotFound(3)),
+            ExitCode::NotFound
+        );
+        assert_eq!(
+            ExitCode::from_core(&niwa_core::Error::InvalidScope("team".to_string())),
+            ExitCode::User
+        );
+        assert_eq!(
+            ExitCode::from_core(&niwa_core::Error::Migration("bad".to_string())),
+            ExitCode::System
+        );
+    }
+
+    #[test]
+    fn test_from_generator() {
+        assert_eq!(
+            ExitCode::from_generator(&niwa_generator::Error::Llm("timeout".to_string())),
+            ExitCode::Llm
+        );
+        assert_eq!(
+            ExitCode::from_generator(&niwa_generator::Error::Core(
+                niwa_core::Error::ReminderNotFound(1)
+            )),
+            ExitCode::NotFound
+        );
+        assert_eq!(
+            ExitCode::from_generator(&niwa_generator::Error::Io(std::io::Error::other("disk"))),
+            ExitCode::System
+        );
+        assert_eq!(
+            ExitCode::from_generator(&niwa_generator::Error::EmptyExtraction),
+            ExitCode::User
+        );
+    }
+}
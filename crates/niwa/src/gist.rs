@@ -0,0 +1,126 @@
+//! Minimal GitHub Gist API client used by `niwa expertise publish`
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use serde_json::json;
+
+const GISTS_API_URL: &str = "https://api.github.com/gists";
+
+/// GitHub Gist client authenticated with a personal access token
+pub struct GistClient {
+    client: reqwest::Client,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct GistResponse {
+    html_url: String,
+}
+
+impl GistClient {
+    /// Create a client using the `GITHUB_TOKEN` environment variable
+    pub fn from_env() -> anyhow::Result<Self> {
+        let token = std::env::var("GITHUB_TOKEN")
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .context("GITHUB_TOKEN is not set (a token with the 'gist' scope is required)")?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            token,
+        })
+    }
+
+    /// Create a Gist with a single file and return its URL
+    pub async fn create(
+        &self,
+        filename: &str,
+        content: &str,
+        description: &str,
+        public: bool,
+    ) -> anyhow::Result<String> {
+        let body = json!({
+            "description": description,
+            "public": public,
+            "files": { filename: { "content": content } },
+        });
+
+        let response = self
+            .request(reqwest::Method::POST, GISTS_API_URL)
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to reach the GitHub API")?;
+
+        if !response.status().is_success() {
+            bail!(
+                "GitHub API returned {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+
+        let gist: GistResponse = response
+            .json()
+            .await
+            .context("Unexpected response from the GitHub API")?;
+        Ok(gist.html_url)
+    }
+
+    /// Delete a Gist by ID
+    ///
+    /// A Gist that no longer exists is treated as already deleted.
+    pub async fn delete(&self, gist_id: &str) -> anyhow::Result<()> {
+        let url = format!("{}/{}", GISTS_API_URL, gist_id);
+        let response = self
+            .request(reqwest::Method::DELETE, &url)
+            .send()
+            .await
+            .context("Failed to reach the GitHub API")?;
+
+        let status = response.status();
+        if !status.is_success() && status != reqwest::StatusCode::NOT_FOUND {
+            bail!(
+                "GitHub API returned {}: {}",
+                status,
+                response.text().await.unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, "niwa-cli")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+}
+
+/// Extract the Gist ID from its HTML URL (`https://gist.github.com/<user>/<id>`)
+pub fn gist_id_from_url(url: &str) -> Option<&str> {
+    url.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gist_id_from_url() {
+        assert_eq!(
+            gist_id_from_url("https://gist.github.com/octocat/aa5a315d61ae9438b18d"),
+            Some("aa5a315d61ae9438b18d")
+        );
+        assert_eq!(
+            gist_id_from_url("https://gist.github.com/aa5a315d61ae9438b18d/"),
+            Some("aa5a315d61ae9438b18d")
+        );
+        assert_eq!(gist_id_from_url("https://example.com/not-a-gist"), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! Bundle commands - move expertises and relations between databases
+
+use crate::state::AppState;
+use clap::{Parser, Subcommand};
+use niwa_core::{bundle, Bundle, Scope};
+use sen::{Args, CliError, CliResult, State};
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+
+/// Export or restore a bundle of expertises with their relations
+///
+/// Usage:
+///   niwa bundle export                        # print to stdout
+///   niwa bundle export -o backup.json --scope company
+///   niwa bundle restore backup.json
+///   niwa bundle restore backup.jsonl          # from 'niwa export --format jsonl'
+#[derive(Parser, Debug)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: Option<BundleCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BundleCommand {
+    /// Export expertises and relations as a JSON bundle
+    Export {
+        /// Write the bundle to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only export this scope (default: all scopes)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Restore a bundle (older bundle versions are migrated automatically)
+    ///
+    /// JSON Lines bundles are detected and restored line by line.
+    Restore {
+        /// Bundle file to restore (JSON or JSON Lines)
+        file: PathBuf,
+    },
+}
+
+#[sen::handler]
+pub async fn bundle(state: State<AppState>, Args(args): Args<BundleArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    match args.command {
+        Some(BundleCommand::Export { output, scope }) => {
+            handle_export(&app, output.as_deref(), scope).await
+        }
+        Some(BundleCommand::Restore { file }) => handle_restore(&app, &file).await,
+        None => Err(CliError::user(
+            "No subcommand specified. Use 'bundle --help' to see available commands.",
+        )),
+    }
+}
+
+async fn handle_export(
+    app: &AppState,
+    output: Option<&std::path::Path>,
+    scope: Option<Scope>,
+) -> CliResult<String> {
+    let bundle = bundle::export(&app.db, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to export bundle: {}", e)))?;
+    let json = bundle
+        .to_json()
+        .map_err(|e| CliError::system(format!("Failed to serialize bundle: {}", e)))?;
+
+    let Some(path) = output else {
+        return Ok(json);
+    };
+
+    std::fs::write(path, json)
+        .map_err(|e| CliError::system(format!("Failed to write bundle: {}", e)))?;
+
+    Ok(format!(
+        "✓ Exported {} expertises and {} relations to {}",
+        bundle.expertises.len(),
+        bundle.relations.len(),
+        path.display()
+    ))
+}
+
+async fn handle_restore(app: &AppState, file: &std::path::Path) -> CliResult<String> {
+    let read_error = |e: std::io::Error| CliError::user(format!("Failed to read bundle: {}", e));
+
+    let mut reader = BufReader::new(std::fs::File::open(file).map_err(read_error)?);
+    let mut content = String::new();
+    reader.read_line(&mut content).map_err(read_error)?;
+
+    let summary = if bundle::is_jsonl(content.trim()) {
+        // Put the consumed first line back in front of the rest
+        bundle::restore_jsonl(&app.db, content.as_bytes().chain(reader)).await
+    } else {
+        reader.read_to_string(&mut content).map_err(read_error)?;
+        let bundle = Bundle::from_json(&content)
+            .map_err(|e| CliError::user(format!("Invalid bundle: {}", e)))?;
+        bundle::restore(&app.db, bundle).await
+    }
+    .map_err(|e| CliError::system(format!("Failed to restore bundle: {}", e)))?;
+
+    Ok(format!(
+        "✓ Restored {} new and {} updated expertises, {} relations",
+        summary.created, summary.updated, summary.relations
+    ))
+}
@@ -0,0 +1,304 @@
+//! Compare command - decide whether two expertises should be merged or linked
+
+use crate::exit_code::not_found;
+use crate::render::truncate_chars;
+use crate::state::AppState;
+use clap::Parser;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::similarity::{text_similarity, DUPLICATE_FRAGMENT_SIMILARITY};
+use niwa_core::{Expertise, Scope, SimilarityMethod, StorageOperations};
+use sen::{Args, CliError, CliResult, State};
+
+/// Share of fragments that must be covered for one expertise to contain another
+const COVERAGE_THRESHOLD: f64 = 0.7;
+
+/// Compare two expertises: tags, fragments and existing relations
+///
+/// Usage:
+///   niwa compare rust-async tokio-patterns
+///   niwa compare rust-async tokio-patterns --scope company
+#[derive(Parser, Debug)]
+pub struct CompareArgs {
+    /// First expertise ID
+    pub id_a: String,
+
+    /// Second expertise ID
+    pub id_b: String,
+
+    /// Scope (if not specified, searches all scopes)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+}
+
+/// Fragments of A matched to fragments of B
+#[derive(Debug, Default)]
+struct FragmentMatch {
+    /// (fragment of A, fragment of B, similarity)
+    shared: Vec<(String, String, f64)>,
+    only_a: Vec<String>,
+    only_b: Vec<String>,
+}
+
+#[sen::handler]
+pub async fn compare(state: State<AppState>, Args(args): Args<CompareArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    if args.id_a == args.id_b {
+        return Err(CliError::user("Cannot compare an expertise with itself"));
+    }
+
+    let a = find_expertise(&app, &args.id_a, args.scope).await?;
+    let b = find_expertise(&app, &args.id_b, args.scope).await?;
+
+    let diff = a.diff(&b);
+    let tag_overlap = SimilarityMethod::Tags.score(&a, &b);
+    let fragments = match_fragments(&diff.fragments_removed, &diff.fragments_added);
+    let identical = a.inner.content.len() - diff.fragments_removed.len();
+
+    let mut output = format!(
+        "\nCompare: {} ({}) ↔ {} ({})\n",
+        a.id(),
+        a.metadata.scope,
+        b.id(),
+        b.metadata.scope
+    );
+
+    // Tags
+    output.push_str(&format!("\nTags: {:.0}% overlap\n", tag_overlap * 100.0));
+    for (label, tags) in [
+        ("shared", &diff.shared_tags),
+        (a.id(), &diff.tags_removed),
+        (b.id(), &diff.tags_added),
+    ] {
+        if !tags.is_empty() {
+            output.push_str(&format!("  {}: {}\n", label, tags.join(", ")));
+        }
+    }
+
+    // Fragments
+    let shared_count = identical + fragments.shared.len();
+    output.push_str(&format!(
+        "\nFragments: {} shared ({} identical, {} similar), {} only in {}, {} only in {}\n",
+        shared_count,
+        identical,
+        fragments.shared.len(),
+        fragments.only_a.len(),
+        a.id(),
+        fragments.only_b.len(),
+        b.id()
+    ));
+
+    if !fragments.shared.is_empty() {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new(a.id()).fg(Color::Green),
+                Cell::new(b.id()).fg(Color::Green),
+                Cell::new("Similarity").fg(Color::Green),
+            ]);
+        for (fragment_a, fragment_b, similarity) in &fragments.shared {
+            table.add_row(vec![
+                preview(fragment_a),
+                preview(fragment_b),
+                format!("{:.2}", similarity),
+            ]);
+        }
+        output.push_str(&format!("{}\n", table));
+    }
+
+    for (id, unique) in [(a.id(), &fragments.only_a), (b.id(), &fragments.only_b)] {
+        if !unique.is_empty() {
+            output.push_str(&format!("\nOnly in {}:\n", id));
+            for fragment in unique {
+                output.push_str(&format!("  - {}\n", preview(fragment)));
+            }
+        }
+    }
+
+    // Relations
+    let relations: Vec<_> = app
+        .db
+        .graph()
+        .get_all_relations(a.id())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?
+        .into_iter()
+        .filter(|r| r.from_id == b.id() || r.to_id == b.id())
+        .collect();
+
+    output.push_str("\nRelation: ");
+    if relations.is_empty() {
+        output.push_str("none\n");
+    } else {
+        let edges: Vec<String> = relations
+            .iter()
+            .map(|r| format!("{} -[{}]-> {}", r.from_id, r.relation_type, r.to_id))
+            .collect();
+        output.push_str(&format!("{}\n", edges.join(", ")));
+    }
+
+    let coverage = |count: usize| {
+        if count == 0 {
+            0.0
+        } else {
+            shared_count as f64 / count as f64
+        }
+    };
+    let suggestion = suggest(
+        a.id(),
+        b.id(),
+        tag_overlap,
+        coverage(a.inner.content.len()),
+        coverage(b.inner.content.len()),
+        !relations.is_empty(),
+    );
+    output.push_str(&format!("\nSuggestion: {}", suggestion));
+
+    Ok(output)
+}
+
+/// Find an expertise in `scope`, or the first scope that has it
+async fn find_expertise(app: &AppState, id: &str, scope: Option<Scope>) -> CliResult<Expertise> {
+    let scopes = match scope {
+        Some(scope) => vec![scope],
+        None => vec![Scope::Personal, Scope::Company, Scope::Project],
+    };
+
+    for scope in scopes {
+        if let Some(expertise) = app
+            .db
+            .storage()
+            .get(id, scope)
+            .await
+            .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+        {
+            return Ok(expertise);
+        }
+    }
+
+    Err(not_found(format!("Expertise not found: {}", id)))
+}
+
+/// Pair each fragment only in A with its most similar fragment only in B
+///
+/// Pairs at or above [`DUPLICATE_FRAGMENT_SIMILARITY`] count as shared; each
+/// fragment of B is used at most once.
+fn match_fragments(only_a: &[String], only_b: &[String]) -> FragmentMatch {
+    let mut result = FragmentMatch::default();
+    let mut unmatched_b: Vec<&String> = only_b.iter().collect();
+
+    for fragment_a in only_a {
+        let best = unmatched_b
+            .iter()
+            .enumerate()
+            .map(|(i, fragment_b)| (i, text_similarity(fragment_a, fragment_b)))
+            .filter(|(_, similarity)| *similarity >= DUPLICATE_FRAGMENT_SIMILARITY)
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+
+        match best {
+            Some((i, similarity)) => {
+                let fragment_b = unmatched_b.remove(i);
+                result
+                    .shared
+                    .push((fragment_a.clone(), fragment_b.clone(), similarity));
+            }
+            None => result.only_a.push(fragment_a.clone()),
+        }
+    }
+
+    result.only_b = unmatched_b.into_iter().cloned().collect();
+    result
+}
+
+/// Recommend merging, linking or leaving two expertises separate
+///
+/// `coverage_a` is the share of A's fragments that also appear in B.
+fn suggest(
+    id_a: &str,
+    id_b: &str,
+    tag_overlap: f64,
+    coverage_a: f64,
+    coverage_b: f64,
+    linked: bool,
+) -> String {
+    let link = |from: &str, to: &str, relation_type: &str| {
+        if linked {
+            format!("already linked ({} {} {})", from, relation_type, to)
+        } else {
+            format!("niwa link {} --to {} --type {}", from, to, relation_type)
+        }
+    };
+
+    if coverage_a >= COVERAGE_THRESHOLD && coverage_b >= COVERAGE_THRESHOLD {
+        "nearly the same knowledge — consider merging them".to_string()
+    } else if coverage_a >= COVERAGE_THRESHOLD {
+        // B contains most of A and adds more
+        link(id_b, id_a, "extends")
+    } else if coverage_b >= COVERAGE_THRESHOLD {
+        link(id_a, id_b, "extends")
+    } else if tag_overlap >= SimilarityMethod::Tags.default_threshold() || coverage_a > 0.0 {
+        link(id_a, id_b, "uses")
+    } else if linked {
+        "little in common, but already linked — check the relation".to_string()
+    } else {
+        "little in common — leave them separate".to_string()
+    }
+}
+
+/// First line of a fragment, shortened for display
+fn preview(fragment: &str) -> String {
+    const MAX_CHARS: usize = 80;
+
+    let line = fragment
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or("");
+    truncate_chars(line.trim(), MAX_CHARS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_match_fragments() {
+        let only_a = strings(&["use tokio spawn for tasks", "avoid unwrap"]);
+        let only_b = strings(&["use tokio spawn for background tasks", "prefer channels"]);
+
+        let result = match_fragments(&only_a, &only_b);
+        assert_eq!(result.shared.len(), 1);
+        assert_eq!(result.shared[0].1, "use tokio spawn for background tasks");
+        assert_eq!(result.only_a, strings(&["avoid unwrap"]));
+        assert_eq!(result.only_b, strings(&["prefer channels"]));
+    }
+
+    #[test]
+    fn test_suggest() {
+        assert!(suggest("a", "b", 0.5, 0.9, 0.8, false).contains("merging"));
+        assert_eq!(
+            suggest("a", "b", 0.5, 0.9, 0.3, false),
+            "niwa link b --to a --type extends"
+        );
+        assert_eq!(
+            suggest("a", "b", 0.4, 0.1, 0.1, false),
+            "niwa link a --to b --type uses"
+        );
+        assert!(suggest("a", "b", 0.4, 0.1, 0.1, true).starts_with("already linked"));
+        assert!(suggest("a", "b", 0.0, 0.0, 0.0, false).contains("separate"));
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("\n  Use tokio  \nmore"), "Use tokio");
+        assert_eq!(
+            preview(&"あ".repeat(100)),
+            format!("{}...", "あ".repeat(80))
+        );
+    }
+}
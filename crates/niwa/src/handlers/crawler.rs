@@ -1,13 +1,23 @@
 //! Crawler commands - automatic expertise extraction from session logs
 
+use crate::email::{Email, EmailProvider, Mailer};
+use crate::events::{CrawlEvent, EventEmitter, EventsOutput};
+use crate::exit_code::generator_error;
+use crate::handlers::gen::{parse_positive, with_context_file, with_on_secret, OnSecret};
+use crate::render::{color_enabled, to_csv, to_json, to_markdown, GenResult, OutputFormat, Render};
 use crate::state::AppState;
-use clap::{Parser, Subcommand};
-use comfy_table::{presets, Table};
-use niwa_core::{RelationType, Scope, StorageOperations};
+use clap::{Parser, Subcommand, ValueEnum};
+use comfy_table::{presets, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Expertise, ExpertiseStatus, ProcessedSession, Scope, StorageOperations};
+use niwa_generator::crawler::{calculate_file_hash, DEFAULT_EXTENSIONS};
+use niwa_generator::{Crawler, EventHandler, FileOutcome, ScanOptions, ScanReport};
 use sen::{Args, CliError, CliResult, State};
-use sha2::{Digest, Sha256};
+use serde::Serialize;
 use std::path::{Path, PathBuf};
-use tracing::{debug, info, warn};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
 
 /// Automatically extract expertise from session logs
 #[derive(Parser, Debug)]
@@ -28,9 +38,10 @@ pub enum CrawlerCommand {
         #[arg(short = 't', long, conflicts_with = "directory")]
         target: Option<String>,
 
-        /// Scope for generated expertises (default: personal)
-        #[arg(short, long, default_value = "personal")]
-        scope: Scope,
+        /// Scope for generated expertises (default: NIWA_DEFAULT_SCOPE, then
+        /// the nearest .niwarc, then `default_scope` in config.toml, then personal)
+        #[arg(short, long)]
+        scope: Option<Scope>,
 
         /// Dry run - show what would be processed without actually processing
         #[arg(short = 'n', long)]
@@ -40,18 +51,138 @@ pub enum CrawlerCommand {
         #[arg(short, long)]
         limit: Option<usize>,
 
-        /// Only process files modified in the last N days
-        #[arg(long)]
-        recent_days: Option<u64>,
+        /// Only process files modified in the last N days (fractions allowed, e.g. 0.5)
+        #[arg(long, value_name = "DAYS", value_parser = parse_recent_days)]
+        recent_days: Option<Duration>,
 
-        /// Automatically link new expertises to existing ones based on shared tags
+        /// Only process files modified in the last N hours
+        #[arg(
+            long,
+            value_name = "HOURS",
+            value_parser = parse_recent_hours,
+            conflicts_with = "recent_days"
+        )]
+        recent_hours: Option<Duration>,
+
+        /// Automatically link new expertises to existing ones
         #[arg(long)]
         auto_link: bool,
 
+        /// How --auto-link finds links (llm: ask the LLM; tags: link expertises
+        /// sharing tags, no API calls)
+        #[arg(long, value_enum, default_value = "llm", requires = "auto_link")]
+        link_strategy: LinkStrategy,
+
+        /// Minimum number of shared tags for --link-strategy tags
+        #[arg(long, value_name = "K", default_value_t = 2, requires = "auto_link")]
+        min_shared_tags: usize,
+
+        /// Let --auto-link link to deprecated expertises
+        #[arg(long, requires = "auto_link")]
+        include_deprecated: bool,
+
+        /// Keep --auto-link from linking to draft (unreviewed) expertises
+        #[arg(long, requires = "auto_link")]
+        exclude_drafts: bool,
+
+        /// Only ask the LLM about the K expertises sharing the most tags with
+        /// each new one (default: every expertise in the scope)
+        #[arg(long, value_name = "K", value_parser = parse_positive, requires = "auto_link")]
+        link_candidates: Option<usize>,
+
         /// Automatically detect scope from file path using scope mappings
-        /// (overrides --scope when a matching pattern is found)
+        /// (overrides --scope when a matching pattern is found; implied by
+        /// `crawler.auto_scope = true` in config.toml)
         #[arg(long)]
         auto_scope: bool,
+
+        /// Disable auto-scope for this run even if config.toml enables it
+        #[arg(long, conflicts_with = "auto_scope")]
+        no_auto_scope: bool,
+
+        /// Resume an interrupted run from its checkpoint instead of rescanning
+        #[arg(long)]
+        resume: bool,
+
+        /// Skip files whose transcript nearly matches an already processed
+        /// one (e.g. a resumed session), within MAX_DISTANCE differing
+        /// fingerprint bits (default: 3)
+        #[arg(
+            long,
+            value_name = "MAX_DISTANCE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "3"
+        )]
+        dedup_near: Option<u32>,
+
+        /// Process up to N files at the same time (near-duplicates of files
+        /// still in progress are not detected)
+        #[arg(long, value_name = "N", default_value_t = 1, value_parser = parse_positive)]
+        concurrency: usize,
+
+        /// Skip files larger than this many bytes (default: `crawler.max_size` in config.toml)
+        #[arg(long, value_name = "BYTES")]
+        max_size: Option<u64>,
+
+        /// Skip files smaller than this many bytes (default: `crawler.min_size` in config.toml)
+        #[arg(long, value_name = "BYTES")]
+        min_size: Option<u64>,
+
+        /// Only process files whose name matches this glob (repeatable, e.g. "*rust*")
+        #[arg(long = "include", value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Scan files with this extension (repeatable, e.g. "chat"; default:
+        /// `crawler.extensions` in config.toml, then log, md, txt, jsonl, toml)
+        #[arg(long = "ext", value_name = "EXT")]
+        ext: Vec<String>,
+
+        /// File with a system prompt replacing the built-in extraction prompt
+        #[arg(long, value_name = "FILE")]
+        prompt_file: Option<PathBuf>,
+
+        /// File with shared project context (glossary, conventions) added to every extraction
+        #[arg(long, value_name = "FILE")]
+        context_file: Option<PathBuf>,
+
+        /// Store extractions with no description and no fragments instead of skipping the file
+        #[arg(long)]
+        keep_empty: bool,
+
+        /// What to do when an extraction contains possible secrets or personal data
+        #[arg(long, value_enum, default_value_t = OnSecret::Redact)]
+        on_secret: OnSecret,
+
+        /// Emit a machine-readable event as each file is processed
+        #[arg(long)]
+        emit_events: bool,
+
+        /// Event encoding (JSON Lines)
+        #[arg(long, default_value = "json", value_parser = ["json"], requires = "emit_events")]
+        events_format: String,
+
+        /// Where to write events: "stdout", a file, a named pipe or a Unix socket
+        #[arg(
+            long,
+            value_name = "stdout|PATH",
+            default_value = "stdout",
+            requires = "emit_events"
+        )]
+        events_output: EventsOutput,
+
+        /// Email a crawl report to this address when the run finishes
+        /// (configure `[smtp]` or `[sendgrid]` in config.toml)
+        #[arg(long, value_name = "ADDRESS")]
+        email_report: Option<String>,
+
+        /// Only send the report when at least one file failed
+        #[arg(long, requires = "email_report")]
+        email_on_error_only: bool,
+
+        /// How to send the report (smtp, or sendgrid for the SendGrid HTTP API)
+        #[arg(long, value_enum, default_value = "smtp", requires = "email_report")]
+        email_provider: EmailProvider,
     },
     /// Initialize crawler with preset paths (claude-code, cursor)
     Init {
@@ -78,6 +209,41 @@ pub enum CrawlerCommand {
         #[command(subcommand)]
         command: ScopeCommand,
     },
+    /// Prune processed-session records so files can be re-processed
+    CleanProcessed {
+        /// Delete records processed more than DAYS days ago (e.g. "90d" or "90")
+        #[arg(long = "older-than", value_name = "DAYS", value_parser = parse_age_secs)]
+        older_than_secs: Option<i64>,
+
+        /// Delete records whose session file no longer exists
+        #[arg(long)]
+        missing_files: bool,
+
+        /// Delete records whose file changed since processing (re-process on next run)
+        #[arg(long)]
+        reprocess_changed: bool,
+
+        /// Only prune records for session files under this directory
+        #[arg(long, value_name = "DIR")]
+        path: Option<PathBuf>,
+
+        /// Show what would be deleted without deleting
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// List the session files the crawler has processed
+    ///
+    /// Shows when each file was ingested and whether the expertise derived
+    /// from it still exists.
+    History {
+        /// Only list records whose expertise was deleted since
+        #[arg(long)]
+        missing: bool,
+
+        /// Output format (default: text, or json with --agent-mode)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -86,7 +252,7 @@ pub enum ScopeCommand {
     Add {
         /// Pattern to match (e.g., "projects/company-*", "work/*")
         pattern: String,
-        /// Scope to assign (personal, company, project)
+        /// Scope to assign (personal/me, company/org, project/proj)
         #[arg(short, long)]
         scope: Scope,
         /// Priority (higher = checked first, default: 10)
@@ -169,36 +335,155 @@ pub async fn crawler(
             dry_run,
             limit,
             recent_days,
+            recent_hours,
             auto_link,
+            link_strategy,
+            min_shared_tags,
+            include_deprecated,
+            exclude_drafts,
+            link_candidates,
             auto_scope,
+            no_auto_scope,
+            resume,
+            dedup_near,
+            concurrency,
+            max_size,
+            min_size,
+            include,
+            ext,
+            prompt_file,
+            context_file,
+            keep_empty,
+            on_secret,
+            emit_events,
+            events_format: _,
+            events_output,
+            email_report,
+            email_on_error_only,
+            email_provider,
         }) => {
-            // Scan mode
-            if let Some(dir) = directory {
-                // Explicit directory specified
-                handle_scan(
-                    &app, &dir, scope, dry_run, limit, recent_days, auto_link, auto_scope,
-                )
-                .await
-            } else if let Some(target_name) = target {
-                // Target registered path by name
-                handle_scan_target(
-                    &app,
-                    &target_name,
-                    scope,
+            // Check the mail settings before spending time on the crawl
+            let mailer = match &email_report {
+                Some(_) => Some(
+                    Mailer::from_config(&app.config, email_provider)
+                        .map_err(|e| CliError::user(format!("Cannot send email report: {}", e)))?,
+                ),
+                None => None,
+            };
+
+            let app = match &prompt_file {
+                Some(path) => app.with_prompt_file(path).await.map_err(|e| {
+                    CliError::user(format!(
+                        "Failed to load prompt file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                None => app.clone(),
+            };
+            let app = with_context_file(app, context_file.as_deref()).await?;
+            let app = if keep_empty {
+                app.with_keep_empty().await.map_err(|e| {
+                    CliError::system(format!("Failed to configure generator: {}", e))
+                })?
+            } else {
+                app
+            };
+            let app = with_on_secret(app, on_secret).await?;
+
+            let events = if emit_events {
+                let emitter = EventEmitter::open(events_output)
+                    .map_err(|e| CliError::user(format!("Failed to open events output: {}", e)))?;
+                Some(Arc::new(Mutex::new(emitter)))
+            } else {
+                None
+            };
+            let events_on_stdout = events
+                .as_ref()
+                .is_some_and(|e| e.lock().is_ok_and(|e| e.is_stdout()));
+            let on_event = events.map(|events| -> EventHandler {
+                Arc::new(move |event| {
+                    if let Ok(mut emitter) = events.lock() {
+                        emitter.emit(CrawlEvent::from(event));
+                    }
+                })
+            });
+
+            let auto_scope = auto_scope || (app.config.crawler.auto_scope && !no_auto_scope);
+            if auto_scope && scope.is_some() {
+                warn!("Note: --scope is overridden by --auto-scope when a mapping matches");
+            }
+
+            let run = CrawlRun {
+                options: ScanOptions {
+                    scope: app.config.scope_or_default(scope).map_err(CliError::user)?,
                     dry_run,
                     limit,
-                    recent_days,
+                    modified_within: recent_days.or(recent_hours),
                     auto_link,
+                    link_strategy: link_strategy.into(),
+                    min_shared_tags,
+                    link_excluded: link_excluded_statuses(include_deprecated, exclude_drafts),
+                    link_candidates,
                     auto_scope,
-                )
-                .await
+                    id_prefix: app.config.project.id_prefix.clone(),
+                    resume,
+                    dedup_near,
+                    max_size: max_size.or(app.config.crawler.max_size),
+                    min_size: min_size.or(app.config.crawler.min_size),
+                    include,
+                    extensions: scan_extensions(ext, app.config.crawler.extensions.as_deref()),
+                    concurrency,
+                    cancelled: install_cancel_handler(),
+                    on_event,
+                },
+                stats: CrawlStats::default(),
+            };
+
+            let source = match (&directory, &target) {
+                (Some(dir), _) => dir.display().to_string(),
+                (None, Some(target)) => target.clone(),
+                (None, None) => "registered paths".to_string(),
+            };
+
+            // Scan mode
+            let mut output = if let Some(dir) = directory {
+                // Explicit directory specified
+                handle_scan(&app, &dir, &run).await
+            } else if let Some(target_name) = target {
+                // Target registered path by name
+                handle_scan_target(&app, &target_name, &run).await
             } else {
                 // Scan all registered paths
-                handle_scan_registered(
-                    &app, scope, dry_run, limit, recent_days, auto_link, auto_scope,
-                )
-                .await
+                handle_scan_registered(&app, &run).await
+            }?;
+
+            if let (Some(to), Some(mailer)) = (&email_report, &mailer) {
+                let failed = run.stats.failed.load(Ordering::SeqCst);
+                if !email_on_error_only || failed > 0 {
+                    let created = run.stats.created.load(Ordering::SeqCst);
+                    let email = Email::crawl_report(to, created, &source, &output);
+                    match mailer.send(&email).await {
+                        Ok(()) => output.push_str(&format!("\n\n✉ Report sent to {}", to)),
+                        Err(e) => {
+                            warn!("Failed to send email report: {:#}", e);
+                            output.push_str(&format!("\n\n⚠ Email report not sent: {:#}", e));
+                        }
+                    }
+                }
+            }
+
+            if app.agent_mode {
+                let interrupted = run.options.cancelled.load(Ordering::SeqCst);
+                output = to_json(&run.stats.result(interrupted));
             }
+
+            // Keep stdout a pure JSON Lines stream when events are written there
+            if events_on_stdout {
+                eprintln!("{}", output);
+                return Ok(String::new());
+            }
+            Ok(output)
         }
         Some(CrawlerCommand::Init { preset }) => handle_init(&app, &preset).await,
         Some(CrawlerCommand::Add { path, name }) => {
@@ -207,6 +492,27 @@ pub async fn crawler(
         Some(CrawlerCommand::List) => handle_list(&app).await,
         Some(CrawlerCommand::Remove { id }) => handle_remove(&app, id).await,
         Some(CrawlerCommand::Scope { command }) => handle_scope(&app, command).await,
+        Some(CrawlerCommand::CleanProcessed {
+            older_than_secs,
+            missing_files,
+            reprocess_changed,
+            path,
+            dry_run,
+        }) => {
+            handle_clean_processed(
+                &app,
+                older_than_secs,
+                missing_files,
+                reprocess_changed,
+                path.as_deref(),
+                dry_run,
+            )
+            .await
+        }
+        Some(CrawlerCommand::History { missing, format }) => {
+            let format = OutputFormat::resolve(format, app.agent_mode);
+            handle_history(&app, missing, format).await
+        }
         None => {
             // Show help when no subcommand is provided
             Err(CliError::user(
@@ -350,12 +656,7 @@ async fn handle_remove(app: &AppState, id: i64) -> CliResult<String> {
 async fn handle_scan_target(
     app: &AppState,
     target_name: &str,
-    default_scope: Scope,
-    dry_run: bool,
-    limit: Option<usize>,
-    recent_days: Option<u64>,
-    auto_link: bool,
-    auto_scope: bool,
+    run: &CrawlRun,
 ) -> CliResult<String> {
     // Get path for the specified target
     let row: Option<(String,)> = sqlx::query_as(
@@ -389,28 +690,10 @@ async fn handle_scan_target(
         )));
     }
 
-    handle_scan(
-        app,
-        &path,
-        default_scope,
-        dry_run,
-        limit,
-        recent_days,
-        auto_link,
-        auto_scope,
-    )
-    .await
+    handle_scan(app, &path, run).await
 }
 
-async fn handle_scan_registered(
-    app: &AppState,
-    default_scope: Scope,
-    dry_run: bool,
-    limit: Option<usize>,
-    recent_days: Option<u64>,
-    auto_link: bool,
-    auto_scope: bool,
-) -> CliResult<String> {
+async fn handle_scan_registered(app: &AppState, run: &CrawlRun) -> CliResult<String> {
     // Get all enabled paths
     let rows: Vec<(String,)> = sqlx::query_as(
         r#"
@@ -430,6 +713,11 @@ async fn handle_scan_registered(
     let mut all_results = Vec::new();
 
     for (path_str,) in rows {
+        if run.options.cancelled.load(Ordering::SeqCst) {
+            all_results.push(format!("\n{}: skipped (interrupted)", path_str));
+            continue;
+        }
+
         let path = PathBuf::from(&path_str);
 
         if !path.exists() {
@@ -437,22 +725,12 @@ async fn handle_scan_registered(
             continue;
         }
 
-        match handle_scan(
-            app,
-            &path,
-            default_scope,
-            dry_run,
-            limit,
-            recent_days,
-            auto_link,
-            auto_scope,
-        )
-        .await
-        {
+        match handle_scan(app, &path, run).await {
             Ok(result) => {
                 all_results.push(format!("\n{}: {}\n{}", path.display(), "✓", result));
             }
             Err(e) => {
+                run.stats.failed.fetch_add(1, Ordering::SeqCst);
                 warn!("Failed to scan {}: {}", path.display(), e);
                 all_results.push(format!("\n{}: ✗ {}", path.display(), e));
             }
@@ -469,489 +747,556 @@ async fn handle_scan_registered(
     Ok(output)
 }
 
-async fn handle_scan(
-    app: &AppState,
-    directory: &Path,
-    default_scope: Scope,
-    dry_run: bool,
-    limit: Option<usize>,
-    recent_days: Option<u64>,
-    auto_link: bool,
-    auto_scope: bool,
-) -> CliResult<String> {
-    // Verify directory exists
-    if !directory.exists() {
-        return Err(CliError::user(format!(
-            "Directory not found: {}",
-            directory.display()
-        )));
-    }
-
-    if !directory.is_dir() {
-        return Err(CliError::user(format!(
-            "Not a directory: {}",
-            directory.display()
-        )));
-    }
-
-    info!("Scanning directory: {}", directory.display());
-
-    // Scan for session log files
-    let session_files = scan_session_files(directory)?;
-    info!("Found {} potential session files", session_files.len());
-
-    if session_files.is_empty() {
-        return Ok("No session files found.".to_string());
-    }
-
-    // Filter by recent_days if specified
-    let filtered_files = if let Some(days) = recent_days {
-        let cutoff_time =
-            std::time::SystemTime::now() - std::time::Duration::from_secs(days * 24 * 60 * 60);
-
-        session_files
-            .into_iter()
-            .filter(|path| {
-                if let Ok(metadata) = std::fs::metadata(path) {
-                    if let Ok(modified) = metadata.modified() {
-                        return modified >= cutoff_time;
-                    }
-                }
-                false
-            })
-            .collect()
-    } else {
-        session_files
-    };
-
-    info!("After recent_days filter: {} files", filtered_files.len());
-
-    // Filter out already processed files and files without meaningful content
-    const MIN_MESSAGES: usize = 3;
-    const MIN_CHARS: usize = 200;
-
-    let mut unprocessed_files = Vec::new();
-    let mut skipped_trivial = 0;
-
-    for file_path in filtered_files {
-        // First check if the file has meaningful content (fast filter)
-        if !has_meaningful_content(&file_path, MIN_MESSAGES, MIN_CHARS) {
-            skipped_trivial += 1;
-            continue;
-        }
-
-        let hash = calculate_file_hash(&file_path)?;
-        let is_processed = is_file_processed(app.db.pool(), &file_path, &hash).await?;
+/// A `crawler run` over one or more directories
+struct CrawlRun {
+    /// Options for each scanned directory
+    options: ScanOptions,
+    /// Totals across every scanned directory (for --email-report)
+    stats: CrawlStats,
+}
 
-        if !is_processed {
-            unprocessed_files.push((file_path, hash));
+/// Crawl `directory` and summarize the outcome
+async fn handle_scan(app: &AppState, directory: &Path, run: &CrawlRun) -> CliResult<String> {
+    let crawler = Crawler::new(app.db.clone(), app.generator.clone());
+    let report = match crawler.scan(directory, &run.options).await {
+        Ok(report) => report,
+        Err(e @ niwa_generator::Error::DirectoryNotFound(_)) => {
+            return Err(CliError::user(e.to_string()))
         }
-    }
+        Err(e) => return Err(generator_error("Crawl failed", e)),
+    };
 
-    if skipped_trivial > 0 {
-        info!(
-            "Skipped {} trivial sessions (< {} messages or < {} chars)",
-            skipped_trivial, MIN_MESSAGES, MIN_CHARS
-        );
-    }
+    run.stats.record(&report);
+    Ok(render_scan(&report, &run.options))
+}
 
-    // Apply limit if specified
-    if let Some(max_count) = limit {
-        unprocessed_files.truncate(max_count);
+/// Text summary of one scanned directory
+fn render_scan(report: &ScanReport, options: &ScanOptions) -> String {
+    if report.found == 0 {
+        return "No session files found.".to_string();
     }
 
-    info!(
-        "Found {} unprocessed files (after filters)",
-        unprocessed_files.len()
-    );
+    let skipped = report.skipped_before;
+    let skipped_note = skipped_breakdown(&[
+        (skipped.trivial, "trivial".to_string()),
+        (
+            skipped.too_large,
+            format!(
+                "too large (> {} bytes)",
+                options.max_size.unwrap_or_default()
+            ),
+        ),
+        (
+            skipped.too_small,
+            format!(
+                "too small (< {} bytes)",
+                options.min_size.unwrap_or_default()
+            ),
+        ),
+    ]);
+    let with_skipped_note = |mut output: String| {
+        if let Some(note) = &skipped_note {
+            output.push_str(&format!("\n{}", note));
+        }
+        output
+    };
 
-    if unprocessed_files.is_empty() {
-        return Ok("All session files have already been processed.".to_string());
+    if report.total_files == 0 {
+        return with_skipped_note("All session files have already been processed.".to_string());
     }
 
-    if dry_run {
+    if options.dry_run {
         let mut output = String::from("Dry run - would process:\n\n");
-        for (file_path, _) in &unprocessed_files {
+        for file_path in &report.pending {
             output.push_str(&format!("  • {}\n", file_path.display()));
         }
-        output.push_str(&format!("\nTotal: {} files", unprocessed_files.len()));
-        return Ok(output);
+        output.push_str(&format!("\nTotal: {} files", report.pending.len()));
+        return with_skipped_note(output);
     }
 
-    // Process each unprocessed file
-    let mut processed_count = 0;
-    let mut failed_count = 0;
-    let mut results = Vec::new();
-    let mut new_expertise_ids = Vec::new();
-    let mut scopes_used: std::collections::HashSet<Scope> = std::collections::HashSet::new();
-
-    for (file_path, file_hash) in unprocessed_files {
-        info!("Processing: {}", file_path.display());
-
-        // Determine scope for this file
-        let file_scope = if auto_scope {
-            resolve_scope_from_path(app.db.pool(), &file_path)
-                .await
-                .unwrap_or(default_scope)
-        } else {
-            default_scope
-        };
-        scopes_used.insert(file_scope);
-
-        match process_session_file(app, &file_path, &file_hash, file_scope).await {
-            Ok(expertise_id) => {
-                processed_count += 1;
-                let scope_indicator = if auto_scope && file_scope != default_scope {
-                    format!(" [{}]", file_scope)
+    let mut output = String::new();
+    for file in &report.files {
+        let path = file.path.display();
+        let line = match &file.outcome {
+            FileOutcome::Created { scope, expertises } => {
+                let scope_indicator = if options.auto_scope && *scope != options.scope {
+                    format!(" [{}]", scope)
                 } else {
                     String::new()
                 };
-                results.push(format!(
+                format!(
                     "✓ {}: {}{}",
-                    file_path.display(),
-                    expertise_id,
+                    path,
+                    summarize_ids(expertises),
                     scope_indicator
-                ));
-                new_expertise_ids.push((expertise_id, file_scope));
-            }
-            Err(e) => {
-                failed_count += 1;
-                warn!("Failed to process {}: {}", file_path.display(), e);
-                results.push(format!("✗ {}: {}", file_path.display(), e));
+                )
             }
-        }
+            FileOutcome::Skipped(reason) => format!("- {}: skipped ({})", path, reason),
+            FileOutcome::Failed(e) => format!("✗ {}: {}", path, e),
+        };
+        output.push_str(&format!("{}\n", line));
     }
-
-    // Auto-link new expertises based on shared tags (per scope)
-    let mut link_count = 0;
-    if auto_link && !new_expertise_ids.is_empty() {
-        info!("Auto-linking {} new expertises", new_expertise_ids.len());
-
-        // Group by scope and link within each scope
-        for scope in scopes_used {
-            let scope_ids: Vec<String> = new_expertise_ids
-                .iter()
-                .filter(|(_, s)| *s == scope)
-                .map(|(id, _)| id.clone())
-                .collect();
-
-            if scope_ids.is_empty() {
-                continue;
-            }
-
-            match auto_link_expertises(app, &scope_ids, scope).await {
-                Ok(count) => {
-                    link_count += count;
-                    if count > 0 {
-                        results.push(format!(
-                            "\n🔗 Auto-linked: {} relations created (scope: {})",
-                            count, scope
-                        ));
-                    }
-                }
-                Err(e) => {
-                    warn!("Auto-link failed for scope {}: {}", scope, e);
-                    results.push(format!("\n⚠ Auto-link failed ({}): {}", scope, e));
-                }
-            }
+    for link in &report.links {
+        match &link.result {
+            Ok(0) => {}
+            Ok(count) => output.push_str(&format!(
+                "\n🔗 Auto-linked: {} relations created (scope: {})\n",
+                count, link.scope
+            )),
+            Err(e) => output.push_str(&format!("\n⚠ Auto-link failed ({}): {}\n", link.scope, e)),
         }
     }
 
-    // Build summary
-    let mut output = String::new();
-
-    for result in results {
-        output.push_str(&format!("{}\n", result));
-    }
-
+    let (processed, skipped, failed) = (report.processed(), report.skipped(), report.failed());
     let mut summary = format!(
-        "\nSummary: {} processed, {} failed, {} total",
-        processed_count,
-        failed_count,
-        processed_count + failed_count
+        "\nSummary: {} processed, {} skipped, {} failed, {} total",
+        processed,
+        skipped,
+        failed,
+        processed + skipped + failed
     );
-    if auto_link && link_count > 0 {
-        summary.push_str(&format!(", {} links", link_count));
+    if options.auto_link && report.link_count() > 0 {
+        summary.push_str(&format!(", {} links", report.link_count()));
+    }
+    if let Some((left, total)) = report.resumed {
+        summary.push_str(&format!(
+            "\nResumed from checkpoint: {} of {} files already done, {} remaining",
+            total - left,
+            total,
+            left
+        ));
+    }
+    let mut summary = with_skipped_note(summary);
+    if report.interrupted {
+        summary.push_str(&format!(
+            "\nInterrupted: {} of {} files left unprocessed (checkpoint saved, re-run with --resume)",
+            report.total_files - processed - skipped - failed,
+            report.total_files
+        ));
     }
     output.push_str(&summary);
+    output
+}
 
-    Ok(output)
+/// Running totals of a crawl
+#[derive(Debug, Default)]
+struct CrawlStats {
+    /// Expertises created
+    created: AtomicUsize,
+    /// Files (or registered directories) that failed
+    failed: AtomicUsize,
+    /// Files that produced expertises
+    processed: AtomicUsize,
+    /// Files skipped while processing (binary, empty extraction)
+    skipped: AtomicUsize,
+    /// Relations created by auto-link
+    links: AtomicUsize,
+    /// Expertises created, in processing order (for --agent-mode)
+    results: Mutex<Vec<GenResult>>,
 }
 
-/// Scan directory recursively for session log files
-fn scan_session_files(dir: &Path) -> Result<Vec<PathBuf>, CliError> {
-    let mut files = Vec::new();
+impl CrawlStats {
+    /// Add the totals of one scanned directory
+    fn record(&self, report: &ScanReport) {
+        let created: Vec<GenResult> = report.created().map(GenResult::from).collect();
+        self.created.fetch_add(created.len(), Ordering::SeqCst);
+        self.failed.fetch_add(report.failed(), Ordering::SeqCst);
+        self.processed
+            .fetch_add(report.processed(), Ordering::SeqCst);
+        self.skipped.fetch_add(report.skipped(), Ordering::SeqCst);
+        self.links.fetch_add(report.link_count(), Ordering::SeqCst);
+        if let Ok(mut results) = self.results.lock() {
+            results.extend(created);
+        }
+    }
 
-    for entry in walkdir::WalkDir::new(dir)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_file() {
-            let path = entry.path();
-
-            // Filter by extension
-            if let Some(ext) = path.extension() {
-                let ext_str = ext.to_string_lossy().to_lowercase();
-                if matches!(ext_str.as_str(), "log" | "md" | "txt" | "jsonl" | "toml") {
-                    files.push(path.to_path_buf());
-                }
-            }
+    /// Result of the crawl as reported in agent mode
+    fn result(&self, interrupted: bool) -> CrawlResult {
+        let created = self.results.lock().map(|r| r.clone()).unwrap_or_default();
+        CrawlResult {
+            summary: CrawlSummary {
+                processed: self.processed.load(Ordering::SeqCst),
+                skipped: self.skipped.load(Ordering::SeqCst),
+                failed: self.failed.load(Ordering::SeqCst),
+                created: created.len(),
+                links: self.links.load(Ordering::SeqCst),
+                interrupted,
+            },
+            created,
         }
     }
+}
 
-    Ok(files)
+/// Outcome of `crawler run` in agent mode
+#[derive(Debug, Serialize)]
+struct CrawlResult {
+    /// Every expertise created, so an agent can chain on the new ids
+    created: Vec<GenResult>,
+    summary: CrawlSummary,
 }
 
-/// Calculate SHA256 hash of file content
-fn calculate_file_hash(path: &Path) -> Result<String, CliError> {
-    let content =
-        std::fs::read(path).map_err(|e| CliError::system(format!("Failed to read file: {}", e)))?;
+/// Crawl-level totals of a [`CrawlResult`]
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct CrawlSummary {
+    processed: usize,
+    skipped: usize,
+    failed: usize,
+    created: usize,
+    links: usize,
+    /// Whether the crawl was stopped with Ctrl-C
+    interrupted: bool,
+}
 
-    let mut hasher = Sha256::new();
-    hasher.update(&content);
-    let hash = hasher.finalize();
+/// Install a Ctrl-C handler that requests a graceful stop
+///
+/// The first interrupt sets the returned flag so the crawler can finish and
+/// record the current file before stopping. A second interrupt exits
+/// immediately.
+fn install_cancel_handler() -> Arc<AtomicBool> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let flag = cancelled.clone();
+
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if flag.swap(true, Ordering::SeqCst) {
+                eprintln!("\nInterrupted again, exiting immediately.");
+                std::process::exit(130);
+            }
+            eprintln!("\n⚠ Interrupt received: finishing the current file, then stopping (Ctrl-C again to force quit)");
+        }
+    });
 
-    Ok(format!("{:x}", hash))
+    cancelled
 }
 
-/// Check if file has already been processed
-async fn is_file_processed(
-    pool: &sqlx::SqlitePool,
-    file_path: &Path,
-    file_hash: &str,
-) -> Result<bool, CliError> {
-    let path_str = file_path.to_string_lossy();
+/// "Skipped before processing: ..." line for the non-zero counts, if any
+fn skipped_breakdown(counts: &[(usize, String)]) -> Option<String> {
+    let parts: Vec<String> = counts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{} {}", count, reason))
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(format!("Skipped before processing: {}", parts.join(", ")))
+}
 
-    let row: Option<(String,)> = sqlx::query_as(
-        r#"
-        SELECT file_hash
-        FROM processed_sessions
-        WHERE file_path = ?
-        "#,
-    )
-    .bind(&*path_str)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| CliError::system(format!("Database error: {}", e)))?;
+/// Parse a --recent-days value such as "7" or "0.5"
+fn parse_recent_days(s: &str) -> Result<Duration, String> {
+    parse_period(s, 24.0 * 60.0 * 60.0, "day")
+}
 
-    match row {
-        Some((existing_hash,)) => {
-            // Check if hash matches (file not modified)
-            Ok(existing_hash == file_hash)
-        }
-        None => Ok(false),
+/// Parse a --recent-hours value such as "6" or "1.5"
+fn parse_recent_hours(s: &str) -> Result<Duration, String> {
+    parse_period(s, 60.0 * 60.0, "hour")
+}
+
+/// Parse a positive, possibly fractional number of `unit`s of `unit_secs` seconds
+fn parse_period(s: &str, unit_secs: f64, unit: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "Invalid {} count '{}' (expected a positive number)",
+            unit, s
+        )
+    };
+    let count: f64 = s.trim().parse().map_err(|_| invalid())?;
+    if count <= 0.0 {
+        return Err(invalid());
     }
+    Duration::try_from_secs_f64(count * unit_secs).map_err(|_| invalid())
 }
 
-/// Maximum file size for in-memory processing (500KB)
-/// Files larger than this will be processed using file attachment to avoid ARG_MAX limits
-const MAX_IN_MEMORY_SIZE: u64 = 500 * 1024;
+/// Parse an age in days such as "90d" or "90" into seconds
+fn parse_age_secs(s: &str) -> Result<i64, String> {
+    let days: u64 = s
+        .trim()
+        .trim_end_matches('d')
+        .parse()
+        .map_err(|_| format!("Invalid day count '{}' (expected e.g. 90d)", s))?;
+    days.checked_mul(24 * 60 * 60)
+        .and_then(|secs| i64::try_from(secs).ok())
+        .ok_or_else(|| format!("Day count '{}' is out of range", s))
+}
 
-/// Process a session file and generate expertise
-///
-/// For small files (<500KB), the content is passed directly to the LLM.
-/// For large files (>=500KB), the file is passed as an attachment to avoid command-line
-/// argument length limitations. Large files may generate multiple expertises.
-async fn process_session_file(
+async fn handle_clean_processed(
     app: &AppState,
-    file_path: &Path,
-    file_hash: &str,
-    scope: Scope,
-) -> Result<String, String> {
-    // Check file size to determine processing method
-    let metadata = std::fs::metadata(file_path)
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
-    let file_size = metadata.len();
-
-    // Generate fallback expertise ID from file name (used if LLM doesn't provide a good one)
-    let fallback_id = generate_expertise_id(file_path);
-
-    debug!("Fallback expertise ID: {}", fallback_id);
-    debug!("File size: {} bytes", file_size);
-
-    let expertises = if file_size < MAX_IN_MEMORY_SIZE {
-        // Small file: use in-memory processing
-        debug!("Using in-memory processing (file size < {}KB)", MAX_IN_MEMORY_SIZE / 1024);
-
-        // Read file content
-        let content = std::fs::read_to_string(file_path)
-            .map_err(|e| format!("Failed to read file: {}", e))?;
-
-        // Generate expertise using LLM
-        let expertise = app
-            .generator
-            .generate_from_log(&content, &fallback_id, scope)
-            .await
-            .map_err(|e| format!("Failed to generate expertise: {}", e))?;
+    older_than_secs: Option<i64>,
+    missing_files: bool,
+    reprocess_changed: bool,
+    dir: Option<&Path>,
+    dry_run: bool,
+) -> CliResult<String> {
+    if older_than_secs.is_none() && !missing_files && !reprocess_changed {
+        return Err(CliError::user(
+            "Specify at least one of --older-than, --missing-files or --reprocess-changed",
+        ));
+    }
 
-        vec![expertise]
-    } else {
-        // Large file: use file attachment processing
-        info!(
-            "Using file-based processing (file size: {}KB)",
-            file_size / 1024
-        );
+    let dirs = dir.map(clean_dirs).transpose()?;
 
-        // Generate expertise(s) using file attachment (may return multiple)
-        app.generator
-            .generate_from_file(file_path, &fallback_id, scope)
-            .await
-            .map_err(|e| format!("Failed to generate expertise from file: {}", e))?
-    };
+    let storage = app.db.storage();
+    let db_err = |e: niwa_core::Error| CliError::system(format!("Database error: {}", e));
 
-    // Store all generated expertises
-    let mut expertise_ids = Vec::new();
-    for expertise in expertises {
-        let expertise_id = expertise.id().to_string();
-        expertise_ids.push(expertise_id.clone());
+    let before = older_than_secs.map(|secs| chrono::Utc::now().timestamp() - secs);
+    let targets: Vec<(String, &'static str)> = storage
+        .list_processed_sessions()
+        .await
+        .map_err(db_err)?
+        .into_iter()
+        .filter(|session| {
+            dirs.as_ref()
+                .is_none_or(|dirs| is_under(&session.file_path, dirs))
+        })
+        .filter_map(|session| {
+            let reason = clean_reason(&session, before, missing_files, reprocess_changed)?;
+            Some((session.file_path, reason))
+        })
+        .collect();
+
+    if targets.is_empty() {
+        return Ok("No processed-session records to clean.".to_string());
+    }
 
-        app.db
-            .storage()
-            .create(expertise)
-            .await
-            .map_err(|e| format!("Failed to store expertise {}: {}", expertise_id, e))?;
+    let mut output = String::from(if dry_run {
+        "Dry run - would delete:\n\n"
+    } else {
+        "Deleted:\n\n"
+    });
+    for (path, reason) in &targets {
+        output.push_str(&format!("  • {} ({})\n", path, reason));
+    }
 
-        info!("Stored expertise: {}", expertise_id);
+    if dry_run {
+        output.push_str(&format!("\nTotal: {} records", targets.len()));
+        return Ok(output);
     }
 
-    // Record as processed (use first ID only, even if multiple)
-    // Note: We only track the first expertise ID to satisfy foreign key constraints
-    let primary_id = expertise_ids[0].clone();
+    let paths: Vec<String> = targets.into_iter().map(|(path, _)| path).collect();
+    let deleted = storage
+        .delete_processed_sessions(&paths)
+        .await
+        .map_err(db_err)?;
 
-    let path_str = file_path.to_string_lossy();
-    let processed_at = chrono::Utc::now().timestamp();
+    output.push_str(&format!(
+        "\n✓ Removed {} processed-session records",
+        deleted
+    ));
+    Ok(output)
+}
 
-    sqlx::query(
-        r#"
-        INSERT OR REPLACE INTO processed_sessions (file_path, file_hash, expertise_id, processed_at)
-        VALUES (?, ?, ?, ?)
-        "#,
-    )
-    .bind(&*path_str)
-    .bind(file_hash)
-    .bind(&primary_id)
-    .bind(processed_at)
-    .execute(app.db.pool())
-    .await
-    .map_err(|e| format!("Failed to record processed session: {}", e))?;
+/// Directory forms to match `clean-processed --path` against
+///
+/// Recorded paths keep the form the crawl source was scanned with, so the
+/// directory is matched both as given and canonicalized.
+fn clean_dirs(dir: &Path) -> CliResult<Vec<PathBuf>> {
+    let canonical = dir
+        .canonicalize()
+        .map_err(|e| CliError::user(format!("Invalid --path {}: {}", dir.display(), e)))?;
+    Ok(vec![dir.to_path_buf(), canonical])
+}
 
-    // Return summary message
-    if expertise_ids.len() == 1 {
-        Ok(primary_id)
-    } else {
-        Ok(format!("{} (+{} more)", primary_id, expertise_ids.len() - 1))
+/// Whether `file_path` lies under any of `dirs`
+fn is_under(file_path: &str, dirs: &[PathBuf]) -> bool {
+    dirs.iter().any(|d| Path::new(file_path).starts_with(d))
+}
+
+/// Why `clean-processed` should delete a record, if it should
+///
+/// Records processed before `before` are "old". Otherwise the session file is
+/// checked: "missing" with `missing_files`, "changed" with `reprocess_changed`
+/// when its content hash no longer matches.
+fn clean_reason(
+    session: &ProcessedSession,
+    before: Option<i64>,
+    missing_files: bool,
+    reprocess_changed: bool,
+) -> Option<&'static str> {
+    if before.is_some_and(|cutoff| session.processed_at < cutoff) {
+        return Some("old");
+    }
+
+    let path = Path::new(&session.file_path);
+    if !path.exists() {
+        return missing_files.then_some("missing");
+    }
+    if !reprocess_changed {
+        return None;
+    }
+    match calculate_file_hash(path) {
+        Ok(hash) => (hash != session.file_hash).then_some("changed"),
+        Err(e) => {
+            warn!("Failed to hash {}: {}", session.file_path, e);
+            None
+        }
     }
 }
 
-/// Generate expertise ID from file path
-fn generate_expertise_id(path: &Path) -> String {
-    // Use file stem (name without extension) as base
-    let file_stem = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("session");
-
-    // Sanitize: replace spaces and special chars with hyphens
-    let sanitized = file_stem
-        .to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '-' })
-        .collect::<String>();
-
-    // Remove consecutive hyphens
-    let cleaned = sanitized
-        .split('-')
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>()
-        .join("-");
-
-    // Limit length
-    if cleaned.len() > 50 {
-        cleaned[..50].to_string()
-    } else {
-        cleaned
+/// One processed session file as listed by `crawler history`
+#[derive(Debug, Serialize)]
+struct HistoryEntry {
+    file_path: String,
+    expertise_id: String,
+    processed_at: i64,
+    /// Whether the derived expertise is still stored
+    expertise_exists: bool,
+}
+
+impl Render for [HistoryEntry] {
+    fn render(&self, format: OutputFormat) -> String {
+        const HEADER: [&str; 4] = ["file_path", "expertise_id", "processed_at", "exists"];
+        let processed = |ts: i64| {
+            chrono::DateTime::from_timestamp(ts, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| ts.to_string())
+        };
+        let records = || {
+            self.iter().map(|entry| {
+                vec![
+                    entry.file_path.clone(),
+                    entry.expertise_id.clone(),
+                    processed(entry.processed_at),
+                    entry.expertise_exists.to_string(),
+                ]
+            })
+        };
+
+        match format {
+            OutputFormat::Text => {
+                if self.is_empty() {
+                    return "No processed sessions recorded.".to_string();
+                }
+
+                let mut table = Table::new();
+                table
+                    .load_preset(presets::UTF8_FULL)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        Cell::new("File").fg(Color::Yellow),
+                        Cell::new("Expertise").fg(Color::Yellow),
+                        Cell::new("Processed").fg(Color::Yellow),
+                        Cell::new("Exists").fg(Color::Yellow),
+                    ]);
+                for entry in self {
+                    let exists = if entry.expertise_exists {
+                        Cell::new("✓")
+                    } else if color_enabled() {
+                        Cell::new("✗").fg(Color::Red)
+                    } else {
+                        Cell::new("✗")
+                    };
+                    table.add_row(vec![
+                        Cell::new(&entry.file_path),
+                        Cell::new(&entry.expertise_id),
+                        Cell::new(processed(entry.processed_at)),
+                        exists,
+                    ]);
+                }
+
+                let deleted = self.iter().filter(|e| !e.expertise_exists).count();
+                format!(
+                    "{}\n\nTotal: {} records ({} with a deleted expertise)",
+                    table,
+                    self.len(),
+                    deleted
+                )
+            }
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Csv => to_csv(&HEADER, records()),
+            OutputFormat::Markdown => to_markdown(&HEADER, records()),
+        }
     }
 }
 
-/// Auto-link new expertises to existing ones using LLM-powered LinkerAgent
-async fn auto_link_expertises(
-    app: &AppState,
-    new_ids: &[String],
-    scope: Scope,
-) -> Result<usize, String> {
+async fn handle_history(app: &AppState, missing: bool, format: OutputFormat) -> CliResult<String> {
     let storage = app.db.storage();
-    let graph = app.db.graph();
-    let mut link_count = 0;
-
-    // Get all existing expertises for comparison
-    let all_expertises = storage
-        .list(scope)
-        .await
-        .map_err(|e| format!("Failed to list expertises: {}", e))?;
+    let db_err = |e: niwa_core::Error| CliError::system(format!("Database error: {}", e));
+
+    let mut entries = Vec::new();
+    for session in storage.list_processed_sessions().await.map_err(db_err)? {
+        // The record doesn't keep the scope; ids are unique across scopes
+        let mut expertise_exists = false;
+        for scope in Scope::all() {
+            if storage
+                .exists(&session.expertise_id, *scope)
+                .await
+                .map_err(db_err)?
+            {
+                expertise_exists = true;
+                break;
+            }
+        }
+        if missing && expertise_exists {
+            continue;
+        }
 
-    if all_expertises.len() <= 1 {
-        return Ok(0); // Need at least 2 expertises to link
+        entries.push(HistoryEntry {
+            file_path: session.file_path,
+            expertise_id: session.expertise_id,
+            processed_at: session.processed_at,
+            expertise_exists,
+        });
     }
 
-    // For each new expertise, use LinkerAgent to suggest links
-    for new_id in new_ids {
-        // Get the new expertise
-        let new_expertise = match storage.get(new_id, scope).await {
-            Ok(Some(e)) => e,
-            _ => continue,
-        };
+    Ok(entries.render(format))
+}
 
-        // Use LinkerAgent to analyze and suggest links
-        let suggested_links = app
-            .generator
-            .suggest_links(&new_expertise, &all_expertises)
-            .await
-            .unwrap_or_default();
-
-        // Create suggested relations
-        for link in suggested_links {
-            // Parse relation type
-            let relation_type = match link.relation_type.to_lowercase().as_str() {
-                "uses" => RelationType::Uses,
-                "extends" => RelationType::Extends,
-                "requires" => RelationType::Requires,
-                "conflicts" => RelationType::Conflicts,
-                _ => RelationType::Uses, // Default to Uses
-            };
+/// Extensions to scan: `--ext` if given, else the config default, else
+/// [`DEFAULT_EXTENSIONS`]
+///
+/// A leading dot is accepted (".chat") and matching is case-insensitive.
+fn scan_extensions(flag: Vec<String>, config: Option<&[String]>) -> Vec<String> {
+    let extensions = if !flag.is_empty() {
+        flag
+    } else if let Some(config) = config {
+        config.to_vec()
+    } else {
+        DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect()
+    };
 
-            // Check if relation already exists
-            let existing_relations = graph
-                .get_all_relations(&link.from_id)
-                .await
-                .unwrap_or_default();
+    extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .collect()
+}
 
-            let already_linked = existing_relations
-                .iter()
-                .any(|r| r.to_id == link.to_id || r.from_id == link.to_id);
-
-            if !already_linked {
-                // Create relation with reason as metadata
-                if let Ok(()) = graph
-                    .create_relation(
-                        &link.from_id,
-                        &link.to_id,
-                        relation_type,
-                        Some(link.reason.clone()),
-                    )
-                    .await
-                {
-                    info!(
-                        "Auto-linked {} -[{}]-> {} (confidence: {:.2}, reason: {})",
-                        link.from_id, relation_type, link.to_id, link.confidence, link.reason
-                    );
-                    link_count += 1;
-                }
-            }
+/// Summarize the expertises stored from one file (e.g. "rust-expert (+2 more)")
+fn summarize_ids(created: &[Expertise]) -> String {
+    match created {
+        [] => String::new(),
+        [first] => first.id().to_string(),
+        [first, rest @ ..] => format!("{} (+{} more)", first.id(), rest.len()),
+    }
+}
+
+/// How `--auto-link` finds links between expertises
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkStrategy {
+    /// Ask the LLM (LinkerAgent) to suggest links
+    Llm,
+    /// Link expertises sharing tags, without calling the LLM
+    Tags,
+}
+
+impl From<LinkStrategy> for niwa_generator::LinkStrategy {
+    fn from(strategy: LinkStrategy) -> Self {
+        match strategy {
+            LinkStrategy::Llm => Self::Llm,
+            LinkStrategy::Tags => Self::Tags,
         }
     }
+}
 
-    Ok(link_count)
+/// Statuses auto-link leaves out: deprecated unless included, drafts if excluded
+fn link_excluded_statuses(include_deprecated: bool, exclude_drafts: bool) -> Vec<ExpertiseStatus> {
+    let mut excluded = Vec::new();
+    if !include_deprecated {
+        excluded.push(ExpertiseStatus::Deprecated);
+    }
+    if exclude_drafts {
+        excluded.push(ExpertiseStatus::Draft);
+    }
+    excluded
 }
 
 // ============================================================================
@@ -1052,202 +1397,458 @@ async fn handle_scope_remove(app: &AppState, id: i64) -> CliResult<String> {
     }
 }
 
-/// Resolve scope from a file path using scope mappings
-pub async fn resolve_scope_from_path(pool: &sqlx::SqlitePool, path: &Path) -> Option<Scope> {
-    let path_str = path.to_string_lossy();
-
-    // Get all mappings ordered by priority (highest first)
-    let rows: Vec<(String, String)> = sqlx::query_as(
-        r#"
-        SELECT pattern, scope
-        FROM scope_mappings
-        ORDER BY priority DESC
-        "#,
-    )
-    .fetch_all(pool)
-    .await
-    .ok()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_generator::FileReport;
 
-    for (pattern, scope_str) in rows {
-        if matches_pattern(&path_str, &pattern) {
-            return scope_str.parse().ok();
-        }
+    fn gen_result(id: &str) -> GenResult {
+        GenResult::from(&niwa_core::Expertise::new(id, "1.0.0"))
     }
 
-    None // No match found
-}
+    #[test]
+    fn test_summarize_ids() {
+        let expertise = |id: &str| Expertise::new(id, "1.0.0");
+        assert_eq!(summarize_ids(&[expertise("a")]), "a");
+        assert_eq!(
+            summarize_ids(&[expertise("a"), expertise("b"), expertise("c")]),
+            "a (+2 more)"
+        );
+    }
 
-/// Check if a session file has meaningful content
-///
-/// Returns true if the session has:
-/// - For JSONL (Claude): At least `min_messages` user/assistant messages combined and `min_chars` total characters
-/// - For TOML (Orcs): File size >= 5KB (heuristic for sessions with actual conversation)
-/// - For other formats: Default to true (process all files)
-///
-/// This filters out empty agent initialization logs and trivial sessions.
-fn has_meaningful_content(path: &Path, min_messages: usize, min_chars: usize) -> bool {
-    // For TOML files (Orcs sessions), use file size heuristic
-    if let Some(ext) = path.extension() {
-        if ext.to_string_lossy().to_lowercase() == "toml" {
-            // TOML sessions: check if file is >= 5KB (typical for sessions with actual content)
-            if let Ok(metadata) = std::fs::metadata(path) {
-                return metadata.len() >= 5 * 1024; // 5KB threshold
+    #[test]
+    fn test_crawl_result() {
+        let stats = CrawlStats::default();
+        stats.processed.fetch_add(2, Ordering::SeqCst);
+        stats.failed.fetch_add(1, Ordering::SeqCst);
+        stats.links.fetch_add(3, Ordering::SeqCst);
+        stats
+            .results
+            .lock()
+            .unwrap()
+            .extend([gen_result("a"), gen_result("b")]);
+
+        let result = stats.result(false);
+        assert_eq!(
+            result.summary,
+            CrawlSummary {
+                processed: 2,
+                skipped: 0,
+                failed: 1,
+                created: 2,
+                links: 3,
+                interrupted: false,
             }
-            return false;
-        }
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&result)).unwrap();
+        assert_eq!(json["created"][1]["id"], "b");
+        assert_eq!(json["created"][0]["fragment_count"], 0);
+        assert_eq!(json["summary"]["links"], 3);
     }
 
-    // For JSONL files (Claude sessions), parse JSON content
-    let file = match std::fs::File::open(path) {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
+    #[test]
+    fn test_emit_events_args() {
+        let args = CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--emit-events",
+            "--events-output",
+            "-",
+        ])
+        .unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                emit_events: true,
+                events_output: EventsOutput::Stdout,
+                ..
+            })
+        ));
+
+        // Event options only make sense with --emit-events
+        assert!(CrawlerArgs::try_parse_from(["crawler", "run", "--events-output", "x"]).is_err());
+        assert!(CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--emit-events",
+            "--events-format",
+            "xml"
+        ])
+        .is_err());
+    }
 
-    let reader = std::io::BufReader::new(file);
-    let mut message_count = 0;
-    let mut total_chars = 0;
+    #[test]
+    fn test_email_report_args() {
+        let args = CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--email-report",
+            "me@example.com",
+            "--email-on-error-only",
+        ])
+        .unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                email_report: Some(_),
+                email_on_error_only: true,
+                email_provider: EmailProvider::Smtp,
+                ..
+            })
+        ));
 
-    for line in std::io::BufRead::lines(reader) {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => continue,
-        };
+        assert!(CrawlerArgs::try_parse_from(["crawler", "run", "--email-on-error-only"]).is_err());
+        assert!(
+            CrawlerArgs::try_parse_from(["crawler", "run", "--email-provider", "sendgrid"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_auto_scope_args() {
+        let args = CrawlerArgs::try_parse_from(["crawler", "run", "--no-auto-scope"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                auto_scope: false,
+                no_auto_scope: true,
+                scope: None,
+                ..
+            })
+        ));
+
+        assert!(
+            CrawlerArgs::try_parse_from(["crawler", "run", "--auto-scope", "--no-auto-scope"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_link_excluded_statuses() {
+        assert_eq!(
+            link_excluded_statuses(false, false),
+            vec![ExpertiseStatus::Deprecated]
+        );
+        assert_eq!(
+            link_excluded_statuses(false, true),
+            vec![ExpertiseStatus::Deprecated, ExpertiseStatus::Draft]
+        );
+        assert!(link_excluded_statuses(true, false).is_empty());
+    }
+
+    #[test]
+    fn test_clean_dirs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let sub = temp.path().join("sessions");
+        std::fs::create_dir(&sub).unwrap();
+
+        let relative = sub.join("..").join("sessions");
+        let dirs = clean_dirs(&relative).unwrap();
+        let under = |path: PathBuf| is_under(&path.to_string_lossy(), &dirs);
+        assert!(under(sub.canonicalize().unwrap().join("a.jsonl")));
+        assert!(under(relative.join("a.jsonl")));
+        assert!(!under(temp.path().join("b.jsonl")));
+
+        assert!(clean_dirs(&temp.path().join("missing")).is_err());
+    }
 
-        // Parse JSON line
-        let json: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(_) => continue,
+    #[test]
+    fn test_parse_age_secs() {
+        assert_eq!(parse_age_secs("90d").unwrap(), 90 * 24 * 60 * 60);
+        assert_eq!(parse_age_secs("30").unwrap(), 30 * 24 * 60 * 60);
+        assert!(parse_age_secs("ninety").is_err());
+        assert!(parse_age_secs("-1d").is_err());
+        assert!(parse_age_secs("106751991167301d").is_err());
+        assert!(parse_age_secs(&u64::MAX.to_string()).is_err());
+
+        let args =
+            CrawlerArgs::try_parse_from(["crawler", "clean-processed", "--older-than", "2d"])
+                .unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::CleanProcessed {
+                older_than_secs: Some(172_800),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_clean_reason() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let file = temp.path().join("session.jsonl");
+        std::fs::write(&file, "log").unwrap();
+        let session = |path: &Path, file_hash: &str, processed_at: i64| ProcessedSession {
+            file_path: path.to_string_lossy().to_string(),
+            file_hash: file_hash.to_string(),
+            expertise_id: "test".to_string(),
+            processed_at,
         };
 
-        // Check message type (user or assistant)
-        let msg_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
-        if msg_type == "user" || msg_type == "assistant" {
-            message_count += 1;
-
-            // Extract content from message
-            if let Some(message) = json.get("message") {
-                // Handle Claude API format: message.content array
-                if let Some(content_array) = message.get("content").and_then(|c| c.as_array()) {
-                    for content_item in content_array {
-                        if let Some(text) = content_item.get("text").and_then(|t| t.as_str()) {
-                            total_chars += text.len();
-                        }
-                    }
-                }
-                // Handle simple string content
-                else if let Some(content_str) = message.get("content").and_then(|c| c.as_str()) {
-                    total_chars += content_str.len();
-                }
-                // Handle direct message as string (user messages)
-                else if let Some(msg_str) = message.as_str() {
-                    total_chars += msg_str.len();
-                }
-            }
-        }
+        let hash = calculate_file_hash(&file).unwrap();
+        let current = session(&file, &hash, 5_000);
+        assert_eq!(clean_reason(&current, Some(2_000), true, true), None);
+        assert_eq!(
+            clean_reason(&current, Some(9_000), false, false),
+            Some("old")
+        );
 
-        // Early exit if we've already met the criteria
-        if message_count >= min_messages && total_chars >= min_chars {
-            return true;
-        }
+        let changed = session(&file, "stale-hash", 5_000);
+        assert_eq!(clean_reason(&changed, None, false, false), None);
+        assert_eq!(clean_reason(&changed, None, false, true), Some("changed"));
+
+        let missing = session(&temp.path().join("gone.jsonl"), &hash, 5_000);
+        assert_eq!(clean_reason(&missing, None, false, true), None);
+        assert_eq!(clean_reason(&missing, None, true, false), Some("missing"));
     }
 
-    message_count >= min_messages && total_chars >= min_chars
-}
+    #[test]
+    fn test_parse_recent() {
+        assert_eq!(
+            parse_recent_hours("6").unwrap(),
+            Duration::from_secs(6 * 60 * 60)
+        );
+        assert_eq!(
+            parse_recent_days("0.5").unwrap(),
+            Duration::from_secs(12 * 60 * 60)
+        );
+        assert_eq!(
+            parse_recent_days("7").unwrap(),
+            Duration::from_secs(7 * 24 * 60 * 60)
+        );
+        assert!(parse_recent_hours("0").is_err());
+        assert!(parse_recent_hours("-1").is_err());
+        assert!(parse_recent_days("NaN").is_err());
+        assert!(parse_recent_days("a week").is_err());
+    }
 
-/// Match a path against a glob-like pattern
-/// Supports:
-/// - `*` matches any sequence of characters (except /)
-/// - `**` matches any sequence including /
-/// - `[...]` character classes (e.g., `[0-9]`, `[a-z]`)
-/// - Literal text matches exactly
-fn matches_pattern(path: &str, pattern: &str) -> bool {
-    // Extract and preserve character classes [...] before escaping
-    let mut result = String::new();
-    let mut chars = pattern.chars().peekable();
-    let mut char_classes: Vec<String> = Vec::new();
-
-    while let Some(c) = chars.next() {
-        if c == '[' {
-            // Collect the entire character class
-            let mut class = String::from("[");
-            while let Some(&next) = chars.peek() {
-                chars.next();
-                class.push(next);
-                if next == ']' {
-                    break;
-                }
-            }
-            // Replace with placeholder (use unique marker)
-            result.push_str(&format!("__CHARCLASS{}__", char_classes.len()));
-            char_classes.push(class);
-        } else {
-            result.push(c);
+    #[test]
+    fn test_recent_args() {
+        let args = CrawlerArgs::try_parse_from(["crawler", "run", "--recent-hours", "6"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                recent_days: None,
+                recent_hours: Some(hours),
+                ..
+            }) if hours == Duration::from_secs(6 * 60 * 60)
+        ));
+        assert!(CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--recent-days",
+            "1",
+            "--recent-hours",
+            "6"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_scan_extensions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for name in ["session.log", "chat-1.chat", "notes.ORG", "data.json"] {
+            std::fs::write(temp_dir.path().join(name), "content").unwrap();
         }
+        let scan = |extensions: Vec<String>| {
+            let mut names: Vec<String> = scan_session_files(temp_dir.path(), &extensions)
+                .unwrap()
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(scan(scan_extensions(Vec::new(), None)), vec!["session.log"]);
+        assert_eq!(
+            scan(scan_extensions(
+                vec!["chat".to_string(), ".org".to_string()],
+                None
+            )),
+            vec!["chat-1.chat", "notes.ORG"]
+        );
+
+        // The flag replaces the config default rather than adding to it
+        let config = vec!["json".to_string()];
+        assert_eq!(
+            scan(scan_extensions(Vec::new(), Some(&config))),
+            vec!["data.json"]
+        );
+        assert_eq!(
+            scan(scan_extensions(vec!["chat".to_string()], Some(&config))),
+            vec!["chat-1.chat"]
+        );
     }
 
-    // Simple glob matching
-    let pattern = result.replace("**", "__DOUBLESTAR__");
-    let pattern = pattern.replace('*', "[^/]*");
-    let pattern = pattern.replace("__DOUBLESTAR__", ".*");
+    #[test]
+    fn test_history_render() {
+        let entries = [
+            HistoryEntry {
+                file_path: "/logs/a.jsonl".to_string(),
+                expertise_id: "rust-expert".to_string(),
+                processed_at: 1_700_000_000,
+                expertise_exists: true,
+            },
+            HistoryEntry {
+                file_path: "/logs/b.jsonl".to_string(),
+                expertise_id: "gone".to_string(),
+                processed_at: 1_700_086_400,
+                expertise_exists: false,
+            },
+        ];
 
-    // Escape other regex chars
-    let mut pattern = regex::escape(&pattern)
-        .replace(r"\[\^/\]\*", "[^/]*")
-        .replace(r"\.\*", ".*");
+        assert_eq!(
+            entries.render(OutputFormat::Csv),
+            "file_path,expertise_id,processed_at,exists\n\
+             /logs/a.jsonl,rust-expert,2023-11-14 22:13,true\n\
+             /logs/b.jsonl,gone,2023-11-15 22:13,false"
+        );
+        assert!(entries
+            .render(OutputFormat::Text)
+            .ends_with("Total: 2 records (1 with a deleted expertise)"));
 
-    // Restore character classes (after escaping, the placeholder becomes escaped)
-    for (i, class) in char_classes.iter().enumerate() {
-        pattern = pattern.replace(&format!("__CHARCLASS{}__", i), class);
+        let json: serde_json::Value =
+            serde_json::from_str(&entries.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json[1]["expertise_exists"], false);
+        assert_eq!(
+            Vec::<HistoryEntry>::new().render(OutputFormat::Text),
+            "No processed sessions recorded."
+        );
     }
 
-    // Match anywhere in the path
-    let regex_pattern = format!("(?i){}", pattern); // Case-insensitive
+    #[test]
+    fn test_dedup_near_args() {
+        let max_distance = niwa_core::fingerprint::DEFAULT_NEAR_DUPLICATE_DISTANCE;
+        let args = CrawlerArgs::try_parse_from(["crawler", "run", "--dedup-near"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run { dedup_near: Some(d), .. }) if d == max_distance
+        ));
+        let args =
+            CrawlerArgs::try_parse_from(["crawler", "run", "--dedup-near=6", "logs"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                dedup_near: Some(6),
+                directory: Some(_),
+                ..
+            })
+        ));
+    }
 
-    regex::Regex::new(&regex_pattern)
-        .map(|re| re.is_match(path))
-        .unwrap_or(false)
-}
+    #[test]
+    fn test_link_strategy_args() {
+        let args = CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--auto-link",
+            "--link-strategy",
+            "tags",
+        ])
+        .unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                link_strategy: LinkStrategy::Tags,
+                min_shared_tags: 2,
+                ..
+            })
+        ));
+        assert!(
+            CrawlerArgs::try_parse_from(["crawler", "run", "--link-strategy", "tags"]).is_err()
+        );
+        assert!(CrawlerArgs::try_parse_from(["crawler", "run", "--include-deprecated"]).is_err());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_link_candidates_args() {
+        let args = CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--auto-link",
+            "--link-candidates",
+            "20",
+        ])
+        .unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run {
+                link_candidates: Some(20),
+                ..
+            })
+        ));
+        assert!(
+            CrawlerArgs::try_parse_from(["crawler", "run", "--link-candidates", "20"]).is_err()
+        );
+        assert!(CrawlerArgs::try_parse_from([
+            "crawler",
+            "run",
+            "--auto-link",
+            "--link-candidates",
+            "0"
+        ])
+        .is_err());
+    }
 
     #[test]
-    fn test_matches_pattern() {
-        // Simple wildcard
-        assert!(matches_pattern("/Users/test/projects/company-foo/file", "company-*"));
-        assert!(matches_pattern("/Users/test/projects/niwa-cli/src", "niwa-*"));
-
-        // Double wildcard
-        assert!(matches_pattern("/Users/test/work/client/project/file", "work/**"));
-
-        // Exact match
-        assert!(matches_pattern("/Users/test/projects/niwa", "niwa"));
-
-        // Character classes
-        assert!(matches_pattern("/Users/test/projects/y1/file", "y[0-9]*"));
-        assert!(matches_pattern("/Users/test/projects/y23/file", "y[0-9]*"));
-        assert!(matches_pattern("/Users/test/projects/y100/file", "y[0-9]*"));
-        assert!(!matches_pattern("/Users/test/projects/yui/file", "y[0-9]*"));
-        assert!(!matches_pattern("/Users/test/projects/ya/file", "y[0-9]*"));
-
-        // No match
-        assert!(!matches_pattern("/Users/test/personal/stuff", "company-*"));
+    fn test_concurrency_args() {
+        let args = CrawlerArgs::try_parse_from(["crawler", "run", "--concurrency", "4"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(CrawlerCommand::Run { concurrency: 4, .. })
+        ));
+        assert!(CrawlerArgs::try_parse_from(["crawler", "run", "--concurrency", "0"]).is_err());
     }
 
     #[test]
-    fn test_generate_expertise_id() {
+    fn test_render_scan() {
+        let options = ScanOptions::default();
         assert_eq!(
-            generate_expertise_id(Path::new("session-2024-01-15.log")),
-            "session-2024-01-15"
+            render_scan(&ScanReport::default(), &options),
+            "No session files found."
         );
+
+        let mut report = ScanReport {
+            found: 3,
+            total_files: 3,
+            files: vec![
+                FileReport {
+                    path: PathBuf::from("a.jsonl"),
+                    outcome: FileOutcome::Created {
+                        scope: Scope::Personal,
+                        expertises: vec![Expertise::new("rust-expert", "1.0.0")],
+                    },
+                },
+                FileReport {
+                    path: PathBuf::from("b.jsonl"),
+                    outcome: FileOutcome::Skipped("binary content".to_string()),
+                },
+            ],
+            interrupted: true,
+            ..Default::default()
+        };
+        report.skipped_before.trivial = 2;
         assert_eq!(
-            generate_expertise_id(Path::new("My Session Log.txt")),
-            "my-session-log"
+            render_scan(&report, &options),
+            "✓ a.jsonl: rust-expert\n\
+             - b.jsonl: skipped (binary content)\n\
+             \nSummary: 1 processed, 1 skipped, 0 failed, 2 total\
+             \nSkipped before processing: 2 trivial\
+             \nInterrupted: 1 of 3 files left unprocessed (checkpoint saved, re-run with --resume)"
         );
+
+        // Resumed with 2 of 3 files pending: 1 was done before the restart
+        report.interrupted = false;
+        report.skipped_before.trivial = 0;
+        report.resumed = Some((2, 3));
         assert_eq!(
-            generate_expertise_id(Path::new("rust_async_patterns.md")),
-            "rust-async-patterns"
+            render_scan(&report, &options),
+            "✓ a.jsonl: rust-expert\n\
+             - b.jsonl: skipped (binary content)\n\
+             \nSummary: 1 processed, 1 skipped, 0 failed, 2 total\
+             \nResumed from checkpoint: 1 of 3 files already done, 2 remaining"
         );
     }
 }
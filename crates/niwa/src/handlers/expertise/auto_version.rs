@@ -0,0 +1,114 @@
+//! Auto-version subcommand - let the LLM re-version changed expertises
+
+use crate::state::AppState;
+use niwa_core::Scope;
+use niwa_generator::bump_version;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_auto_version(
+    app: &AppState,
+    scope: Scope,
+    dry_run: bool,
+) -> CliResult<String> {
+    let storage = app.db.storage();
+    let expertises = storage
+        .list(scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+    let mut output = Vec::new();
+    let mut changed = 0;
+    for mut expertise in expertises {
+        let id = expertise.id().to_string();
+        if expertise.metadata.locked {
+            output.push(format!("  - {}: locked", id));
+            continue;
+        }
+
+        // Versions are listed newest first; the current one may be among them
+        let versions = storage
+            .list_versions(&id)
+            .await
+            .map_err(|e| CliError::system(format!("Database error: {}", e)))?;
+        let previous = match versions.iter().find(|v| v.as_str() != expertise.version()) {
+            Some(version) => storage
+                .get_version(&id, version)
+                .await
+                .map_err(|e| CliError::system(format!("Database error: {}", e)))?,
+            None => None,
+        };
+        let Some(previous) = previous else {
+            output.push(format!("  - {}: no earlier version", id));
+            continue;
+        };
+        if previous.diff(&expertise).is_empty() {
+            output.push(format!(
+                "  - {}: unchanged since v{}",
+                id,
+                previous.version()
+            ));
+            continue;
+        }
+
+        let recommendation = match app
+            .generator
+            .suggest_version_bump(&previous, &expertise)
+            .await
+        {
+            Ok(recommendation) => recommendation,
+            Err(e) => {
+                output.push(format!("  ✗ {}: {}", id, e));
+                continue;
+            }
+        };
+        let old_version = expertise.version().to_string();
+        let new_version = bump_version(previous.version(), recommendation.bump);
+        if new_version == old_version {
+            output.push(format!(
+                "  - {}: v{} already matches ({})",
+                id,
+                old_version,
+                recommendation.bump.as_str()
+            ));
+            continue;
+        }
+
+        let line = format!(
+            "{}: v{} → v{} ({}): {}",
+            id,
+            old_version,
+            new_version,
+            recommendation.bump.as_str(),
+            recommendation.reasoning
+        );
+        if !dry_run {
+            expertise.inner.version = new_version;
+            expertise.metadata.version_reason = Some(recommendation.reasoning);
+            if let Err(e) = storage.update(expertise).await {
+                output.push(format!("  ✗ {}: {}", id, e));
+                continue;
+            }
+        }
+        changed += 1;
+        output.push(format!("  ✓ {}", line));
+    }
+
+    if output.is_empty() {
+        return Ok(format!("No expertises found in scope: {}", scope));
+    }
+    let mut result = format!(
+        "{} {} version(s) in scope {}\n{}",
+        if dry_run {
+            "Would renumber"
+        } else {
+            "Renumbered"
+        },
+        changed,
+        scope,
+        output.join("\n")
+    );
+    if dry_run {
+        result.push_str("\n\n(dry run - no versions changed)");
+    }
+    Ok(result)
+}
@@ -0,0 +1,127 @@
+//! Batch-describe subcommand - generate descriptions with the LLM
+
+use crate::render::truncate_chars;
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+pub(super) async fn handle_batch_describe(
+    app: &AppState,
+    missing_only: bool,
+    dry_run: bool,
+    concurrency: usize,
+    scope: Scope,
+) -> CliResult<String> {
+    let expertises = app
+        .db
+        .storage()
+        .list(scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+    let mut output = Vec::new();
+    let mut targets = Vec::new();
+    for expertise in expertises {
+        if missing_only && !expertise.description().trim().is_empty() {
+            continue;
+        }
+        if expertise.metadata.locked {
+            output.push(format!("  - {}: skipped (locked)", expertise.id()));
+        } else if expertise.inner.content.is_empty() {
+            output.push(format!("  - {}: skipped (no fragments)", expertise.id()));
+        } else {
+            targets.push(expertise);
+        }
+    }
+
+    if targets.is_empty() {
+        output.insert(0, format!("No expertises to describe in {} scope", scope));
+        return Ok(output.join("\n"));
+    }
+
+    if dry_run {
+        output.insert(
+            0,
+            format!(
+                "Would describe {} expertise(s) in {} scope:",
+                targets.len(),
+                scope
+            ),
+        );
+        for expertise in &targets {
+            let current = expertise.description();
+            let current = if current.trim().is_empty() {
+                "(none)".to_string()
+            } else {
+                truncate_chars(current.trim(), 60)
+            };
+            output.push(format!("  • {}: {}", expertise.id(), current));
+        }
+        output.push("\n(dry run - no LLM calls made)".to_string());
+        return Ok(output.join("\n"));
+    }
+
+    // Fragments go to the LLM concurrently, at most `concurrency` at a time
+    let limit = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+    for (index, expertise) in targets.iter().enumerate() {
+        let generator = app.generator.clone();
+        let limit = limit.clone();
+        let fragments: Vec<String> = expertise
+            .inner
+            .content
+            .iter()
+            .map(|w| w.fragment.to_prompt())
+            .collect();
+        tasks.spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            (index, generator.generate_description(&fragments).await)
+        });
+    }
+
+    let mut results = Vec::with_capacity(targets.len());
+    while let Some(joined) = tasks.join_next().await {
+        results.push(joined.map_err(|e| CliError::system(format!("LLM task failed: {}", e)))?);
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    let storage = app.db.storage();
+    let mut updated = 0;
+    let mut failed = 0;
+    for (index, result) in results {
+        let id = targets[index].id();
+        let saved = match result {
+            Ok(description) => storage
+                .update_description(id, scope, &description)
+                .await
+                .map(|_| description)
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match saved {
+            Ok(description) => {
+                updated += 1;
+                output.push(format!("  ✓ {}: {}", id, description));
+            }
+            Err(e) => {
+                failed += 1;
+                output.push(format!("  ✗ {}: {}", id, e));
+            }
+        }
+    }
+
+    output.insert(
+        0,
+        format!(
+            "Described {} of {} expertise(s) in {} scope ({} failed)",
+            updated,
+            targets.len(),
+            scope,
+            failed
+        ),
+    );
+    Ok(output.join("\n"))
+}
@@ -0,0 +1,80 @@
+//! Context-export subcommand - render an expertise as LLM context
+
+use super::{check_not_deprecated, get_expertise};
+use crate::state::AppState;
+use niwa_core::{Priority, Scope};
+use niwa_generator::ExpertiseGenerator;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_context_export(
+    app: &AppState,
+    id: &str,
+    max_tokens: usize,
+    min_priority: Priority,
+    xml: bool,
+    clipboard: bool,
+    include_deprecated: bool,
+    scope: Scope,
+) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+    check_not_deprecated(&expertise, include_deprecated)?;
+    let context = ExpertiseGenerator::export_as_context(&expertise, max_tokens, &min_priority);
+    let context = if xml {
+        format!("<niwa_expertise id=\"{}\">{}</niwa_expertise>", id, context)
+    } else {
+        context
+    };
+
+    if !clipboard {
+        return Ok(context);
+    }
+    copy_to_clipboard(&context)?;
+    Ok(format!(
+        "✓ Copied context for {} to the clipboard (~{} tokens)",
+        id,
+        ExpertiseGenerator::estimate_tokens(&context)
+    ))
+}
+
+/// Copy `text` to the system clipboard using the platform's clipboard tool
+fn copy_to_clipboard(text: &str) -> CliResult<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    const TOOLS: [(&str, &[&str]); 5] = [
+        ("pbcopy", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+        ("clip", &[]),
+    ];
+
+    for (program, args) in TOOLS {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| CliError::system(format!("Failed to write to {}: {}", program, e)))?;
+        }
+        let status = child
+            .wait()
+            .map_err(|e| CliError::system(format!("Failed to run {}: {}", program, e)))?;
+        if status.success() {
+            return Ok(());
+        }
+        return Err(CliError::system(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+
+    Err(CliError::user(
+        "No clipboard tool found (install pbcopy, wl-copy, xclip or xsel)",
+    ))
+}
@@ -0,0 +1,134 @@
+//! Convert subcommand - turn text fragments into richer fragment types
+
+use super::get_expertise;
+use crate::exit_code::generator_error;
+use crate::state::AppState;
+use niwa_core::{Expertise, KnowledgeFragment, Scope};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_convert(
+    app: &AppState,
+    id: &str,
+    from: &str,
+    to: &str,
+    index: Option<usize>,
+    dry_run: bool,
+    scope: Scope,
+) -> CliResult<String> {
+    if !from.eq_ignore_ascii_case("text") {
+        return Err(CliError::user(format!(
+            "Unsupported source type: {} (only 'text' fragments can be converted)",
+            from
+        )));
+    }
+    if !matches!(
+        to.to_lowercase().as_str(),
+        "logic" | "guideline" | "quality" | "quality-standard"
+    ) {
+        return Err(CliError::user(format!(
+            "Unsupported target type: {} (expected logic, guideline or quality)",
+            to
+        )));
+    }
+
+    let expertise = get_expertise(app, id, scope).await?;
+
+    let original = expertise.clone();
+    let converted = match index {
+        Some(index) => app
+            .generator
+            .upgrade_fragment(expertise, index, to)
+            .await
+            .map_err(|e| CliError::user(format!("Failed to convert fragment: {}", e)))?,
+        None => {
+            if !original
+                .inner
+                .content
+                .iter()
+                .any(|w| matches!(w.fragment, KnowledgeFragment::Text(_)))
+            {
+                return Ok(format!("No text fragments to convert in {}", id));
+            }
+            app.generator
+                .upgrade_fragments(expertise, to)
+                .await
+                .map_err(|e| generator_error("Failed to convert fragments", e))?
+        }
+    };
+
+    let changes = format_changes(&original, &converted);
+    if changes.is_empty() {
+        return Ok(format!("No fragments were converted in {}", id));
+    }
+
+    if dry_run {
+        return Ok(format!(
+            "Dry run: would convert {} fragment(s) in {}\n\n{}",
+            changes.len(),
+            id,
+            changes.join("\n")
+        ));
+    }
+
+    // update() snapshots the current version before writing
+    app.db
+        .storage()
+        .update(converted.clone())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
+
+    Ok(format!(
+        "✓ Converted {} fragment(s) in {} → v{}\n\n{}",
+        changes.len(),
+        id,
+        converted.version(),
+        changes.join("\n")
+    ))
+}
+
+/// Describe fragments whose type changed between two versions of an expertise
+fn format_changes(original: &Expertise, converted: &Expertise) -> Vec<String> {
+    original
+        .inner
+        .content
+        .iter()
+        .zip(converted.inner.content.iter())
+        .enumerate()
+        .filter(|(_, (before, after))| before.fragment.type_label() != after.fragment.type_label())
+        .map(|(i, (before, after))| {
+            format!(
+                "[{}] {} → {}\n    before: {}\n    after:  {}",
+                i,
+                before.fragment.type_label(),
+                after.fragment.type_label(),
+                before.fragment.summary(),
+                after.fragment.summary()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_core::WeightedFragment;
+
+    #[test]
+    fn test_format_changes() {
+        let mut original = Expertise::new("rust-release", "1.0.0");
+        original.inner.content = vec![
+            WeightedFragment::new(KnowledgeFragment::Text("Keep this".to_string())),
+            WeightedFragment::new(KnowledgeFragment::Text("Bump, then publish".to_string())),
+        ];
+
+        let mut converted = original.clone();
+        converted.inner.content[1].fragment = KnowledgeFragment::Logic {
+            instruction: "Release a crate".to_string(),
+            steps: vec!["Bump version".to_string(), "cargo publish".to_string()],
+        };
+
+        let changes = format_changes(&original, &converted);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].starts_with("[1] "));
+    }
+}
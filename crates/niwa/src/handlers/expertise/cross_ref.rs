@@ -0,0 +1,149 @@
+//! Cross-ref subcommand - find expertises that reference one
+
+use crate::exit_code::not_found;
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{RelationType, Scope};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_cross_ref(
+    app: &AppState,
+    id: &str,
+    auto_link: bool,
+    min_confidence: f64,
+) -> CliResult<String> {
+    if !(0.0..=1.0).contains(&min_confidence) {
+        return Err(CliError::user(
+            "--min-confidence must be between 0.0 and 1.0",
+        ));
+    }
+
+    let mut found = false;
+    for scope in [Scope::Personal, Scope::Company, Scope::Project] {
+        if app
+            .db
+            .storage()
+            .exists(id, scope)
+            .await
+            .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+        {
+            found = true;
+            break;
+        }
+    }
+    if !found {
+        return Err(not_found(format!("Expertise not found: {}", id)));
+    }
+
+    let references = app
+        .db
+        .query()
+        .find_cross_references(id)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to find cross references: {}", e)))?;
+
+    if references.is_empty() {
+        return Ok(format!("No expertises reference '{}'.", id));
+    }
+
+    let existing: Vec<String> = app
+        .db
+        .graph()
+        .get_dependents(id)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Scope").fg(Color::Green),
+            Cell::new("Found In").fg(Color::Green),
+            Cell::new("Confidence").fg(Color::Green),
+            Cell::new("Linked").fg(Color::Green),
+        ]);
+
+    for reference in &references {
+        let mut found_in = Vec::new();
+        if reference.in_tags {
+            found_in.push("tags".to_string());
+        }
+        if reference.in_description {
+            found_in.push("description".to_string());
+        }
+        if reference.fragment_mentions > 0 {
+            found_in.push(format!("{} fragments", reference.fragment_mentions));
+        }
+
+        table.add_row(vec![
+            reference.id.clone(),
+            reference.scope.to_string(),
+            found_in.join(", "),
+            format!("{:.2}", reference.confidence()),
+            if existing.contains(&reference.id) {
+                "yes".to_string()
+            } else {
+                String::new()
+            },
+        ]);
+    }
+
+    let unlinked: Vec<_> = references
+        .iter()
+        .filter(|r| !existing.contains(&r.id))
+        .collect();
+
+    let mut output = format!(
+        "\n{}\n\nTotal: {} expertises reference {}",
+        table,
+        references.len(),
+        id
+    );
+
+    if !auto_link {
+        if !unlinked.is_empty() {
+            output.push_str("\n\nSuggested relations:\n");
+            for reference in &unlinked {
+                output.push_str(&format!(
+                    "  niwa link {} --to {} --type uses\n",
+                    reference.id, id
+                ));
+            }
+            output.push_str("\nOr re-run with --auto-link to create them.");
+        }
+        return Ok(output);
+    }
+
+    let mut linked = 0;
+    let mut skipped = 0;
+    output.push('\n');
+    for reference in unlinked {
+        if reference.confidence() < min_confidence {
+            skipped += 1;
+            continue;
+        }
+
+        match app
+            .db
+            .graph()
+            .create_relation(&reference.id, id, RelationType::Uses, None)
+            .await
+        {
+            Ok(()) => {
+                linked += 1;
+                output.push_str(&format!("\n✓ Linked {} -[uses]-> {}", reference.id, id));
+            }
+            Err(e) => {
+                output.push_str(&format!("\n⚠ Could not link {}: {}", reference.id, e));
+            }
+        }
+    }
+
+    output.push_str(&format!(
+        "\n\n{} relations created, {} below confidence {:.2}",
+        linked, skipped, min_confidence
+    ));
+    Ok(output)
+}
@@ -0,0 +1,29 @@
+//! Delete subcommand - remove an expertise
+
+use super::{confirm_locked_override, get_expertise, locked_error};
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_delete(
+    app: &AppState,
+    id: &str,
+    force: bool,
+    scope: Scope,
+) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+
+    let storage = app.db.storage();
+    let result = if expertise.metadata.locked {
+        if !force {
+            return Err(locked_error(id));
+        }
+        confirm_locked_override(id, "Delete")?;
+        storage.delete_forced(id, scope).await
+    } else {
+        storage.delete(id, scope).await
+    };
+    result.map_err(|e| CliError::system(format!("Failed to delete expertise: {}", e)))?;
+
+    Ok(format!("✓ Deleted {} (scope: {})", id, scope))
+}
@@ -0,0 +1,45 @@
+//! Description-edit subcommand - rewrite the description of an expertise
+
+use super::{edit_in_editor, get_expertise, locked_error};
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_description_edit(
+    app: &AppState,
+    id: &str,
+    text: Option<String>,
+    scope: Scope,
+) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+    if expertise.metadata.locked {
+        return Err(locked_error(id));
+    }
+    let current = expertise.description();
+
+    // Without --text, clap guarantees --editor was given
+    let description = match text {
+        Some(text) => text,
+        None => edit_in_editor(&format!("description-{}.txt", id), &current)?,
+    };
+    let description = description.trim();
+
+    if description.is_empty() {
+        return Err(CliError::user("Description must not be empty"));
+    }
+    if description == current.trim() {
+        return Ok(format!("Description of {} unchanged", id));
+    }
+
+    app.db
+        .storage()
+        .update_description(id, scope, description)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to update description: {}", e)))?;
+
+    Ok(format!(
+        "✓ Updated description of {} (previous saved as v{})",
+        id,
+        expertise.version()
+    ))
+}
@@ -0,0 +1,142 @@
+//! Extract-relations subcommand - find implicit relations with the LLM
+
+use super::get_expertise;
+use crate::render::to_json;
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{RelationMetadata, RelationType, Scope};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_extract_relations(
+    app: &AppState,
+    id: &str,
+    apply: bool,
+    scope: Scope,
+) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+
+    let mut known_ids: Vec<String> = app
+        .db
+        .storage()
+        .list_all()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?
+        .iter()
+        .map(|e| e.id().to_string())
+        .collect();
+    known_ids.sort();
+    known_ids.dedup();
+
+    let relations = app
+        .generator
+        .extract_implicit_relations(&expertise, &known_ids)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to extract relations: {}", e)))?;
+
+    if relations.is_empty() {
+        return Ok(format!("No implicit relations found in '{}'.", id));
+    }
+
+    let existing = app
+        .db
+        .graph()
+        .get_dependencies(id)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Target").fg(Color::Green),
+            Cell::new("Type").fg(Color::Green),
+            Cell::new("Confidence").fg(Color::Green),
+            Cell::new("Mentioned In").fg(Color::Green),
+            Cell::new("Linked").fg(Color::Green),
+        ]);
+
+    for relation in &relations {
+        table.add_row(vec![
+            relation.target_id_hint.clone(),
+            relation.relation_type.clone(),
+            format!("{:.2}", relation.confidence),
+            relation.source_fragment.clone(),
+            if existing.contains(&relation.target_id_hint) {
+                "yes".to_string()
+            } else {
+                String::new()
+            },
+        ]);
+    }
+
+    let mut output = format!(
+        "\n{}\n\nTotal: {} implicit relations in {}",
+        table,
+        relations.len(),
+        id
+    );
+
+    if !apply {
+        if relations
+            .iter()
+            .any(|r| !existing.contains(&r.target_id_hint))
+        {
+            output.push_str("\n\nRe-run with --apply to create the unlinked relations.");
+        }
+        return Ok(output);
+    }
+
+    let mut linked = 0;
+    let mut seen = existing;
+    output.push('\n');
+    for relation in relations {
+        if seen.contains(&relation.target_id_hint) {
+            continue;
+        }
+
+        let relation_type = match relation.relation_type.parse::<RelationType>() {
+            Ok(relation_type) => relation_type,
+            Err(e) => {
+                output.push_str(&format!("\n⚠ Skipped {}: {}", relation.target_id_hint, e));
+                continue;
+            }
+        };
+
+        let metadata = RelationMetadata {
+            reason: Some(relation.source_fragment.clone()),
+            confidence: Some(f64::from(relation.confidence)),
+            source: Some("extract-relations".to_string()),
+            ..Default::default()
+        };
+        match app
+            .db
+            .graph()
+            .create_relation(
+                id,
+                &relation.target_id_hint,
+                relation_type,
+                Some(metadata.to_json()),
+            )
+            .await
+        {
+            Ok(()) => {
+                linked += 1;
+                output.push_str(&format!(
+                    "\n✓ Linked {} -[{}]-> {}",
+                    id, relation_type, relation.target_id_hint
+                ));
+            }
+            Err(e) => {
+                output.push_str(&format!(
+                    "\n⚠ Could not link {}: {}",
+                    relation.target_id_hint, e
+                ));
+            }
+        }
+        seen.push(relation.target_id_hint);
+    }
+
+    output.push_str(&format!("\n\n{} relations created", linked));
+    Ok(output)
+}
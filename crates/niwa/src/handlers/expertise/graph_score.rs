@@ -0,0 +1,73 @@
+//! Graph-score subcommand - rank expertises by PageRank
+
+use crate::exit_code::not_found;
+use crate::handlers::graph::{
+    rank_expertises, RankedExpertise, PAGERANK_DAMPING, PAGERANK_ITERATIONS,
+};
+use crate::render::OutputFormat;
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+use std::collections::HashSet;
+
+pub(super) async fn handle_graph_score(
+    app: &AppState,
+    id: Option<&str>,
+    all: bool,
+    top: usize,
+    scope: Option<Scope>,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let storage = app.db.storage();
+    let expertises = match scope {
+        Some(scope) => storage.list(scope).await,
+        None => storage.list_all().await,
+    }
+    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+    let graph = app.db.graph();
+    let relations = graph
+        .all_relations()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+    let scores = graph
+        .pagerank(PAGERANK_ITERATIONS, PAGERANK_DAMPING)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to score expertises: {}", e)))?;
+    let mut ranked = rank_expertises(scores, &expertises, &relations);
+
+    if let Some(id) = id {
+        return match ranked.into_iter().find(|r| r.id == id) {
+            Some(score) => Ok([score].render(format)),
+            None if expertises.iter().any(|e| e.id() == id) => Ok(format!(
+                "{} has no relations (score 0).\nUse 'niwa link' to create relations.",
+                id
+            )),
+            None => Err(not_found(format!("Expertise not found: {}", id))),
+        };
+    }
+
+    if all {
+        let scored: HashSet<String> = ranked.iter().map(|r| r.id.clone()).collect();
+        for expertise in expertises.iter().filter(|e| !scored.contains(e.id())) {
+            ranked.push(RankedExpertise {
+                rank: ranked.len() + 1,
+                id: expertise.id().to_string(),
+                scope: expertise.metadata.scope.to_string(),
+                score: 0.0,
+                incoming: 0,
+                outgoing: 0,
+            });
+        }
+    } else {
+        ranked.truncate(top);
+    }
+
+    if ranked.is_empty() && format == OutputFormat::Text {
+        return Ok(format!(
+            "Found {} expertises but no relations to score.\nUse 'niwa link' to create relations.",
+            expertises.len()
+        ));
+    }
+    Ok(ranked.render(format))
+}
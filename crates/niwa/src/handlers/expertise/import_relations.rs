@@ -0,0 +1,122 @@
+//! Import-relations subcommand - create relations from a CSV file
+
+use crate::state::AppState;
+use niwa_core::{BulkRelationInput, BulkRelationReport};
+use sen::{CliError, CliResult};
+use std::path::Path;
+
+pub(super) async fn handle_import_relations(
+    app: &AppState,
+    file: &Path,
+    dry_run: bool,
+    skip_cycles: bool,
+) -> CliResult<String> {
+    let text = std::fs::read_to_string(file)
+        .map_err(|e| CliError::user(format!("Failed to read {}: {}", file.display(), e)))?;
+    let inputs = parse_relations_csv(&text)
+        .map_err(|e| CliError::user(format!("Invalid CSV in {}: {}", file.display(), e)))?;
+    let rows = inputs.len();
+
+    let graph = app.db.graph();
+    let report = if dry_run {
+        graph.check_bulk_relations(inputs, skip_cycles).await
+    } else {
+        graph.bulk_create_relations(inputs, skip_cycles).await
+    }
+    .map_err(|e| match e {
+        niwa_core::Error::CircularDependency { from, to } => CliError::user(format!(
+            "{} -> {} would create a cycle; nothing was imported. \
+             Use --skip-cycles to skip such rows.",
+            from, to
+        )),
+        e => CliError::system(format!("Failed to import relations: {}", e)),
+    })?;
+
+    Ok(format_bulk_report(&report, rows, dry_run))
+}
+
+/// Read `from_id,to_id,relation_type,metadata` rows (with a header row)
+fn parse_relations_csv(text: &str) -> Result<Vec<BulkRelationInput>, csv::Error> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .deserialize()
+        .collect()
+}
+
+pub(crate) fn format_bulk_report(
+    report: &BulkRelationReport,
+    rows: usize,
+    dry_run: bool,
+) -> String {
+    let mut output = format!(
+        "{} {} of {} relations",
+        if dry_run {
+            "Would create"
+        } else {
+            "✓ Created"
+        },
+        report.created,
+        rows
+    );
+    for (count, reason) in [
+        (report.skipped_duplicate, "already exist"),
+        (report.skipped_cycle, "would create a cycle"),
+        (report.skipped_missing_id, "reference a missing expertise"),
+    ] {
+        if count > 0 {
+            output.push_str(&format!("\n  Skipped {} that {}", count, reason));
+        }
+    }
+    if !report.errors.is_empty() {
+        output.push_str(&format!("\n  {} invalid:", report.errors.len()));
+        for error in &report.errors {
+            output.push_str(&format!("\n    - {}", error));
+        }
+    }
+    if dry_run {
+        output.push_str("\n\n(dry run - no relations created)");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_relations_csv() {
+        let csv = "from_id,to_id,relation_type,metadata\n\
+                   rust-expert, error-handling ,uses,\n\
+                   rust-expert,tokio,requires,\"{\"\"confidence\"\":0.9}\"\n";
+        let inputs = parse_relations_csv(csv).unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].to_id, "error-handling");
+        assert_eq!(inputs[0].metadata, None);
+        assert_eq!(inputs[1].metadata.as_deref(), Some(r#"{"confidence":0.9}"#));
+
+        // A row missing required columns is rejected
+        let short = "from_id,to_id,relation_type,metadata\ntokio,async\n";
+        assert!(parse_relations_csv(short).is_err());
+    }
+
+    #[test]
+    fn test_format_bulk_report() {
+        let report = BulkRelationReport {
+            created: 3,
+            skipped_duplicate: 1,
+            skipped_cycle: 0,
+            skipped_missing_id: 2,
+            errors: vec!["Relation 4: Invalid relation type: depends".to_string()],
+        };
+        assert_eq!(
+            format_bulk_report(&report, 7, true),
+            "Would create 3 of 7 relations\n  \
+             Skipped 1 that already exist\n  \
+             Skipped 2 that reference a missing expertise\n  \
+             1 invalid:\n    - Relation 4: Invalid relation type: depends\n\n\
+             (dry run - no relations created)"
+        );
+    }
+}
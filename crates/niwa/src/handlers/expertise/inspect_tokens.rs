@@ -0,0 +1,201 @@
+//! Inspect-tokens subcommand - show where the tokens of an expertise go
+
+use crate::render::{color_enabled, truncate_chars};
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::Expertise;
+use niwa_generator::ExpertiseGenerator;
+
+/// Token usage of one field of an expertise
+struct TokenField {
+    label: String,
+    chars: usize,
+    tokens: usize,
+    /// Index into the fragments, for fragment fields
+    fragment: Option<usize>,
+}
+
+impl TokenField {
+    fn new(label: String, text: &str, fragment: Option<usize>) -> Self {
+        Self {
+            label,
+            chars: text.chars().count(),
+            tokens: ExpertiseGenerator::estimate_tokens(text),
+            fragment,
+        }
+    }
+}
+
+/// Description, tags and each fragment of an expertise with their token counts
+fn token_fields(expertise: &Expertise) -> Vec<TokenField> {
+    let mut fields = vec![
+        TokenField::new("Description".to_string(), &expertise.description(), None),
+        TokenField::new("Tags".to_string(), &expertise.tags().join(", "), None),
+    ];
+    for (i, weighted) in expertise.inner.content.iter().enumerate() {
+        let text = weighted.fragment.to_prompt();
+        let label = format!(
+            "Fragment #{}: {}",
+            i + 1,
+            truncate_chars(&text.split_whitespace().collect::<Vec<_>>().join(" "), 30)
+        );
+        fields.push(TokenField::new(label, &text, Some(i)));
+    }
+    fields
+}
+
+/// Fragments that fit in `budget` tokens, taking the highest weight per token first
+///
+/// `fragments` holds `(tokens, weight)` per fragment. A fragment too large
+/// for the remaining budget is skipped and smaller ones are still tried.
+/// Returns the chosen indices in the order they were taken.
+fn fit_budget(fragments: &[(usize, u32)], budget: usize) -> Vec<usize> {
+    let density = |(tokens, weight): (usize, u32)| f64::from(weight) / tokens.max(1) as f64;
+    let mut order: Vec<usize> = (0..fragments.len()).collect();
+    order.sort_by(|&a, &b| {
+        density(fragments[b])
+            .total_cmp(&density(fragments[a]))
+            .then(a.cmp(&b))
+    });
+
+    let mut remaining = budget;
+    order
+        .into_iter()
+        .filter(|&i| {
+            let tokens = fragments[i].0;
+            let fits = tokens <= remaining;
+            if fits {
+                remaining -= tokens;
+            }
+            fits
+        })
+        .collect()
+}
+
+pub(super) fn inspect_tokens(
+    expertise: &Expertise,
+    model: &str,
+    threshold: Option<usize>,
+    budget: Option<usize>,
+) -> String {
+    let fields = token_fields(expertise);
+    let sum: usize = fields.iter().map(|f| f.tokens).sum();
+    let full = expertise.to_prompt_string();
+    let full_tokens = ExpertiseGenerator::estimate_tokens(&full);
+
+    // The three largest fragments are highlighted
+    let mut largest: Vec<&TokenField> = fields.iter().filter(|f| f.fragment.is_some()).collect();
+    largest.sort_by(|a, b| b.tokens.cmp(&a.tokens));
+    let largest: Vec<usize> = largest.iter().take(3).filter_map(|f| f.fragment).collect();
+
+    let color = color_enabled();
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Field").fg(Color::Green),
+            Cell::new("Characters").fg(Color::Green),
+            Cell::new("Est. Tokens").fg(Color::Green),
+        ]);
+    for field in &fields {
+        let over = field.fragment.is_some() && threshold.is_some_and(|t| field.tokens > t);
+        let top = field.fragment.is_some_and(|i| largest.contains(&i));
+        let mut tokens = Cell::new(field.tokens);
+        if color && over {
+            tokens = tokens.fg(Color::Red);
+        } else if color && top {
+            tokens = tokens.fg(Color::Yellow);
+        }
+        let label = match (over, top) {
+            (true, _) => format!("{} (over {})", field.label, threshold.unwrap_or_default()),
+            (false, true) => format!("{} ▲", field.label),
+            _ => field.label.clone(),
+        };
+        table.add_row(vec![Cell::new(label), Cell::new(field.chars), tokens]);
+    }
+    table.add_row(vec![
+        Cell::new("Sum of fields"),
+        Cell::new(fields.iter().map(|f| f.chars).sum::<usize>()),
+        Cell::new(sum),
+    ]);
+    table.add_row(vec![
+        Cell::new("Full prompt (to_prompt_string)"),
+        Cell::new(full.chars().count()),
+        Cell::new(full_tokens),
+    ]);
+
+    let mut output = format!(
+        "Token usage of {} (estimated for {})\n\n{}\n",
+        expertise.id(),
+        model,
+        table
+    );
+    if full_tokens != sum {
+        output.push_str(&format!(
+            "\nPrompt formatting adds {} token(s) beyond the fields.\n",
+            full_tokens as i64 - sum as i64
+        ));
+    }
+    output.push_str("▲ = largest fragments\n");
+
+    if let Some(budget) = budget {
+        let fragments: Vec<(usize, u32)> = expertise
+            .inner
+            .content
+            .iter()
+            .zip(fields.iter().filter(|f| f.fragment.is_some()))
+            .map(|(w, f)| (f.tokens, ExpertiseGenerator::priority_weight(&w.priority)))
+            .collect();
+        let chosen = fit_budget(&fragments, budget);
+        let used: usize = chosen.iter().map(|&i| fragments[i].0).sum();
+        output.push_str(&format!(
+            "\nBudget {}: {} of {} fragments fit ({} tokens, by priority per token)\n",
+            budget,
+            chosen.len(),
+            fragments.len(),
+            used
+        ));
+        for i in chosen {
+            output.push_str(&format!(
+                "  • #{} ({} tokens, weight {})\n",
+                i + 1,
+                fragments[i].0,
+                fragments[i].1
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_core::{KnowledgeFragment, WeightedFragment};
+
+    #[test]
+    fn test_fit_budget() {
+        // Densities: 0.04, 0.5, 0.1, 0.2
+        let fragments = [(100, 4), (4, 2), (10, 1), (20, 4)];
+        assert_eq!(fit_budget(&fragments, 30), vec![1, 3]);
+        assert_eq!(fit_budget(&fragments, 34), vec![1, 3, 2]);
+        assert_eq!(fit_budget(&fragments, 1000), vec![1, 3, 2, 0]);
+        assert!(fit_budget(&fragments, 3).is_empty());
+    }
+
+    #[test]
+    fn test_token_fields() {
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        expertise.inner.description = Some("Idiomatic Rust".to_string());
+        expertise.inner.tags = vec!["rust".to_string(), "errors".to_string()];
+        expertise.inner.content = vec![WeightedFragment::new(KnowledgeFragment::Text(
+            "Prefer ? over\nunwrap".to_string(),
+        ))];
+
+        let fields = token_fields(&expertise);
+        assert_eq!(fields.len(), 3);
+        assert_eq!((fields[0].chars, fields[0].tokens), (14, 4));
+        assert_eq!(fields[1].chars, "rust, errors".len());
+        assert_eq!(fields[2].label, "Fragment #1: Prefer ? over unwrap");
+        assert_eq!(fields[2].fragment, Some(0));
+    }
+}
@@ -0,0 +1,178 @@
+//! Link-map subcommand - render the relations around an expertise as a document
+
+use crate::exit_code::not_found;
+use crate::state::AppState;
+use clap::ValueEnum;
+use niwa_core::{LinkMap, LinkNode, Scope};
+use sen::{CliError, CliResult};
+use std::collections::HashSet;
+
+/// Output format of `expertise link-map`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkMapFormat {
+    Markdown,
+    Html,
+}
+
+pub(super) async fn handle_link_map(
+    app: &AppState,
+    id: &str,
+    depth: usize,
+    all_scopes: bool,
+    format: LinkMapFormat,
+    scope: Scope,
+) -> CliResult<String> {
+    let storage = app.db.storage();
+    let expertises = if all_scopes {
+        storage.list_all().await
+    } else {
+        storage.list(scope).await
+    }
+    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+    let expertise = expertises.iter().find(|e| e.id() == id).ok_or_else(|| {
+        if all_scopes {
+            not_found(format!("Expertise not found: {} (in any scope)", id))
+        } else {
+            not_found(format!("Expertise not found: {} (scope: {})", id, scope))
+        }
+    })?;
+
+    let mut map = app
+        .db
+        .graph()
+        .build_link_map(id, depth)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to build link map: {}", e)))?;
+    let known: HashSet<&str> = expertises.iter().map(|e| e.id()).collect();
+    map.retain(|id| known.contains(id));
+
+    Ok(match format {
+        LinkMapFormat::Markdown => link_map_markdown(&map, expertise.description()),
+        LinkMapFormat::Html => link_map_html(&map, expertise.description()),
+    })
+}
+
+/// Render a link map as Markdown with `[id](id)` links
+fn link_map_markdown(map: &LinkMap, description: &str) -> String {
+    fn list(nodes: &[LinkNode], indent: usize, output: &mut String) {
+        for node in nodes {
+            output.push_str(&format!(
+                "{}- [{}]({}) ({})\n",
+                "  ".repeat(indent),
+                node.id,
+                node.id,
+                node.relation_type
+            ));
+            list(&node.children, indent + 1, output);
+        }
+    }
+
+    let mut output = format!("# {}\n\n", map.id);
+    if !description.trim().is_empty() {
+        output.push_str(&format!("{}\n\n", description.trim()));
+    }
+    for (title, nodes) in [
+        ("Dependencies", &map.dependencies),
+        ("Dependents", &map.dependents),
+    ] {
+        output.push_str(&format!("## {}\n\n", title));
+        if nodes.is_empty() {
+            output.push_str("_None_\n");
+        } else {
+            list(nodes, 0, &mut output);
+        }
+        output.push('\n');
+    }
+    output.trim_end().to_string()
+}
+
+/// Render a link map as an HTML fragment with `<a href="id">` links
+fn link_map_html(map: &LinkMap, description: &str) -> String {
+    fn list(nodes: &[LinkNode], output: &mut String) {
+        output.push_str("<ul>\n");
+        for node in nodes {
+            let id = escape_html(&node.id);
+            output.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> ({})",
+                id, id, node.relation_type
+            ));
+            if !node.children.is_empty() {
+                output.push('\n');
+                list(&node.children, output);
+            }
+            output.push_str("</li>\n");
+        }
+        output.push_str("</ul>\n");
+    }
+
+    let mut output = format!("<h1>{}</h1>\n", escape_html(&map.id));
+    if !description.trim().is_empty() {
+        output.push_str(&format!("<p>{}</p>\n", escape_html(description.trim())));
+    }
+    for (title, nodes) in [
+        ("Dependencies", &map.dependencies),
+        ("Dependents", &map.dependents),
+    ] {
+        output.push_str(&format!("<h2>{}</h2>\n", title));
+        if nodes.is_empty() {
+            output.push_str("<p><em>None</em></p>\n");
+        } else {
+            list(nodes, &mut output);
+        }
+    }
+    output.trim_end().to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_core::RelationType;
+
+    fn sample_link_map() -> LinkMap {
+        let relations: Vec<niwa_core::graph::Relation> = [
+            ("web", "http", RelationType::Uses),
+            ("http", "tls", RelationType::Requires),
+            ("web", "api", RelationType::Extends),
+        ]
+        .into_iter()
+        .map(|(from, to, relation_type)| niwa_core::graph::Relation {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type,
+            metadata: None,
+            created_at: 0,
+        })
+        .collect();
+        LinkMap::from_relations(&relations, "web", 2)
+    }
+
+    #[test]
+    fn test_link_map_markdown() {
+        assert_eq!(
+            link_map_markdown(&sample_link_map(), "Web <services>"),
+            "# web\n\n\
+             Web <services>\n\n\
+             ## Dependencies\n\n\
+             - [api](api) (extends)\n\
+             - [http](http) (uses)\n  \
+             - [tls](tls) (requires)\n\n\
+             ## Dependents\n\n\
+             _None_"
+        );
+    }
+
+    #[test]
+    fn test_link_map_html() {
+        let html = link_map_html(&sample_link_map(), "Web <services>");
+        assert!(html.starts_with("<h1>web</h1>\n<p>Web &lt;services&gt;</p>\n"));
+        assert!(html.contains("<li><a href=\"http\">http</a> (uses)\n<ul>\n<li><a href=\"tls\">"));
+        assert!(html.ends_with("<h2>Dependents</h2>\n<p><em>None</em></p>"));
+    }
+}
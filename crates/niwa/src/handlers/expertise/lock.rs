@@ -0,0 +1,45 @@
+//! Lock and unlock subcommands - protect expertises from changes
+
+use super::get_expertise;
+use crate::exit_code::not_found;
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_lock(
+    app: &AppState,
+    id: &str,
+    reason: Option<&str>,
+    scope: Scope,
+) -> CliResult<String> {
+    app.db
+        .storage()
+        .lock(id, scope, reason)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::NotFound { .. } => {
+                not_found(format!("Expertise not found: {} (scope: {})", id, scope))
+            }
+            e => CliError::system(format!("Failed to lock expertise: {}", e)),
+        })?;
+
+    match reason {
+        Some(reason) => Ok(format!("✓ Locked {} ({})", id, reason)),
+        None => Ok(format!("✓ Locked {}", id)),
+    }
+}
+
+pub(super) async fn handle_unlock(app: &AppState, id: &str, scope: Scope) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+    if !expertise.metadata.locked {
+        return Ok(format!("{} is not locked", id));
+    }
+
+    app.db
+        .storage()
+        .unlock(id, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to unlock expertise: {}", e)))?;
+
+    Ok(format!("✓ Unlocked {}", id))
+}
@@ -0,0 +1,1299 @@
+//! Expertise maintenance commands
+
+mod auto_version;
+mod batch_describe;
+mod context_export;
+mod convert;
+mod cross_ref;
+mod delete;
+mod description_edit;
+mod extract_relations;
+mod graph_score;
+mod import_relations;
+mod inspect_tokens;
+mod link_map;
+mod lock;
+mod playground;
+mod publish;
+mod remind;
+mod rename_tag;
+mod score_all;
+mod split_by_tag;
+mod touch;
+mod transfer;
+mod translate;
+mod verify_json;
+mod version_compare;
+mod word_count;
+
+use crate::exit_code::not_found;
+use crate::render::OutputFormat;
+use crate::state::AppState;
+use crate::tui::run_cluster_browser;
+use clap::{Parser, Subcommand, ValueEnum};
+use niwa_core::{
+    ConflictPolicy, Expertise, ExpertiseStatus, LinkMap, Priority, RepeatInterval, ResolveResult,
+    Scope,
+};
+use sen::{Args, CliError, CliResult, State};
+use std::path::PathBuf;
+
+use auto_version::handle_auto_version;
+use batch_describe::handle_batch_describe;
+use context_export::handle_context_export;
+use convert::handle_convert;
+use cross_ref::handle_cross_ref;
+use delete::handle_delete;
+use description_edit::handle_description_edit;
+use extract_relations::handle_extract_relations;
+use graph_score::handle_graph_score;
+use import_relations::handle_import_relations;
+use inspect_tokens::inspect_tokens;
+use link_map::handle_link_map;
+use lock::{handle_lock, handle_unlock};
+use playground::handle_playground;
+use publish::{handle_publish, handle_published, handle_unpublish};
+use remind::{handle_remind, handle_remind_complete, handle_reminders, parse_delay};
+use rename_tag::handle_rename_tag;
+use score_all::handle_score_all;
+use split_by_tag::handle_split_by_tag;
+use touch::handle_touch;
+use transfer::handle_transfer;
+use translate::handle_translate;
+use verify_json::handle_verify_json;
+use version_compare::handle_version_compare;
+use word_count::handle_word_count;
+
+pub(crate) use import_relations::format_bulk_report;
+pub use link_map::LinkMapFormat;
+pub use publish::PublishFormat;
+pub use remind::ReminderDelay;
+pub(crate) use version_compare::format_comparison;
+
+/// Maintain stored expertises
+///
+/// Usage:
+///   niwa expertise touch rust-expert
+///   niwa expertise touch rust-expert --scope company
+///   niwa expertise convert rust-expert --to logic --dry-run
+///   niwa expertise convert rust-expert --to guideline --index 2
+///   niwa expertise publish rust-expert --public --title "Rust tips"
+///   niwa expertise published
+///   niwa expertise unpublish rust-expert
+///   niwa expertise rename-tag --old k8s --new kubernetes --dry-run
+///   niwa expertise cross-ref rust-expert --auto-link
+///   niwa expertise playground rust-expert --compare
+///   niwa expertise extract-relations rust-expert --apply
+///   niwa expertise description-edit rust-expert --text "Idiomatic Rust patterns"
+///   niwa expertise description-edit rust-expert --editor
+///   niwa expertise lock rust-expert --reason "Reviewed 2025-01-15"
+///   niwa expertise unlock rust-expert
+///   niwa expertise delete rust-expert
+///   niwa expertise split-by-tag web-dev --max 3 --dry-run
+///   niwa expertise version-compare rust-expert 1.0.0 2.0.0
+///   niwa expertise version-compare rust-expert 1.0.0 2.0.0 --format json
+///   niwa expertise cluster-visualize --scope personal
+///   niwa expertise word-count --scope personal --tag rust --histogram
+///   niwa expertise transfer rust-expert --to-db ~/.niwa/work.db --move
+///   niwa expertise verify-json --repair
+///   niwa expertise remind rust-expert --in 7d --message "Review before sprint planning"
+///   niwa expertise remind rust-expert --in 1m --repeat monthly
+///   niwa expertise reminders --due
+///   niwa expertise reminders --check && notify-send "NIWA: Review due"
+///   niwa expertise remind-complete 3
+///   niwa expertise graph-score --top 10
+///   niwa expertise graph-score rust-expert
+///   niwa expertise translate rust-error-handling --lang ja
+///   niwa expertise translate rust-error-handling --lang ja --id rust-error-handling-ja
+///   niwa expertise context-export rust-expert --max-tokens 2000 --xml
+///   niwa expertise context-export rust-expert --clipboard
+///   niwa expertise context-export rust-expert --max-tokens 500 --min-priority high
+///   niwa expertise link-map rust-expert --depth 2 > KNOWLEDGE.md
+///   niwa expertise link-map rust-expert --all-scopes --format html
+///   niwa expertise batch-describe --missing-only --dry-run
+///   niwa expertise batch-describe --scope company --concurrency 4
+///   niwa expertise inspect-tokens rust-expert --threshold 200
+///   niwa expertise inspect-tokens rust-expert --budget 1000
+///   niwa expertise score-all --scope personal --recalculate
+///   niwa expertise score-all --quality --export-csv scores.csv
+///   niwa expertise import-relations knowledge-map.csv --dry-run
+///   niwa expertise import-relations knowledge-map.csv --skip-cycles
+///   niwa expertise auto-version --scope personal --dry-run
+#[derive(Parser, Debug)]
+pub struct ExpertiseArgs {
+    #[command(subcommand)]
+    pub command: Option<ExpertiseCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExpertiseCommand {
+    /// Mark an expertise as reviewed (updates the timestamp only)
+    Touch {
+        /// Expertise ID
+        id: String,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Convert text fragments into richer fragment types using the LLM
+    Convert {
+        /// Expertise ID
+        id: String,
+
+        /// Source fragment type (only "text" is supported)
+        #[arg(long, default_value = "text")]
+        from: String,
+
+        /// Target fragment type (logic, guideline, quality)
+        #[arg(long)]
+        to: String,
+
+        /// Convert only the fragment at this index (0-based)
+        #[arg(long)]
+        index: Option<usize>,
+
+        /// Preview the converted fragments without saving
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Publish an expertise as a GitHub Gist (requires GITHUB_TOKEN)
+    Publish {
+        /// Expertise ID
+        id: String,
+
+        /// Gist file format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: PublishFormat,
+
+        /// Create a public Gist
+        #[arg(long, conflicts_with = "private")]
+        public: bool,
+
+        /// Create a secret Gist (default)
+        #[arg(long)]
+        private: bool,
+
+        /// Gist description (defaults to the expertise description)
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// List published expertises with their URLs
+    Published,
+
+    /// Delete the published Gist of an expertise
+    Unpublish {
+        /// Expertise ID
+        id: String,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Rename a tag across all expertises
+    RenameTag {
+        /// Tag to rename
+        #[arg(long)]
+        old: String,
+
+        /// New tag name
+        #[arg(long)]
+        new: String,
+
+        /// Only rename within this scope (default: all scopes)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+
+        /// List affected expertises without renaming
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Find expertises whose tags, description or fragments mention an ID
+    CrossRef {
+        /// Expertise ID to look for
+        id: String,
+
+        /// Create "uses" relations from each referencing expertise
+        #[arg(long)]
+        auto_link: bool,
+
+        /// Minimum confidence (0.0-1.0) for --auto-link
+        #[arg(long, default_value = "0.7", requires = "auto_link")]
+        min_confidence: f64,
+    },
+
+    /// Chat with the LLM using an expertise as context
+    Playground {
+        /// Expertise ID
+        id: String,
+
+        /// Also answer without the expertise and show both side by side
+        #[arg(long)]
+        compare: bool,
+
+        /// Allow a deprecated expertise
+        #[arg(long)]
+        include_deprecated: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Find relations that fragment text mentions without a graph edge
+    ExtractRelations {
+        /// Expertise ID
+        id: String,
+
+        /// Create the found relations in the graph
+        #[arg(long)]
+        apply: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Replace the description without running the LLM
+    DescriptionEdit {
+        /// Expertise ID
+        id: String,
+
+        /// New description
+        #[arg(long, required_unless_present = "editor", conflicts_with = "editor")]
+        text: Option<String>,
+
+        /// Edit the current description in $EDITOR
+        #[arg(long)]
+        editor: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Protect an expertise from updates, improvements and deletion
+    Lock {
+        /// Expertise ID
+        id: String,
+
+        /// Why the expertise is locked
+        #[arg(long)]
+        reason: Option<String>,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Remove the lock from an expertise
+    Unlock {
+        /// Expertise ID
+        id: String,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Split a multi-topic expertise into focused parts using the LLM
+    SplitByTag {
+        /// Expertise ID
+        id: String,
+
+        /// Minimum number of parts
+        #[arg(long, default_value_t = 2)]
+        min: usize,
+
+        /// Maximum number of parts
+        #[arg(long, default_value_t = 5)]
+        max: usize,
+
+        /// Show the proposed parts without saving
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete the original after storing the parts (no relations are kept)
+        #[arg(long)]
+        delete_original: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Delete an expertise
+    Delete {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Delete even if the expertise is locked (asks for confirmation)
+        #[arg(long)]
+        force: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Show what changed between two versions of an expertise
+    VersionCompare {
+        /// Expertise ID
+        id: String,
+
+        /// Older version (e.g. 1.0.0)
+        v1: String,
+
+        /// Newer version (e.g. 2.0.0)
+        v2: String,
+
+        /// Output format (default: text, or json with --agent-mode)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Browse communities of related expertises in an interactive terminal view
+    ///
+    /// Arrow keys navigate, Enter shows the highlighted expertise, `/` searches
+    /// by full text, `l` asks the LinkerAgent for link suggestions, `g` lists
+    /// the relations in the community and `q` quits.
+    ClusterVisualize {
+        /// Filter by scope (personal/me, company/org, project/proj)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Show corpus-wide word count statistics of text fragments
+    WordCount {
+        /// Filter by scope (personal/me, company/org, project/proj)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+
+        /// Only count expertises with this tag (repeat to require several)
+        #[arg(long)]
+        tag: Vec<String>,
+
+        /// Add a histogram of fragment lengths in 10-character buckets (text output)
+        #[arg(long)]
+        histogram: bool,
+
+        /// Output format (default: text, or json with --agent-mode)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Copy or move an expertise to another database
+    ///
+    /// Tags and version history go along. Relations are kept only when the
+    /// expertise at the other end is already in the target database.
+    Transfer {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Target database file (e.g. ~/.niwa/work.db)
+        #[arg(long)]
+        to_db: PathBuf,
+
+        /// Keep the expertise in this database (default)
+        #[arg(long, conflicts_with = "move_")]
+        copy: bool,
+
+        /// Delete the expertise from this database after the transfer
+        #[arg(long = "move")]
+        move_: bool,
+
+        /// What to do if the target already has the id (fail, skip, overwrite)
+        #[arg(long, default_value = "fail")]
+        on_conflict: ConflictPolicy,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Find stored expertises whose JSON no longer parses
+    ///
+    /// With --repair, rows are rebuilt from their bare expertise content.
+    /// Rows that can't be repaired are flagged and hidden from queries.
+    VerifyJson {
+        /// Try to repair each malformed row
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Set a reminder to review an expertise
+    Remind {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// When the review is due, from now (e.g. 7d, 2w, 1m for one month)
+        #[arg(long = "in", value_name = "DURATION", value_parser = parse_delay)]
+        in_: ReminderDelay,
+
+        /// Note shown with the reminder
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Come due again after completion (daily, weekly, monthly)
+        #[arg(long)]
+        repeat: Option<RepeatInterval>,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// List pending review reminders
+    Reminders {
+        /// Only show reminders that are past due
+        #[arg(long)]
+        due: bool,
+
+        /// Print nothing if no reminder is due and exit 0, otherwise exit 1 (for cron)
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Mark a reminder as done (recurring reminders move to their next due date)
+    RemindComplete {
+        /// Reminder ID (as shown by `expertise reminders`)
+        reminder_id: i64,
+    },
+
+    /// Score expertises by PageRank importance in the relation graph
+    ///
+    /// A high score means many other (important) expertises use, extend or
+    /// require it.
+    GraphScore {
+        /// Show the score of this expertise only (or a unique prefix of its ID)
+        id: Option<String>,
+
+        /// List every expertise, including those without relations (score 0)
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+
+        /// Number of top-scoring expertises to list
+        #[arg(long, default_value_t = 10, conflicts_with_all = ["id", "all"])]
+        top: usize,
+
+        /// Filter by scope (personal/me, company/org, project/proj)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+
+        /// Output format (default: text, or json with --agent-mode)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Translate an expertise into another language (technical terms stay in English)
+    Translate {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Target language code (e.g. ja, de, zh)
+        #[arg(long)]
+        lang: String,
+
+        /// ID for the translated copy (default: <id>-<lang>)
+        #[arg(long = "id", value_name = "NEW_ID")]
+        new_id: Option<String>,
+
+        /// Scope of the expertise to translate, where the copy is also stored
+        /// (default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Print a compact context block for injecting into an LLM prompt
+    ///
+    /// Fragments are ordered by priority and the lowest-priority ones are
+    /// dropped to fit the token budget. Critical fragments are always kept.
+    ContextExport {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Token budget (estimated at about four characters per token)
+        #[arg(long, default_value_t = 4000)]
+        max_tokens: usize,
+
+        /// Leave out fragments below this priority
+        #[arg(long, value_enum, default_value_t = FragmentPriority::Low)]
+        min_priority: FragmentPriority,
+
+        /// Wrap the block in <niwa_expertise id="..."> tags
+        #[arg(long)]
+        xml: bool,
+
+        /// Copy the block to the clipboard instead of printing it
+        #[arg(long)]
+        clipboard: bool,
+
+        /// Allow a deprecated expertise
+        #[arg(long)]
+        include_deprecated: bool,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Render the dependencies and dependents of an expertise as a document
+    ///
+    /// Sections are sorted by ID, so regenerating an unchanged graph gives
+    /// an identical file.
+    LinkMap {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Levels of relations to follow in each direction
+        #[arg(long, default_value_t = 2)]
+        depth: usize,
+
+        /// Include related expertises from every scope, not just --scope
+        #[arg(long)]
+        all_scopes: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: LinkMapFormat,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Generate descriptions with the LLM for every expertise in a scope
+    ///
+    /// Locked expertises and those without fragments are skipped. The
+    /// previous description is kept as a version.
+    BatchDescribe {
+        /// Only describe expertises whose description is empty
+        #[arg(long)]
+        missing_only: bool,
+
+        /// List the expertises that would be described without calling the LLM
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Maximum number of concurrent LLM calls
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..))]
+        concurrency: u8,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Show where the tokens of an expertise are spent, field by field
+    ///
+    /// Counts are estimates (about four characters per token).
+    InspectTokens {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Model the estimate is for (shown in the report)
+        #[arg(long, default_value = "claude-sonnet-4-5")]
+        model: String,
+
+        /// Mark fragments above this many tokens in red
+        #[arg(long)]
+        threshold: Option<usize>,
+
+        /// Show which fragments fit in this many tokens, highest priority per token first
+        #[arg(long)]
+        budget: Option<usize>,
+
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+    },
+
+    /// Compute health and PageRank scores for every expertise and cache them
+    ///
+    /// Scores cached within the last hour are reused unless --recalculate is
+    /// given. `niwa list --sort health-score` and `--sort pagerank` read the
+    /// cache.
+    ScoreAll {
+        /// Filter by scope (personal/me, company/org, project/proj)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+
+        /// Recompute even if cached scores are recent
+        #[arg(long)]
+        recalculate: bool,
+
+        /// Also rate each expertise with the LLM (one call per expertise)
+        #[arg(long)]
+        quality: bool,
+
+        /// Write the scores to this CSV file
+        #[arg(long, value_name = "PATH")]
+        export_csv: Option<PathBuf>,
+    },
+
+    /// Create relations in bulk from a CSV file
+    ///
+    /// The file needs a header row with the columns
+    /// from_id,to_id,relation_type,metadata (metadata may be empty). Nothing
+    /// is created if any row would close a cycle, unless --skip-cycles is
+    /// given.
+    ImportRelations {
+        /// CSV file to import
+        file: PathBuf,
+
+        /// Report what would be created without creating anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Skip rows that would close a cycle instead of aborting
+        #[arg(long)]
+        skip_cycles: bool,
+    },
+
+    /// Re-choose the last version bump of each expertise with the LLM
+    ///
+    /// Each expertise is compared with its previous version in the history
+    /// and renumbered from it as a major, minor or patch change. The
+    /// reasoning is stored with the new version. Locked expertises and ones
+    /// without history are skipped.
+    AutoVersion {
+        /// Scope (personal/me, company/org, project/proj; default: the configured default scope)
+        #[arg(short, long)]
+        scope: Option<Scope>,
+
+        /// Show the recommended versions without saving them
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+}
+
+/// Fragment priority tier, most important first
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FragmentPriority {
+    Critical,
+    High,
+    Normal,
+    Low,
+}
+
+impl From<FragmentPriority> for Priority {
+    fn from(priority: FragmentPriority) -> Self {
+        match priority {
+            FragmentPriority::Critical => Self::Critical,
+            FragmentPriority::High => Self::High,
+            FragmentPriority::Normal => Self::Normal,
+            FragmentPriority::Low => Self::Low,
+        }
+    }
+}
+
+#[sen::handler]
+pub async fn expertise(
+    state: State<AppState>,
+    Args(args): Args<ExpertiseArgs>,
+) -> CliResult<String> {
+    let app = state.read().await;
+
+    match args.command {
+        Some(ExpertiseCommand::Touch { id, scope }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_touch(&app, &id, scope).await
+        }
+        Some(ExpertiseCommand::Convert {
+            id,
+            from,
+            to,
+            index,
+            dry_run,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_convert(&app, &id, &from, &to, index, dry_run, scope).await
+        }
+        Some(ExpertiseCommand::Publish {
+            id,
+            format,
+            public,
+            private: _,
+            title,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_publish(&app, &id, format, public, title.as_deref(), scope).await
+        }
+        Some(ExpertiseCommand::Published) => handle_published(&app).await,
+        Some(ExpertiseCommand::Unpublish { id, scope }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_unpublish(&app, &id, scope).await
+        }
+        Some(ExpertiseCommand::RenameTag {
+            old,
+            new,
+            scope,
+            dry_run,
+        }) => handle_rename_tag(&app, &old, &new, scope, dry_run).await,
+        Some(ExpertiseCommand::CrossRef {
+            id,
+            auto_link,
+            min_confidence,
+        }) => handle_cross_ref(&app, &id, auto_link, min_confidence).await,
+        Some(ExpertiseCommand::Playground {
+            id,
+            compare,
+            include_deprecated,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_playground(&app, &id, compare, include_deprecated, scope).await
+        }
+        Some(ExpertiseCommand::ExtractRelations { id, apply, scope }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_extract_relations(&app, &id, apply, scope).await
+        }
+        Some(ExpertiseCommand::DescriptionEdit {
+            id,
+            text,
+            editor: _,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_description_edit(&app, &id, text, scope).await
+        }
+        Some(ExpertiseCommand::Lock { id, reason, scope }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_lock(&app, &id, reason.as_deref(), scope).await
+        }
+        Some(ExpertiseCommand::Unlock { id, scope }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_unlock(&app, &id, scope).await
+        }
+        Some(ExpertiseCommand::SplitByTag {
+            id,
+            min,
+            max,
+            dry_run,
+            delete_original,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_split_by_tag(&app, &id, min, max, dry_run, delete_original, scope).await
+        }
+        Some(ExpertiseCommand::Delete { id, force, scope }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            handle_delete(&app, &id, force, scope).await
+        }
+        Some(ExpertiseCommand::VersionCompare { id, v1, v2, format }) => {
+            let format = OutputFormat::resolve(format, app.agent_mode);
+            handle_version_compare(&app, &id, &v1, &v2, format).await
+        }
+        Some(ExpertiseCommand::ClusterVisualize { scope }) => {
+            run_cluster_browser(&app, scope).await
+        }
+        Some(ExpertiseCommand::WordCount {
+            scope,
+            tag,
+            histogram,
+            format,
+        }) => {
+            let format = OutputFormat::resolve(format, app.agent_mode);
+            handle_word_count(&app, scope, &tag, histogram, format).await
+        }
+        Some(ExpertiseCommand::Transfer {
+            id,
+            to_db,
+            copy: _,
+            move_,
+            on_conflict,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            handle_transfer(&app, &id, &to_db, move_, on_conflict, scope).await
+        }
+        Some(ExpertiseCommand::VerifyJson { repair }) => handle_verify_json(&app, repair).await,
+        Some(ExpertiseCommand::Remind {
+            id,
+            in_,
+            message,
+            repeat,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            handle_remind(&app, &id, in_, message.as_deref(), repeat, scope).await
+        }
+        Some(ExpertiseCommand::Reminders { due, check }) => {
+            handle_reminders(&app, due, check).await
+        }
+        Some(ExpertiseCommand::RemindComplete { reminder_id }) => {
+            handle_remind_complete(&app, reminder_id).await
+        }
+        Some(ExpertiseCommand::GraphScore {
+            id,
+            all,
+            top,
+            scope,
+            format,
+        }) => {
+            let id = match id {
+                Some(id) => Some(resolve_id(&app, &id, scope).await?),
+                None => None,
+            };
+            let format = OutputFormat::resolve(format, app.agent_mode);
+            handle_graph_score(&app, id.as_deref(), all, top, scope, format).await
+        }
+        Some(ExpertiseCommand::Translate {
+            id,
+            lang,
+            new_id,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            let new_id = new_id.unwrap_or_else(|| format!("{}-{}", id, lang));
+            handle_translate(&app, &id, &lang, &new_id, scope).await
+        }
+        Some(ExpertiseCommand::ContextExport {
+            id,
+            max_tokens,
+            min_priority,
+            xml,
+            clipboard,
+            include_deprecated,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            handle_context_export(
+                &app,
+                &id,
+                max_tokens,
+                min_priority.into(),
+                xml,
+                clipboard,
+                include_deprecated,
+                scope,
+            )
+            .await
+        }
+        Some(ExpertiseCommand::LinkMap {
+            id,
+            depth,
+            all_scopes,
+            format,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let lookup_scope = (!all_scopes).then_some(scope);
+            let id = resolve_id(&app, &id, lookup_scope).await?;
+            handle_link_map(&app, &id, depth, all_scopes, format, scope).await
+        }
+        Some(ExpertiseCommand::BatchDescribe {
+            missing_only,
+            dry_run,
+            concurrency,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_batch_describe(&app, missing_only, dry_run, concurrency.into(), scope).await
+        }
+        Some(ExpertiseCommand::InspectTokens {
+            id,
+            model,
+            threshold,
+            budget,
+            scope,
+        }) => {
+            let scope = scope_or_default(&app, scope)?;
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            let expertise = get_expertise(&app, &id, scope).await?;
+            Ok(inspect_tokens(&expertise, &model, threshold, budget))
+        }
+        Some(ExpertiseCommand::ScoreAll {
+            scope,
+            recalculate,
+            quality,
+            export_csv,
+        }) => handle_score_all(&app, scope, recalculate, quality, export_csv.as_deref()).await,
+        Some(ExpertiseCommand::ImportRelations {
+            file,
+            dry_run,
+            skip_cycles,
+        }) => handle_import_relations(&app, &file, dry_run, skip_cycles).await,
+        Some(ExpertiseCommand::AutoVersion { scope, dry_run }) => {
+            let scope = scope_or_default(&app, scope)?;
+            handle_auto_version(&app, scope, dry_run).await
+        }
+        None => Err(CliError::user(
+            "No subcommand specified. Use 'expertise --help' to see available commands.",
+        )),
+    }
+}
+
+/// `--scope` if given, else the configured default scope
+fn scope_or_default(app: &AppState, scope: Option<Scope>) -> CliResult<Scope> {
+    app.config.scope_or_default(scope).map_err(CliError::user)
+}
+
+/// Deprecated expertises are kept out of prompts unless explicitly included
+fn check_not_deprecated(expertise: &Expertise, include_deprecated: bool) -> CliResult<()> {
+    if include_deprecated || expertise.metadata.status != ExpertiseStatus::Deprecated {
+        return Ok(());
+    }
+    Err(CliError::user(format!(
+        "{} is deprecated. Use --include-deprecated to use it anyway.",
+        expertise.id()
+    )))
+}
+
+/// Error for a write to a locked expertise without `--force`
+pub(crate) fn locked_error(id: &str) -> CliError {
+    CliError::user(niwa_core::Error::ExpertiseLocked { id: id.to_string() }.to_string())
+}
+
+/// Ask before `--force` overrides a lock; declining aborts the command
+pub(crate) fn confirm_locked_override(id: &str, action: &str) -> CliResult<()> {
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(format!("{} is locked. {} it anyway?", id, action))
+        .default(false)
+        .interact()
+        .map_err(|e| CliError::system(format!("Failed to read confirmation: {}", e)))?;
+
+    if confirmed {
+        Ok(())
+    } else {
+        Err(CliError::user("Aborted"))
+    }
+}
+
+/// Open `text` in `$VISUAL`/`$EDITOR` (default: vi) and return the saved content
+///
+/// The temp file is named after `file_name` so editors pick the right syntax.
+pub(crate) fn edit_in_editor(file_name: &str, text: &str) -> CliResult<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| CliError::user("$EDITOR is empty"))?;
+
+    let path = std::env::temp_dir().join(format!("niwa-{}-{}", std::process::id(), file_name));
+    std::fs::write(&path, format!("{}\n", text))
+        .map_err(|e| CliError::system(format!("Failed to write temp file: {}", e)))?;
+
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let status = status
+        .map_err(|e| CliError::system(format!("Failed to run editor '{}': {}", editor, e)))?;
+    if !status.success() {
+        return Err(CliError::user(format!("Editor exited with {}", status)));
+    }
+    edited.map_err(|e| CliError::system(format!("Failed to read edited file: {}", e)))
+}
+
+/// Resolve `id` as an exact ID or a unique ID prefix
+///
+/// An ambiguous prefix is an error listing the candidates. Without any match
+/// `id` is returned as is, so callers report it as not found.
+pub(crate) async fn resolve_id(
+    app: &AppState,
+    id: &str,
+    scope: Option<Scope>,
+) -> CliResult<String> {
+    let resolved = app
+        .db
+        .storage()
+        .resolve_prefix(id, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Database error: {}", e)))?;
+
+    match resolved {
+        ResolveResult::Unique(id) => Ok(id),
+        ResolveResult::Ambiguous(candidates) => Err(CliError::user(format!(
+            "Ambiguous ID '{}' matches {} expertises:\n  {}\n\nUse a longer prefix.",
+            id,
+            candidates.len(),
+            candidates.join("\n  ")
+        ))),
+        ResolveResult::None => Ok(id.to_string()),
+    }
+}
+
+pub(crate) async fn get_expertise(app: &AppState, id: &str, scope: Scope) -> CliResult<Expertise> {
+    app.db
+        .storage()
+        .get(id, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+        .ok_or_else(|| not_found(format!("Expertise not found: {} (scope: {})", id, scope)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_description_edit_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(
+                ["expertise", "description-edit", "rust-expert"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+
+        assert!(matches!(
+            parse(&["--text", "New"]).unwrap().command,
+            Some(ExpertiseCommand::DescriptionEdit {
+                text: Some(_),
+                editor: false,
+                ..
+            })
+        ));
+        assert!(parse(&["--editor"]).is_ok());
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--text", "New", "--editor"]).is_err());
+    }
+
+    #[test]
+    fn test_transfer_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(
+                ["expertise", "transfer", "rust-expert", "--to-db", "work.db"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+
+        assert!(matches!(
+            parse(&[]).unwrap().command,
+            Some(ExpertiseCommand::Transfer {
+                move_: false,
+                on_conflict: ConflictPolicy::Fail,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&["--move", "--on-conflict", "overwrite"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::Transfer {
+                move_: true,
+                on_conflict: ConflictPolicy::Overwrite,
+                ..
+            })
+        ));
+        assert!(parse(&["--copy"]).is_ok());
+        assert!(parse(&["--copy", "--move"]).is_err());
+        assert!(parse(&["--on-conflict", "merge"]).is_err());
+    }
+
+    #[test]
+    fn test_remind_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(["expertise", "remind", "rust-expert"].iter().chain(args))
+        };
+
+        assert!(matches!(
+            parse(&["--in", "2w", "--repeat", "weekly"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::Remind {
+                in_: ReminderDelay::Days(14),
+                repeat: Some(RepeatInterval::Weekly),
+                ..
+            })
+        ));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--in", "soon"]).is_err());
+        assert!(parse(&["--in", "1d", "--repeat", "yearly"]).is_err());
+    }
+
+    #[test]
+    fn test_graph_score_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(["expertise", "graph-score"].iter().chain(args))
+        };
+
+        assert!(matches!(
+            parse(&[]).unwrap().command,
+            Some(ExpertiseCommand::GraphScore {
+                id: None,
+                all: false,
+                top: 10,
+                ..
+            })
+        ));
+        assert!(parse(&["rust-expert"]).is_ok());
+        assert!(parse(&["--all", "--top", "5"]).is_err());
+        assert!(parse(&["rust-expert", "--all"]).is_err());
+    }
+
+    #[test]
+    fn test_context_export_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(["expertise", "context-export"].iter().chain(args))
+        };
+
+        assert!(matches!(
+            parse(&["rust-expert"]).unwrap().command,
+            Some(ExpertiseCommand::ContextExport {
+                max_tokens: 4000,
+                xml: false,
+                clipboard: false,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&["rust-expert", "--max-tokens", "500", "--xml"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::ContextExport {
+                max_tokens: 500,
+                xml: true,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&["rust-expert", "--include-deprecated"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::ContextExport {
+                include_deprecated: true,
+                min_priority: FragmentPriority::Low,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&["rust-expert", "--min-priority", "high"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::ContextExport {
+                min_priority: FragmentPriority::High,
+                ..
+            })
+        ));
+        assert!(parse(&["rust-expert", "--min-priority", "urgent"]).is_err());
+        assert!(parse(&[]).is_err());
+    }
+
+    #[test]
+    fn test_check_not_deprecated() {
+        let mut expertise = Expertise::new("old-api", "1.0.0");
+        assert!(check_not_deprecated(&expertise, false).is_ok());
+
+        expertise.metadata.status = ExpertiseStatus::Deprecated;
+        assert!(check_not_deprecated(&expertise, false).is_err());
+        assert!(check_not_deprecated(&expertise, true).is_ok());
+    }
+
+    #[test]
+    fn test_translate_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(["expertise", "translate"].iter().chain(args))
+        };
+
+        match parse(&["rust-error-handling", "--lang", "ja"])
+            .unwrap()
+            .command
+        {
+            Some(ExpertiseCommand::Translate {
+                id,
+                lang,
+                new_id,
+                scope,
+            }) => {
+                assert_eq!(id, "rust-error-handling");
+                assert_eq!(lang, "ja");
+                assert_eq!(new_id, None);
+                // Left to the configured default scope
+                assert_eq!(scope, None);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            parse(&["rust-error-handling", "--lang", "ja", "--id", "rust-eh-ja"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::Translate { new_id: Some(ref new_id), .. }) if new_id == "rust-eh-ja"
+        ));
+        assert!(parse(&["rust-error-handling"]).is_err());
+    }
+
+    #[test]
+    fn test_batch_describe_args() {
+        let parse = |args: &[&str]| {
+            ExpertiseArgs::try_parse_from(["expertise", "batch-describe"].iter().chain(args))
+        };
+
+        match parse(&[]).unwrap().command {
+            Some(ExpertiseCommand::BatchDescribe {
+                missing_only,
+                dry_run,
+                concurrency,
+                scope,
+            }) => {
+                assert!(!missing_only);
+                assert!(!dry_run);
+                assert_eq!(concurrency, 2);
+                assert_eq!(scope, None);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            parse(&["--missing-only", "-n", "--concurrency", "4"])
+                .unwrap()
+                .command,
+            Some(ExpertiseCommand::BatchDescribe {
+                missing_only: true,
+                dry_run: true,
+                concurrency: 4,
+                ..
+            })
+        ));
+        assert!(parse(&["--concurrency", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_auto_version_args() {
+        let args = ExpertiseArgs::try_parse_from(["expertise", "auto-version"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(ExpertiseCommand::AutoVersion {
+                scope: None,
+                dry_run: false,
+            })
+        ));
+
+        let args =
+            ExpertiseArgs::try_parse_from(["expertise", "auto-version", "-s", "company", "-n"])
+                .unwrap();
+        assert!(matches!(
+            args.command,
+            Some(ExpertiseCommand::AutoVersion {
+                scope: Some(Scope::Company),
+                dry_run: true,
+            })
+        ));
+    }
+}
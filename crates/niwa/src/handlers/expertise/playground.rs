@@ -0,0 +1,136 @@
+//! Playground subcommand - try an expertise as LLM context
+
+use super::{check_not_deprecated, get_expertise};
+use crate::exit_code::generator_error;
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_playground(
+    app: &AppState,
+    id: &str,
+    compare: bool,
+    include_deprecated: bool,
+    scope: Scope,
+) -> CliResult<String> {
+    use std::io::Write;
+
+    let expertise = get_expertise(app, id, scope).await?;
+    check_not_deprecated(&expertise, include_deprecated)?;
+    let context = expertise.to_prompt_string();
+
+    println!(
+        "Playground for {} ({} fragments){}",
+        id,
+        expertise.inner.content.len(),
+        if compare {
+            ", comparing with no context"
+        } else {
+            ""
+        }
+    );
+    println!("Type a prompt and press Enter. 'exit' or Ctrl-D to quit.\n");
+
+    let mut prompts = 0;
+    let mut line = String::new();
+    loop {
+        print!("> ");
+        std::io::stdout().flush().ok();
+
+        line.clear();
+        let read = std::io::stdin()
+            .read_line(&mut line)
+            .map_err(|e| CliError::system(format!("Failed to read input: {}", e)))?;
+        if read == 0 {
+            println!();
+            break;
+        }
+        let prompt = line.trim();
+        if prompt.is_empty() {
+            continue;
+        }
+        if matches!(prompt, "exit" | "quit") {
+            break;
+        }
+        prompts += 1;
+
+        let llm_err = |e: niwa_generator::Error| generator_error("LLM error", e);
+        let with_context = app
+            .generator
+            .respond(Some(&context), prompt)
+            .await
+            .map_err(llm_err)?;
+
+        if !compare {
+            println!("\n{}\n", with_context.trim());
+            continue;
+        }
+
+        let without_context = app.generator.respond(None, prompt).await.map_err(llm_err)?;
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new(format!("With {}", id)).fg(Color::Green),
+                Cell::new("Without expertise").fg(Color::Green),
+            ])
+            .add_row(vec![with_context.trim(), without_context.trim()]);
+        println!("\n{}", table);
+
+        let diff = diff_lines(&with_context, &without_context);
+        if diff.is_empty() {
+            println!("\n(no differences)\n");
+        } else {
+            println!(
+                "\nDifferences (+ only with expertise, - only without):\n{}",
+                diff
+            );
+        }
+    }
+
+    Ok(format!("Playground closed after {} prompts", prompts))
+}
+
+/// Line-level difference between two responses
+///
+/// Lines only in `with` are prefixed with `+`, lines only in `without` with
+/// `-`. Blank lines and surrounding whitespace are ignored.
+fn diff_lines(with: &str, without: &str) -> String {
+    let normalize = |text: &str| -> Vec<String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    let with = normalize(with);
+    let without = normalize(without);
+
+    let added = with.iter().filter(|l| !without.contains(l));
+    let removed = without.iter().filter(|l| !with.contains(l));
+
+    added
+        .map(|l| format!("+ {}\n", l))
+        .chain(removed.map(|l| format!("- {}\n", l)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let with = "Use anyhow for apps\n\nPrefer thiserror for libraries\n";
+        let without = "Use anyhow for apps\nPanic on errors\n";
+
+        assert_eq!(
+            diff_lines(with, without),
+            "+ Prefer thiserror for libraries\n- Panic on errors\n"
+        );
+        assert_eq!(diff_lines("same\n", "  same  "), "");
+    }
+}
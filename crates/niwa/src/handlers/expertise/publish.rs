@@ -0,0 +1,140 @@
+//! Publish, published and unpublish subcommands - share expertises as GitHub Gists
+
+use super::get_expertise;
+use crate::gist::{gist_id_from_url, GistClient};
+use crate::render::to_markdown;
+use crate::state::AppState;
+use clap::ValueEnum;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Expertise, Scope};
+use sen::{CliError, CliResult};
+
+/// File format of a published expertise
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PublishFormat {
+    Markdown,
+    Json,
+}
+
+pub(super) async fn handle_publish(
+    app: &AppState,
+    id: &str,
+    format: PublishFormat,
+    public: bool,
+    title: Option<&str>,
+    scope: Scope,
+) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+
+    if let Some(url) = &expertise.metadata.published_url {
+        return Err(CliError::user(format!(
+            "{} is already published at {}. Run 'niwa expertise unpublish {}' first.",
+            id, url, id
+        )));
+    }
+
+    let client = GistClient::from_env().map_err(|e| CliError::user(e.to_string()))?;
+
+    let (filename, content) = match format {
+        PublishFormat::Markdown => (format!("{}.md", id), expertise.to_markdown()),
+        PublishFormat::Json => (
+            format!("{}.json", id),
+            serde_json::to_string_pretty(&expertise)
+                .map_err(|e| CliError::system(format!("Failed to serialize expertise: {}", e)))?,
+        ),
+    };
+    let description = title
+        .map(str::to_string)
+        .unwrap_or_else(|| expertise.description());
+
+    let url = client
+        .create(&filename, &content, &description, public)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to create Gist: {}", e)))?;
+
+    let published_at = chrono::Utc::now().timestamp();
+    app.db
+        .storage()
+        .set_published(id, scope, Some((&url, published_at)))
+        .await
+        .map_err(|e| CliError::system(format!("Failed to record publication: {}", e)))?;
+
+    Ok(format!(
+        "✓ Published {} ({})\n  {}",
+        id,
+        if public { "public" } else { "secret" },
+        url
+    ))
+}
+
+pub(super) async fn handle_published(app: &AppState) -> CliResult<String> {
+    let expertises = app
+        .db
+        .storage()
+        .list_all()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+    let published: Vec<&Expertise> = expertises
+        .iter()
+        .filter(|e| e.metadata.published_url.is_some())
+        .collect();
+
+    if published.is_empty() {
+        return Ok("No published expertises.".to_string());
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Scope").fg(Color::Green),
+            Cell::new("Published").fg(Color::Green),
+            Cell::new("URL").fg(Color::Green),
+        ]);
+
+    for exp in &published {
+        let published_at = exp
+            .metadata
+            .published_at
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        table.add_row(vec![
+            exp.id().to_string(),
+            exp.metadata.scope.to_string(),
+            published_at,
+            exp.metadata.published_url.clone().unwrap_or_default(),
+        ]);
+    }
+
+    Ok(format!("{}\n\nTotal: {} published", table, published.len()))
+}
+
+pub(super) async fn handle_unpublish(app: &AppState, id: &str, scope: Scope) -> CliResult<String> {
+    let expertise = get_expertise(app, id, scope).await?;
+
+    let url = expertise
+        .metadata
+        .published_url
+        .ok_or_else(|| CliError::user(format!("{} is not published", id)))?;
+    let gist_id =
+        gist_id_from_url(&url).ok_or_else(|| CliError::user(format!("Not a Gist URL: {}", url)))?;
+
+    let client = GistClient::from_env().map_err(|e| CliError::user(e.to_string()))?;
+    client
+        .delete(gist_id)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to delete Gist: {}", e)))?;
+
+    app.db
+        .storage()
+        .set_published(id, scope, None)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to record publication: {}", e)))?;
+
+    Ok(format!("✓ Unpublished {} (deleted {})", id, url))
+}
@@ -0,0 +1,181 @@
+//! Remind, reminders and remind-complete subcommands - review reminders
+
+use super::get_expertise;
+use crate::exit_code::not_found;
+use crate::handlers::show::format_timestamp;
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Reminder, RepeatInterval, Scope};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_remind(
+    app: &AppState,
+    id: &str,
+    delay: ReminderDelay,
+    message: Option<&str>,
+    repeat: Option<RepeatInterval>,
+    scope: Scope,
+) -> CliResult<String> {
+    get_expertise(app, id, scope).await?;
+
+    let due_at = delay.due_from(chrono::Utc::now());
+    let reminder_id = app
+        .db
+        .storage()
+        .add_reminder(id, due_at, message, repeat)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to add reminder: {}", e)))?;
+
+    let repeat = repeat
+        .map(|r| format!(", repeats {}", r))
+        .unwrap_or_default();
+    Ok(format!(
+        "✓ Reminder #{} for {} due {}{}",
+        reminder_id,
+        id,
+        format_timestamp(due_at),
+        repeat
+    ))
+}
+
+pub(super) async fn handle_reminders(app: &AppState, due: bool, check: bool) -> CliResult<String> {
+    let now = chrono::Utc::now().timestamp();
+    let reminders = app
+        .db
+        .storage()
+        .list_reminders((due || check).then_some(now))
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list reminders: {}", e)))?;
+
+    if check {
+        return match reminders.len() {
+            0 => Ok(String::new()),
+            n => Err(CliError::user(format!("{} reminder(s) due", n))),
+        };
+    }
+
+    if reminders.is_empty() {
+        return Ok(if due {
+            "No reminders due.".to_string()
+        } else {
+            "No pending reminders.".to_string()
+        });
+    }
+
+    Ok(format!(
+        "{}\n\nTotal: {} reminder(s)",
+        reminders_table(&reminders, now),
+        reminders.len()
+    ))
+}
+
+pub(super) async fn handle_remind_complete(app: &AppState, reminder_id: i64) -> CliResult<String> {
+    let next_due = app
+        .db
+        .storage()
+        .complete_reminder(reminder_id)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::ReminderNotFound(_) => {
+                not_found(format!("No pending reminder #{}", reminder_id))
+            }
+            e => CliError::system(format!("Failed to complete reminder: {}", e)),
+        })?;
+
+    match next_due {
+        Some(next_due) => Ok(format!(
+            "✓ Completed reminder #{} (next due {})",
+            reminder_id,
+            format_timestamp(next_due)
+        )),
+        None => Ok(format!("✓ Completed reminder #{}", reminder_id)),
+    }
+}
+
+/// Table of pending reminders, with past-due dates in red
+fn reminders_table(reminders: &[Reminder], now: i64) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Expertise").fg(Color::Green),
+            Cell::new("Due").fg(Color::Green),
+            Cell::new("Repeat").fg(Color::Green),
+            Cell::new("Message").fg(Color::Green),
+        ]);
+
+    for reminder in reminders {
+        let mut due = Cell::new(format_timestamp(reminder.due_at));
+        if reminder.due_at <= now {
+            due = due.fg(Color::Red);
+        }
+        table.add_row(vec![
+            Cell::new(reminder.id),
+            Cell::new(&reminder.expertise_id),
+            due,
+            Cell::new(reminder.repeat.map(|r| r.to_string()).unwrap_or_default()),
+            Cell::new(reminder.message.clone().unwrap_or_default()),
+        ]);
+    }
+
+    table
+}
+
+/// How far ahead `expertise remind --in` sets the due date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderDelay {
+    Days(u32),
+    Months(u32),
+}
+
+impl ReminderDelay {
+    /// Due timestamp this far after `now`
+    fn due_from(self, now: chrono::DateTime<chrono::Utc>) -> i64 {
+        let due = match self {
+            ReminderDelay::Days(days) => Some(now + chrono::Duration::days(i64::from(days))),
+            ReminderDelay::Months(months) => now.checked_add_months(chrono::Months::new(months)),
+        };
+        due.unwrap_or(now).timestamp()
+    }
+}
+
+/// Parse a reminder delay such as "7d", "2w" or "1m" (months)
+pub(super) fn parse_delay(s: &str) -> Result<ReminderDelay, String> {
+    let s = s.trim();
+    let invalid = || format!("Invalid duration '{}' (expected e.g. 7d, 2w or 1m)", s);
+    let unit = s.chars().last().ok_or_else(invalid)?;
+    let count: u32 = s[..s.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+
+    match unit.to_ascii_lowercase() {
+        'd' => Ok(ReminderDelay::Days(count)),
+        'w' => count
+            .checked_mul(7)
+            .map(ReminderDelay::Days)
+            .ok_or_else(invalid),
+        'm' => Ok(ReminderDelay::Months(count)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("7d").unwrap(), ReminderDelay::Days(7));
+        assert_eq!(parse_delay("2w").unwrap(), ReminderDelay::Days(14));
+        assert_eq!(parse_delay("1m").unwrap(), ReminderDelay::Months(1));
+        assert!(parse_delay("7").is_err());
+        assert!(parse_delay("d").is_err());
+        assert!(parse_delay("3y").is_err());
+
+        let now = chrono::DateTime::from_timestamp(1_738_281_600, 0).unwrap(); // 2025-01-31
+        assert_eq!(ReminderDelay::Days(1).due_from(now), 1_738_368_000);
+        assert_eq!(ReminderDelay::Months(1).due_from(now), 1_740_700_800); // 2025-02-28
+    }
+}
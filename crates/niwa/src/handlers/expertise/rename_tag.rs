@@ -0,0 +1,65 @@
+//! Rename-tag subcommand - rename a tag across expertises
+
+use crate::state::AppState;
+use niwa_core::{Scope, SearchOptions};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_rename_tag(
+    app: &AppState,
+    old: &str,
+    new: &str,
+    scope: Option<Scope>,
+    dry_run: bool,
+) -> CliResult<String> {
+    let new = new.trim();
+    if new.is_empty() {
+        return Err(CliError::user("New tag must not be empty"));
+    }
+    if old == new {
+        return Err(CliError::user("Old and new tag are the same"));
+    }
+
+    if dry_run {
+        let mut options = SearchOptions::new().tag(old);
+        if let Some(scope) = scope {
+            options = options.scope(scope);
+        }
+        let affected = app
+            .db
+            .query()
+            .list(options)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+        if affected.is_empty() {
+            return Ok(format!("No expertises tagged '{}'.", old));
+        }
+
+        let mut output = format!(
+            "Would rename '{}' to '{}' on {} expertises:\n",
+            old,
+            new,
+            affected.len()
+        );
+        for exp in &affected {
+            output.push_str(&format!("  {} ({})\n", exp.id(), exp.metadata.scope));
+        }
+        return Ok(output.trim_end().to_string());
+    }
+
+    let renamed = app
+        .db
+        .storage()
+        .rename_tag_globally(old, new, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to rename tag: {}", e)))?;
+
+    if renamed == 0 {
+        return Ok(format!("No expertises tagged '{}'.", old));
+    }
+
+    Ok(format!(
+        "✓ Renamed tag '{}' to '{}' on {} expertises",
+        old, new, renamed
+    ))
+}
@@ -0,0 +1,199 @@
+//! Score-all subcommand - health scores for every expertise
+
+use crate::exit_code::generator_error;
+use crate::handlers::graph::{PAGERANK_DAMPING, PAGERANK_ITERATIONS};
+use crate::handlers::show::format_timestamp;
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Expertise, ExpertiseHealth, ExpertiseScore, Scope};
+use sen::{CliError, CliResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Cached scores younger than this are reused unless --recalculate is given
+const SCORE_CACHE_TTL_SECS: i64 = 60 * 60;
+
+pub(super) async fn handle_score_all(
+    app: &AppState,
+    scope: Option<Scope>,
+    recalculate: bool,
+    quality: bool,
+    export_csv: Option<&Path>,
+) -> CliResult<String> {
+    let storage = app.db.storage();
+    let expertises = match scope {
+        Some(scope) => storage.list(scope).await,
+        None => storage.list_all().await,
+    }
+    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+    if expertises.is_empty() {
+        return Ok("No expertises to score.".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let cached = storage
+        .list_scores()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to read cached scores: {}", e)))?;
+    let fresh = |e: &Expertise| {
+        cached.get(e.id()).filter(|s| {
+            now - s.computed_at < SCORE_CACHE_TTL_SECS && (!quality || s.quality_score.is_some())
+        })
+    };
+
+    let from_cache = !recalculate && expertises.iter().all(|e| fresh(e).is_some());
+    let scores: Vec<ExpertiseScore> = if from_cache {
+        expertises
+            .iter()
+            .filter_map(|e| fresh(e).cloned())
+            .collect()
+    } else {
+        let graph = app.db.graph();
+        let relations = graph
+            .all_relations()
+            .await
+            .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+        let mut relation_counts: HashMap<&str, usize> = HashMap::new();
+        for relation in &relations {
+            *relation_counts.entry(&relation.from_id).or_default() += 1;
+            *relation_counts.entry(&relation.to_id).or_default() += 1;
+        }
+        // PageRank runs once over the whole graph
+        let pagerank: HashMap<String, f64> = graph
+            .pagerank(PAGERANK_ITERATIONS, PAGERANK_DAMPING)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to score expertises: {}", e)))?
+            .into_iter()
+            .collect();
+
+        let mut scores = Vec::with_capacity(expertises.len());
+        for expertise in &expertises {
+            let id = expertise.id();
+            let relation_count = relation_counts.get(id).copied().unwrap_or(0);
+            // Without --quality, keep the rating of an earlier --quality run
+            let quality_score = if quality {
+                let report =
+                    app.generator.quality_check(expertise).await.map_err(|e| {
+                        generator_error(&format!("Quality check of {} failed", id), e)
+                    })?;
+                Some(f64::from(report.score))
+            } else {
+                cached.get(id).and_then(|s| s.quality_score)
+            };
+            scores.push(ExpertiseScore {
+                expertise_id: id.to_string(),
+                health_score: ExpertiseHealth::assess(expertise, relation_count, now).score(),
+                pagerank_score: pagerank.get(id).copied().unwrap_or(0.0),
+                quality_score,
+                computed_at: now,
+            });
+        }
+        storage
+            .save_scores(&scores)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to cache scores: {}", e)))?;
+        scores
+    };
+
+    let mut rows: Vec<(&Expertise, &ExpertiseScore)> = expertises.iter().zip(&scores).collect();
+    rows.sort_by(|a, b| b.1.health_score.total_cmp(&a.1.health_score));
+
+    if let Some(path) = export_csv {
+        let csv = scores_to_csv(&rows)
+            .map_err(|e| CliError::system(format!("Failed to write CSV: {}", e)))?;
+        std::fs::write(path, csv)
+            .map_err(|e| CliError::system(format!("Failed to write {}: {}", path.display(), e)))?;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Scope").fg(Color::Green),
+            Cell::new("Health").fg(Color::Green),
+            Cell::new("PageRank").fg(Color::Green),
+            Cell::new("Quality").fg(Color::Green),
+        ]);
+    for (expertise, score) in &rows {
+        table.add_row(vec![
+            expertise.id().to_string(),
+            expertise.metadata.scope.to_string(),
+            format!("{:.2}", score.health_score),
+            format!("{:.4}", score.pagerank_score),
+            score
+                .quality_score
+                .map(|q| format!("{:.2}", q))
+                .unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+
+    let mut output = format!("\n{}\n\n", table);
+    let computed_at = scores.iter().map(|s| s.computed_at).min().unwrap_or(now);
+    if from_cache {
+        output.push_str(&format!(
+            "{} expertises, cached scores from {} (use --recalculate to refresh)",
+            rows.len(),
+            format_timestamp(computed_at)
+        ));
+    } else {
+        output.push_str(&format!("Scored and cached {} expertises", rows.len()));
+    }
+    if let Some(path) = export_csv {
+        output.push_str(&format!("\n✓ Exported scores to {}", path.display()));
+    }
+    Ok(output)
+}
+
+/// Render scores as CSV with a header row; `quality_score` is empty when unrated
+fn scores_to_csv(rows: &[(&Expertise, &ExpertiseScore)]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "expertise_id",
+        "scope",
+        "health_score",
+        "pagerank_score",
+        "quality_score",
+        "computed_at",
+    ])?;
+    for (expertise, score) in rows {
+        writer.write_record([
+            expertise.id(),
+            expertise.metadata.scope.as_str(),
+            &score.health_score.to_string(),
+            &score.pagerank_score.to_string(),
+            &score
+                .quality_score
+                .map(|q| q.to_string())
+                .unwrap_or_default(),
+            &score.computed_at.to_string(),
+        ])?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_to_csv() {
+        let expertise = Expertise::new("rust-expert", "1.0.0");
+        let score = ExpertiseScore {
+            expertise_id: "rust-expert".to_string(),
+            health_score: 0.8,
+            pagerank_score: 0.25,
+            quality_score: None,
+            computed_at: 1700000000,
+        };
+
+        let csv = scores_to_csv(&[(&expertise, &score)]).unwrap();
+        assert_eq!(
+            csv,
+            "expertise_id,scope,health_score,pagerank_score,quality_score,computed_at\n\
+             rust-expert,personal,0.8,0.25,,1700000000\n"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Split-by-tag subcommand - split an expertise into one per tag
+
+use super::{get_expertise, locked_error};
+use crate::render::to_json;
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Expertise, RelationMetadata, RelationType, Scope};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_split_by_tag(
+    app: &AppState,
+    id: &str,
+    min: usize,
+    max: usize,
+    dry_run: bool,
+    delete_original: bool,
+    scope: Scope,
+) -> CliResult<String> {
+    if min < 2 || min > max {
+        return Err(CliError::user(format!(
+            "Invalid part range: --min {} --max {} (need 2 <= min <= max)",
+            min, max
+        )));
+    }
+
+    let original = get_expertise(app, id, scope).await?;
+    if delete_original && original.metadata.locked && !dry_run {
+        return Err(locked_error(id));
+    }
+
+    let parts = app
+        .generator
+        .split_by_topic(&original, min, max)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to split expertise: {}", e)))?;
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Fragments").fg(Color::Green),
+            Cell::new("Tags").fg(Color::Green),
+            Cell::new("Description").fg(Color::Green),
+        ]);
+    for part in &parts {
+        table.add_row(vec![
+            part.id().to_string(),
+            part.inner.content.len().to_string(),
+            tag_distribution(part, &original),
+            part.description(),
+        ]);
+    }
+
+    let mut output = format!(
+        "\nSplit {} ({} fragments) into {} parts:\n{}\n",
+        id,
+        original.inner.content.len(),
+        parts.len(),
+        table
+    );
+
+    if dry_run {
+        output.push_str("\n(dry run, nothing saved)");
+        return Ok(output);
+    }
+
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(format!("Store {} parts?", parts.len()))
+        .default(true)
+        .interact()
+        .map_err(|e| CliError::system(format!("Failed to read confirmation: {}", e)))?;
+    if !confirmed {
+        output.push_str("\nAborted, nothing saved");
+        return Ok(output);
+    }
+
+    let storage = app.db.storage();
+    for part in &parts {
+        storage
+            .create(part.clone())
+            .await
+            .map_err(|e| CliError::system(format!("Failed to store {}: {}", part.id(), e)))?;
+    }
+
+    if delete_original {
+        storage
+            .delete(id, scope)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to delete {}: {}", id, e)))?;
+        output.push_str(&format!(
+            "\n✓ Stored {} parts and deleted {}",
+            parts.len(),
+            id
+        ));
+        return Ok(output);
+    }
+
+    let metadata = RelationMetadata {
+        source: Some("split-by-tag".to_string()),
+        ..Default::default()
+    };
+    for part in &parts {
+        app.db
+            .graph()
+            .create_relation(
+                part.id(),
+                id,
+                RelationType::Extends,
+                Some(metadata.to_json()),
+            )
+            .await
+            .map_err(|e| {
+                CliError::system(format!("Failed to link {} to {}: {}", part.id(), id, e))
+            })?;
+    }
+
+    output.push_str(&format!(
+        "\n✓ Stored {} parts, each linked to {} (extends)",
+        parts.len(),
+        id
+    ));
+    Ok(output)
+}
+
+/// Tags of a part, with tags not on the original marked as new
+fn tag_distribution(part: &Expertise, original: &Expertise) -> String {
+    part.tags()
+        .iter()
+        .map(|tag| {
+            if original.tags().contains(tag) {
+                tag.clone()
+            } else {
+                format!("{} (new)", tag)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_distribution() {
+        let mut original = Expertise::new("web-dev", "1.0.0");
+        original.inner.tags = vec!["web".to_string(), "css".to_string()];
+        let mut part = Expertise::new("web-dev-css", "1.0.0");
+        part.inner.tags = vec!["css".to_string(), "layout".to_string()];
+
+        assert_eq!(tag_distribution(&part, &original), "css, layout (new)");
+    }
+}
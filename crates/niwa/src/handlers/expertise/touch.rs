@@ -0,0 +1,26 @@
+//! Touch subcommand - mark an expertise as reviewed
+
+use crate::exit_code::not_found;
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_touch(app: &AppState, id: &str, scope: Scope) -> CliResult<String> {
+    let updated_at = app
+        .db
+        .storage()
+        .touch_only(id, scope)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::NotFound { .. } => {
+                not_found(format!("Expertise not found: {} (scope: {})", id, scope))
+            }
+            e => CliError::system(format!("Failed to touch expertise: {}", e)),
+        })?;
+
+    let timestamp = chrono::DateTime::from_timestamp(updated_at, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| updated_at.to_string());
+
+    Ok(format!("✓ Marked {} as reviewed ({})", id, timestamp))
+}
@@ -0,0 +1,132 @@
+//! Transfer subcommand - copy or move an expertise to another database
+
+use super::locked_error;
+use crate::exit_code::not_found;
+use crate::state::AppState;
+use niwa_core::{ConflictPolicy, Database, Scope};
+use sen::{CliError, CliResult};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Whether two paths name the same file, following `..`, symlinks and relative paths
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+pub(super) async fn handle_transfer(
+    app: &AppState,
+    id: &str,
+    to_db: &Path,
+    move_: bool,
+    on_conflict: ConflictPolicy,
+    scope: Scope,
+) -> CliResult<String> {
+    let target = Database::open(to_db)
+        .await
+        .map_err(|e| CliError::user(format!("Failed to open {}: {}", to_db.display(), e)))?;
+    if same_file(target.path(), app.db.path()) {
+        return Err(CliError::user(
+            "The target database is the one this command is using",
+        ));
+    }
+
+    let storage = app.db.storage();
+    let bundle = storage
+        .export_expertise(id, scope)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::NotFound { .. } => not_found(e.to_string()),
+            e => CliError::system(format!("Failed to export expertise: {}", e)),
+        })?;
+    if move_ && bundle.expertise.metadata.locked {
+        return Err(locked_error(id));
+    }
+
+    let target_storage = target.storage();
+    if on_conflict == ConflictPolicy::Skip
+        && target_storage
+            .exists(id, scope)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to check target: {}", e)))?
+    {
+        return Ok(format!(
+            "{} already exists in {}; nothing transferred",
+            id,
+            target.path().display()
+        ));
+    }
+
+    // Edges to expertises the target doesn't have are dropped on import
+    let known: HashSet<String> = target_storage
+        .list_all()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list target expertises: {}", e)))?
+        .iter()
+        .map(|e| e.id().to_string())
+        .chain(std::iter::once(id.to_string()))
+        .collect();
+    let dropped: Vec<String> = bundle
+        .relations
+        .iter()
+        .filter(|r| !known.contains(bundle.other_end(r)))
+        .map(|r| format!("{} -[{}]-> {}", r.from_id, r.relation_type, r.to_id))
+        .collect();
+    let kept = bundle.relations.len() - dropped.len();
+    let versions = bundle.versions.len();
+
+    target_storage
+        .import_expertise(bundle, on_conflict)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::AlreadyExists { .. } => {
+                CliError::user(format!("{} (use --on-conflict skip or overwrite)", e))
+            }
+            e => CliError::system(format!("Failed to import expertise: {}", e)),
+        })?;
+
+    if move_ {
+        storage
+            .delete(id, scope)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to delete expertise: {}", e)))?;
+    }
+
+    let mut output = format!(
+        "✓ {} {} to {} ({} versions, {} relations)",
+        if move_ { "Moved" } else { "Copied" },
+        id,
+        target.path().display(),
+        versions,
+        kept
+    );
+    if !dropped.is_empty() {
+        output.push_str(&format!(
+            "\n\n⚠ Dropped {} relations to expertises not in the target:",
+            dropped.len()
+        ));
+        for relation in &dropped {
+            output.push_str(&format!("\n  {}", relation));
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("niwa.db");
+        std::fs::write(&db, "").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+
+        assert!(same_file(&db, &dir.path().join("sub/../niwa.db")));
+        assert!(same_file(&db, &dir.path().join("./niwa.db")));
+        assert!(!same_file(&db, &dir.path().join("other.db")));
+    }
+}
@@ -0,0 +1,48 @@
+//! Translate subcommand - translate an expertise into another language
+
+use super::get_expertise;
+use crate::exit_code::generator_error;
+use crate::state::AppState;
+use niwa_core::Scope;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_translate(
+    app: &AppState,
+    id: &str,
+    lang: &str,
+    new_id: &str,
+    scope: Scope,
+) -> CliResult<String> {
+    let storage = app.db.storage();
+    if storage
+        .exists(new_id, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+    {
+        return Err(CliError::user(format!(
+            "Expertise already exists: {} (scope: {}). Use --id to pick another ID.",
+            new_id, scope
+        )));
+    }
+
+    let original = get_expertise(app, id, scope).await?;
+    let translated = app
+        .generator
+        .translate(&original, lang, new_id, scope)
+        .await
+        .map_err(|e| generator_error("Failed to translate expertise", e))?;
+
+    storage
+        .create(translated.clone())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to save translation: {}", e)))?;
+
+    Ok(format!(
+        "✓ Translated {} → {} ({}, {} fragments)\n  Use 'niwa show {}' to review it.",
+        id,
+        new_id,
+        lang,
+        translated.inner.content.len(),
+        new_id
+    ))
+}
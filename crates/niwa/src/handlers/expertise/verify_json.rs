@@ -0,0 +1,46 @@
+//! Verify-json subcommand - find and repair malformed stored JSON
+
+use crate::state::AppState;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_verify_json(app: &AppState, repair: bool) -> CliResult<String> {
+    let storage = app.db.storage();
+    let db_err = |e: niwa_core::Error| CliError::system(format!("Database error: {}", e));
+
+    let malformed = storage.scan_for_malformed().await.map_err(db_err)?;
+    if malformed.is_empty() {
+        return Ok("✓ All expertise rows have valid JSON".to_string());
+    }
+
+    let mut output = format!("Found {} malformed rows:\n", malformed.len());
+    if !repair {
+        for (id, scope, error) in &malformed {
+            output.push_str(&format!("\n  • {} ({}): {}", id, scope, error));
+        }
+        output.push_str("\n\nRun with --repair to rebuild them.");
+        return Ok(output);
+    }
+
+    let mut repaired = 0;
+    for (id, scope, error) in &malformed {
+        if storage.repair_malformed(id).await.map_err(db_err)? {
+            repaired += 1;
+            output.push_str(&format!("\n  ✓ {} ({}): repaired", id, scope));
+        } else {
+            output.push_str(&format!(
+                "\n  ✗ {} ({}): {} - flagged as malformed",
+                id, scope, error
+            ));
+        }
+    }
+
+    output.push_str(&format!(
+        "\n\nRepaired: {}, failed: {}",
+        repaired,
+        malformed.len() - repaired
+    ));
+    if repaired < malformed.len() {
+        output.push_str("\nFlagged rows are hidden from list, search and show until fixed.");
+    }
+    Ok(output)
+}
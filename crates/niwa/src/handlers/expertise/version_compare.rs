@@ -0,0 +1,215 @@
+//! Version-compare subcommand - diff two versions of an expertise
+
+use crate::exit_code::not_found;
+use crate::render::{color_enabled, to_csv, to_json, to_markdown, OutputFormat, Render};
+use crate::state::AppState;
+use niwa_core::VersionComparison;
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_version_compare(
+    app: &AppState,
+    id: &str,
+    v1: &str,
+    v2: &str,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let storage = app.db.storage();
+    let comparison = match storage.compare_versions(id, v1, v2).await {
+        Ok(comparison) => comparison,
+        Err(e @ niwa_core::Error::VersionNotFound { .. }) => {
+            let versions = storage.list_versions(id).await.unwrap_or_default();
+            let available = if versions.is_empty() {
+                "none".to_string()
+            } else {
+                versions.join(", ")
+            };
+            return Err(not_found(format!(
+                "{}\n\nVersions in history: {}",
+                e, available
+            )));
+        }
+        Err(e) => {
+            return Err(CliError::system(format!(
+                "Failed to compare versions: {}",
+                e
+            )))
+        }
+    };
+
+    let view = VersionDiff {
+        comparison,
+        color: color_enabled(),
+    };
+    Ok(view.render(format))
+}
+
+/// Text rendering of a version comparison, as printed by `version-compare`
+pub(crate) fn format_comparison(comparison: VersionComparison) -> String {
+    VersionDiff {
+        comparison,
+        color: color_enabled(),
+    }
+    .render_text()
+}
+
+/// A version comparison with its terminal color setting
+struct VersionDiff {
+    comparison: VersionComparison,
+    color: bool,
+}
+
+impl VersionDiff {
+    const HEADER: [&'static str; 3] = ["change", "before", "after"];
+
+    /// One (change, before, after) row per difference
+    fn records(&self) -> Vec<Vec<String>> {
+        let c = &self.comparison;
+        let row = |change: &str, before: &str, after: &str| {
+            vec![change.to_string(), before.to_string(), after.to_string()]
+        };
+
+        let mut records = Vec::new();
+        if c.description_changed {
+            records.push(row("description", "", ""));
+        }
+        records.extend(c.tags_added.iter().map(|t| row("tag added", "", t)));
+        records.extend(c.tags_removed.iter().map(|t| row("tag removed", t, "")));
+        records.extend(c.new_fragments.iter().map(|f| row("added", "", f)));
+        records.extend(c.removed_fragments.iter().map(|f| row("removed", f, "")));
+        records.extend(
+            c.modified_fragments
+                .iter()
+                .map(|(old, new)| row("modified", old, new)),
+        );
+        records
+    }
+
+    /// `text` in the given ANSI color when colors are enabled
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn render_text(&self) -> String {
+        const RED: &str = "31";
+        const GREEN: &str = "32";
+        const YELLOW: &str = "33";
+
+        let c = &self.comparison;
+        let mut output = format!(
+            "\nVersion compare: {} {} → {}\n",
+            c.id, c.from_version, c.to_version
+        );
+        if c.is_empty() {
+            output.push_str("\nNo differences.");
+            return output;
+        }
+
+        if c.description_changed {
+            output.push_str(&format!(
+                "\nDescription: {}\n",
+                self.paint("changed", YELLOW)
+            ));
+        }
+        if !c.tags_added.is_empty() || !c.tags_removed.is_empty() {
+            let tags: Vec<String> = c
+                .tags_added
+                .iter()
+                .map(|t| self.paint(&format!("+{}", t), GREEN))
+                .chain(
+                    c.tags_removed
+                        .iter()
+                        .map(|t| self.paint(&format!("-{}", t), RED)),
+                )
+                .collect();
+            output.push_str(&format!("\nTags: {}\n", tags.join(" ")));
+        }
+
+        if !c.new_fragments.is_empty()
+            || !c.removed_fragments.is_empty()
+            || !c.modified_fragments.is_empty()
+        {
+            output.push_str("\nFragments:\n");
+        }
+        for fragment in &c.new_fragments {
+            output.push_str(&format!(
+                "{}\n",
+                self.paint(&format!("+ {}", fragment), GREEN)
+            ));
+        }
+        for fragment in &c.removed_fragments {
+            output.push_str(&format!(
+                "{}\n",
+                self.paint(&format!("- {}", fragment), RED)
+            ));
+        }
+        for (old, new) in &c.modified_fragments {
+            output.push_str(&format!(
+                "{}\n    {}\n    {}\n",
+                self.paint("~ modified", YELLOW),
+                self.paint(&format!("- {}", old), RED),
+                self.paint(&format!("+ {}", new), GREEN)
+            ));
+        }
+
+        output.push_str(&format!(
+            "\nSummary: {} new, {} removed, {} modified fragments",
+            c.new_fragments.len(),
+            c.removed_fragments.len(),
+            c.modified_fragments.len()
+        ));
+        output
+    }
+}
+
+impl Render for VersionDiff {
+    fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.render_text(),
+            OutputFormat::Json => to_json(&self.comparison),
+            OutputFormat::Csv => to_csv(&Self::HEADER, self.records()),
+            OutputFormat::Markdown => to_markdown(&Self::HEADER, self.records()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_diff_render() {
+        let view = VersionDiff {
+            comparison: VersionComparison {
+                id: "rust-async".to_string(),
+                from_version: "1.0.0".to_string(),
+                to_version: "2.0.0".to_string(),
+                new_fragments: vec!["Pin futures".to_string()],
+                modified_fragments: vec![("Use spawn".to_string(), "Use tokio::spawn".to_string())],
+                tags_added: vec!["async".to_string()],
+                ..Default::default()
+            },
+            color: false,
+        };
+
+        let text = view.render(OutputFormat::Text);
+        assert!(text.contains("Tags: +async"));
+        assert!(text.contains("+ Pin futures"));
+        assert!(text.contains("~ modified\n    - Use spawn\n    + Use tokio::spawn"));
+        assert!(!text.contains('\x1b'));
+
+        assert_eq!(
+            view.render(OutputFormat::Csv),
+            "change,before,after\n\
+             tag added,,async\n\
+             added,,Pin futures\n\
+             modified,Use spawn,Use tokio::spawn"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&view.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["modified_fragments"][0][1], "Use tokio::spawn");
+    }
+}
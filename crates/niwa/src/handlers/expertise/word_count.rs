@@ -0,0 +1,154 @@
+//! Word-count subcommand - corpus-wide fragment length statistics
+
+use crate::render::{to_csv, to_json, to_markdown, OutputFormat, Render};
+use crate::state::AppState;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Scope, WordCountStats};
+use sen::{CliError, CliResult};
+
+pub(super) async fn handle_word_count(
+    app: &AppState,
+    scope: Option<Scope>,
+    tags: &[String],
+    histogram: bool,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let stats = app
+        .db
+        .query()
+        .word_count_stats(scope, tags)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to count words: {}", e)))?;
+    Ok(WordCountReport { stats, histogram }.render(format))
+}
+
+/// Word count statistics, optionally with a fragment length histogram
+struct WordCountReport {
+    stats: WordCountStats,
+    histogram: bool,
+}
+
+impl WordCountReport {
+    const HEADER: [&'static str; 2] = ["metric", "value"];
+
+    fn records(&self) -> Vec<Vec<String>> {
+        let s = &self.stats;
+        [
+            ("expertises", s.expertise_count.to_string()),
+            ("fragments", s.fragment_count.to_string()),
+            ("total words", s.total_words.to_string()),
+            (
+                "avg words per expertise",
+                format!("{:.1}", s.avg_words_per_expertise),
+            ),
+            (
+                "avg words per fragment",
+                format!("{:.1}", s.avg_words_per_fragment),
+            ),
+            (
+                "median fragment length (chars)",
+                s.median_fragment_length.to_string(),
+            ),
+            ("longest expertise", s.longest_expertise_id.clone()),
+            ("shortest expertise", s.shortest_expertise_id.clone()),
+        ]
+        .into_iter()
+        .map(|(metric, value)| vec![metric.to_string(), value])
+        .collect()
+    }
+
+    fn render_text(&self) -> String {
+        if self.stats.expertise_count == 0 {
+            return "No expertises found.".to_string();
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("Metric").fg(Color::Cyan),
+                Cell::new("Value").fg(Color::Cyan),
+            ]);
+        for record in self.records() {
+            table.add_row(record);
+        }
+
+        let mut output = format!("\n{}\n", table);
+        if self.histogram && !self.stats.fragment_lengths.is_empty() {
+            output.push_str(&format!(
+                "\nFragment length (chars):\n{}",
+                length_histogram(&self.stats.fragment_lengths)
+            ));
+        }
+        output
+    }
+}
+
+impl Render for WordCountReport {
+    fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.render_text(),
+            OutputFormat::Json => to_json(&self.stats),
+            OutputFormat::Csv => to_csv(&Self::HEADER, self.records()),
+            OutputFormat::Markdown => to_markdown(&Self::HEADER, self.records()),
+        }
+    }
+}
+
+/// ASCII histogram of `lengths` in 10-character buckets, from the shortest
+/// bucket to the longest (empty buckets in between are kept)
+fn length_histogram(lengths: &[usize]) -> String {
+    const BUCKET: usize = 10;
+    const MAX_BAR: usize = 40;
+
+    let (Some(min), Some(max)) = (lengths.iter().min(), lengths.iter().max()) else {
+        return String::new();
+    };
+    let (first, last) = (min / BUCKET, max / BUCKET);
+    let mut counts = vec![0usize; last - first + 1];
+    for length in lengths {
+        counts[length / BUCKET - first] += 1;
+    }
+
+    let peak = counts.iter().copied().max().unwrap_or(1);
+    let label_width = format!("{}-{}", last * BUCKET, last * BUCKET + BUCKET - 1).len();
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let start = (first + i) * BUCKET;
+            let label = format!("{}-{}", start, start + BUCKET - 1);
+            let bar = "#".repeat((count * MAX_BAR).div_ceil(peak));
+            format!(
+                "{:>width$} | {} {}\n",
+                label,
+                bar,
+                count,
+                width = label_width
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_histogram() {
+        assert_eq!(length_histogram(&[]), "");
+
+        let histogram = length_histogram(&[12, 15, 18, 35, 9]);
+        let lines: Vec<&str> = histogram.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!("  0-9 | {} 1", "#".repeat(14)),
+                format!("10-19 | {} 3", "#".repeat(40)),
+                "20-29 |  0".to_string(),
+                format!("30-39 | {} 1", "#".repeat(14)),
+            ]
+        );
+    }
+}
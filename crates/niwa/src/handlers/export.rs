@@ -0,0 +1,168 @@
+//! Export command - write the knowledge graph in analysis-friendly formats
+
+use crate::state::AppState;
+use clap::{Parser, ValueEnum};
+use niwa_core::bundle;
+use niwa_core::graph::Relation;
+use sen::{Args, CliError, CliResult, State};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+/// Export the knowledge graph for analysis outside NIWA
+///
+/// Usage:
+///   niwa export --format relations-csv
+///   niwa export --format relations-csv -o relations.csv
+///   niwa export --format relations-json -o links.json
+///   niwa export --format jsonl -o backup.jsonl
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    /// Export format
+    #[arg(short, long, value_enum)]
+    pub format: ExportFormat,
+
+    /// Write to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Export format
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One row per relation: from_id,to_id,relation_type,confidence,source,created_at
+    RelationsCsv,
+    /// Relations only, as a JSON array (restore with `niwa import-relations`)
+    RelationsJson,
+    /// Bundle as JSON Lines, one expertise or relation per line (restore with
+    /// `niwa bundle restore`)
+    Jsonl,
+}
+
+#[sen::handler]
+pub async fn export(state: State<AppState>, Args(args): Args<ExportArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    let (content, count) = match args.format {
+        format @ (ExportFormat::RelationsCsv | ExportFormat::RelationsJson) => {
+            let relations = app
+                .db
+                .graph()
+                .all_relations()
+                .await
+                .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+            let content = if format == ExportFormat::RelationsCsv {
+                relations_to_csv(&relations)
+                    .map_err(|e| CliError::system(format!("Failed to write CSV: {}", e)))?
+            } else {
+                bundle::relations_to_json(&relations)
+                    .map_err(|e| CliError::system(format!("Failed to write JSON: {}", e)))?
+            };
+            (content, relations.len())
+        }
+        // Streamed, so large bases never sit in memory as one string
+        ExportFormat::Jsonl => return export_jsonl(&app, args.output).await,
+    };
+
+    let Some(path) = args.output else {
+        return Ok(content.trim_end().to_string());
+    };
+
+    std::fs::write(&path, content)
+        .map_err(|e| CliError::system(format!("Failed to write {}: {}", path.display(), e)))?;
+
+    Ok(format!("✓ Exported {} rows to {}", count, path.display()))
+}
+
+/// Stream a JSONL bundle to `output`, or straight to stdout
+async fn export_jsonl(app: &AppState, output: Option<PathBuf>) -> CliResult<String> {
+    let write_error = |e| CliError::system(format!("Failed to export bundle: {}", e));
+
+    let Some(path) = output else {
+        bundle::export_jsonl(&app.db, None, BufWriter::new(std::io::stdout()))
+            .await
+            .map_err(write_error)?;
+        return Ok(String::new());
+    };
+
+    let file = std::fs::File::create(&path)
+        .map_err(|e| CliError::system(format!("Failed to create {}: {}", path.display(), e)))?;
+    let (expertises, relations) = bundle::export_jsonl(&app.db, None, BufWriter::new(file))
+        .await
+        .map_err(write_error)?;
+
+    Ok(format!(
+        "✓ Exported {} expertises and {} relations to {}",
+        expertises,
+        relations,
+        path.display()
+    ))
+}
+
+/// Render relations as CSV with a header row
+///
+/// `confidence` and `source` come from the relation's metadata and are left
+/// empty when not recorded.
+fn relations_to_csv(relations: &[Relation]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "from_id",
+        "to_id",
+        "relation_type",
+        "confidence",
+        "source",
+        "created_at",
+    ])?;
+
+    for relation in relations {
+        let metadata = relation.parsed_metadata();
+        let confidence = metadata
+            .confidence
+            .map(|c| c.to_string())
+            .unwrap_or_default();
+        let source = metadata.source.unwrap_or_default();
+
+        writer.write_record([
+            relation.from_id.as_str(),
+            relation.to_id.as_str(),
+            relation.relation_type.as_str(),
+            &confidence,
+            &source,
+            &relation.created_at.to_string(),
+        ])?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_core::RelationType;
+
+    fn relation(from: &str, to: &str, metadata: Option<&str>) -> Relation {
+        Relation {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type: RelationType::Uses,
+            metadata: metadata.map(String::from),
+            created_at: 1700000000,
+        }
+    }
+
+    #[test]
+    fn test_relations_to_csv() {
+        let relations = vec![
+            relation("a", "b", Some(r#"{"confidence":0.8,"source":"auto-link"}"#)),
+            relation("a", "c,d", Some("plain text reason")),
+        ];
+
+        let csv = relations_to_csv(&relations).unwrap();
+        assert_eq!(
+            csv,
+            "from_id,to_id,relation_type,confidence,source,created_at\n\
+             a,b,uses,0.8,auto-link,1700000000\n\
+             a,\"c,d\",uses,,,1700000000\n"
+        );
+    }
+}
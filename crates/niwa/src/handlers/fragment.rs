@@ -0,0 +1,116 @@
+//! Fragment commands - edit the fragments of a stored expertise
+
+use crate::handlers::expertise::{get_expertise, locked_error, resolve_id};
+use crate::state::AppState;
+use clap::{Parser, Subcommand};
+use niwa_core::{Scope, StorageOperations};
+use sen::{Args, CliError, CliResult, State};
+
+/// Edit the fragments of an expertise
+///
+/// Usage:
+///   niwa fragment move rust-expert 3 0
+///   niwa fragment move rust-expert 0 2 --scope company
+#[derive(Parser, Debug)]
+pub struct FragmentArgs {
+    #[command(subcommand)]
+    pub command: Option<FragmentCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FragmentCommand {
+    /// Move a fragment to another position
+    ///
+    /// Fragment order is the order the prompt rendering presents them in.
+    /// Indices start at 0 (`show --fragments` numbers them from 1). The
+    /// version is not bumped.
+    Move {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// Current index of the fragment
+        from: usize,
+
+        /// Index the fragment should end up at
+        to: usize,
+
+        /// Scope (personal/me, company/org, project/proj)
+        #[arg(short, long, default_value = "personal")]
+        scope: Scope,
+    },
+}
+
+#[sen::handler]
+pub async fn fragment(state: State<AppState>, Args(args): Args<FragmentArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    match args.command {
+        Some(FragmentCommand::Move {
+            id,
+            from,
+            to,
+            scope,
+        }) => {
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            handle_move(&app, &id, from, to, scope).await
+        }
+        None => Err(CliError::user(
+            "No subcommand specified. Use 'fragment --help' to see available commands.",
+        )),
+    }
+}
+
+async fn handle_move(
+    app: &AppState,
+    id: &str,
+    from: usize,
+    to: usize,
+    scope: Scope,
+) -> CliResult<String> {
+    let mut expertise = get_expertise(app, id, scope).await?;
+    if expertise.metadata.locked {
+        return Err(locked_error(id));
+    }
+
+    expertise
+        .move_fragment(from, to)
+        .map_err(|e| CliError::user(e.to_string()))?;
+    if from == to {
+        return Ok(format!("Fragment {} of {} is already in place", from, id));
+    }
+
+    app.db
+        .storage()
+        .update(expertise)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
+
+    Ok(format!("✓ Moved fragment {} → {} in {}", from, to, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_args() {
+        let args =
+            FragmentArgs::try_parse_from(["fragment", "move", "rust-expert", "3", "0"]).unwrap();
+        match args.command {
+            Some(FragmentCommand::Move {
+                id,
+                from,
+                to,
+                scope,
+            }) => {
+                assert_eq!((id.as_str(), from, to), ("rust-expert", 3, 0));
+                assert_eq!(scope, Scope::Personal);
+            }
+            None => panic!("expected the move subcommand"),
+        }
+
+        assert!(
+            FragmentArgs::try_parse_from(["fragment", "move", "rust-expert", "-1", "0"]).is_err()
+        );
+    }
+}
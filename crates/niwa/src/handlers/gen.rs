@@ -1,56 +1,338 @@
 //! Generation commands
 
+use crate::exit_code::{generator_error, not_found};
+use crate::handlers::expertise::{
+    confirm_locked_override, format_comparison, get_expertise, locked_error,
+};
+use crate::handlers::show::{format_expertise, FragmentPage};
+use crate::render::{to_json, GenResult};
 use crate::state::AppState;
-use clap::Parser;
-use niwa_core::{Scope, StorageOperations};
+use clap::{Parser, ValueEnum};
+use niwa_core::{Scope, StorageOperations, VersionComparison};
+use niwa_generator::{split_changelog, SecretPolicy, VersionBumpStrategy};
 use sen::{Args, CliError, CliResult, State};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Generate Expertise from log file or text
 ///
 /// Usage:
 ///   niwa gen --file session.log --id rust-expert --scope personal
 ///   niwa gen --text "Some knowledge..." --id quick-tip
+///   niwa gen --from-code src/storage.rs --id storage-design --language rust
+///   niwa gen --file session.log --id sql-tuning --prompt-file prompts/sql.md
+///   niwa gen --enrich rust-expert --file new-session.log --min-new-fragments 3
+///   niwa gen --from-changelog CHANGELOG.md --id project-history-v2
+///   niwa gen --from-changelog CHANGELOG.md --id project-history --split
+///   niwa gen --file session.log --id tokio-tips --tags rust,async --required-tags team-a
+///   niwa gen --file session.log --id tokio-tips --confidence-threshold 0.6
+///   niwa gen --file long-session.log --id tokio-tips --max-fragments 10
+///   niwa gen --file session.log --id tokio-tips --min-fragments 3 --max-fragments 8
+///   niwa gen --file session.log --id api-design --context-file docs/glossary.md
+///   niwa gen --from-meeting-notes notes/2025-01-15.md --id storage-rewrite-decisions
+///   niwa gen --file session.log --id deploy-notes --on-secret reject
 #[derive(Parser, Debug)]
 pub struct GenArgs {
     /// Log file path to generate expertise from
-    #[arg(short = 'f', long, conflicts_with = "text")]
+    #[arg(short = 'f', long, conflicts_with_all = ["text", "from_code"])]
     pub file: Option<PathBuf>,
 
     /// Direct text input (alternative to --file)
-    #[arg(short = 't', long, conflicts_with = "file")]
+    #[arg(short = 't', long, conflicts_with_all = ["file", "from_code"])]
     pub text: Option<String>,
 
+    /// Source code file to analyze for design knowledge
+    #[arg(long, value_name = "FILE", conflicts_with = "from_changelog")]
+    pub from_code: Option<PathBuf>,
+
+    /// Changelog (e.g. CHANGELOG.md) to extract release knowledge from
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["file", "text"])]
+    pub from_changelog: Option<PathBuf>,
+
+    /// Meeting notes to extract decisions, constraints and action items from
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["file", "text", "from_code", "from_changelog", "enrich"]
+    )]
+    pub from_meeting_notes: Option<PathBuf>,
+
+    /// With --from-changelog, generate one expertise per version section
+    // clap skips `requires` when the required arg conflicts with a present
+    // one, so the conflicts are spelled out here too
+    #[arg(
+        long,
+        requires = "from_changelog",
+        conflicts_with_all = ["file", "text", "enrich"]
+    )]
+    pub split: bool,
+
+    /// Language of --from-code (auto-detected from the file extension if omitted)
+    #[arg(long, requires = "from_code")]
+    pub language: Option<String>,
+
     /// Expertise ID
-    #[arg(long)]
-    pub id: String,
+    #[arg(long, required_unless_present = "enrich")]
+    pub id: Option<String>,
+
+    /// Add only novel fragments from --file/--text to this existing expertise
+    #[arg(long, value_name = "ID", conflicts_with_all = ["id", "from_code", "from_changelog"])]
+    pub enrich: Option<String>,
+
+    /// With --enrich, save only if at least this many new fragments are found
+    #[arg(long, default_value_t = 1, requires = "enrich")]
+    pub min_new_fragments: usize,
+
+    /// Scope (personal/me, company/org, project/proj; default: NIWA_DEFAULT_SCOPE,
+    /// then the nearest .niwarc, then `default_scope` in config.toml, then personal)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+
+    /// File with a system prompt replacing the built-in extraction prompt
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["from_code", "from_changelog", "from_meeting_notes"]
+    )]
+    pub prompt_file: Option<PathBuf>,
+
+    /// File with domain context (glossary, conventions) added to the extraction prompt
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["from_code", "from_changelog"])]
+    pub context_file: Option<PathBuf>,
+
+    /// Seed tags the LLM should include alongside its own (comma-separated)
+    #[arg(
+        long,
+        value_name = "TAGS",
+        value_delimiter = ',',
+        conflicts_with = "enrich"
+    )]
+    pub tags: Vec<String>,
+
+    /// Tags added to the result even if the LLM leaves them out (comma-separated)
+    #[arg(
+        long,
+        value_name = "TAGS",
+        value_delimiter = ',',
+        conflicts_with = "enrich"
+    )]
+    pub required_tags: Vec<String>,
+
+    /// Reject the extraction if the LLM reports less confidence (0.0-1.0)
+    #[arg(
+        long,
+        value_name = "CONFIDENCE",
+        conflicts_with_all = ["from_code", "from_changelog", "from_meeting_notes", "enrich"]
+    )]
+    pub confidence_threshold: Option<f32>,
+
+    /// Keep at most this many fragments, asking the LLM for the most important ones
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_positive,
+        conflicts_with_all = ["from_code", "from_changelog", "from_meeting_notes", "enrich"]
+    )]
+    pub max_fragments: Option<usize>,
+
+    /// Reject the extraction if it has fewer fragments
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["from_code", "from_changelog", "from_meeting_notes", "enrich"]
+    )]
+    pub min_fragments: Option<usize>,
+
+    /// Store the extraction even if it has no description and no fragments
+    #[arg(
+        long,
+        conflicts_with_all = ["from_code", "from_changelog", "from_meeting_notes", "enrich"]
+    )]
+    pub keep_empty: bool,
+
+    /// What to do when the log extraction contains possible secrets or
+    /// personal data (API keys, passwords, email addresses)
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OnSecret::Redact,
+        conflicts_with_all = ["from_code", "from_changelog", "from_meeting_notes", "enrich"]
+    )]
+    pub on_secret: OnSecret,
+}
+
+/// Handling of secrets found in generated content (`--on-secret`)
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnSecret {
+    /// Replace them with [REDACTED:<kind>]
+    #[default]
+    Redact,
+    /// Keep them, but save the expertise as a draft for review
+    Warn,
+    /// Fail without saving anything
+    Reject,
+}
+
+impl From<OnSecret> for SecretPolicy {
+    fn from(on_secret: OnSecret) -> Self {
+        match on_secret {
+            OnSecret::Redact => Self::Redact,
+            OnSecret::Warn => Self::Warn,
+            OnSecret::Reject => Self::Reject,
+        }
+    }
+}
+
+/// Apply --on-secret to the generator of `app`, unless it is the default
+pub(crate) async fn with_on_secret(app: AppState, on_secret: OnSecret) -> CliResult<AppState> {
+    if on_secret == OnSecret::default() {
+        return Ok(app);
+    }
+    app.with_secret_policy(on_secret.into())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))
+}
+
+/// Parse a count that must be at least 1
+pub(crate) fn parse_positive(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 1 => Ok(n),
+        _ => Err(format!("'{}' is not a positive number", s)),
+    }
+}
+
+/// Clone the app state with the generator customized by --prompt-file,
+/// --context-file, the tag flags, the extraction limits and --on-secret
+async fn generation_state(app: &AppState, args: &GenArgs) -> CliResult<AppState> {
+    let app = match &args.prompt_file {
+        Some(path) => app.with_prompt_file(path).await.map_err(|e| {
+            CliError::user(format!(
+                "Failed to load prompt file {}: {}",
+                path.display(),
+                e
+            ))
+        })?,
+        None => app.clone(),
+    };
+    let app = with_context_file(app, args.context_file.as_deref()).await?;
+
+    let clean = |tags: &[String]| -> Vec<String> {
+        tags.iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+    let (seed_tags, required_tags) = (clean(&args.tags), clean(&args.required_tags));
+    let app = if seed_tags.is_empty() && required_tags.is_empty() {
+        app
+    } else {
+        app.with_tags(seed_tags, required_tags)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))?
+    };
+
+    let app = match args.confidence_threshold {
+        Some(min_confidence) => app
+            .with_min_confidence(min_confidence)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))?,
+        None => app,
+    };
 
-    /// Scope (personal, team, company)
-    #[arg(short, long, default_value = "personal")]
-    pub scope: Scope,
+    let app = if args.min_fragments.is_some() || args.max_fragments.is_some() {
+        app.with_fragment_limits(args.min_fragments, args.max_fragments)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))?
+    } else {
+        app
+    };
+
+    let app = if args.keep_empty {
+        app.with_keep_empty()
+            .await
+            .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))?
+    } else {
+        app
+    };
+
+    with_on_secret(app, args.on_secret).await
 }
 
 #[sen::handler]
 pub async fn generate(state: State<AppState>, Args(args): Args<GenArgs>) -> CliResult<String> {
+    let scope = state
+        .read()
+        .await
+        .config
+        .scope_or_default(args.scope)
+        .map_err(CliError::user)?;
+
+    if let Some(code_path) = &args.from_code {
+        return generate_from_code(&state, &args, scope, code_path).await;
+    }
+    if let Some(changelog_path) = &args.from_changelog {
+        return generate_from_changelog(&state, &args, scope, changelog_path).await;
+    }
+    if let Some(notes_path) = &args.from_meeting_notes {
+        return generate_from_meeting_notes(&state, &args, scope, notes_path).await;
+    }
+
     // Get content from file or text
-    let log_content = if let Some(file_path) = args.file {
-        std::fs::read_to_string(&file_path)
+    let log_content = if let Some(file_path) = &args.file {
+        std::fs::read_to_string(file_path)
             .map_err(|e| CliError::user(format!("Failed to read log file: {}", e)))?
-    } else if let Some(text) = args.text {
-        text
+    } else if let Some(text) = &args.text {
+        text.clone()
     } else {
         return Err(CliError::user(
             "Either --file or --text must be provided".to_string(),
         ));
     };
 
+    if let Some(enrich_id) = &args.enrich {
+        return enrich(&state, &args, scope, enrich_id, &log_content).await;
+    }
+    let id = args.id.as_deref().unwrap_or_default();
+
+    if let Some(threshold) = args.confidence_threshold {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(CliError::user(
+                "--confidence-threshold must be between 0.0 and 1.0",
+            ));
+        }
+    }
+    if let (Some(min), Some(max)) = (args.min_fragments, args.max_fragments) {
+        if min > max {
+            return Err(CliError::user(format!(
+                "--min-fragments ({}) must not exceed --max-fragments ({})",
+                min, max
+            )));
+        }
+    }
+
     // Generate expertise
-    let app = state.read().await;
-    let expertise = app
+    let app = generation_state(&*state.read().await, &args).await?;
+    let mut expertise = app
         .generator
-        .generate_from_log(&log_content, &args.id, args.scope)
+        .generate_from_log(&log_content, id, scope)
         .await
-        .map_err(|e| CliError::system(format!("Failed to generate expertise: {}", e)))?;
+        .map_err(|e| match e {
+            niwa_generator::Error::EmptyExtraction => CliError::user(format!(
+                "{}; nothing was stored (use --keep-empty to store it anyway)",
+                e
+            )),
+            niwa_generator::Error::LowConfidence { .. } => CliError::user(format!(
+                "{} (--confidence-threshold {}); nothing was stored",
+                e,
+                args.confidence_threshold.unwrap_or_default()
+            )),
+            niwa_generator::Error::TooFewFragments { .. } => {
+                CliError::user(format!("{} (--min-fragments); nothing was stored", e))
+            }
+            e => generator_error("Failed to generate expertise", e),
+        })?;
+    if let Some(file_path) = &args.file {
+        expertise.metadata.source_file = Some(file_path.display().to_string());
+    }
 
     // Store in database
     app.db
@@ -59,19 +341,293 @@ pub async fn generate(state: State<AppState>, Args(args): Args<GenArgs>) -> CliR
         .await
         .map_err(|e| CliError::system(format!("Failed to store expertise: {}", e)))?;
 
+    Ok(created_output(
+        &app,
+        &[GenResult::from(&expertise)],
+        format!(
+            "✓ Generated expertise: {} v{}\n  Scope: {}\n  Description: {}",
+            expertise.id(),
+            expertise.version(),
+            scope,
+            expertise.description()
+        ),
+    ))
+}
+
+/// Output for the expertises `gen` stored: a JSON array of [`GenResult`] in
+/// agent mode, else `text`
+fn created_output(app: &AppState, created: &[GenResult], text: String) -> String {
+    if app.agent_mode {
+        to_json(created)
+    } else {
+        text
+    }
+}
+
+/// Generate expertise from a source code file
+async fn generate_from_code(
+    state: &State<AppState>,
+    args: &GenArgs,
+    scope: Scope,
+    code_path: &Path,
+) -> CliResult<String> {
+    let language = match &args.language {
+        Some(language) => language.clone(),
+        None => detect_language(code_path)
+            .ok_or_else(|| {
+                CliError::user(format!(
+                    "Could not detect language for {}. Use --language to specify it.",
+                    code_path.display()
+                ))
+            })?
+            .to_string(),
+    };
+
+    let code = std::fs::read_to_string(code_path)
+        .map_err(|e| CliError::user(format!("Failed to read source file: {}", e)))?;
+
+    let id = args.id.as_deref().unwrap_or_default();
+    let app = generation_state(&*state.read().await, args).await?;
+    let expertise = app
+        .generator
+        .generate_from_code(&code, &language, id, scope)
+        .await
+        .map_err(|e| generator_error("Failed to generate expertise", e))?;
+
+    app.db
+        .storage()
+        .create(expertise.clone())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to store expertise: {}", e)))?;
+
+    Ok(created_output(
+        &app,
+        &[GenResult::from(&expertise)],
+        format!(
+            "✓ Generated expertise: {} v{}\n  Scope: {}\n  Language: {}\n  Tags: {}\n  Description: {}",
+            expertise.id(),
+            expertise.version(),
+            scope,
+            language,
+            expertise.tags().join(", "),
+            expertise.description()
+        ),
+    ))
+}
+
+/// Generate expertise from a changelog, optionally one per version section
+async fn generate_from_changelog(
+    state: &State<AppState>,
+    args: &GenArgs,
+    scope: Scope,
+    changelog_path: &Path,
+) -> CliResult<String> {
+    let content = std::fs::read_to_string(changelog_path)
+        .map_err(|e| CliError::user(format!("Failed to read changelog: {}", e)))?;
+    let id = args.id.as_deref().unwrap_or_default();
+
+    // Without --split the whole file is one section
+    let sections: Vec<(String, String)> = if args.split {
+        let sections = split_changelog(&content);
+        if sections.is_empty() {
+            return Err(CliError::user(format!(
+                "No version sections (\"## <version>\" headings) found in {}",
+                changelog_path.display()
+            )));
+        }
+        sections
+            .into_iter()
+            .map(|section| (format!("{}-{}", id, section.slug()), section.content))
+            .collect()
+    } else {
+        vec![(id.to_string(), content)]
+    };
+
+    let app = generation_state(&*state.read().await, args).await?;
+    let mut lines = Vec::with_capacity(sections.len());
+    let mut created = Vec::with_capacity(sections.len());
+    for (section_id, section) in &sections {
+        let expertise = app
+            .generator
+            .generate_from_changelog(section, section_id, scope)
+            .await
+            .map_err(|e| {
+                CliError::system(format!(
+                    "Failed to generate expertise {}: {}",
+                    section_id, e
+                ))
+            })?;
+
+        app.db
+            .storage()
+            .create(expertise.clone())
+            .await
+            .map_err(|e| CliError::system(format!("Failed to store expertise: {}", e)))?;
+
+        lines.push(format!(
+            "✓ Generated expertise: {} v{}\n  Tags: {}\n  Description: {}",
+            expertise.id(),
+            expertise.version(),
+            expertise.tags().join(", "),
+            expertise.description()
+        ));
+        created.push(GenResult::from(&expertise));
+    }
+
+    if sections.len() > 1 {
+        lines.push(format!(
+            "\nGenerated {} expertises from {} (scope: {})",
+            sections.len(),
+            changelog_path.display(),
+            scope
+        ));
+    }
+    Ok(created_output(&app, &created, lines.join("\n")))
+}
+
+/// Generate expertise from meeting notes
+async fn generate_from_meeting_notes(
+    state: &State<AppState>,
+    args: &GenArgs,
+    scope: Scope,
+    notes_path: &Path,
+) -> CliResult<String> {
+    let notes = std::fs::read_to_string(notes_path)
+        .map_err(|e| CliError::user(format!("Failed to read meeting notes: {}", e)))?;
+
+    let id = args.id.as_deref().unwrap_or_default();
+    let app = generation_state(&*state.read().await, args).await?;
+    let expertise = app
+        .generator
+        .generate_from_meeting_notes(&notes, id, scope)
+        .await
+        .map_err(|e| generator_error("Failed to generate expertise", e))?;
+
+    app.db
+        .storage()
+        .create(expertise.clone())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to store expertise: {}", e)))?;
+
+    Ok(created_output(
+        &app,
+        &[GenResult::from(&expertise)],
+        format!(
+            "✓ Generated expertise: {} v{}\n  Scope: {}\n  Tags: {}\n  Description: {}",
+            expertise.id(),
+            expertise.version(),
+            scope,
+            expertise.tags().join(", "),
+            expertise.description()
+        ),
+    ))
+}
+
+/// Map a file extension to a language name
+fn detect_language(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let language = match ext.as_str() {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "rb" => "ruby",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "swift" => "swift",
+        "scala" => "scala",
+        "hs" => "haskell",
+        "ex" | "exs" => "elixir",
+        "php" => "php",
+        "sh" | "bash" => "bash",
+        "sql" => "sql",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Append novel fragments from a new source to an existing expertise
+async fn enrich(
+    state: &State<AppState>,
+    args: &GenArgs,
+    scope: Scope,
+    id: &str,
+    new_content: &str,
+) -> CliResult<String> {
+    let app = state.read().await;
+    let app = match &args.prompt_file {
+        Some(path) => app.with_prompt_file(path).await.map_err(|e| {
+            CliError::user(format!(
+                "Failed to load prompt file {}: {}",
+                path.display(),
+                e
+            ))
+        })?,
+        None => app.clone(),
+    };
+    let app = with_context_file(app, args.context_file.as_deref()).await?;
+
+    let existing = app
+        .db
+        .storage()
+        .get(id, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+        .ok_or_else(|| not_found(format!("Expertise not found: {} (scope: {})", id, scope)))?;
+
+    let (enriched, added) = app
+        .generator
+        .enrich(existing, new_content)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to enrich expertise: {}", e)))?;
+
+    if added == 0 || added < args.min_new_fragments {
+        return Ok(format!(
+            "Found {} new fragment(s) (minimum {}); {} left unchanged",
+            added, args.min_new_fragments, id
+        ));
+    }
+
+    app.db
+        .storage()
+        .update(enriched.clone())
+        .await
+        .map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
+
     Ok(format!(
-        "✓ Generated expertise: {} v{}\n  Scope: {}\n  Description: {}",
-        expertise.id(),
-        expertise.version(),
-        args.scope,
-        expertise.description()
+        "✓ Added {} new fragments to {} → v{}",
+        added,
+        id,
+        enriched.version()
     ))
 }
 
+/// Load --context-file into the generator of `app`, if given
+pub(crate) async fn with_context_file(app: AppState, path: Option<&Path>) -> CliResult<AppState> {
+    match path {
+        Some(path) => app.with_context_file(path).await.map_err(|e| {
+            CliError::user(format!(
+                "Failed to load context file {}: {}",
+                path.display(),
+                e
+            ))
+        }),
+        None => Ok(app),
+    }
+}
+
 /// Improve existing Expertise
 ///
 /// Usage:
 ///   niwa improve rust-expert --instruction "Add error handling examples" --scope personal
+///   niwa improve rust-expert --instruction "Use team terms" --context-file docs/glossary.md
+///   niwa improve rust-expert --instruction "Tighten wording" --dry-run
+///   niwa improve rust-expert --instruction "Drop outdated advice" --auto-version
+///   niwa improve rust-expert --instruction "Add setup steps" --on-secret warn
 #[derive(Parser, Debug)]
 pub struct ImproveArgs {
     /// Expertise ID to improve
@@ -81,46 +637,620 @@ pub struct ImproveArgs {
     #[arg(short, long)]
     pub instruction: String,
 
-    /// Scope (personal, team, company)
-    #[arg(short, long, default_value = "personal")]
-    pub scope: Scope,
+    /// Scope (personal/me, company/org, project/proj; default: NIWA_DEFAULT_SCOPE,
+    /// then the nearest .niwarc, then `default_scope` in config.toml, then personal)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+
+    /// Improve even if the expertise is locked (asks for confirmation)
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show the proposed changes without saving them
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// File with domain context (glossary, conventions) added to the prompt
+    #[arg(long, value_name = "FILE")]
+    pub context_file: Option<PathBuf>,
+
+    /// Let the LLM choose a major, minor or patch bump (default: minor)
+    #[arg(long)]
+    pub auto_version: bool,
+
+    /// What to do when the improved expertise contains possible secrets
+    #[arg(long, value_enum, default_value_t = OnSecret::Redact)]
+    pub on_secret: OnSecret,
 }
 
 #[sen::handler]
 pub async fn improve(state: State<AppState>, Args(args): Args<ImproveArgs>) -> CliResult<String> {
-    let app = state.read().await;
+    let app = with_context_file(state.read().await.clone(), args.context_file.as_deref()).await?;
+    let mut app = with_on_secret(app, args.on_secret).await?;
+    if args.auto_version {
+        app = app
+            .with_version_strategy(VersionBumpStrategy::Auto)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))?;
+    }
+    let scope = app
+        .config
+        .scope_or_default(args.scope)
+        .map_err(CliError::user)?;
 
     // Get existing expertise
     let expertise = app
         .db
         .storage()
-        .get(&args.id, args.scope)
+        .get(&args.id, scope)
         .await
         .map_err(|e| CliError::system(format!("Database error: {}", e)))?
         .ok_or_else(|| {
-            CliError::user(format!(
+            not_found(format!(
                 "Expertise not found: {} (scope: {})",
-                args.id, args.scope
+                args.id, scope
             ))
         })?;
 
+    // Check the lock before spending an LLM call
+    let locked = expertise.metadata.locked;
+    if locked && !args.dry_run {
+        if !args.force {
+            return Err(locked_error(&args.id));
+        }
+        confirm_locked_override(&args.id, "Improve")?;
+    }
+
     // Improve it
+    let original = expertise.clone();
     let improved = app
         .generator
         .improve(expertise, &args.instruction)
         .await
-        .map_err(|e| CliError::system(format!("Failed to improve expertise: {}", e)))?;
+        .map_err(|e| generator_error("Failed to improve expertise", e))?;
+
+    if args.dry_run {
+        return Ok(format!(
+            "{}\n\n(dry run: {} was not changed)",
+            format_comparison(VersionComparison::new(&original, &improved)),
+            args.id
+        ));
+    }
 
     // Update in database
-    app.db
-        .storage()
-        .update(improved.clone())
-        .await
-        .map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
+    let storage = app.db.storage();
+    let result = if locked {
+        storage.update_forced(improved.clone()).await
+    } else {
+        storage.update(improved.clone()).await
+    };
+    result.map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
 
-    Ok(format!(
+    let mut output = format!(
         "✓ Improved expertise: {} → v{}",
         improved.id(),
         improved.version()
-    ))
+    );
+    if let Some(reason) = &improved.metadata.version_reason {
+        output.push_str(&format!("\n  Version: {}", reason));
+    }
+    Ok(output)
+}
+
+/// Refresh the fragments of an expertise from an updated log
+///
+/// The log is extracted as for `gen`, but only the fragments are replaced:
+/// the ID, scope, tags and relations are kept.
+///
+/// Usage:
+///   niwa regen rust-expert --file session.log
+///   niwa regen rust-expert --file session.log --update-description
+///   niwa regen rust-expert --file session.log --scope company --dry-run
+#[derive(Parser, Debug)]
+pub struct RegenArgs {
+    /// Expertise ID to regenerate
+    pub id: String,
+
+    /// Log file to extract the new fragments from
+    #[arg(short, long)]
+    pub file: PathBuf,
+
+    /// Scope (personal/me, company/org, project/proj; default: NIWA_DEFAULT_SCOPE,
+    /// then the nearest .niwarc, then `default_scope` in config.toml, then personal)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+
+    /// Also replace the description with the extracted one
+    #[arg(long)]
+    pub update_description: bool,
+
+    /// Regenerate even if the expertise is locked (asks for confirmation)
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show the proposed changes without saving them
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// File with domain context (glossary, conventions) added to the prompt
+    #[arg(long, value_name = "FILE")]
+    pub context_file: Option<PathBuf>,
+
+    /// Let the LLM choose a major, minor or patch bump (default: minor)
+    #[arg(long)]
+    pub auto_version: bool,
+
+    /// What to do when the extracted fragments contain possible secrets
+    #[arg(long, value_enum, default_value_t = OnSecret::Redact)]
+    pub on_secret: OnSecret,
+}
+
+#[sen::handler]
+pub async fn regen(state: State<AppState>, Args(args): Args<RegenArgs>) -> CliResult<String> {
+    let app = with_context_file(state.read().await.clone(), args.context_file.as_deref()).await?;
+    let mut app = with_on_secret(app, args.on_secret).await?;
+    if args.auto_version {
+        app = app
+            .with_version_strategy(VersionBumpStrategy::Auto)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to configure generator: {}", e)))?;
+    }
+
+    let log_content = std::fs::read_to_string(&args.file)
+        .map_err(|e| CliError::user(format!("Failed to read log file: {}", e)))?;
+    let scope = app
+        .config
+        .scope_or_default(args.scope)
+        .map_err(CliError::user)?;
+
+    let expertise = app
+        .db
+        .storage()
+        .get(&args.id, scope)
+        .await
+        .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            not_found(format!(
+                "Expertise not found: {} (scope: {})",
+                args.id, scope
+            ))
+        })?;
+
+    // Check the lock before spending an LLM call
+    let locked = expertise.metadata.locked;
+    if locked && !args.dry_run {
+        if !args.force {
+            return Err(locked_error(&args.id));
+        }
+        confirm_locked_override(&args.id, "Regenerate")?;
+    }
+
+    let original = expertise.clone();
+    let mut regenerated = app
+        .generator
+        .regenerate(expertise, &log_content, args.update_description)
+        .await
+        .map_err(|e| generator_error("Failed to regenerate expertise", e))?;
+    let source = args.file.display().to_string();
+    regenerated.set_source(source.as_str());
+    regenerated.metadata.source_file = Some(source);
+
+    if args.dry_run {
+        return Ok(format!(
+            "{}\n\n(dry run: {} was not changed)",
+            format_comparison(VersionComparison::new(&original, &regenerated)),
+            args.id
+        ));
+    }
+
+    let storage = app.db.storage();
+    let result = if locked {
+        storage.update_forced(regenerated.clone()).await
+    } else {
+        storage.update(regenerated.clone()).await
+    };
+    result.map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
+
+    let mut output = format!(
+        "✓ Regenerated expertise: {} → v{} ({} fragments, was {})",
+        regenerated.id(),
+        regenerated.version(),
+        regenerated.inner.content.len(),
+        original.inner.content.len()
+    );
+    if let Some(reason) = &regenerated.metadata.version_reason {
+        output.push_str(&format!("\n  Version: {}", reason));
+    }
+    Ok(output)
+}
+
+/// Merge several expertises into a new one
+///
+/// The source expertises are kept.
+///
+/// Usage:
+///   niwa merge rust-async tokio-patterns --id rust-async-merged
+///   niwa merge rust-async tokio-patterns --id rust-async-merged --description "Async Rust" --dry-run
+#[derive(Parser, Debug)]
+pub struct MergeArgs {
+    /// IDs of the expertises to merge
+    #[arg(required = true, num_args = 2..)]
+    pub ids: Vec<String>,
+
+    /// ID of the merged expertise
+    #[arg(long)]
+    pub id: String,
+
+    /// Description of the merged expertise (default: derived from the sources)
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Scope of the sources and the merged expertise (personal/me, company/org,
+    /// project/proj; default: NIWA_DEFAULT_SCOPE, then the nearest .niwarc, then
+    /// `default_scope` in config.toml, then personal)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+
+    /// Show the merged result and conflicts without saving it
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// File with domain context (glossary, conventions) added to the prompt
+    #[arg(long, value_name = "FILE")]
+    pub context_file: Option<PathBuf>,
+
+    /// What to do when the merged expertise contains possible secrets
+    #[arg(long, value_enum, default_value_t = OnSecret::Redact)]
+    pub on_secret: OnSecret,
+}
+
+#[sen::handler]
+pub async fn merge(state: State<AppState>, Args(args): Args<MergeArgs>) -> CliResult<String> {
+    let app = with_context_file(state.read().await.clone(), args.context_file.as_deref()).await?;
+    let app = with_on_secret(app, args.on_secret).await?;
+    let scope = app
+        .config
+        .scope_or_default(args.scope)
+        .map_err(CliError::user)?;
+
+    if !args.dry_run
+        && app
+            .db
+            .storage()
+            .exists(&args.id, scope)
+            .await
+            .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+    {
+        return Err(CliError::user(format!(
+            "Expertise already exists: {} (scope: {})",
+            args.id, scope
+        )));
+    }
+
+    let mut sources = Vec::with_capacity(args.ids.len());
+    for id in &args.ids {
+        sources.push(get_expertise(&app, id, scope).await?);
+    }
+    let description = args
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("Merged from {}", args.ids.join(", ")));
+
+    let (merged, conflicts) = app
+        .generator
+        .merge_with_conflicts(&sources, &args.id, &description, scope)
+        .await
+        .map_err(|e| generator_error("Failed to merge expertises", e))?;
+
+    let mut output = if args.dry_run {
+        format!(
+            "{}\nWould create: {} (from {})\n",
+            format_expertise(&merged, true, false, FragmentPage::default()),
+            merged.id(),
+            args.ids.join(", ")
+        )
+    } else {
+        app.db
+            .storage()
+            .create(merged.clone())
+            .await
+            .map_err(|e| CliError::system(format!("Failed to store expertise: {}", e)))?;
+        format!(
+            "✓ Merged {} into {} v{}\n",
+            args.ids.join(", "),
+            merged.id(),
+            merged.version()
+        )
+    };
+
+    if conflicts.is_empty() {
+        output.push_str("\nNo conflicts found.");
+    } else {
+        output.push_str(&format!("\nConflicts found ({}):\n", conflicts.len()));
+        for conflict in &conflicts {
+            output.push_str(&format!("  - {}\n", conflict));
+        }
+    }
+    if args.dry_run {
+        output.push_str("\n(dry run: nothing was saved)");
+    }
+
+    Ok(output.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language(Path::new("src/main.rs")), Some("rust"));
+        assert_eq!(
+            detect_language(Path::new("app/Component.TSX")),
+            Some("typescript")
+        );
+        assert_eq!(detect_language(Path::new("script.py")), Some("python"));
+        assert_eq!(detect_language(Path::new("README")), None);
+        assert_eq!(detect_language(Path::new("notes.docx")), None);
+    }
+
+    #[test]
+    fn test_enrich_args() {
+        let args = GenArgs::try_parse_from([
+            "gen",
+            "--enrich",
+            "rust-expert",
+            "--file",
+            "new.log",
+            "--min-new-fragments",
+            "3",
+        ])
+        .unwrap();
+        assert_eq!(args.enrich.as_deref(), Some("rust-expert"));
+        assert_eq!(args.min_new_fragments, 3);
+        assert!(args.id.is_none());
+
+        // --id is required unless enriching
+        assert!(GenArgs::try_parse_from(["gen", "--file", "new.log"]).is_err());
+        assert!(GenArgs::try_parse_from(["gen", "--enrich", "a", "--id", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_meeting_notes_args() {
+        let args = GenArgs::try_parse_from([
+            "gen",
+            "--from-meeting-notes",
+            "notes.md",
+            "--id",
+            "decisions",
+        ])
+        .unwrap();
+        assert_eq!(args.from_meeting_notes, Some(PathBuf::from("notes.md")));
+
+        assert!(GenArgs::try_parse_from(["gen", "--from-meeting-notes", "notes.md"]).is_err());
+        assert!(GenArgs::try_parse_from([
+            "gen",
+            "--from-meeting-notes",
+            "notes.md",
+            "--file",
+            "session.log",
+            "--id",
+            "x",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_tag_args() {
+        let args = GenArgs::try_parse_from([
+            "gen",
+            "--text",
+            "x",
+            "--id",
+            "tips",
+            "--tags",
+            "rust,async",
+            "--required-tags",
+            "team-a",
+        ])
+        .unwrap();
+        assert_eq!(args.tags, vec!["rust", "async"]);
+        assert_eq!(args.required_tags, vec!["team-a"]);
+
+        assert!(
+            GenArgs::try_parse_from(["gen", "--text", "x", "--enrich", "a", "--tags", "rust"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_confidence_threshold_args() {
+        let args = GenArgs::try_parse_from([
+            "gen",
+            "--text",
+            "x",
+            "--id",
+            "tips",
+            "--confidence-threshold",
+            "0.6",
+        ])
+        .unwrap();
+        assert_eq!(args.confidence_threshold, Some(0.6));
+
+        assert!(GenArgs::try_parse_from([
+            "gen",
+            "--from-code",
+            "lib.rs",
+            "--id",
+            "x",
+            "--confidence-threshold",
+            "0.6"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_fragment_limit_args() {
+        let parse = |args: &[&str]| {
+            GenArgs::try_parse_from(["gen", "--text", "x", "--id", "tips"].iter().chain(args))
+        };
+
+        let args = parse(&["--min-fragments", "3", "--max-fragments", "10"]).unwrap();
+        assert_eq!(args.min_fragments, Some(3));
+        assert_eq!(args.max_fragments, Some(10));
+
+        assert!(parse(&["--max-fragments", "0"]).is_err());
+        assert!(GenArgs::try_parse_from([
+            "gen",
+            "--text",
+            "x",
+            "--enrich",
+            "tips",
+            "--max-fragments",
+            "5"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_default_scope() {
+        let config = crate::config::Config {
+            default_scope: Some(Scope::Project),
+            ..Default::default()
+        };
+
+        let args = GenArgs::try_parse_from(["gen", "--text", "x", "--id", "tips"]).unwrap();
+        assert_eq!(args.scope, None);
+        assert_eq!(config.scope_or_default(args.scope), Ok(Scope::Project));
+
+        let args =
+            GenArgs::try_parse_from(["gen", "--text", "x", "--id", "tips", "--scope", "company"])
+                .unwrap();
+        assert_eq!(config.scope_or_default(args.scope), Ok(Scope::Company));
+    }
+
+    #[test]
+    fn test_changelog_args() {
+        let args = GenArgs::try_parse_from([
+            "gen",
+            "--from-changelog",
+            "CHANGELOG.md",
+            "--id",
+            "history",
+            "--split",
+        ])
+        .unwrap();
+        assert!(args.split);
+
+        // --split needs a changelog, and a changelog excludes other inputs
+        assert!(
+            GenArgs::try_parse_from(["gen", "--file", "a.log", "--id", "x", "--split"]).is_err()
+        );
+        assert!(GenArgs::try_parse_from([
+            "gen",
+            "--from-changelog",
+            "CHANGELOG.md",
+            "--file",
+            "a.log",
+            "--id",
+            "x"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_merge_args() {
+        let args = MergeArgs::try_parse_from([
+            "merge",
+            "rust-async",
+            "tokio-patterns",
+            "--id",
+            "merged",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert_eq!(args.ids, vec!["rust-async", "tokio-patterns"]);
+        assert!(args.dry_run);
+
+        assert!(MergeArgs::try_parse_from(["merge", "only-one", "--id", "merged"]).is_err());
+        assert!(MergeArgs::try_parse_from(["merge", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_improve_dry_run_args() {
+        let args =
+            ImproveArgs::try_parse_from(["improve", "rust-expert", "-i", "Tighten", "--dry-run"])
+                .unwrap();
+        assert!(args.dry_run);
+        assert!(!args.auto_version);
+
+        let args =
+            ImproveArgs::try_parse_from(["improve", "rust-expert", "-i", "Trim", "--auto-version"])
+                .unwrap();
+        assert!(args.auto_version);
+    }
+
+    #[test]
+    fn test_on_secret_args() {
+        let args = GenArgs::try_parse_from(["gen", "--text", "x", "--id", "tips"]).unwrap();
+        assert_eq!(args.on_secret, OnSecret::Redact);
+
+        let args = GenArgs::try_parse_from([
+            "gen",
+            "--text",
+            "x",
+            "--id",
+            "tips",
+            "--on-secret",
+            "reject",
+        ])
+        .unwrap();
+        assert_eq!(SecretPolicy::from(args.on_secret), SecretPolicy::Reject);
+
+        let args = ImproveArgs::try_parse_from([
+            "improve",
+            "rust-expert",
+            "-i",
+            "Trim",
+            "--on-secret",
+            "warn",
+        ])
+        .unwrap();
+        assert_eq!(args.on_secret, OnSecret::Warn);
+
+        assert!(GenArgs::try_parse_from([
+            "gen",
+            "--from-code",
+            "lib.rs",
+            "--id",
+            "x",
+            "--on-secret",
+            "warn"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_regen_args() {
+        let args =
+            RegenArgs::try_parse_from(["regen", "rust-expert", "--file", "new.log"]).unwrap();
+        assert_eq!(args.id, "rust-expert");
+        assert_eq!(args.file, PathBuf::from("new.log"));
+        assert_eq!(args.scope, None);
+        assert!(!args.update_description);
+
+        let args = RegenArgs::try_parse_from([
+            "regen",
+            "rust-expert",
+            "-f",
+            "new.log",
+            "--update-description",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert!(args.update_description);
+        assert!(args.dry_run);
+
+        assert!(RegenArgs::try_parse_from(["regen", "rust-expert"]).is_err());
+    }
 }
@@ -1,17 +1,32 @@
 //! Graph visualization commands
 
+use crate::exit_code::not_found;
+use crate::render::{to_csv, to_json, to_markdown, OutputFormat, Render};
 use crate::state::AppState;
-use clap::Parser;
-use niwa_core::{Scope, StorageOperations};
+use clap::{Parser, ValueEnum};
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::graph::Relation;
+use niwa_core::{Expertise, RelationType, Scope, StorageOperations};
 use sen::{Args, CliError, CliResult, State};
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
+/// PageRank iterations used unless `--iterations` says otherwise
+pub(crate) const PAGERANK_ITERATIONS: usize = 50;
+
+/// PageRank damping factor used unless `--damping` says otherwise
+pub(crate) const PAGERANK_DAMPING: f64 = 0.85;
+
 /// Display expertise dependency graph
 ///
 /// Usage:
 ///   niwa graph                    # Show all expertises and relations
 ///   niwa graph rust-expert        # Show subgraph centered on rust-expert
 ///   niwa graph --scope personal   # Filter by scope
+///   niwa graph --format d2 --group-by-scope > graph.d2
+///   niwa graph rust-expert --format json
+///   niwa graph --type uses --type requires   # Dependencies only, without conflicts
+///   niwa graph --rank             # Rank expertises by PageRank centrality
 #[derive(Parser, Debug)]
 pub struct GraphArgs {
     /// Optional expertise ID to center the graph on
@@ -24,12 +39,229 @@ pub struct GraphArgs {
     /// Maximum depth for subgraph (default: 2)
     #[arg(short, long, default_value = "2")]
     pub depth: usize,
+
+    /// Only show relations of this type (repeatable; uses, extends, conflicts, requires)
+    #[arg(
+        short = 't',
+        long = "type",
+        value_name = "TYPE",
+        conflicts_with = "rank"
+    )]
+    pub relation_types: Vec<RelationType>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: GraphFormat,
+
+    /// Wrap each scope's nodes in a container (d2 only)
+    #[arg(long)]
+    pub group_by_scope: bool,
+
+    /// List expertises by PageRank centrality instead of drawing the graph
+    #[arg(long, conflicts_with_all = ["id", "group_by_scope"])]
+    pub rank: bool,
+
+    /// PageRank iterations (with --rank)
+    #[arg(long, default_value_t = PAGERANK_ITERATIONS, requires = "rank")]
+    pub iterations: usize,
+
+    /// PageRank damping factor between 0 and 1 (with --rank)
+    #[arg(long, default_value_t = PAGERANK_DAMPING, requires = "rank")]
+    pub damping: f64,
+}
+
+/// Graph output format
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Indented tree for the terminal
+    Text,
+    /// D2 diagram source (render with `d2 input.d2 output.svg`)
+    D2,
+    /// Nodes and edges as JSON
+    Json,
+    /// One row per edge
+    Csv,
+    /// Markdown table of edges
+    Markdown,
+}
+
+impl GraphFormat {
+    /// The shared output format, for formats rendered from a [`GraphView`]
+    fn output_format(self) -> Option<OutputFormat> {
+        match self {
+            Self::Text | Self::D2 => None,
+            Self::Json => Some(OutputFormat::Json),
+            Self::Csv => Some(OutputFormat::Csv),
+            Self::Markdown => Some(OutputFormat::Markdown),
+        }
+    }
+}
+
+/// Nodes and edges of a (sub)graph
+#[derive(Debug, Serialize)]
+pub struct GraphView {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub scope: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub relation_type: String,
+    pub confidence: Option<f64>,
+}
+
+impl GraphView {
+    fn new(expertises: &[Expertise], relations: &[Relation]) -> Self {
+        Self {
+            nodes: expertises
+                .iter()
+                .map(|e| GraphNode {
+                    id: e.id().to_string(),
+                    scope: e.metadata.scope.to_string(),
+                })
+                .collect(),
+            edges: relations
+                .iter()
+                .map(|r| GraphEdge {
+                    from: r.from_id.clone(),
+                    to: r.to_id.clone(),
+                    relation_type: r.relation_type.to_string(),
+                    confidence: r.confidence(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Render for GraphView {
+    fn render(&self, format: OutputFormat) -> String {
+        const HEADER: [&str; 4] = ["from", "to", "relation_type", "confidence"];
+        let records = || {
+            self.edges.iter().map(|edge| {
+                vec![
+                    edge.from.clone(),
+                    edge.to.clone(),
+                    edge.relation_type.clone(),
+                    edge.confidence.map(|c| c.to_string()).unwrap_or_default(),
+                ]
+            })
+        };
+
+        match format {
+            OutputFormat::Text => self
+                .edges
+                .iter()
+                .map(|edge| format!("{} -[{}]-> {}", edge.from, edge.relation_type, edge.to))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Csv => to_csv(&HEADER, records()),
+            OutputFormat::Markdown => to_markdown(&HEADER, records()),
+        }
+    }
+}
+
+/// An expertise with its PageRank score
+#[derive(Debug, Serialize)]
+pub struct RankedExpertise {
+    pub rank: usize,
+    pub id: String,
+    pub scope: String,
+    pub score: f64,
+    /// Relations pointing at this expertise
+    pub incoming: usize,
+    /// Relations starting from this expertise
+    pub outgoing: usize,
+}
+
+impl Render for [RankedExpertise] {
+    fn render(&self, format: OutputFormat) -> String {
+        const HEADER: [&str; 6] = ["rank", "id", "scope", "score", "incoming", "outgoing"];
+        let records = || {
+            self.iter().map(|ranked| {
+                vec![
+                    ranked.rank.to_string(),
+                    ranked.id.clone(),
+                    ranked.scope.clone(),
+                    format!("{:.4}", ranked.score),
+                    ranked.incoming.to_string(),
+                    ranked.outgoing.to_string(),
+                ]
+            })
+        };
+
+        match format {
+            OutputFormat::Text => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        Cell::new("Rank").fg(Color::Cyan),
+                        Cell::new("ID").fg(Color::Cyan),
+                        Cell::new("Scope").fg(Color::Cyan),
+                        Cell::new("Score").fg(Color::Cyan),
+                        Cell::new("In").fg(Color::Cyan),
+                        Cell::new("Out").fg(Color::Cyan),
+                    ]);
+                for record in records() {
+                    table.add_row(record);
+                }
+                format!(
+                    "\n{}\n\nRanked {} expertises by PageRank",
+                    table,
+                    self.len()
+                )
+            }
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Csv => to_csv(&HEADER, records()),
+            OutputFormat::Markdown => to_markdown(&HEADER, records()),
+        }
+    }
+}
+
+/// Rank the listed expertises that have relations, most central first
+pub(crate) fn rank_expertises(
+    scores: Vec<(String, f64)>,
+    expertises: &[Expertise],
+    relations: &[Relation],
+) -> Vec<RankedExpertise> {
+    let scopes: HashMap<&str, String> = expertises
+        .iter()
+        .map(|e| (e.id(), e.metadata.scope.to_string()))
+        .collect();
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| Some((scopes.get(id.as_str())?.clone(), id, score)))
+        .enumerate()
+        .map(|(i, (scope, id, score))| RankedExpertise {
+            rank: i + 1,
+            incoming: relations.iter().filter(|r| r.to_id == id).count(),
+            outgoing: relations.iter().filter(|r| r.from_id == id).count(),
+            id,
+            scope,
+            score,
+        })
+        .collect()
 }
 
 #[sen::handler]
 pub async fn graph(state: State<AppState>, Args(args): Args<GraphArgs>) -> CliResult<String> {
     let app = state.read().await;
 
+    if args.group_by_scope && args.format != GraphFormat::D2 {
+        return Err(CliError::user("--group-by-scope requires --format d2"));
+    }
+
     // Get all expertises
     let expertises = if let Some(scope) = args.scope {
         app.db
@@ -49,19 +281,53 @@ pub async fn graph(state: State<AppState>, Args(args): Args<GraphArgs>) -> CliRe
         return Ok("No expertises found.".to_string());
     }
 
-    // Get all relations
-    let mut all_relations = Vec::new();
-    for exp in &expertises {
-        let relations = app
+    // Get all relations in one query, keeping edges that start from a listed expertise
+    let ids: HashSet<&str> = expertises.iter().map(|e| e.id()).collect();
+    let all_relations: Vec<_> = app
+        .db
+        .graph()
+        .all_relations()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?
+        .into_iter()
+        .filter(|r| ids.contains(r.from_id.as_str()) && has_type(r, &args.relation_types))
+        .collect();
+
+    if args.rank {
+        let format = match args.format.output_format() {
+            Some(format) => format,
+            None if args.format == GraphFormat::Text => OutputFormat::resolve(None, app.agent_mode),
+            None => return Err(CliError::user("--rank does not support --format d2")),
+        };
+        if !(0.0..=1.0).contains(&args.damping) {
+            return Err(CliError::user("--damping must be between 0.0 and 1.0"));
+        }
+
+        let scores = app
             .db
             .graph()
-            .get_outgoing(exp.id())
+            .pagerank(args.iterations, args.damping)
             .await
-            .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
-        all_relations.extend(relations);
+            .map_err(|e| CliError::system(format!("Failed to rank expertises: {}", e)))?;
+        let ranked = rank_expertises(scores, &expertises, &all_relations);
+        if ranked.is_empty() && format == OutputFormat::Text {
+            return Ok(format!(
+                "Found {} expertises but no relations to rank.\nUse 'niwa link' to create relations.",
+                expertises.len()
+            ));
+        }
+        return Ok(ranked.render(format));
     }
 
-    if all_relations.is_empty() {
+    if all_relations.is_empty() && args.format == GraphFormat::Text {
+        if !args.relation_types.is_empty() {
+            let types: Vec<&str> = args.relation_types.iter().map(|t| t.as_str()).collect();
+            return Ok(format!(
+                "Found {} expertises but no {} relations.",
+                expertises.len(),
+                types.join("/")
+            ));
+        }
         return Ok(format!(
             "Found {} expertises but no relations.\nUse 'niwa link' to create relations.",
             expertises.len()
@@ -91,20 +357,43 @@ pub async fn graph(state: State<AppState>, Args(args): Args<GraphArgs>) -> CliRe
         }
 
         if !found {
-            return Err(CliError::user(format!(
-                "Expertise not found: {}",
-                center_id
-            )));
+            return Err(not_found(format!("Expertise not found: {}", center_id)));
         }
 
-        build_subgraph(&center_id, &all_relations, args.depth)
+        if args.format == GraphFormat::Text {
+            build_subgraph(&center_id, &all_relations, args.depth)
+        } else {
+            let relations = reachable_relations(&center_id, &all_relations, args.depth);
+            let nodes: HashSet<&str> = relations
+                .iter()
+                .flat_map(|r| [r.from_id.as_str(), r.to_id.as_str()])
+                .chain([center_id.as_str()])
+                .collect();
+            let expertises: Vec<Expertise> = expertises
+                .into_iter()
+                .filter(|e| nodes.contains(e.id()))
+                .collect();
+            match args.format.output_format() {
+                Some(format) => GraphView::new(&expertises, &relations).render(format),
+                None => build_d2_graph(&expertises, &relations, args.group_by_scope),
+            }
+        }
     } else {
-        build_full_graph(&expertises, &all_relations)
+        match (args.format, args.format.output_format()) {
+            (GraphFormat::Text, _) => build_full_graph(&expertises, &all_relations),
+            (_, Some(format)) => GraphView::new(&expertises, &all_relations).render(format),
+            _ => build_d2_graph(&expertises, &all_relations, args.group_by_scope),
+        }
     };
 
     Ok(output)
 }
 
+/// Whether `relation` is of one of `types` (any type when `types` is empty)
+fn has_type(relation: &Relation, types: &[RelationType]) -> bool {
+    types.is_empty() || types.contains(&relation.relation_type)
+}
+
 /// Build a full graph visualization
 fn build_full_graph(
     expertises: &[niwa_core::Expertise],
@@ -229,7 +518,10 @@ fn display_node(
 
             output.push_str(&format!(
                 "{}{}[{}]→ {}\n",
-                child_indent_str, connector, relation.relation_type, relation.to_id
+                child_indent_str,
+                connector,
+                relation_label(relation),
+                relation.to_id
             ));
 
             // Recursively display child's children
@@ -275,7 +567,10 @@ fn display_node_with_depth(
 
             output.push_str(&format!(
                 "{}{}[{}]→ {}\n",
-                child_indent_str, connector, relation.relation_type, relation.to_id
+                child_indent_str,
+                connector,
+                relation_label(relation),
+                relation.to_id
             ));
 
             // Recursively display child's children with depth limit
@@ -292,3 +587,304 @@ fn display_node_with_depth(
         }
     }
 }
+
+/// Collect relations reachable from `center_id` within `max_depth` hops
+fn reachable_relations(
+    center_id: &str,
+    relations: &[niwa_core::graph::Relation],
+    max_depth: usize,
+) -> Vec<niwa_core::graph::Relation> {
+    let mut visited: HashSet<&str> = HashSet::from([center_id]);
+    let mut frontier = vec![center_id];
+    let mut reachable = Vec::new();
+
+    for _ in 0..max_depth {
+        let mut next = Vec::new();
+        for relation in relations {
+            if frontier.contains(&relation.from_id.as_str()) {
+                reachable.push(relation.clone());
+                if visited.insert(relation.to_id.as_str()) {
+                    next.push(relation.to_id.as_str());
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    reachable
+}
+
+/// Build a D2 diagram of the given expertises and relations
+///
+/// Personal expertises are drawn as ovals and company ones with a green fill.
+/// With `group_by_scope`, each scope becomes a container and edges use the
+/// container-qualified node keys. Relation endpoints outside `expertises`
+/// are drawn at the top level.
+fn build_d2_graph(
+    expertises: &[Expertise],
+    relations: &[niwa_core::graph::Relation],
+    group_by_scope: bool,
+) -> String {
+    let scopes: HashMap<&str, Scope> = expertises
+        .iter()
+        .map(|e| (e.id(), e.metadata.scope))
+        .collect();
+
+    // Full D2 key for a node, qualified by its container when grouping
+    let key = |id: &str| match scopes.get(id) {
+        Some(scope) if group_by_scope => format!("{}.{}", scope, d2_quote(id)),
+        _ => d2_quote(id),
+    };
+
+    let mut output = String::new();
+
+    if group_by_scope {
+        for scope in [Scope::Personal, Scope::Company, Scope::Project] {
+            let members: Vec<&Expertise> = expertises
+                .iter()
+                .filter(|e| e.metadata.scope == scope)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+
+            output.push_str(&format!("{}: {{\n", scope));
+            for exp in members {
+                push_d2_node(&mut output, &d2_quote(exp.id()), scope, "  ");
+            }
+            output.push_str("}\n");
+        }
+    } else {
+        for exp in expertises {
+            push_d2_node(&mut output, &d2_quote(exp.id()), exp.metadata.scope, "");
+        }
+    }
+
+    if !relations.is_empty() {
+        output.push('\n');
+    }
+    for relation in relations {
+        output.push_str(&format!(
+            "{} -> {}: {}\n",
+            key(&relation.from_id),
+            key(&relation.to_id),
+            relation_label(relation)
+        ));
+    }
+
+    output
+}
+
+/// Append a D2 node declaration styled by scope
+/// Edge label: the relation type, with its confidence when recorded
+fn relation_label(relation: &niwa_core::graph::Relation) -> String {
+    match relation.confidence() {
+        Some(confidence) => format!("{} {:.2}", relation.relation_type, confidence),
+        None => relation.relation_type.to_string(),
+    }
+}
+
+fn push_d2_node(output: &mut String, key: &str, scope: Scope, indent: &str) {
+    match scope {
+        Scope::Personal => output.push_str(&format!("{}{}.shape: oval\n", indent, key)),
+        Scope::Company => output.push_str(&format!("{}{}.style.fill: \"#e8f5e9\"\n", indent, key)),
+        Scope::Project => output.push_str(&format!("{}{}\n", indent, key)),
+    }
+}
+
+/// Quote a node ID as a D2 key
+fn d2_quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expertise(id: &str, scope: Scope) -> Expertise {
+        let mut exp = Expertise::new(id, "1.0.0");
+        exp.metadata.scope = scope;
+        exp
+    }
+
+    fn typed_relation(from: &str, to: &str, relation_type: RelationType) -> Relation {
+        Relation {
+            relation_type,
+            ..relation(from, to)
+        }
+    }
+
+    fn relation(from: &str, to: &str) -> Relation {
+        Relation {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type: RelationType::Uses,
+            metadata: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_graph_view() {
+        let expertises = vec![
+            expertise("rust-expert", Scope::Personal),
+            expertise("error-handling", Scope::Company),
+        ];
+        let relations = vec![relation("rust-expert", "error-handling")];
+        let view = GraphView::new(&expertises, &relations);
+
+        assert_eq!(
+            view.render(OutputFormat::Csv),
+            "from,to,relation_type,confidence\nrust-expert,error-handling,uses,"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&view.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["nodes"][1]["scope"], "company");
+        assert_eq!(json["edges"][0]["to"], "error-handling");
+    }
+
+    #[test]
+    fn test_build_d2_graph() {
+        let expertises = vec![
+            expertise("rust-expert", Scope::Personal),
+            expertise("error-handling", Scope::Company),
+        ];
+        let relations = vec![relation("rust-expert", "error-handling")];
+
+        let d2 = build_d2_graph(&expertises, &relations, false);
+        assert_eq!(
+            d2,
+            "\"rust-expert\".shape: oval\n\
+             \"error-handling\".style.fill: \"#e8f5e9\"\n\
+             \n\
+             \"rust-expert\" -> \"error-handling\": uses\n"
+        );
+    }
+
+    #[test]
+    fn test_relation_label() {
+        let mut inferred = relation("a", "b");
+        inferred.metadata = Some(r#"{"confidence":0.8,"source":"auto-link"}"#.to_string());
+        assert_eq!(relation_label(&inferred), "uses 0.80");
+
+        let mut legacy = relation("a", "b");
+        legacy.metadata = Some("plain reason".to_string());
+        assert_eq!(relation_label(&legacy), "uses");
+    }
+
+    #[test]
+    fn test_build_d2_graph_grouped() {
+        let expertises = vec![
+            expertise("rust-expert", Scope::Personal),
+            expertise("error-handling", Scope::Company),
+        ];
+        let relations = vec![
+            relation("rust-expert", "error-handling"),
+            relation("rust-expert", "elsewhere"),
+        ];
+
+        let d2 = build_d2_graph(&expertises, &relations, true);
+        assert!(d2.contains("personal: {\n  \"rust-expert\".shape: oval\n}\n"));
+        assert!(d2.contains("company: {\n  \"error-handling\".style.fill: \"#e8f5e9\"\n}\n"));
+        assert!(d2.contains("personal.\"rust-expert\" -> company.\"error-handling\": uses\n"));
+        assert!(d2.contains("personal.\"rust-expert\" -> \"elsewhere\": uses\n"));
+        assert!(!d2.contains("project: {"));
+    }
+
+    #[test]
+    fn test_reachable_relations() {
+        let relations = vec![relation("a", "b"), relation("b", "c"), relation("x", "y")];
+
+        let reachable = reachable_relations("a", &relations, 1);
+        assert_eq!(reachable.len(), 1);
+
+        let reachable = reachable_relations("a", &relations, 5);
+        let targets: Vec<_> = reachable.iter().map(|r| r.to_id.as_str()).collect();
+        assert_eq!(targets, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_rank_expertises() {
+        let expertises = vec![
+            expertise("core", Scope::Personal),
+            expertise("user", Scope::Company),
+        ];
+        let relations = vec![relation("user", "core")];
+        let scores = vec![
+            ("core".to_string(), 0.6),
+            ("other-scope".to_string(), 0.3),
+            ("user".to_string(), 0.1),
+        ];
+
+        let ranked = rank_expertises(scores, &expertises, &relations);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!((ranked[0].rank, ranked[0].id.as_str()), (1, "core"));
+        assert_eq!((ranked[0].incoming, ranked[0].outgoing), (1, 0));
+        assert_eq!((ranked[1].rank, ranked[1].scope.as_str()), (2, "company"));
+        assert_eq!(
+            ranked.render(OutputFormat::Csv),
+            "rank,id,scope,score,incoming,outgoing\n\
+             1,core,personal,0.6000,1,0\n\
+             2,user,company,0.1000,0,1"
+        );
+    }
+
+    #[test]
+    fn test_rank_args() {
+        let args = GraphArgs::try_parse_from(["graph", "--rank", "--damping", "0.9"]).unwrap();
+        assert!(args.rank);
+        assert_eq!(args.damping, 0.9);
+        assert!(GraphArgs::try_parse_from(["graph", "--iterations", "10"]).is_err());
+        assert!(GraphArgs::try_parse_from(["graph", "rust-expert", "--rank"]).is_err());
+    }
+
+    #[test]
+    fn test_filter_by_type() {
+        let expertises = vec![
+            expertise("app", Scope::Project),
+            expertise("db", Scope::Project),
+            expertise("orm", Scope::Project),
+        ];
+        let relations = vec![
+            typed_relation("app", "db", RelationType::Requires),
+            typed_relation("app", "orm", RelationType::Uses),
+            typed_relation("db", "orm", RelationType::Conflicts),
+        ];
+        let types = [RelationType::Uses, RelationType::Requires];
+        let filtered: Vec<Relation> = relations
+            .into_iter()
+            .filter(|r| has_type(r, &types))
+            .collect();
+        assert_eq!(filtered.len(), 2);
+
+        let tree = build_full_graph(&expertises, &filtered);
+        assert!(tree.contains("[requires]→ db"));
+        assert!(tree.contains("[uses]→ orm"));
+        assert!(!tree.contains("conflicts"));
+        assert!(tree.ends_with("Total: 3 expertises, 2 relations"));
+
+        let csv = GraphView::new(&expertises, &filtered).render(OutputFormat::Csv);
+        assert!(!csv.contains("conflicts"));
+
+        // No types given keeps everything
+        assert!(has_type(
+            &typed_relation("db", "orm", RelationType::Conflicts),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn test_type_args() {
+        let args =
+            GraphArgs::try_parse_from(["graph", "--type", "uses", "-t", "requires"]).unwrap();
+        assert_eq!(
+            args.relation_types,
+            vec![RelationType::Uses, RelationType::Requires]
+        );
+        assert!(GraphArgs::try_parse_from(["graph", "--type", "supersedes"]).is_err());
+        assert!(GraphArgs::try_parse_from(["graph", "--type", "uses", "--rank"]).is_err());
+    }
+}
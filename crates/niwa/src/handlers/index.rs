@@ -0,0 +1,41 @@
+//! Search index maintenance commands
+
+use crate::state::AppState;
+use clap::{Parser, Subcommand};
+use sen::{Args, CliError, CliResult, State};
+
+/// Maintain the full-text search index
+///
+/// Usage:
+///   niwa index rebuild
+#[derive(Parser, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub command: Option<IndexCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// Rebuild the FTS5 index from stored expertises
+    Rebuild,
+}
+
+#[sen::handler]
+pub async fn index(state: State<AppState>, Args(args): Args<IndexArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    match args.command {
+        Some(IndexCommand::Rebuild) => {
+            let count = app
+                .db
+                .query()
+                .rebuild_index()
+                .await
+                .map_err(|e| CliError::system(format!("Failed to rebuild index: {}", e)))?;
+            Ok(format!("✓ Rebuilt search index ({} expertises)", count))
+        }
+        None => Err(CliError::user(
+            "No subcommand specified. Use 'index --help' to see available commands.",
+        )),
+    }
+}
@@ -0,0 +1,101 @@
+//! Info command - environment diagnostics for bug reports
+
+use crate::state::AppState;
+use clap::Parser;
+use sen::{Args, CliError, CliResult, State};
+use serde::Serialize;
+
+/// Show database, version and provider information
+///
+/// Usage:
+///   niwa info
+///   niwa info --agent-mode   # JSON output
+#[derive(Parser, Debug)]
+pub struct InfoArgs {}
+
+/// Diagnostic information about the NIWA installation
+#[derive(Serialize, Debug)]
+struct Info {
+    database_path: String,
+    database_size_bytes: u64,
+    schema_version: i64,
+    niwa_core_version: &'static str,
+    niwa_generator_version: &'static str,
+    llm_provider: String,
+    llm_model: String,
+    expertise_count: usize,
+    relation_count: usize,
+}
+
+#[sen::handler]
+pub async fn info(state: State<AppState>, Args(_args): Args<InfoArgs>) -> CliResult<String> {
+    let app = state.read().await;
+    let db_err = |e: niwa_core::Error| CliError::system(format!("Database error: {}", e));
+
+    let path = app.db.path();
+    let options = app.generator.options();
+
+    let info = Info {
+        database_path: path.display().to_string(),
+        database_size_bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        schema_version: app.db.schema_version().await.map_err(db_err)?,
+        niwa_core_version: niwa_core::VERSION,
+        niwa_generator_version: niwa_generator::VERSION,
+        llm_provider: format!("{:?}", options.provider).to_lowercase(),
+        llm_model: options.model.clone(),
+        expertise_count: app.db.query().count(None).await.map_err(db_err)?,
+        relation_count: app.db.graph().count_relations().await.map_err(db_err)?,
+    };
+
+    if app.agent_mode {
+        return serde_json::to_string_pretty(&info)
+            .map_err(|e| CliError::system(format!("Failed to serialize info: {}", e)));
+    }
+
+    let mut output = String::new();
+    output.push_str(&format!("Database:       {}\n", info.database_path));
+    output.push_str(&format!(
+        "Size:           {}\n",
+        format_size(info.database_size_bytes)
+    ));
+    output.push_str(&format!("Schema:         v{}\n", info.schema_version));
+    output.push_str(&format!("niwa-core:      {}\n", info.niwa_core_version));
+    output.push_str(&format!(
+        "niwa-generator: {}\n",
+        info.niwa_generator_version
+    ));
+    output.push_str(&format!(
+        "LLM:            {} ({})\n",
+        info.llm_provider, info.llm_model
+    ));
+    output.push_str(&format!("Expertises:     {}\n", info.expertise_count));
+    output.push_str(&format!("Relations:      {}", info.relation_count));
+
+    Ok(output)
+}
+
+/// Format a byte count for display
+fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
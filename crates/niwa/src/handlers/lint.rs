@@ -0,0 +1,94 @@
+//! Lint command - find (and fix) data problems in stored expertises
+
+use crate::state::AppState;
+use clap::Parser;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Scope, StorageOperations};
+use sen::{Args, CliError, CliResult, State};
+
+/// Check stored expertises for duplicate text fragments
+///
+/// Repeated `improve` runs could append the same text fragment more than once.
+/// Without --fix, problems are only reported.
+///
+/// Usage:
+///   niwa lint
+///   niwa lint --fix
+///   niwa lint --scope company --fix
+#[derive(Parser, Debug)]
+pub struct LintArgs {
+    /// Save the normalized expertises (locked expertises are skipped)
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Filter by scope (personal/me, company/org, project/proj)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+}
+
+#[sen::handler]
+pub async fn lint(state: State<AppState>, Args(args): Args<LintArgs>) -> CliResult<String> {
+    let app = state.read().await;
+    let storage = app.db.storage();
+
+    let expertises = match args.scope {
+        Some(scope) => storage.list(scope).await,
+        None => storage.list_all().await,
+    }
+    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+    let checked = expertises.len();
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Scope").fg(Color::Green),
+            Cell::new("Duplicate Fragments").fg(Color::Green),
+            Cell::new("Status").fg(Color::Green),
+        ]);
+
+    let mut problems = 0;
+    let mut fixed = 0;
+    for mut expertise in expertises {
+        let removed = expertise.normalize();
+        if removed == 0 {
+            continue;
+        }
+        problems += 1;
+
+        let status = if !args.fix {
+            "found"
+        } else if expertise.metadata.locked {
+            "skipped (locked)"
+        } else {
+            storage.update(expertise.clone()).await.map_err(|e| {
+                CliError::system(format!("Failed to update '{}': {}", expertise.id(), e))
+            })?;
+            fixed += 1;
+            "fixed"
+        };
+
+        table.add_row(vec![
+            expertise.id().to_string(),
+            expertise.metadata.scope.to_string(),
+            removed.to_string(),
+            status.to_string(),
+        ]);
+    }
+
+    if problems == 0 {
+        return Ok(format!("✓ No problems found in {} expertises", checked));
+    }
+
+    let summary = if args.fix {
+        format!("✓ Fixed {} of {} expertises with problems", fixed, problems)
+    } else {
+        format!(
+            "{} of {} expertises have problems (run with --fix to repair)",
+            problems, checked
+        )
+    };
+    Ok(format!("\n{}\n\n{}", table, summary))
+}
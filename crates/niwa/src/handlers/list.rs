@@ -1,74 +1,200 @@
 //! List commands
 
+use crate::handlers::graph::{PAGERANK_DAMPING, PAGERANK_ITERATIONS};
+use crate::render::{ExpertiseView, OutputFormat, Render};
+use crate::scope_filter::ScopeFilter;
 use crate::state::AppState;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
-use niwa_core::{Scope, StorageOperations};
+use niwa_core::{ExpertiseStatus, SearchOptions};
 use sen::{Args, CliError, CliResult, State};
+use std::collections::HashMap;
 
 /// List all expertises
 ///
 /// Usage:
 ///   niwa list
 ///   niwa list --scope personal
+///   niwa list --scope all
+///   niwa list --tag rust --modified-since 2025-01-01
+///   niwa list --created-before 2024-06-30
+///   niwa list --format csv > expertises.csv
+///   niwa list --sort graph-score
+///   niwa list --sort health-score     # cached by 'niwa expertise score-all'
+///   niwa list --translated-only
+///   niwa list --status draft
 #[derive(Parser, Debug)]
 pub struct ListArgs {
-    /// Filter by scope (personal, team, company)
+    /// Filter by scope (all, personal/me, company/org, project/proj)
+    ///
+    /// Defaults to $NIWA_SCOPE_FILTER, or all scopes when unset.
     #[arg(short, long)]
-    pub scope: Option<Scope>,
+    pub scope: Option<ScopeFilter>,
+
+    /// Filter by tag (repeatable, all tags must match)
+    #[arg(short, long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Only show expertises modified on or after this date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    pub modified_since: Option<i64>,
+
+    /// Only show expertises modified on or before this date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_end_of_day)]
+    pub modified_before: Option<i64>,
+
+    /// Only show expertises created on or after this date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    pub created_since: Option<i64>,
+
+    /// Only show expertises created on or before this date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_end_of_day)]
+    pub created_before: Option<i64>,
+
+    /// Only show expertises with this review status (draft, reviewed, deprecated)
+    #[arg(long)]
+    pub status: Option<ExpertiseStatus>,
+
+    /// Only show translations (created by `niwa expertise translate`)
+    #[arg(long)]
+    pub translated_only: bool,
+
+    /// Sort order (scope: by scope, then most recently updated first;
+    /// graph-score: highest PageRank importance first; health-score and
+    /// pagerank: cached scores from `niwa expertise score-all`)
+    #[arg(long, value_enum, default_value = "scope")]
+    pub sort: ListSort,
+
+    /// Output format (default: text, or json with --agent-mode)
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+}
+
+/// Sort order of `niwa list`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListSort {
+    /// By scope, then most recently updated first
+    Scope,
+    /// Highest PageRank score in the relation graph first
+    GraphScore,
+    /// Highest cached health score first
+    HealthScore,
+    /// Highest cached PageRank score first
+    Pagerank,
+}
+
+/// Age in days after which `list` shows an expertise as stale, matching
+/// the `niwa stale` default
+const DEFAULT_STALE_AFTER_DAYS: u32 = 180;
+
+/// Parse a `YYYY-MM-DD` date into a Unix timestamp (midnight UTC)
+fn parse_date(s: &str) -> Result<i64, String> {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp())
+        .map_err(|e| format!("Invalid date '{}' (expected YYYY-MM-DD): {}", s, e))
+}
+
+/// Parse a `YYYY-MM-DD` date into the last second of that day (UTC)
+///
+/// The `--*-before` filters compare with `<=`, so this keeps the whole day.
+fn parse_end_of_day(s: &str) -> Result<i64, String> {
+    parse_date(s).map(|midnight| midnight + 24 * 60 * 60 - 1)
 }
 
 #[sen::handler]
 pub async fn list(state: State<AppState>, Args(args): Args<ListArgs>) -> CliResult<String> {
     let app = state.read().await;
 
-    let expertises = if let Some(scope) = args.scope {
-        app.db.storage().list(scope).await
-    } else {
-        app.db.storage().list_all().await
+    let scope_filter = ScopeFilter::resolve(args.scope).map_err(CliError::user)?;
+
+    let mut options = SearchOptions::new().tags(args.tags);
+    if let Some(scope) = scope_filter.scope() {
+        options = options.scope(scope);
+    }
+    if let Some(status) = args.status {
+        options = options.status(status);
+    }
+    if let Some(ts) = args.modified_since {
+        options = options.modified_since(ts);
+    }
+    if let Some(ts) = args.modified_before {
+        options = options.modified_before(ts);
+    }
+    if let Some(ts) = args.created_since {
+        options = options.created_since(ts);
+    }
+    if let Some(ts) = args.created_before {
+        options = options.created_before(ts);
     }
-    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
 
-    if expertises.is_empty() {
-        return Ok("No expertises found.".to_string());
+    let mut expertises = app
+        .db
+        .query()
+        .list(options)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+    if args.translated_only {
+        expertises.retain(|e| e.metadata.translated_from.is_some());
     }
 
-    // Build table
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("ID").fg(Color::Green),
-            Cell::new("Version").fg(Color::Green),
-            Cell::new("Scope").fg(Color::Green),
-            Cell::new("Tags").fg(Color::Green),
-            Cell::new("Description").fg(Color::Green),
-        ]);
+    if args.sort == ListSort::GraphScore {
+        let scores: HashMap<String, f64> = app
+            .db
+            .graph()
+            .pagerank(PAGERANK_ITERATIONS, PAGERANK_DAMPING)
+            .await
+            .map_err(|e| CliError::system(format!("Failed to score expertises: {}", e)))?
+            .into_iter()
+            .collect();
+        let score = |id: &str| scores.get(id).copied().unwrap_or(0.0);
+        // Stable, so expertises without relations keep their scope order
+        expertises.sort_by(|a, b| score(b.id()).total_cmp(&score(a.id())));
+    }
 
-    for exp in &expertises {
-        let tags = exp.tags().join(", ");
-        let description = exp.description();
-        let truncated_desc = if description.len() > 50 {
-            format!("{}...", &description[..50])
-        } else {
-            description
+    if matches!(args.sort, ListSort::HealthScore | ListSort::Pagerank) {
+        let cached = app
+            .db
+            .storage()
+            .list_scores()
+            .await
+            .map_err(|e| CliError::system(format!("Failed to read cached scores: {}", e)))?;
+        if cached.is_empty() {
+            return Err(CliError::user(
+                "No cached scores. Run 'niwa expertise score-all' first.",
+            ));
+        }
+        // Unscored expertises go last
+        let score = |id: &str| {
+            cached
+                .get(id)
+                .map_or(f64::NEG_INFINITY, |s| match args.sort {
+                    ListSort::HealthScore => s.health_score,
+                    _ => s.pagerank_score,
+                })
         };
+        expertises.sort_by(|a, b| score(b.id()).total_cmp(&score(a.id())));
+    }
 
-        table.add_row(vec![
-            exp.id(),
-            exp.version(),
-            &exp.metadata.scope.to_string(),
-            &tags,
-            &truncated_desc,
-        ]);
+    let format = OutputFormat::resolve(args.format, app.agent_mode);
+    if expertises.is_empty() && format == OutputFormat::Text {
+        return Ok("No expertises found.".to_string());
     }
 
-    Ok(format!(
-        "\n{}\n\nTotal: {} expertises",
-        table,
-        expertises.len()
-    ))
+    let stale_after_days = app
+        .config
+        .list
+        .stale_after_days
+        .unwrap_or(DEFAULT_STALE_AFTER_DAYS);
+    let cutoff = chrono::Utc::now().timestamp() - i64::from(stale_after_days) * 24 * 60 * 60;
+    let views: Vec<ExpertiseView> = expertises
+        .iter()
+        .map(|expertise| ExpertiseView {
+            stale: expertise.metadata.updated_at < cutoff,
+            ..ExpertiseView::from(expertise)
+        })
+        .collect();
+    Ok(views.render(format))
 }
 
 /// List all tags
@@ -105,3 +231,28 @@ pub async fn tags(state: State<AppState>) -> CliResult<String> {
 
     Ok(format!("\n{}", table))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-02").unwrap(), 86_400);
+        assert!(parse_date("2025-13-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_end_of_day() {
+        // Midday on the boundary date is "on or before" it
+        let midday = parse_date("2025-01-15").unwrap() + 12 * 60 * 60;
+        assert!(midday <= parse_end_of_day("2025-01-15").unwrap());
+        assert!(midday > parse_end_of_day("2025-01-14").unwrap());
+        assert!(parse_date("2025-01-16").unwrap() > parse_end_of_day("2025-01-15").unwrap());
+
+        let args = ListArgs::try_parse_from(["list", "--created-before", "2025-01-15"]).unwrap();
+        assert_eq!(args.created_before, parse_end_of_day("2025-01-15").ok());
+        assert!(ListArgs::try_parse_from(["list", "--modified-before", "15/01/2025"]).is_err());
+    }
+}
@@ -1,10 +1,23 @@
 //! Command handlers
 
+pub mod bundle;
+pub mod compare;
 pub mod crawler;
+pub mod expertise;
+pub mod export;
+pub mod fragment;
 pub mod gen;
 pub mod graph;
+pub mod index;
+pub mod info;
+pub mod lint;
 pub mod list;
+pub mod open;
+pub mod related;
 pub mod relations;
+pub mod retag;
 pub mod search;
 pub mod show;
+pub mod stale;
+pub mod status;
 pub mod tutorial;
@@ -0,0 +1,160 @@
+//! Open command - edit an expertise's JSON by hand
+
+use crate::handlers::expertise::{edit_in_editor, get_expertise, locked_error, resolve_id};
+use crate::state::AppState;
+use clap::Parser;
+use niwa_core::{Expertise, Scope, StorageOperations};
+use niwa_generator::{bump_version, SemverBump};
+use sen::{Args, CliError, CliResult, State};
+
+/// Edit an expertise as JSON in $EDITOR
+///
+/// The stored expertise is opened as pretty-printed JSON. On save it is
+/// validated and updated, saving the previous version in the history. Unless
+/// you changed the version yourself, the minor version is bumped. The id and
+/// scope can't be changed here. Nothing happens if the file is saved
+/// unchanged.
+///
+/// Usage:
+///   niwa open rust-expert
+///   niwa open rust-expert --scope company
+///   EDITOR="code --wait" niwa open rust-expert
+#[derive(Parser, Debug)]
+pub struct OpenArgs {
+    /// Expertise ID (or a unique prefix of it)
+    pub id: String,
+
+    /// Scope (personal/me, company/org, project/proj)
+    #[arg(short, long, default_value = "personal")]
+    pub scope: Scope,
+}
+
+#[sen::handler]
+pub async fn open(state: State<AppState>, Args(args): Args<OpenArgs>) -> CliResult<String> {
+    let app = state.read().await;
+    let id = resolve_id(&app, &args.id, Some(args.scope)).await?;
+
+    let expertise = get_expertise(&app, &id, args.scope).await?;
+    if expertise.metadata.locked {
+        return Err(locked_error(&id));
+    }
+
+    let json = serde_json::to_string_pretty(&expertise)
+        .map_err(|e| CliError::system(format!("Failed to serialize expertise: {}", e)))?;
+    let edited = edit_in_editor(&format!("{}.json", id), &json)?;
+
+    let Some(updated) = parse_edited(&expertise, &edited)? else {
+        return Ok(format!("{} unchanged", id));
+    };
+    let version = updated.version().to_string();
+
+    app.db
+        .storage()
+        .update(updated)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to update expertise: {}", e)))?;
+
+    Ok(format!(
+        "✓ Updated {} → v{} (previous saved as v{})",
+        id,
+        version,
+        expertise.version()
+    ))
+}
+
+/// Validate the edited JSON of `original`
+///
+/// Returns `None` when nothing changed. The minor version is bumped unless
+/// the edit changed the version.
+fn parse_edited(original: &Expertise, edited: &str) -> CliResult<Option<Expertise>> {
+    let mut updated = Expertise::from_json(edited)
+        .map_err(|e| CliError::user(format!("Invalid expertise JSON: {}", e)))?;
+
+    if updated.id() != original.id() {
+        return Err(CliError::user(format!(
+            "The id can't be changed here ({} → {})",
+            original.id(),
+            updated.id()
+        )));
+    }
+    if updated.metadata.scope != original.metadata.scope {
+        return Err(CliError::user(format!(
+            "The scope can't be changed here ({} → {})",
+            original.metadata.scope, updated.metadata.scope
+        )));
+    }
+
+    // Compare as values so reformatting alone doesn't count as a change
+    let value = |e: &Expertise| serde_json::to_value(e).ok();
+    if value(&updated) == value(original) {
+        return Ok(None);
+    }
+
+    if updated.version() == original.version() {
+        updated.inner.version = bump_version(original.version(), SemverBump::Minor);
+        updated.metadata.version_reason = None;
+    }
+    Ok(Some(updated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn original() -> Expertise {
+        let mut expertise = Expertise::new("rust-expert", "1.2.0");
+        expertise.inner.description = Some("Idiomatic Rust".to_string());
+        expertise
+    }
+
+    fn edit(expertise: &Expertise, f: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut value = serde_json::to_value(expertise).unwrap();
+        f(&mut value);
+        serde_json::to_string_pretty(&value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_edited() {
+        let original = original();
+
+        // Saved as is, or only reformatted
+        let json = serde_json::to_string_pretty(&original).unwrap();
+        assert!(matches!(parse_edited(&original, &json), Ok(None)));
+        let compact = serde_json::to_string(&original).unwrap();
+        assert!(matches!(parse_edited(&original, &compact), Ok(None)));
+
+        let json = edit(&original, |v| v["description"] = "Safe Rust".into());
+        let updated = parse_edited(&original, &json).ok().flatten().unwrap();
+        assert_eq!(updated.description(), "Safe Rust");
+        assert_eq!(updated.version(), "1.3.0");
+
+        // A version set by hand is kept
+        let json = edit(&original, |v| {
+            v["description"] = "Safe Rust".into();
+            v["version"] = "2.0.0".into();
+        });
+        let updated = parse_edited(&original, &json).ok().flatten().unwrap();
+        assert_eq!(updated.version(), "2.0.0");
+    }
+
+    #[test]
+    fn test_parse_edited_rejects_invalid() {
+        let original = original();
+
+        assert!(parse_edited(&original, "{ not json").is_err());
+        assert!(parse_edited(&original, "").is_err());
+
+        let json = edit(&original, |v| v["id"] = "go-expert".into());
+        assert!(parse_edited(&original, &json).is_err());
+
+        let json = edit(&original, |v| v["scope"] = "company".into());
+        assert!(parse_edited(&original, &json).is_err());
+    }
+
+    #[test]
+    fn test_open_args() {
+        let args = OpenArgs::try_parse_from(["open", "rust-expert", "-s", "org"]).unwrap();
+        assert_eq!(args.id, "rust-expert");
+        assert_eq!(args.scope, Scope::Company);
+    }
+}
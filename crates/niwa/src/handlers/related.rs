@@ -0,0 +1,162 @@
+//! Related command - find expertises similar to a given one
+
+use crate::exit_code::not_found;
+use crate::render::truncate_chars;
+use crate::state::AppState;
+use clap::{Parser, ValueEnum};
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::{Scope, SimilarityConfig, SimilarityMethod, StorageOperations};
+use sen::{Args, CliError, CliResult, State};
+
+/// List the expertises most similar to one, most similar first
+///
+/// Similarity is the tag overlap by default, or the edit distance of the
+/// descriptions with --method description.
+///
+/// Usage:
+///   niwa related tokio-patterns
+///   niwa related tokio-patterns --method description --threshold 0.5
+///   niwa related tokio-patterns --scope company --limit 5
+#[derive(Parser, Debug)]
+pub struct RelatedArgs {
+    /// Expertise ID
+    pub id: String,
+
+    /// Scope of the expertise and the candidates (default: all scopes)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+
+    /// How to compare expertises
+    #[arg(long, value_enum, default_value_t = Method::Tags)]
+    pub method: Method,
+
+    /// Minimum similarity, 0.0-1.0 (default: 0.3 for tags, 0.6 for description)
+    #[arg(long)]
+    pub threshold: Option<f64>,
+
+    /// Show at most this many expertises
+    #[arg(short, long, default_value = "10")]
+    pub limit: usize,
+}
+
+/// Similarity measure (`--method`)
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    /// Jaccard overlap of the tags
+    Tags,
+    /// Levenshtein similarity of the descriptions
+    Description,
+}
+
+impl From<Method> for SimilarityMethod {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::Tags => Self::Tags,
+            Method::Description => Self::Description,
+        }
+    }
+}
+
+/// Similarity settings from --method and --threshold
+fn similarity_config(method: Method, threshold: Option<f64>) -> CliResult<SimilarityConfig> {
+    let config = SimilarityConfig::new(method.into());
+    match threshold {
+        None => Ok(config),
+        Some(threshold) if (0.0..=1.0).contains(&threshold) => Ok(config.with_threshold(threshold)),
+        Some(_) => Err(CliError::user("--threshold must be between 0.0 and 1.0")),
+    }
+}
+
+#[sen::handler]
+pub async fn related(state: State<AppState>, Args(args): Args<RelatedArgs>) -> CliResult<String> {
+    let app = state.read().await;
+    let storage = app.db.storage();
+    let config = similarity_config(args.method, args.threshold)?;
+
+    let candidates = match args.scope {
+        Some(scope) => storage.list(scope).await,
+        None => storage.list_all().await,
+    }
+    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+
+    let target = candidates
+        .iter()
+        .find(|e| e.id() == args.id)
+        .ok_or_else(|| not_found(format!("Expertise not found: {}", args.id)))?;
+
+    let ranked = config.rank(target, &candidates);
+    if ranked.is_empty() {
+        return Ok(format!(
+            "No expertises with similarity >= {:.2} to '{}'.",
+            config.threshold, args.id
+        ));
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Scope").fg(Color::Green),
+            Cell::new("Similarity").fg(Color::Green),
+            Cell::new("Description").fg(Color::Green),
+        ]);
+
+    for (expertise, score) in ranked.iter().take(args.limit) {
+        table.add_row(vec![
+            expertise.id().to_string(),
+            expertise.metadata.scope.to_string(),
+            format!("{:.2}", score),
+            truncate_chars(&expertise.description(), 60),
+        ]);
+    }
+
+    Ok(format!(
+        "\n{}\n\nTotal: {} related expertises (showing {})",
+        table,
+        ranked.len(),
+        ranked.len().min(args.limit)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_related_args() {
+        let args = RelatedArgs::try_parse_from(["related", "tokio"]).unwrap();
+        assert_eq!(args.method, Method::Tags);
+        assert_eq!(args.threshold, None);
+        assert_eq!(args.limit, 10);
+
+        let args = RelatedArgs::try_parse_from([
+            "related",
+            "tokio",
+            "--method",
+            "description",
+            "--threshold",
+            "0.5",
+        ])
+        .unwrap();
+        assert_eq!(args.method, Method::Description);
+        assert_eq!(args.threshold, Some(0.5));
+
+        assert!(
+            RelatedArgs::try_parse_from(["related", "tokio", "--method", "embedding"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_similarity_config() {
+        let config = similarity_config(Method::Description, None).unwrap();
+        assert_eq!(config.method, SimilarityMethod::Description);
+        assert_eq!(config.threshold, 0.6);
+
+        let config = similarity_config(Method::Tags, Some(0.8)).unwrap();
+        assert_eq!(config.threshold, 0.8);
+
+        assert!(similarity_config(Method::Tags, Some(1.5)).is_err());
+    }
+}
@@ -1,22 +1,37 @@
 //! Relations commands
 
+use crate::exit_code::not_found;
+use crate::handlers::expertise::{format_bulk_report, resolve_id};
+use crate::render::{to_csv, to_json, to_markdown, OutputFormat, Render};
 use crate::state::AppState;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
-use niwa_core::{RelationType, Scope, StorageOperations};
+use niwa_core::bundle;
+use niwa_core::graph::Relation;
+use niwa_core::{
+    Expertise, RelationMetadata, RelationType, Scope, SimilarityMethod, StorageOperations,
+};
 use sen::{Args, CliError, CliResult, State};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
 /// Create a relation between two expertises
 ///
 /// Usage:
 ///   niwa link rust-expert --to error-handling --type uses
 ///   niwa link rust-expert --to error-handling --scope personal
+///   niwa link rust-expert --to error-handling --type requires --yes
+///
+/// A pair can be linked with several types. When the pair is already linked
+/// with another type, the new edge is added alongside it after confirmation.
 #[derive(Parser, Debug)]
 pub struct LinkArgs {
-    /// Source expertise ID
+    /// Source expertise ID (or a unique prefix of it)
     pub from_id: String,
 
-    /// Target expertise ID
+    /// Target expertise ID (or a unique prefix of it)
     #[arg(short, long)]
     pub to: String,
 
@@ -31,11 +46,17 @@ pub struct LinkArgs {
     /// Optional metadata (JSON)
     #[arg(short, long)]
     pub metadata: Option<String>,
+
+    /// Add the relation without asking when the pair already has another type
+    #[arg(short, long)]
+    pub yes: bool,
 }
 
 #[sen::handler]
-pub async fn link(state: State<AppState>, Args(args): Args<LinkArgs>) -> CliResult<String> {
+pub async fn link(state: State<AppState>, Args(mut args): Args<LinkArgs>) -> CliResult<String> {
     let app = state.read().await;
+    args.from_id = resolve_id(&app, &args.from_id, args.scope).await?;
+    args.to = resolve_id(&app, &args.to, args.scope).await?;
 
     // Verify source expertise exists
     let scopes_to_check = match args.scope {
@@ -58,7 +79,7 @@ pub async fn link(state: State<AppState>, Args(args): Args<LinkArgs>) -> CliResu
     }
 
     if !from_found {
-        return Err(CliError::user(format!(
+        return Err(not_found(format!(
             "Source expertise not found: {}",
             args.from_id
         )));
@@ -80,25 +101,70 @@ pub async fn link(state: State<AppState>, Args(args): Args<LinkArgs>) -> CliResu
     }
 
     if !to_found {
-        return Err(CliError::user(format!(
+        return Err(not_found(format!(
             "Target expertise not found: {}",
             args.to
         )));
     }
 
+    let graph = app.db.graph();
+    let existing = graph
+        .relation_types_between(&args.from_id, &args.to)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+    let replacing = existing.contains(&args.relation_type);
+    if !replacing && !existing.is_empty() && !args.yes {
+        confirm_additional_type(&args.from_id, &args.to, &existing, args.relation_type)?;
+    }
+
     // Create relation
-    app.db
-        .graph()
+    graph
         .create_relation(&args.from_id, &args.to, args.relation_type, args.metadata)
         .await
         .map_err(|e| CliError::system(format!("Failed to create relation: {}", e)))?;
 
+    let action = if replacing { "Updated" } else { "Created" };
     Ok(format!(
-        "✓ Created relation: {} -[{}]-> {}",
-        args.from_id, args.relation_type, args.to
+        "✓ {} relation: {} -[{}]-> {}",
+        action, args.from_id, args.relation_type, args.to
     ))
 }
 
+/// Ask before linking a pair that already has relations of other types
+///
+/// Without a terminal to ask on, fails with a hint to pass --yes.
+fn confirm_additional_type(
+    from_id: &str,
+    to_id: &str,
+    existing: &[RelationType],
+    relation_type: RelationType,
+) -> CliResult<()> {
+    let existing = existing
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!("{} is already linked to {} ({})", from_id, to_id, existing);
+
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::user(format!(
+            "{}. Use --yes to add a '{}' relation as well.",
+            message, relation_type
+        )));
+    }
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(format!("{}. Add '{}' as well?", message, relation_type))
+        .default(false)
+        .interact()
+        .map_err(|e| CliError::system(format!("Failed to read confirmation: {}", e)))?;
+
+    if confirmed {
+        Ok(())
+    } else {
+        Err(CliError::user("Aborted"))
+    }
+}
+
 /// Show dependencies and relations
 ///
 /// Usage:
@@ -106,9 +172,10 @@ pub async fn link(state: State<AppState>, Args(args): Args<LinkArgs>) -> CliResu
 ///   niwa deps rust-expert --incoming
 ///   niwa deps rust-expert --all
 ///   niwa deps rust-expert --scope personal
+///   niwa deps rust-expert --all --format json
 #[derive(Parser, Debug)]
 pub struct DepsArgs {
-    /// Expertise ID
+    /// Expertise ID (or a unique prefix of it)
     pub id: String,
 
     /// Show incoming relations (dependents)
@@ -122,11 +189,16 @@ pub struct DepsArgs {
     /// Scope (if not specified, searches all scopes)
     #[arg(short, long)]
     pub scope: Option<Scope>,
+
+    /// Output format (default: text, or json with --agent-mode)
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
 }
 
 #[sen::handler]
-pub async fn deps(state: State<AppState>, Args(args): Args<DepsArgs>) -> CliResult<String> {
+pub async fn deps(state: State<AppState>, Args(mut args): Args<DepsArgs>) -> CliResult<String> {
     let app = state.read().await;
+    args.id = resolve_id(&app, &args.id, args.scope).await?;
 
     // Verify expertise exists
     let scopes_to_check = match args.scope {
@@ -149,7 +221,7 @@ pub async fn deps(state: State<AppState>, Args(args): Args<DepsArgs>) -> CliResu
     }
 
     if !found {
-        return Err(CliError::user(format!("Expertise not found: {}", args.id)));
+        return Err(not_found(format!("Expertise not found: {}", args.id)));
     }
 
     // Get relations based on flags
@@ -173,62 +245,504 @@ pub async fn deps(state: State<AppState>, Args(args): Args<DepsArgs>) -> CliResu
             .map_err(|e| CliError::system(format!("Failed to get outgoing relations: {}", e)))?
     };
 
-    if relations.is_empty() {
-        let direction = if args.all {
-            "any"
-        } else if args.incoming {
-            "incoming"
-        } else {
-            "outgoing"
+    let selection = if args.all {
+        DepsSelection::All
+    } else if args.incoming {
+        DepsSelection::Incoming
+    } else {
+        DepsSelection::Outgoing
+    };
+    let view = DepsView::new(&args.id, selection, &relations);
+    Ok(view.render(OutputFormat::resolve(args.format, app.agent_mode)))
+}
+
+/// Which relations `deps` lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DepsSelection {
+    Outgoing,
+    Incoming,
+    All,
+}
+
+/// Side of a listed relation the expertise is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    /// The expertise depends on the other one
+    Outgoing,
+    /// The other expertise depends on this one
+    Incoming,
+}
+
+/// One relation as listed by `deps`
+#[derive(Debug, Serialize)]
+struct DepRelation {
+    direction: Direction,
+    /// The expertise at the other end
+    expertise: String,
+    #[serde(rename = "type")]
+    relation_type: RelationType,
+    metadata: RelationMetadata,
+}
+
+/// Relations of one expertise as listed by `deps`
+#[derive(Debug)]
+struct DepsView {
+    id: String,
+    selection: DepsSelection,
+    relations: Vec<DepRelation>,
+}
+
+impl DepsView {
+    fn new(id: &str, selection: DepsSelection, relations: &[Relation]) -> Self {
+        let relations = relations
+            .iter()
+            .map(|relation| {
+                let (direction, expertise) = if relation.from_id == id {
+                    (Direction::Outgoing, &relation.to_id)
+                } else {
+                    (Direction::Incoming, &relation.from_id)
+                };
+                DepRelation {
+                    direction,
+                    expertise: expertise.clone(),
+                    relation_type: relation.relation_type,
+                    metadata: relation.parsed_metadata(),
+                }
+            })
+            .collect();
+        Self {
+            id: id.to_string(),
+            selection,
+            relations,
+        }
+    }
+}
+
+impl Render for DepsView {
+    fn render(&self, format: OutputFormat) -> String {
+        const HEADER: [&str; 6] = [
+            "direction",
+            "expertise",
+            "type",
+            "reason",
+            "confidence",
+            "source",
+        ];
+        // Text shows the direction as an arrow, the other formats as a word
+        let records = |arrows: bool| {
+            self.relations.iter().map(move |relation| {
+                let direction = match (relation.direction, arrows) {
+                    (Direction::Outgoing, true) => "→",
+                    (Direction::Incoming, true) => "←",
+                    (Direction::Outgoing, false) => "outgoing",
+                    (Direction::Incoming, false) => "incoming",
+                };
+                let metadata = &relation.metadata;
+                vec![
+                    direction.to_string(),
+                    relation.expertise.clone(),
+                    relation.relation_type.to_string(),
+                    metadata.reason.clone().unwrap_or_else(|| "-".to_string()),
+                    metadata
+                        .confidence
+                        .map(|c| format!("{:.2}", c))
+                        .unwrap_or_else(|| "-".to_string()),
+                    metadata.source.clone().unwrap_or_else(|| "-".to_string()),
+                ]
+            })
         };
-        return Ok(format!("No {} relations found for: {}", direction, args.id));
+
+        match format {
+            OutputFormat::Text => {
+                if self.relations.is_empty() {
+                    let direction = match self.selection {
+                        DepsSelection::All => "any",
+                        DepsSelection::Incoming => "incoming",
+                        DepsSelection::Outgoing => "outgoing",
+                    };
+                    return format!("No {} relations found for: {}", direction, self.id);
+                }
+
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        Cell::new("Direction").fg(Color::Cyan),
+                        Cell::new("Expertise").fg(Color::Cyan),
+                        Cell::new("Type").fg(Color::Cyan),
+                        Cell::new("Reason").fg(Color::Cyan),
+                        Cell::new("Confidence").fg(Color::Cyan),
+                        Cell::new("Source").fg(Color::Cyan),
+                    ]);
+                for record in records(true) {
+                    table.add_row(record);
+                }
+
+                let title = match self.selection {
+                    DepsSelection::All => "All Relations",
+                    DepsSelection::Incoming => "Incoming Relations (Dependents)",
+                    DepsSelection::Outgoing => "Outgoing Relations (Dependencies)",
+                };
+                format!(
+                    "\n{}: {}\n\n{}\n\nTotal: {} relations",
+                    title,
+                    self.id,
+                    table,
+                    self.relations.len()
+                )
+            }
+            OutputFormat::Json => to_json(&self.relations),
+            OutputFormat::Csv => to_csv(&HEADER, records(false)),
+            OutputFormat::Markdown => to_markdown(&HEADER, records(false)),
+        }
     }
+}
+
+/// Maintain relations in bulk
+#[derive(Parser, Debug)]
+pub struct RelationsArgs {
+    #[command(subcommand)]
+    pub command: Option<RelationsCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RelationsCommand {
+    /// Recompute the confidence of auto-linked relations from current shared tags
+    ///
+    /// The confidence becomes the tag overlap (Jaccard) of the two endpoints,
+    /// so edge strength follows tag changes. The original LLM confidence is
+    /// kept in the metadata as `original_confidence`.
+    ///
+    /// Usage:
+    ///   niwa relations reweight
+    ///   niwa relations reweight --min-confidence 0.2
+    ///   niwa relations reweight --dry-run
+    Reweight {
+        /// Delete relations whose new confidence is below this (0.0-1.0)
+        #[arg(long)]
+        min_confidence: Option<f64>,
+
+        /// Show the new confidences without saving
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+}
+
+#[sen::handler]
+pub async fn relations(
+    state: State<AppState>,
+    Args(args): Args<RelationsArgs>,
+) -> CliResult<String> {
+    let app = state.read().await;
+
+    match args.command {
+        Some(RelationsCommand::Reweight {
+            min_confidence,
+            dry_run,
+        }) => handle_reweight(&app, min_confidence, dry_run).await,
+        None => Err(CliError::user(
+            "No subcommand specified. Use 'relations --help' to see available commands.",
+        )),
+    }
+}
+
+async fn handle_reweight(
+    app: &AppState,
+    min_confidence: Option<f64>,
+    dry_run: bool,
+) -> CliResult<String> {
+    if min_confidence.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+        return Err(CliError::user(
+            "--min-confidence must be between 0.0 and 1.0",
+        ));
+    }
+
+    let graph = app.db.graph();
+    let relations = graph
+        .all_relations()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+    let expertises: HashMap<String, Expertise> = app
+        .db
+        .storage()
+        .list_all()
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?
+        .into_iter()
+        .map(|e| (e.id().to_string(), e))
+        .collect();
 
-    // Build table
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
-        .set_content_arrangement(ContentArrangement::Dynamic);
-
-    // Header
-    table.set_header(vec![
-        Cell::new("Direction").fg(Color::Cyan),
-        Cell::new("Expertise").fg(Color::Cyan),
-        Cell::new("Type").fg(Color::Cyan),
-        Cell::new("Metadata").fg(Color::Cyan),
-    ]);
-
-    // Rows
-    for relation in &relations {
-        let (direction, expertise_id) = if relation.from_id == args.id {
-            ("→", relation.to_id.as_str())
-        } else {
-            ("←", relation.from_id.as_str())
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("From").fg(Color::Cyan),
+            Cell::new("Type").fg(Color::Cyan),
+            Cell::new("To").fg(Color::Cyan),
+            Cell::new("Old").fg(Color::Cyan),
+            Cell::new("New").fg(Color::Cyan),
+            Cell::new("Action").fg(Color::Cyan),
+        ]);
+
+    let mut reweighted = 0;
+    let mut deleted = 0;
+    for relation in relations
+        .iter()
+        .filter(|r| r.source().as_deref() == Some("auto-link"))
+    {
+        let (Some(from), Some(to)) = (
+            expertises.get(&relation.from_id),
+            expertises.get(&relation.to_id),
+        ) else {
+            continue;
         };
 
-        let metadata = relation.metadata.as_deref().unwrap_or("-");
+        let metadata = reweighted_metadata(relation, from, to);
+        let confidence = metadata.confidence.unwrap_or_default();
+        let below = min_confidence.is_some_and(|min| confidence < min);
+
+        let action = match (below, dry_run) {
+            (true, true) => "would delete",
+            (false, true) => "would update",
+            (true, false) => {
+                graph
+                    .delete_relation(&relation.from_id, &relation.to_id, relation.relation_type)
+                    .await
+                    .map_err(|e| CliError::system(format!("Failed to delete relation: {}", e)))?;
+                "deleted"
+            }
+            (false, false) => {
+                graph
+                    .update_metadata(
+                        &relation.from_id,
+                        &relation.to_id,
+                        relation.relation_type,
+                        Some(metadata.to_json()),
+                    )
+                    .await
+                    .map_err(|e| CliError::system(format!("Failed to update relation: {}", e)))?;
+                "updated"
+            }
+        };
+        if below {
+            deleted += 1;
+        } else {
+            reweighted += 1;
+        }
 
         table.add_row(vec![
-            Cell::new(direction),
-            Cell::new(expertise_id),
-            Cell::new(relation.relation_type.to_string()),
-            Cell::new(metadata),
+            relation.from_id.clone(),
+            relation.relation_type.to_string(),
+            relation.to_id.clone(),
+            relation
+                .confidence()
+                .map(|c| format!("{:.2}", c))
+                .unwrap_or_else(|| "-".to_string()),
+            format!("{:.2}", confidence),
+            action.to_string(),
         ]);
     }
 
-    let title = if args.all {
-        "All Relations"
-    } else if args.incoming {
-        "Incoming Relations (Dependents)"
+    if reweighted + deleted == 0 {
+        return Ok("No auto-linked relations to reweight.".to_string());
+    }
+
+    let summary = if dry_run {
+        format!(
+            "Dry run: {} relations would be reweighted, {} deleted",
+            reweighted, deleted
+        )
     } else {
-        "Outgoing Relations (Dependencies)"
+        format!(
+            "✓ Reweighted {} relations, deleted {} below the minimum confidence",
+            reweighted, deleted
+        )
     };
+    Ok(format!("\n{}\n\n{}", table, summary))
+}
 
-    Ok(format!(
-        "\n{}: {}\n\n{}\n\nTotal: {} relations",
-        title,
-        args.id,
-        table,
-        relations.len()
-    ))
+/// Restore relations exported with `niwa export --format relations-json`
+///
+/// Only the relations are restored; the expertises must already exist.
+/// Edges with a missing endpoint or that would close a cycle are skipped and
+/// reported, as are edges that already exist.
+///
+/// Usage:
+///   niwa export --format relations-json -o links.json
+///   niwa import-relations links.json --dry-run
+///   niwa import-relations links.json
+#[derive(Parser, Debug)]
+pub struct ImportRelationsArgs {
+    /// JSON file written by `niwa export --format relations-json`
+    pub file: PathBuf,
+
+    /// Report what would be created without creating anything
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+#[sen::handler]
+pub async fn import_relations(
+    state: State<AppState>,
+    Args(args): Args<ImportRelationsArgs>,
+) -> CliResult<String> {
+    let app = state.read().await;
+
+    let json = std::fs::read_to_string(&args.file)
+        .map_err(|e| CliError::user(format!("Failed to read {}: {}", args.file.display(), e)))?;
+    let report = bundle::restore_relations(&app.db, &json, args.dry_run)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::Serialization(e) => CliError::user(format!(
+                "Invalid relations file {}: {}",
+                args.file.display(),
+                e
+            )),
+            e => CliError::system(format!("Failed to import relations: {}", e)),
+        })?;
+
+    let rows = report.created
+        + report.skipped_duplicate
+        + report.skipped_cycle
+        + report.skipped_missing_id
+        + report.errors.len();
+    Ok(format_bulk_report(&report, rows, args.dry_run))
+}
+
+/// Metadata of `relation` with its confidence set to the tag overlap of its endpoints
+///
+/// The first reweight moves the LLM confidence to `original_confidence`.
+fn reweighted_metadata(relation: &Relation, from: &Expertise, to: &Expertise) -> RelationMetadata {
+    let mut metadata = relation.parsed_metadata();
+    if let Some(original) = metadata.confidence {
+        metadata
+            .extra
+            .entry("original_confidence")
+            .or_insert_with(|| original.into());
+    }
+    metadata.confidence = Some(SimilarityMethod::Tags.score(from, to));
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged(id: &str, tags: &[&str]) -> Expertise {
+        let mut expertise = Expertise::new(id, "1.0.0");
+        expertise.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+        expertise
+    }
+
+    #[test]
+    fn test_link_args() {
+        let args = LinkArgs::try_parse_from([
+            "link",
+            "rust-expert",
+            "--to",
+            "error-handling",
+            "-t",
+            "requires",
+            "--yes",
+        ])
+        .unwrap();
+        assert_eq!(args.relation_type, RelationType::Requires);
+        assert!(args.yes);
+
+        let args =
+            LinkArgs::try_parse_from(["link", "rust-expert", "--to", "error-handling"]).unwrap();
+        assert_eq!(args.relation_type, RelationType::Uses);
+        assert!(!args.yes);
+    }
+
+    #[test]
+    fn test_deps_view() {
+        let relation = |from: &str, to: &str, metadata: Option<&str>| Relation {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type: RelationType::Requires,
+            metadata: metadata.map(str::to_string),
+            created_at: 0,
+        };
+        let view = DepsView::new(
+            "tokio",
+            DepsSelection::All,
+            &[
+                relation("tokio", "mio", Some(r#"{"reason":"I/O driver"}"#)),
+                relation("axum", "tokio", None),
+            ],
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&view.render(OutputFormat::Json)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "direction": "outgoing",
+                    "expertise": "mio",
+                    "type": "requires",
+                    "metadata": { "reason": "I/O driver" },
+                },
+                {
+                    "direction": "incoming",
+                    "expertise": "axum",
+                    "type": "requires",
+                    "metadata": {},
+                },
+            ])
+        );
+        assert_eq!(
+            view.render(OutputFormat::Csv),
+            "direction,expertise,type,reason,confidence,source\n\
+             outgoing,mio,requires,I/O driver,-,-\n\
+             incoming,axum,requires,-,-,-"
+        );
+
+        let empty = DepsView::new("tokio", DepsSelection::Incoming, &[]);
+        assert_eq!(
+            empty.render(OutputFormat::Text),
+            "No incoming relations found for: tokio"
+        );
+        assert_eq!(empty.render(OutputFormat::Json), "[]");
+    }
+
+    #[test]
+    fn test_import_relations_args() {
+        let args =
+            ImportRelationsArgs::try_parse_from(["import-relations", "links.json", "-n"]).unwrap();
+        assert_eq!(args.file, PathBuf::from("links.json"));
+        assert!(args.dry_run);
+
+        assert!(ImportRelationsArgs::try_parse_from(["import-relations"]).is_err());
+    }
+
+    #[test]
+    fn test_reweighted_metadata() {
+        let mut relation = Relation {
+            from_id: "tokio".to_string(),
+            to_id: "async-std".to_string(),
+            relation_type: RelationType::Uses,
+            metadata: Some(r#"{"confidence":0.9,"source":"auto-link"}"#.to_string()),
+            created_at: 0,
+        };
+        let from = tagged("tokio", &["rust", "async", "runtime"]);
+
+        let metadata = reweighted_metadata(&relation, &from, &tagged("a", &["rust", "async"]));
+        assert!((metadata.confidence.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metadata.extra["original_confidence"], 0.9);
+        assert_eq!(metadata.source.as_deref(), Some("auto-link"));
+
+        // Fewer shared tags mean a weaker edge; the original confidence stays
+        relation.metadata = Some(metadata.to_json());
+        let metadata = reweighted_metadata(&relation, &from, &tagged("a", &["rust", "web"]));
+        assert!((metadata.confidence.unwrap() - 0.25).abs() < 1e-9);
+        assert_eq!(metadata.extra["original_confidence"], 0.9);
+
+        let metadata = reweighted_metadata(&relation, &from, &tagged("a", &["python"]));
+        assert_eq!(metadata.confidence, Some(0.0));
+    }
 }
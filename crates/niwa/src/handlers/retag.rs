@@ -0,0 +1,155 @@
+//! Retag command - propose tags for expertises that have none
+
+use crate::exit_code::{generator_error, not_found};
+use crate::handlers::expertise::resolve_id;
+use crate::state::AppState;
+use clap::Parser;
+use niwa_core::{Expertise, Scope, StorageOperations};
+use sen::{Args, CliError, CliResult, State};
+
+/// Most frequent existing tags offered to the LLM as vocabulary
+const KNOWN_TAG_LIMIT: usize = 50;
+
+/// Propose tags with the LLM for an expertise, or for every untagged one
+///
+/// Suggestions prefer tags already used elsewhere in the knowledge base.
+/// Without --apply they are only printed.
+///
+/// Usage:
+///   niwa retag rust-expert
+///   niwa retag rust-expert --apply
+///   niwa retag --untagged --scope company --apply
+#[derive(Parser, Debug)]
+pub struct RetagArgs {
+    /// Expertise ID (or a unique prefix of it)
+    #[arg(required_unless_present = "untagged", conflicts_with = "untagged")]
+    pub id: Option<String>,
+
+    /// Process every expertise without tags
+    #[arg(long)]
+    pub untagged: bool,
+
+    /// Add the suggested tags (default: only print them)
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Scope (personal/me, company/org, project/proj); with --untagged, all
+    /// scopes by default
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+}
+
+#[sen::handler]
+pub async fn retag(state: State<AppState>, Args(args): Args<RetagArgs>) -> CliResult<String> {
+    let app = state.read().await;
+    let storage = app.db.storage();
+
+    let targets: Vec<Expertise> = match &args.id {
+        Some(id) => {
+            let scope = args.scope.unwrap_or(Scope::Personal);
+            let id = resolve_id(&app, id, Some(scope)).await?;
+            let expertise = storage
+                .get(&id, scope)
+                .await
+                .map_err(|e| CliError::system(format!("Database error: {}", e)))?
+                .ok_or_else(|| {
+                    not_found(format!("Expertise not found: {} (scope: {})", id, scope))
+                })?;
+            vec![expertise]
+        }
+        None => match args.scope {
+            Some(scope) => storage.list(scope).await,
+            None => storage.list_all().await,
+        }
+        .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?
+        .into_iter()
+        .filter(|e| e.tags().is_empty())
+        .collect(),
+    };
+
+    if targets.is_empty() {
+        return Ok("No untagged expertises found.".to_string());
+    }
+
+    let known_tags: Vec<String> = app
+        .db
+        .query()
+        .list_tags(args.scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list tags: {}", e)))?
+        .into_iter()
+        .take(KNOWN_TAG_LIMIT)
+        .map(|(tag, _)| tag)
+        .collect();
+
+    let mut output = Vec::new();
+    let mut tagged = 0;
+    for expertise in &targets {
+        let id = expertise.id();
+        let scope = expertise.metadata.scope;
+
+        let suggested = match app.generator.suggest_tags(expertise, &known_tags).await {
+            Ok(tags) => tags,
+            // A single expertise reports the failure; a batch moves on
+            Err(e) if args.id.is_some() => {
+                return Err(generator_error("Failed to suggest tags", e))
+            }
+            Err(e) => {
+                output.push(format!("  ✗ {}: {}", id, e));
+                continue;
+            }
+        };
+        if suggested.is_empty() {
+            output.push(format!("  - {}: no new tags suggested", id));
+            continue;
+        }
+
+        if !args.apply {
+            output.push(format!("  • {}: {}", id, suggested.join(", ")));
+            continue;
+        }
+        match storage.add_tags(id, scope, &suggested).await {
+            Ok(added) => {
+                tagged += 1;
+                output.push(format!("  ✓ {}: {}", id, added.join(", ")));
+            }
+            Err(e) if args.id.is_some() => {
+                return Err(CliError::system(format!("Failed to add tags: {}", e)))
+            }
+            Err(e) => output.push(format!("  ✗ {}: {}", id, e)),
+        }
+    }
+
+    let header = if args.apply {
+        format!("Tagged {} of {} expertise(s)", tagged, targets.len())
+    } else {
+        format!("Suggested tags for {} expertise(s)", targets.len())
+    };
+    let mut result = format!("{}\n{}", header, output.join("\n"));
+    if !args.apply {
+        result.push_str("\n\nUse --apply to add them.");
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retag_args() {
+        let parse = |args: &[&str]| RetagArgs::try_parse_from(["retag"].iter().chain(args));
+
+        let args = parse(&["rust-expert", "--apply"]).unwrap();
+        assert_eq!(args.id.as_deref(), Some("rust-expert"));
+        assert!(args.apply);
+        assert!(!args.untagged);
+
+        let args = parse(&["--untagged", "--scope", "company"]).unwrap();
+        assert!(args.untagged);
+        assert_eq!(args.scope, Some(Scope::Company));
+
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["rust-expert", "--untagged"]).is_err());
+    }
+}
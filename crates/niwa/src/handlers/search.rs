@@ -1,24 +1,95 @@
 //! Search command
 
+use crate::render::{
+    color_enabled, to_csv, to_json, to_markdown, truncate_chars, ExpertiseView, OutputFormat,
+    Render,
+};
+use crate::scope_filter::ScopeFilter;
 use crate::state::AppState;
 use clap::Parser;
 use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
-use niwa_core::SearchOptions;
+use niwa_core::{Scope, SearchDiagnostics, SearchOptions};
 use sen::{Args, CliResult, State};
+use serde::Serialize;
+
+/// Corpus size above which `--regex` warns about the full scan
+const REGEX_SCAN_WARN_ROWS: usize = 1000;
+
+/// ANSI bold yellow, used for matched query terms
+const HIGHLIGHT_START: &str = "\x1b[1;33m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
 
 /// Search expertises
 ///
 /// Usage:
 ///   niwa search "rust error handling"
 ///   niwa search "async" --limit 10
+///   niwa search "async" --scope company
+///   niwa search "tokio runtime" --explain-missing
+///   niwa search "async" --no-color
+///   niwa search "async" --format json
+///   niwa search --unique-tags --min-tags 2
+///   niwa search --unique-tags --invert
+///   niwa search --regex "spawn_\w+" --case-insensitive
 #[derive(Parser, Debug)]
 pub struct SearchArgs {
     /// Search query
-    pub query: String,
+    #[arg(
+        required_unless_present_any = ["unique_tags", "regex"],
+        conflicts_with_all = ["unique_tags", "regex"]
+    )]
+    pub query: Option<String>,
+
+    /// Search ids, descriptions, tags and text fragments with a regular expression
+    ///
+    /// Slower than the full-text query: every expertise is scanned.
+    #[arg(long, value_name = "PATTERN", conflicts_with_all = ["unique_tags", "explain_missing"])]
+    pub regex: Option<String>,
+
+    /// Match the --regex pattern regardless of case
+    #[arg(long, requires = "regex", conflicts_with_all = ["query", "unique_tags"])]
+    pub case_insensitive: bool,
 
     /// Maximum number of results
     #[arg(short, long)]
     pub limit: Option<usize>,
+
+    /// Filter by scope (all, personal/me, company/org, project/proj)
+    ///
+    /// Defaults to $NIWA_SCOPE_FILTER, or all scopes when unset.
+    #[arg(short, long)]
+    pub scope: Option<ScopeFilter>,
+
+    /// Diagnose why the query returns no results instead of searching
+    #[arg(long)]
+    pub explain_missing: bool,
+
+    /// Don't highlight matched terms (also off with $NO_COLOR or when piped)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Output format (default: text, or json with --agent-mode)
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Find expertises whose tag set no other expertise shares
+    #[arg(long, conflicts_with_all = ["limit", "explain_missing"])]
+    pub unique_tags: bool,
+
+    /// Ignore expertises with fewer tags (with --unique-tags)
+    // clap skips `requires` when the required arg conflicts with a present
+    // one, so the conflict with the query is spelled out here too
+    #[arg(
+        long,
+        default_value = "1",
+        requires = "unique_tags",
+        conflicts_with = "query"
+    )]
+    pub min_tags: usize,
+
+    /// Find the most duplicated tag sets instead (with --unique-tags)
+    #[arg(long, requires = "unique_tags", conflicts_with = "query")]
+    pub invert: bool,
 }
 
 #[sen::handler]
@@ -27,48 +98,431 @@ pub async fn search(state: State<AppState>, Args(args): Args<SearchArgs>) -> Cli
     if let Some(limit) = args.limit {
         options = options.limit(limit);
     }
+    let scope_filter = ScopeFilter::resolve(args.scope).map_err(sen::CliError::user)?;
+    if let Some(scope) = scope_filter.scope() {
+        options = options.scope(scope);
+    }
 
     let app = state.read().await;
+    let format = OutputFormat::resolve(args.format, app.agent_mode);
+
+    if args.unique_tags {
+        return find_tag_combos(&app, &args, scope_filter.scope(), format).await;
+    }
+    if let Some(pattern) = &args.regex {
+        return search_regex(&app, &args, pattern, scope_filter.scope(), format).await;
+    }
+    let query = args.query.clone().unwrap_or_default();
+
+    if args.explain_missing {
+        let report = app
+            .db
+            .query()
+            .explain(&query)
+            .await
+            .map_err(|e| sen::CliError::system(format!("Search diagnosis failed: {}", e)))?;
+        return Ok(format_diagnostics(&report));
+    }
 
     let results = app
         .db
         .query()
-        .search(&args.query, options)
+        .search(&query, options)
+        .await
+        .map_err(|e| sen::CliError::system(format!("Search failed: {}", e)))?;
+
+    if results.is_empty() && format == OutputFormat::Text {
+        return Ok(format!("No results found for: {}", query));
+    }
+
+    let highlight = !args.no_color && format == OutputFormat::Text && color_enabled();
+
+    let result = SearchResult {
+        query,
+        results: results.iter().map(ExpertiseView::from).collect(),
+        highlight,
+    };
+    Ok(result.render(format))
+}
+
+/// Regular-expression search; scans every expertise in scope
+async fn search_regex(
+    app: &AppState,
+    args: &SearchArgs,
+    pattern: &str,
+    scope: Option<Scope>,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let pattern = if args.case_insensitive {
+        format!("(?i){}", pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    let query = app.db.query();
+    let scanned = query
+        .count(scope)
         .await
         .map_err(|e| sen::CliError::system(format!("Search failed: {}", e)))?;
+    if scanned > REGEX_SCAN_WARN_ROWS {
+        // stderr, so JSON output stays parseable
+        eprintln!(
+            "⚠ Regex search scans all {} expertises without an index; narrow it with --scope or use a full-text query",
+            scanned
+        );
+    }
+
+    let mut results = query
+        .search_regex(&pattern, scope)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::InvalidPattern(_) => sen::CliError::user(e.to_string()),
+            e => sen::CliError::system(format!("Search failed: {}", e)),
+        })?;
+    if let Some(limit) = args.limit {
+        results.truncate(limit);
+    }
+
+    if results.is_empty() && format == OutputFormat::Text {
+        return Ok(format!("No results found for: /{}/", pattern));
+    }
+
+    // Highlighting splits the query into terms, which doesn't fit a pattern
+    let result = SearchResult {
+        query: pattern,
+        results: results.iter().map(ExpertiseView::from).collect(),
+        highlight: false,
+    };
+    Ok(result.render(format))
+}
+
+/// Expertises with a unique tag set, or with --invert the shared tag sets
+async fn find_tag_combos(
+    app: &AppState,
+    args: &SearchArgs,
+    scope: Option<Scope>,
+    format: OutputFormat,
+) -> CliResult<String> {
+    let query = app.db.query();
+
+    if !args.invert {
+        let unique = query
+            .find_unique_tag_combos(args.min_tags, scope)
+            .await
+            .map_err(|e| sen::CliError::system(format!("Tag analysis failed: {}", e)))?;
+        if unique.is_empty() && format == OutputFormat::Text {
+            return Ok("No expertises with a unique tag set found.".to_string());
+        }
+        let views: Vec<ExpertiseView> = unique.iter().map(ExpertiseView::from).collect();
+        return Ok(views.render(format));
+    }
+
+    let shared = query
+        .find_shared_tag_combos(args.min_tags, scope)
+        .await
+        .map_err(|e| sen::CliError::system(format!("Tag analysis failed: {}", e)))?;
+    if shared.is_empty() && format == OutputFormat::Text {
+        return Ok("No shared tag sets found.".to_string());
+    }
+
+    // Results come most shared first, so groups keep that order
+    let mut combos: Vec<TagCombo> = Vec::new();
+    for (expertise, _) in shared {
+        let mut tags = expertise.tags().to_vec();
+        tags.sort();
+        match combos.iter_mut().find(|combo| combo.tags == tags) {
+            Some(combo) => combo.ids.push(expertise.id().to_string()),
+            None => combos.push(TagCombo {
+                tags,
+                ids: vec![expertise.id().to_string()],
+            }),
+        }
+    }
+    Ok(combos.render(format))
+}
 
-    if results.is_empty() {
-        return Ok(format!("No results found for: {}", args.query));
-    }
-
-    // Build table
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("ID").fg(Color::Yellow),
-            Cell::new("Version").fg(Color::Yellow),
-            Cell::new("Tags").fg(Color::Yellow),
-            Cell::new("Description").fg(Color::Yellow),
-        ]);
-
-    for exp in &results {
-        let tags = exp.tags().join(", ");
-        let description = exp.description();
-        let truncated_desc = if description.len() > 60 {
-            format!("{}...", &description[..60])
-        } else {
-            description
+/// A tag set shared by several expertises
+#[derive(Debug, Serialize)]
+pub struct TagCombo {
+    pub tags: Vec<String>,
+    pub ids: Vec<String>,
+}
+
+impl Render for [TagCombo] {
+    fn render(&self, format: OutputFormat) -> String {
+        const HEADER: [&str; 3] = ["tags", "count", "ids"];
+        let records = || {
+            self.iter().map(|combo| {
+                vec![
+                    combo.tags.join(", "),
+                    combo.ids.len().to_string(),
+                    combo.ids.join(", "),
+                ]
+            })
         };
 
-        table.add_row(vec![exp.id(), exp.version(), &tags, &truncated_desc]);
+        match format {
+            OutputFormat::Text => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        Cell::new("Tags").fg(Color::Yellow),
+                        Cell::new("Shared By").fg(Color::Yellow),
+                        Cell::new("Expertises").fg(Color::Yellow),
+                    ]);
+                for record in records() {
+                    table.add_row(record);
+                }
+                format!(
+                    "\n{}\n\nFound: {} shared tag sets (candidates for merging)",
+                    table,
+                    self.len()
+                )
+            }
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Csv => to_csv(&HEADER, records()),
+            OutputFormat::Markdown => to_markdown(&HEADER, records()),
+        }
+    }
+}
+
+/// Results of one search query
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub query: String,
+    pub results: Vec<ExpertiseView>,
+    /// Highlight matched terms in text output
+    #[serde(skip)]
+    pub highlight: bool,
+}
+
+impl Render for SearchResult {
+    fn render(&self, format: OutputFormat) -> String {
+        let records = || self.results.iter().map(ExpertiseView::record);
+        match format {
+            OutputFormat::Text => self.render_text(),
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Csv => to_csv(&ExpertiseView::HEADER, records()),
+            OutputFormat::Markdown => to_markdown(&ExpertiseView::HEADER, records()),
+        }
+    }
+}
+
+impl SearchResult {
+    fn render_text(&self) -> String {
+        let terms = query_terms(&self.query);
+
+        // Build table
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("ID").fg(Color::Yellow),
+                Cell::new("Version").fg(Color::Yellow),
+                Cell::new("Tags").fg(Color::Yellow),
+                Cell::new("Description").fg(Color::Yellow),
+            ]);
+
+        for view in &self.results {
+            let truncated_desc = truncate_chars(&view.description, 60);
+            let truncated_desc = if self.highlight {
+                highlight_terms(&truncated_desc, &terms)
+            } else {
+                truncated_desc
+            };
+
+            table.add_row(vec![
+                view.id.clone(),
+                view.version.clone(),
+                view.tags.join(", "),
+                truncated_desc,
+            ]);
+        }
+
+        format!(
+            "\nSearch: \"{}\"\n\n{}\n\nFound: {} results",
+            self.query,
+            table,
+            self.results.len()
+        )
+    }
+}
+
+/// Words of a search query, without quotes, wildcards and FTS5 operators
+fn query_terms(query: &str) -> Vec<&str> {
+    query
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| matches!(c, '"' | '*' | '(' | ')')))
+        .filter(|word| !word.is_empty() && !matches!(*word, "AND" | "OR" | "NOT" | "NEAR"))
+        .collect()
+}
+
+/// Wrap case-insensitive occurrences of `terms` in `text` with ANSI bold
+///
+/// Overlapping or adjacent matches are merged into one highlighted span.
+fn highlight_terms(text: &str, terms: &[&str]) -> String {
+    let lower: Vec<(usize, char)> = text
+        .char_indices()
+        .map(|(i, c)| (i, c.to_lowercase().next().unwrap_or(c)))
+        .collect();
+
+    // Byte ranges of all matches, sorted by start
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        let term: Vec<char> = term
+            .chars()
+            .map(|c| c.to_lowercase().next().unwrap_or(c))
+            .collect();
+        if term.is_empty() || term.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=lower.len() - term.len() {
+            if lower[start..start + term.len()]
+                .iter()
+                .map(|(_, c)| *c)
+                .eq(term.iter().copied())
+            {
+                let end = lower
+                    .get(start + term.len())
+                    .map_or(text.len(), |(i, _)| *i);
+                ranges.push((lower[start].0, end));
+            }
+        }
+    }
+    ranges.sort_unstable();
+
+    let mut output = String::with_capacity(text.len());
+    let mut pos = 0;
+    let mut iter = ranges.into_iter().peekable();
+    while let Some((start, mut end)) = iter.next() {
+        if start < pos {
+            // Fully inside the previous span
+            continue;
+        }
+        while let Some(&(next_start, next_end)) = iter.peek() {
+            if next_start > end {
+                break;
+            }
+            end = end.max(next_end);
+            iter.next();
+        }
+        output.push_str(&text[pos..start]);
+        output.push_str(HIGHLIGHT_START);
+        output.push_str(&text[start..end]);
+        output.push_str(HIGHLIGHT_END);
+        pos = end;
+    }
+    output.push_str(&text[pos..]);
+    output
+}
+
+/// Render a search diagnosis report
+fn format_diagnostics(report: &SearchDiagnostics) -> String {
+    let mut output = format!("\nSearch diagnosis: \"{}\"\n\n", report.query);
+
+    match &report.fts_error {
+        Some(e) => output.push_str(&format!("  FTS5 index:        query error ({})\n", e)),
+        None => output.push_str(&format!("  FTS5 index:        {}\n", report.fts_matches)),
+    }
+    for (term, count) in &report.term_matches {
+        output.push_str(&format!("    term \"{}\": {}\n", term, count));
+    }
+    output.push_str(&format!(
+        "  Raw descriptions:  {}\n",
+        report.description_matches
+    ));
+    output.push_str(&format!(
+        "  Fragments:         {}\n",
+        report.fragment_matches
+    ));
+
+    let verdict = if report.fts_error.is_some() {
+        "The query is not valid FTS5 syntax — try quoting it or removing operators.".to_string()
+    } else if report.index_out_of_sync() {
+        format!(
+            "Query '{}' found in: raw descriptions ({}), fragments ({}), but FTS5 index shows 0 — try running `niwa index rebuild`",
+            report.query, report.description_matches, report.fragment_matches
+        )
+    } else if report.fts_matches == 0 && report.fragment_matches > 0 {
+        format!(
+            "Query '{}' only appears in fragment text ({}), which is not part of the search index.",
+            report.query, report.fragment_matches
+        )
+    } else if report.fts_matches == 0 {
+        "No matching knowledge exists at any level.".to_string()
+    } else {
+        format!("The index matches {} expertises.", report.fts_matches)
+    };
+
+    output.push_str(&format!("\n{}", verdict));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(text: &str) -> String {
+        format!("{}{}{}", HIGHLIGHT_START, text, HIGHLIGHT_END)
     }
 
-    Ok(format!(
-        "\nSearch: \"{}\"\n\n{}\n\nFound: {} results",
-        args.query,
-        table,
-        results.len()
-    ))
+    #[test]
+    fn test_unique_tags_args() {
+        let args =
+            SearchArgs::try_parse_from(["search", "--unique-tags", "--min-tags", "2"]).unwrap();
+        assert!(args.query.is_none());
+        assert_eq!(args.min_tags, 2);
+
+        assert!(SearchArgs::try_parse_from(["search"]).is_err());
+        assert!(SearchArgs::try_parse_from(["search", "rust", "--unique-tags"]).is_err());
+        assert!(SearchArgs::try_parse_from(["search", "rust", "--invert"]).is_err());
+    }
+
+    #[test]
+    fn test_regex_args() {
+        let args =
+            SearchArgs::try_parse_from(["search", "--regex", r"spawn_\w+", "--case-insensitive"])
+                .unwrap();
+        assert!(args.query.is_none());
+        assert_eq!(args.regex.as_deref(), Some(r"spawn_\w+"));
+        assert!(args.case_insensitive);
+
+        assert!(SearchArgs::try_parse_from(["search", "rust", "--regex", "x"]).is_err());
+        assert!(SearchArgs::try_parse_from(["search", "rust", "--case-insensitive"]).is_err());
+        assert!(
+            SearchArgs::try_parse_from(["search", "--unique-tags", "--case-insensitive"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_query_terms() {
+        assert_eq!(
+            query_terms("\"tokio runtime\" OR async*"),
+            vec!["tokio", "runtime", "async"]
+        );
+    }
+
+    #[test]
+    fn test_highlight_terms() {
+        assert_eq!(
+            highlight_terms("Rust error handling in rust", &["rust"]),
+            format!("{} error handling in {}", marked("Rust"), marked("rust"))
+        );
+
+        // Overlapping terms become one span
+        assert_eq!(
+            highlight_terms("async runtime", &["async run", "runtime"]),
+            marked("async runtime")
+        );
+
+        // Multi-byte text keeps char boundaries
+        assert_eq!(
+            highlight_terms("非同期 Tokio", &["tokio"]),
+            format!("非同期 {}", marked("Tokio"))
+        );
+        assert_eq!(highlight_terms("no match", &["zzz"]), "no match");
+    }
 }
@@ -1,40 +1,86 @@
 //! Show command
 
+use crate::exit_code::not_found;
+use crate::handlers::expertise::resolve_id;
+use crate::render::{humanize_age, truncate_chars};
 use crate::state::AppState;
 use clap::Parser;
-use niwa_core::{KnowledgeFragment, Scope, StorageOperations};
+use niwa_core::{Expertise, KnowledgeFragment, Scope, StorageOperations};
 use sen::{Args, CliResult, State};
 
 /// Show detailed information about an Expertise
 ///
 /// Usage:
 ///   niwa show rust-expert
+///   niwa show rust-exp
 ///   niwa show rust-expert --scope company
 ///   niwa show rust-expert --fragments
+///   niwa show rust-expert --fragments --provenance
+///   niwa show rust-expert --fragments --max-fragments 20 --from 21
 #[derive(Parser, Debug)]
 pub struct ShowArgs {
-    /// Expertise ID to display
+    /// Expertise ID to display (or a unique prefix of it)
     pub id: String,
 
-    /// Scope (personal, team, company). If not specified, searches all scopes.
+    /// Scope (personal/me, company/org, project/proj). If not specified,
+    /// searches all scopes.
     #[arg(short, long)]
     pub scope: Option<Scope>,
 
     /// Show fragment contents
     #[arg(short, long)]
     pub fragments: bool,
+
+    /// Show the source each fragment was extracted from (with --fragments)
+    #[arg(long, requires = "fragments")]
+    pub provenance: bool,
+
+    /// Show at most this many fragments (with --fragments)
+    #[arg(long, value_name = "N", requires = "fragments")]
+    pub max_fragments: Option<usize>,
+
+    /// Start at this fragment number, as shown by `#N` (with --fragments)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = parse_fragment_number,
+        requires = "fragments"
+    )]
+    pub from: usize,
+}
+
+/// Parse a 1-based fragment number
+fn parse_fragment_number(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 1 => Ok(n),
+        _ => Err(format!(
+            "Invalid fragment number '{}' (numbering starts at 1)",
+            s
+        )),
+    }
+}
+
+/// Which fragments `format_expertise` prints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct FragmentPage {
+    /// Index of the first fragment (0-based)
+    pub start: usize,
+    /// Maximum number of fragments (all when `None`)
+    pub limit: Option<usize>,
 }
 
 #[sen::handler]
 pub async fn show(state: State<AppState>, Args(args): Args<ShowArgs>) -> CliResult<String> {
     let app = state.read().await;
+    let id = resolve_id(&app, &args.id, args.scope).await?;
 
     // If scope is specified, search only that scope
-    // Otherwise, search all scopes in order: personal, team, company
+    // Otherwise, search all scopes in order: personal, project, company
     let expertise = if let Some(scope) = args.scope {
         app.db
             .storage()
-            .get(&args.id, scope)
+            .get(&id, scope)
             .await
             .map_err(|e| sen::CliError::system(format!("Database error: {}", e)))?
     } else {
@@ -44,7 +90,7 @@ pub async fn show(state: State<AppState>, Args(args): Args<ShowArgs>) -> CliResu
             if let Some(exp) = app
                 .db
                 .storage()
-                .get(&args.id, scope)
+                .get(&id, scope)
                 .await
                 .map_err(|e| sen::CliError::system(format!("Database error: {}", e)))?
             {
@@ -57,16 +103,31 @@ pub async fn show(state: State<AppState>, Args(args): Args<ShowArgs>) -> CliResu
 
     let expertise = expertise.ok_or_else(|| {
         if let Some(scope) = args.scope {
-            sen::CliError::user(format!(
-                "Expertise not found: {} (scope: {})",
-                args.id, scope
-            ))
+            not_found(format!("Expertise not found: {} (scope: {})", id, scope))
         } else {
-            sen::CliError::user(format!("Expertise not found: {} (in any scope)", args.id))
+            not_found(format!("Expertise not found: {} (in any scope)", id))
         }
     })?;
 
-    // Format output
+    let page = FragmentPage {
+        start: args.from - 1,
+        limit: args.max_fragments,
+    };
+    Ok(format_expertise(
+        &expertise,
+        args.fragments,
+        args.provenance,
+        page,
+    ))
+}
+
+/// Format an expertise the way `niwa show` prints it
+pub(crate) fn format_expertise(
+    expertise: &Expertise,
+    fragments: bool,
+    provenance: bool,
+    page: FragmentPage,
+) -> String {
     let mut output = String::new();
     output.push_str("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     output.push_str(&format!("  Expertise: {}\n", expertise.id()));
@@ -74,14 +135,36 @@ pub async fn show(state: State<AppState>, Args(args): Args<ShowArgs>) -> CliResu
 
     output.push_str(&format!("Version:     {}\n", expertise.version()));
     output.push_str(&format!("Scope:       {}\n", expertise.metadata.scope));
+    output.push_str(&format!("Status:      {}\n", expertise.metadata.status));
     output.push_str(&format!(
         "Created:     {}\n",
         format_timestamp(expertise.metadata.created_at)
     ));
     output.push_str(&format!(
-        "Updated:     {}\n",
-        format_timestamp(expertise.metadata.updated_at)
+        "Updated:     {} ({} ago)\n",
+        format_timestamp(expertise.metadata.updated_at),
+        humanize_age(expertise.metadata.updated_at)
     ));
+    if let Some(generated_by) = &expertise.metadata.generated_by {
+        let generated_at = expertise
+            .metadata
+            .generated_at
+            .map(|ts| format!(" at {}", format_timestamp(ts)))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "Generated:   by {}{}\n",
+            generated_by, generated_at
+        ));
+    }
+    if let Some(source_type) = &expertise.metadata.source_type {
+        match &expertise.metadata.source_file {
+            Some(file) => output.push_str(&format!("Source:      {} ({})\n", source_type, file)),
+            None => output.push_str(&format!("Source:      {}\n", source_type)),
+        }
+    }
+    if let Some(original) = &expertise.metadata.translated_from {
+        output.push_str(&format!("Translated:  from {}\n", original));
+    }
 
     if !expertise.tags().is_empty() {
         output.push_str(&format!("\nTags:        {}\n", expertise.tags().join(", ")));
@@ -95,12 +178,24 @@ pub async fn show(state: State<AppState>, Args(args): Args<ShowArgs>) -> CliResu
     ));
 
     // Show fragments if requested
-    if args.fragments && !expertise.inner.content.is_empty() {
+    if fragments && !expertise.inner.content.is_empty() {
         output.push_str("\n────────────────────────────────────────\n");
         output.push_str("  Fragments\n");
         output.push_str("────────────────────────────────────────\n\n");
 
-        for (i, weighted_fragment) in expertise.inner.content.iter().enumerate() {
+        let total = expertise.inner.content.len();
+        let end = page
+            .limit
+            .map_or(total, |limit| page.start.saturating_add(limit).min(total));
+        let shown = expertise
+            .inner
+            .content
+            .iter()
+            .enumerate()
+            .skip(page.start)
+            .take(end.saturating_sub(page.start));
+
+        for (i, weighted_fragment) in shown {
             let content = match &weighted_fragment.fragment {
                 KnowledgeFragment::Text(text) => text.clone(),
                 KnowledgeFragment::Logic { instruction, steps } => {
@@ -135,23 +230,97 @@ pub async fn show(state: State<AppState>, Args(args): Args<ShowArgs>) -> CliResu
             output.push_str(&format!("#{} ", i + 1));
 
             // Truncate long content for display
-            let display_content = if content.len() > 500 {
-                format!("{}...", &content[..500])
-            } else {
-                content
-            };
+            let display_content = truncate_chars(&content, 500);
             output.push_str(&display_content);
+            output.push('\n');
+            if provenance {
+                let source = expertise.fragment_source(i).unwrap_or("unknown");
+                output.push_str(&format!("   Source: {}\n", source));
+            }
+            output.push('\n');
+        }
+
+        if page.start >= total {
+            output.push_str(&format!(
+                "No fragments from #{} ({} total)\n\n",
+                page.start + 1,
+                total
+            ));
+        } else if page.start > 0 || end < total {
+            output.push_str(&format!("Showing {}–{} of {}", page.start + 1, end, total));
+            if end < total {
+                output.push_str(&format!(" (use --from {} for more)", end + 1));
+            }
             output.push_str("\n\n");
         }
     }
 
     output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    Ok(output)
+    output
 }
 
-fn format_timestamp(ts: i64) -> String {
+pub(crate) fn format_timestamp(ts: i64) -> String {
     use chrono::{DateTime, Utc};
     let dt = DateTime::<Utc>::from_timestamp(ts, 0).unwrap_or_else(Utc::now);
     dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_core::WeightedFragment;
+
+    fn expertise_with_fragments(count: usize) -> Expertise {
+        let mut expertise = Expertise::new("rust-expert", "1.0.0");
+        for i in 1..=count {
+            expertise
+                .inner
+                .content
+                .push(WeightedFragment::new(KnowledgeFragment::Text(format!(
+                    "tip {}",
+                    i
+                ))));
+        }
+        expertise
+    }
+
+    #[test]
+    fn test_fragment_page() {
+        let expertise = expertise_with_fragments(5);
+
+        let all = format_expertise(&expertise, true, false, FragmentPage::default());
+        assert!(all.contains("#1 tip 1\n") && all.contains("#5 tip 5\n"));
+        assert!(!all.contains("Showing"));
+
+        let page = FragmentPage {
+            start: 1,
+            limit: Some(2),
+        };
+        let output = format_expertise(&expertise, true, false, page);
+        assert!(!output.contains("#1 tip 1\n"));
+        assert!(output.contains("#2 tip 2\n") && output.contains("#3 tip 3\n"));
+        assert!(!output.contains("#4 tip 4\n"));
+        assert!(output.contains("Showing 2–3 of 5 (use --from 4 for more)"));
+
+        let page = FragmentPage {
+            start: 9,
+            limit: None,
+        };
+        let output = format_expertise(&expertise, true, false, page);
+        assert!(output.contains("No fragments from #10 (5 total)"));
+    }
+
+    #[test]
+    fn test_show_args() {
+        assert!(
+            ShowArgs::try_parse_from(["show", "rust-expert", "--max-fragments", "20"]).is_err()
+        );
+        assert!(ShowArgs::try_parse_from(["show", "rust-expert", "-f", "--from", "0"]).is_err());
+
+        let args = ShowArgs::try_parse_from(["show", "rust-expert", "-f", "--max-fragments", "20"])
+            .unwrap();
+        assert_eq!(args.max_fragments, Some(20));
+        assert_eq!(args.from, 1);
+    }
+}
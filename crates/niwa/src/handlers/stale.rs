@@ -0,0 +1,97 @@
+//! Stale command - find expertises that have not been updated recently
+
+use crate::state::AppState;
+use clap::Parser;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::Scope;
+use sen::{Args, CliError, CliResult, State};
+
+/// List expertises not updated within the given number of days, oldest first
+///
+/// Usage:
+///   niwa stale
+///   niwa stale --days 90 --scope company
+#[derive(Parser, Debug)]
+pub struct StaleArgs {
+    /// Minimum age in days since the last update
+    #[arg(short, long, default_value = "180")]
+    pub days: u32,
+
+    /// Filter by scope (personal/me, company/org, project/proj)
+    #[arg(short, long)]
+    pub scope: Option<Scope>,
+}
+
+#[sen::handler]
+pub async fn stale(state: State<AppState>, Args(args): Args<StaleArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    let expertises = app
+        .db
+        .query()
+        .stale(args.days, args.scope)
+        .await
+        .map_err(|e| CliError::system(format!("Failed to list stale expertises: {}", e)))?;
+
+    if expertises.is_empty() {
+        return Ok(format!("No expertises older than {} days.", args.days));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ID").fg(Color::Green),
+            Cell::new("Version").fg(Color::Green),
+            Cell::new("Scope").fg(Color::Green),
+            Cell::new("Updated").fg(Color::Green),
+            Cell::new("Age").fg(Color::Green),
+        ]);
+
+    for exp in &expertises {
+        let updated = chrono::DateTime::from_timestamp(exp.metadata.updated_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        table.add_row(vec![
+            exp.id().to_string(),
+            exp.version().to_string(),
+            exp.metadata.scope.to_string(),
+            updated,
+            format_age_days(now - exp.metadata.updated_at),
+        ]);
+    }
+
+    Ok(format!(
+        "\n{}\n\nTotal: {} expertises not updated in {} days",
+        table,
+        expertises.len(),
+        args.days
+    ))
+}
+
+/// Format an age in seconds as whole days
+fn format_age_days(seconds: i64) -> String {
+    let days = seconds.max(0) / (24 * 60 * 60);
+    if days == 1 {
+        "1 day".to_string()
+    } else {
+        format!("{} days", days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_age_days() {
+        assert_eq!(format_age_days(0), "0 days");
+        assert_eq!(format_age_days(24 * 60 * 60), "1 day");
+        assert_eq!(format_age_days(200 * 24 * 60 * 60 + 5), "200 days");
+        assert_eq!(format_age_days(-10), "0 days");
+    }
+}
@@ -0,0 +1,104 @@
+//! Status commands - track which expertises a human has reviewed
+
+use crate::exit_code::not_found;
+use crate::handlers::expertise::resolve_id;
+use crate::state::AppState;
+use clap::{Parser, Subcommand};
+use niwa_core::{ExpertiseStatus, Scope};
+use sen::{Args, CliError, CliResult, State};
+
+/// Set the review status of an expertise
+///
+/// Generated expertises start as drafts. Mark one reviewed once you have
+/// vetted it, or deprecated to leave it out of context assembly.
+///
+/// Usage:
+///   niwa status set rust-expert reviewed
+///   niwa status set old-api deprecated --scope company
+///   niwa list --status draft                  # what still needs review
+#[derive(Parser, Debug)]
+pub struct StatusArgs {
+    #[command(subcommand)]
+    pub command: Option<StatusCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatusCommand {
+    /// Set the status (draft, reviewed, deprecated)
+    Set {
+        /// Expertise ID (or a unique prefix of it)
+        id: String,
+
+        /// New status (draft, reviewed, deprecated)
+        status: ExpertiseStatus,
+
+        /// Scope (personal/me, company/org, project/proj)
+        #[arg(short, long, default_value = "personal")]
+        scope: Scope,
+    },
+}
+
+#[sen::handler]
+pub async fn status(state: State<AppState>, Args(args): Args<StatusArgs>) -> CliResult<String> {
+    let app = state.read().await;
+
+    match args.command {
+        Some(StatusCommand::Set { id, status, scope }) => {
+            let id = resolve_id(&app, &id, Some(scope)).await?;
+            handle_set(&app, &id, status, scope).await
+        }
+        None => Err(CliError::user(
+            "No subcommand specified. Use 'status --help' to see available commands.",
+        )),
+    }
+}
+
+async fn handle_set(
+    app: &AppState,
+    id: &str,
+    status: ExpertiseStatus,
+    scope: Scope,
+) -> CliResult<String> {
+    app.db
+        .storage()
+        .set_status(id, scope, status)
+        .await
+        .map_err(|e| match e {
+            niwa_core::Error::NotFound { .. } => {
+                not_found(format!("Expertise not found: {} (scope: {})", id, scope))
+            }
+            e => CliError::system(format!("Failed to set status: {}", e)),
+        })?;
+
+    Ok(format!("✓ Marked {} as {}", id, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_args() {
+        let parse = |args: &[&str]| StatusArgs::try_parse_from(["status"].iter().chain(args));
+
+        assert!(matches!(
+            parse(&["set", "rust-expert", "reviewed"]).unwrap().command,
+            Some(StatusCommand::Set {
+                status: ExpertiseStatus::Reviewed,
+                scope: Scope::Personal,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&["set", "old-api", "Deprecated", "--scope", "company"])
+                .unwrap()
+                .command,
+            Some(StatusCommand::Set {
+                status: ExpertiseStatus::Deprecated,
+                scope: Scope::Company,
+                ..
+            })
+        ));
+        assert!(parse(&["set", "rust-expert", "archived"]).is_err());
+    }
+}
@@ -48,6 +48,16 @@ Show details:
 Browse by tags:
   $ niwa tags
 
+Filter by date (combine with --scope / --tag):
+  $ niwa list --modified-since 2025-01-01
+  $ niwa list --tag rust --created-before 2024-12-31
+
+Migrate what changed since a date to another database
+(list prints no fragments, so copy each listed id with transfer):
+  $ niwa list --modified-since 2025-01-01 --format json \
+      | jq -r '.[] | "\(.id) --scope \(.scope)"' \
+      | xargs -L1 niwa expertise transfer --to-db ~/.niwa/work.db --on-conflict skip
+
 ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
   🔗 Use Case 3: Build Knowledge Graph
 ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -0,0 +1,93 @@
+//! Global `--quiet` / `--verbose` flags and tracing setup
+
+use tracing_subscriber::EnvFilter;
+
+/// How much diagnostic output to produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Errors only (`--quiet`, `-q`)
+    Quiet,
+    /// `RUST_LOG`, or info when unset
+    Normal,
+    /// Debug logs from NIWA crates (`--verbose`, `-v`)
+    Verbose,
+}
+
+impl Verbosity {
+    /// Remove the global verbosity flags from `args`
+    ///
+    /// The flags are accepted anywhere on the command line and never reach the
+    /// router, so handlers don't need to declare them.
+    pub fn extract(args: Vec<String>) -> Result<(Self, Vec<String>), String> {
+        let mut quiet = false;
+        let mut verbose = false;
+
+        let remaining = args
+            .into_iter()
+            .filter(|arg| match arg.as_str() {
+                "--quiet" | "-q" => {
+                    quiet = true;
+                    false
+                }
+                "--verbose" | "-v" => {
+                    verbose = true;
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+
+        let verbosity = match (quiet, verbose) {
+            (true, true) => return Err("--quiet and --verbose cannot be used together".into()),
+            (true, false) => Self::Quiet,
+            (false, true) => Self::Verbose,
+            (false, false) => Self::Normal,
+        };
+        Ok((verbosity, remaining))
+    }
+
+    /// Tracing filter for this verbosity; explicit flags override `RUST_LOG`
+    pub fn env_filter(self) -> EnvFilter {
+        match self {
+            Self::Quiet => EnvFilter::new("error"),
+            Self::Normal => {
+                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
+            }
+            Self::Verbose => EnvFilter::new("info,niwa=debug,niwa_core=debug,niwa_generator=debug"),
+        }
+    }
+}
+
+/// Initialize tracing on stderr, so stdout carries only command output
+pub fn init(verbosity: Verbosity) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(verbosity.env_filter())
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extract() {
+        let (verbosity, rest) = Verbosity::extract(args(&["niwa", "-q", "list"])).unwrap();
+        assert_eq!(verbosity, Verbosity::Quiet);
+        assert_eq!(rest, args(&["niwa", "list"]));
+
+        let (verbosity, rest) =
+            Verbosity::extract(args(&["niwa", "list", "--verbose", "--scope", "company"])).unwrap();
+        assert_eq!(verbosity, Verbosity::Verbose);
+        assert_eq!(rest, args(&["niwa", "list", "--scope", "company"]));
+
+        let (verbosity, _) = Verbosity::extract(args(&["niwa", "list"])).unwrap();
+        assert_eq!(verbosity, Verbosity::Normal);
+
+        assert!(Verbosity::extract(args(&["niwa", "-q", "-v", "list"])).is_err());
+    }
+}
@@ -2,29 +2,50 @@
 //!
 //! A command-line tool for managing AI expertise graphs.
 
+mod config;
+mod email;
+mod events;
+mod exit_code;
+mod gist;
 mod handlers;
+mod logging;
+mod render;
+mod scope_filter;
 mod state;
+mod tui;
 
-use handlers::{crawler, gen, graph, list, relations, search, show, tutorial};
+use exit_code::ExitCode;
+use handlers::{
+    bundle, compare, crawler, expertise, export, fragment, gen, graph, index, info, lint, list,
+    open, related, relations, retag, search, show, stale, status, tutorial,
+};
+use logging::Verbosity;
 use sen::Router;
 use state::AppState;
 
 #[tokio::main]
 async fn main() {
+    // Global --quiet/--verbose are handled here and never reach the router
+    let (verbosity, args) = match Verbosity::extract(std::env::args().collect()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(ExitCode::User.code());
+        }
+    };
+
     // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
-        .init();
+    logging::init(verbosity);
+
+    // The router strips --agent-mode before handlers run, so detect it up front
+    let agent_mode = args.iter().any(|arg| arg == "--agent-mode");
 
     // Initialize application state
-    let state = match AppState::new().await {
+    let state = match AppState::new(agent_mode).await {
         Ok(state) => state,
         Err(e) => {
             eprintln!("Failed to initialize NIWA: {}", e);
-            std::process::exit(1);
+            std::process::exit(ExitCode::System.code());
         }
     };
 
@@ -32,24 +53,43 @@ async fn main() {
     let router = Router::new()
         // Help & Tutorial
         .route("tutorial", tutorial::tutorial())
+        .route("info", info::info())
         // Generation commands
         .route("gen", gen::generate())
         .route("improve", gen::improve())
+        .route("regen", gen::regen())
+        .route("merge", gen::merge())
         .route("crawler", crawler::crawler())
         // Query commands
         .route("list", list::list())
         .route("show", show::show())
         .route("search", search::search())
         .route("tags", list::tags)
+        .route("index", index::index())
+        .route("stale", stale::stale())
+        .route("lint", lint::lint())
+        .route("compare", compare::compare())
+        .route("related", related::related())
         // Relations commands
         .route("link", relations::link())
         .route("deps", relations::deps())
+        .route("relations", relations::relations())
+        .route("import-relations", relations::import_relations())
         .route("graph", graph::graph())
+        // Maintenance commands
+        .route("expertise", expertise::expertise())
+        .route("fragment", fragment::fragment())
+        .route("open", open::open())
+        .route("retag", retag::retag())
+        .route("status", status::status())
+        .route("bundle", bundle::bundle())
+        .route("export", export::export())
         .with_state(state)
         .with_agent_mode(); // JSON output for LLM integration
 
     // Execute
-    let response = router.execute().await;
+    exit_code::reset();
+    let response = router.execute_with(&args).await;
 
     // Output
     if response.agent_mode {
@@ -58,5 +98,6 @@ async fn main() {
         println!("{}", response.output);
     }
 
-    std::process::exit(response.exit_code);
+    // Refine the router's user/system split (see exit_code.rs for the table)
+    std::process::exit(exit_code::resolve(response.exit_code));
 }
@@ -0,0 +1,349 @@
+//! Output formats shared by the listing commands
+//!
+//! Handlers build a view DTO and let it render itself in the format chosen
+//! with `--format text|json|csv|markdown`. Without the flag, `--agent-mode`
+//! selects JSON and everything else gets the text tables.
+
+use clap::ValueEnum;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use niwa_core::Expertise;
+use serde::Serialize;
+use std::io::IsTerminal;
+
+/// Output format of a rendered view
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Tables and trees for the terminal
+    #[default]
+    Text,
+    /// Pretty-printed JSON
+    Json,
+    /// CSV with a header row
+    Csv,
+    /// Markdown table
+    Markdown,
+}
+
+impl OutputFormat {
+    /// The format to use: the flag if given, else JSON in agent mode, else text
+    pub fn resolve(flag: Option<Self>, agent_mode: bool) -> Self {
+        match flag {
+            Some(format) => format,
+            None if agent_mode => Self::Json,
+            None => Self::Text,
+        }
+    }
+}
+
+/// A view that can be printed in every [`OutputFormat`]
+pub trait Render {
+    fn render(&self, format: OutputFormat) -> String;
+}
+
+/// One expertise as shown by `list` and `search`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpertiseView {
+    pub id: String,
+    pub version: String,
+    pub scope: String,
+    pub tags: Vec<String>,
+    pub description: String,
+    pub locked: bool,
+    pub status: String,
+    pub updated_at: i64,
+    /// Highlight the age in text output (set by the caller)
+    #[serde(skip)]
+    pub stale: bool,
+}
+
+impl From<&Expertise> for ExpertiseView {
+    fn from(expertise: &Expertise) -> Self {
+        Self {
+            id: expertise.id().to_string(),
+            version: expertise.version().to_string(),
+            scope: expertise.metadata.scope.to_string(),
+            tags: expertise.tags().to_vec(),
+            description: expertise.description(),
+            locked: expertise.metadata.locked,
+            status: expertise.metadata.status.to_string(),
+            updated_at: expertise.metadata.updated_at,
+            stale: false,
+        }
+    }
+}
+
+impl ExpertiseView {
+    /// Column names for CSV and markdown
+    pub const HEADER: [&'static str; 7] = [
+        "id",
+        "version",
+        "scope",
+        "tags",
+        "description",
+        "locked",
+        "status",
+    ];
+
+    /// Values matching [`Self::HEADER`]
+    pub fn record(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.version.clone(),
+            self.scope.clone(),
+            self.tags.join(", "),
+            self.description.clone(),
+            self.locked.to_string(),
+            self.status.clone(),
+        ]
+    }
+
+    /// ID with a 🔒 marker when locked
+    pub fn display_id(&self) -> String {
+        if self.locked {
+            format!("{} 🔒", self.id)
+        } else {
+            self.id.clone()
+        }
+    }
+}
+
+impl Render for [ExpertiseView] {
+    fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        Cell::new("ID").fg(Color::Green),
+                        Cell::new("Version").fg(Color::Green),
+                        Cell::new("Scope").fg(Color::Green),
+                        Cell::new("Status").fg(Color::Green),
+                        Cell::new("Tags").fg(Color::Green),
+                        Cell::new("Description").fg(Color::Green),
+                        Cell::new("Age").fg(Color::Green),
+                    ]);
+
+                for view in self {
+                    let truncated_desc = truncate_chars(&view.description, 50);
+
+                    let age = Cell::new(humanize_age(view.updated_at));
+                    table.add_row(vec![
+                        Cell::new(view.display_id()),
+                        Cell::new(&view.version),
+                        Cell::new(&view.scope),
+                        Cell::new(&view.status),
+                        Cell::new(view.tags.join(", ")),
+                        Cell::new(truncated_desc),
+                        if view.stale { age.fg(Color::Red) } else { age },
+                    ]);
+                }
+
+                format!("\n{}\n\nTotal: {} expertises", table, self.len())
+            }
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Csv => to_csv(
+                &ExpertiseView::HEADER,
+                self.iter().map(ExpertiseView::record),
+            ),
+            OutputFormat::Markdown => to_markdown(
+                &ExpertiseView::HEADER,
+                self.iter().map(ExpertiseView::record),
+            ),
+        }
+    }
+}
+
+/// One expertise stored by `gen` or `crawler run`, as reported in agent mode
+///
+/// Lets an orchestrating agent chain on the new id (e.g. `show` or `link` it).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GenResult {
+    pub id: String,
+    pub version: String,
+    pub scope: String,
+    pub tags: Vec<String>,
+    pub fragment_count: usize,
+    /// File the expertise was generated from, if any
+    pub source_file: Option<String>,
+}
+
+impl From<&Expertise> for GenResult {
+    fn from(expertise: &Expertise) -> Self {
+        Self {
+            id: expertise.id().to_string(),
+            version: expertise.version().to_string(),
+            scope: expertise.metadata.scope.to_string(),
+            tags: expertise.tags().to_vec(),
+            fragment_count: expertise.inner.content.len(),
+            source_file: expertise.metadata.source_file.clone(),
+        }
+    }
+}
+
+/// Whether text output may use ANSI colors: stdout is a terminal and `$NO_COLOR` is unset
+pub fn color_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+}
+
+/// Shorten `s` to at most `max` characters, appending "..." when cut
+///
+/// Counts characters rather than bytes, so multibyte text (e.g. Japanese
+/// descriptions) is never split inside a character.
+pub fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+/// Time since `ts` (Unix seconds) in short form, e.g. "5h", "3d", "2mo"
+pub fn humanize_age(ts: i64) -> String {
+    format_age(chrono::Utc::now().timestamp() - ts)
+}
+
+fn format_age(seconds: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    match seconds.max(0) {
+        s if s < MINUTE => "now".to_string(),
+        s if s < HOUR => format!("{}m", s / MINUTE),
+        s if s < DAY => format!("{}h", s / HOUR),
+        s if s < 30 * DAY => format!("{}d", s / DAY),
+        s if s < 365 * DAY => format!("{}mo", s / (30 * DAY)),
+        s => format!("{}y", s / (365 * DAY)),
+    }
+}
+
+/// Pretty-printed JSON of a view
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+}
+
+/// CSV with a header row, without the trailing newline
+pub fn to_csv(header: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    // Writing to a Vec only fails on invalid records, which strings can't be
+    let _ = writer.write_record(header);
+    for row in rows {
+        let _ = writer.write_record(&row);
+    }
+    let bytes = writer.into_inner().unwrap_or_default();
+    String::from_utf8_lossy(&bytes).trim_end().to_string()
+}
+
+/// Markdown table with `|` and newlines in cells escaped
+pub fn to_markdown(header: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+
+    let mut output = format!("| {} |\n", header.join(" | "));
+    output.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape(cell)).collect();
+        output.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(id: &str, description: &str) -> ExpertiseView {
+        ExpertiseView {
+            id: id.to_string(),
+            version: "1.0.0".to_string(),
+            scope: "personal".to_string(),
+            tags: vec!["rust".to_string(), "async".to_string()],
+            description: description.to_string(),
+            locked: false,
+            status: "draft".to_string(),
+            updated_at: 1700000000,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_chars("rust error handling", 4), "rust...");
+
+        // Byte slicing at 50 would land inside a 3-byte character and panic
+        let japanese = "エラー処理のベストプラクティス".repeat(5);
+        assert!(!japanese.is_char_boundary(50));
+        let truncated = truncate_chars(&japanese, 50);
+        assert_eq!(truncated.chars().count(), 53);
+        assert!(truncated.ends_with("..."));
+    }
+
+    #[test]
+    fn test_format_age() {
+        const DAY: i64 = 24 * 60 * 60;
+        assert_eq!(format_age(-5), "now");
+        assert_eq!(format_age(59), "now");
+        assert_eq!(format_age(5 * 60), "5m");
+        assert_eq!(format_age(5 * 60 * 60), "5h");
+        assert_eq!(format_age(3 * DAY), "3d");
+        assert_eq!(format_age(65 * DAY), "2mo");
+        assert_eq!(format_age(800 * DAY), "2y");
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(OutputFormat::resolve(None, false), OutputFormat::Text);
+        assert_eq!(OutputFormat::resolve(None, true), OutputFormat::Json);
+        assert_eq!(
+            OutputFormat::resolve(Some(OutputFormat::Csv), true),
+            OutputFormat::Csv
+        );
+    }
+
+    #[test]
+    fn test_gen_result() {
+        let mut expertise = Expertise::new("rust-async", "1.0.0");
+        expertise.inner.tags = vec!["rust".to_string()];
+        expertise.metadata.source_file = Some("session.jsonl".to_string());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&to_json(&GenResult::from(&expertise))).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "rust-async",
+                "version": "1.0.0",
+                "scope": "personal",
+                "tags": ["rust"],
+                "fragment_count": 0,
+                "source_file": "session.jsonl",
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_expertises() {
+        let views = [view("rust-async", "Tokio | futures")];
+
+        assert_eq!(
+            views.render(OutputFormat::Csv),
+            "id,version,scope,tags,description,locked,status\n\
+             rust-async,1.0.0,personal,\"rust, async\",Tokio | futures,false,draft"
+        );
+        assert_eq!(
+            views.render(OutputFormat::Markdown),
+            "| id | version | scope | tags | description | locked | status |\n\
+             | --- | --- | --- | --- | --- | --- | --- |\n\
+             | rust-async | 1.0.0 | personal | rust, async | Tokio \\| futures | false | draft |"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&views.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["id"], "rust-async");
+        assert!(views
+            .render(OutputFormat::Text)
+            .contains("Total: 1 expertises"));
+    }
+}
@@ -0,0 +1,99 @@
+//! `--scope` values for commands that can cover every scope
+//!
+//! `list` and `search` accept `--scope all` in addition to the regular scopes.
+//! When the flag is omitted they use `NIWA_SCOPE_FILTER` if set, and all
+//! scopes otherwise.
+
+use niwa_core::Scope;
+use std::fmt;
+use std::str::FromStr;
+
+/// Environment variable holding the default `--scope` for list/search
+pub const SCOPE_FILTER_ENV: &str = "NIWA_SCOPE_FILTER";
+
+/// A single scope, or all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeFilter {
+    All,
+    Only(Scope),
+}
+
+impl ScopeFilter {
+    /// The scope to filter by, or `None` for all scopes
+    pub fn scope(self) -> Option<Scope> {
+        match self {
+            Self::All => None,
+            Self::Only(scope) => Some(scope),
+        }
+    }
+
+    /// Resolve the filter for a command: the flag if given, else the default
+    /// from `NIWA_SCOPE_FILTER`
+    pub fn resolve(flag: Option<Self>) -> Result<Self, String> {
+        match flag {
+            Some(filter) => Ok(filter),
+            None => Self::from_config(std::env::var(SCOPE_FILTER_ENV).ok().as_deref()),
+        }
+    }
+
+    /// Parse a configured default; unset or empty means all scopes
+    fn from_config(value: Option<&str>) -> Result<Self, String> {
+        match value.map(str::trim) {
+            None | Some("") => Ok(Self::All),
+            Some(value) => value
+                .parse()
+                .map_err(|e| format!("Invalid {}: {}", SCOPE_FILTER_ENV, e)),
+        }
+    }
+}
+
+impl FromStr for ScopeFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+        s.parse().map(Self::Only).map_err(|_| {
+            format!(
+                "unknown scope '{}' (expected all, personal, company or project; \
+                 aliases: me, org, proj)",
+                s
+            )
+        })
+    }
+}
+
+impl fmt::Display for ScopeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::Only(scope) => write!(f, "{}", scope),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("all".parse(), Ok(ScopeFilter::All));
+        assert_eq!("ALL".parse(), Ok(ScopeFilter::All));
+        assert_eq!("company".parse(), Ok(ScopeFilter::Only(Scope::Company)));
+        assert_eq!("me".parse(), Ok(ScopeFilter::Only(Scope::Personal)));
+        assert!("team".parse::<ScopeFilter>().is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        assert_eq!(ScopeFilter::from_config(None), Ok(ScopeFilter::All));
+        assert_eq!(ScopeFilter::from_config(Some(" ")), Ok(ScopeFilter::All));
+        assert_eq!(
+            ScopeFilter::from_config(Some("project")),
+            Ok(ScopeFilter::Only(Scope::Project))
+        );
+        assert!(ScopeFilter::from_config(Some("everything")).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 //! Application state
 
+use crate::config::Config;
 use niwa_core::Database;
-use niwa_generator::{ExpertiseGenerator, GenerationOptions, LlmProvider};
+use niwa_generator::{
+    ExpertiseGenerator, GenerationOptions, LlmProvider, SecretPolicy, VersionBumpStrategy,
+};
+use std::path::Path;
 use std::sync::Arc;
 
 /// Application state shared across handlers
@@ -11,14 +15,20 @@ pub struct AppState {
     pub db: Arc<Database>,
     /// LLM-powered generator
     pub generator: Arc<ExpertiseGenerator>,
+    /// Whether `--agent-mode` was requested (handlers emit JSON)
+    pub agent_mode: bool,
+    /// User configuration (~/.niwa/config.toml)
+    pub config: Arc<Config>,
 }
 
 impl AppState {
     /// Create a new AppState
-    pub async fn new() -> anyhow::Result<Self> {
+    pub async fn new(agent_mode: bool) -> anyhow::Result<Self> {
         // Open database
         let db = Database::open_default().await?;
 
+        let config = Config::load_default()?;
+
         // Create generator with provider from environment variable
         let provider = Self::get_llm_provider_from_env();
         let generator = if provider != LlmProvider::Claude {
@@ -35,6 +45,161 @@ impl AppState {
         Ok(Self {
             db: Arc::new(db),
             generator: Arc::new(generator),
+            agent_mode,
+            config: Arc::new(config),
+        })
+    }
+
+    /// Clone this state with a generator using the extractor prompt in `path`
+    ///
+    /// Used by `--prompt-file` to replace the built-in extraction prompt.
+    pub async fn with_prompt_file(&self, path: &Path) -> anyhow::Result<Self> {
+        let system_prompt = std::fs::read_to_string(path)?;
+        if system_prompt.trim().is_empty() {
+            anyhow::bail!("prompt file is empty");
+        }
+
+        let options = GenerationOptions {
+            extractor_system_prompt: Some(system_prompt),
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator appending the context in `path` to its prompts
+    ///
+    /// Used by `--context-file` on `gen`, `improve` and `crawler run`.
+    pub async fn with_context_file(&self, path: &Path) -> anyhow::Result<Self> {
+        let context = std::fs::read_to_string(path)?;
+        if context.trim().is_empty() {
+            anyhow::bail!("context file is empty");
+        }
+
+        let options = GenerationOptions {
+            additional_context: Some(context),
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator using seed and required tags
+    ///
+    /// Used by `gen --tags` and `gen --required-tags`.
+    pub async fn with_tags(
+        &self,
+        seed_tags: Vec<String>,
+        required_tags: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let options = GenerationOptions {
+            seed_tags,
+            required_tags,
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator that rejects log extractions
+    /// reported with less than `min_confidence`
+    ///
+    /// Used by `gen --confidence-threshold`.
+    pub async fn with_min_confidence(&self, min_confidence: f32) -> anyhow::Result<Self> {
+        let options = GenerationOptions {
+            min_confidence,
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator that keeps log extractions within
+    /// `min_fragments..=max_fragments` fragments
+    ///
+    /// Used by `gen --min-fragments/--max-fragments`.
+    pub async fn with_fragment_limits(
+        &self,
+        min_fragments: Option<usize>,
+        max_fragments: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        let options = GenerationOptions {
+            min_fragments,
+            max_fragments,
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator bumping versions under `strategy`
+    ///
+    /// Used by `improve --auto-version`.
+    pub async fn with_version_strategy(
+        &self,
+        version_strategy: VersionBumpStrategy,
+    ) -> anyhow::Result<Self> {
+        let options = GenerationOptions {
+            version_strategy,
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator handling generated secrets under `on_secret`
+    ///
+    /// Used by `--on-secret` on `gen`, `improve`, `merge` and `crawler run`.
+    pub async fn with_secret_policy(&self, on_secret: SecretPolicy) -> anyhow::Result<Self> {
+        let options = GenerationOptions {
+            on_secret,
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
+        })
+    }
+
+    /// Clone this state with a generator that keeps empty extractions
+    ///
+    /// Used by `--keep-empty` on `gen` and `crawler run`.
+    pub async fn with_keep_empty(&self) -> anyhow::Result<Self> {
+        let options = GenerationOptions {
+            keep_empty: true,
+            ..self.generator.options().clone()
+        };
+        let generator = ExpertiseGenerator::with_options(options).await?;
+
+        Ok(Self {
+            generator: Arc::new(generator),
+            ..self.clone()
         })
     }
 
@@ -0,0 +1,622 @@
+//! Interactive terminal browser for expertise communities
+//!
+//! Used by `niwa expertise cluster-visualize`. The browser state and key
+//! handling are kept apart from the terminal so they can be tested directly.
+//! `/` runs a full-text (FTS5) search over the browsed expertises.
+
+use crate::handlers::show::{format_expertise, FragmentPage};
+use crate::state::AppState;
+use niwa_core::graph::Relation;
+use niwa_core::{
+    detect_communities, Expertise, ExpertiseStatus, Scope, SearchOptions, StorageOperations,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use sen::{CliError, CliResult};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+/// Lines scrolled by PageUp/PageDown in a page view
+const PAGE_STEP: usize = 10;
+
+/// A community with a display name
+#[derive(Debug)]
+struct Cluster {
+    /// Most common tag among the members, or the most central member's ID
+    name: String,
+    /// Member IDs with their coreness, most central first
+    members: Vec<(String, usize)>,
+}
+
+/// Panel with the keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Panel {
+    Clusters,
+    Members,
+}
+
+/// Full-screen scrollable text (show output, graph, link suggestions)
+#[derive(Debug)]
+struct Page {
+    title: String,
+    lines: Vec<String>,
+    scroll: usize,
+}
+
+/// What the event loop should do after a key press
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    /// Ask the LinkerAgent for links from this expertise
+    SuggestLinks(String),
+    /// Full-text search for this query
+    Search(String),
+}
+
+/// Browser state
+struct ClusterBrowser {
+    clusters: Vec<Cluster>,
+    expertises: HashMap<String, Expertise>,
+    relations: Vec<Relation>,
+    focus: Panel,
+    cluster: usize,
+    member: usize,
+    page: Option<Page>,
+    status: Option<String>,
+    /// Query being typed after `/`
+    search: Option<String>,
+}
+
+impl ClusterBrowser {
+    fn new(expertises: Vec<Expertise>, relations: Vec<Relation>) -> Self {
+        let expertises: HashMap<String, Expertise> = expertises
+            .into_iter()
+            .map(|e| (e.id().to_string(), e))
+            .collect();
+        // Relations to expertises outside the listed scope are ignored
+        let relations: Vec<Relation> = relations
+            .into_iter()
+            .filter(|r| expertises.contains_key(&r.from_id) && expertises.contains_key(&r.to_id))
+            .collect();
+
+        let clusters = detect_communities(&relations)
+            .into_iter()
+            .map(|community| Cluster {
+                name: cluster_name(&community.members, &expertises),
+                members: community.members,
+            })
+            .collect();
+
+        Self {
+            clusters,
+            expertises,
+            relations,
+            focus: Panel::Clusters,
+            cluster: 0,
+            member: 0,
+            page: None,
+            status: None,
+            search: None,
+        }
+    }
+
+    /// ID of the highlighted expertise
+    fn focused_id(&self) -> Option<&str> {
+        self.clusters
+            .get(self.cluster)
+            .and_then(|c| c.members.get(self.member))
+            .map(|(id, _)| id.as_str())
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Action {
+        self.status = None;
+
+        if let Some(query) = &mut self.search {
+            match code {
+                KeyCode::Esc => self.search = None,
+                KeyCode::Enter => {
+                    let query = std::mem::take(query);
+                    self.search = None;
+                    if !query.trim().is_empty() {
+                        return Action::Search(query);
+                    }
+                }
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Char(c) => query.push(c),
+                _ => {}
+            }
+            return Action::None;
+        }
+
+        if let Some(page) = &mut self.page {
+            let last = page.lines.len().saturating_sub(1);
+            match code {
+                KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => self.page = None,
+                KeyCode::Up | KeyCode::Char('k') => page.scroll = page.scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => page.scroll = (page.scroll + 1).min(last),
+                KeyCode::PageUp => page.scroll = page.scroll.saturating_sub(PAGE_STEP),
+                KeyCode::PageDown => page.scroll = (page.scroll + PAGE_STEP).min(last),
+                KeyCode::Home => page.scroll = 0,
+                KeyCode::End => page.scroll = last,
+                _ => {}
+            }
+            return Action::None;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Left | KeyCode::Char('h') => self.focus = Panel::Clusters,
+            KeyCode::Right | KeyCode::Tab => self.focus = Panel::Members,
+            KeyCode::Enter => {
+                if let Some(expertise) = self.focused_id().and_then(|id| self.expertises.get(id)) {
+                    self.page = Some(Page {
+                        title: expertise.id().to_string(),
+                        lines: lines(&format_expertise(
+                            expertise,
+                            true,
+                            false,
+                            FragmentPage::default(),
+                        )),
+                        scroll: 0,
+                    });
+                }
+            }
+            KeyCode::Char('g') => self.open_graph(),
+            KeyCode::Char('/') => self.search = Some(String::new()),
+            KeyCode::Char('l') => {
+                if let Some(id) = self.focused_id() {
+                    return Action::SuggestLinks(id.to_string());
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (index, len) = match self.focus {
+            Panel::Clusters => (&mut self.cluster, self.clusters.len()),
+            Panel::Members => (
+                &mut self.member,
+                self.clusters
+                    .get(self.cluster)
+                    .map_or(0, |c| c.members.len()),
+            ),
+        };
+        if len == 0 {
+            return;
+        }
+        *index = index.saturating_add_signed(delta).min(len - 1);
+        if self.focus == Panel::Clusters {
+            self.member = 0;
+        }
+    }
+
+    /// Open the relations inside the selected cluster as a page
+    fn open_graph(&mut self) {
+        let Some(cluster) = self.clusters.get(self.cluster) else {
+            return;
+        };
+        let in_cluster = |id: &str| cluster.members.iter().any(|(m, _)| m == id);
+        let edges: Vec<&Relation> = self
+            .relations
+            .iter()
+            .filter(|r| in_cluster(&r.from_id) && in_cluster(&r.to_id))
+            .collect();
+
+        let mut lines = vec![
+            format!(
+                "{} expertises, {} relations",
+                cluster.members.len(),
+                edges.len()
+            ),
+            String::new(),
+        ];
+        for relation in edges {
+            let confidence = relation
+                .confidence()
+                .map(|c| format!(" ({:.2})", c))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{} -[{}]-> {}{}",
+                relation.from_id,
+                relation.relation_type.as_str(),
+                relation.to_id,
+                confidence
+            ));
+        }
+
+        self.page = Some(Page {
+            title: format!("Graph: {}", cluster.name),
+            lines,
+            scroll: 0,
+        });
+    }
+
+    /// Show the expertises matching `query` and highlight the first one in a community
+    fn show_search_results(&mut self, query: &str, hits: &[String]) {
+        let community = |id: &str| {
+            self.clusters.iter().enumerate().find_map(|(c, cluster)| {
+                let m = cluster.members.iter().position(|(m, _)| m == id)?;
+                Some((c, m))
+            })
+        };
+
+        let mut lines = Vec::new();
+        let mut first = None;
+        for id in hits {
+            match community(id) {
+                Some((c, m)) => {
+                    lines.push(format!("{}  ({})", id, self.clusters[c].name));
+                    first = first.or(Some((c, m)));
+                }
+                None => lines.push(format!("{}  (not in a community)", id)),
+            }
+        }
+        if lines.is_empty() {
+            lines.push("No matches.".to_string());
+        }
+        if let Some((cluster, member)) = first {
+            self.cluster = cluster;
+            self.member = member;
+            self.focus = Panel::Members;
+        }
+
+        self.page = Some(Page {
+            title: format!("Search: {}", query),
+            lines,
+            scroll: 0,
+        });
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+        let help_text = match (&self.search, &self.status, &self.page) {
+            (Some(query), _, _) => format!("/{}  (Enter search, Esc cancel)", query),
+            (None, Some(status), _) => status.clone(),
+            (None, None, Some(_)) => "↑/↓ scroll  PgUp/PgDn page  Esc back".to_string(),
+            (None, None, None) => {
+                "↑/↓ move  ←/→ switch panel  Enter show  / search  l links  g graph  q quit"
+                    .to_string()
+            }
+        };
+        frame.render_widget(Paragraph::new(help_text).dark_gray(), help);
+
+        if let Some(page) = &self.page {
+            let text: Vec<Line> = page.lines.iter().map(|l| Line::raw(l.as_str())).collect();
+            frame.render_widget(
+                Paragraph::new(text)
+                    .block(Block::bordered().title(page.title.as_str()))
+                    .wrap(Wrap { trim: false })
+                    .scroll((page.scroll.min(u16::MAX as usize) as u16, 0)),
+                main,
+            );
+            return;
+        }
+
+        let [top, details] =
+            Layout::vertical([Constraint::Min(6), Constraint::Length(9)]).areas(main);
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(top);
+
+        let highlight = |panel: Panel| {
+            if self.focus == panel {
+                Style::new().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::new().bold()
+            }
+        };
+
+        let clusters = List::new(
+            self.clusters
+                .iter()
+                .map(|c| format!("{} ({})", c.name, c.members.len())),
+        )
+        .block(Block::bordered().title("Communities"))
+        .highlight_style(highlight(Panel::Clusters));
+        let mut state = ListState::default().with_selected(Some(self.cluster));
+        frame.render_stateful_widget(clusters, left, &mut state);
+
+        let members = self
+            .clusters
+            .get(self.cluster)
+            .map(|c| c.members.as_slice())
+            .unwrap_or_default();
+        let members = List::new(
+            members
+                .iter()
+                .map(|(id, coreness)| format!("{}  [core {}]", id, coreness)),
+        )
+        .block(Block::bordered().title("Expertises (by coreness)"))
+        .highlight_style(highlight(Panel::Members));
+        let mut state = ListState::default().with_selected(Some(self.member));
+        frame.render_stateful_widget(members, right, &mut state);
+
+        frame.render_widget(
+            Paragraph::new(self.details())
+                .block(Block::bordered().title("Details"))
+                .wrap(Wrap { trim: true }),
+            details,
+        );
+    }
+
+    /// Summary of the highlighted expertise for the bottom panel
+    fn details(&self) -> String {
+        let Some(expertise) = self.focused_id().and_then(|id| self.expertises.get(id)) else {
+            return String::new();
+        };
+        let relations = self
+            .relations
+            .iter()
+            .filter(|r| r.from_id == expertise.id() || r.to_id == expertise.id())
+            .count();
+        format!(
+            "{} v{}  (scope: {}, relations: {})\nTags: {}\n\n{}",
+            expertise.id(),
+            expertise.version(),
+            expertise.metadata.scope,
+            relations,
+            expertise.tags().join(", "),
+            expertise.description()
+        )
+    }
+}
+
+/// Name a community after its most common tag (ties go to the
+/// alphabetically first), or its most central member if untagged
+fn cluster_name(members: &[(String, usize)], expertises: &HashMap<String, Expertise>) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (id, _) in members {
+        for tag in expertises.get(id).map(|e| e.tags()).unwrap_or_default() {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(tag, _)| tag.to_string())
+        .unwrap_or_else(|| members[0].0.clone())
+}
+
+fn lines(text: &str) -> Vec<String> {
+    text.lines().map(str::to_string).collect()
+}
+
+/// Launch the cluster browser over expertises in `scope` (or every scope)
+pub async fn run_cluster_browser(app: &AppState, scope: Option<Scope>) -> CliResult<String> {
+    if !std::io::stdout().is_terminal() {
+        return Err(CliError::user(
+            "cluster-visualize needs an interactive terminal",
+        ));
+    }
+
+    let storage = app.db.storage();
+    let expertises = match scope {
+        Some(scope) => storage.list(scope).await,
+        None => storage.list_all().await,
+    }
+    .map_err(|e| CliError::system(format!("Failed to list expertises: {}", e)))?;
+    let graph = app.db.graph();
+    let relations = match scope {
+        Some(_) => {
+            let ids: Vec<String> = expertises.iter().map(|e| e.id().to_string()).collect();
+            graph.relations_among(&ids).await
+        }
+        None => graph.all_relations().await,
+    }
+    .map_err(|e| CliError::system(format!("Failed to get relations: {}", e)))?;
+
+    let mut browser = ClusterBrowser::new(expertises, relations);
+    if browser.clusters.is_empty() {
+        return Ok("No communities found: none of the expertises are related yet.".to_string());
+    }
+
+    let mut terminal = ratatui::init();
+    let result = browse(&mut terminal, app, &mut browser).await;
+    ratatui::restore();
+    result.map_err(|e| CliError::system(format!("Terminal error: {}", e)))?;
+
+    Ok(format!("Explored {} communities", browser.clusters.len()))
+}
+
+async fn browse(
+    terminal: &mut DefaultTerminal,
+    app: &AppState,
+    browser: &mut ClusterBrowser,
+) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|frame| browser.draw(frame))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match browser.handle_key(key.code) {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::SuggestLinks(id) => {
+                browser.status = Some(format!("Asking LinkerAgent about {}...", id));
+                terminal.draw(|frame| browser.draw(frame))?;
+                browser.status = None;
+
+                let expertise = &browser.expertises[&id];
+                // Deprecated expertises are not worth linking to
+                let others: Vec<Expertise> = browser
+                    .expertises
+                    .values()
+                    .filter(|e| e.metadata.status != ExpertiseStatus::Deprecated)
+                    .cloned()
+                    .collect();
+                let lines = match app.generator.suggest_links(expertise, &others).await {
+                    Ok(links) if links.is_empty() => vec!["No links suggested.".to_string()],
+                    Ok(links) => links
+                        .iter()
+                        .map(|link| {
+                            format!(
+                                "{} -[{}]-> {} ({:.2}): {}",
+                                link.from_id,
+                                link.relation_type,
+                                link.to_id,
+                                link.confidence,
+                                link.reason
+                            )
+                        })
+                        .collect(),
+                    Err(e) => vec![format!("LinkerAgent failed: {}", e)],
+                };
+                browser.page = Some(Page {
+                    title: format!("Suggested links: {}", id),
+                    lines,
+                    scroll: 0,
+                });
+            }
+            Action::Search(query) => {
+                match app.db.query().search(&query, SearchOptions::new()).await {
+                    Ok(results) => {
+                        // Only expertises loaded into the browser (i.e. in scope)
+                        let hits: Vec<String> = results
+                            .iter()
+                            .map(|e| e.id().to_string())
+                            .filter(|id| browser.expertises.contains_key(id))
+                            .collect();
+                        browser.show_search_results(&query, &hits);
+                    }
+                    Err(e) => {
+                        browser.page = Some(Page {
+                            title: format!("Search: {}", query),
+                            lines: vec![format!("Search failed: {}", e)],
+                            scroll: 0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use niwa_core::RelationType;
+
+    fn browser() -> ClusterBrowser {
+        let expertise = |id: &str, tags: &[&str]| {
+            let mut e = Expertise::new(id, "1.0.0");
+            e.inner.tags = tags.iter().map(|t| t.to_string()).collect();
+            e
+        };
+        let relation = |from: &str, to: &str| Relation {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relation_type: RelationType::Uses,
+            metadata: None,
+            created_at: 0,
+        };
+        ClusterBrowser::new(
+            vec![
+                expertise("tokio", &["rust", "async"]),
+                expertise("axum", &["rust", "web"]),
+                expertise("serde", &["rust"]),
+                expertise("react", &[]),
+                expertise("redux", &[]),
+                expertise("lonely", &["misc"]),
+            ],
+            vec![
+                relation("axum", "tokio"),
+                relation("axum", "serde"),
+                relation("redux", "react"),
+                relation("axum", "elsewhere"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_cluster_browser_navigation() {
+        let mut browser = browser();
+        let names: Vec<&str> = browser.clusters.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["rust", "react"]);
+        assert_eq!(browser.focused_id(), Some("axum"));
+
+        browser.handle_key(KeyCode::Right);
+        browser.handle_key(KeyCode::Down);
+        assert_eq!(browser.focused_id(), Some("serde"));
+
+        // Moving to another cluster resets the member selection
+        browser.handle_key(KeyCode::Left);
+        browser.handle_key(KeyCode::Down);
+        browser.handle_key(KeyCode::Down);
+        assert_eq!(browser.cluster, 1);
+        assert_eq!(browser.focused_id(), Some("react"));
+
+        assert_eq!(
+            browser.handle_key(KeyCode::Char('l')),
+            Action::SuggestLinks("react".to_string())
+        );
+
+        browser.handle_key(KeyCode::Enter);
+        let page = browser.page.as_ref().unwrap();
+        assert!(page.lines.iter().any(|l| l.contains("Expertise: react")));
+        // Esc leaves the page rather than the browser
+        assert_eq!(browser.handle_key(KeyCode::Esc), Action::None);
+        assert!(browser.page.is_none());
+
+        browser.handle_key(KeyCode::Char('g'));
+        let page = browser.page.as_ref().unwrap();
+        assert_eq!(page.lines[0], "2 expertises, 1 relations");
+        assert_eq!(page.lines[2], "redux -[uses]-> react");
+        // Scrolling stops at the last line
+        browser.handle_key(KeyCode::PageDown);
+        browser.handle_key(KeyCode::Down);
+        assert_eq!(browser.page.as_ref().unwrap().scroll, 2);
+        browser.handle_key(KeyCode::Esc);
+
+        assert_eq!(browser.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_cluster_browser_search() {
+        let mut browser = browser();
+
+        browser.handle_key(KeyCode::Char('/'));
+        for c in "serdx".chars() {
+            browser.handle_key(KeyCode::Char(c));
+        }
+        browser.handle_key(KeyCode::Backspace);
+        // Keys go to the query, not the browser
+        assert_eq!(browser.search.as_deref(), Some("serd"));
+        assert_eq!(browser.handle_key(KeyCode::Char('e')), Action::None);
+        assert_eq!(
+            browser.handle_key(KeyCode::Enter),
+            Action::Search("serde".to_string())
+        );
+        assert!(browser.search.is_none());
+
+        browser.show_search_results("serde", &["lonely".to_string(), "serde".to_string()]);
+        let page = browser.page.as_ref().unwrap();
+        assert_eq!(page.lines[0], "lonely  (not in a community)");
+        assert_eq!(page.lines[1], "serde  (rust)");
+        assert_eq!(browser.focused_id(), Some("serde"));
+
+        browser.show_search_results("nothing", &[]);
+        assert_eq!(browser.page.as_ref().unwrap().lines, vec!["No matches."]);
+
+        // An empty query searches nothing
+        browser.handle_key(KeyCode::Esc);
+        browser.handle_key(KeyCode::Char('/'));
+        assert_eq!(browser.handle_key(KeyCode::Enter), Action::None);
+    }
+}